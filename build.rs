@@ -0,0 +1,25 @@
+//! Records the git commit TuxSplit is built from, for the About dialog and
+//! `--doctor`. Builds without git, e.g. from a release tarball, leave it unset
+//! and show "unknown" instead (see `version::commit`).
+
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    for path in [".git/HEAD", ".git/refs"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=TUXSPLIT_GIT_COMMIT={}", commit.trim());
+    }
+}
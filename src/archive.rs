@@ -0,0 +1,283 @@
+//! Dated copies of splits files, kept before big route changes so the run, with
+//! its whole history, can always be gone back to.
+//!
+//! Archives live in `archives/` under the data directory, named
+//! `<game>-<category>-<date>.lss`, and `archives/index.yaml` records what each
+//! one was: the date, the personal best at the time and the attempt count.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use livesplit_core::{Run, TimingMethod};
+use serde::{Deserialize, Serialize};
+
+use crate::paths::{ARCHIVE_INDEX_FILE, ARCHIVES_DIR};
+use crate::storage::write_atomic;
+
+/// Longest game or category part of an archive name, in characters.
+const MAX_COMPONENT_CHARS: usize = 64;
+/// Used for a game or category with nothing left after sanitizing.
+const UNNAMED: &str = "untitled";
+
+/// Makes `name` usable as part of a file name on any system: path separators,
+/// characters Windows reserves and control characters become `_`, as does
+/// whitespace. Letters of any script are kept.
+pub fn sanitize_component(name: &str) -> String {
+    let mut sanitized = String::new();
+    for c in name.chars() {
+        let keep = !c.is_whitespace()
+            && !c.is_control()
+            && !matches!(
+                c,
+                '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*' | '-'
+            );
+        if keep {
+            sanitized.push(c);
+        } else if !sanitized.ends_with('_') {
+            sanitized.push('_');
+        }
+    }
+    // No hidden files, ".." or trailing dots, which Windows drops
+    let trimmed: String = sanitized
+        .trim_matches(['_', '.'])
+        .chars()
+        .take(MAX_COMPONENT_CHARS)
+        .collect();
+    let trimmed = trimmed.trim_end_matches(['_', '.']);
+    if trimmed.is_empty() {
+        UNNAMED.to_owned()
+    } else {
+        trimmed.to_owned()
+    }
+}
+
+/// `<game>-<category>-<date>.lss`, with `date` as YYYY-MM-DD, or
+/// `<game>-<category>.lss` without a date. Dashes within the names are replaced
+/// so they only separate the parts.
+pub fn archive_file_name(game: &str, category: &str, date: &str) -> String {
+    let date: String = date
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '-')
+        .collect();
+    let stem = format!(
+        "{}-{}",
+        sanitize_component(game),
+        sanitize_component(category)
+    );
+    if date.is_empty() {
+        format!("{stem}.lss")
+    } else {
+        format!("{stem}-{date}.lss")
+    }
+}
+
+/// `name` if `taken` says it is free, else the first free of `<stem>-2.lss`,
+/// `<stem>-3.lss`, ...
+pub fn resolve_collision(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(name) {
+        return name.to_owned();
+    }
+    let (stem, extension) = name
+        .rsplit_once('.')
+        .map_or((name, String::new()), |(stem, ext)| {
+            (stem, format!(".{ext}"))
+        });
+    (2..)
+        .map(|copy| format!("{stem}-{copy}{extension}"))
+        .find(|candidate| !taken(candidate))
+        .expect("some copy number is free")
+}
+
+/// One archived splits file.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ArchiveEntry {
+    /// File name inside the archives directory.
+    pub file: String,
+    /// Splits file it is a copy of.
+    pub source: PathBuf,
+    /// Local date it was archived on, YYYY-MM-DD.
+    pub date: String,
+    pub game: String,
+    pub category: String,
+    /// Personal best when archived, in milliseconds of the timing method used
+    /// then, `None` without a finished run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub personal_best_ms: Option<i64>,
+    pub attempts: u32,
+}
+
+impl ArchiveEntry {
+    pub fn path_in(&self, dir: &Path) -> PathBuf {
+        dir.join(&self.file)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ArchiveIndex {
+    #[serde(default)]
+    pub archives: Vec<ArchiveEntry>,
+}
+
+impl ArchiveIndex {
+    /// The index of `dir`, empty when there is none or it cannot be read.
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(ARCHIVE_INDEX_FILE);
+        let Ok(contents) = fs::read(&path) else {
+            return Self::default();
+        };
+        serde_yaml::from_slice(&contents).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring the archive index {}: {e}", path.display());
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        let yaml = serde_yaml::to_string(self).map_err(io::Error::other)?;
+        write_atomic(dir.join(ARCHIVE_INDEX_FILE), yaml)
+    }
+
+    /// Archives whose file is still there, newest first.
+    pub fn existing(&self, dir: &Path) -> Vec<ArchiveEntry> {
+        self.archives
+            .iter()
+            .rev()
+            .filter(|entry| entry.path_in(dir).is_file())
+            .cloned()
+            .collect()
+    }
+}
+
+/// The archives directory under the data directory `root`.
+pub fn archives_dir(root: &Path) -> PathBuf {
+    root.join(ARCHIVES_DIR)
+}
+
+/// Copies the splits file `splits`, holding `run`, into `dir` and records it in
+/// the index. The personal best is taken in `method`.
+pub fn archive(
+    dir: &Path,
+    splits: &Path,
+    run: &Run,
+    method: TimingMethod,
+    date: &str,
+) -> io::Result<ArchiveEntry> {
+    let contents = fs::read(splits)?;
+    fs::create_dir_all(dir)?;
+    let mut index = ArchiveIndex::load(dir);
+
+    let name = archive_file_name(run.game_name(), run.category_name(), date);
+    let file = resolve_collision(&name, |candidate| {
+        dir.join(candidate).exists() || index.archives.iter().any(|entry| entry.file == candidate)
+    });
+    write_atomic(dir.join(&file), contents)?;
+
+    let entry = ArchiveEntry {
+        file,
+        source: splits.to_owned(),
+        date: date.to_owned(),
+        game: run.game_name().to_owned(),
+        category: run.category_name().to_owned(),
+        personal_best_ms: run
+            .segments()
+            .last()
+            .and_then(|segment| segment.personal_best_split_time()[method])
+            .map(|time| time.total_milliseconds().round() as i64),
+        attempts: run.attempt_count(),
+    };
+    index.archives.push(entry.clone());
+    index.save(dir)?;
+    Ok(entry)
+}
+
+/// Writes the archived `entry` of `dir` over the splits file `splits`.
+pub fn restore(dir: &Path, entry: &ArchiveEntry, splits: &Path) -> io::Result<()> {
+    let contents = fs::read(entry.path_in(dir))?;
+    write_atomic(splits, contents)
+}
+
+#[cfg(test)]
+mod archive_tests {
+    use super::*;
+    use livesplit_core::run::saver::livesplit::save_run;
+    use livesplit_core::{Segment, Time, TimeSpan};
+
+    #[test]
+    fn names_are_sanitized_for_any_file_system() {
+        assert_eq!(sanitize_component("Super Mario 64"), "Super_Mario_64");
+        assert_eq!(sanitize_component("Any% / No LBLJ"), "Any%_No_LBLJ");
+        assert_eq!(sanitize_component("a\\b:c*d?"), "a_b_c_d");
+        assert_eq!(sanitize_component("Glitch-less"), "Glitch_less");
+        assert_eq!(sanitize_component("ポケモン 赤"), "ポケモン_赤");
+        assert_eq!(sanitize_component("Pokémon"), "Pokémon");
+        // Nothing that hides the file or leaves the directory
+        assert_eq!(sanitize_component("../.."), "untitled");
+        assert_eq!(sanitize_component(".hidden."), "hidden");
+        assert_eq!(sanitize_component("  \t"), "untitled");
+        assert_eq!(sanitize_component(&"x".repeat(100)).chars().count(), 64);
+    }
+
+    #[test]
+    fn file_names_are_dated_and_numbered_on_collision() {
+        let name = archive_file_name("Celeste", "Any% / Glitchless", "2026-03-14");
+        assert_eq!(name, "Celeste-Any%_Glitchless-2026-03-14.lss");
+        // Without a date, the part is left out
+        assert_eq!(archive_file_name("Celeste", "Any%", ""), "Celeste-Any%.lss");
+
+        let taken = [
+            "Celeste-Any%-2026-03-14.lss",
+            "Celeste-Any%-2026-03-14-2.lss",
+        ];
+        let resolve = |name: &str| resolve_collision(name, |c| taken.contains(&c));
+        assert_eq!(
+            resolve("Celeste-Any%-2026-03-14.lss"),
+            "Celeste-Any%-2026-03-14-3.lss"
+        );
+        assert_eq!(
+            resolve("Celeste-100%-2026-03-14.lss"),
+            "Celeste-100%-2026-03-14.lss"
+        );
+    }
+
+    #[test]
+    fn archives_are_copied_indexed_and_restored() {
+        let root = std::env::temp_dir().join(format!("tuxsplit-archive-{}", std::process::id()));
+        let dir = archives_dir(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        let mut run = Run::new();
+        run.set_game_name("Celeste");
+        run.set_category_name("Any%");
+        let mut segment = Segment::new("Summit");
+        segment.set_personal_best_split_time(
+            Time::new().with_real_time(Some(TimeSpan::from_seconds(1800.5))),
+        );
+        run.push_segment(segment);
+        run.set_attempt_count(42);
+        let mut lss = String::new();
+        save_run(&run, &mut lss).unwrap();
+        let splits = root.join("any.lss");
+        fs::write(&splits, &lss).unwrap();
+
+        let first = archive(&dir, &splits, &run, TimingMethod::RealTime, "2026-03-14").unwrap();
+        let second = archive(&dir, &splits, &run, TimingMethod::RealTime, "2026-03-14").unwrap();
+        assert_eq!(first.file, "Celeste-Any%-2026-03-14.lss");
+        assert_eq!(second.file, "Celeste-Any%-2026-03-14-2.lss");
+        assert_eq!(first.personal_best_ms, Some(1_800_500));
+        assert_eq!(first.attempts, 42);
+
+        let index = ArchiveIndex::load(&dir);
+        assert_eq!(index.existing(&dir), [second.clone(), first.clone()]);
+
+        fs::write(&splits, "changed").unwrap();
+        restore(&dir, &first, &splits).unwrap();
+        assert_eq!(fs::read_to_string(&splits).unwrap(), lss);
+
+        // Archives deleted by hand drop out of the list
+        fs::remove_file(second.path_in(&dir)).unwrap();
+        assert_eq!(ArchiveIndex::load(&dir).existing(&dir), [first]);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
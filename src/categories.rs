@@ -0,0 +1,376 @@
+//! Categories of a game kept in separate splits files.
+//!
+//! Runners doing several categories of a game list them in a YAML file in the
+//! splits directory (`tuxsplit-categories.yaml`), grouped by game name. Each
+//! category points at its own splits file; relative paths resolve against that
+//! directory, so the folder can be moved as a whole.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use livesplit_core::{Run, RunEditor, Segment};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::storage::write_atomic;
+
+const CATEGORIES_FILE: &str = "tuxsplit-categories.yaml";
+
+#[derive(Default, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct CategorySets {
+    /// Categories of every game, keyed by game name.
+    pub games: BTreeMap<String, Vec<Category>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Category {
+    pub name: String,
+    pub splits: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CategoryError {
+    EmptyName,
+    /// The game already has a category with this name.
+    DuplicateName(String),
+    NotFound(String),
+}
+
+impl std::fmt::Display for CategoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::EmptyName => write!(f, "Category names cannot be empty"),
+            Self::DuplicateName(name) => write!(f, "There is already a category named \"{name}\""),
+            Self::NotFound(name) => write!(f, "There is no category named \"{name}\""),
+        }
+    }
+}
+
+impl CategorySets {
+    /// Path of the categories file of a splits directory.
+    pub fn path_in(dir: &Path) -> PathBuf {
+        dir.join(CATEGORIES_FILE)
+    }
+
+    /// Loads the categories file of a splits directory. A missing or unreadable
+    /// file yields no categories.
+    pub fn load(dir: &Path) -> Self {
+        let path = Self::path_in(dir);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_yaml::from_str(&contents).unwrap_or_else(|e| {
+            warn!("Ignoring malformed categories file {}: {e}", path.display());
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), std::io::Error> {
+        let path = Self::path_in(dir);
+        if self.games.is_empty() && !path.exists() {
+            return Ok(());
+        }
+        let yaml = serde_yaml::to_string(self).map_err(std::io::Error::other)?;
+        write_atomic(path, yaml)
+    }
+
+    pub fn categories(&self, game: &str) -> &[Category] {
+        self.games.get(game).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn add(&mut self, game: &str, name: &str, splits: PathBuf) -> Result<(), CategoryError> {
+        let name = Self::validate_name(name)?;
+        let categories = self.games.entry(game.to_owned()).or_default();
+        if categories.iter().any(|category| category.name == name) {
+            return Err(CategoryError::DuplicateName(name.to_owned()));
+        }
+        categories.push(Category {
+            name: name.to_owned(),
+            splits,
+        });
+        Ok(())
+    }
+
+    /// Lists the splits file `splits` under `name`, unless a category of the game
+    /// already points at it. Makes the file switched away from reachable again.
+    pub fn ensure_listed(
+        &mut self,
+        game: &str,
+        name: &str,
+        splits: PathBuf,
+    ) -> Result<(), CategoryError> {
+        if self
+            .categories(game)
+            .iter()
+            .any(|category| category.splits == splits)
+        {
+            return Ok(());
+        }
+        self.add(game, name, splits)
+    }
+
+    pub fn rename(&mut self, game: &str, old: &str, new: &str) -> Result<(), CategoryError> {
+        let new = Self::validate_name(new)?;
+        let categories = self
+            .games
+            .get_mut(game)
+            .ok_or_else(|| CategoryError::NotFound(old.to_owned()))?;
+        if old != new && categories.iter().any(|category| category.name == new) {
+            return Err(CategoryError::DuplicateName(new.to_owned()));
+        }
+        let category = categories
+            .iter_mut()
+            .find(|category| category.name == old)
+            .ok_or_else(|| CategoryError::NotFound(old.to_owned()))?;
+        new.clone_into(&mut category.name);
+        Ok(())
+    }
+
+    /// Removes a category from the list. Its splits file is left on disk.
+    pub fn remove(&mut self, game: &str, name: &str) -> Result<Category, CategoryError> {
+        let categories = self
+            .games
+            .get_mut(game)
+            .ok_or_else(|| CategoryError::NotFound(name.to_owned()))?;
+        let index = categories
+            .iter()
+            .position(|category| category.name == name)
+            .ok_or_else(|| CategoryError::NotFound(name.to_owned()))?;
+        let removed = categories.remove(index);
+        if categories.is_empty() {
+            self.games.remove(game);
+        }
+        Ok(removed)
+    }
+
+    /// Category of `game` whose splits file is `splits` (as resolved from `dir`).
+    pub fn find_by_splits(&self, dir: &Path, game: &str, splits: &Path) -> Option<&Category> {
+        self.categories(game)
+            .iter()
+            .find(|category| category.resolve(dir) == splits)
+    }
+
+    fn validate_name(name: &str) -> Result<&str, CategoryError> {
+        let name = name.trim();
+        if name.is_empty() {
+            Err(CategoryError::EmptyName)
+        } else {
+            Ok(name)
+        }
+    }
+}
+
+impl Category {
+    /// Absolute path of the category's splits file.
+    pub fn resolve(&self, dir: &Path) -> PathBuf {
+        dir.join(&self.splits)
+    }
+}
+
+/// `path` relative to `dir` when it lies inside it, as stored in the categories file.
+pub fn relative_to(dir: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(dir)
+        .map_or_else(|_| path.to_path_buf(), Path::to_path_buf)
+}
+
+/// File name for a new category's splits, "<game> - <category>.lss", numbered if
+/// a file by that name already exists.
+pub fn new_splits_path(dir: &Path, game: &str, name: &str) -> PathBuf {
+    let stem = format!("{game} - {name}").replace(['/', '\\'], "-");
+    let mut path = dir.join(format!("{stem}.lss"));
+    let mut copy = 2;
+    while path.exists() {
+        path = dir.join(format!("{stem} ({copy}).lss"));
+        copy += 1;
+    }
+    path
+}
+
+/// A copy of `run` for a new category: same segments, no times or history.
+pub fn blank_category_run(run: Run, name: &str) -> Option<Run> {
+    let mut editor = RunEditor::new(run).ok()?;
+    editor.clear_times();
+    editor.set_category_name(name);
+    Some(editor.close())
+}
+
+/// A run without times named `game` / `category` with a segment per name.
+pub fn run_from_names(game: &str, category: &str, names: &[String]) -> Run {
+    let mut run = Run::new();
+    run.set_game_name(game.trim());
+    run.set_category_name(category.trim());
+    for name in names {
+        run.push_segment(Segment::new(name.as_str()));
+    }
+    run
+}
+
+#[cfg(test)]
+mod categories_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{Segment, TimingMethod};
+
+    const GAME: &str = "Celeste";
+
+    fn sets() -> CategorySets {
+        let mut sets = CategorySets::default();
+        sets.add(GAME, "Any%", "any.lss".into()).unwrap();
+        sets.add(GAME, "100%", "full.lss".into()).unwrap();
+        sets
+    }
+
+    #[test]
+    fn adding_rejects_empty_and_duplicate_names() {
+        let mut sets = sets();
+        assert_eq!(
+            sets.add(GAME, "  ", "x.lss".into()),
+            Err(CategoryError::EmptyName)
+        );
+        assert_eq!(
+            sets.add(GAME, " Any% ", "x.lss".into()),
+            Err(CategoryError::DuplicateName("Any%".to_owned()))
+        );
+        // Names only clash within a game
+        sets.add("Celeste 64", "Any%", "c64.lss".into()).unwrap();
+        assert_eq!(sets.categories(GAME).len(), 2);
+        assert!(sets.categories("Portal").is_empty());
+    }
+
+    #[test]
+    fn renaming_keeps_the_file_and_refuses_clashes() {
+        let mut sets = sets();
+        sets.rename(GAME, "Any%", "Any% (No CH)").unwrap();
+        assert_eq!(sets.categories(GAME)[0].name, "Any% (No CH)");
+        assert_eq!(sets.categories(GAME)[0].splits, PathBuf::from("any.lss"));
+
+        assert_eq!(
+            sets.rename(GAME, "100%", "Any% (No CH)"),
+            Err(CategoryError::DuplicateName("Any% (No CH)".to_owned()))
+        );
+        assert_eq!(
+            sets.rename(GAME, "Glitchless", "All Hearts"),
+            Err(CategoryError::NotFound("Glitchless".to_owned()))
+        );
+        sets.rename(GAME, "100%", "100%").unwrap();
+    }
+
+    #[test]
+    fn removing_the_last_category_drops_the_game() {
+        let mut sets = sets();
+        assert_eq!(
+            sets.remove(GAME, "Any%").unwrap().splits,
+            PathBuf::from("any.lss")
+        );
+        sets.remove(GAME, "100%").unwrap();
+        assert!(sets.games.is_empty());
+        assert!(sets.remove(GAME, "100%").is_err());
+    }
+
+    #[test]
+    fn ensure_listed_only_adds_unknown_files() {
+        let mut sets = sets();
+        sets.ensure_listed(GAME, "Any% copy", "any.lss".into())
+            .unwrap();
+        assert_eq!(sets.categories(GAME).len(), 2);
+        sets.ensure_listed(GAME, "Glitchless", "glitchless.lss".into())
+            .unwrap();
+        assert_eq!(sets.categories(GAME).len(), 3);
+    }
+
+    #[test]
+    fn paths_resolve_against_the_directory() {
+        let dir = Path::new("/runs/celeste");
+        let sets = sets();
+        assert_eq!(
+            sets.find_by_splits(dir, GAME, Path::new("/runs/celeste/full.lss"))
+                .map(|category| category.name.as_str()),
+            Some("100%")
+        );
+        assert_eq!(
+            relative_to(dir, Path::new("/runs/celeste/any.lss")),
+            PathBuf::from("any.lss")
+        );
+        assert_eq!(
+            relative_to(dir, Path::new("/elsewhere/any.lss")),
+            PathBuf::from("/elsewhere/any.lss")
+        );
+    }
+
+    #[test]
+    fn save_and_load_from_disk() {
+        let dir = std::env::temp_dir().join(format!("tuxsplit-categories-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // Nothing to write, nothing written
+        CategorySets::default().save(&dir).unwrap();
+        assert!(!CategorySets::path_in(&dir).exists());
+
+        let sets = sets();
+        sets.save(&dir).unwrap();
+        assert_eq!(CategorySets::load(&dir), sets);
+
+        fs::write(CategorySets::path_in(&dir), "games: [").unwrap();
+        assert_eq!(CategorySets::load(&dir), CategorySets::default());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn new_splits_files_do_not_overwrite_existing_ones() {
+        let dir =
+            std::env::temp_dir().join(format!("tuxsplit-new-category-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let first = new_splits_path(&dir, GAME, "Any%/NMG");
+        assert_eq!(first, dir.join("Celeste - Any%-NMG.lss"));
+        fs::write(&first, "").unwrap();
+        assert_eq!(
+            new_splits_path(&dir, GAME, "Any%/NMG"),
+            dir.join("Celeste - Any%-NMG (2).lss")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn blank_run_keeps_segment_names_only() {
+        let mut run = Run::new();
+        run.set_game_name(GAME);
+        run.set_category_name("Any%");
+        for (name, seconds) in [("Forsaken City", 40.0), ("Old Site", 95.0)] {
+            let mut segment = Segment::new(name);
+            let time = real(seconds);
+            segment.set_personal_best_split_time(time);
+            segment.set_best_segment_time(time);
+            run.push_segment(segment);
+        }
+
+        let blank = blank_category_run(run, "100%").unwrap();
+        assert_eq!(blank.game_name(), GAME);
+        assert_eq!(blank.category_name(), "100%");
+        let names: Vec<&str> = blank.segments().iter().map(|s| s.name()).collect();
+        assert_eq!(names, ["Forsaken City", "Old Site"]);
+        for segment in blank.segments() {
+            assert_eq!(
+                segment.personal_best_split_time()[TimingMethod::RealTime],
+                None
+            );
+            assert_eq!(segment.best_segment_time()[TimingMethod::RealTime], None);
+        }
+    }
+
+    #[test]
+    fn runs_from_names_have_a_segment_per_name() {
+        let names = ["Forsaken City".to_owned(), "Old Site".to_owned()];
+        let run = run_from_names(" Celeste ", "Any%", &names);
+        assert_eq!(run.game_name(), GAME);
+        assert_eq!(run.category_name(), "Any%");
+        let segments: Vec<&str> = run.segments().iter().map(|s| s.name()).collect();
+        assert_eq!(segments, names);
+    }
+}
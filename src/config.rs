@@ -2,18 +2,36 @@
 // Original code by: CryZe
 // Original repository: github.com/CryZe/livesplit-one-desktop
 // Commit: c636ba8
+use crate::error::{Source, TuxSplitError};
 use crate::formatters::{TimeFormat, TimeFormatPreset};
+#[cfg(feature = "webhooks")]
+use crate::proto::EventKind;
+use crate::safe_mode::SafeMode;
+use crate::sidecar::RunSidecar;
+use crate::storage::write_atomic;
+use crate::utils::auto_splitter;
+use crate::utils::clock::DEFAULT_CLOCK_JUMP_THRESHOLD_SECS;
+use crate::utils::contrast::parse_hex_rgba;
+use crate::utils::focus::FocusToggles;
+use crate::utils::hooks::HookKind;
+use crate::utils::hotkeys::{
+    HotkeyDiagnostic, Hotkeys, QueuedHotkeys, parse_hotkeys, restore_unread_keys, unbound_actions,
+};
+use crate::utils::reset_guard::UserActions;
+use crate::utils::timer_actions::ActionQueue;
+use crate::utils::window_placement::WindowGeometry;
 
 use livesplit_core::{
-    HotkeyConfig, HotkeySystem, Run, Segment, SharedTimer, Timer, TimingMethod, auto_splitting,
+    Run, Segment, Timer, TimingMethod,
     run::{parser::composite, saver::livesplit::save_timer},
 };
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     fs,
     path::{Path, PathBuf},
 };
-use tracing::error;
+use tracing::{info, warn};
 
 pub type SharedConfig = std::sync::Arc<std::sync::RwLock<Config>>;
 
@@ -26,14 +44,39 @@ pub struct Config {
     window: Window,
     #[serde(default)]
     pub style: Style,
-    #[serde(default)]
-    pub hotkeys: HotkeyConfig,
+    /// Read binding by binding in `from_yaml`.
+    #[serde(default, skip_deserializing)]
+    pub hotkeys: Hotkeys,
     #[serde(default)]
     pub format: Format,
     #[serde(default)]
+    pub display: Display,
+    #[serde(default)]
+    pub editor: Editor,
+    #[serde(default)]
+    pub viewer: Viewer,
+    #[serde(default)]
+    pub autosplitter: AutoSplitter,
+    #[serde(default)]
+    pub notifications: Notifications,
+    #[serde(default)]
+    pub remote: Remote,
+    #[serde(default)]
+    pub hooks: Hooks,
+    #[serde(default)]
+    pub webhooks: Webhooks,
+    #[serde(default)]
     connections: Connections,
     #[serde(skip)]
-    hotkey_system: Option<HotkeySystem>,
+    hotkey_system: Option<QueuedHotkeys>,
+    /// Problems found in the `hotkeys` section when the config was loaded.
+    #[serde(skip)]
+    hotkey_diagnostics: Vec<HotkeyDiagnostic>,
+    /// Invalid colors dropped from `display.colors` when the config was loaded.
+    #[serde(skip)]
+    color_diagnostics: Vec<String>,
+    #[serde(skip)]
+    revision: u64,
 }
 
 #[allow(clippy::missing_fields_in_debug)]
@@ -45,6 +88,14 @@ impl std::fmt::Debug for Config {
             .field("style", &self.style)
             .field("hotkeys", &self.hotkeys)
             .field("format", &self.format)
+            .field("display", &self.display)
+            .field("editor", &self.editor)
+            .field("viewer", &self.viewer)
+            .field("autosplitter", &self.autosplitter)
+            .field("notifications", &self.notifications)
+            .field("remote", &self.remote)
+            .field("hooks", &self.hooks)
+            .field("webhooks", &self.webhooks)
             .finish()
     }
 }
@@ -57,8 +108,19 @@ impl Clone for Config {
             style: self.style.clone(),
             hotkeys: self.hotkeys,
             format: self.format.clone(),
+            display: self.display.clone(),
+            editor: self.editor.clone(),
+            viewer: self.viewer.clone(),
+            autosplitter: self.autosplitter.clone(),
+            notifications: self.notifications.clone(),
+            remote: self.remote.clone(),
+            hooks: self.hooks.clone(),
+            webhooks: self.webhooks.clone(),
             connections: self.connections.clone(),
             hotkey_system: None,
+            hotkey_diagnostics: self.hotkey_diagnostics.clone(),
+            color_diagnostics: self.color_diagnostics.clone(),
+            revision: self.revision,
         }
     }
 }
@@ -71,6 +133,18 @@ pub struct General {
     pub comparison: Option<String>,
     pub auto_splitter: Option<PathBuf>,
     pub additional_info: AdditionalInfoVisibility,
+    /// Seconds the system clock may drift from the real time between two ticks
+    /// of a running attempt before it is reported as a clock jump.
+    pub clock_jump_threshold_secs: Option<f64>,
+    /// Target time of the whole attempt for goal mode, e.g. "8:00:00".
+    pub goal_time: Option<String>,
+    /// Milliseconds between two refreshes of the timer, replacing the rate
+    /// derived from the precision on screen (see `utils::refresh`).
+    pub refresh_rate_ms: Option<u64>,
+    /// Tick off the run's pre-flight checklist (see `RunSidecar::preflight`)
+    /// before an attempt can start.
+    #[serde(default)]
+    pub preflight: bool,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -79,6 +153,7 @@ pub struct Style {
     pub max_segments_displayed: Option<usize>,
     pub segments_scroll_follow_from: Option<usize>,
     pub show_icons: Option<bool>,
+    pub colors: Option<ColorOverrides>,
 }
 
 impl Default for Style {
@@ -87,6 +162,437 @@ impl Default for Style {
             max_segments_displayed: Some(10),
             segments_scroll_follow_from: Some(8),
             show_icons: Some(true),
+            colors: None,
+        }
+    }
+}
+
+/// CSS colors that replace the bundled ones, e.g. imported from a LiveSplit layout.
+#[derive(Default, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct ColorOverrides {
+    pub background: Option<String>,
+    pub ahead: Option<String>,
+    pub behind: Option<String>,
+    pub gold: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Display {
+    pub show_best_pace: bool,
+    /// Show under completed splits how far the attempt is behind sum of best pace.
+    pub show_sob_drift: bool,
+    /// Plot the delta of each split under the splits list.
+    pub show_sparkline: bool,
+    /// Show the wall-clock time the run is predicted to end at.
+    pub show_eta: bool,
+    /// Show the time spent in the current segment under the timer.
+    pub show_segment_timer: bool,
+    /// Show how much of the attempt went to loads (real time minus game time).
+    pub show_load_time: bool,
+    /// Show which segment the personal best was in at the current attempt time.
+    pub show_pb_position: bool,
+    /// Rows of the current split info next to the timer (see `ui::timer::components`).
+    /// Empty shows the best segment and the current comparison.
+    pub info_rows: Vec<String>,
+    pub timer_font: Option<TimerFont>,
+    /// Decimal places of the deltas (0-3), apart from the split format.
+    pub delta_digits: u8,
+    /// Show deltas under a tenth of a second as "±0.0".
+    pub delta_drop_zero_tenths: bool,
+    pub animations: Animations,
+    pub headerbar: Headerbar,
+    /// Characters of a segment name shown in the splits, and written to history
+    /// exports, before its middle is cut out. Longer names are ellipsized to the
+    /// width of the row anyway.
+    pub split_name_length: Option<usize>,
+    /// Comparison whose split times are shown dimmed after the current
+    /// comparison's. `none`, like leaving it out, shows none.
+    pub secondary_comparison: Option<String>,
+    /// Order in which parts of the timer are collapsed when the window is too
+    /// small for it (see `utils::compaction`). Empty uses the default order.
+    pub collapse_order: Vec<String>,
+    pub colors: StateColors,
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Self {
+            show_best_pace: false,
+            show_sob_drift: false,
+            show_sparkline: false,
+            show_eta: false,
+            show_segment_timer: false,
+            show_load_time: false,
+            show_pb_position: false,
+            info_rows: Vec::new(),
+            timer_font: None,
+            delta_digits: 1,
+            delta_drop_zero_tenths: false,
+            animations: Animations::Auto,
+            headerbar: Headerbar::Full,
+            split_name_length: None,
+            secondary_comparison: None,
+            collapse_order: Vec::new(),
+            colors: StateColors::default(),
+        }
+    }
+}
+
+impl Display {
+    /// The secondary comparison, if one is set.
+    pub fn secondary_comparison(&self) -> Option<&str> {
+        self.secondary_comparison
+            .as_deref()
+            .filter(|name| !name.is_empty() && !name.eq_ignore_ascii_case("none"))
+    }
+}
+
+/// Colors of the timer states as hex strings (`#rgb`, `#rgba`, `#rrggbb` or
+/// `#rrggbbaa`), defined as the `tuxsplit_*` named colors of the stylesheet.
+/// Unset ones follow the light or dark style (see `ui::style`).
+#[derive(Default, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct StateColors {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ahead: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub behind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gold: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paused: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished: Option<String>,
+}
+
+impl StateColors {
+    /// The states, as named in the config.
+    pub const KEYS: [&str; 5] = ["ahead", "behind", "gold", "paused", "finished"];
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.slot(key).and_then(|color| color.as_deref())
+    }
+
+    pub fn set(&mut self, key: &str, color: Option<String>) {
+        if let Some(slot) = self.slot_mut(key) {
+            *slot = color;
+        }
+    }
+
+    /// Unsets the colors that are not hex colors, describing each.
+    pub fn drop_invalid(&mut self) -> Vec<String> {
+        let mut diagnostics = Vec::new();
+        for key in Self::KEYS {
+            let Some(slot) = self.slot_mut(key) else {
+                continue;
+            };
+            if let Some(color) = slot.take_if(|color| parse_hex_rgba(color).is_none()) {
+                diagnostics.push(format!(
+                    "display.colors.{key}: \"{color}\" is not a hex color, using the default"
+                ));
+            }
+        }
+        diagnostics
+    }
+
+    fn slot(&self, key: &str) -> Option<&Option<String>> {
+        match key {
+            "ahead" => Some(&self.ahead),
+            "behind" => Some(&self.behind),
+            "gold" => Some(&self.gold),
+            "paused" => Some(&self.paused),
+            "finished" => Some(&self.finished),
+            _ => None,
+        }
+    }
+
+    fn slot_mut(&mut self, key: &str) -> Option<&mut Option<String>> {
+        match key {
+            "ahead" => Some(&mut self.ahead),
+            "behind" => Some(&mut self.behind),
+            "gold" => Some(&mut self.gold),
+            "paused" => Some(&mut self.paused),
+            "finished" => Some(&mut self.finished),
+            _ => None,
+        }
+    }
+}
+
+/// Whether flashes and other animations play. `auto` follows the system
+/// "reduce animations" setting (`gtk-enable-animations`).
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Animations {
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+impl Animations {
+    /// Resolves the override against the system setting.
+    pub const fn enabled(self, system_enabled: bool) -> bool {
+        match self {
+            Self::Auto => system_enabled,
+            Self::On => true,
+            Self::Off => false,
+        }
+    }
+}
+
+/// Window chrome of the main window. `compact` keeps a slim bar with the menu
+/// and the timer phase; `none` drops the bar for a gear button over the timer,
+/// for tiling window managers without title bars.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Headerbar {
+    #[default]
+    Full,
+    Compact,
+    None,
+}
+
+/// Column keys of the segment editor table with their default widths.
+/// A width of -1 lets the column size itself to its content.
+pub const EDITOR_COLUMN_DEFAULTS: [(&str, i32); 8] = [
+    ("name", -1),
+    ("split-time", 130),
+    ("pb-date", 110),
+    ("segment-time", 130),
+    ("best", 130),
+    ("best-pace", 130),
+    ("reached", 90),
+    ("exclude", -1),
+];
+
+/// State of the segment editor window, restored the next time it is opened.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct Editor {
+    pub column_widths: BTreeMap<String, i32>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Show the real time and game time of every time cell together, read-only.
+    pub show_both_methods: bool,
+    /// Show the miniature of the timer beside the editor.
+    pub show_preview: bool,
+    /// Show how often the attempts reached each segment.
+    pub show_reached: bool,
+    /// Show the day each PB split time was achieved.
+    pub show_pb_dates: bool,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self {
+            column_widths: BTreeMap::new(),
+            width: None,
+            height: None,
+            show_both_methods: false,
+            show_preview: true,
+            show_reached: false,
+            show_pb_dates: false,
+        }
+    }
+}
+
+impl Editor {
+    /// Stored column widths completed with the defaults of the missing columns.
+    pub fn resolved_column_widths(&self) -> BTreeMap<String, i32> {
+        let mut widths: BTreeMap<String, i32> = EDITOR_COLUMN_DEFAULTS
+            .iter()
+            .map(|&(key, width)| (key.to_owned(), width))
+            .collect();
+        widths.extend(
+            self.column_widths
+                .iter()
+                .map(|(key, &width)| (key.clone(), width)),
+        );
+        widths
+    }
+}
+
+/// State of the read-only viewer window, reopened on the next launch if it was
+/// open when the app quit.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct Viewer {
+    pub open: bool,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Hide the run info above the splits.
+    pub compact: bool,
+    /// Text size relative to the main window.
+    pub scale: f64,
+}
+
+impl Default for Viewer {
+    fn default() -> Self {
+        Self {
+            open: false,
+            width: None,
+            height: None,
+            compact: false,
+            scale: 1.0,
+        }
+    }
+}
+
+/// Timer control left to the auto-splitter's game time (see `utils::auto_start`).
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct AutoSplitter {
+    /// Start the timer once game time starts running.
+    pub auto_start: bool,
+    /// Reset the attempt once game time is deinitialized.
+    pub auto_reset: bool,
+    /// Hold resets the auto-splitter script makes on its own until they are
+    /// confirmed (see `utils::reset_guard`).
+    pub confirm_reset: bool,
+    /// Block scripted resets coming less than this many seconds after the last
+    /// one. 0 disables the limit.
+    pub reset_cooldown_secs: u32,
+}
+
+impl AutoSplitter {
+    /// Whether scripted resets are told apart from the user's at all.
+    pub const fn guards_resets(&self) -> bool {
+        self.confirm_reset || self.reset_cooldown_secs > 0
+    }
+}
+
+/// Toasts that can be turned off.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct Notifications {
+    /// Announce once per attempt that the PB can no longer be beaten.
+    pub pb_dead: bool,
+    /// Ask for attention with a desktop notification when the window is not
+    /// focused and the attempt is ahead of the PB in its last segments.
+    pub urgency_on_pace: bool,
+    /// How many of the last segments count for `urgency_on_pace`.
+    pub urgency_segments: usize,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            pb_dead: false,
+            urgency_on_pace: false,
+            urgency_segments: 3,
+        }
+    }
+}
+
+/// Timer corrections over local HTTP (see `integrations::remote`).
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct Remote {
+    pub enabled: bool,
+    /// Port on localhost to listen on.
+    pub port: u16,
+}
+
+impl Default for Remote {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 16834,
+        }
+    }
+}
+
+/// Commands run on timer events (see `utils::hooks`). Nothing runs unless
+/// `enabled` is set.
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct Hooks {
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_split: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_gold: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_reset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_finish: Option<String>,
+}
+
+impl Hooks {
+    /// The command to run for `kind`, if hooks are enabled and it has one.
+    pub fn command(&self, kind: HookKind) -> Option<&str> {
+        if !self.enabled {
+            return None;
+        }
+        let command = match kind {
+            HookKind::Start => &self.on_start,
+            HookKind::Split => &self.on_split,
+            HookKind::Gold => &self.on_gold,
+            HookKind::Reset => &self.on_reset,
+            HookKind::Finish => &self.on_finish,
+        };
+        command
+            .as_deref()
+            .filter(|command| !command.trim().is_empty())
+    }
+}
+
+/// URLs posted a JSON event on golds, finishes and personal bests (see
+/// `integrations::webhooks`).
+#[derive(Default, Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct Webhooks {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_gold: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_finish: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_pb: Option<String>,
+}
+
+#[cfg(feature = "webhooks")]
+impl Webhooks {
+    /// The URL to post `kind` to, if it has one.
+    pub fn url(&self, kind: EventKind) -> Option<&str> {
+        let url = match kind {
+            EventKind::Gold => &self.on_gold,
+            EventKind::Finish => &self.on_finish,
+            EventKind::Pb => &self.on_pb,
+        };
+        url.as_deref().map(str::trim).filter(|url| !url.is_empty())
+    }
+}
+
+/// Font used by the running timer. `size` is the pixel size of the large digits;
+/// the fractional part is drawn at half of it.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct TimerFont {
+    pub family: String,
+    pub size: u32,
+}
+
+impl Default for TimerFont {
+    fn default() -> Self {
+        Self {
+            family: "monospace".to_owned(),
+            size: 36,
         }
     }
 }
@@ -96,6 +602,9 @@ impl Default for Style {
 #[serde(default)]
 struct Window {
     always_on_top: bool,
+    /// Geometry of the main window, for runs without one of their own.
+    #[serde(flatten)]
+    geometry: WindowGeometry,
 }
 
 #[derive(Default, Deserialize, Serialize, Debug, Clone)]
@@ -138,6 +647,8 @@ pub struct AdditionalInfoVisibility {
     pub show_current_pace: bool,
     pub show_total_playtime: bool,
     pub show_pb_chance: bool,
+    /// Time left before the goal, when `general.goal-time` is set.
+    pub show_goal: bool,
 }
 
 impl Default for AdditionalInfoVisibility {
@@ -150,90 +661,160 @@ impl Default for AdditionalInfoVisibility {
             show_current_pace: false,
             show_total_playtime: false,
             show_pb_chance: false,
+            show_goal: true,
         }
     }
 }
 
 impl Config {
-    pub fn parse(path: impl AsRef<Path>) -> Option<Self> {
-        let buf = fs::read(path).ok()?;
-        serde_yaml::from_slice(&buf).ok()
+    pub fn parse(path: impl AsRef<Path>) -> Result<Self, TuxSplitError> {
+        let path = path.as_ref();
+        let config_error = |source: Source| TuxSplitError::Config {
+            path: path.to_owned(),
+            source,
+        };
+        let buf = fs::read(path).map_err(|e| config_error(e.into()))?;
+        Self::from_yaml(&buf).map_err(|e| config_error(e.into()))
     }
 
-    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), std::io::Error> {
-        let buf = serde_yaml::to_string(self).unwrap();
-        fs::write(path, buf)?;
-        Ok(())
+    /// Parses a config, reading the `hotkeys` section binding by binding so a bad
+    /// key does not make the whole file unusable (see `hotkey_diagnostics`).
+    pub fn from_yaml(buf: &[u8]) -> Result<Self, serde_yaml::Error> {
+        let mut value: serde_yaml::Value = serde_yaml::from_slice(buf)?;
+        let section = value
+            .as_mapping_mut()
+            .and_then(|mapping| mapping.remove("hotkeys"));
+
+        let mut config: Self = serde_yaml::from_value(value)?;
+        let (hotkeys, diagnostics) = parse_hotkeys(section.as_ref());
+        for diagnostic in &diagnostics {
+            warn!("config.yaml: {diagnostic}");
+        }
+        config.hotkeys = hotkeys;
+        config.hotkey_diagnostics = diagnostics;
+        config.color_diagnostics = config.display.colors.drop_invalid();
+        for diagnostic in &config.color_diagnostics {
+            warn!("config.yaml: {diagnostic}");
+        }
+        Ok(config)
+    }
+
+    pub fn hotkey_diagnostics(&self) -> &[HotkeyDiagnostic] {
+        &self.hotkey_diagnostics
+    }
+
+    pub fn color_diagnostics(&self) -> &[String] {
+        &self.color_diagnostics
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TuxSplitError> {
+        let path = path.as_ref();
+        self.to_yaml()
+            .map_err(Source::from)
+            .and_then(|buf| write_atomic(path, buf).map_err(Source::from))
+            .map_err(|source| TuxSplitError::Config {
+                path: path.to_owned(),
+                source,
+            })
+    }
+
+    /// The config as written by `save`. Hotkeys whose key could not be read are
+    /// written as they were, not as unbound.
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        let mut value = serde_yaml::to_value(self)?;
+        if let Some(section) = value.get_mut("hotkeys") {
+            restore_unread_keys(section, &self.hotkey_diagnostics);
+        }
+        serde_yaml::to_string(&value)
     }
 
-    pub fn parse_run(&self) -> Option<Run> {
-        let path = self.general.splits.clone()?;
-        let file = fs::read(&path).ok()?;
-        let mut run = composite::parse(&file, Some(&path)).ok()?.run;
-        run.fix_splits();
-        Some(run)
+    /// Records that the config changed and must be written by the next autosave.
+    pub fn mark_dirty(&mut self) {
+        self.revision += 1;
     }
 
-    pub fn parse_run_or_default(&self) -> Run {
-        self.parse_run().unwrap_or_else(|| {
-            let mut run = Run::new();
-            run.set_game_name("Game");
-            run.set_category_name("Category");
-            run.push_segment(Segment::new("Time"));
-            run
-        })
+    /// Counter bumped by every `mark_dirty` call.
+    pub const fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// The run used until a splits file is loaded.
+    pub fn default_run() -> Run {
+        let mut run = Run::new();
+        run.set_game_name("Game");
+        run.set_category_name("Category");
+        run.push_segment(Segment::new("Time"));
+        run
     }
 
     pub fn is_game_time(&self) -> bool {
         self.general.timing_method == Some(TimingMethod::GameTime)
     }
 
+    /// Drift between the system clock and the real time reported as a clock jump.
+    /// Values that are not a positive duration fall back to the default.
+    pub fn clock_jump_threshold(&self) -> time::Duration {
+        self.general
+            .clock_jump_threshold_secs
+            .filter(|secs| *secs > 0.0)
+            .and_then(time::Duration::checked_seconds_f64)
+            .unwrap_or(time::Duration::seconds_f64(
+                DEFAULT_CLOCK_JUMP_THRESHOLD_SECS,
+            ))
+    }
+
+    pub const fn window_geometry(&self) -> &WindowGeometry {
+        &self.window.geometry
+    }
+
+    pub fn set_window_geometry(&mut self, geometry: WindowGeometry) {
+        self.window.geometry = geometry;
+    }
+
     pub fn set_splits_path(&mut self, path: PathBuf) {
         self.general.splits = Some(path);
     }
 
     pub fn disable_hotkey_system(&mut self) {
-        if self.hotkey_system.is_none() {
-            return;
+        if let Some(hotkey_system) = &mut self.hotkey_system {
+            hotkey_system.deactivate();
         }
-        let hotkey_system: &mut HotkeySystem = self.hotkey_system.get_or_insert(
-            // Will never happen
-            HotkeySystem::with_config(
-                Timer::new(self.parse_run_or_default())
-                    .expect("Failed to create timer")
-                    .into_shared(),
-                self.hotkeys,
-            )
-            .expect("Failed to create HotkeySystem"),
-        );
-        hotkey_system.deactivate();
     }
 
     pub fn enable_hotkey_system(&mut self) {
-        if self.hotkey_system.is_none() {
-            return;
+        if let Some(hotkey_system) = &mut self.hotkey_system
+            && let Err(e) = hotkey_system.activate()
+        {
+            warn!("Could not bind the hotkeys: {e}");
         }
-        let hotkey_system: &mut HotkeySystem = self.hotkey_system.get_or_insert(
-            // Will never happen
-            HotkeySystem::with_config(
-                Timer::new(self.parse_run_or_default())
-                    .expect("Failed to create timer")
-                    .into_shared(),
-                self.hotkeys,
-            )
-            .expect("Failed to create HotkeySystem"),
-        );
-        hotkey_system.activate();
     }
 
-    pub fn create_hotkey_system(&mut self, timer: SharedTimer) -> Option<()> {
-        let hotkey_system_res = HotkeySystem::with_config(timer, self.hotkeys);
-        if let Ok(hotkey_system) = hotkey_system_res {
-            self.hotkey_system = Some(hotkey_system);
-            Some(())
-        } else {
-            None
+    /// Binds the hotkeys to the timer behind `queue`. Resets from the reset key
+    /// are recorded in `user_actions`, presses of the focus mode key in `focus`.
+    /// Nothing is bound in safe mode.
+    pub fn create_hotkey_system(
+        &mut self,
+        queue: &ActionQueue,
+        user_actions: &UserActions,
+        focus: &FocusToggles,
+        safe_mode: SafeMode,
+    ) -> Result<(), TuxSplitError> {
+        if !safe_mode.hotkeys() {
+            info!("Safe mode, not binding the hotkeys");
+            return Ok(());
+        }
+        let unbound = unbound_actions(&self.hotkeys);
+        if !unbound.is_empty() {
+            info!("Hotkeys without a key: {}", unbound.join(", "));
         }
+        let mut hotkey_system =
+            QueuedHotkeys::new(self.hotkeys, queue.clone(), user_actions.clone())
+                .with_focus_toggles(focus.clone());
+        hotkey_system
+            .activate()
+            .map_err(|e| TuxSplitError::Hotkey { source: e.into() })?;
+        self.hotkey_system = Some(hotkey_system);
+        Ok(())
     }
 
     pub fn configure_timer(&self, timer: &mut Timer) {
@@ -245,13 +826,19 @@ impl Config {
         }
     }
 
-    pub fn save_splits(&self, timer: &Timer) {
-        if let Some(path) = &self.general.splits {
-            let mut buf = String::new();
-            let _ = save_timer(timer, &mut buf);
-            // FIXME: Don't ignore not being able to save.
-            let _ = fs::write(path, &buf);
-        }
+    /// Writes the run of `timer` to the current splits file, if there is one.
+    pub fn save_splits(&self, timer: &Timer) -> Result<(), TuxSplitError> {
+        let Some(path) = &self.general.splits else {
+            return Ok(());
+        };
+        let mut buf = String::new();
+        save_timer(timer, &mut buf)
+            .map_err(|e| Source::from(e.to_string()))
+            .and_then(|()| write_atomic(path, buf).map_err(Source::from))
+            .map_err(|source| TuxSplitError::RunSave {
+                path: path.clone(),
+                source,
+            })
     }
 
     pub const fn setup_logging(&self) {
@@ -289,16 +876,312 @@ impl Config {
         // }
     }
 
-    pub fn maybe_load_auto_splitter(&self, runtime: &auto_splitting::Runtime) {
-        if let Some(auto_splitter) = &self.general.auto_splitter
-            && let Err(e) = runtime.load_script_blocking(auto_splitter.clone())
-        {
-            error!("Auto Splitter failed to load: {}", &e); // TODO: Create a custom error that
-            // pops up in the UI
+    /// Loads the auto splitter of the config, if any and not in safe mode, with
+    /// the settings kept for it in `sidecar`.
+    pub fn maybe_load_auto_splitter(
+        &self,
+        runtime: &auto_splitter::Runtime,
+        sidecar: &RunSidecar,
+        safe_mode: SafeMode,
+    ) -> Result<(), TuxSplitError> {
+        let Some(auto_splitter) = &self.general.auto_splitter else {
+            return Ok(());
+        };
+        if !safe_mode.auto_splitter() {
+            info!("Safe mode, not loading {}", auto_splitter.display());
+            return Ok(());
         }
+        let settings = sidecar
+            .auto_splitter_settings
+            .get(auto_splitter)
+            .cloned()
+            .unwrap_or_default();
+        runtime
+            .load_script_blocking(auto_splitter.clone(), &settings)
+            .map_err(|e| TuxSplitError::AutoSplitter {
+                path: auto_splitter.clone(),
+                source: e.into(),
+            })
     }
 
     pub fn into_shared(self) -> SharedConfig {
         std::sync::Arc::new(std::sync::RwLock::new(self))
     }
 }
+
+/// Reads and parses the splits file at `path`.
+pub fn read_run(path: &Path) -> Result<Run, TuxSplitError> {
+    let run_error = |source: Source| TuxSplitError::RunParse {
+        path: path.to_owned(),
+        source,
+    };
+    let file = fs::read(path).map_err(|e| run_error(e.into()))?;
+    parse_splits(&file, path).map_err(run_error)
+}
+
+/// Parses `file`, the contents of the splits file at `path`.
+pub fn parse_splits(file: &[u8], path: &Path) -> Result<Run, Source> {
+    let mut run = composite::parse(file, Some(path))?.run;
+    run.fix_splits();
+    Ok(run)
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+
+    #[test]
+    fn clock_jump_thresholds_out_of_range_fall_back_to_the_default() {
+        let default = time::Duration::seconds_f64(DEFAULT_CLOCK_JUMP_THRESHOLD_SECS);
+        let mut config = Config::default();
+        assert_eq!(config.clock_jump_threshold(), default);
+        config.general.clock_jump_threshold_secs = Some(0.5);
+        assert_eq!(
+            config.clock_jump_threshold(),
+            time::Duration::milliseconds(500)
+        );
+        for secs in [1e20, f64::INFINITY, f64::NAN, 0.0, -3.0] {
+            config.general.clock_jump_threshold_secs = Some(secs);
+            assert_eq!(config.clock_jump_threshold(), default, "{secs}");
+        }
+    }
+
+    #[test]
+    fn animations_override_the_system_setting_unless_auto() {
+        for system in [true, false] {
+            assert_eq!(Animations::Auto.enabled(system), system);
+            assert!(Animations::On.enabled(system));
+            assert!(!Animations::Off.enabled(system));
+        }
+    }
+
+    #[test]
+    fn animations_default_to_auto_and_parse_lowercase() {
+        assert_eq!(Config::default().display.animations, Animations::Auto);
+        let config: Config = serde_yaml::from_str("display:\n  animations: off\n").unwrap();
+        assert_eq!(config.display.animations, Animations::Off);
+    }
+
+    #[test]
+    fn headerbar_defaults_to_full_and_parses_lowercase() {
+        assert_eq!(Config::default().display.headerbar, Headerbar::Full);
+        let config: Config = serde_yaml::from_str("display:\n  headerbar: none\n").unwrap();
+        assert_eq!(config.display.headerbar, Headerbar::None);
+    }
+
+    #[test]
+    fn partial_column_widths_are_filled_with_defaults() {
+        let editor = Editor {
+            column_widths: BTreeMap::from([("name".to_owned(), 320), ("best".to_owned(), 90)]),
+            ..Editor::default()
+        };
+
+        let widths = editor.resolved_column_widths();
+        assert_eq!(widths.len(), EDITOR_COLUMN_DEFAULTS.len());
+        assert_eq!(widths["name"], 320);
+        assert_eq!(widths["best"], 90);
+        assert_eq!(widths["split-time"], 130);
+        assert_eq!(widths["pb-date"], 110);
+        assert_eq!(widths["exclude"], -1);
+    }
+
+    #[test]
+    fn safe_mode_binds_no_hotkeys_and_loads_no_auto_splitter() {
+        let mut config = Config::default();
+        config.general.auto_splitter = Some(PathBuf::from("/nonexistent/splitter.wasm"));
+        let timer = Timer::new(Config::default_run()).unwrap().into_shared();
+        let queue = ActionQueue::spawn(timer.clone());
+        let runtime = auto_splitter::Runtime::new(timer, queue.clone());
+        let sidecar = RunSidecar::default();
+        assert!(
+            config
+                .maybe_load_auto_splitter(&runtime, &sidecar, SafeMode::default())
+                .is_err()
+        );
+        assert!(
+            config
+                .maybe_load_auto_splitter(&runtime, &sidecar, SafeMode::new(true))
+                .is_ok()
+        );
+
+        config
+            .create_hotkey_system(
+                &queue,
+                &UserActions::default(),
+                &FocusToggles::default(),
+                SafeMode::new(true),
+            )
+            .unwrap();
+        assert!(config.hotkey_system.is_none());
+    }
+
+    #[test]
+    fn bad_hotkeys_do_not_discard_the_rest_of_the_config() {
+        let config = Config::from_yaml(
+            b"display:\n  show-eta: true\nhotkeys:\n  split: Space\n  reset: Nmpad3\n",
+        )
+        .unwrap();
+        assert!(config.display.show_eta);
+        assert!(config.hotkeys.timer.split.is_some());
+        assert_eq!(config.hotkeys.timer.reset, None);
+        assert_eq!(config.hotkey_diagnostics().len(), 1);
+        // Diagnostics survive the clones handed around the UI
+        assert_eq!(config.clone().hotkey_diagnostics().len(), 1);
+        // Saving keeps what was written for the user to fix
+        let saved = config.to_yaml().unwrap();
+        assert!(saved.contains("reset: Nmpad3"), "{saved}");
+        let saved = Config::from_yaml(saved.as_bytes()).unwrap();
+        assert_eq!(saved.hotkey_diagnostics(), config.hotkey_diagnostics());
+    }
+
+    #[test]
+    fn invalid_state_colors_fall_back_with_a_diagnostic() {
+        let config = Config::from_yaml(
+            b"display:\n  colors:\n    ahead: '#33d17a'\n    behind: red\n    paused: '#8888'\n",
+        )
+        .unwrap();
+        let colors = &config.display.colors;
+        assert_eq!(colors.get("ahead"), Some("#33d17a"));
+        assert_eq!(colors.get("paused"), Some("#8888"));
+        assert_eq!(colors.behind, None);
+        assert_eq!(config.color_diagnostics().len(), 1);
+        assert!(config.color_diagnostics()[0].starts_with("display.colors.behind"));
+        // Unset colors are not written back
+        let yaml = serde_yaml::to_string(&config.display.colors).unwrap();
+        assert!(!yaml.contains("behind"), "{yaml}");
+    }
+
+    #[test]
+    fn configs_without_editor_section_still_parse() {
+        let config: Config = serde_yaml::from_str("display:\n  show-best-pace: true\n").unwrap();
+        assert!(config.editor.column_widths.is_empty());
+        assert_eq!(config.editor.width, None);
+        assert_eq!(config.editor.resolved_column_widths()["segment-time"], 130);
+    }
+
+    #[test]
+    fn viewer_section_defaults_to_closed_at_full_scale() {
+        let config: Config = serde_yaml::from_str("viewer:\n  open: true\n").unwrap();
+        assert!(config.viewer.open);
+        assert!(!config.viewer.compact);
+        assert!((config.viewer.scale - 1.0).abs() < f64::EPSILON);
+        assert_eq!(config.viewer.width, None);
+    }
+
+    #[test]
+    #[cfg(feature = "webhooks")]
+    fn webhooks_are_read_by_event() {
+        let yaml = "webhooks:\n  on-gold: http://lights.local/gold\n  on-pb: ' '\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            config.webhooks.url(EventKind::Gold),
+            Some("http://lights.local/gold")
+        );
+        assert_eq!(config.webhooks.url(EventKind::Pb), None);
+        assert_eq!(config.webhooks.url(EventKind::Finish), None);
+    }
+
+    #[test]
+    fn hooks_only_run_once_enabled() {
+        let yaml = "hooks:\n  on-split: obs-cli scene switch Splits\n  on-reset: ' '\n";
+        let mut config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(!config.hooks.enabled);
+        assert_eq!(config.hooks.command(HookKind::Split), None);
+
+        config.hooks.enabled = true;
+        assert_eq!(
+            config.hooks.command(HookKind::Split),
+            Some("obs-cli scene switch Splits")
+        );
+        assert_eq!(config.hooks.command(HookKind::Reset), None);
+        assert_eq!(config.hooks.command(HookKind::Gold), None);
+    }
+
+    #[test]
+    fn missing_splits_files_are_run_parse_errors() {
+        let path =
+            std::env::temp_dir().join(format!("tuxsplit-missing-{}.lss", std::process::id()));
+        assert!(matches!(
+            read_run(&path),
+            Err(TuxSplitError::RunParse { path: p, .. }) if p == path
+        ));
+    }
+
+    /// Best segments missing from the file are filled from the PB segment
+    /// times by livesplit-core when the run is read, so a loaded run never has
+    /// empty golds where the PB has a time. After a skipped PB split, the next
+    /// gold gets the time of both segments.
+    #[test]
+    fn reading_a_run_seeds_missing_golds_from_the_pb() {
+        use crate::test_support::real;
+        use livesplit_core::run::saver::livesplit::save_run;
+        use livesplit_core::{Segment, TimeSpan};
+
+        let mut run = Run::new();
+        for (name, pb, gold) in [
+            ("Forest", Some(10.0), None),
+            ("Castle", Some(25.0), Some(12.0)),
+            ("Tower", None, None),
+            ("Boss", Some(55.0), None),
+        ] {
+            let mut segment = Segment::new(name);
+            if let Some(pb) = pb {
+                segment.set_personal_best_split_time(real(pb));
+            }
+            if let Some(gold) = gold {
+                segment.set_best_segment_time(real(gold));
+            }
+            run.push_segment(segment);
+        }
+        let mut lss = String::new();
+        save_run(&run, &mut lss).unwrap();
+        let path = std::env::temp_dir().join(format!("tuxsplit-golds-{}.lss", std::process::id()));
+        fs::write(&path, lss).unwrap();
+
+        let golds = |run: &Run| -> Vec<_> {
+            run.segments()
+                .iter()
+                .map(|segment| segment.best_segment_time().real_time)
+                .collect()
+        };
+        let mut run = read_run(&path).unwrap();
+        let seeded = [
+            Some(TimeSpan::from_seconds(10.0)),
+            Some(TimeSpan::from_seconds(12.0)),
+            None,
+            Some(TimeSpan::from_seconds(30.0)),
+        ];
+        assert_eq!(golds(&run), seeded);
+
+        // Nor can the timer hold a run missing them, so there is nothing left to offer
+        run.segment_mut(0).best_segment_time_mut().real_time = None;
+        let timer = Timer::new(run).unwrap();
+        assert_eq!(golds(timer.run()), seeded);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unwritable_config_dirs_are_config_errors() {
+        // A file where the config directory should be: not even root can write there
+        let dir = std::env::temp_dir().join(format!("tuxsplit-not-a-dir-{}", std::process::id()));
+        fs::write(&dir, b"").unwrap();
+        let result = Config::default().save(dir.join("config.yaml"));
+        fs::remove_file(&dir).unwrap();
+
+        let Err(TuxSplitError::Config { path, source }) = result else {
+            panic!("expected a config error, got {result:?}");
+        };
+        assert_eq!(path, dir.join("config.yaml"));
+        assert!(source.downcast_ref::<std::io::Error>().is_some());
+    }
+
+    #[test]
+    fn broken_configs_are_config_errors() {
+        let path =
+            std::env::temp_dir().join(format!("tuxsplit-broken-{}.yaml", std::process::id()));
+        fs::write(&path, b"display: [not, a, mapping").unwrap();
+        let result = Config::parse(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(TuxSplitError::Config { .. })));
+    }
+}
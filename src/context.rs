@@ -1,29 +1,83 @@
 //! Global application context providing shared access to the Timer, Config,
 //! Runtime (auto-splitting), and a signal bus for run mutations.
 
-use std::cell::RefCell;
-use std::sync::{Arc, RwLock};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Instant;
 
 use glib::prelude::*;
+use glib::translate::{IntoGlib, ToGlibPtr};
 use glib::{subclass::Signal, subclass::prelude::*};
 use std::sync::OnceLock;
 
-use std::env;
 use std::path::{Path, PathBuf};
 
-use gtk4::gio;
+use gtk4::{Overlay, gio};
 
 use adw::prelude::*;
-use adw::{Application, ApplicationWindow, ToolbarView};
+use adw::{Application, ApplicationWindow, ToastOverlay, ToolbarView};
 
 use tracing::debug;
 use tracing::info;
+use tracing::warn;
 
-use livesplit_core::{Run, SharedTimer, Timer, auto_splitting::Runtime};
+use livesplit_core::run::saver::livesplit::save_timer;
+use livesplit_core::{Run, SharedTimer, Timer, TimerPhase};
 
-use crate::config::Config;
+use crate::config::{Animations, Config, read_run};
+use crate::error::TuxSplitError;
+#[cfg(feature = "remote-control")]
+use crate::integrations::remote::{RemoteServer, StateSource, describe, session_token};
+#[cfg(feature = "webhooks")]
+use crate::integrations::webhooks::{self, FailureReports, webhook_events};
+use crate::paths::{self, CONFIG_FILE, INSTANCE_LOCK_FILE, RECOVERY_FILE};
+#[cfg(feature = "webhooks")]
+use crate::proto::EventV1;
+#[cfg(any(feature = "remote-control", feature = "webhooks"))]
+use crate::proto::StateFormat;
+use crate::safe_mode::{self, SafeMode};
+use crate::sidecar::{ClockJump, RunSidecar};
+use crate::startup::{SplitsProblem, StartupAction, startup_action};
+use crate::storage::{
+    Autosave, InstanceLock, LockState, RunSaveState, is_running_instance, write_atomic,
+};
 use crate::ui::TuxSplitHeader;
+use crate::ui::dnd;
+use crate::ui::editor::{check_duplicate_names, check_sidecar};
+use crate::ui::error::show_error;
+use crate::ui::header::{install_window_drag, present_load_splits};
+use crate::ui::palette::Command;
+use crate::ui::preflight;
 use crate::ui::timer::TuxSplitTimer;
+#[cfg(feature = "webhooks")]
+use crate::ui::timer::finish::beats_personal_best;
+use crate::ui::viewer;
+use crate::utils::anomalies::{attempt_segment_times, detect_anomalies, segment_histories};
+use crate::utils::auto_splitter::{Runtime, ScriptSetting};
+use crate::utils::auto_splitter_settings::{self, SettingValue};
+use crate::utils::auto_start::{AutoAction, GameTimeState, detect_action};
+use crate::utils::clock::{ClockJumpTracker, ClockSample};
+use crate::utils::flash::SplitState;
+use crate::utils::focus::{FocusMode, FocusToggles};
+use crate::utils::goal::{finish_message, parse_goal, timer_remaining};
+use crate::utils::golds::{GoldEvent, GoldTracker, PendingGolds};
+#[cfg(feature = "webhooks")]
+use crate::utils::hooks::HookEvent;
+use crate::utils::hooks::{self, HookLimiter, detect_hook_events, hook_env};
+use crate::utils::notify_batch::NotifyBatch;
+use crate::utils::pb_cutoff::{PbCutoff, PbCutoffEvent, timer_pb_outlook};
+use crate::utils::pb_pace::{PaceAlert, PaceEvent, timer_pb_delta, timer_segments_left};
+use crate::utils::regeneration::{self, Job, Regeneration};
+use crate::utils::reset_guard::{ResetDecision, ResetGuard, UserActions};
+use crate::utils::run_snapshots::SharedRunSnapshots;
+use crate::utils::sidecar_repair::{RepairPlan, apply_sidecar_repair};
+use crate::utils::timer_actions::{ActionGate, ActionQueue, TimerAction};
+use crate::utils::window_placement::{captured_geometry, resolve_placement};
+
+const AUTOSAVE_INTERVAL_SECS: u32 = 30;
+/// How long `emit_run_changed_debounced` waits for the changes to stop.
+const RUN_CHANGED_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(100);
 
 mod imp {
     use super::*;
@@ -32,6 +86,68 @@ mod imp {
         pub timer: RefCell<SharedTimer>,
         pub runtime: RefCell<Runtime>,
         pub config: RefCell<Config>,
+        pub golds: RefCell<GoldTracker>,
+        pub pending_golds: RefCell<PendingGolds>,
+        pub sidecar: RefCell<RunSidecar>,
+        pub autosave: RefCell<Autosave>,
+        pub run_save: RefCell<RunSaveState>,
+        pub user_actions: RefCell<UserActions>,
+        pub actions: RefCell<ActionQueue>,
+        /// Starts held back by `actions` for the pre-flight checklist.
+        pub preflight_gate: RefCell<ActionGate>,
+        /// Resets of the auto splitter held for the reset guard.
+        pub reset_gate: RefCell<ActionGate>,
+        /// Runs as they were before their data was rewritten, see `undo_data_change`.
+        pub snapshots: RefCell<SharedRunSnapshots>,
+        pub reset_guard: RefCell<ResetGuard>,
+        pub clock: RefCell<ClockJumpTracker>,
+        pub pb_cutoff: RefCell<PbCutoff>,
+        pub pace_alert: RefCell<PaceAlert>,
+        /// Focus mode of the timer, for this session only.
+        pub focus_mode: Cell<FocusMode>,
+        /// Presses of the focus mode hotkey, taken on every tick.
+        pub focus_toggles: RefCell<FocusToggles>,
+        /// "run-changed" emissions held back or delayed, see `freeze_notifications`.
+        pub run_notifications: RefCell<NotifyBatch>,
+        /// Edits whose comparisons are being regenerated on a worker.
+        pub regeneration: RefCell<Regeneration<Run>>,
+        /// Phase on the last tick, to report the goal once on finishing.
+        pub goal_phase: Cell<TimerPhase>,
+        /// Phase on the last tick, to look for missed splits once on finishing.
+        pub anomaly_phase: Cell<TimerPhase>,
+        /// Phase and comparison of the timer on the last tick, to emit
+        /// "phase-changed" and "comparison-changed" (see `update_timer_state`).
+        pub phase: Cell<TimerPhase>,
+        pub comparison: RefCell<String>,
+        /// Timer state the command hooks were last checked against.
+        pub hook_state: Cell<Option<SplitState>>,
+        pub hook_limiter: RefCell<HookLimiter>,
+        pub commands: RefCell<Vec<Command>>,
+        pub shut_down: Cell<bool>,
+        pub game_time: Cell<Option<GameTimeState>>,
+        pub editor_open: Cell<bool>,
+        /// Whether config.yaml could not be read on startup, so it is backed up
+        /// before the defaults are written over it (see `write_config`).
+        pub config_unreadable: Cell<bool>,
+        /// Whether saving is turned off because another instance holds the
+        /// instance lock.
+        pub read_only: Cell<bool>,
+        /// Whether this instance holds the instance lock.
+        pub holds_lock: Cell<bool>,
+        /// The window whose geometry is remembered per run.
+        pub main_window: glib::WeakRef<ApplicationWindow>,
+        pub startup_errors: RefCell<Vec<TuxSplitError>>,
+        /// Splits file of the config that could not be opened on startup, kept
+        /// out of the config until the user decides (see `crate::startup`).
+        pub unopened_splits: RefCell<Option<(PathBuf, SplitsProblem)>>,
+        #[cfg(feature = "remote-control")]
+        pub remote: RefCell<Option<RemoteServer>>,
+        /// Token remote requests must carry, new on every start. Made when
+        /// first needed (see `remote_token`).
+        #[cfg(feature = "remote-control")]
+        pub remote_token: RefCell<Option<String>>,
+        #[cfg(feature = "webhooks")]
+        pub webhook_failures: RefCell<FailureReports>,
     }
 
     impl Default for TuxSplitContext {
@@ -45,12 +161,54 @@ mod imp {
             run.push_segment(segment);
             let timer = Timer::new(run).expect("timer");
             let shared = timer.into_shared();
-            let runtime = Runtime::new(shared.clone());
+            let snapshots = SharedRunSnapshots::default();
+            let actions = ActionQueue::spawn_recording(shared.clone(), snapshots.clone());
+            let runtime = Runtime::new(shared.clone(), actions.clone());
             let config = Config::default();
             Self {
                 timer: RefCell::new(shared),
                 runtime: RefCell::new(runtime),
                 config: RefCell::new(config),
+                golds: RefCell::new(GoldTracker::new()),
+                pending_golds: RefCell::new(PendingGolds::default()),
+                sidecar: RefCell::new(RunSidecar::default()),
+                autosave: RefCell::new(Autosave::default()),
+                run_save: RefCell::new(RunSaveState::default()),
+                user_actions: RefCell::new(UserActions::default()),
+                actions: RefCell::new(actions),
+                preflight_gate: RefCell::new(ActionGate::default()),
+                reset_gate: RefCell::new(ActionGate::default()),
+                snapshots: RefCell::new(snapshots),
+                reset_guard: RefCell::new(ResetGuard::default()),
+                clock: RefCell::new(ClockJumpTracker::default()),
+                pb_cutoff: RefCell::new(PbCutoff::default()),
+                pace_alert: RefCell::new(PaceAlert::default()),
+                focus_mode: Cell::new(FocusMode::default()),
+                focus_toggles: RefCell::new(FocusToggles::default()),
+                run_notifications: RefCell::new(NotifyBatch::default()),
+                regeneration: RefCell::new(Regeneration::default()),
+                goal_phase: Cell::new(TimerPhase::NotRunning),
+                anomaly_phase: Cell::new(TimerPhase::NotRunning),
+                phase: Cell::new(TimerPhase::NotRunning),
+                comparison: RefCell::new(String::new()),
+                hook_state: Cell::new(None),
+                hook_limiter: RefCell::new(HookLimiter::default()),
+                commands: RefCell::new(Vec::new()),
+                shut_down: Cell::new(false),
+                game_time: Cell::new(None),
+                editor_open: Cell::new(false),
+                config_unreadable: Cell::new(false),
+                read_only: Cell::new(false),
+                holds_lock: Cell::new(false),
+                main_window: glib::WeakRef::new(),
+                startup_errors: RefCell::new(Vec::new()),
+                unopened_splits: RefCell::new(None),
+                #[cfg(feature = "remote-control")]
+                remote: RefCell::new(None),
+                #[cfg(feature = "remote-control")]
+                remote_token: RefCell::new(None),
+                #[cfg(feature = "webhooks")]
+                webhook_failures: RefCell::new(FailureReports::default()),
             }
         }
     }
@@ -71,6 +229,69 @@ mod imp {
                     // (structure, times, metadata). Listeners should refresh
                     // any cached segment representations.
                     Signal::builder("run-changed").action().build(),
+                    // Emitted when the current attempt earns a gold, undoes one,
+                    // or is reset with golds on it.
+                    Signal::builder("golds-changed").build(),
+                    // Emitted when the window chrome setting (`display.headerbar`)
+                    // changes, so only the chrome is rebuilt.
+                    Signal::builder("headerbar-changed").build(),
+                    // Emitted when the run gains changes that are not in its
+                    // splits file, or loses them by being saved or reloaded.
+                    Signal::builder("dirty-changed").build(),
+                    // Emitted when comparisons start or stop being regenerated
+                    // on a worker (see `set_edited_run`).
+                    Signal::builder("regeneration-changed").build(),
+                    // Emitted when saving is turned off or on (see `set_read_only`).
+                    Signal::builder("read-only-changed").build(),
+                    // Emitted when a reset asked for by the auto-splitter script
+                    // is held back until the user allows it (see `allow_reset`).
+                    Signal::builder("reset-deferred").build(),
+                    // Emitted on the first system clock jump of an attempt, with
+                    // its size in milliseconds (negative when the clock went back).
+                    Signal::builder("clock-jumped")
+                        .param_types([i64::static_type()])
+                        .build(),
+                    // Emitted the first time in an attempt that the PB can no
+                    // longer be beaten.
+                    Signal::builder("pb-lost").build(),
+                    // Emitted when a late attempt gets ahead of the PB while the
+                    // window is not focused, with the segments to go, and with 0
+                    // once it is off pace again or over.
+                    Signal::builder("pb-pace")
+                        .param_types([u32::static_type()])
+                        .build(),
+                    // Emitted when focus mode is turned on or off (see
+                    // `toggle_focus_mode`).
+                    Signal::builder("focus-mode-changed").build(),
+                    // Emitted when the timer starts, pauses, resumes, finishes
+                    // or resets, whoever drove it (see `update_timer_state`).
+                    Signal::builder("phase-changed").build(),
+                    // Emitted when the timer compares against another
+                    // comparison (see `update_timer_state`).
+                    Signal::builder("comparison-changed").build(),
+                    // Emitted when an attempt was asked to start while the
+                    // pre-flight checklist is on (see `update_preflight`).
+                    Signal::builder("preflight-requested").build(),
+                    // Emitted when an attempt finishes with a goal time set, with
+                    // how far over or under the goal it was.
+                    Signal::builder("goal-finished")
+                        .param_types([String::static_type()])
+                        .build(),
+                    // Emitted when an attempt finishes with segments far slower
+                    // than their history, with a line about each.
+                    Signal::builder("anomalies-found")
+                        .param_types([String::static_type()])
+                        .build(),
+                    // Emitted for every timer action applied on remote request,
+                    // with a description of it.
+                    Signal::builder("remote-action")
+                        .param_types([String::static_type()])
+                        .build(),
+                    // Emitted the first time a webhook fails in the session,
+                    // with its URL.
+                    Signal::builder("webhook-failed")
+                        .param_types([String::static_type()])
+                        .build(),
                 ]
             })
         }
@@ -81,37 +302,154 @@ glib::wrapper! {
     pub struct TuxSplitContext(ObjectSubclass<imp::TuxSplitContext>);
 }
 
+/// Holds back "run-changed" while alive, see `TuxSplitContext::freeze_notifications`.
+#[must_use = "notifications thaw when the freeze is dropped"]
+pub struct NotificationFreeze {
+    ctx: TuxSplitContext,
+    debounced: bool,
+}
+
+impl NotificationFreeze {
+    /// Debounces the emission on thaw rather than emitting right away, for edits
+    /// made on every keystroke.
+    pub const fn debounced(mut self) -> Self {
+        self.debounced = true;
+        self
+    }
+}
+
+impl Drop for NotificationFreeze {
+    fn drop(&mut self) {
+        if self.debounced {
+            self.ctx.thaw_notifications_debounced();
+        } else {
+            self.ctx.thaw_notifications();
+        }
+    }
+}
+
 impl TuxSplitContext {
-    /// Construct a new initialized global context.
-    ///
-    /// Panics if the timer or hotkey system cannot be created.
+    /// Construct a new initialized global context. What fails on the way is kept
+    /// for `take_startup_errors`, with defaults in its place.
     fn init() -> Self {
-        let mut config = load_config();
-        let run = config.parse_run_or_default();
+        let mut errors = Vec::new();
+        let mut config_unreadable = false;
+        let mut config = load_config().unwrap_or_else(|e| {
+            errors.push(e);
+            config_unreadable = true;
+            Config::default()
+        });
+        let mut unopened_splits = None;
+        let run = match startup_action(config.general.splits.as_deref(), |path| std::fs::read(path))
+        {
+            StartupAction::Load(run) => *run,
+            StartupAction::StartEmpty => Config::default_run(),
+            StartupAction::Recover { path, problem } => {
+                warn!(
+                    "Could not open the splits file {}: {}",
+                    path.display(),
+                    problem.describe()
+                );
+                // Never saved over the file that could not be opened
+                config.general.splits = None;
+                unopened_splits = Some((path, problem));
+                Config::default_run()
+            }
+        };
 
-        let timer = Timer::new(run).expect("Failed to create timer");
+        // A run without segments cannot be timed
+        let timer = Timer::new(run)
+            .or_else(|_| Timer::new(Config::default_run()))
+            .expect("the default run has a segment");
         let shared_timer = timer.into_shared();
 
-        let runtime = Runtime::new(shared_timer.clone());
+        let sidecar = config
+            .general
+            .splits
+            .as_deref()
+            .map(RunSidecar::load)
+            .unwrap_or_default();
+
+        let user_actions = UserActions::default();
+        let focus_toggles = FocusToggles::default();
+        let snapshots = SharedRunSnapshots::default();
+        let preflight_gate = ActionGate::default();
+        let reset_gate = ActionGate::default();
+        let actions = ActionQueue::spawn_gated(
+            shared_timer.clone(),
+            snapshots.clone(),
+            preflight_gate.clone(),
+            reset_gate.clone(),
+        );
+
+        let runtime = Runtime::new(shared_timer.clone(), actions.clone());
 
         config.configure_timer(&mut shared_timer.write().unwrap());
-        config.maybe_load_auto_splitter(&runtime);
+        if let Err(e) = config.maybe_load_auto_splitter(&runtime, &sidecar, safe_mode::current()) {
+            errors.push(e);
+        }
 
-        let Some(()) = config.create_hotkey_system(shared_timer.clone()) else {
-            panic!("Could not load HotkeySystem");
-        };
+        if let Err(e) = config.create_hotkey_system(
+            &actions,
+            &user_actions,
+            &focus_toggles,
+            safe_mode::current(),
+        ) {
+            errors.push(e);
+        }
+        for e in &errors {
+            warn!("{e}: {}", e.details());
+        }
 
         let obj: Self = glib::Object::new();
         {
             let imp = obj.imp();
+            imp.sidecar.replace(sidecar);
+            imp.user_actions.replace(user_actions);
+            imp.focus_toggles.replace(focus_toggles);
+            imp.actions.replace(actions);
+            imp.preflight_gate.replace(preflight_gate);
+            imp.reset_gate.replace(reset_gate);
+            imp.snapshots.replace(snapshots);
             imp.timer.replace(shared_timer);
             imp.runtime.replace(runtime);
             imp.config.replace(config);
+            imp.config_unreadable.set(config_unreadable);
+            imp.startup_errors.replace(errors);
+            imp.unopened_splits.replace(unopened_splits);
+        }
+        #[cfg(feature = "remote-control")]
+        if obj.config().remote.enabled
+            && safe_mode::current().remote_control()
+            && let Err(e) = obj.set_remote_enabled(true, safe_mode::current())
+        {
+            warn!("{e}: {}", e.details());
+            obj.imp().startup_errors.borrow_mut().push(e);
         }
 
         obj
     }
 
+    /// The splits file of the config that could not be opened on startup, and why,
+    /// until it is located again or forgotten.
+    pub fn unopened_splits(&self) -> Option<(PathBuf, SplitsProblem)> {
+        self.imp().unopened_splits.borrow().clone()
+    }
+
+    /// Drops the splits file that could not be opened from the config.
+    pub fn forget_unopened_splits(&self) {
+        if self.imp().unopened_splits.take().is_some()
+            && let Ok(mut c) = self.config_mut()
+        {
+            c.mark_dirty();
+        }
+    }
+
+    /// Errors met while starting up, once: they are shown when the window opens.
+    pub fn take_startup_errors(&self) -> Vec<TuxSplitError> {
+        self.imp().startup_errors.take()
+    }
+
     pub fn get_instance() -> Arc<Self> {
         thread_local! {
             static INSTANCE: OnceLock<Arc<TuxSplitContext>> = OnceLock::new();
@@ -127,7 +465,12 @@ impl TuxSplitContext {
         self.imp().timer.borrow().clone()
     }
 
+    /// The current run, or the latest edit of it while that is still being
+    /// regenerated, so edits made meanwhile build on each other.
     pub fn get_run(&self) -> Run {
+        if let Some(run) = self.imp().regeneration.borrow().latest() {
+            return run.clone();
+        }
         self.timer().read().unwrap().run().clone()
     }
 
@@ -139,28 +482,1017 @@ impl TuxSplitContext {
         self.imp().config.try_borrow_mut()
     }
 
+    /// Whether flashes and other animations should play, from the
+    /// `display.animations` override and the system setting. Never in safe mode.
+    pub fn animations_enabled(&self, safe_mode: SafeMode) -> bool {
+        let system = gtk4::Settings::default().is_none_or(|s| s.is_gtk_enable_animations());
+        animations_allowed(safe_mode, self.config().display.animations, system)
+    }
+
     pub fn runtime(&self) -> std::cell::Ref<'_, Runtime> {
         self.imp().runtime.borrow()
     }
 
+    /// Emit "run-changed", or only once the notifications thaw while they are
+    /// frozen (see `freeze_notifications`).
     pub fn emit_run_changed(&self) {
-        self.emit_by_name::<()>("run-changed", &[]);
+        if self.imp().run_notifications.borrow_mut().notify() {
+            self.emit_by_name::<()>("run-changed", &[]);
+        }
+    }
+
+    /// Emit "run-changed" once no other change came for `RUN_CHANGED_DEBOUNCE`,
+    /// for changes made on every keystroke.
+    pub fn emit_run_changed_debounced(&self) {
+        let generation = self.imp().run_notifications.borrow_mut().debounce();
+        glib::timeout_add_local_once(RUN_CHANGED_DEBOUNCE, move || {
+            let ctx = TuxSplitContext::get_instance();
+            if ctx
+                .imp()
+                .run_notifications
+                .borrow()
+                .debounce_elapsed(generation)
+            {
+                ctx.emit_run_changed();
+            }
+        });
+    }
+
+    /// Hold back "run-changed" until the returned guard is dropped, then emit it
+    /// once if the run changed meanwhile. Freezes nest, and only the outermost
+    /// one emits. For bulk edits, whose every step would refresh the listeners.
+    pub fn freeze_notifications(&self) -> NotificationFreeze {
+        self.imp().run_notifications.borrow_mut().freeze();
+        NotificationFreeze {
+            ctx: self.clone(),
+            debounced: false,
+        }
+    }
+
+    /// Ends a freeze of `freeze_notifications`, which its guard does when dropped.
+    pub fn thaw_notifications(&self) {
+        if self.imp().run_notifications.borrow_mut().thaw() {
+            self.emit_by_name::<()>("run-changed", &[]);
+        }
+    }
+
+    fn thaw_notifications_debounced(&self) {
+        if self.imp().run_notifications.borrow_mut().thaw() {
+            self.emit_run_changed_debounced();
+        }
+    }
+
+    /// Feed the current timer state to the gold tracker, emitting golds-changed
+    /// if anything changed. Returns the gold events for this update.
+    pub fn update_golds(&self, timer: &Timer) -> Vec<GoldEvent> {
+        let segments = timer.run().segments();
+        let excluded = self.excluded_segments(segments);
+        let events = self.imp().golds.borrow_mut().observe(
+            timer.current_phase(),
+            segments,
+            &excluded,
+            timer.current_timing_method(),
+        );
+        if !events.is_empty() {
+            let saved = self
+                .imp()
+                .pending_golds
+                .borrow_mut()
+                .observe(&events, segments);
+            if !saved.is_empty() {
+                let today = today();
+                let mut sidecar = self.sidecar_mut();
+                for index in saved {
+                    sidecar.set_gold_date(segments[index].name(), index, today.clone());
+                }
+            }
+            self.emit_by_name::<()>("golds-changed", &[]);
+        }
+        events
+    }
+
+    /// Whether the run has changes that are not in its splits file. Decides both
+    /// the unsaved-changes prompts and the shutdown recovery copy.
+    pub fn run_dirty(&self) -> bool {
+        self.imp().run_save.borrow().is_dirty()
+    }
+
+    /// Flag the run as changed by an edit, emitting dirty-changed if it was clean.
+    pub fn mark_run_modified(&self) {
+        if self.imp().run_save.borrow_mut().mark_modified() {
+            self.emit_by_name::<()>("dirty-changed", &[]);
+        }
+    }
+
+    /// Flag the run as matching its splits file, emitting dirty-changed if it had
+    /// changes.
+    pub fn mark_saved(&self) {
+        self.timer().write().unwrap().mark_as_unmodified();
+        if self.imp().run_save.borrow_mut().mark_saved() {
+            self.emit_by_name::<()>("dirty-changed", &[]);
+        }
+    }
+
+    /// Pick up the changes the timer made to the run by itself, like splits and
+    /// attempts added to the history on reset.
+    pub fn update_dirty(&self, timer: &Timer) {
+        let modified = timer.run().has_been_modified();
+        if self.imp().run_save.borrow_mut().observe(modified) {
+            self.emit_by_name::<()>("dirty-changed", &[]);
+        }
+    }
+
+    /// Decide on the resets the auto-splitter script asked for since the last
+    /// tick, held by the action queue while resets are guarded. The ones
+    /// allowed are queued again on the script's behalf.
+    pub fn update_reset_guard(&self) {
+        let flags = self.config().autosplitter.clone();
+        let gate = self.imp().reset_gate.borrow();
+        gate.set_armed(flags.guards_resets());
+        if !gate.take_requests() {
+            return;
+        }
+        drop(gate);
+        let last_user_action = self.imp().user_actions.borrow().last();
+        let decision =
+            self.imp()
+                .reset_guard
+                .borrow_mut()
+                .decide(Instant::now(), last_user_action, &flags);
+        match decision {
+            Some(ResetDecision::Allow) => self.enqueue(TimerAction::Reset {
+                update_splits: true,
+            }),
+            Some(ResetDecision::Defer) => {
+                warn!("Held back a reset from the auto-splitter until it is allowed");
+                self.emit_by_name::<()>("reset-deferred", &[]);
+            }
+            Some(ResetDecision::Block) => {
+                warn!("Blocked a reset from the auto-splitter within the reset cooldown");
+            }
+            None => {}
+        }
+    }
+
+    /// Resets the attempt on the user's behalf, like a held back reset they allowed.
+    pub fn allow_reset(&self) {
+        self.imp().user_actions.borrow().record();
+        self.enqueue(TimerAction::Reset {
+            update_splits: true,
+        });
+    }
+
+    /// Queues a change to the timer behind the ones already on their way, from
+    /// hotkeys or elsewhere (see `utils::timer_actions`).
+    pub fn enqueue(&self, action: TimerAction) {
+        self.imp().actions.borrow().send(action);
+    }
+
+    /// Feed the current timer state to the auto start and reset driven by the
+    /// auto-splitter's game time, applying them unless the editor is open.
+    pub fn update_auto_start(&self, timer: &Timer) {
+        let state = GameTimeState::of(timer);
+        let Some(prev) = self.imp().game_time.replace(Some(state)) else {
+            return;
+        };
+        if self.editor_open() {
+            return;
+        }
+        let action = detect_action(prev, state, &self.config().autosplitter);
+        match action {
+            Some(AutoAction::Start) => {
+                info!("Game time started running, starting the timer");
+                // The game is running already, too late for the checklist
+                self.imp().actions.borrow().send_checked(TimerAction::Start);
+            }
+            Some(AutoAction::Reset) => {
+                info!("Game time went away, resetting the attempt");
+                self.allow_reset();
+            }
+            None => {}
+        }
+    }
+
+    /// Compare the system clock with the real time of a running attempt. Jumps
+    /// are logged and recorded in the sidecar under the attempt; the first one
+    /// of an attempt emits "clock-jumped".
+    pub fn update_clock(&self, timer: &Timer) {
+        let threshold = self.config().clock_jump_threshold();
+        let Some(jump) = self.imp().clock.borrow_mut().observe(
+            ClockSample::now(),
+            timer.current_phase(),
+            threshold,
+        ) else {
+            return;
+        };
+
+        let offset_ms = i64::try_from(jump.offset.whole_milliseconds()).unwrap_or(i64::MAX);
+        warn!("System clock jumped by {} during the attempt", jump.offset);
+        // The attempt gets the index after the last one when it is reset
+        let attempt = timer
+            .run()
+            .attempt_history()
+            .iter()
+            .map(livesplit_core::run::Attempt::index)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let at = glib::DateTime::now_local()
+            .and_then(|now| now.format("%F %T"))
+            .map(String::from)
+            .unwrap_or_default();
+        self.sidecar_mut()
+            .record_clock_jump(attempt, ClockJump { at, offset_ms });
+        if jump.first {
+            self.emit_by_name::<()>("clock-jumped", &[&offset_ms]);
+        }
+    }
+
+    /// Starts the remote control on the configured port, or stops it. It never
+    /// starts in safe mode.
+    #[cfg(feature = "remote-control")]
+    pub fn set_remote_enabled(
+        &self,
+        enabled: bool,
+        safe_mode: SafeMode,
+    ) -> Result<(), TuxSplitError> {
+        let imp = self.imp();
+        // Frees the port before listening again
+        imp.remote.replace(None);
+        if !enabled || !safe_mode.remote_control() {
+            return Ok(());
+        }
+        let port = self.config().remote.port;
+        let queue = imp.actions.borrow().clone();
+        let state = StateSource {
+            timer: self.timer(),
+            format: StateFormat::from_config(&self.config()),
+        };
+        let server = RemoteServer::start(port, self.remote_token()?, queue, state)
+            .map_err(|source| TuxSplitError::RemoteControl { port, source })?;
+        imp.remote.replace(Some(server));
+        Ok(())
+    }
+
+    /// The token of this session, made the first time it is asked for.
+    #[cfg(feature = "remote-control")]
+    pub fn remote_token(&self) -> Result<String, TuxSplitError> {
+        let mut token = self.imp().remote_token.borrow_mut();
+        if let Some(token) = token.as_ref() {
+            return Ok(token.clone());
+        }
+        let port = self.config().remote.port;
+        Ok(token.insert(session_token(port)?).clone())
+    }
+
+    /// Emit "remote-action" for every action applied on remote request since the
+    /// last tick.
+    #[cfg(feature = "remote-control")]
+    pub fn update_remote(&self) {
+        let applied: Vec<_> = self
+            .imp()
+            .remote
+            .borrow()
+            .as_ref()
+            .map(|server| server.applied().collect())
+            .unwrap_or_default();
+        for action in applied {
+            self.emit_by_name::<()>("remote-action", &[&describe(&action)]);
+        }
+    }
+
+    /// Follow whether the attempt can still beat the PB. The first loss of an
+    /// attempt emits "pb-lost".
+    pub fn update_pb_cutoff(&self, timer: &Timer) {
+        let event = self
+            .imp()
+            .pb_cutoff
+            .borrow_mut()
+            .observe(timer.current_phase(), || timer_pb_outlook(timer));
+        if event == Some(PbCutoffEvent::Lost { first: true }) {
+            self.emit_by_name::<()>("pb-lost", &[]);
+        }
+    }
+
+    /// Follow whether a late attempt is on PB pace for
+    /// `notifications.urgency_on_pace`, and emit "pb-pace" when that changes.
+    pub fn update_pace_alert(&self, timer: &Timer) {
+        let (enabled, last_segments) = {
+            let notifications = &self.config().notifications;
+            (
+                notifications.urgency_on_pace,
+                notifications.urgency_segments,
+            )
+        };
+        // Turning the option off mid-attempt clears a raised alert
+        let phase = if enabled {
+            timer.current_phase()
+        } else {
+            TimerPhase::NotRunning
+        };
+        let focused = self
+            .imp()
+            .main_window
+            .upgrade()
+            .is_some_and(|window| window.is_active());
+        let event = self.imp().pace_alert.borrow_mut().observe(
+            phase,
+            timer_segments_left(timer),
+            timer_pb_delta(timer),
+            last_segments,
+            focused,
+        );
+        let segments_left = match event {
+            Some(PaceEvent::Raised { segments_left }) => segments_left,
+            Some(PaceEvent::Cleared) => 0,
+            None => return,
+        };
+        self.emit_by_name::<()>(
+            "pb-pace",
+            &[&u32::try_from(segments_left).unwrap_or(u32::MAX)],
+        );
+    }
+
+    pub fn focus_mode(&self) -> FocusMode {
+        self.imp().focus_mode.get()
+    }
+
+    /// Turns focus mode on or off, emitting "focus-mode-changed".
+    pub fn toggle_focus_mode(&self) {
+        let mode = self.imp().focus_mode.get().toggled();
+        self.imp().focus_mode.set(mode);
+        self.emit_by_name::<()>("focus-mode-changed", &[]);
+    }
+
+    /// Hold back the starts of attempts while the pre-flight checklist is on and
+    /// the run has one, and emit "preflight-requested" for those asked for since
+    /// the last tick. Starts are also held while edits are being regenerated,
+    /// since setting them would reset the attempt; they go through once the
+    /// edits are in.
+    pub fn update_preflight(&self) {
+        let checklist = self.config().general.preflight && !self.sidecar().preflight.is_empty();
+        let regenerating = self.regenerating();
+        let gate = self.imp().preflight_gate.borrow();
+        gate.set_armed(checklist || regenerating);
+        if regenerating || !gate.take_requests() {
+            return;
+        }
+        drop(gate);
+        if checklist {
+            self.emit_by_name::<()>("preflight-requested", &[]);
+        } else {
+            self.start_checked();
+        }
+    }
+
+    /// Starts the attempt the pre-flight checklist was ticked off for.
+    pub fn start_checked(&self) {
+        self.imp().actions.borrow().send_checked(TimerAction::Start);
+    }
+
+    /// Emit "phase-changed" and "comparison-changed" when the phase or the
+    /// comparison of the timer changed since the last tick. Hotkeys, the
+    /// auto splitter and remote requests drive the timer off the main thread,
+    /// so the changes are picked up here.
+    pub fn update_timer_state(&self, timer: &Timer) {
+        if self.imp().phase.replace(timer.current_phase()) != timer.current_phase() {
+            self.emit_by_name::<()>("phase-changed", &[]);
+        }
+        let comparison = timer.current_comparison();
+        if *self.imp().comparison.borrow() != comparison {
+            self.imp().comparison.replace(comparison.to_owned());
+            self.emit_by_name::<()>("comparison-changed", &[]);
+        }
+    }
+
+    /// Toggle focus mode for the presses of its hotkey since the last tick.
+    pub fn update_focus_mode(&self) {
+        if self.imp().focus_toggles.borrow().take() {
+            self.toggle_focus_mode();
+        }
+    }
+
+    /// Whether the attempt in progress, or the one that just ended, can no longer
+    /// beat the PB.
+    pub fn pb_lost(&self) -> bool {
+        self.imp().pb_cutoff.borrow().is_lost()
+    }
+
+    /// Report the attempt against the goal time when it finishes, with
+    /// "goal-finished".
+    pub fn update_goal(&self, timer: &Timer) {
+        let phase = timer.current_phase();
+        let previous = self.imp().goal_phase.replace(phase);
+        if phase != TimerPhase::Ended || previous == TimerPhase::Ended {
+            return;
+        }
+        let message = {
+            let config = self.config();
+            let Some(goal) = parse_goal(config.general.goal_time.as_deref()) else {
+                return;
+            };
+            finish_message(timer_remaining(goal, timer), &config.format.split)
+        };
+        self.emit_by_name::<()>("goal-finished", &[&message]);
+    }
+
+    /// Look for segments of the attempt that took far longer than usual when it
+    /// finishes, emitting "anomalies-found" if there are any.
+    pub fn update_anomalies(&self, timer: &Timer) {
+        let phase = timer.current_phase();
+        let previous = self.imp().anomaly_phase.replace(phase);
+        if phase != TimerPhase::Ended || previous == TimerPhase::Ended {
+            return;
+        }
+        let method = timer.current_timing_method();
+        let run = timer.run();
+        let anomalies = detect_anomalies(
+            &segment_histories(run, method),
+            &attempt_segment_times(run, method),
+        );
+        if anomalies.is_empty() {
+            return;
+        }
+        let format = self.config().format.split.clone();
+        let lines: Vec<String> = anomalies
+            .iter()
+            .map(|anomaly| anomaly.describe(run.segment(anomaly.index).name(), &format))
+            .collect();
+        self.emit_by_name::<()>("anomalies-found", &[&lines.join("\n")]);
+    }
+
+    /// Run the command hooks of the config for what happened on the timer since
+    /// the last update. `golds` are the gold events of the same update. None run
+    /// in safe mode.
+    pub fn update_hooks(&self, timer: &Timer, golds: &[GoldEvent], safe_mode: SafeMode) {
+        let state = SplitState {
+            phase: timer.current_phase(),
+            index: timer.current_split_index(),
+        };
+        let Some(previous) = self.imp().hook_state.replace(Some(state)) else {
+            return;
+        };
+        let method = timer.current_timing_method();
+        let events = detect_hook_events(
+            previous,
+            state,
+            |index| {
+                timer
+                    .run()
+                    .segments()
+                    .get(index)
+                    .is_some_and(|segment| segment.split_time()[method].is_some())
+            },
+            golds,
+        );
+        if events.is_empty() || !safe_mode.hooks() {
+            return;
+        }
+        #[cfg(feature = "webhooks")]
+        self.post_webhooks(&events, timer);
+
+        let config = self.config();
+        for event in events {
+            let Some(command) = config.hooks.command(event.kind) else {
+                continue;
+            };
+            if !self.imp().hook_limiter.borrow_mut().allow(Instant::now()) {
+                debug!("Too many hooks, skipping the {} one", event.kind.name());
+                continue;
+            }
+            hooks::spawn(command, &hook_env(&event, timer));
+        }
+    }
+
+    /// Post the webhooks of the config for `events`, in the background. A failure
+    /// is logged, and emits "webhook-failed" the first time for each URL.
+    #[cfg(feature = "webhooks")]
+    fn post_webhooks(&self, events: &[HookEvent], timer: &Timer) {
+        let config = self.config();
+        let method = timer.current_timing_method();
+        let is_pb = || {
+            timer.run().segments().last().is_some_and(|last| {
+                beats_personal_best(
+                    last.split_time()[method],
+                    last.personal_best_split_time()[method],
+                )
+            })
+        };
+        let format = StateFormat::from_config(&config);
+        for (kind, index) in webhook_events(events, is_pb) {
+            let Some(url) = config.webhooks.url(kind).map(str::to_owned) else {
+                continue;
+            };
+            let Some(body) = EventV1::from_timer(kind, index, timer, &format)
+                .and_then(|event| serde_json::to_string(&event).ok())
+            else {
+                continue;
+            };
+            glib::MainContext::default().spawn_local(async move {
+                let target = url.clone();
+                let result = gio::spawn_blocking(move || webhooks::post(&target, &body))
+                    .await
+                    .unwrap_or_else(|_| Err("the request thread panicked".to_owned()));
+                let Err(e) = result else {
+                    debug!("Posted the webhook to {url}");
+                    return;
+                };
+                warn!("Could not post the webhook to {url}: {e}");
+                let ctx = TuxSplitContext::get_instance();
+                if ctx.imp().webhook_failures.borrow_mut().first(&url) {
+                    ctx.emit_by_name::<()>("webhook-failed", &[&url]);
+                }
+            });
+        }
+    }
+
+    /// Describe an action of the menu for the command palette. Registering the
+    /// same action again replaces its description.
+    pub fn register_command(&self, command: Command) {
+        let mut commands = self.imp().commands.borrow_mut();
+        commands.retain(|c| c.action != command.action);
+        commands.push(command);
+    }
+
+    pub fn commands(&self) -> Vec<Command> {
+        self.imp().commands.borrow().clone()
+    }
+
+    /// Whether the split editor is open. The run must not change under it.
+    pub fn editor_open(&self) -> bool {
+        self.imp().editor_open.get()
+    }
+
+    pub fn set_editor_open(&self, open: bool) {
+        self.imp().editor_open.set(open);
+    }
+
+    /// Indices of the segments that produced a gold in the current attempt.
+    pub fn golds(&self) -> Vec<usize> {
+        self.imp().golds.borrow().golds().to_vec()
+    }
+
+    pub fn sidecar(&self) -> std::cell::Ref<'_, RunSidecar> {
+        self.imp().sidecar.borrow()
+    }
+
+    pub fn sidecar_mut(&self) -> std::cell::RefMut<'_, RunSidecar> {
+        self.imp().sidecar.borrow_mut()
+    }
+
+    /// Exclusion flag of every given segment, in order.
+    pub fn excluded_segments(&self, segments: &[livesplit_core::Segment]) -> Vec<bool> {
+        self.sidecar().excluded_flags(segments)
+    }
+
+    /// Reload the sidecar of the current splits file (e.g. after loading new splits).
+    pub fn reload_sidecar(&self) {
+        let sidecar = self
+            .config()
+            .general
+            .splits
+            .as_deref()
+            .map(RunSidecar::load)
+            .unwrap_or_default();
+        self.imp().sidecar.replace(sidecar);
+    }
+
+    /// The settings the auto splitter registered, with the values it runs with.
+    pub fn auto_splitter_settings(&self) -> Vec<ScriptSetting> {
+        self.runtime().settings()
+    }
+
+    /// Set the auto-splitter setting `key`, keep the settings of the script in
+    /// the sidecar and load the script again with them.
+    pub fn set_auto_splitter_setting(&self, key: &str, value: SettingValue) {
+        let Some(script) = self.config().general.auto_splitter.clone() else {
+            return;
+        };
+        let defaults = self
+            .auto_splitter_settings()
+            .into_iter()
+            .map(|setting| (setting.key, setting.default))
+            .collect();
+        let mut settings = auto_splitter_settings::restore(&self.sidecar(), &script, &defaults);
+        settings.insert(key.to_owned(), value);
+        auto_splitter_settings::store(&mut self.sidecar_mut(), &script, settings.clone());
+        self.save_sidecar();
+        self.runtime().reload_settings(&settings);
+    }
+
+    /// Load the auto splitter again with the settings kept for the current run,
+    /// after other splits were loaded.
+    fn restore_auto_splitter_settings(&self) {
+        let Some(script) = self.config().general.auto_splitter.clone() else {
+            return;
+        };
+        let settings = self
+            .sidecar()
+            .auto_splitter_settings
+            .get(&script)
+            .cloned()
+            .unwrap_or_default();
+        self.runtime().reload_settings(&settings);
+    }
+
+    /// Write the sidecar next to the current splits file.
+    pub fn save_sidecar(&self) {
+        if self.read_only() {
+            return;
+        }
+        if let Some(path) = self.config().general.splits.as_deref()
+            && let Err(e) = self.sidecar().save(path)
+        {
+            warn!("Could not save sidecar for {}: {e}", path.display());
+        }
+    }
+
+    /// Apply a reviewed `plan` to the sidecar and write it.
+    pub fn repair_sidecar(&self, plan: &RepairPlan) {
+        if plan.is_empty() {
+            return;
+        }
+        apply_sidecar_repair(&mut self.sidecar_mut(), plan);
+        self.save_sidecar();
+        self.emit_run_changed();
+    }
+
+    /// Write the run and its sidecar to the current splits file. Once written the
+    /// run counts as saved, so it no longer needs a recovery copy.
+    pub fn save_splits(&self) -> Result<(), TuxSplitError> {
+        if self.read_only() {
+            return Err(TuxSplitError::ReadOnly);
+        }
+        self.flush_regeneration();
+        let Ok(c) = self.imp().config.try_borrow() else {
+            return Err(TuxSplitError::ConfigBusy);
+        };
+        let shared_timer = self.timer();
+        let t = shared_timer.read().unwrap_or_else(PoisonError::into_inner);
+        c.save_splits(&t)?;
+        drop(t);
+        let saved = c.general.splits.is_some();
+        drop(c);
+        if saved {
+            // Nothing left for the shutdown recovery file to keep
+            self.mark_saved();
+        }
+        self.save_sidecar();
+        Ok(())
+    }
+
+    /// Make `path` the current splits file and load it. When the file cannot be
+    /// read, the run and splits file stay as they were.
+    pub fn load_splits(&self, path: PathBuf) -> Result<(), TuxSplitError> {
+        let run = read_run(&path)?;
+        // The window as it was goes with the run being unloaded
+        self.remember_window_placement();
+        if let Ok(mut c) = self.config_mut() {
+            c.mark_dirty();
+            c.set_splits_path(path);
+        }
+        self.imp().unopened_splits.take();
+        self.reload_sidecar();
+        self.restore_auto_splitter_settings();
+        self.set_run(run);
+        self.mark_saved();
+        self.apply_window_placement();
+        if self.imp().holds_lock.get() {
+            // Tells a second instance which splits are in use
+            self.take_instance_lock();
+        }
+        Ok(())
+    }
+
+    /// Whether saving is turned off, leaving the files to another instance.
+    pub fn read_only(&self) -> bool {
+        self.imp().read_only.get()
+    }
+
+    pub fn set_read_only(&self, read_only: bool) {
+        if self.imp().read_only.replace(read_only) != read_only {
+            info!("Saving turned {}", if read_only { "off" } else { "on" });
+            self.emit_by_name::<()>("read-only-changed", &[]);
+        }
+    }
+
+    /// Who holds the instance lock, removing it when its instance is gone.
+    pub fn instance_lock_state(&self) -> LockState {
+        InstanceLock::check(
+            &paths::current().root.join(INSTANCE_LOCK_FILE),
+            std::process::id(),
+            is_running_instance,
+        )
+    }
+
+    /// Writes the instance lock for this instance, taking it over if another one
+    /// holds it.
+    pub fn take_instance_lock(&self) {
+        let path = paths::current().root.join(INSTANCE_LOCK_FILE);
+        let lock = InstanceLock {
+            pid: std::process::id(),
+            splits: self.config().general.splits.clone(),
+        };
+        match lock.acquire(&path) {
+            Ok(()) => self.imp().holds_lock.set(true),
+            Err(e) => warn!("Could not write the instance lock {}: {e}", path.display()),
+        }
+    }
+
+    pub fn set_main_window(&self, window: &ApplicationWindow) {
+        self.imp().main_window.set(Some(window));
+    }
+
+    /// Gives the main window the geometry of the current run, or else the one
+    /// of the config (see `utils::window_placement`).
+    pub fn apply_window_placement(&self) {
+        let Some(window) = self.imp().main_window.upgrade() else {
+            return;
+        };
+        // GTK 4 has no way to move a window, so stored positions stay unused
+        let placement = resolve_placement(
+            self.sidecar().window.as_ref(),
+            self.config().window_geometry(),
+            monitor_size(&window),
+            false,
+        );
+        if placement.width.is_some() || placement.height.is_some() {
+            window.set_default_size(
+                placement.width.unwrap_or(-1),
+                placement.height.unwrap_or(-1),
+            );
+        }
+        if placement.maximized {
+            window.maximize();
+        } else if window.is_maximized() {
+            window.unmaximize();
+        }
+    }
+
+    /// Stores the geometry of the main window in the config, and in the sidecar
+    /// of the current run if there is one.
+    pub fn remember_window_placement(&self) {
+        let Some(window) = self.imp().main_window.upgrade() else {
+            return;
+        };
+        let (width, height) = window.default_size();
+        let maximized = window.is_maximized();
+        if let Ok(mut cfg) = self.config_mut() {
+            let geometry = captured_geometry(Some(cfg.window_geometry()), width, height, maximized);
+            if geometry != *cfg.window_geometry() {
+                cfg.mark_dirty();
+                cfg.set_window_geometry(geometry);
+            }
+        }
+        if self.config().general.splits.is_none() {
+            return;
+        }
+        let geometry = {
+            let sidecar = self.sidecar();
+            captured_geometry(sidecar.window.as_ref(), width, height, maximized)
+        };
+        if self.sidecar().window != Some(geometry) {
+            self.sidecar_mut().window = Some(geometry);
+            self.save_sidecar();
+        }
     }
 
     /// Replace the run (full set_run) and emit run-changed. Re-configures
     /// timer based on current config (useful if comparisons / settings depend
-    /// on run contents).
+    /// on run contents). The run counts as modified until `mark_saved`.
     pub fn set_run(&self, new_run: Run) {
+        // Edits still being regenerated are superseded
+        let cancelled = self.imp().regeneration.borrow_mut().cancel();
+        if cancelled {
+            self.emit_by_name::<()>("regeneration-changed", &[]);
+        }
+        self.replace_run(new_run);
+    }
+
+    fn replace_run(&self, new_run: Run) {
         let timer_arc = self.timer();
         {
             let mut timer = timer_arc.write().unwrap();
             let _ = timer.set_run(new_run);
             // Re-apply config in case it needs to reinitialize aspects of the timer.
             self.config().configure_timer(&mut timer);
+            // Replacing the run resets the attempt, and its golds with it.
+            self.update_golds(&timer);
         }
+        self.mark_run_modified();
         self.emit_run_changed();
     }
 
+    /// Replace the run with an edit of it. Runs with long histories have their
+    /// comparisons regenerated on a worker before being set, so the window does
+    /// not stall; edits made in the meantime only regenerate the latest.
+    pub fn set_edited_run(&self, run: Run) {
+        if !regeneration::needs_background(&run) {
+            self.set_run(run);
+            return;
+        }
+        let job = self.imp().regeneration.borrow_mut().request(run);
+        if let Some(job) = job {
+            Self::spawn_regeneration(job);
+        }
+        // Hold starts right away rather than from the next tick on
+        self.update_preflight();
+        self.emit_by_name::<()>("regeneration-changed", &[]);
+    }
+
+    fn spawn_regeneration(job: Job<Run>) {
+        debug!("Regenerating comparisons in the background");
+        glib::MainContext::default().spawn_local(async move {
+            let Job { generation, input } = job;
+            let run = gio::spawn_blocking(move || regeneration::precompute_comparisons(input))
+                .await
+                .ok();
+            TuxSplitContext::get_instance().finish_regeneration(generation, run);
+        });
+    }
+
+    /// Sets the result of the worker of `generation` unless it was superseded,
+    /// and starts the next one.
+    fn finish_regeneration(&self, generation: u64, run: Option<Run>) {
+        let completion = self.imp().regeneration.borrow_mut().complete(generation);
+        if let Some(latest) = completion.apply {
+            if run.is_none() {
+                warn!("Background regeneration failed, regenerating comparisons here");
+            }
+            self.replace_run(run.unwrap_or(latest));
+        }
+        match completion.next {
+            Some(job) => Self::spawn_regeneration(job),
+            None => self.emit_by_name::<()>("regeneration-changed", &[]),
+        }
+    }
+
+    /// Whether edits are waiting for their comparisons to be regenerated.
+    pub fn regenerating(&self) -> bool {
+        self.imp().regeneration.borrow().is_running()
+    }
+
+    /// Sets the latest edit right away, regenerating it here, for what needs the
+    /// timer to hold it, such as saving.
+    fn flush_regeneration(&self) {
+        let latest = self.imp().regeneration.borrow().latest().cloned();
+        if let Some(run) = latest {
+            self.set_run(run);
+        }
+    }
+
+    /// Keeps a copy of `run` before a change that rewrites its data, such as
+    /// saving the split editor. Resets are taken care of by the action queue.
+    pub fn take_run_snapshot(&self, run: &Run, description: &str) {
+        let snapshots = self.imp().snapshots.borrow();
+        snapshots
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take(run, description);
+    }
+
+    /// What the latest snapshot was taken before, and when.
+    pub fn latest_run_snapshot(&self) -> Option<(String, time::OffsetDateTime)> {
+        let snapshots = self.imp().snapshots.borrow();
+        let snapshots = snapshots.lock().unwrap_or_else(PoisonError::into_inner);
+        snapshots
+            .latest()
+            .map(|snapshot| (snapshot.description.clone(), snapshot.taken_at))
+    }
+
+    /// Whether a snapshot can be put back: putting one back replaces the run, so
+    /// not during an attempt or while the editor is open.
+    pub fn can_undo_data_change(&self) -> bool {
+        !self.editor_open()
+            && self.timer().read().unwrap().current_phase() == TimerPhase::NotRunning
+            && self.latest_run_snapshot().is_some()
+    }
+
+    /// Puts back the run of the latest snapshot, returning what was undone.
+    pub fn undo_data_change(&self) -> Option<String> {
+        if !self.can_undo_data_change() {
+            return None;
+        }
+        let snapshot = {
+            let snapshots = self.imp().snapshots.borrow();
+            let mut snapshots = snapshots.lock().unwrap_or_else(PoisonError::into_inner);
+            snapshots.pop()?
+        };
+        info!("Undoing data change: {}", snapshot.description);
+        self.set_run(snapshot.run);
+        Some(snapshot.description)
+    }
+
+    /// Checks every 30 seconds whether the config changed and writes it if so.
+    /// Changes must be flagged with `Config::mark_dirty`.
+    pub fn start_autosave(&self) {
+        glib::timeout_add_seconds_local(AUTOSAVE_INTERVAL_SECS, || {
+            TuxSplitContext::get_instance().save_config_if_dirty();
+            glib::ControlFlow::Continue
+        });
+    }
+
+    /// Writes the config unless it is unchanged since the last write.
+    pub fn save_config_if_dirty(&self) {
+        if self.read_only() {
+            return;
+        }
+        let Ok(config) = self.imp().config.try_borrow() else {
+            return; // Being modified, the next check will catch it
+        };
+        let revision = config.revision();
+        if !self.imp().autosave.borrow().needs_save(revision) {
+            return;
+        }
+
+        match self.write_config(&config, &paths::current().config_file()) {
+            Ok(()) => {
+                debug!("Config autosaved");
+                self.imp().autosave.borrow_mut().mark_saved(revision);
+            }
+            Err(e) => warn!("Could not autosave config: {e}"),
+        }
+    }
+
+    /// Writes `config` to `path`. A config.yaml that could not be read on startup
+    /// is first moved to config.yaml.bak, so the user's settings are not lost to
+    /// the defaults used in its place.
+    ///
+    /// The splits file that could not be opened stays in the file until the user
+    /// removes it, so it is tried again on the next start.
+    fn write_config(&self, config: &Config, path: &Path) -> Result<(), TuxSplitError> {
+        if config.general.splits.is_none()
+            && let Some((splits, _)) = &*self.imp().unopened_splits.borrow()
+        {
+            let mut config = config.clone();
+            config.set_splits_path(splits.clone());
+            return self.write_config(&config, path);
+        }
+        if self.imp().config_unreadable.get() {
+            let backup = back_up_config(path).map_err(|source| TuxSplitError::Io {
+                path: path.to_owned(),
+                source,
+            })?;
+            info!("Moved the unreadable config to {}", backup.display());
+            self.imp().config_unreadable.set(false);
+        }
+        config.save(path)
+    }
+
+    /// Saves everything that would otherwise be lost when the app goes away: the
+    /// config, and a recovery copy of the run when it has unsaved changes or an
+    /// attempt is live.
+    ///
+    /// Called from the application shutdown and from the SIGTERM/SIGINT handlers,
+    /// so only the first call does anything. It only writes local files, so it
+    /// cannot hang the session logout.
+    pub fn graceful_shutdown(&self) {
+        self.graceful_shutdown_into(&paths::current().root);
+    }
+
+    /// `graceful_shutdown` writing into `dir`. Returns false if it already ran.
+    fn graceful_shutdown_into(&self, dir: &Path) -> bool {
+        if self.imp().shut_down.replace(true) {
+            return false;
+        }
+        info!("Shutting down TuxSplit");
+        if self.imp().holds_lock.get() {
+            InstanceLock::release(&dir.join(INSTANCE_LOCK_FILE), std::process::id());
+        }
+        safe_mode::clear_crash(dir);
+        if self.read_only() {
+            info!("Saving is turned off, leaving the config and splits as they are");
+            return true;
+        }
+
+        match self.imp().config.try_borrow() {
+            Ok(config) => {
+                if let Err(e) = self.write_config(&config, &dir.join(CONFIG_FILE)) {
+                    warn!("Could not save config on shutdown: {e}");
+                }
+            }
+            Err(_) => warn!("Config is being modified, not saving it on shutdown"),
+        }
+
+        let timer = self.timer();
+        let Ok(timer) = timer.read() else {
+            return true;
+        };
+        self.update_dirty(&timer);
+        if recovery_needed(timer.current_phase(), self.run_dirty()) {
+            let path = dir.join(RECOVERY_FILE);
+            let mut buf = String::new();
+            let result = save_timer(&timer, &mut buf)
+                .map_err(|e| std::io::Error::other(e.to_string()))
+                .and_then(|()| write_atomic(&path, buf));
+            match result {
+                Ok(()) => info!("Wrote unsaved run to {}", path.display()),
+                Err(e) => warn!("Could not write {}: {e}", path.display()),
+            }
+        }
+        true
+    }
+
     pub fn disable_hotkeys(&self) {
         if let Ok(mut cfg_write) = self.config_mut() {
             cfg_write.disable_hotkey_system();
@@ -181,49 +1513,487 @@ pub fn build_ui(app: &Application) {
         .build();
 
     let toolbar_view = ToolbarView::new();
-    let header = TuxSplitHeader::new(&window);
-    toolbar_view.add_top_bar(header.header());
-
     let mut timer_widget = TuxSplitTimer::new();
     timer_widget.start_refresh_loop();
-    toolbar_view.set_content(Some(timer_widget.clamped()));
+    TuxSplitContext::get_instance().start_autosave();
+    // Holds the menu when there is no header bar
+    let chrome_overlay = Overlay::builder().child(timer_widget.clamped()).build();
+    TuxSplitHeader::new(&window).install(&toolbar_view, &chrome_overlay);
+    install_window_drag(&timer_widget.run_info());
+    dnd::install(&window);
+    // Toasts of actions run from the header menu
+    let toast_overlay = ToastOverlay::new();
+    toast_overlay.set_child(Some(&chrome_overlay));
+    toolbar_view.set_content(Some(&toast_overlay));
 
     window.set_content(Some(&toolbar_view));
+    let ctx = TuxSplitContext::get_instance();
+    ctx.set_main_window(&window);
+    ctx.apply_window_placement();
+    // Viewers only mirror this window
+    window.connect_close_request(|_| {
+        TuxSplitContext::get_instance().remember_window_placement();
+        viewer::close_all();
+        glib::Propagation::Proceed
+    });
     window.present();
+    claim_instance(&window);
+    offer_safe_mode(app, &window);
+    if TuxSplitContext::get_instance().config().viewer.open {
+        viewer::open(app);
+    }
+    check_sidecar(&window);
+    check_duplicate_names(&window);
+    report_hotkey_problems(&window, &toast_overlay);
+    for error in TuxSplitContext::get_instance().take_startup_errors() {
+        show_error(&window, &error);
+    }
+    offer_splits_recovery(&window);
+    offer_deferred_resets(&toast_overlay);
+    warn_clock_jumps(&toast_overlay);
+    announce_lost_pb(&toast_overlay);
+    preflight::connect(&window);
+    drop_missing_secondary_comparison(&toast_overlay);
+    call_back_on_pace(app, &window);
+    announce_goal_finish(&toast_overlay);
+    announce_missed_splits(&window, &toast_overlay);
+    announce_remote_actions(&toast_overlay);
+    #[cfg(feature = "webhooks")]
+    announce_webhook_failures(&toast_overlay);
 }
 
-pub fn shutdown() {
-    info!("Shutting down TuxSplit");
-    TuxSplitContext::get_instance()
-        .config()
-        .save(get_config_path().join("config.yaml"))
-        .expect("Failed to save config on shutdown");
+/// Size of the monitor showing `window`, or of the first one before it is shown.
+fn monitor_size(window: &ApplicationWindow) -> Option<(i32, i32)> {
+    let display = WidgetExt::display(window);
+    let monitor = window
+        .surface()
+        .and_then(|surface| display.monitor_at_surface(&surface))
+        .or_else(|| {
+            display
+                .monitors()
+                .item(0)
+                .and_downcast::<gtk4::gdk::Monitor>()
+        })?;
+    let geometry = monitor.geometry();
+    Some((geometry.width(), geometry.height()))
+}
+
+/// Takes the instance lock, or, when another instance holds it, asks whether to
+/// run without saving, take the lock over or quit: two instances saving the same
+/// files overwrite each other.
+fn claim_instance(window: &ApplicationWindow) {
+    let ctx = TuxSplitContext::get_instance();
+    let LockState::Held(other) = ctx.instance_lock_state() else {
+        ctx.take_instance_lock();
+        return;
+    };
+    let splits = other.splits.as_deref().map_or_else(
+        || "no splits file".to_owned(),
+        |path| path.display().to_string(),
+    );
+    let dialog = adw::AlertDialog::builder()
+        .heading("TuxSplit Is Already Running")
+        .body(format!(
+            "Another instance (process {}, with {splits}) appears to be timing. Both saving \
+             the same files would overwrite each other's changes.",
+            other.pid
+        ))
+        .default_response("read-only")
+        .close_response("read-only")
+        .build();
+    dialog.add_response("quit", "_Quit");
+    dialog.add_response("take-over", "_Take Over");
+    dialog.add_response("read-only", "_Continue Without Saving");
+    dialog.set_response_appearance("take-over", adw::ResponseAppearance::Destructive);
+    dialog.set_response_appearance("read-only", adw::ResponseAppearance::Suggested);
+    let window_binding = window.clone();
+    dialog.connect_response(None, move |_, response| {
+        let ctx = TuxSplitContext::get_instance();
+        match response {
+            "take-over" => ctx.take_instance_lock(),
+            "quit" => {
+                // Quitting must not save over the other instance's files either
+                ctx.set_read_only(true);
+                if let Some(app) = window_binding.application() {
+                    app.quit();
+                }
+            }
+            _ => ctx.set_read_only(true),
+        }
+    });
+    dialog.present(Some(window));
 }
 
-fn load_config() -> Config {
-    let user_cfg = get_config_path().join("config.yaml");
-    if user_cfg.is_file()
-        && let Some(cfg) = Config::parse(&user_cfg)
-    {
-        debug!("Loaded user config {}", user_cfg.display());
-        return cfg;
+/// When the last session crashed, offers to start over in safe mode, to tell
+/// whether the config or a setup caused it.
+fn offer_safe_mode(app: &Application, window: &ApplicationWindow) {
+    let root = &paths::current().root;
+    let Some(crash) = safe_mode::last_crash(root) else {
+        return;
+    };
+    // Asked once per crash
+    safe_mode::clear_crash(root);
+    if safe_mode::current().enabled() {
+        return;
     }
-    Config::default()
+    let reason = crash.lines().next().unwrap_or_default();
+    let dialog = adw::AlertDialog::builder()
+        .heading("TuxSplit Quit Unexpectedly")
+        .body(format!(
+            "The last session crashed: {reason}\n\nIn safe mode TuxSplit starts without \
+             custom colors and fonts, hotkeys, the auto splitter, the remote control, \
+             hooks and webhooks, to tell whether one of them caused it."
+        ))
+        .default_response("safe-mode")
+        .close_response("continue")
+        .build();
+    dialog.add_response("continue", "_Start Normally");
+    dialog.add_response("safe-mode", "Restart in _Safe Mode");
+    dialog.set_response_appearance("safe-mode", adw::ResponseAppearance::Suggested);
+    let app = app.clone();
+    dialog.connect_response(Some("safe-mode"), move |_, _| {
+        // Restarted by `main` once this instance is gone
+        safe_mode::request_restart();
+        app.quit();
+    });
+    dialog.present(Some(window));
+}
+
+/// Asks what to do about the splits file of the config that could not be opened
+/// on startup: find it again, drop it from the config, or go on with the empty
+/// run for this session (the config keeps pointing at the file).
+fn offer_splits_recovery(window: &ApplicationWindow) {
+    let Some((path, problem)) = TuxSplitContext::get_instance().unopened_splits() else {
+        return;
+    };
+    let dialog = adw::AlertDialog::builder()
+        .heading("Could Not Open Splits")
+        .body(format!(
+            "TuxSplit could not open {}: {}.\n{}.\n\nAn empty run is used, and it will not be saved over that file.",
+            path.display(),
+            problem.describe(),
+            problem.hint()
+        ))
+        .default_response("locate")
+        .close_response("empty")
+        .build();
+    dialog.add_response("remove", "_Remove from Config");
+    dialog.add_response("empty", "Start with _Empty Run");
+    dialog.add_response("locate", "_Locate File…");
+    dialog.set_response_appearance("locate", adw::ResponseAppearance::Suggested);
+    let window_binding = window.clone();
+    dialog.connect_response(None, move |_, response| match response {
+        "locate" => present_load_splits(&window_binding),
+        "remove" => TuxSplitContext::get_instance().forget_unopened_splits(),
+        _ => {}
+    });
+    dialog.present(Some(window));
+}
+
+/// A toast offering to let through the reset the auto-splitter was kept from
+/// making. A script insisting on it keeps the same toast up.
+fn offer_deferred_resets(overlay: &ToastOverlay) {
+    let shown: Rc<Cell<bool>> = Rc::default();
+    let overlay = overlay.clone();
+    TuxSplitContext::get_instance().connect_local("reset-deferred", false, move |_| {
+        if shown.get() {
+            return None;
+        }
+        let toast = adw::Toast::builder()
+            .title("The auto-splitter tried to reset the attempt")
+            .button_label("Allow")
+            .timeout(10)
+            .build();
+        toast.connect_button_clicked(|_| TuxSplitContext::get_instance().allow_reset());
+        let shown_binding = shown.clone();
+        toast.connect_dismissed(move |_| {
+            shown_binding.set(false);
+        });
+        shown.set(true);
+        overlay.add_toast(toast);
+        None
+    });
+}
+
+fn warn_clock_jumps(overlay: &ToastOverlay) {
+    let overlay = overlay.clone();
+    TuxSplitContext::get_instance().connect_local("clock-jumped", false, move |_| {
+        overlay.add_toast(
+            adw::Toast::builder()
+                .title("System clock changed during the run; real-time may be inaccurate")
+                .timeout(10)
+                .build(),
+        );
+        None
+    });
+}
+
+fn announce_lost_pb(overlay: &ToastOverlay) {
+    let overlay = overlay.clone();
+    TuxSplitContext::get_instance().connect_local("pb-lost", false, move |_| {
+        if TuxSplitContext::get_instance()
+            .config()
+            .notifications
+            .pb_dead
+        {
+            overlay.add_toast(
+                adw::Toast::builder()
+                    .title("PB no longer possible")
+                    .timeout(5)
+                    .build(),
+            );
+        }
+        None
+    });
+}
+
+/// Turns the secondary comparison off when the run no longer has it, e.g. after
+/// it was removed in the editor or another run was loaded.
+fn drop_missing_secondary_comparison(overlay: &ToastOverlay) {
+    let check = {
+        let overlay = overlay.clone();
+        move || {
+            let ctx = TuxSplitContext::get_instance();
+            let Some(name) = ctx
+                .config()
+                .display
+                .secondary_comparison()
+                .map(str::to_owned)
+            else {
+                return;
+            };
+            if ctx.get_run().comparisons().any(|c| c == name) {
+                return;
+            }
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.display.secondary_comparison = None;
+            }
+            overlay.add_toast(
+                adw::Toast::builder()
+                    .title(format!("No secondary comparison: the run has no “{name}”"))
+                    .timeout(5)
+                    .build(),
+            );
+        }
+    };
+    check();
+    TuxSplitContext::get_instance().connect_local("run-changed", false, move |_| {
+        check();
+        None
+    });
 }
 
-fn get_config_path() -> PathBuf {
-    if let Ok(path_str) = env::var("TUXSPLIT_DATADIR") {
-        PathBuf::from(&path_str)
-    } else if let Ok(path_str) = env::var("XDG_CONFIG_HOME") {
-        PathBuf::from(path_str).join("tuxsplit")
-    } else if let Ok(home) = env::var("HOME") {
-        let path = PathBuf::from(home).join(".config").join("tuxsplit");
-        if !path.is_dir() {
-            std::fs::create_dir_all(&path).expect("Failed to create config directory");
+/// Id of the desktop notification of `call_back_on_pace`.
+const PACE_NOTIFICATION: &str = "pb-pace";
+
+/// Calls the runner back to the window when a late attempt is on PB pace, with
+/// a notification and the urgency hint of the window. Neither takes the focus
+/// from the game.
+fn call_back_on_pace(app: &Application, window: &ApplicationWindow) {
+    // The window manager may leave the hint on once the window has the focus
+    window.connect_is_active_notify(|window| {
+        if window.is_active() {
+            set_urgency_hint(window, false);
+        }
+    });
+    let app = app.clone();
+    let window = window.downgrade();
+    TuxSplitContext::get_instance().connect_local("pb-pace", false, move |args| {
+        let segments_left = args[1].get::<u32>().unwrap_or_default();
+        let window = window.upgrade()?;
+        if segments_left == 0 {
+            app.withdraw_notification(PACE_NOTIFICATION);
+            set_urgency_hint(&window, false);
+            return None;
         }
-        path
-    } else {
-        PathBuf::from("/tmp")
+        let notification = gio::Notification::new("PB pace");
+        notification.set_body(Some(&if segments_left == 1 {
+            "PB pace — 1 segment to go".to_owned()
+        } else {
+            format!("PB pace — {segments_left} segments to go")
+        }));
+        notification.set_priority(gio::NotificationPriority::High);
+        app.send_notification(Some(PACE_NOTIFICATION), &notification);
+        if !window.is_active() {
+            set_urgency_hint(&window, true);
+        }
+        None
+    });
+}
+
+/// Sets the urgency hint of `window`, which asks the window manager for
+/// attention without raising the window. GTK 4 only has it on X11, the backend
+/// TuxSplit runs on.
+fn set_urgency_hint(window: &ApplicationWindow, urgent: bool) {
+    unsafe extern "C" {
+        fn gdk_x11_surface_set_urgency_hint(
+            surface: *mut gtk4::gdk::ffi::GdkSurface,
+            urgent: glib::ffi::gboolean,
+        );
+    }
+    let Some(surface) = window.surface() else {
+        return;
+    };
+    if surface.type_().name() != "GdkX11Surface" {
+        return;
+    }
+    // SAFETY: the surface is a live GdkX11Surface, as the function expects
+    unsafe {
+        gdk_x11_surface_set_urgency_hint(surface.to_glib_none().0, urgent.into_glib());
+    }
+}
+
+/// Says how a finished attempt did against the run's goal.
+fn announce_goal_finish(overlay: &ToastOverlay) {
+    let overlay = overlay.clone();
+    TuxSplitContext::get_instance().connect_local("goal-finished", false, move |args| {
+        let title = args[1].get::<String>().unwrap_or_default();
+        overlay.add_toast(adw::Toast::builder().title(title).timeout(8).build());
+        None
+    });
+}
+
+/// Points at the segments of a finished attempt that look like missed splits,
+/// so their times can be checked in the attempt history.
+fn announce_missed_splits(window: &ApplicationWindow, overlay: &ToastOverlay) {
+    let overlay = overlay.clone();
+    let window = window.clone();
+    TuxSplitContext::get_instance().connect_local("anomalies-found", false, move |args| {
+        let lines = args[1].get::<String>().unwrap_or_default();
+        let toast = adw::Toast::builder()
+            .title("Possible missed splits")
+            .button_label("Details")
+            .timeout(10)
+            .build();
+        let window = window.clone();
+        toast.connect_button_clicked(move |_| {
+            let dialog = adw::AlertDialog::builder()
+                .heading("Possible Missed Splits")
+                .body(format!(
+                    "These segments took far longer than they usually do:\n\n{lines}"
+                ))
+                .default_response("close")
+                .close_response("close")
+                .build();
+            dialog.add_response("close", "_Close");
+            dialog.add_response("history", "Attempt _History");
+            let parent = window.clone();
+            dialog.connect_response(Some("history"), move |_, _| {
+                crate::ui::history::present_history_dialog(&parent);
+            });
+            dialog.present(Some(&window));
+        });
+        overlay.add_toast(toast);
+        None
+    });
+}
+
+/// Tells the runner what was changed for them.
+fn announce_remote_actions(overlay: &ToastOverlay) {
+    let overlay = overlay.clone();
+    TuxSplitContext::get_instance().connect_local("remote-action", false, move |args| {
+        let title = args[1].get::<String>().unwrap_or_default();
+        overlay.add_toast(adw::Toast::builder().title(title).timeout(3).build());
+        None
+    });
+}
+
+#[cfg(feature = "webhooks")]
+fn announce_webhook_failures(overlay: &ToastOverlay) {
+    let overlay = overlay.clone();
+    TuxSplitContext::get_instance().connect_local("webhook-failed", false, move |args| {
+        let url = args[1].get::<String>().unwrap_or_default();
+        overlay.add_toast(
+            adw::Toast::builder()
+                .title(format!(
+                    "Could not reach the webhook {}",
+                    glib::markup_escape_text(&url)
+                ))
+                .timeout(5)
+                .build(),
+        );
+        None
+    });
+}
+
+/// One toast for every problem of the `hotkeys` section, with the details a click away.
+fn report_hotkey_problems(window: &ApplicationWindow, overlay: &ToastOverlay) {
+    let diagnostics: Vec<String> = TuxSplitContext::get_instance()
+        .config()
+        .hotkey_diagnostics()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    if diagnostics.is_empty() {
+        return;
+    }
+
+    let toast = adw::Toast::builder()
+        .title(if diagnostics.len() == 1 {
+            "A hotkey in config.yaml was ignored".to_owned()
+        } else {
+            format!("{} hotkeys in config.yaml were ignored", diagnostics.len())
+        })
+        .button_label("Details")
+        .timeout(0)
+        .build();
+    let window = window.clone();
+    toast.connect_button_clicked(move |_| {
+        let dialog = adw::AlertDialog::builder()
+            .heading("Hotkey Problems")
+            .body(diagnostics.join("\n"))
+            .default_response("close")
+            .close_response("close")
+            .build();
+        dialog.add_response("close", "_Close");
+        dialog.present(Some(&window));
+    });
+    overlay.add_toast(toast);
+}
+
+pub fn shutdown() {
+    TuxSplitContext::get_instance().graceful_shutdown();
+}
+
+/// Whether the run must be written to the recovery file on shutdown: it has
+/// edits or finished attempts that were not saved, or an attempt is still going.
+pub(crate) fn recovery_needed(phase: TimerPhase, run_modified: bool) -> bool {
+    run_modified || phase != TimerPhase::NotRunning
+}
+
+/// Whether animations may play, from safe mode, the `display.animations`
+/// override and the system setting.
+pub(crate) fn animations_allowed(safe_mode: SafeMode, config: Animations, system: bool) -> bool {
+    safe_mode.animations() && config.enabled(system)
+}
+
+/// Today's local date as `YYYY-MM-DD`, the format gold dates are stored in.
+pub(crate) fn today() -> Option<String> {
+    glib::DateTime::now_local()
+        .and_then(|now| now.format("%F"))
+        .map(String::from)
+        .ok()
+}
+
+/// The user config, or the default one when there is none yet.
+pub(crate) fn load_config() -> Result<Config, TuxSplitError> {
+    let user_cfg = paths::current().config_file();
+    if !user_cfg.is_file() {
+        return Ok(Config::default());
+    }
+    let cfg = Config::parse(&user_cfg)?;
+    debug!("Loaded user config {}", user_cfg.display());
+    Ok(cfg)
+}
+
+/// Moves the config at `path` to config.yaml.bak beside it, replacing an older
+/// backup. Returns where it went; a config that is not there needs no backup.
+fn back_up_config(path: &Path) -> std::io::Result<PathBuf> {
+    let backup = path.with_extension("yaml.bak");
+    match std::fs::rename(path, &backup) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(backup),
     }
 }
 
@@ -235,3 +2005,140 @@ fn register_gresource(resource_path: &Path) {
         debug!("Registered GResource from {}", resource_path.display());
     }
 }
+
+#[cfg(test)]
+mod shutdown_tests {
+    use super::*;
+
+    #[test]
+    fn recovery_is_needed_for_unsaved_edits_and_live_attempts() {
+        assert!(!recovery_needed(TimerPhase::NotRunning, false));
+        assert!(recovery_needed(TimerPhase::NotRunning, true));
+        assert!(recovery_needed(TimerPhase::Running, false));
+        assert!(recovery_needed(TimerPhase::Paused, false));
+        assert!(recovery_needed(TimerPhase::Ended, false));
+    }
+
+    #[test]
+    fn unreadable_configs_are_backed_up_before_being_written_over() {
+        let dir = std::env::temp_dir().join(format!("tuxsplit-backup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE);
+        std::fs::write(&path, "general: [unclosed").unwrap();
+
+        let backup = back_up_config(&path).unwrap();
+        assert_eq!(backup, dir.join("config.yaml.bak"));
+        assert_eq!(
+            std::fs::read_to_string(&backup).unwrap(),
+            "general: [unclosed"
+        );
+        assert!(!path.exists());
+        // Nothing to back up the second time
+        assert!(back_up_config(&path).is_ok());
+        assert!(backup.is_file());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn shutting_down_with_the_empty_run_keeps_the_unopened_splits() {
+        let dir = std::env::temp_dir().join(format!("tuxsplit-unopened-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let splits = dir.join("gone.lss");
+
+        // "Start with Empty Run" leaves the file out of the config for the session
+        let ctx = TuxSplitContext::get_instance();
+        ctx.config_mut().unwrap().general.splits = None;
+        ctx.imp()
+            .unopened_splits
+            .replace(Some((splits.clone(), SplitsProblem::Missing)));
+
+        assert!(ctx.graceful_shutdown_into(&dir));
+        let written = Config::parse(dir.join(CONFIG_FILE)).unwrap();
+        assert_eq!(written.general.splits.as_deref(), Some(splits.as_path()));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn graceful_shutdown_only_runs_once() {
+        let dir = std::env::temp_dir().join(format!("tuxsplit-shutdown-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let ctx = TuxSplitContext::get_instance();
+        ctx.timer().write().unwrap().start();
+
+        assert!(ctx.graceful_shutdown_into(&dir));
+        assert!(dir.join("config.yaml").is_file());
+        assert!(dir.join(RECOVERY_FILE).is_file());
+
+        std::fs::remove_file(dir.join(RECOVERY_FILE)).unwrap();
+        assert!(!ctx.graceful_shutdown_into(&dir));
+        assert!(!dir.join(RECOVERY_FILE).exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod safe_mode_tests {
+    use super::*;
+
+    #[test]
+    fn animations_never_play_in_safe_mode() {
+        let safe = SafeMode::new(true);
+        for config in [Animations::Auto, Animations::On, Animations::Off] {
+            assert!(!animations_allowed(safe, config, true));
+            assert!(!animations_allowed(safe, config, false));
+        }
+        let normal = SafeMode::default();
+        assert!(animations_allowed(normal, Animations::On, false));
+        assert!(animations_allowed(normal, Animations::Auto, true));
+        assert!(!animations_allowed(normal, Animations::Auto, false));
+        assert!(!animations_allowed(normal, Animations::Off, true));
+    }
+
+    #[cfg(feature = "remote-control")]
+    #[test]
+    fn the_remote_control_never_starts_in_safe_mode() {
+        let ctx = TuxSplitContext::get_instance();
+        ctx.set_remote_enabled(true, SafeMode::new(true)).unwrap();
+        assert!(ctx.imp().remote.borrow().is_none());
+    }
+
+    #[test]
+    fn hooks_never_run_in_safe_mode() {
+        let dir = std::env::temp_dir().join(format!("tuxsplit-safe-hooks-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let started = dir.join("started");
+        let split = dir.join("split");
+
+        let ctx = TuxSplitContext::get_instance();
+        {
+            let mut config = ctx.config_mut().unwrap();
+            config.hooks.enabled = true;
+            config.hooks.on_start = Some(format!("touch {}", started.display()));
+            config.hooks.on_split = Some(format!("touch {}", split.display()));
+        }
+        let mut run = Run::new();
+        run.push_segment(livesplit_core::Segment::new("One"));
+        run.push_segment(livesplit_core::Segment::new("Two"));
+        let mut timer = Timer::new(run).unwrap();
+
+        ctx.update_hooks(&timer, &[], SafeMode::new(true));
+        timer.start();
+        ctx.update_hooks(&timer, &[], SafeMode::new(true));
+        // The same setup runs the hook outside safe mode
+        timer.split();
+        ctx.update_hooks(&timer, &[], SafeMode::default());
+
+        let deadline = Instant::now() + std::time::Duration::from_secs(5);
+        while !split.exists() && Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert!(split.exists());
+        assert!(!started.exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,578 @@
+//! `tuxsplit --doctor`: environment checks that run without opening a window.
+//!
+//! Every check is a standalone function returning a [`Check`], so they can be
+//! reused outside of the CLI (e.g. from a diagnostics page in the settings).
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use livesplit_core::{Run, Segment, Timer};
+
+use crate::config::Config;
+use crate::error::TuxSplitError;
+use crate::paths::Paths;
+use crate::startup::diagnose_splits;
+use crate::utils::auto_splitter::Runtime;
+use crate::utils::auto_splitter_settings::SettingsMap;
+use crate::utils::contrast::HEX_COLOR_FORMS;
+use crate::utils::hotkeys::{HotkeyDiagnostic, QueuedHotkeys};
+use crate::utils::reset_guard::UserActions;
+use crate::utils::timer_actions::ActionQueue;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl Severity {
+    fn tag(self) -> &'static str {
+        match self {
+            Self::Ok => "[ OK ]",
+            Self::Warn => "[WARN]",
+            Self::Fail => "[FAIL]",
+        }
+    }
+}
+
+/// The result of a single check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub severity: Severity,
+    pub detail: String,
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            severity: Severity::Ok,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            severity: Severity::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            severity: Severity::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Runs every check against the current environment and user configuration.
+    pub fn collect() -> Self {
+        Self::collect_checks(true)
+    }
+
+    /// The checks that can run next to the app: those creating a hotkey backend
+    /// or loading the auto splitter are left out, as they would compete with the
+    /// ones the app already has.
+    pub fn collect_in_app() -> Self {
+        Self::collect_checks(false)
+    }
+
+    fn collect_checks(standalone: bool) -> Self {
+        let paths = crate::paths::current();
+        let config = crate::context::load_config();
+        let config_file = check_config_file(config.as_ref().err());
+        let config = config.unwrap_or_default();
+
+        let mut checks = vec![
+            check_version(),
+            check_data_mode(paths),
+            check_config_dir(&paths.root),
+            config_file,
+            check_splits(config.general.splits.as_deref()),
+        ];
+        if standalone {
+            checks.push(check_hotkeys(&config));
+        }
+        checks.push(check_hotkey_bindings(config.hotkey_diagnostics()));
+        checks.push(check_state_colors(config.color_diagnostics()));
+        if standalone {
+            checks.push(check_auto_splitter(config.general.auto_splitter.as_deref()));
+        }
+        checks.extend([
+            check_resources(&crate::GRESOURCE_PATHS),
+            check_toolkit(),
+            check_session(|key| std::env::var(key).ok()),
+        ]);
+        Self { checks }
+    }
+
+    pub fn worst(&self) -> Severity {
+        self.checks
+            .iter()
+            .map(|check| check.severity)
+            .max()
+            .unwrap_or(Severity::Ok)
+    }
+
+    /// 0 when everything is fine, 1 when there are warnings, 2 on failures.
+    pub fn exit_code(&self) -> i32 {
+        match self.worst() {
+            Severity::Ok => 0,
+            Severity::Warn => 1,
+            Severity::Fail => 2,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for check in &self.checks {
+            let _ = writeln!(
+                out,
+                "{} {}: {}",
+                check.severity.tag(),
+                check.name,
+                check.detail
+            );
+            if let Some(hint) = &check.hint {
+                let _ = writeln!(out, "       hint: {hint}");
+            }
+        }
+        out
+    }
+}
+
+/// The version and commit of the build, to quote in bug reports.
+pub fn check_version() -> Check {
+    Check::ok("Version", crate::version::describe())
+}
+
+/// Which of the locations in [`crate::paths`] is in use, and its root.
+pub fn check_data_mode(paths: &Paths) -> Check {
+    Check::ok(
+        "Data mode",
+        format!("{}, in {}", paths.mode, paths.root.display()),
+    )
+}
+
+/// The configuration directory must exist and be writable, as the config is saved there on exit.
+pub fn check_config_dir(dir: &Path) -> Check {
+    const NAME: &str = "Config directory";
+
+    if !dir.is_dir() {
+        return Check::fail(
+            NAME,
+            format!("{} does not exist", dir.display()),
+            "Create it, or point TUXSPLIT_DATADIR to an existing directory",
+        );
+    }
+
+    let probe = dir.join(".tuxsplit-doctor");
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            Check::ok(NAME, format!("{} (writable)", dir.display()))
+        }
+        Err(e) => Check::fail(
+            NAME,
+            format!("{} is not writable: {e}", dir.display()),
+            "Settings will not be saved; fix the directory permissions",
+        ),
+    }
+}
+
+/// The config file, when there is one, must parse; TuxSplit falls back to the
+/// defaults otherwise.
+pub fn check_config_file(error: Option<&TuxSplitError>) -> Check {
+    const NAME: &str = "Config file";
+
+    match error {
+        None => Check::ok(NAME, "config.yaml parsed (or not created yet)"),
+        Some(e) => Check::fail(
+            NAME,
+            format!("{e}: {}", e.details()),
+            "Fix the reported line, or remove config.yaml to start from the defaults",
+        ),
+    }
+}
+
+/// The configured splits file must exist and be readable by the LiveSplit parser.
+pub fn check_splits(path: Option<&Path>) -> Check {
+    const NAME: &str = "Splits";
+
+    let Some(path) = path else {
+        return Check::warn(
+            NAME,
+            "no splits file configured",
+            "Load splits from the menu; an empty run is used until then",
+        );
+    };
+
+    // The same diagnosis the timer acts on when it starts
+    match diagnose_splits(path, |path| fs::read(path)) {
+        Ok(run) => Check::ok(
+            NAME,
+            format!(
+                "{} ({} segments, {} attempts)",
+                path.display(),
+                run.len(),
+                run.attempt_count()
+            ),
+        ),
+        Err(problem) => Check::fail(
+            NAME,
+            format!("{}: {}", path.display(), problem.describe()),
+            problem.hint(),
+        ),
+    }
+}
+
+/// Tries to create the global hotkey backend, like the timer does on startup.
+pub fn check_hotkeys(config: &Config) -> Check {
+    const NAME: &str = "Global hotkeys";
+
+    let queue = ActionQueue::spawn(placeholder_timer().into_shared());
+    let mut hotkeys = QueuedHotkeys::new(config.hotkeys, queue, UserActions::default());
+    match hotkeys.activate() {
+        Ok(()) => Check::ok(NAME, "hotkey backend available"),
+        Err(e) => Check::fail(
+            NAME,
+            format!("hotkey backend unavailable: {e}"),
+            "Global hotkeys need an X11 or XWayland session",
+        ),
+    }
+}
+
+/// Reports the bindings of the `hotkeys` section that could not be used.
+pub fn check_hotkey_bindings(diagnostics: &[HotkeyDiagnostic]) -> Check {
+    const NAME: &str = "Hotkey bindings";
+
+    if diagnostics.is_empty() {
+        return Check::ok(NAME, "every binding in config.yaml is valid");
+    }
+    let detail = diagnostics
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ");
+    Check::warn(
+        NAME,
+        detail,
+        "Key names follow livesplit-core, e.g. Numpad1, KeyA or Ctrl + F1",
+    )
+}
+
+/// Reports the colors of `display.colors` that are not hex colors.
+pub fn check_state_colors(diagnostics: &[String]) -> Check {
+    const NAME: &str = "State colors";
+
+    if diagnostics.is_empty() {
+        return Check::ok(NAME, "every color in config.yaml is valid");
+    }
+    Check::warn(
+        NAME,
+        diagnostics.join("; "),
+        format!("Colors are written {HEX_COLOR_FORMS}, e.g. '#33d17a'"),
+    )
+}
+
+/// Starts the auto-splitting runtime and checks the configured script, if any.
+pub fn check_auto_splitter(script: Option<&Path>) -> Check {
+    const NAME: &str = "Auto splitter";
+
+    let timer = placeholder_timer().into_shared();
+    let runtime = Runtime::new(timer.clone(), ActionQueue::spawn(timer));
+    let Some(script) = script else {
+        return Check::ok(NAME, "runtime available, no script configured");
+    };
+
+    if !script.is_file() {
+        return Check::warn(
+            NAME,
+            format!("{} does not exist", script.display()),
+            "Update or remove auto-splitter in config.yaml",
+        );
+    }
+
+    match runtime.load_script_blocking(script.to_path_buf(), &SettingsMap::new()) {
+        Ok(()) => Check::ok(NAME, format!("{} loaded", script.display())),
+        Err(e) => Check::fail(
+            NAME,
+            format!("{} failed to load: {e}", script.display()),
+            "Only WebAssembly auto splitters are supported",
+        ),
+    }
+}
+
+/// The compiled resources (stylesheet and icons) must be installed in one of the known places.
+pub fn check_resources(candidates: &[&str]) -> Check {
+    const NAME: &str = "Resources";
+
+    match candidates.iter().find(|path| Path::new(path).is_file()) {
+        Some(path) => Check::ok(NAME, *path),
+        None => Check::fail(
+            NAME,
+            format!("no tuxsplit.gresource in {}", candidates.join(", ")),
+            "Install TuxSplit with meson (or the Flatpak) so styles and icons are available",
+        ),
+    }
+}
+
+/// Reports the GTK and libadwaita versions found at runtime.
+pub fn check_toolkit() -> Check {
+    const NAME: &str = "Toolkit";
+
+    let gtk = (
+        gtk4::major_version(),
+        gtk4::minor_version(),
+        gtk4::micro_version(),
+    );
+    let adw = (
+        adw::major_version(),
+        adw::minor_version(),
+        adw::micro_version(),
+    );
+    let detail = format!(
+        "GTK {}.{}.{}, libadwaita {}.{}.{}",
+        gtk.0, gtk.1, gtk.2, adw.0, adw.1, adw.2
+    );
+
+    if (gtk.0, gtk.1) < (4, 12) || (adw.0, adw.1) < (1, 8) {
+        Check::warn(
+            NAME,
+            detail,
+            "TuxSplit is built for GTK 4.12 and libadwaita 1.8 or newer; update your runtime",
+        )
+    } else {
+        Check::ok(NAME, detail)
+    }
+}
+
+/// Detects the display session from the environment (`env` is `std::env::var` outside tests).
+pub fn check_session(env: impl Fn(&str) -> Option<String>) -> Check {
+    const NAME: &str = "Session";
+
+    let session_type = env("XDG_SESSION_TYPE").unwrap_or_default();
+    let wayland = env("WAYLAND_DISPLAY").is_some() || session_type == "wayland";
+    let x11 = env("DISPLAY").is_some();
+
+    match (wayland, x11) {
+        (false, true) => Check::ok(NAME, "X11"),
+        (true, true) => Check::warn(
+            NAME,
+            "Wayland with XWayland",
+            "Global hotkeys go through XWayland and only see keys pressed in X11 windows",
+        ),
+        (true, false) => Check::fail(
+            NAME,
+            "Wayland without XWayland",
+            "Install or enable XWayland; TuxSplit runs on the X11 backend",
+        ),
+        (false, false) => Check::fail(
+            NAME,
+            "no display detected",
+            "Run TuxSplit from a graphical session",
+        ),
+    }
+}
+
+fn placeholder_timer() -> Timer {
+    let mut run = Run::new();
+    run.push_segment(Segment::new("Doctor"));
+    Timer::new(run).expect("Failed to create timer")
+}
+
+#[cfg(test)]
+mod doctor_tests {
+    use super::*;
+    use livesplit_core::run::saver::livesplit::save_run;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn temp_dir() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "tuxsplit-doctor-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn the_data_mode_and_root_are_reported() {
+        let paths = Paths::resolve(None, false, |key| {
+            (key == "TUXSPLIT_DATADIR").then(|| "/media/usb/tuxsplit".to_owned())
+        });
+        let check = check_data_mode(&paths);
+        assert_eq!(check.severity, Severity::Ok);
+        assert_eq!(check.detail, "TUXSPLIT_DATADIR, in /media/usb/tuxsplit");
+    }
+
+    #[test]
+    fn config_dir_writable_missing_and_not_a_directory() {
+        let dir = temp_dir();
+        assert_eq!(check_config_dir(&dir).severity, Severity::Ok);
+        assert!(
+            fs::read_dir(&dir).unwrap().next().is_none(),
+            "probe file must be cleaned up"
+        );
+
+        assert_eq!(
+            check_config_dir(&dir.join("missing")).severity,
+            Severity::Fail
+        );
+
+        let file = dir.join("config.yaml");
+        fs::write(&file, "general: {}").unwrap();
+        assert_eq!(check_config_dir(&file).severity, Severity::Fail);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn splits_none_missing_invalid_and_valid() {
+        let dir = temp_dir();
+
+        assert_eq!(check_splits(None).severity, Severity::Warn);
+        let missing = check_splits(Some(&dir.join("missing.lss")));
+        assert_eq!(missing.severity, Severity::Fail);
+        assert!(
+            missing.detail.ends_with("the file does not exist"),
+            "{}",
+            missing.detail
+        );
+
+        let invalid = dir.join("invalid.lss");
+        fs::write(&invalid, [0xFF, 0x00, 0x13, 0x37]).unwrap();
+        assert_eq!(check_splits(Some(&invalid)).severity, Severity::Fail);
+
+        let mut run = Run::new();
+        run.set_game_name("Game");
+        run.set_category_name("Any%");
+        run.push_segment(Segment::new("A"));
+        run.push_segment(Segment::new("B"));
+        let mut buf = String::new();
+        save_run(&run, &mut buf).unwrap();
+        let valid = dir.join("valid.lss");
+        fs::write(&valid, buf).unwrap();
+
+        let check = check_splits(Some(&valid));
+        assert_eq!(check.severity, Severity::Ok);
+        assert!(check.detail.contains("2 segments"), "{}", check.detail);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn resources_found_in_any_candidate() {
+        let dir = temp_dir();
+        let resource = dir.join("tuxsplit.gresource");
+        fs::write(&resource, b"").unwrap();
+        let missing = dir.join("nope.gresource");
+
+        let found = check_resources(&[missing.to_str().unwrap(), resource.to_str().unwrap()]);
+        assert_eq!(found.severity, Severity::Ok);
+        assert_eq!(
+            check_resources(&[missing.to_str().unwrap()]).severity,
+            Severity::Fail
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn session_detection() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| (*v).to_owned())
+            }
+        };
+
+        assert_eq!(
+            check_session(env(&[("DISPLAY", ":0")])).severity,
+            Severity::Ok
+        );
+        assert_eq!(
+            check_session(env(&[("WAYLAND_DISPLAY", "wayland-0"), ("DISPLAY", ":0")])).severity,
+            Severity::Warn
+        );
+        assert_eq!(
+            check_session(env(&[("XDG_SESSION_TYPE", "wayland")])).severity,
+            Severity::Fail
+        );
+        assert_eq!(check_session(env(&[])).severity, Severity::Fail);
+    }
+
+    #[test]
+    fn hotkey_bindings_list_every_problem() {
+        assert_eq!(check_hotkey_bindings(&[]).severity, Severity::Ok);
+
+        let check = check_hotkey_bindings(&[
+            HotkeyDiagnostic::UnknownAction("splt".to_owned()),
+            HotkeyDiagnostic::UnknownKey {
+                action: "reset".to_owned(),
+                key: "Nmpad3".to_owned(),
+            },
+        ]);
+        assert_eq!(check.severity, Severity::Warn);
+        assert_eq!(
+            check.detail,
+            "splt: not a hotkey action; reset: unknown key \"Nmpad3\", left unbound"
+        );
+    }
+
+    #[test]
+    fn invalid_state_colors_are_a_warning() {
+        assert_eq!(check_state_colors(&[]).severity, Severity::Ok);
+        let config =
+            Config::from_yaml(b"display:\n  colors:\n    gold: '#e5a50a'\n    finished: white\n")
+                .unwrap();
+        let check = check_state_colors(config.color_diagnostics());
+        assert_eq!(check.severity, Severity::Warn);
+        assert!(
+            check.detail.contains("display.colors.finished"),
+            "{}",
+            check.detail
+        );
+    }
+
+    #[test]
+    fn exit_code_follows_worst_severity() {
+        let mut report = Report::default();
+        assert_eq!(report.exit_code(), 0);
+
+        report.checks.push(Check::ok("A", "fine"));
+        assert_eq!(report.exit_code(), 0);
+
+        report.checks.push(Check::warn("B", "meh", "hint"));
+        assert_eq!(report.exit_code(), 1);
+
+        report.checks.push(Check::fail("C", "broken", "hint"));
+        report.checks.push(Check::ok("D", "fine"));
+        assert_eq!(report.worst(), Severity::Fail);
+        assert_eq!(report.exit_code(), 2);
+
+        let rendered = report.render();
+        assert!(rendered.contains("[WARN] B: meh\n       hint: hint\n"));
+        assert!(rendered.contains("[ OK ] D: fine\n"));
+    }
+}
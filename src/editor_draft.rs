@@ -0,0 +1,282 @@
+//! Edits of the split editor that were neither saved nor cancelled.
+//!
+//! Closing the editor window with edits pending reverts the run and keeps them as
+//! a draft in the data directory (`editor-draft.lss`, with `editor-draft.yaml`
+//! describing it). The next time the editor is opened on the same run, and the
+//! splits file was not saved since, the draft is offered back into the editing
+//! session. Saving or cancelling in the editor deletes it.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use livesplit_core::Run;
+use livesplit_core::run::saver::livesplit::save_run;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::parse_splits;
+use crate::paths::{EDITOR_DRAFT_FILE, EDITOR_DRAFT_META_FILE};
+use crate::storage::write_atomic;
+
+/// What identifies a run well enough to tell a draft belongs to it.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RunIdentity {
+    pub game: String,
+    pub category: String,
+    pub segment_count: usize,
+}
+
+impl RunIdentity {
+    pub fn of(run: &Run) -> Self {
+        Self {
+            game: run.game_name().to_owned(),
+            category: run.category_name().to_owned(),
+            segment_count: run.len(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct DraftMeta {
+    /// Splits file of the run, `None` for a run that was never saved.
+    pub splits: Option<PathBuf>,
+    /// The run as it was when the editor was opened.
+    pub base: RunIdentity,
+    /// The parts of the run the draft changes, see `edited_parts`.
+    pub edited: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DraftDecision {
+    Offer,
+    /// The draft is of another splits file: kept for when that one is edited.
+    OtherRun,
+    /// The run changed since the draft was written: it no longer applies.
+    Outdated,
+}
+
+/// Whether the draft described by `meta`, written at `draft_time`, is offered
+/// when editing `run` from the `splits` file last modified at `splits_time`.
+pub fn decide(
+    meta: &DraftMeta,
+    draft_time: Option<SystemTime>,
+    splits: Option<&Path>,
+    splits_time: Option<SystemTime>,
+    run: &Run,
+) -> DraftDecision {
+    if meta.splits.as_deref() != splits {
+        return DraftDecision::OtherRun;
+    }
+    if meta.base != RunIdentity::of(run) {
+        return DraftDecision::Outdated;
+    }
+    match (draft_time, splits_time) {
+        (Some(draft), Some(splits)) if splits > draft => DraftDecision::Outdated,
+        (None, _) => DraftDecision::Outdated,
+        _ => DraftDecision::Offer,
+    }
+}
+
+/// The parts of `base` that `draft` changes, as shown when offering it.
+pub fn edited_parts(base: &Run, draft: &Run) -> Vec<String> {
+    let names = |run: &Run| -> Vec<String> {
+        run.segments()
+            .iter()
+            .map(|segment| segment.name().to_owned())
+            .collect()
+    };
+    let mut parts = Vec::new();
+    if base.game_name() != draft.game_name() {
+        parts.push("game name");
+    }
+    if base.category_name() != draft.category_name() {
+        parts.push("category");
+    }
+    if base.offset() != draft.offset() || base.attempt_count() != draft.attempt_count() {
+        parts.push("timer settings");
+    }
+    if names(base) != names(draft) {
+        parts.push("segments");
+    } else if base.segments() != draft.segments() {
+        parts.push("segment times");
+    }
+    if parts.is_empty() && base != draft {
+        parts.push("run details");
+    }
+    parts.into_iter().map(str::to_owned).collect()
+}
+
+/// A draft read back from the data directory.
+#[derive(Debug)]
+pub struct Draft {
+    pub meta: DraftMeta,
+    pub run: Run,
+    /// When the draft was written.
+    pub written: Option<SystemTime>,
+}
+
+/// Writes `draft`, an edited version of `base` from `splits`, into `dir`.
+pub fn save(dir: &Path, splits: Option<&Path>, base: &Run, draft: &Run) -> io::Result<()> {
+    let meta = DraftMeta {
+        splits: splits.map(Path::to_owned),
+        base: RunIdentity::of(base),
+        edited: edited_parts(base, draft),
+    };
+    let mut lss = String::new();
+    save_run(draft, &mut lss).map_err(|e| io::Error::other(e.to_string()))?;
+    let yaml = serde_yaml::to_string(&meta).map_err(io::Error::other)?;
+    write_atomic(dir.join(EDITOR_DRAFT_FILE), lss)?;
+    write_atomic(dir.join(EDITOR_DRAFT_META_FILE), yaml)
+}
+
+/// Reads the draft of `dir`, if there is a usable one.
+pub fn load(dir: &Path) -> Option<Draft> {
+    let path = dir.join(EDITOR_DRAFT_FILE);
+    let lss = fs::read(&path).ok()?;
+    let meta = fs::read(dir.join(EDITOR_DRAFT_META_FILE)).ok()?;
+    let meta: DraftMeta = match serde_yaml::from_slice(&meta) {
+        Ok(meta) => meta,
+        Err(e) => {
+            warn!("Ignoring the editor draft: {e}");
+            return None;
+        }
+    };
+    let run = match parse_splits(&lss, &path) {
+        Ok(run) => run,
+        Err(e) => {
+            warn!("Ignoring the editor draft: {e}");
+            return None;
+        }
+    };
+    let written = fs::metadata(&path).and_then(|m| m.modified()).ok();
+    Some(Draft { meta, run, written })
+}
+
+/// Deletes the draft of `dir`, if any.
+pub fn delete(dir: &Path) {
+    for file in [EDITOR_DRAFT_FILE, EDITOR_DRAFT_META_FILE] {
+        let path = dir.join(file);
+        if let Err(e) = fs::remove_file(&path)
+            && e.kind() != io::ErrorKind::NotFound
+        {
+            warn!("Could not delete {}: {e}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod editor_draft_tests {
+    use super::*;
+    use livesplit_core::Segment;
+    use std::time::Duration;
+
+    const SPLITS: &str = "/home/runner/splits/any.lss";
+
+    fn run(game: &str, segments: &[&str]) -> Run {
+        let mut run = Run::new();
+        run.set_game_name(game);
+        run.set_category_name("Any%");
+        for name in segments {
+            run.push_segment(Segment::new(*name));
+        }
+        run
+    }
+
+    fn meta(base: &Run) -> DraftMeta {
+        DraftMeta {
+            splits: Some(PathBuf::from(SPLITS)),
+            base: RunIdentity::of(base),
+            edited: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn drafts_newer_than_the_splits_file_of_the_same_run_are_offered() {
+        let base = run("Game", &["One", "Two"]);
+        let written = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let decide_at = |splits_time| {
+            decide(
+                &meta(&base),
+                Some(written),
+                Some(Path::new(SPLITS)),
+                splits_time,
+                &base,
+            )
+        };
+        assert_eq!(
+            decide_at(Some(written - Duration::from_secs(60))),
+            DraftDecision::Offer
+        );
+        // Saved after the draft was written
+        assert_eq!(
+            decide_at(Some(written + Duration::from_secs(60))),
+            DraftDecision::Outdated
+        );
+        // A run that was never saved has no file to be newer
+        let unsaved = DraftMeta {
+            splits: None,
+            ..meta(&base)
+        };
+        assert_eq!(
+            decide(&unsaved, Some(written), None, None, &base),
+            DraftDecision::Offer
+        );
+    }
+
+    #[test]
+    fn drafts_of_other_runs_are_not_offered() {
+        let base = run("Game", &["One", "Two"]);
+        let now = Some(SystemTime::now());
+        assert_eq!(
+            decide(
+                &meta(&base),
+                now,
+                Some(Path::new("/other.lss")),
+                None,
+                &base
+            ),
+            DraftDecision::OtherRun
+        );
+        for changed in [run("Other", &["One", "Two"]), run("Game", &["One"])] {
+            assert_eq!(
+                decide(&meta(&base), now, Some(Path::new(SPLITS)), None, &changed),
+                DraftDecision::Outdated
+            );
+        }
+    }
+
+    #[test]
+    fn edited_parts_name_what_changed() {
+        let base = run("Game", &["One", "Two"]);
+        let mut draft = base.clone();
+        assert!(edited_parts(&base, &draft).is_empty());
+        draft.set_category_name("100%");
+        draft.segment_mut(1).set_name("Three");
+        assert_eq!(edited_parts(&base, &draft), ["category", "segments"]);
+    }
+
+    #[test]
+    fn drafts_are_written_read_and_deleted() {
+        let dir =
+            std::env::temp_dir().join(format!("tuxsplit-editor-draft-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let base = run("Game", &["One", "Two"]);
+        let mut draft = base.clone();
+        draft.set_game_name("Game (Draft)");
+
+        save(&dir, Some(Path::new(SPLITS)), &base, &draft).unwrap();
+        let loaded = load(&dir).unwrap();
+        assert_eq!(loaded.run.game_name(), "Game (Draft)");
+        assert_eq!(loaded.meta.base.game, "Game");
+        assert_eq!(loaded.meta.edited, ["game name"]);
+        assert!(loaded.written.is_some());
+
+        delete(&dir);
+        assert!(load(&dir).is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,127 @@
+//! Errors of the operations the user can run into: reading and writing the config
+//! and splits, and starting the hotkeys, auto splitter and remote control.
+//!
+//! Each carries its underlying error as `source`, which the error dialog of
+//! `ui::error` shows as technical details under a human message.
+
+use std::error::Error as StdError;
+use std::path::PathBuf;
+
+/// Underlying error of a `TuxSplitError`.
+pub type Source = Box<dyn StdError + Send + Sync>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TuxSplitError {
+    #[error("Could not use the config file {}", path.display())]
+    Config {
+        path: PathBuf,
+        #[source]
+        source: Source,
+    },
+    #[error("Could not open the splits file {}", path.display())]
+    RunParse {
+        path: PathBuf,
+        #[source]
+        source: Source,
+    },
+    #[error("Could not save the splits to {}", path.display())]
+    RunSave {
+        path: PathBuf,
+        #[source]
+        source: Source,
+    },
+    #[error("Global hotkeys are unavailable")]
+    Hotkey {
+        #[source]
+        source: Source,
+    },
+    #[error("Could not load the auto splitter {}", path.display())]
+    AutoSplitter {
+        path: PathBuf,
+        #[source]
+        source: Source,
+    },
+    #[error("Could not start the remote control on port {port}")]
+    RemoteControl {
+        port: u16,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Saving is turned off while another TuxSplit instance is running")]
+    ReadOnly,
+    #[error("The splits were not saved while the settings were being changed")]
+    ConfigBusy,
+    #[error("Could not write {}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+impl TuxSplitError {
+    /// Short heading for the error dialog.
+    pub const fn title(&self) -> &'static str {
+        match self {
+            Self::Config { .. } => "Config Problem",
+            Self::RunParse { .. } => "Could Not Open Splits",
+            Self::RunSave { .. } => "Could Not Save Splits",
+            Self::Hotkey { .. } => "Hotkeys Unavailable",
+            Self::AutoSplitter { .. } => "Auto Splitter Not Loaded",
+            Self::RemoteControl { .. } => "Remote Control Unavailable",
+            Self::ReadOnly => "Saving Turned Off",
+            Self::ConfigBusy => "Could Not Save Splits",
+            Self::Io { .. } => "Could Not Write File",
+        }
+    }
+
+    /// What the user can do about it, if anything.
+    pub const fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::Config { .. } => Some("TuxSplit runs with default settings until it is fixed."),
+            Self::RunParse { .. } => {
+                Some("The file may not be a LiveSplit splits file, or may be damaged.")
+            }
+            Self::RunSave { .. } | Self::Io { .. } => {
+                Some("Check that the folder exists and that you can write to it.")
+            }
+            Self::Hotkey { .. } => Some("Global hotkeys need an X11 or XWayland session."),
+            Self::AutoSplitter { .. } => None,
+            Self::ReadOnly => Some("Close the other instance and restart TuxSplit to save."),
+            Self::ConfigBusy => Some("Try saving again."),
+            Self::RemoteControl { .. } => {
+                Some("Another program may be using the port. Pick another one in the preferences.")
+            }
+        }
+    }
+
+    /// The chain of underlying errors, one per line.
+    pub fn details(&self) -> String {
+        let mut details = Vec::new();
+        let mut source = self.source();
+        while let Some(error) = source {
+            details.push(error.to_string());
+            source = error.source();
+        }
+        details.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod error_tests {
+    use super::*;
+
+    #[test]
+    fn details_follow_the_source_chain() {
+        let error = TuxSplitError::RunSave {
+            path: PathBuf::from("/splits/any.lss"),
+            source: Box::new(std::io::Error::other("disk full")),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Could not save the splits to /splits/any.lss"
+        );
+        assert_eq!(error.details(), "disk full");
+        assert!(error.hint().is_some());
+    }
+}
@@ -0,0 +1,49 @@
+use glib::DateTime;
+
+/// Wall-clock time a run ends at when `remaining` is left from `now`.
+pub fn finish_eta(now: &DateTime, remaining: time::Duration) -> Option<DateTime> {
+    let remaining = remaining.max(time::Duration::ZERO);
+    now.add_seconds(remaining.as_seconds_f64()).ok()
+}
+
+/// Formats an ETA as a clock time in the user's locale, e.g. "ends ~22:41:00".
+pub fn format_eta(eta: &DateTime) -> Option<String> {
+    let clock = eta.format("%X").ok()?;
+    Some(format!("ends ~{clock}"))
+}
+
+#[cfg(test)]
+mod clock_tests {
+    use super::*;
+
+    fn at(hour: i32, minute: i32) -> DateTime {
+        DateTime::from_utc(2025, 3, 14, hour, minute, 0.0).unwrap()
+    }
+
+    #[test]
+    fn eta_adds_the_remaining_time() {
+        let eta = finish_eta(&at(20, 15), time::Duration::minutes(75)).unwrap();
+        assert_eq!((eta.hour(), eta.minute()), (21, 30));
+        assert_eq!(eta.day_of_month(), 14);
+    }
+
+    #[test]
+    fn eta_rolls_over_midnight() {
+        let eta = finish_eta(&at(23, 50), time::Duration::minutes(25)).unwrap();
+        assert_eq!((eta.hour(), eta.minute()), (0, 15));
+        assert_eq!(eta.day_of_month(), 15);
+    }
+
+    #[test]
+    fn negative_remaining_time_ends_now() {
+        let eta = finish_eta(&at(12, 0), time::Duration::seconds(-30)).unwrap();
+        assert_eq!((eta.hour(), eta.minute()), (12, 0));
+    }
+
+    #[test]
+    fn formatted_eta_is_a_clock_time() {
+        let formatted = format_eta(&at(22, 41)).unwrap();
+        assert!(formatted.starts_with("ends ~"));
+        assert!(formatted.contains("41"));
+    }
+}
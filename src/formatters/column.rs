@@ -0,0 +1,147 @@
+//! Text of the comparison column of the splits: the time in the current
+//! comparison and, dimmed after it, the time in the secondary comparison
+//! (`display.secondary-comparison`), e.g. "12:34.5 / 12:40.1".
+
+/// Characters the column may take on a row of normal width.
+pub const WIDE_COLUMN_CHARS: usize = 24;
+/// Characters the column may take on a narrow row, see `NARROW_ROW_WIDTH`.
+pub const NARROW_COLUMN_CHARS: usize = 16;
+/// Rows narrower than this, in pixels, are narrow.
+pub const NARROW_ROW_WIDTH: i32 = 420;
+
+/// Characters of the column on a row `row_width` pixels wide. Rows not laid out
+/// yet have a width of 0 and count as wide.
+pub fn column_chars(row_width: i32) -> usize {
+    if row_width > 0 && row_width < NARROW_ROW_WIDTH {
+        NARROW_COLUMN_CHARS
+    } else {
+        WIDE_COLUMN_CHARS
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComparisonColumn {
+    pub primary: String,
+    /// Dimmed text after `primary`, with its separator.
+    pub secondary: Option<String>,
+}
+
+/// The column for the `primary` and `secondary` times in at most `max_chars`
+/// characters, a space between the two included:
+///
+/// - "12:34.5 / 12:40.1" when both fit;
+/// - "12:34.5 / …40.1" otherwise, the secondary time losing the hours and
+///   minutes it shares with the primary one;
+/// - "12:34.5" when that does not fit either. The tooltip still has both.
+///
+/// The primary time is never shortened.
+pub fn comparison_column(
+    primary: &str,
+    secondary: Option<&str>,
+    max_chars: usize,
+) -> ComparisonColumn {
+    let fits =
+        |secondary: &str| primary.chars().count() + 1 + secondary.chars().count() <= max_chars;
+    let secondary = secondary.and_then(|secondary| {
+        let full = format!("/ {secondary}");
+        if fits(&full) {
+            return Some(full);
+        }
+        let shared = shared_fields(primary, secondary);
+        let short = format!("/ …{}", &secondary[shared..]);
+        (shared > 0 && fits(&short)).then_some(short)
+    });
+    ComparisonColumn {
+        primary: primary.to_owned(),
+        secondary,
+    }
+}
+
+/// Bytes at the start of `secondary` that repeat `primary` up to and including
+/// a separator, e.g. 5 for "1:12:34.5" and "1:12:40.1".
+fn shared_fields(primary: &str, secondary: &str) -> usize {
+    let mut shared = 0;
+    for ((index, a), b) in secondary.char_indices().zip(primary.chars()) {
+        if a != b {
+            break;
+        }
+        if matches!(a, ':' | '.') {
+            shared = index + a.len_utf8();
+        }
+    }
+    shared
+}
+
+#[cfg(test)]
+mod column_tests {
+    use super::*;
+
+    fn column(
+        primary: &str,
+        secondary: Option<&str>,
+        max_chars: usize,
+    ) -> (String, Option<String>) {
+        let column = comparison_column(primary, secondary, max_chars);
+        (column.primary, column.secondary)
+    }
+
+    #[test]
+    fn both_times_are_shown_when_they_fit() {
+        assert_eq!(
+            column("12:34.5", Some("12:40.1"), WIDE_COLUMN_CHARS),
+            ("12:34.5".to_owned(), Some("/ 12:40.1".to_owned()))
+        );
+        assert_eq!(
+            column("1:12:34.5", Some("1:12:40.1"), WIDE_COLUMN_CHARS),
+            ("1:12:34.5".to_owned(), Some("/ 1:12:40.1".to_owned()))
+        );
+        assert_eq!(
+            column("12:34.5", None, WIDE_COLUMN_CHARS),
+            ("12:34.5".to_owned(), None)
+        );
+    }
+
+    #[test]
+    fn narrow_rows_drop_the_shared_fields_then_the_secondary_time() {
+        // 17 characters in full, 15 without the shared minutes
+        assert_eq!(
+            column("12:34.5", Some("12:40.1"), NARROW_COLUMN_CHARS),
+            ("12:34.5".to_owned(), Some("/ …40.1".to_owned()))
+        );
+        assert_eq!(
+            column("1:12:34.5", Some("1:12:40.1"), 17),
+            ("1:12:34.5".to_owned(), Some("/ …40.1".to_owned()))
+        );
+        // 17 characters even when shortened
+        assert_eq!(
+            column("1:12:34.5", Some("1:12:40.1"), NARROW_COLUMN_CHARS),
+            ("1:12:34.5".to_owned(), None)
+        );
+        // Nothing shared: all or nothing
+        assert_eq!(
+            column("59:58.0", Some("1:00:02.3"), NARROW_COLUMN_CHARS).1,
+            None
+        );
+        // The primary time is kept whole however narrow the row
+        assert_eq!(
+            column("1:12:34.5", Some("-"), 4),
+            ("1:12:34.5".to_owned(), None)
+        );
+    }
+
+    #[test]
+    fn shared_fields_end_at_a_separator() {
+        assert_eq!(shared_fields("1:12:34.5", "1:12:40.1"), 5);
+        assert_eq!(shared_fields("12:34.5", "12:34.9"), 6);
+        // "1" is shared, but not up to a separator
+        assert_eq!(shared_fields("12:34.5", "13:40.1"), 0);
+        assert_eq!(shared_fields("12:34.5", "-"), 0);
+    }
+
+    #[test]
+    fn narrow_rows_get_fewer_characters() {
+        assert_eq!(column_chars(0), WIDE_COLUMN_CHARS);
+        assert_eq!(column_chars(300), NARROW_COLUMN_CHARS);
+        assert_eq!(column_chars(NARROW_ROW_WIDTH), WIDE_COLUMN_CHARS);
+    }
+}
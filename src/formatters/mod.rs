@@ -1,3 +1,6 @@
+pub mod clock;
+pub mod column;
 pub mod label;
 pub mod time;
+pub mod tooltip;
 pub use time::*;
@@ -31,6 +31,28 @@ impl Default for TimeFormat {
         }
     }
 }
+
+/// CSS classes of the groups of `TimeFormat::digit_groups`, in order.
+pub const DIGIT_GROUP_CLASSES: [&str; DIGIT_GROUPS] = [
+    "digits-sign",
+    "digits-hours",
+    "separator",
+    "digits-minutes",
+    "separator",
+    "digits-seconds",
+    "separator",
+    "digits-frac",
+];
+pub const DIGIT_GROUPS: usize = 8;
+
+/// A run of the digits of the big timer, shown in its own label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigitGroup {
+    pub class: &'static str,
+    pub text: String,
+    pub visible: bool,
+}
+
 #[derive(Debug, Clone, Copy)]
 #[allow(clippy::enum_variant_names)]
 pub enum TimeFormatPreset {
@@ -252,9 +274,10 @@ impl TimeFormat {
         self.format_time_span_opt(span_opt)
     }
 
-    /// Formats the overall timer's current attempt duration into a string using this format.
-    pub fn format_timer(&self, timer: &Timer) -> String {
-        let dur = timer
+    /// Time shown by the big timer: the current attempt from the run offset,
+    /// without pauses and, in game time, loads. Negative while counting down.
+    pub fn timer_duration(timer: &Timer) -> TimeDuration {
+        timer
             .current_attempt_duration()
             .to_duration()
             .checked_add(timer.run().offset().to_duration())
@@ -266,13 +289,80 @@ impl TimeFormat {
             } else {
                 TimeDuration::ZERO
             })
-            .unwrap_or_default();
-        let out = self.format_duration(&dur);
-        if dur < TimeDuration::ZERO {
-            format!("-{out}")
-        } else {
-            out
+            .unwrap_or_default()
+    }
+
+    /// `duration` split into the groups the big timer shows in separate labels,
+    /// always `DIGIT_GROUPS` of them in this order: sign, hours, separator,
+    /// minutes, separator, seconds, decimal separator and fraction. Groups this
+    /// format leaves out, or that would only be leading zeros ("0:"), are hidden;
+    /// the visible texts put together read like `format_duration`, with a "-"
+    /// before negative durations.
+    pub fn digit_groups(&self, duration: &TimeDuration) -> Vec<DigitGroup> {
+        let span = TimeSpan::from_milliseconds(duration.whole_nanoseconds() as f64 / 1_000_000.0);
+        let abs_ms = span.total_milliseconds().abs() as i64;
+        let hours = abs_ms / 3_600_000;
+        let minutes = (abs_ms / 60_000) % 60;
+        let seconds = (abs_ms / 1_000) % 60;
+        let millis = abs_ms % 1_000;
+
+        let mut groups: Vec<DigitGroup> = DIGIT_GROUP_CLASSES
+            .iter()
+            .map(|class| DigitGroup {
+                class,
+                text: String::new(),
+                visible: false,
+            })
+            .collect();
+        groups[0].text.push('-');
+        groups[0].visible = *duration < TimeDuration::ZERO;
+
+        // Same walk as `format_time_span`, each token written to its own group
+        let pattern = self.compute_pattern(Some(abs_ms));
+        let mut shown = String::new();
+        let mut separator = 2;
+        let mut chars = pattern.chars().peekable();
+        while let Some(ch) = chars.next() {
+            let mut count = 1usize;
+            while chars.peek() == Some(&ch) {
+                chars.next();
+                count += 1;
+            }
+            let before = shown.len();
+            let index = match ch {
+                'h' => {
+                    Self::append_number(&mut shown, hours, false);
+                    separator = 2;
+                    1
+                }
+                'm' => {
+                    Self::append_number(&mut shown, minutes, false);
+                    separator = 4;
+                    3
+                }
+                's' => {
+                    Self::append_number(&mut shown, seconds, true);
+                    separator = 6;
+                    5
+                }
+                'd' => {
+                    Self::append_fraction(&mut shown, millis, count);
+                    7
+                }
+                _ => {
+                    if !shown.is_empty() {
+                        for _ in 0..count {
+                            shown.push(ch);
+                        }
+                    }
+                    separator
+                }
+            };
+            let group = &mut groups[index];
+            group.text.push_str(&shown[before..]);
+            group.visible |= shown.len() > before;
         }
+        groups
     }
 
     /// Formats a segment duration.
@@ -381,9 +471,42 @@ pub fn parse_hms(input: &str) -> Result<TimeDuration, TimeParseError> {
     Ok(TimeDuration::new(total_secs as i64, nanos as i32))
 }
 
+/// A more forgiving `parse_hms` for text coming from other programs (e.g. a spreadsheet column).
+///
+/// Surrounding whitespace is ignored, only the first tab-separated cell is read, a comma
+/// is accepted as decimal separator and the fractional part may be left out ("1:44").
+pub fn parse_pasted_time(input: &str) -> Result<TimeDuration, TimeParseError> {
+    let cell = input.split('\t').next().unwrap_or_default().trim();
+    if cell.is_empty() {
+        return Err(TimeParseError);
+    }
+
+    let mut normalized = if cell.contains('.') {
+        cell.to_owned()
+    } else {
+        cell.replacen(',', ".", 1)
+    };
+    if !normalized.contains('.') {
+        normalized.push_str(".0");
+    }
+
+    parse_hms(&normalized)
+}
+
+/// `parse_pasted_time` that also accepts a leading sign, for values that may be
+/// negative such as the run offset ("-0:05" starts the timer five seconds early).
+pub fn parse_signed_time(input: &str) -> Result<TimeDuration, TimeParseError> {
+    let trimmed = input.trim();
+    if let Some(rest) = trimmed.strip_prefix('-') {
+        parse_pasted_time(rest).map(|d| -d)
+    } else {
+        parse_pasted_time(trimmed.strip_prefix('+').unwrap_or(trimmed))
+    }
+}
+
 #[cfg(test)]
 mod format_tests {
-    use super::TimeFormat;
+    use super::{DIGIT_GROUP_CLASSES, DIGIT_GROUPS, TimeFormat, TimeFormatPreset};
     use livesplit_core::TimeSpan;
 
     fn make_tf(hours: bool, minutes: bool, seconds: bool, decimals: u8) -> TimeFormat {
@@ -586,6 +709,146 @@ mod format_tests {
         let d = time::Duration::seconds(10);
         assert_eq!(tf.format_duration_opt(Some(d)), "10.00");
     }
+
+    /// The visible groups of `ms`, as (class, text).
+    fn visible_groups(tf: &TimeFormat, ms: i64) -> Vec<(&'static str, String)> {
+        tf.digit_groups(&time::Duration::milliseconds(ms))
+            .into_iter()
+            .filter(|group| group.visible)
+            .map(|group| (group.class, group.text))
+            .collect()
+    }
+
+    fn joined(tf: &TimeFormat, ms: i64) -> String {
+        visible_groups(tf, ms)
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect()
+    }
+
+    #[test]
+    fn digit_groups_read_like_the_formatted_time() {
+        let formats = [
+            make_tf(true, true, true, 1),
+            make_tf(true, true, true, 2),
+            make_tf(true, true, true, 3),
+            make_tf(false, true, true, 2),
+            make_tf(false, false, true, 3),
+            make_tf(true, true, true, 0),
+            TimeFormat::from_preset(TimeFormatPreset::SmartDecimals),
+        ];
+        let times = [
+            0,
+            7,
+            999,
+            1_000,
+            59_999,
+            60_000,
+            61_230,
+            3_599_999,
+            3_600_000,
+            3_661_005,
+            36_000_000,
+            360_000_000,
+        ];
+        for tf in &formats {
+            for ms in times {
+                let expected = tf.format_duration(&time::Duration::milliseconds(ms));
+                assert_eq!(joined(tf, ms), expected, "{ms} ms");
+                assert_eq!(
+                    joined(tf, -ms),
+                    if ms == 0 {
+                        expected
+                    } else {
+                        format!("-{expected}")
+                    }
+                );
+                assert_eq!(
+                    tf.digit_groups(&time::Duration::milliseconds(ms)).len(),
+                    DIGIT_GROUPS
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn leading_zero_groups_are_hidden() {
+        let tf = make_tf(true, true, true, 2);
+        let group = |class: &'static str, text: &str| (class, text.to_owned());
+        assert_eq!(
+            visible_groups(&tf, 5_120),
+            [
+                group("digits-seconds", "5"),
+                group("separator", "."),
+                group("digits-frac", "12")
+            ]
+        );
+        assert_eq!(
+            visible_groups(&tf, 3_599_999),
+            [
+                group("digits-minutes", "59"),
+                group("separator", ":"),
+                group("digits-seconds", "59"),
+                group("separator", "."),
+                group("digits-frac", "99"),
+            ]
+        );
+        // The hour rolls over into its own group
+        assert_eq!(
+            visible_groups(&tf, 3_600_000),
+            [
+                group("digits-hours", "1"),
+                group("separator", ":"),
+                group("digits-minutes", "00"),
+                group("separator", ":"),
+                group("digits-seconds", "00"),
+                group("separator", "."),
+                group("digits-frac", "00"),
+            ]
+        );
+        // Hidden groups keep their place, so labels map to them one to one
+        let groups = tf.digit_groups(&time::Duration::milliseconds(5_120));
+        assert_eq!(
+            groups.iter().map(|group| group.class).collect::<Vec<_>>(),
+            DIGIT_GROUP_CLASSES
+        );
+        assert!(!groups[1].visible && groups[1].text.is_empty());
+    }
+
+    #[test]
+    fn countdowns_from_the_run_offset_carry_a_sign() {
+        let tf = make_tf(true, true, true, 1);
+        assert_eq!(
+            visible_groups(&tf, -5_250),
+            [
+                ("digits-sign", "-".to_owned()),
+                ("digits-seconds", "5".to_owned()),
+                ("separator", ".".to_owned()),
+                ("digits-frac", "2".to_owned()),
+            ]
+        );
+        assert_eq!(joined(&tf, -65_000), "-1:05.0");
+    }
+
+    #[test]
+    fn precision_sets_the_fraction_digits() {
+        for (places, fraction) in [(1, "1"), (2, "12"), (3, "123")] {
+            let groups = visible_groups(&make_tf(true, true, true, places), 4_123);
+            assert_eq!(
+                groups.last().unwrap(),
+                &("digits-frac", fraction.to_owned())
+            );
+        }
+        // Without decimals there is neither a fraction nor its separator
+        assert_eq!(
+            visible_groups(&make_tf(true, true, true, 0), 4_123),
+            [("digits-seconds", "4".to_owned())]
+        );
+        // Smart decimals drop them over a minute
+        let smart = TimeFormat::from_preset(TimeFormatPreset::SmartDecimals);
+        assert_eq!(joined(&smart, 59_990), "59.99");
+        assert_eq!(joined(&smart, 60_000), "1:00");
+    }
 }
 
 #[allow(unused_imports)]
@@ -682,3 +945,56 @@ mod parse_tests {
         assert_eq!(parse_hms("1:44").err(), Some(TimeParseError));
     }
 }
+
+#[cfg(test)]
+mod parse_pasted_tests {
+    use super::{TimeParseError, parse_pasted_time, parse_signed_time};
+
+    #[test]
+    fn accepts_everything_parse_hms_does() {
+        let d = parse_pasted_time("1:45.23").unwrap();
+        assert_eq!(d.whole_seconds(), 105);
+        assert_eq!(d.subsec_nanoseconds(), 230_000_000);
+    }
+
+    #[test]
+    fn missing_fraction_and_whitespace() {
+        assert_eq!(parse_pasted_time(" 1:44 \r").unwrap().whole_seconds(), 104);
+        assert_eq!(parse_pasted_time("12").unwrap().whole_seconds(), 12);
+        assert_eq!(parse_pasted_time("1:2:3").unwrap().whole_seconds(), 3723);
+    }
+
+    #[test]
+    fn comma_decimal_separator() {
+        let d = parse_pasted_time("1:05,5").unwrap();
+        assert_eq!(d.whole_seconds(), 65);
+        assert_eq!(d.subsec_nanoseconds(), 500_000_000);
+    }
+
+    #[test]
+    fn only_first_cell_is_read() {
+        assert_eq!(
+            parse_pasted_time("1:00.0\tBoss").unwrap().whole_seconds(),
+            60
+        );
+    }
+
+    #[test]
+    fn garbage_and_empty_input_fail() {
+        assert_eq!(parse_pasted_time("").err(), Some(TimeParseError));
+        assert_eq!(parse_pasted_time("   ").err(), Some(TimeParseError));
+        assert_eq!(parse_pasted_time("Boss").err(), Some(TimeParseError));
+        assert_eq!(parse_pasted_time("1:75").err(), Some(TimeParseError));
+    }
+
+    #[test]
+    fn signed_times() {
+        assert_eq!(parse_signed_time("-0:05").unwrap().whole_seconds(), -5);
+        assert_eq!(parse_signed_time(" +1:00.5").unwrap().whole_seconds(), 60);
+        let d = parse_signed_time("-1,5").unwrap();
+        assert_eq!(d.whole_seconds(), -1);
+        assert_eq!(d.subsec_nanoseconds(), -500_000_000);
+        assert_eq!(parse_signed_time("-").err(), Some(TimeParseError));
+        assert_eq!(parse_signed_time("--1").err(), Some(TimeParseError));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::formatters::label::format_label;
+use crate::utils::pb_dates::PbSplitDate;
+use crate::utils::reached::Reached;
+
+/// Placeholder for comparisons that have no time for a segment.
+const MISSING_TIME: &str = "—";
+
+/// Builds the Pango markup of a split row tooltip listing the segment's time in
+/// every comparison. `times` holds `(comparison name, formatted time)` pairs;
+/// names are shortened through `format_label`.
+pub fn comparison_tooltip_markup(segment_name: &str, times: &[(String, Option<String>)]) -> String {
+    let mut markup = format!("<b>{}</b>", escape_markup(segment_name));
+    for (comparison, time) in times {
+        markup.push_str(&format!(
+            "\n{}: <tt>{}</tt>",
+            escape_markup(format_label(comparison)),
+            escape_markup(time.as_deref().unwrap_or(MISSING_TIME))
+        ));
+    }
+    markup
+}
+
+/// Tooltip line with the segment's time in the secondary comparison, e.g.
+/// "Shown dimmed: Avg 1:05.00".
+pub fn secondary_tooltip_line(comparison: &str, time: Option<&str>) -> String {
+    format!(
+        "Shown dimmed: {} <tt>{}</tt>",
+        escape_markup(format_label(comparison)),
+        escape_markup(time.unwrap_or(MISSING_TIME))
+    )
+}
+
+/// Tooltip line with the segment's best segment and, when known, the day it
+/// was set, e.g. "Gold: 1:02.4 — set 2023-08-14".
+pub fn gold_tooltip_line(time: Option<&str>, date: Option<&str>) -> String {
+    let mut line = format!(
+        "Gold: <tt>{}</tt>",
+        escape_markup(time.unwrap_or(MISSING_TIME))
+    );
+    if let Some(date) = date {
+        line.push_str(&format!(" — set {}", escape_markup(date)));
+    }
+    line
+}
+
+/// Tooltip line with the day the PB split time was achieved, e.g.
+/// "PB achieved: 2024-05-02", or "PB achieved: edited".
+pub fn pb_date_tooltip_line(date: PbSplitDate) -> String {
+    format!("PB achieved: {}", escape_markup(&date.format()))
+}
+
+/// Tooltip line with how often the attempts reached the segment, e.g.
+/// "Reached: 75% of 40 attempts".
+pub fn reached_tooltip_line(reached: &Reached) -> String {
+    let mut line = format!("Reached: <tt>{}</tt>", escape_markup(&reached.format()));
+    if reached.attempts > 0 {
+        line.push_str(&format!(
+            " of {} attempt{}",
+            reached.attempts,
+            if reached.attempts == 1 { "" } else { "s" }
+        ));
+    }
+    line
+}
+
+/// Escapes the characters with a meaning in Pango markup.
+pub fn escape_markup(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tooltip_tests {
+    use super::*;
+
+    #[test]
+    fn comparisons_are_labeled_and_missing_times_use_a_dash() {
+        let markup = comparison_tooltip_markup(
+            "Boss",
+            &[
+                ("Personal Best".to_owned(), Some("1:02.40".to_owned())),
+                ("Best Segments".to_owned(), None),
+                ("My Race".to_owned(), Some("1:05.00".to_owned())),
+            ],
+        );
+        assert_eq!(
+            markup,
+            "<b>Boss</b>\nPB: <tt>1:02.40</tt>\nSOB: <tt>—</tt>\nMy Race: <tt>1:05.00</tt>"
+        );
+    }
+
+    #[test]
+    fn segment_and_comparison_names_are_escaped() {
+        let markup = comparison_tooltip_markup(
+            "<Ganon> & Co",
+            &[("Tom & Jerry's <PB>".to_owned(), Some("0:01".to_owned()))],
+        );
+        assert_eq!(
+            markup,
+            "<b>&lt;Ganon&gt; &amp; Co</b>\nTom &amp; Jerry&apos;s &lt;PB&gt;: <tt>0:01</tt>"
+        );
+    }
+
+    #[test]
+    fn secondary_line_names_the_comparison() {
+        assert_eq!(
+            secondary_tooltip_line("Average Segments", Some("12:40.1")),
+            "Shown dimmed: Avg <tt>12:40.1</tt>"
+        );
+        assert_eq!(
+            secondary_tooltip_line("<Race>", None),
+            "Shown dimmed: &lt;Race&gt; <tt>—</tt>"
+        );
+    }
+
+    #[test]
+    fn gold_line_mentions_the_date_when_known() {
+        assert_eq!(
+            gold_tooltip_line(Some("1:02.4"), Some("2023-08-14")),
+            "Gold: <tt>1:02.4</tt> — set 2023-08-14"
+        );
+        assert_eq!(gold_tooltip_line(None, None), "Gold: <tt>—</tt>");
+    }
+
+    #[test]
+    fn pb_date_line_tells_edited_times_apart() {
+        let date = time::Date::from_calendar_date(2024, time::Month::May, 2).unwrap();
+        assert_eq!(
+            pb_date_tooltip_line(PbSplitDate::Achieved(date)),
+            "PB achieved: 2024-05-02"
+        );
+        assert_eq!(
+            pb_date_tooltip_line(PbSplitDate::Edited),
+            "PB achieved: edited"
+        );
+    }
+
+    #[test]
+    fn reached_line_counts_the_attempts() {
+        let reached = |reached, attempts| reached_tooltip_line(&Reached { reached, attempts });
+        assert_eq!(reached(3, 4), "Reached: <tt>75%</tt> of 4 attempts");
+        assert_eq!(reached(1, 1), "Reached: <tt>100%</tt> of 1 attempt");
+        assert_eq!(reached(0, 0), "Reached: <tt>—</tt>");
+    }
+}
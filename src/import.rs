@@ -0,0 +1,372 @@
+//! Importing segments from a route document exported as CSV, such as a
+//! spreadsheet with "Segment, Estimate" columns.
+//!
+//! The first line names the columns. Commas, semicolons (spreadsheets in locales
+//! with a decimal comma) and tabs separate the cells, whichever the header uses;
+//! quoted cells may hold separators, doubled quotes and line breaks. A leading
+//! UTF-8 byte order mark is ignored.
+
+use livesplit_core::{Run, RunEditor, Segment, TimeSpan, TimingMethod};
+
+use crate::formatters::time::parse_pasted_time;
+
+const BOM: char = '\u{feff}';
+const DELIMITERS: [char; 3] = [',', ';', '\t'];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CsvError {
+    #[error("The file has no header row")]
+    Empty,
+    #[error("A quoted cell starting on line {line} is never closed")]
+    UnclosedQuote { line: usize },
+}
+
+/// The cells of a CSV document. Every row has as many cells as the header,
+/// filled with empty cells or cut when it had another count.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvTable {
+    pub headers: Vec<String>,
+    /// Rows under the header, each with the line it starts on.
+    pub rows: Vec<(usize, Vec<String>)>,
+}
+
+/// The delimiter used most outside quotes on the header line of `text`, a comma
+/// when there is none.
+fn detect_delimiter(text: &str) -> char {
+    let mut counts = [0usize; DELIMITERS.len()];
+    let mut quoted = false;
+    for c in text.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '\n' if !quoted => break,
+            _ if !quoted => {
+                if let Some(index) = DELIMITERS.iter().position(|d| *d == c) {
+                    counts[index] += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    // Earlier delimiters win ties
+    let (index, _) = counts
+        .iter()
+        .enumerate()
+        .rev()
+        .max_by_key(|(_, count)| **count)
+        .unwrap_or((0, &0));
+    if counts[index] == 0 {
+        ','
+    } else {
+        DELIMITERS[index]
+    }
+}
+
+/// Reads `text` as CSV. Blank lines are skipped.
+pub fn parse_csv(text: &str) -> Result<CsvTable, CsvError> {
+    let text = text.strip_prefix(BOM).unwrap_or(text);
+    let delimiter = detect_delimiter(text);
+
+    let mut records: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut record = Vec::new();
+    let mut cell = String::new();
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut quoted_since = None;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted_since.is_some() {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    cell.push('"');
+                }
+                '"' => quoted_since = None,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    cell.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if cell.trim().is_empty() => {
+                cell.clear();
+                quoted_since = Some(line);
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::take(&mut cell));
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            _ if c == delimiter => record.push(std::mem::take(&mut cell)),
+            _ => cell.push(c),
+        }
+    }
+    if let Some(line) = quoted_since {
+        return Err(CsvError::UnclosedQuote { line });
+    }
+    if !cell.is_empty() || !record.is_empty() {
+        record.push(cell);
+        records.push((record_line, record));
+    }
+
+    let mut records = records
+        .into_iter()
+        .filter(|(_, cells)| cells.iter().any(|cell| !cell.trim().is_empty()));
+    let (_, headers) = records.next().ok_or(CsvError::Empty)?;
+    let headers: Vec<String> = headers.iter().map(|h| h.trim().to_owned()).collect();
+    let rows = records
+        .map(|(line, mut cells)| {
+            cells.resize(headers.len(), String::new());
+            (line, cells)
+        })
+        .collect();
+    Ok(CsvTable { headers, rows })
+}
+
+/// What the time column holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeKind {
+    /// Time from the start of the run to the end of the segment.
+    SplitTime,
+    /// Time of the segment alone, like an estimate.
+    SegmentTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnMapping {
+    pub name: usize,
+    pub time: Option<(usize, TimeKind)>,
+}
+
+impl ColumnMapping {
+    /// The columns whose header looks like a segment name and a time, else the
+    /// first column as names and no times.
+    pub fn guess(headers: &[String]) -> Self {
+        let find = |words: &[&str]| {
+            headers.iter().position(|header| {
+                let header = header.to_lowercase();
+                words.iter().any(|word| header.contains(word))
+            })
+        };
+        let name = find(&["segment", "name", "split"]).unwrap_or(0);
+        let time = headers
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != name)
+            .find_map(|(index, header)| {
+                let header = header.to_lowercase();
+                if header.contains("split") || header.contains("pb") {
+                    Some((index, TimeKind::SplitTime))
+                } else if ["time", "estimate", "segment"]
+                    .iter()
+                    .any(|word| header.contains(word))
+                {
+                    Some((index, TimeKind::SegmentTime))
+                } else {
+                    None
+                }
+            });
+        Self { name, time }
+    }
+}
+
+/// Segments read from a table with a mapping.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportPlan {
+    pub segments: Vec<(String, Option<TimeSpan>)>,
+    pub kind: Option<TimeKind>,
+    /// Time cells that could not be read, as (line, text). Their segment is
+    /// imported without a time.
+    pub invalid: Vec<(usize, String)>,
+}
+
+/// The segments `mapping` reads from `table`. Rows without a name are skipped,
+/// empty time cells leave the segment without a time.
+pub fn plan_import(table: &CsvTable, mapping: ColumnMapping) -> ImportPlan {
+    let mut plan = ImportPlan {
+        kind: mapping.time.map(|(_, kind)| kind),
+        ..ImportPlan::default()
+    };
+    for (line, cells) in &table.rows {
+        let name = cells.get(mapping.name).map_or("", |name| name.trim());
+        if name.is_empty() {
+            continue;
+        }
+        let time = mapping.time.and_then(|(column, _)| {
+            let cell = cells.get(column)?.trim();
+            if cell.is_empty() {
+                return None;
+            }
+            match parse_pasted_time(cell) {
+                Ok(time) => Some(TimeSpan::from(time)),
+                Err(_) => {
+                    plan.invalid.push((*line, cell.to_owned()));
+                    None
+                }
+            }
+        });
+        plan.segments.push((name.to_owned(), time));
+    }
+    plan
+}
+
+/// `run` with its segments replaced by those of `plan`, their times set as
+/// personal best times of `method`. Everything else about the run is kept.
+/// `None` when the plan has no segments.
+pub fn apply_import(mut run: Run, plan: &ImportPlan, method: TimingMethod) -> Option<Run> {
+    let ((first, _), _) = plan.segments.split_first()?;
+    *run.segments_mut() = vec![Segment::new(first.as_str())];
+    let mut editor = RunEditor::new(run).ok()?;
+    editor.select_timing_method(method);
+    for (index, (name, time)) in plan.segments.iter().enumerate() {
+        if index > 0 {
+            editor.insert_segment_below();
+        }
+        let mut segment = editor.active_segment();
+        segment.set_name(name.as_str());
+        match plan.kind {
+            Some(TimeKind::SplitTime) => segment.set_split_time(*time),
+            Some(TimeKind::SegmentTime) => segment.set_segment_time(*time),
+            None => {}
+        }
+    }
+    Some(editor.close())
+}
+
+#[cfg(test)]
+mod import_tests {
+    use super::*;
+
+    fn cells(row: &[&str]) -> Vec<String> {
+        row.iter().map(|&cell| cell.to_owned()).collect()
+    }
+
+    #[test]
+    fn headers_and_rows_are_read_with_their_lines() {
+        let table = parse_csv("Segment,Estimate\nForest,1:30\n\nCave,2:00.5\n").unwrap();
+        assert_eq!(table.headers, cells(&["Segment", "Estimate"]));
+        assert_eq!(
+            table.rows,
+            [
+                (2, cells(&["Forest", "1:30"])),
+                (4, cells(&["Cave", "2:00.5"]))
+            ]
+        );
+    }
+
+    #[test]
+    fn quotes_hold_delimiters_quotes_and_line_breaks() {
+        let table = parse_csv(
+            "Segment,Estimate\r\n\"Forest, part 1\",\"1:30\"\r\n\"The \"\"Cave\"\"\nexit\",2:00\r\nEnd,",
+        )
+        .unwrap();
+        assert_eq!(
+            table.rows,
+            [
+                (2, cells(&["Forest, part 1", "1:30"])),
+                (3, cells(&["The \"Cave\"\nexit", "2:00"])),
+                (5, cells(&["End", ""])),
+            ]
+        );
+        assert_eq!(
+            parse_csv("Segment\n\"Forest\nCave"),
+            Err(CsvError::UnclosedQuote { line: 2 })
+        );
+    }
+
+    #[test]
+    fn semicolons_tabs_and_byte_order_marks_are_handled() {
+        let table = parse_csv("\u{feff}Segment;Estimate\nForest;1:30,5\n").unwrap();
+        assert_eq!(table.headers, cells(&["Segment", "Estimate"]));
+        assert_eq!(table.rows, [(2, cells(&["Forest", "1:30,5"]))]);
+
+        let table = parse_csv("Segment\tEstimate\nForest, part 1\t1:30\n").unwrap();
+        assert_eq!(table.rows, [(2, cells(&["Forest, part 1", "1:30"]))]);
+
+        // Short rows are padded, long ones cut
+        let table = parse_csv("A,B\n1\n1,2,3\n").unwrap();
+        assert_eq!(
+            table.rows,
+            [(2, cells(&["1", ""])), (3, cells(&["1", "2"]))]
+        );
+        assert_eq!(parse_csv("\u{feff}\n\n"), Err(CsvError::Empty));
+    }
+
+    #[test]
+    fn mappings_are_guessed_from_the_headers() {
+        let guess = |headers: &[&str]| ColumnMapping::guess(&cells(headers));
+        assert_eq!(
+            guess(&["Segment", "Estimate"]),
+            ColumnMapping {
+                name: 0,
+                time: Some((1, TimeKind::SegmentTime))
+            }
+        );
+        assert_eq!(
+            guess(&["Notes", "Name", "PB Split"]),
+            ColumnMapping {
+                name: 1,
+                time: Some((2, TimeKind::SplitTime))
+            }
+        );
+        assert_eq!(
+            guess(&["Level"]),
+            ColumnMapping {
+                name: 0,
+                time: None
+            }
+        );
+    }
+
+    #[test]
+    fn unreadable_times_are_reported_by_line() {
+        let table =
+            parse_csv("Segment,Estimate\nForest,1:30\n,0:10\nCave,soon\nSummit,\n").unwrap();
+        let plan = plan_import(
+            &table,
+            ColumnMapping {
+                name: 0,
+                time: Some((1, TimeKind::SegmentTime)),
+            },
+        );
+        assert_eq!(
+            plan.segments,
+            [
+                ("Forest".to_owned(), Some(TimeSpan::from_seconds(90.0))),
+                ("Cave".to_owned(), None),
+                ("Summit".to_owned(), None),
+            ]
+        );
+        assert_eq!(plan.invalid, [(4, "soon".to_owned())]);
+    }
+
+    #[test]
+    fn segment_times_add_up_to_split_times() {
+        let mut run = Run::new();
+        run.set_game_name("Celeste");
+        run.push_segment(Segment::new("Old"));
+        let table = parse_csv("Segment,Estimate\nForest,1:30\nCave,2:00\n").unwrap();
+        let mapping = ColumnMapping::guess(&table.headers);
+        let run = apply_import(run, &plan_import(&table, mapping), TimingMethod::RealTime).unwrap();
+
+        assert_eq!(run.game_name(), "Celeste");
+        let splits: Vec<_> = run
+            .segments()
+            .iter()
+            .map(|segment| (segment.name(), segment.personal_best_split_time().real_time))
+            .collect();
+        assert_eq!(
+            splits,
+            [
+                ("Forest", Some(TimeSpan::from_seconds(90.0))),
+                ("Cave", Some(TimeSpan::from_seconds(210.0))),
+            ]
+        );
+        assert!(apply_import(Run::new(), &ImportPlan::default(), TimingMethod::RealTime).is_none());
+    }
+}
@@ -0,0 +1,50 @@
+//! Online services and the remote control. Requests go through the system `curl`,
+//! and every service is behind a feature, so builds without them carry no network
+//! code.
+
+#[cfg(feature = "remote-control")]
+pub mod remote;
+#[cfg(feature = "speedrun-com")]
+pub mod speedruncom;
+#[cfg(feature = "splitsio-upload")]
+pub mod splitsio;
+#[cfg(feature = "webhooks")]
+pub mod webhooks;
+
+/// Runs `curl` with `args`, feeding it `stdin`, and returns the response body.
+/// Blocks until the request is done, so it belongs on a background thread.
+#[cfg(any(
+    feature = "splitsio-upload",
+    feature = "speedrun-com",
+    feature = "webhooks"
+))]
+fn curl(args: &[&str], stdin: Option<&str>) -> Result<Vec<u8>, String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--max-time", "30"])
+        .args(args)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not run curl: {e}"))?;
+
+    if let Some(input) = stdin
+        && let Some(mut pipe) = child.stdin.take()
+    {
+        pipe.write_all(input.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_owned());
+    }
+    Ok(output.stdout)
+}
@@ -0,0 +1,568 @@
+//! Remote control over local HTTP, for a second person correcting splits while
+//! the runner plays (race commentary, co-op routing).
+//!
+//! `POST /undo`, `/skip`, `/pause`, `/resume` and `/set-comparison` with a
+//! `{"name": ...}` body. Every request carries the token of the session in the
+//! `X-TuxSplit-Token` header; the token is made anew on every start and shown in
+//! the preferences. Mutations go through the timer action queue, so they obey
+//! the same phase rules as the hotkeys: one that does not apply is answered with
+//! 409 Conflict.
+//!
+//! `GET /state`, with the same token, answers the state of the timer as JSON
+//! (`proto::StateV1`), e.g. for an OBS overlay.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use livesplit_core::SharedTimer;
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::error::TuxSplitError;
+use crate::proto::{StateFormat, StateV1};
+use crate::utils::timer_actions::{ActionOutcome, ActionQueue, TimerAction};
+
+/// Header holding the session token.
+pub const TOKEN_HEADER: &str = "x-tuxsplit-token";
+const ENDPOINTS: [&str; 5] = ["/undo", "/skip", "/pause", "/resume", "/set-comparison"];
+/// The read-only endpoint, answered with the state of the timer.
+pub const STATE_PATH: &str = "/state";
+/// Largest request body accepted, plenty for a comparison name.
+const MAX_BODY: usize = 4096;
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often the listener looks for a stop request between connections.
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    BadRequest,
+    Unauthorized,
+    NotFound,
+    MethodNotAllowed,
+    Conflict,
+    PayloadTooLarge,
+    InternalServerError,
+}
+
+impl Status {
+    pub const fn code(self) -> u16 {
+        match self {
+            Self::Ok => 200,
+            Self::BadRequest => 400,
+            Self::Unauthorized => 401,
+            Self::NotFound => 404,
+            Self::MethodNotAllowed => 405,
+            Self::Conflict => 409,
+            Self::PayloadTooLarge => 413,
+            Self::InternalServerError => 500,
+        }
+    }
+
+    pub const fn reason(self) -> &'static str {
+        match self {
+            Self::Ok => "OK",
+            Self::BadRequest => "Bad Request",
+            Self::Unauthorized => "Unauthorized",
+            Self::NotFound => "Not Found",
+            Self::MethodNotAllowed => "Method Not Allowed",
+            Self::Conflict => "Conflict",
+            Self::PayloadTooLarge => "Payload Too Large",
+            Self::InternalServerError => "Internal Server Error",
+        }
+    }
+}
+
+/// The parts of an HTTP request the endpoints look at.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub token: Option<String>,
+    pub body: String,
+}
+
+#[derive(Deserialize)]
+struct SetComparison {
+    name: String,
+}
+
+/// Reads one request, headers and body.
+pub fn read_request(reader: &mut impl BufRead) -> Result<Request, Status> {
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|_| Status::BadRequest)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(Status::BadRequest);
+    };
+    let mut request = Request {
+        method: method.to_owned(),
+        // The query is of no use to any endpoint
+        path: target.split('?').next().unwrap_or_default().to_owned(),
+        ..Request::default()
+    };
+
+    let mut length = 0;
+    loop {
+        line.clear();
+        if reader
+            .read_line(&mut line)
+            .map_err(|_| Status::BadRequest)?
+            == 0
+        {
+            return Err(Status::BadRequest);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = header.split_once(':').ok_or(Status::BadRequest)?;
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            length = value.parse().map_err(|_| Status::BadRequest)?;
+        } else if name.eq_ignore_ascii_case(TOKEN_HEADER) {
+            request.token = Some(value.to_owned());
+        }
+    }
+    if length > MAX_BODY {
+        return Err(Status::PayloadTooLarge);
+    }
+
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| Status::BadRequest)?;
+    request.body = String::from_utf8(body).map_err(|_| Status::BadRequest)?;
+    Ok(request)
+}
+
+fn authorize(request: &Request, token: &str) -> Result<(), Status> {
+    if request
+        .token
+        .as_deref()
+        .is_some_and(|given| tokens_match(given, token))
+    {
+        Ok(())
+    } else {
+        Err(Status::Unauthorized)
+    }
+}
+
+/// The action `request` asks for, once its token matches `token`.
+pub fn route(request: &Request, token: &str) -> Result<TimerAction, Status> {
+    authorize(request, token)?;
+    let path = request.path.as_str();
+    if !ENDPOINTS.contains(&path) {
+        return Err(Status::NotFound);
+    }
+    if request.method != "POST" {
+        return Err(Status::MethodNotAllowed);
+    }
+    Ok(match path {
+        "/undo" => TimerAction::Undo,
+        "/skip" => TimerAction::Skip,
+        "/pause" => TimerAction::Pause,
+        "/resume" => TimerAction::Resume,
+        _ => {
+            let body: SetComparison =
+                serde_json::from_str(&request.body).map_err(|_| Status::BadRequest)?;
+            TimerAction::SetComparison(body.name)
+        }
+    })
+}
+
+/// Answers `request`, waiting for the action it asks for to be applied. The
+/// action comes back with the status when it was.
+pub fn respond(
+    request: &Request,
+    token: &str,
+    queue: &ActionQueue,
+) -> (Status, Option<TimerAction>) {
+    let action = match route(request, token) {
+        Ok(action) => action,
+        Err(status) => return (status, None),
+    };
+    match queue.enqueue(action).recv() {
+        Ok(result) if result.outcome == ActionOutcome::Applied => (Status::Ok, Some(result.action)),
+        _ => (Status::Conflict, None),
+    }
+}
+
+/// Answers `GET /state` with the state of `timer` as JSON.
+pub fn respond_state(
+    request: &Request,
+    token: &str,
+    timer: &SharedTimer,
+    format: &StateFormat,
+) -> Result<String, Status> {
+    authorize(request, token)?;
+    if request.method != "GET" {
+        return Err(Status::MethodNotAllowed);
+    }
+    let timer = timer.read().unwrap_or_else(PoisonError::into_inner);
+    serde_json::to_string(&StateV1::from_timer(&timer, format))
+        .map_err(|_| Status::InternalServerError)
+}
+
+/// Short description of a remote action, for the toast telling the runner.
+pub fn describe(action: &TimerAction) -> String {
+    match action {
+        TimerAction::Undo => "Split undone remotely".to_owned(),
+        TimerAction::Skip => "Split skipped remotely".to_owned(),
+        TimerAction::Pause => "Timer paused remotely".to_owned(),
+        TimerAction::Resume => "Timer resumed remotely".to_owned(),
+        TimerAction::SetComparison(name) => format!("Comparison set to {name} remotely"),
+        other => format!("{other:?} applied remotely"),
+    }
+}
+
+/// A fresh random token, 256 bits from the kernel's generator as hex. Failing to
+/// read the generator keeps the remote control on `port` from starting.
+pub fn session_token(port: u16) -> Result<String, TuxSplitError> {
+    let mut bytes = [0u8; 32];
+    File::open("/dev/urandom")
+        .and_then(|mut urandom| urandom.read_exact(&mut bytes))
+        .map_err(|source| TuxSplitError::RemoteControl { port, source })?;
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Compares every byte, so the time taken says nothing about the token.
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// What the listener answers `GET /state` from. `format` is the one of the
+/// config when the server started.
+pub struct StateSource {
+    pub timer: SharedTimer,
+    pub format: StateFormat,
+}
+
+/// The listener, on localhost. Stops when dropped, freeing the port.
+pub struct RemoteServer {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    applied: Receiver<TimerAction>,
+}
+
+impl RemoteServer {
+    pub fn start(
+        port: u16,
+        token: String,
+        queue: ActionQueue,
+        state: StateSource,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let (sender, applied) = mpsc::channel();
+        let thread = {
+            let stop = stop.clone();
+            thread::Builder::new()
+                .name("remote-control".to_owned())
+                .spawn(move || serve(&listener, &token, &queue, &state, &sender, &stop))?
+        };
+        info!("Remote control listening on port {port}");
+        Ok(Self {
+            stop,
+            thread: Some(thread),
+            applied,
+        })
+    }
+
+    /// Actions applied on remote request since the last call.
+    pub fn applied(&self) -> impl Iterator<Item = TimerAction> + '_ {
+        self.applied.try_iter()
+    }
+}
+
+impl Drop for RemoteServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(
+    listener: &TcpListener,
+    token: &str,
+    queue: &ActionQueue,
+    state: &StateSource,
+    applied: &Sender<TimerAction>,
+    stop: &AtomicBool,
+) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = handle(stream, token, queue, state, applied) {
+                    debug!("Remote control connection failed: {e}");
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_INTERVAL),
+            Err(e) => {
+                warn!("Remote control stopped: {e}");
+                return;
+            }
+        }
+    }
+}
+
+fn handle(
+    stream: TcpStream,
+    token: &str,
+    queue: &ActionQueue,
+    state: &StateSource,
+    applied: &Sender<TimerAction>,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = match read_request(&mut reader) {
+        Ok(request) if request.path == STATE_PATH => {
+            match respond_state(&request, token, &state.timer, &state.format) {
+                Ok(json) => Response {
+                    status: Status::Ok,
+                    content_type: "application/json",
+                    body: json,
+                },
+                Err(status) => Response::plain(status),
+            }
+        }
+        Ok(request) => {
+            let (status, action) = respond(&request, token, queue);
+            if let Some(action) = action {
+                let _ = applied.send(action);
+            }
+            Response::plain(status)
+        }
+        Err(status) => Response::plain(status),
+    };
+    write_response(&stream, &response)
+}
+
+/// What a client is answered.
+struct Response {
+    status: Status,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    /// The status alone, its reason as the body.
+    fn plain(status: Status) -> Self {
+        Self {
+            status,
+            content_type: "text/plain",
+            body: status.reason().to_owned(),
+        }
+    }
+}
+
+fn write_response(mut stream: &TcpStream, response: &Response) -> io::Result<()> {
+    let Response {
+        status,
+        content_type,
+        body,
+    } = response;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        status.code(),
+        status.reason(),
+        body.len()
+    )
+}
+
+#[cfg(test)]
+mod remote_tests {
+    use super::*;
+    use livesplit_core::{Run, Segment, Timer, TimerPhase};
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    fn parse(raw: &str) -> Result<Request, Status> {
+        read_request(&mut raw.as_bytes())
+    }
+
+    fn post(path: &str, token: Option<&str>, body: &str) -> Request {
+        Request {
+            method: "POST".to_owned(),
+            path: path.to_owned(),
+            token: token.map(str::to_owned),
+            body: body.to_owned(),
+        }
+    }
+
+    fn running_timer() -> livesplit_core::SharedTimer {
+        let mut run = Run::new();
+        for name in ["S1", "S2", "S3"] {
+            run.push_segment(Segment::new(name));
+        }
+        let mut timer = Timer::new(run).unwrap();
+        timer.start();
+        timer.split();
+        timer.into_shared()
+    }
+
+    #[test]
+    fn requests_are_parsed_with_token_and_body() {
+        let request = parse(
+            "POST /set-comparison?x=1 HTTP/1.1\r\nHost: localhost\r\nX-TuxSplit-Token: abc\r\n\
+             Content-Length: 17\r\n\r\n{\"name\":\"Target\"}",
+        )
+        .unwrap();
+        assert_eq!(
+            request,
+            Request {
+                method: "POST".to_owned(),
+                path: "/set-comparison".to_owned(),
+                token: Some("abc".to_owned()),
+                body: "{\"name\":\"Target\"}".to_owned(),
+            }
+        );
+
+        assert_eq!(parse("garbage\r\n\r\n"), Err(Status::BadRequest));
+        assert_eq!(
+            parse("POST /undo HTTP/1.1\r\nno colon\r\n\r\n"),
+            Err(Status::BadRequest)
+        );
+        assert_eq!(
+            parse("POST /undo HTTP/1.1\r\nContent-Length: 100000\r\n\r\n"),
+            Err(Status::PayloadTooLarge)
+        );
+        // Cut off before the end of the body
+        assert_eq!(
+            parse("POST /undo HTTP/1.1\r\nContent-Length: 10\r\n\r\n{}"),
+            Err(Status::BadRequest)
+        );
+    }
+
+    #[test]
+    fn routes_need_the_token_and_post() {
+        assert_eq!(
+            route(&post("/undo", None, ""), TOKEN),
+            Err(Status::Unauthorized)
+        );
+        assert_eq!(
+            route(&post("/undo", Some("0123456789abcdee"), ""), TOKEN),
+            Err(Status::Unauthorized)
+        );
+        // Unknown paths are only told apart once authorized
+        assert_eq!(
+            route(&post("/reset", None, ""), TOKEN),
+            Err(Status::Unauthorized)
+        );
+        assert_eq!(
+            route(&post("/reset", Some(TOKEN), ""), TOKEN),
+            Err(Status::NotFound)
+        );
+
+        let mut get = post("/undo", Some(TOKEN), "");
+        get.method = "GET".to_owned();
+        assert_eq!(route(&get, TOKEN), Err(Status::MethodNotAllowed));
+
+        assert_eq!(
+            route(&post("/pause", Some(TOKEN), ""), TOKEN),
+            Ok(TimerAction::Pause)
+        );
+        assert_eq!(
+            route(
+                &post("/set-comparison", Some(TOKEN), "{\"name\":\"Target\"}"),
+                TOKEN
+            ),
+            Ok(TimerAction::SetComparison("Target".to_owned()))
+        );
+        assert_eq!(
+            route(&post("/set-comparison", Some(TOKEN), "Target"), TOKEN),
+            Err(Status::BadRequest)
+        );
+    }
+
+    #[test]
+    fn mutations_go_through_the_queue_and_its_phase_rules() {
+        let timer = running_timer();
+        let queue = ActionQueue::spawn(timer.clone());
+
+        assert_eq!(
+            respond(&post("/undo", Some(TOKEN), ""), TOKEN, &queue),
+            (Status::Ok, Some(TimerAction::Undo))
+        );
+        assert_eq!(timer.read().unwrap().current_split_index(), Some(0));
+
+        // Resuming a running timer does not apply
+        assert_eq!(
+            respond(&post("/resume", Some(TOKEN), ""), TOKEN, &queue),
+            (Status::Conflict, None)
+        );
+        assert_eq!(
+            respond(&post("/pause", Some(TOKEN), ""), TOKEN, &queue).0,
+            Status::Ok
+        );
+        assert_eq!(timer.read().unwrap().current_phase(), TimerPhase::Paused);
+
+        assert_eq!(
+            respond(
+                &post("/set-comparison", Some(TOKEN), "{\"name\":\"Nope\"}"),
+                TOKEN,
+                &queue
+            ),
+            (Status::Conflict, None)
+        );
+        // Refused requests never reach the timer
+        assert_eq!(
+            respond(&post("/skip", None, ""), TOKEN, &queue),
+            (Status::Unauthorized, None)
+        );
+        assert_eq!(timer.read().unwrap().current_split_index(), Some(0));
+    }
+
+    #[test]
+    fn the_state_is_read_with_the_token_and_get() {
+        let timer = running_timer();
+        let format = StateFormat::default();
+        let mut get = post(STATE_PATH, Some(TOKEN), "");
+        get.method = "GET".to_owned();
+
+        let json = respond_state(&get, TOKEN, &timer, &format).unwrap();
+        let state: StateV1 = serde_json::from_str(&json).unwrap();
+        assert_eq!(state.schema, crate::proto::SCHEMA_V1);
+        assert_eq!(state.current_split, Some(1));
+        assert_eq!(state.splits.len(), 3);
+
+        assert_eq!(
+            respond_state(&post(STATE_PATH, Some(TOKEN), ""), TOKEN, &timer, &format),
+            Err(Status::MethodNotAllowed)
+        );
+        get.token = None;
+        assert_eq!(
+            respond_state(&get, TOKEN, &timer, &format),
+            Err(Status::Unauthorized)
+        );
+    }
+
+    #[test]
+    fn session_tokens_differ() {
+        let token = session_token(0).unwrap();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, session_token(0).unwrap());
+        assert!(tokens_match(&token, &token.clone()));
+        assert!(!tokens_match(&token, &token[1..]));
+    }
+}
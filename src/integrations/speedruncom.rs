@@ -0,0 +1,289 @@
+//! Game and category lookups on speedrun.com, to start new runs with the right
+//! names.
+//!
+//! Games are searched with `GET /api/v1/games?name=...&embed=categories` (see
+//! https://github.com/speedruncomorg/api/blob/master/version1/games.md), which
+//! answers with the categories of each game embedded. Only built with the
+//! `speedrun-com` feature.
+
+use livesplit_core::{Run, Segment};
+use serde::Deserialize;
+
+use crate::integrations::curl;
+
+const GAMES_ENDPOINT: &str = "https://www.speedrun.com/api/v1/games";
+/// Games asked for per search.
+const MAX_RESULTS: usize = 10;
+
+/// Custom variables of the run holding the speedrun.com IDs of its game and category.
+pub const GAME_ID_VARIABLE: &str = "speedrun.com game ID";
+pub const CATEGORY_ID_VARIABLE: &str = "speedrun.com category ID";
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+struct Page<T> {
+    data: Vec<T>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub id: String,
+    pub names: GameNames,
+    pub abbreviation: String,
+    categories: Page<Category>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GameNames {
+    pub international: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Category {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: CategoryKind,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CategoryKind {
+    PerGame,
+    /// Individual level runs, which have no splits.
+    PerLevel,
+}
+
+impl Game {
+    pub fn name(&self) -> &str {
+        &self.names.international
+    }
+
+    /// The categories runs of the whole game are in.
+    pub fn full_game_categories(&self) -> impl Iterator<Item = &Category> {
+        self.categories
+            .data
+            .iter()
+            .filter(|category| category.kind == CategoryKind::PerGame)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LookupError {
+    /// The request did not go through.
+    Network(String),
+    /// speedrun.com answered with something unexpected.
+    Response(String),
+}
+
+impl std::fmt::Display for LookupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Network(e) => write!(f, "Could not reach speedrun.com: {e}"),
+            Self::Response(e) => write!(f, "Unexpected answer from speedrun.com: {e}"),
+        }
+    }
+}
+
+pub fn parse_games(body: &[u8]) -> Result<Vec<Game>, LookupError> {
+    serde_json::from_slice::<Page<Game>>(body)
+        .map(|page| page.data)
+        .map_err(|e| LookupError::Response(e.to_string()))
+}
+
+/// Games whose name matches `name`, with their categories. Blocks until
+/// speedrun.com answers, so it belongs on a background thread.
+pub fn search_games(name: &str) -> Result<Vec<Game>, LookupError> {
+    let name = format!("name={name}");
+    let max = format!("max={MAX_RESULTS}");
+    let body = curl(
+        &[
+            "--get",
+            "--user-agent",
+            concat!("tuxsplit/", env!("CARGO_PKG_VERSION")),
+            "--data-urlencode",
+            &name,
+            "--data",
+            "embed=categories",
+            "--data",
+            &max,
+            GAMES_ENDPOINT,
+        ],
+        None,
+    )
+    .map_err(LookupError::Network)?;
+    parse_games(&body)
+}
+
+/// A run of `category` of `game` with `segments` placeholder segments and no
+/// times, carrying the speedrun.com IDs of both.
+pub fn new_run(game: &Game, category: &Category, segments: usize) -> Run {
+    let mut run = manual_run(game.name(), &category.name, segments);
+    let metadata = run.metadata_mut();
+    metadata
+        .custom_variable_mut(GAME_ID_VARIABLE)
+        .permanent()
+        .set_value(game.id.as_str());
+    metadata
+        .custom_variable_mut(CATEGORY_ID_VARIABLE)
+        .permanent()
+        .set_value(category.id.as_str());
+    run
+}
+
+/// A run typed in by hand, when speedrun.com could not be asked.
+pub fn manual_run(game: &str, category: &str, segments: usize) -> Run {
+    let mut run = Run::new();
+    run.set_game_name(game.trim());
+    run.set_category_name(category.trim());
+    for index in 1..=segments.max(1) {
+        run.push_segment(Segment::new(format!("Split {index}")));
+    }
+    run
+}
+
+#[cfg(test)]
+mod speedruncom_tests {
+    use super::*;
+
+    /// Answer of `GET /api/v1/games?name=celeste&embed=categories`, trimmed to
+    /// two games and the fields around the ones read.
+    const GAMES: &str = r#"{
+        "data": [
+            {
+                "id": "o1y9j9v6",
+                "names": {
+                    "international": "Celeste",
+                    "japanese": null,
+                    "twitch": "Celeste"
+                },
+                "boostReceived": 0,
+                "boostDistinctDonors": 0,
+                "abbreviation": "celeste",
+                "weblink": "https://www.speedrun.com/celeste",
+                "released": 2018,
+                "release-date": "2018-01-25",
+                "ruleset": {
+                    "show-milliseconds": true,
+                    "require-verification": true,
+                    "require-video": true,
+                    "run-times": ["realtime"],
+                    "default-time": "realtime",
+                    "emulators-allowed": false
+                },
+                "romhack": false,
+                "gametypes": [],
+                "platforms": ["8gej2n93", "7m6ylw9p"],
+                "assets": {},
+                "categories": {
+                    "data": [
+                        {
+                            "id": "7kjpl1gk",
+                            "name": "Any%",
+                            "weblink": "https://www.speedrun.com/celeste#Any",
+                            "type": "per-game",
+                            "rules": "Reach the summit.",
+                            "players": { "type": "exactly", "value": 1 },
+                            "miscellaneous": false,
+                            "links": []
+                        },
+                        {
+                            "id": "xk9l3g4d",
+                            "name": "Clear",
+                            "weblink": "https://www.speedrun.com/celeste#Clear",
+                            "type": "per-level",
+                            "rules": null,
+                            "players": { "type": "exactly", "value": 1 },
+                            "miscellaneous": false,
+                            "links": []
+                        },
+                        {
+                            "id": "mkeyl926",
+                            "name": "True Ending",
+                            "weblink": "https://www.speedrun.com/celeste#True_Ending",
+                            "type": "per-game",
+                            "rules": null,
+                            "players": { "type": "exactly", "value": 1 },
+                            "miscellaneous": false,
+                            "links": []
+                        }
+                    ]
+                },
+                "links": []
+            },
+            {
+                "id": "j1nejgx1",
+                "names": {
+                    "international": "Celeste Classic",
+                    "japanese": null,
+                    "twitch": "Celeste Classic"
+                },
+                "abbreviation": "celeste_classic",
+                "categories": { "data": [] },
+                "links": []
+            }
+        ],
+        "pagination": { "offset": 0, "max": 10, "size": 2, "links": [] }
+    }"#;
+
+    #[test]
+    fn games_come_with_their_full_game_categories() {
+        let games = parse_games(GAMES.as_bytes()).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].name(), "Celeste");
+        assert_eq!(games[0].abbreviation, "celeste");
+
+        let categories: Vec<&str> = games[0]
+            .full_game_categories()
+            .map(|category| category.name.as_str())
+            .collect();
+        assert_eq!(categories, ["Any%", "True Ending"]);
+        assert_eq!(games[1].full_game_categories().count(), 0);
+    }
+
+    #[test]
+    fn unexpected_answers_are_response_errors() {
+        assert!(matches!(
+            parse_games(br#"{"status": 400, "message": "Bad request"}"#),
+            Err(LookupError::Response(_))
+        ));
+        assert!(matches!(
+            parse_games(b"<html>"),
+            Err(LookupError::Response(_))
+        ));
+        assert_eq!(parse_games(br#"{"data": []}"#), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn new_runs_carry_the_names_ids_and_placeholders() {
+        let games = parse_games(GAMES.as_bytes()).unwrap();
+        let category = games[0].full_game_categories().nth(1).unwrap();
+        let run = new_run(&games[0], category, 3);
+
+        assert_eq!(run.game_name(), "Celeste");
+        assert_eq!(run.category_name(), "True Ending");
+        let names: Vec<&str> = run.segments().iter().map(Segment::name).collect();
+        assert_eq!(names, ["Split 1", "Split 2", "Split 3"]);
+        assert_eq!(
+            run.metadata().custom_variable_value(GAME_ID_VARIABLE),
+            Some("o1y9j9v6")
+        );
+        assert_eq!(
+            run.metadata().custom_variable_value(CATEGORY_ID_VARIABLE),
+            Some("mkeyl926")
+        );
+        assert!(
+            run.metadata()
+                .custom_variable(GAME_ID_VARIABLE)
+                .unwrap()
+                .is_permanent
+        );
+    }
+
+    #[test]
+    fn manual_runs_have_at_least_one_segment() {
+        let run = manual_run(" Celeste ", "Any%", 0);
+        assert_eq!(run.game_name(), "Celeste");
+        assert_eq!(run.len(), 1);
+        assert!(run.metadata().custom_variable(GAME_ID_VARIABLE).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+//! Webhooks: golds, finishes and personal bests posted as JSON to the URLs of
+//! the `webhooks` section, e.g. for home automation or a chat bot.
+//!
+//! The body is a `proto::EventV1`. Requests are short, retried once and sent
+//! from a worker thread; a webhook that fails is logged and never affects the
+//! timer.
+
+use std::collections::HashSet;
+
+use crate::integrations::curl;
+use crate::proto::EventKind;
+use crate::utils::hooks::{HookEvent, HookKind};
+
+/// Seconds a request may take, connecting included.
+const TIMEOUT_SECS: &str = "5";
+/// Tries per event, the first one included.
+const TRIES: usize = 2;
+
+/// The webhook events among the hook events of one timer update, with the
+/// segment they are about. A finish beating the personal best is also a PB;
+/// `is_pb` is only asked for finishes.
+pub fn webhook_events(
+    events: &[HookEvent],
+    is_pb: impl FnOnce() -> bool,
+) -> Vec<(EventKind, usize)> {
+    let mut webhooks = Vec::new();
+    let mut is_pb = Some(is_pb);
+    for event in events {
+        let Some(index) = event.index else { continue };
+        match event.kind {
+            HookKind::Gold => webhooks.push((EventKind::Gold, index)),
+            HookKind::Finish => {
+                webhooks.push((EventKind::Finish, index));
+                if is_pb.take().is_some_and(|is_pb| is_pb()) {
+                    webhooks.push((EventKind::Pb, index));
+                }
+            }
+            _ => {}
+        }
+    }
+    webhooks
+}
+
+/// Posts `body` to `url`, trying twice. Blocks until done, so it belongs on a
+/// background thread.
+pub fn post(url: &str, body: &str) -> Result<(), String> {
+    let args = [
+        "--max-time",
+        TIMEOUT_SECS,
+        "--request",
+        "POST",
+        "--header",
+        "Content-Type: application/json",
+        "--data-binary",
+        "@-",
+        url,
+    ];
+    let mut error = String::new();
+    for _ in 0..TRIES {
+        match curl(&args, Some(body)) {
+            Ok(_) => return Ok(()),
+            Err(e) => error = e,
+        }
+    }
+    Err(error)
+}
+
+/// Endpoints whose failures were already shown, so each is shown once a session.
+#[derive(Debug, Default)]
+pub struct FailureReports {
+    reported: HashSet<String>,
+}
+
+impl FailureReports {
+    /// Whether a failure of `url` should be shown, counting it if so.
+    pub fn first(&mut self, url: &str) -> bool {
+        self.reported.insert(url.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod webhooks_tests {
+    use super::*;
+
+    #[test]
+    fn golds_and_finishes_are_posted_and_pbs_told_apart() {
+        let event = |kind, index| HookEvent { kind, index };
+        let events = [
+            event(HookKind::Split, Some(2)),
+            event(HookKind::Gold, Some(2)),
+            event(HookKind::Finish, Some(2)),
+        ];
+        assert_eq!(
+            webhook_events(&events, || true),
+            [
+                (EventKind::Gold, 2),
+                (EventKind::Finish, 2),
+                (EventKind::Pb, 2)
+            ]
+        );
+        assert_eq!(
+            webhook_events(&events[..2], || panic!("only asked on finishes")),
+            [(EventKind::Gold, 2)]
+        );
+        assert!(webhook_events(&[event(HookKind::Reset, None)], || true).is_empty());
+    }
+
+    #[test]
+    fn each_endpoint_fails_loudly_once() {
+        let mut reports = FailureReports::default();
+        assert!(reports.first("http://lights.local/gold"));
+        assert!(!reports.first("http://lights.local/gold"));
+        assert!(reports.first("http://bot.local/pb"));
+    }
+}
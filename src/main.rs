@@ -1,14 +1,44 @@
+mod archive;
+mod categories;
 mod config;
 mod context;
+mod doctor;
+mod editor_draft;
+mod error;
 mod formatters;
+mod import;
+#[cfg(any(
+    feature = "splitsio-upload",
+    feature = "speedrun-com",
+    feature = "remote-control",
+    feature = "webhooks"
+))]
+mod integrations;
+mod paths;
+mod practice;
+#[cfg(any(feature = "remote-control", feature = "webhooks"))]
+mod proto;
+mod safe_mode;
+mod sidecar;
+mod startup;
+mod storage;
+#[cfg(test)]
+mod test_support;
 mod ui;
 mod utils;
+mod version;
 
 use std::path::Path;
+use std::sync::Arc;
 
 use tracing::info;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
 
 use crate::context::{build_ui, shutdown};
+use crate::safe_mode::SafeMode;
+use crate::utils::frame_stats;
+use crate::utils::log_buffer::{RingBufferLayer, log_buffer};
 use adw::Application;
 use adw::prelude::*;
 use gtk4::{
@@ -19,17 +49,59 @@ use gtk4::{
 
 const RESOURCE_ICONS: &str = "/com/tunixr/tuxsplit/icons";
 const RESOURCE_CSS: &str = "/com/tunixr/tuxsplit/css/tuxsplit.css";
+// Linux signal numbers, for the handlers saving state before the app is killed
+const SIGINT: i32 = 2;
+const SIGTERM: i32 = 15;
+
+const GRESOURCE_PATHS: [&str; 2] = [
+    "/app/share/tuxsplit.gresource",
+    "/usr/share/tuxsplit/tuxsplit.gresource",
+];
 
 fn main() {
+    let mut args: Vec<String> = std::env::args().collect();
+    // GTK rejects options it does not know
+    let portable = args.iter().skip(1).any(|arg| arg == "--portable");
+    args.retain(|arg| arg != "--portable");
+    // Before anything (GTK included) reads the XDG directories
+    for (key, dir) in paths::init(portable).xdg_redirects() {
+        unsafe {
+            std::env::set_var(key, dir);
+        }
+    }
+
+    if args.iter().skip(1).any(|arg| arg == "--doctor") {
+        let report = doctor::Report::collect();
+        print!("{}", report.render());
+        std::process::exit(report.exit_code());
+    }
+
     unsafe {
         std::env::set_var("GDK_BACKEND", "x11"); // Livesplit-core does not support Wayland global shortcut portal yet
     }
 
-    // Set tracing to stdout
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::DEBUG)
+    // Set tracing to stdout, and to the buffer of the debug log window
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(LevelFilter::DEBUG))
+        .with(
+            RingBufferLayer::new(Arc::clone(log_buffer()), tracing::Level::DEBUG)
+                .with_filter(LevelFilter::DEBUG),
+        )
         .init();
 
+    let debug = args.iter().skip(1).any(|arg| arg == "--debug");
+    args.retain(|arg| arg != "--debug");
+    if args.iter().skip(1).any(|arg| arg == frame_stats::FLAG) {
+        frame_stats::set_enabled(true);
+    }
+    args.retain(|arg| arg != frame_stats::FLAG);
+    let safe = args.iter().skip(1).any(|arg| arg == safe_mode::FLAG);
+    args.retain(|arg| arg != safe_mode::FLAG);
+    if safe_mode::init(safe).enabled() {
+        info!("Starting in safe mode");
+    }
+    safe_mode::install_crash_marker(paths::current().root.clone());
+
     register_gresource();
     info!("Starting TuxSplit");
     adw::init().expect("Failed to initialize libadwaita");
@@ -40,8 +112,11 @@ fn main() {
 
     {
         app.connect_activate(move |app| {
-            load_styles();
+            load_styles(safe_mode::current());
             build_ui(app);
+            if debug {
+                ui::debug_log::present(app);
+            }
         });
     }
     {
@@ -49,10 +124,35 @@ fn main() {
             shutdown();
         });
     }
-    app.run();
+    // Logout and system shutdown SIGTERM the app, which skips the shutdown signal
+    for signal in [SIGTERM, SIGINT] {
+        let app = app.clone();
+        glib::unix_signal_add_local(signal, move || {
+            shutdown();
+            app.quit();
+            glib::ControlFlow::Break
+        });
+    }
+    app.run_with_args(&args);
+    if safe_mode::restart_requested() {
+        restart_in_safe_mode();
+    }
+}
+
+/// Replaces this process with TuxSplit in safe mode, with the same arguments.
+fn restart_in_safe_mode() {
+    use std::os::unix::process::CommandExt;
+
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    args.push(safe_mode::FLAG.to_owned());
+    let error = match std::env::current_exe() {
+        Ok(exe) => std::process::Command::new(exe).args(args).exec(),
+        Err(e) => e,
+    };
+    tracing::warn!("Could not restart in safe mode: {error}");
 }
 
-fn load_styles() {
+fn load_styles(safe_mode: SafeMode) {
     let display = Display::default().expect("Could not connect to a display");
     let css_provider = CssProvider::new();
     css_provider.load_from_resource(RESOURCE_CSS);
@@ -65,22 +165,20 @@ fn load_styles() {
         &css_provider,
         gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
     );
+
+    ui::style::follow_color_scheme();
+    let ctx = context::TuxSplitContext::get_instance();
+    ui::style::apply_config(&ctx.config(), safe_mode);
 }
 
 fn register_gresource() {
-    let path = Path::new("/app/share/tuxsplit.gresource");
-    if path.exists() {
-        let res = gio::Resource::load(path).expect("Failed to load resource");
-        info!("Registered GResource from {}", path.display());
-        gio::resources_register(&res);
-        return;
-    }
-    let usr_path = Path::new("/usr/share/tuxsplit/tuxsplit.gresource");
-    if usr_path.exists() {
-        let res = gio::Resource::load(usr_path).expect("Failed to load resource");
-        info!("Registered GResource from {}", usr_path.display());
-        gio::resources_register(&res);
-        return;
+    for path in GRESOURCE_PATHS.iter().map(Path::new) {
+        if path.exists() {
+            let res = gio::Resource::load(path).expect("Failed to load resource");
+            info!("Registered GResource from {}", path.display());
+            gio::resources_register(&res);
+            return;
+        }
     }
     panic!("Could not load resources");
 }
@@ -0,0 +1,269 @@
+//! Where TuxSplit keeps its state: the config, the recovery file and new splits
+//! created without a splits folder all live under one root directory.
+//!
+//! The root is, by precedence:
+//! 1. `<exe_dir>/data` in portable mode, enabled by `--portable` or by a
+//!    `portable.marker` file next to the executable;
+//! 2. `$TUXSPLIT_DATADIR`;
+//! 3. `$XDG_CONFIG_HOME/tuxsplit`;
+//! 4. `$HOME/.config/tuxsplit`;
+//! 5. `/tmp`.
+//!
+//! In portable mode the XDG directories of the process are redirected under the
+//! root as well, so GTK (recent files, caches) does not write to the home
+//! directory either.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tracing::warn;
+
+/// File next to the executable turning portable mode on.
+pub const PORTABLE_MARKER: &str = "portable.marker";
+pub const CONFIG_FILE: &str = "config.yaml";
+/// Copy of the run (with the live attempt) written on shutdown when it has
+/// unsaved changes.
+pub const RECOVERY_FILE: &str = "recovery.lss";
+/// Edits of the split editor closed without saving or cancelling them (see
+/// `editor_draft`), and what they were made on.
+pub const EDITOR_DRAFT_FILE: &str = "editor-draft.lss";
+pub const EDITOR_DRAFT_META_FILE: &str = "editor-draft.yaml";
+/// Dated copies of splits files made with "Archive Run..." (see `archive`),
+/// listed in the index file inside it.
+pub const ARCHIVES_DIR: &str = "archives";
+pub const ARCHIVE_INDEX_FILE: &str = "index.yaml";
+/// Segment times of practice sessions, one file per game and category (see
+/// `practice`), kept out of the splits.
+pub const PRACTICE_DIR: &str = "practice";
+/// PID and splits file of the running instance, so a second one can tell (see
+/// `storage::InstanceLock`).
+pub const INSTANCE_LOCK_FILE: &str = "instance.lock";
+/// Message of the panic that ended the last session, removed on a clean
+/// shutdown (see `safe_mode`).
+pub const CRASH_MARKER_FILE: &str = "crash.marker";
+
+/// What decided the root directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Portable,
+    DataDirOverride,
+    XdgConfigHome,
+    Home,
+    Temporary,
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Portable => "portable",
+            Self::DataDirOverride => "TUXSPLIT_DATADIR",
+            Self::XdgConfigHome => "XDG_CONFIG_HOME",
+            Self::Home => "home directory",
+            Self::Temporary => "temporary (no HOME)",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    pub mode: Mode,
+    pub root: PathBuf,
+}
+
+impl Paths {
+    /// Resolves the root from the directory of the executable, the `--portable`
+    /// flag and the environment read through `env`. Empty variables count as
+    /// unset.
+    pub fn resolve(
+        exe_dir: Option<&Path>,
+        portable_flag: bool,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let env = |key: &str| env(key).filter(|value| !value.is_empty());
+        if let Some(exe_dir) = exe_dir
+            && (portable_flag || exe_dir.join(PORTABLE_MARKER).is_file())
+        {
+            return Self::new(Mode::Portable, exe_dir.join("data"));
+        }
+        if let Some(dir) = env("TUXSPLIT_DATADIR") {
+            Self::new(Mode::DataDirOverride, PathBuf::from(dir))
+        } else if let Some(dir) = env("XDG_CONFIG_HOME") {
+            Self::new(Mode::XdgConfigHome, PathBuf::from(dir).join("tuxsplit"))
+        } else if let Some(home) = env("HOME") {
+            Self::new(
+                Mode::Home,
+                PathBuf::from(home).join(".config").join("tuxsplit"),
+            )
+        } else {
+            Self::new(Mode::Temporary, PathBuf::from("/tmp"))
+        }
+    }
+
+    fn new(mode: Mode, root: PathBuf) -> Self {
+        Self { mode, root }
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.root.join(CONFIG_FILE)
+    }
+
+    /// The XDG variables to point under the root before GTK starts, so nothing
+    /// else lands in the home directory. Empty outside of portable mode.
+    pub fn xdg_redirects(&self) -> Vec<(&'static str, PathBuf)> {
+        if self.mode != Mode::Portable {
+            return Vec::new();
+        }
+        let xdg = self.root.join("xdg");
+        vec![
+            ("XDG_CONFIG_HOME", xdg.join("config")),
+            ("XDG_DATA_HOME", xdg.join("data")),
+            ("XDG_CACHE_HOME", xdg.join("cache")),
+            ("XDG_STATE_HOME", xdg.join("state")),
+        ]
+    }
+
+    /// Creates the root when missing. Saving into it reports the problem if it
+    /// cannot be created.
+    fn ensure_root(&self) {
+        if !self.root.is_dir()
+            && let Err(e) = std::fs::create_dir_all(&self.root)
+        {
+            warn!("Could not create {}: {e}", self.root.display());
+        }
+    }
+}
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+/// Resolves the paths of this process from its executable and environment.
+/// Called once from `main`, before anything reads or redirects the XDG
+/// variables; later calls keep the first result.
+pub fn init(portable_flag: bool) -> &'static Paths {
+    PATHS.get_or_init(|| {
+        let exe = std::env::current_exe().ok();
+        let exe_dir = exe.as_deref().and_then(Path::parent);
+        let paths = Paths::resolve(exe_dir, portable_flag, |key| std::env::var(key).ok());
+        paths.ensure_root();
+        paths
+    })
+}
+
+/// The paths of this process, resolved without `--portable` if `init` was not
+/// called.
+pub fn current() -> &'static Paths {
+    init(false)
+}
+
+#[cfg(test)]
+mod paths_tests {
+    use super::*;
+    use std::fs;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        |key| {
+            vars.iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| (*value).to_owned())
+        }
+    }
+
+    fn exe_dir(name: &str, marker: bool) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("tuxsplit-paths-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        if marker {
+            fs::write(dir.join(PORTABLE_MARKER), "").unwrap();
+        }
+        dir
+    }
+
+    const ALL: [(&str, &str); 3] = [
+        ("TUXSPLIT_DATADIR", "/srv/tuxsplit"),
+        ("XDG_CONFIG_HOME", "/home/runner/.xdg"),
+        ("HOME", "/home/runner"),
+    ];
+
+    #[test]
+    fn the_portable_marker_wins_over_the_environment() {
+        let dir = exe_dir("marker", true);
+        let paths = Paths::resolve(Some(&dir), false, env(&ALL));
+        assert_eq!(paths.mode, Mode::Portable);
+        assert_eq!(paths.root, dir.join("data"));
+        assert_eq!(paths.config_file(), dir.join("data").join("config.yaml"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn the_flag_enables_portable_mode_without_a_marker() {
+        let dir = exe_dir("flag", false);
+        assert_eq!(
+            Paths::resolve(Some(&dir), false, env(&ALL)).mode,
+            Mode::DataDirOverride
+        );
+        let paths = Paths::resolve(Some(&dir), true, env(&ALL));
+        assert_eq!(paths.mode, Mode::Portable);
+        assert_eq!(paths.root, dir.join("data"));
+        fs::remove_dir_all(dir).unwrap();
+
+        // Without a known executable, the environment decides
+        assert_eq!(
+            Paths::resolve(None, true, env(&ALL)).mode,
+            Mode::DataDirOverride
+        );
+    }
+
+    #[test]
+    fn environment_overrides_come_in_order() {
+        let resolve = |vars: &[(&str, &str)]| Paths::resolve(None, false, env(vars));
+
+        let paths = resolve(&ALL);
+        assert_eq!(
+            (paths.mode, paths.root),
+            (Mode::DataDirOverride, PathBuf::from("/srv/tuxsplit"))
+        );
+        let paths = resolve(&ALL[1..]);
+        assert_eq!(
+            (paths.mode, paths.root),
+            (
+                Mode::XdgConfigHome,
+                PathBuf::from("/home/runner/.xdg/tuxsplit")
+            )
+        );
+        let paths = resolve(&ALL[2..]);
+        assert_eq!(
+            (paths.mode, paths.root),
+            (Mode::Home, PathBuf::from("/home/runner/.config/tuxsplit"))
+        );
+        let paths = resolve(&[]);
+        assert_eq!(
+            (paths.mode, paths.root),
+            (Mode::Temporary, PathBuf::from("/tmp"))
+        );
+
+        // Empty variables are skipped
+        let paths = resolve(&[("TUXSPLIT_DATADIR", ""), ("XDG_CONFIG_HOME", ""), ALL[2]]);
+        assert_eq!(paths.mode, Mode::Home);
+    }
+
+    #[test]
+    fn only_portable_mode_redirects_xdg() {
+        let dir = exe_dir("redirects", true);
+        let portable = Paths::resolve(Some(&dir), false, env(&[]));
+        let redirects = portable.xdg_redirects();
+        assert_eq!(redirects.len(), 4);
+        assert!(
+            redirects
+                .iter()
+                .all(|(_, path)| path.starts_with(&portable.root))
+        );
+        assert!(redirects.iter().any(|(key, _)| *key == "XDG_DATA_HOME"));
+        fs::remove_dir_all(dir).unwrap();
+
+        assert!(
+            Paths::resolve(None, false, env(&ALL))
+                .xdg_redirects()
+                .is_empty()
+        );
+    }
+}
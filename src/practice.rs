@@ -0,0 +1,199 @@
+//! Segment times of practice sessions, kept out of the run's history.
+//!
+//! Each game and category has a JSON lines file in `practice/` under the data
+//! directory, one completed segment per line. The statistics shown in the
+//! editor and the practice dialog are computed from it.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::archive::sanitize_component;
+use crate::paths::PRACTICE_DIR;
+use crate::storage::write_atomic;
+
+/// Attempts averaged in `PracticeStats::recent_average_ms`.
+pub const RECENT_ATTEMPTS: usize = 10;
+
+/// One practiced segment.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct PracticeRecord {
+    pub segment: String,
+    pub time_ms: i64,
+    /// Local date and time it was completed.
+    pub date: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PracticeStats {
+    pub count: usize,
+    pub best_ms: i64,
+    pub median_ms: i64,
+    /// Average of the last `RECENT_ATTEMPTS`, or of all of them when fewer.
+    pub recent_average_ms: i64,
+}
+
+/// Statistics of `segment` over `records`, in the order they were recorded.
+/// `None` when it was never practiced.
+pub fn segment_stats(records: &[PracticeRecord], segment: &str) -> Option<PracticeStats> {
+    let times: Vec<i64> = records
+        .iter()
+        .filter(|record| record.segment == segment)
+        .map(|record| record.time_ms)
+        .collect();
+    let best_ms = *times.iter().min()?;
+
+    let mut sorted = times.clone();
+    sorted.sort_unstable();
+    let middle = sorted.len() / 2;
+    let median_ms = if sorted.len().is_multiple_of(2) {
+        i64::midpoint(sorted[middle - 1], sorted[middle])
+    } else {
+        sorted[middle]
+    };
+
+    let recent = &times[times.len().saturating_sub(RECENT_ATTEMPTS)..];
+    let recent_average_ms = recent.iter().sum::<i64>() / recent.len() as i64;
+
+    Some(PracticeStats {
+        count: times.len(),
+        best_ms,
+        median_ms,
+        recent_average_ms,
+    })
+}
+
+/// Practice file of the run `game` / `category` under the data directory `root`.
+pub fn practice_file(root: &Path, game: &str, category: &str) -> PathBuf {
+    root.join(PRACTICE_DIR).join(format!(
+        "{}-{}.jsonl",
+        sanitize_component(game),
+        sanitize_component(category)
+    ))
+}
+
+/// The records of `path`, skipping lines that cannot be read.
+pub fn load(path: &Path) -> Vec<PracticeRecord> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                warn!("Skipping a practice record of {}: {e}", path.display());
+                None
+            }
+        })
+        .collect()
+}
+
+/// Adds `record` at the end of `path`.
+pub fn append(path: &Path, record: &PracticeRecord) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut line = serde_json::to_string(record).map_err(io::Error::other)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Removes the records of `segment` from `path`, returning how many there were.
+pub fn clear_segment(path: &Path, segment: &str) -> io::Result<usize> {
+    let records = load(path);
+    let (cleared, kept): (Vec<_>, Vec<_>) = records
+        .into_iter()
+        .partition(|record| record.segment == segment);
+    if cleared.is_empty() {
+        return Ok(0);
+    }
+    let mut contents = String::new();
+    for record in &kept {
+        contents.push_str(&serde_json::to_string(record).map_err(io::Error::other)?);
+        contents.push('\n');
+    }
+    write_atomic(path, contents)?;
+    Ok(cleared.len())
+}
+
+#[cfg(test)]
+mod practice_tests {
+    use super::*;
+
+    fn records(segment: &str, times: &[i64]) -> Vec<PracticeRecord> {
+        times
+            .iter()
+            .map(|&time_ms| PracticeRecord {
+                segment: segment.to_owned(),
+                time_ms,
+                date: "2026-03-14 20:00:00".to_owned(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn segments_never_practiced_have_no_stats() {
+        assert_eq!(segment_stats(&[], "Forest"), None);
+        assert_eq!(segment_stats(&records("Cave", &[1_000]), "Forest"), None);
+    }
+
+    #[test]
+    fn a_single_attempt_is_its_own_best_median_and_average() {
+        assert_eq!(
+            segment_stats(&records("Forest", &[61_500]), "Forest"),
+            Some(PracticeStats {
+                count: 1,
+                best_ms: 61_500,
+                median_ms: 61_500,
+                recent_average_ms: 61_500,
+            })
+        );
+    }
+
+    #[test]
+    fn the_recent_average_only_covers_the_last_ten() {
+        // Twelve attempts getting faster, with another segment in between
+        let mut all = records("Forest", &[90_000, 80_000]);
+        all.extend(records("Cave", &[5_000]));
+        all.extend(records("Forest", &[60_000; 10]));
+        let stats = segment_stats(&all, "Forest").unwrap();
+        assert_eq!(stats.count, 12);
+        assert_eq!(stats.best_ms, 60_000);
+        assert_eq!(stats.median_ms, 60_000);
+        assert_eq!(stats.recent_average_ms, 60_000);
+
+        let even = segment_stats(&records("Forest", &[1_000, 4_000, 2_000, 3_000]), "Forest");
+        assert_eq!(even.map(|stats| stats.median_ms), Some(2_500));
+        assert_eq!(even.map(|stats| stats.recent_average_ms), Some(2_500));
+    }
+
+    #[test]
+    fn records_are_appended_and_cleared_per_segment() {
+        let root = std::env::temp_dir().join(format!("tuxsplit-practice-{}", std::process::id()));
+        let path = practice_file(&root, "Celeste", "Any%");
+        assert!(path.ends_with("practice/Celeste-Any%.jsonl"));
+
+        for record in records("Forest", &[60_000, 61_000])
+            .iter()
+            .chain(&records("Cave", &[30_000]))
+        {
+            append(&path, record).unwrap();
+        }
+        assert_eq!(load(&path).len(), 3);
+
+        assert_eq!(clear_segment(&path, "Forest").unwrap(), 2);
+        assert_eq!(load(&path), records("Cave", &[30_000]));
+        assert_eq!(clear_segment(&path, "Forest").unwrap(), 0);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,481 @@
+//! The state of the timer as published to other programs (OBS overlays,
+//! scripts), e.g. by `GET /state` of the remote control.
+//!
+//! The structs are versioned: `StateV1` only ever gains fields, so consumers
+//! written against an older build keep working. Anything else (renaming,
+//! removing or retyping a field) needs a `StateV2` with `schema: 2`. The golden
+//! fixture in `tests/fixtures/proto/state-v1.json` guards this.
+//!
+//! Times are given both as milliseconds and as text formatted like the splits of
+//! the timer, so overlays do not need to reimplement the formatting.
+//!
+//! `EventV1`, what webhooks are posted, follows the same rules, with its
+//! fixture in `tests/fixtures/proto/event-v1.json`.
+
+#[cfg(feature = "remote-control")]
+use livesplit_core::TimerPhase;
+use livesplit_core::{TimeSpan, Timer, TimingMethod};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::formatters::TimeFormat;
+use crate::utils::comparisons::format_delta;
+
+/// Version of the state described by `StateV1`.
+pub const SCHEMA_V1: u32 = 1;
+
+/// How times are formatted in the state. Taken from the config, and sent along
+/// to threads that cannot read it.
+#[derive(Debug, Clone, Default)]
+pub struct StateFormat {
+    pub split: TimeFormat,
+    pub delta_digits: u8,
+    pub delta_drop_zero_tenths: bool,
+}
+
+impl StateFormat {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            split: config.format.split.clone(),
+            delta_digits: config.display.delta_digits,
+            delta_drop_zero_tenths: config.display.delta_drop_zero_tenths,
+        }
+    }
+
+    fn time(&self, span: TimeSpan) -> TimeValue {
+        TimeValue {
+            ms: milliseconds(span),
+            text: self.split.format_time_span(&span),
+        }
+    }
+
+    /// `time` minus `compared`. The milliseconds are the difference of the
+    /// milliseconds of both, so they add up for overlays.
+    fn delta(&self, time: TimeSpan, compared: TimeSpan) -> TimeValue {
+        TimeValue {
+            ms: milliseconds(time) - milliseconds(compared),
+            text: format_delta(
+                (time - compared).to_duration(),
+                &self.split,
+                self.delta_digits,
+                self.delta_drop_zero_tenths,
+            ),
+        }
+    }
+}
+
+fn milliseconds(span: TimeSpan) -> i64 {
+    span.to_duration().whole_milliseconds() as i64
+}
+
+/// A time, or a signed difference between two times.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeValue {
+    /// Milliseconds, negative for deltas ahead of the comparison.
+    pub ms: i64,
+    /// Formatted like the timer shows it, with a sign for deltas.
+    pub text: String,
+}
+
+#[cfg(feature = "remote-control")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Phase {
+    NotRunning,
+    Running,
+    Paused,
+    Ended,
+}
+
+#[cfg(feature = "remote-control")]
+impl From<TimerPhase> for Phase {
+    fn from(phase: TimerPhase) -> Self {
+        match phase {
+            TimerPhase::NotRunning => Self::NotRunning,
+            TimerPhase::Running => Self::Running,
+            TimerPhase::Paused => Self::Paused,
+            TimerPhase::Ended => Self::Ended,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Method {
+    RealTime,
+    GameTime,
+}
+
+impl From<TimingMethod> for Method {
+    fn from(method: TimingMethod) -> Self {
+        match method {
+            TimingMethod::RealTime => Self::RealTime,
+            TimingMethod::GameTime => Self::GameTime,
+        }
+    }
+}
+
+#[cfg(feature = "remote-control")]
+/// The whole state, version 1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct StateV1 {
+    /// Always `SCHEMA_V1`.
+    pub schema: u32,
+    pub phase: Phase,
+    /// Timing method every time of the state is in.
+    pub timing_method: Method,
+    pub game: String,
+    pub category: String,
+    /// Name of the comparison the deltas are against.
+    pub comparison: String,
+    /// Index of the segment being run, absent when not running. Equal to the
+    /// number of segments once the run ended.
+    pub current_split: Option<usize>,
+    pub times: TimesV1,
+    pub splits: Vec<SplitV1>,
+}
+
+#[cfg(feature = "remote-control")]
+/// Times of the attempt as a whole. Absent when there is no such time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TimesV1 {
+    /// Time of the attempt so far, the final time once it ended.
+    pub current: Option<TimeValue>,
+    /// Final time of the personal best.
+    pub personal_best: Option<TimeValue>,
+    /// Final time of the comparison.
+    pub comparison: Option<TimeValue>,
+}
+
+#[cfg(feature = "remote-control")]
+/// One segment of the run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SplitV1 {
+    pub name: String,
+    /// Split time of the attempt, absent until split (and for skips).
+    pub time: Option<TimeValue>,
+    /// Split time of the comparison.
+    pub comparison: Option<TimeValue>,
+    /// `time` minus `comparison`, when both are there.
+    pub delta: Option<TimeValue>,
+    /// Best time ever achieved on the segment alone.
+    pub best_segment: Option<TimeValue>,
+    /// Passed without a split time in this attempt.
+    pub skipped: bool,
+    /// The segment being run.
+    pub current: bool,
+}
+
+#[cfg(feature = "remote-control")]
+impl StateV1 {
+    pub fn from_timer(timer: &Timer, format: &StateFormat) -> Self {
+        let method = timer.current_timing_method();
+        let comparison = timer.current_comparison();
+        let phase = timer.current_phase();
+        let current_split = timer.current_split_index();
+        let run = timer.run();
+
+        let splits = (0..)
+            .zip(run.segments())
+            .map(|(index, segment)| {
+                let time = segment.split_time()[method];
+                let compared = segment.comparison(comparison)[method];
+                let passed = current_split.is_some_and(|current| index < current);
+                SplitV1 {
+                    name: segment.name().to_owned(),
+                    time: time.map(|time| format.time(time)),
+                    comparison: compared.map(|time| format.time(time)),
+                    delta: time
+                        .zip(compared)
+                        .map(|(time, compared)| format.delta(time, compared)),
+                    best_segment: segment.best_segment_time()[method].map(|time| format.time(time)),
+                    skipped: passed && time.is_none(),
+                    current: phase != TimerPhase::Ended && current_split == Some(index),
+                }
+            })
+            .collect();
+
+        let last = run.segments().last();
+        let current = (phase != TimerPhase::NotRunning)
+            .then(|| timer.snapshot().current_time()[method])
+            .flatten();
+        Self {
+            schema: SCHEMA_V1,
+            phase: phase.into(),
+            timing_method: method.into(),
+            game: run.game_name().to_owned(),
+            category: run.category_name().to_owned(),
+            comparison: comparison.to_owned(),
+            current_split,
+            times: TimesV1 {
+                current: current.map(|time| format.time(time)),
+                personal_best: last
+                    .and_then(|segment| segment.personal_best_split_time()[method])
+                    .map(|time| format.time(time)),
+                comparison: last
+                    .and_then(|segment| segment.comparison(comparison)[method])
+                    .map(|time| format.time(time)),
+            },
+            splits,
+        }
+    }
+}
+
+#[cfg(feature = "webhooks")]
+/// What an event is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventKind {
+    /// A segment beat its best segment.
+    Gold,
+    /// The last split was done.
+    Finish,
+    /// The last split was done, beating the personal best.
+    Pb,
+}
+
+#[cfg(feature = "webhooks")]
+/// An event of the timer, version 1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct EventV1 {
+    /// Always `SCHEMA_V1`.
+    pub schema: u32,
+    pub event: EventKind,
+    pub timing_method: Method,
+    pub game: String,
+    pub category: String,
+    /// Name of the comparison `delta` is against.
+    pub comparison: String,
+    /// Index of the segment the event is about.
+    pub split_index: usize,
+    pub segment: String,
+    /// Split time of the segment: the final time for finishes.
+    pub time: Option<TimeValue>,
+    /// `time` minus the split time of the comparison, when both are there.
+    pub delta: Option<TimeValue>,
+}
+
+#[cfg(feature = "webhooks")]
+impl EventV1 {
+    /// `event` about the segment at `index`, read from `timer` after it happened.
+    /// `None` when the run has no such segment.
+    pub fn from_timer(
+        event: EventKind,
+        index: usize,
+        timer: &Timer,
+        format: &StateFormat,
+    ) -> Option<Self> {
+        let method = timer.current_timing_method();
+        let comparison = timer.current_comparison();
+        let run = timer.run();
+        let segment = run.segments().get(index)?;
+        let time = segment.split_time()[method];
+        let compared = segment.comparison(comparison)[method];
+        Some(Self {
+            schema: SCHEMA_V1,
+            event,
+            timing_method: method.into(),
+            game: run.game_name().to_owned(),
+            category: run.category_name().to_owned(),
+            comparison: comparison.to_owned(),
+            split_index: index,
+            segment: segment.name().to_owned(),
+            time: time.map(|time| format.time(time)),
+            delta: time
+                .zip(compared)
+                .map(|(time, compared)| format.delta(time, compared)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod proto_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{Run, Segment};
+
+    #[cfg(feature = "remote-control")]
+    const GOLDEN_V1: &str = include_str!("../tests/fixtures/proto/state-v1.json");
+    #[cfg(feature = "webhooks")]
+    const GOLDEN_EVENT_V1: &str = include_str!("../tests/fixtures/proto/event-v1.json");
+
+    /// Three segments with a personal best of 10, 25 and 40 seconds.
+    fn timer() -> Timer {
+        let mut run = Run::new();
+        run.set_game_name("Game");
+        run.set_category_name("Any%");
+        for (name, pb) in [("One", 10.0), ("Two", 25.0), ("Three", 40.0)] {
+            let mut segment = Segment::new(name);
+            segment.set_personal_best_split_time(real(pb));
+            segment.set_best_segment_time(real(pb / 2.0));
+            run.push_segment(segment);
+        }
+        Timer::new(run).unwrap()
+    }
+
+    #[cfg(feature = "remote-control")]
+    fn state_of(timer: &Timer) -> StateV1 {
+        StateV1::from_timer(timer, &StateFormat::default())
+    }
+
+    #[cfg(feature = "remote-control")]
+    #[test]
+    fn the_golden_fixture_still_deserializes() {
+        let golden: StateV1 = serde_json::from_str(GOLDEN_V1).unwrap();
+        assert_eq!(golden.schema, SCHEMA_V1);
+        assert_eq!(golden.phase, Phase::Running);
+        assert_eq!(golden.splits.len(), 3);
+        assert!(golden.splits[0].skipped);
+        assert_eq!(golden.splits[1].delta.as_ref().unwrap().ms, -1500);
+        // And what the current structs write reads back the same
+        let written = serde_json::to_string(&golden).unwrap();
+        assert_eq!(serde_json::from_str::<StateV1>(&written).unwrap(), golden);
+        assert_keys_kept(GOLDEN_V1, &written);
+    }
+
+    /// Fails unless every key of the `old` JSON is still written in `new`, at
+    /// the same place: fields may be added, never renamed or removed.
+    fn assert_keys_kept(old: &str, new: &str) {
+        fn walk(old: &serde_json::Value, new: &serde_json::Value, path: &str) {
+            use serde_json::Value;
+            match (old, new) {
+                (Value::Object(old), Value::Object(new)) => {
+                    for (key, value) in old {
+                        let path = format!("{path}.{key}");
+                        let Some(written) = new.get(key) else {
+                            panic!("{path} is no longer written");
+                        };
+                        walk(value, written, &path);
+                    }
+                }
+                (Value::Array(old), Value::Array(new)) => {
+                    for (index, (value, written)) in old.iter().zip(new).enumerate() {
+                        walk(value, written, &format!("{path}[{index}]"));
+                    }
+                }
+                _ => {}
+            }
+        }
+        walk(
+            &serde_json::from_str(old).unwrap(),
+            &serde_json::from_str(new).unwrap(),
+            "",
+        );
+    }
+
+    #[test]
+    fn added_fields_are_fine() {
+        assert_keys_kept(
+            r#"{"splits": [{"name": "One"}]}"#,
+            r#"{"splits": [{"name": "One", "gold": false}], "new": 1}"#,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = ".splits[0].skipped is no longer written")]
+    fn removed_or_renamed_fields_are_caught() {
+        assert_keys_kept(
+            r#"{"splits": [{"name": "One", "skipped": true}]}"#,
+            r#"{"splits": [{"name": "One", "was-skipped": true}]}"#,
+        );
+    }
+
+    #[cfg(feature = "remote-control")]
+    #[test]
+    fn a_timer_not_running_has_no_times_of_its_own() {
+        let state = state_of(&timer());
+        assert_eq!(state.schema, 1);
+        assert_eq!(state.phase, Phase::NotRunning);
+        assert_eq!(state.current_split, None);
+        assert_eq!(state.times.current, None);
+        assert_eq!(state.times.personal_best.as_ref().unwrap().ms, 40_000);
+        assert!(state.splits.iter().all(|split| split.time.is_none()
+            && split.delta.is_none()
+            && !split.skipped
+            && !split.current));
+        assert_eq!(state.splits[1].comparison.as_ref().unwrap().ms, 25_000);
+
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["phase"], "not-running");
+        assert_eq!(json["timing-method"], "real-time");
+        assert!(json["current-split"].is_null());
+    }
+
+    #[cfg(feature = "remote-control")]
+    #[test]
+    fn skipped_splits_are_told_apart_from_future_ones() {
+        let mut timer = timer();
+        timer.start();
+        timer.skip_split();
+        timer.split();
+        let state = state_of(&timer);
+        assert_eq!(state.phase, Phase::Running);
+        assert_eq!(state.current_split, Some(2));
+        assert!(state.splits[0].skipped);
+        assert!(state.splits[0].time.is_none());
+        assert!(!state.splits[1].skipped);
+        let time = state.splits[1].time.as_ref().unwrap();
+        let delta = state.splits[1].delta.as_ref().unwrap();
+        // Split right away, so far ahead of the comparison
+        assert_eq!(delta.ms, time.ms - 25_000);
+        assert!(delta.text.starts_with('-'), "{}", delta.text);
+        assert!(state.splits[2].current && !state.splits[2].skipped);
+        assert!(state.splits[2].time.is_none());
+    }
+
+    #[cfg(feature = "remote-control")]
+    #[test]
+    fn paused_and_ended_attempts() {
+        let mut timer = timer();
+        timer.start();
+        timer.pause();
+        let state = state_of(&timer);
+        assert_eq!(state.phase, Phase::Paused);
+        assert!(state.times.current.is_some());
+        assert_eq!(serde_json::to_value(&state).unwrap()["phase"], "paused");
+
+        timer.resume();
+        for _ in 0..3 {
+            timer.split();
+        }
+        let state = state_of(&timer);
+        assert_eq!(state.phase, Phase::Ended);
+        assert_eq!(state.current_split, Some(3));
+        assert!(state.splits.iter().all(|split| split.time.is_some()
+            && split.delta.is_some()
+            && !split.current
+            && !split.skipped));
+        assert_eq!(
+            state.times.current.as_ref().map(|time| time.ms),
+            state.splits[2].time.as_ref().map(|time| time.ms)
+        );
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[test]
+    fn events_match_the_golden_fixture() {
+        // A split time set by hand, so the fixture does not depend on the clock
+        let mut run = timer().run().clone();
+        run.segments_mut()[0].split_time_mut()[TimingMethod::RealTime] =
+            Some(TimeSpan::from_seconds(8.5));
+        let timer = Timer::new(run).unwrap();
+        let format = StateFormat {
+            delta_digits: 1,
+            ..StateFormat::default()
+        };
+
+        let event = EventV1::from_timer(EventKind::Gold, 0, &timer, &format).unwrap();
+        let written = serde_json::to_string_pretty(&event).unwrap();
+        assert_eq!(written, GOLDEN_EVENT_V1.trim_end());
+        assert_eq!(
+            serde_json::from_str::<EventV1>(GOLDEN_EVENT_V1).unwrap(),
+            event
+        );
+        assert_keys_kept(GOLDEN_EVENT_V1, &written);
+        assert!(EventV1::from_timer(EventKind::Pb, 3, &timer, &format).is_none());
+    }
+}
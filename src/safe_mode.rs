@@ -0,0 +1,159 @@
+//! Safe mode (`--safe-mode`): TuxSplit without the parts a config or a setup can
+//! break, to tell a bug of the app from one of the setup. The bundled stylesheet
+//! is used without the colors, overrides and font of the config, and the
+//! hotkeys, the auto splitter, the remote control, hooks, webhooks and
+//! animations stay off. The header and the About dialog say so.
+//!
+//! Each of those asks `current()` whether it may start, so this is the one place
+//! deciding what safe mode turns off.
+//!
+//! A panic leaves `CRASH_MARKER_FILE` in the data directory, removed again by a
+//! clean shutdown. Finding it on startup means the last session crashed, and
+//! safe mode is offered.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tracing::warn;
+
+use crate::paths::CRASH_MARKER_FILE;
+
+pub const FLAG: &str = "--safe-mode";
+
+/// What may run, for safe mode or a normal start.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SafeMode {
+    enabled: bool,
+}
+
+impl SafeMode {
+    pub const fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    pub const fn enabled(self) -> bool {
+        self.enabled
+    }
+
+    /// Colors, color overrides and the timer font of the config, on top of the
+    /// bundled stylesheet.
+    pub const fn custom_css(self) -> bool {
+        !self.enabled
+    }
+
+    pub const fn hotkeys(self) -> bool {
+        !self.enabled
+    }
+
+    pub const fn auto_splitter(self) -> bool {
+        !self.enabled
+    }
+
+    pub const fn remote_control(self) -> bool {
+        !self.enabled
+    }
+
+    /// Command hooks and webhooks.
+    pub const fn hooks(self) -> bool {
+        !self.enabled
+    }
+
+    pub const fn animations(self) -> bool {
+        !self.enabled
+    }
+}
+
+static SAFE_MODE: OnceLock<SafeMode> = OnceLock::new();
+
+/// Called once from `main`; later calls keep the first value.
+pub fn init(enabled: bool) -> SafeMode {
+    *SAFE_MODE.get_or_init(|| SafeMode::new(enabled))
+}
+
+/// Safe mode as `init` set it, off when it was never called.
+pub fn current() -> SafeMode {
+    SAFE_MODE.get().copied().unwrap_or_default()
+}
+
+static RESTART: AtomicBool = AtomicBool::new(false);
+
+/// Asks `main` to start TuxSplit again in safe mode once the app quit.
+pub fn request_restart() {
+    RESTART.store(true, Ordering::Relaxed);
+}
+
+pub fn restart_requested() -> bool {
+    RESTART.load(Ordering::Relaxed)
+}
+
+/// Leaves the crash marker in `dir` when the process panics, before the panic
+/// is reported as usual.
+pub fn install_crash_marker(dir: PathBuf) {
+    let report = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        record_crash(&dir, &info.to_string());
+        report(info);
+    }));
+}
+
+fn record_crash(dir: &Path, message: &str) {
+    if let Err(e) = std::fs::write(dir.join(CRASH_MARKER_FILE), message) {
+        warn!("Could not write the crash marker: {e}");
+    }
+}
+
+/// The message of the panic that ended the last session, if it crashed.
+pub fn last_crash(dir: &Path) -> Option<String> {
+    std::fs::read_to_string(dir.join(CRASH_MARKER_FILE)).ok()
+}
+
+/// Forgets the crash of the last session, on a clean shutdown.
+pub fn clear_crash(dir: &Path) {
+    let path = dir.join(CRASH_MARKER_FILE);
+    if let Err(e) = std::fs::remove_file(&path)
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        warn!("Could not remove {}: {e}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod safe_mode_tests {
+    use super::*;
+
+    #[test]
+    fn safe_mode_turns_every_subsystem_off() {
+        let safe = SafeMode::new(true);
+        assert!(safe.enabled());
+        assert!(!safe.custom_css());
+        assert!(!safe.hotkeys());
+        assert!(!safe.auto_splitter());
+        assert!(!safe.remote_control());
+        assert!(!safe.hooks());
+        assert!(!safe.animations());
+
+        let normal = SafeMode::default();
+        assert!(!normal.enabled());
+        assert!(normal.custom_css() && normal.hotkeys() && normal.auto_splitter());
+        assert!(normal.remote_control() && normal.hooks() && normal.animations());
+    }
+
+    #[test]
+    fn crashes_are_remembered_until_a_clean_shutdown() {
+        let dir = std::env::temp_dir().join(format!("tuxsplit-crash-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(last_crash(&dir), None);
+
+        record_crash(&dir, "panicked at src/main.rs:1:1");
+        assert_eq!(
+            last_crash(&dir).as_deref(),
+            Some("panicked at src/main.rs:1:1")
+        );
+        clear_crash(&dir);
+        assert_eq!(last_crash(&dir), None);
+        // Nothing to clear after a clean session
+        clear_crash(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
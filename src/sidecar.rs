@@ -0,0 +1,553 @@
+//! Per-run data TuxSplit keeps next to the splits file.
+//!
+//! LiveSplit's `.lss` format has no room for TuxSplit specific settings, so they are
+//! stored in a YAML file beside it (`run.lss` -> `run.tuxsplit.yaml`). Segment data
+//! is keyed by segment name so it follows segments when they are reordered.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use livesplit_core::Segment;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::storage::write_atomic;
+use crate::utils::attempt_history::remap_attempt_keys;
+use crate::utils::auto_splitter_settings::SettingsMap;
+use crate::utils::window_placement::WindowGeometry;
+
+const SIDECAR_EXTENSION: &str = "tuxsplit.yaml";
+/// Longest "split on" description, in characters.
+pub const SPLIT_ON_MAX_CHARS: usize = 80;
+/// Longest item of the pre-flight checklist, in characters.
+pub const PREFLIGHT_ITEM_MAX_CHARS: usize = 120;
+
+#[derive(Default, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct RunSidecar {
+    pub segments: BTreeMap<String, SegmentMeta>,
+    /// System clock jumps noticed while attempts were running, by attempt index.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub clock_jumps: BTreeMap<i32, Vec<ClockJump>>,
+    /// Tags of attempts ("race", "practice"...), by attempt index, to filter the
+    /// attempt history by.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub attempt_tags: BTreeMap<i32, Vec<String>>,
+    /// Geometry of the main window while the run was loaded, used over the one
+    /// of the config (see `utils::window_placement`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<WindowGeometry>,
+    /// Things to check before starting an attempt ("capture running", "audio
+    /// muted"...), ticked off when `general.preflight` is on.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub preflight: Vec<String>,
+    /// Settings of the auto-splitter scripts used with the run, by script path
+    /// (see `utils::auto_splitter_settings`).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub auto_splitter_settings: BTreeMap<PathBuf, SettingsMap>,
+}
+
+/// The system clock moving by more than the real time of an attempt (suspend,
+/// NTP corrections), which makes the attempt's real time unreliable.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ClockJump {
+    /// Local date and time it was noticed, as `YYYY-MM-DD HH:MM:SS`.
+    pub at: String,
+    /// How far the clock moved, negative when it went back.
+    pub offset_ms: i64,
+}
+
+impl ClockJump {
+    pub fn describe(&self) -> String {
+        let direction = if self.offset_ms < 0 { "back" } else { "ahead" };
+        format!(
+            "Clock moved {direction} {:.1} s at {}",
+            self.offset_ms.unsigned_abs() as f64 / 1000.0,
+            self.at
+        )
+    }
+}
+
+#[derive(Default, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct SegmentMeta {
+    /// Leave the segment out of gold detection and time save estimates
+    /// (menus, credits...). It is still shown in the splits list.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub excluded: bool,
+    /// Day the best segment was set, as `YYYY-MM-DD`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gold_date: Option<String>,
+    /// Position of the segment when the entry was last written, to find it
+    /// again after the segment was renamed outside TuxSplit.
+    #[serde(alias = "gold-index", skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    /// When to split, e.g. "door closes behind you", shown under the current
+    /// split while timing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split_on: Option<String>,
+}
+
+impl SegmentMeta {
+    fn is_empty(&self) -> bool {
+        !self.excluded && self.gold_date.is_none() && self.split_on.is_none()
+    }
+}
+
+impl RunSidecar {
+    /// Path of the sidecar file belonging to a splits file.
+    pub fn path_for(splits: &Path) -> PathBuf {
+        splits.with_extension(SIDECAR_EXTENSION)
+    }
+
+    /// Loads the sidecar of a splits file. A missing or unreadable file yields an
+    /// empty sidecar.
+    pub fn load(splits: &Path) -> Self {
+        let path = Self::path_for(splits);
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_yaml::from_str(&contents).unwrap_or_else(|e| {
+            warn!("Ignoring malformed sidecar {}: {e}", path.display());
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, splits: &Path) -> Result<(), std::io::Error> {
+        let path = Self::path_for(splits);
+        if self.segments.is_empty()
+            && self.clock_jumps.is_empty()
+            && self.attempt_tags.is_empty()
+            && self.window.is_none()
+            && self.preflight.is_empty()
+            && self.auto_splitter_settings.is_empty()
+            && !path.exists()
+        {
+            return Ok(());
+        }
+        let yaml = serde_yaml::to_string(self).map_err(std::io::Error::other)?;
+        write_atomic(path, yaml)
+    }
+
+    pub fn is_excluded(&self, name: &str) -> bool {
+        self.segments.get(name).is_some_and(|meta| meta.excluded)
+    }
+
+    pub fn set_excluded(&mut self, name: &str, index: usize, excluded: bool) {
+        self.update_segment(name, index, |meta| meta.excluded = excluded);
+    }
+
+    /// Exclusion flag of every segment, in run order.
+    pub fn excluded_flags(&self, segments: &[Segment]) -> Vec<bool> {
+        (0..segments.len())
+            .map(|index| {
+                self.meta_at(segments, index)
+                    .is_some_and(|meta| meta.excluded)
+            })
+            .collect()
+    }
+
+    /// Records the day the best segment at `index` was set, or forgets it.
+    pub fn set_gold_date(&mut self, name: &str, index: usize, date: Option<String>) {
+        self.update_segment(name, index, |meta| meta.gold_date = date);
+    }
+
+    /// Day the best segment at `index` was set.
+    pub fn gold_date(&self, segments: &[Segment], index: usize) -> Option<&str> {
+        self.meta_at(segments, index)?.gold_date.as_deref()
+    }
+
+    /// Entry of the segment at `index`. Found by name, or else by position among
+    /// the entries whose segment no longer exists.
+    fn meta_at(&self, segments: &[Segment], index: usize) -> Option<&SegmentMeta> {
+        let segment = segments.get(index)?;
+        if let Some(meta) = self.segments.get(segment.name()) {
+            return Some(meta);
+        }
+        self.segments
+            .iter()
+            .filter(|(name, _)| !segments.iter().any(|s| s.name() == name.as_str()))
+            .find(|(_, meta)| meta.index == Some(index))
+            .map(|(_, meta)| meta)
+    }
+
+    pub fn split_on(&self, name: &str) -> Option<&str> {
+        self.segments.get(name)?.split_on.as_deref()
+    }
+
+    /// Sets when to split at a segment, on one line of at most
+    /// `SPLIT_ON_MAX_CHARS` characters. Blank text removes it.
+    pub fn set_split_on(&mut self, name: &str, index: usize, text: &str) {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let text = (!text.is_empty()).then(|| text.chars().take(SPLIT_ON_MAX_CHARS).collect());
+        self.update_segment(name, index, |meta| meta.split_on = text);
+    }
+
+    /// When to split at the segment at `index`.
+    pub fn split_on_at(&self, segments: &[Segment], index: usize) -> Option<&str> {
+        self.meta_at(segments, index)?.split_on.as_deref()
+    }
+
+    /// "Split on" description of every segment, in run order.
+    pub fn split_on_descriptions(&self, segments: &[Segment]) -> Vec<Option<String>> {
+        (0..segments.len())
+            .map(|index| self.split_on_at(segments, index).map(str::to_owned))
+            .collect()
+    }
+
+    pub fn record_clock_jump(&mut self, attempt: i32, jump: ClockJump) {
+        self.clock_jumps.entry(attempt).or_default().push(jump);
+    }
+
+    pub fn clock_jumps(&self, attempt: i32) -> &[ClockJump] {
+        self.clock_jumps.get(&attempt).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn attempt_tags(&self, attempt: i32) -> &[String] {
+        self.attempt_tags.get(&attempt).map_or(&[], Vec::as_slice)
+    }
+
+    /// Tags `attempt` with `tag`, trimmed. Blank and repeated tags are ignored;
+    /// returns whether the tag was added.
+    pub fn add_attempt_tag(&mut self, attempt: i32, tag: &str) -> bool {
+        let tag = tag.trim();
+        let tags = self.attempt_tags.entry(attempt).or_default();
+        let added = !tag.is_empty() && !tags.iter().any(|t| t == tag);
+        if added {
+            tags.push(tag.to_owned());
+        }
+        if tags.is_empty() {
+            self.attempt_tags.remove(&attempt);
+        }
+        added
+    }
+
+    pub fn remove_attempt_tag(&mut self, attempt: i32, tag: &str) {
+        if let Some(tags) = self.attempt_tags.get_mut(&attempt) {
+            tags.retain(|t| t != tag);
+            if tags.is_empty() {
+                self.attempt_tags.remove(&attempt);
+            }
+        }
+    }
+
+    /// Every tag in use, sorted.
+    pub fn all_attempt_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self.attempt_tags.values().flatten().cloned().collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Forgets the data of a deleted attempt and renumbers that of the later
+    /// ones, as `utils::attempt_history::remove_attempt` does in the run.
+    pub fn remove_attempt(&mut self, attempt: i32) {
+        self.attempt_tags = remap_attempt_keys(std::mem::take(&mut self.attempt_tags), attempt);
+        self.clock_jumps = remap_attempt_keys(std::mem::take(&mut self.clock_jumps), attempt);
+    }
+
+    /// Moves the data stored for a segment to its new name.
+    pub fn rename_segment(&mut self, old: &str, new: &str) {
+        if old == new {
+            return;
+        }
+        if let Some(meta) = self.segments.remove(old) {
+            self.segments.insert(new.to_owned(), meta);
+        }
+    }
+
+    /// Gives a segment split off a duplicate name the data its twins share, so
+    /// disambiguating names changes nothing else.
+    pub fn copy_segment(&mut self, from: &str, to: &str) {
+        if let Some(meta) = self.segments.get(from).cloned() {
+            self.segments.insert(to.to_owned(), meta);
+        }
+    }
+
+    /// Replaces the pre-flight checklist with `items`, each on one line of at
+    /// most `PREFLIGHT_ITEM_MAX_CHARS` characters. Blank items are left out.
+    pub fn set_preflight(&mut self, items: &[&str]) {
+        self.preflight = items
+            .iter()
+            .map(|item| item.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|item| !item.is_empty())
+            .map(|item| item.chars().take(PREFLIGHT_ITEM_MAX_CHARS).collect())
+            .collect();
+    }
+
+    fn update_segment(&mut self, name: &str, index: usize, update: impl FnOnce(&mut SegmentMeta)) {
+        let meta = self.segments.entry(name.to_owned()).or_default();
+        meta.index = Some(index);
+        update(meta);
+        if meta.is_empty() {
+            self.segments.remove(name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod sidecar_tests {
+    use super::*;
+
+    fn segments(names: &[&str]) -> Vec<Segment> {
+        names.iter().map(|&name| Segment::new(name)).collect()
+    }
+
+    #[test]
+    fn sidecar_path_sits_next_to_the_splits() {
+        assert_eq!(
+            RunSidecar::path_for(Path::new("/runs/any%.lss")),
+            PathBuf::from("/runs/any%.tuxsplit.yaml")
+        );
+    }
+
+    #[test]
+    fn exclusion_follows_segments_when_reordered() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_excluded("Credits", 2, true);
+
+        assert_eq!(
+            sidecar.excluded_flags(&segments(&["Intro", "Boss", "Credits"])),
+            vec![false, false, true]
+        );
+        assert_eq!(
+            sidecar.excluded_flags(&segments(&["Credits", "Intro", "Boss"])),
+            vec![true, false, false]
+        );
+    }
+
+    #[test]
+    fn clearing_a_flag_drops_the_entry_and_renames_move_it() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_excluded("Menu", 0, true);
+        sidecar.rename_segment("Menu", "Main Menu");
+        assert!(sidecar.is_excluded("Main Menu"));
+        assert!(!sidecar.is_excluded("Menu"));
+
+        sidecar.set_excluded("Main Menu", 0, false);
+        assert!(sidecar.segments.is_empty());
+    }
+
+    #[test]
+    fn gold_dates_follow_names_and_fall_back_to_positions() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_gold_date("Boss", 1, Some("2023-08-14".to_owned()));
+        sidecar.set_gold_date("Credits", 2, Some("2021-03-02".to_owned()));
+
+        // Reordered: found by name
+        let reordered = segments(&["Boss", "Intro", "Credits"]);
+        assert_eq!(sidecar.gold_date(&reordered, 0), Some("2023-08-14"));
+        assert_eq!(sidecar.gold_date(&reordered, 1), None);
+
+        // "Boss" renamed in another tool: found by its old position
+        let renamed = segments(&["Intro", "Final Boss", "Credits"]);
+        assert_eq!(sidecar.gold_date(&renamed, 1), Some("2023-08-14"));
+        assert_eq!(sidecar.gold_date(&renamed, 2), Some("2021-03-02"));
+        // A segment with an entry of its own does not borrow another's date
+        sidecar.set_excluded("Intro", 0, true);
+        let shuffled = segments(&["Final Boss", "Intro", "Credits"]);
+        assert_eq!(sidecar.gold_date(&shuffled, 1), None);
+        assert_eq!(sidecar.gold_date(&shuffled, 3), None);
+
+        sidecar.set_gold_date("Credits", 2, None);
+        assert_eq!(sidecar.gold_date(&renamed, 2), None);
+        assert!(!sidecar.segments.contains_key("Credits"));
+    }
+
+    #[test]
+    fn exclusions_and_split_on_fall_back_to_positions() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_excluded("Menu", 0, true);
+        sidecar.set_split_on("Boss", 1, "door closes");
+
+        // Both renamed in another tool: found by their old positions
+        let renamed = segments(&["Title", "Final Boss", "Credits"]);
+        assert_eq!(sidecar.excluded_flags(&renamed), vec![true, false, false]);
+        assert_eq!(
+            sidecar.split_on_descriptions(&renamed),
+            vec![None, Some("door closes".to_owned()), None]
+        );
+        // A name lookup that hits wins over the position
+        let moved = segments(&["Boss", "Final Menu"]);
+        assert_eq!(sidecar.split_on_at(&moved, 0), Some("door closes"));
+        assert_eq!(sidecar.split_on_at(&moved, 1), None);
+    }
+
+    #[test]
+    fn copying_keeps_the_original_entry() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_excluded("Boss", 0, true);
+        sidecar.copy_segment("Boss", "Boss (2)");
+        sidecar.copy_segment("Forest", "Forest (2)");
+
+        assert_eq!(
+            sidecar.excluded_flags(&segments(&["Boss", "Boss (2)", "Forest (2)"])),
+            vec![true, true, false]
+        );
+        assert_eq!(sidecar.segments.len(), 2);
+    }
+
+    #[test]
+    fn yaml_round_trip_and_missing_fields() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_excluded("Credits", 2, true);
+
+        let yaml = serde_yaml::to_string(&sidecar).unwrap();
+        assert_eq!(serde_yaml::from_str::<RunSidecar>(&yaml).unwrap(), sidecar);
+
+        let empty: RunSidecar = serde_yaml::from_str("segments:\n  Boss: {}\n").unwrap();
+        assert!(!empty.is_excluded("Boss"));
+        assert!(!yaml.contains("clock-jumps"));
+        assert!(!yaml.contains("window"));
+    }
+
+    #[test]
+    fn split_on_descriptions_are_one_short_line() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_split_on("Forest", 0, "  door closes\n behind you ");
+        sidecar.set_split_on("Cave", 2, &"x".repeat(100));
+        assert_eq!(
+            sidecar.split_on_descriptions(&segments(&["Forest", "Boss", "Cave"])),
+            vec![
+                Some("door closes behind you".to_owned()),
+                None,
+                Some("x".repeat(SPLIT_ON_MAX_CHARS))
+            ]
+        );
+
+        sidecar.set_split_on("Cave", 2, " ");
+        assert_eq!(sidecar.split_on("Cave"), None);
+        assert!(!sidecar.segments.contains_key("Cave"));
+    }
+
+    #[test]
+    fn split_on_descriptions_round_trip_and_default_to_none() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_split_on("Forest", 0, "door closes behind you");
+        sidecar.set_excluded("Credits", 2, true);
+
+        let yaml = serde_yaml::to_string(&sidecar).unwrap();
+        assert!(yaml.contains("split-on: door closes behind you"));
+        assert_eq!(serde_yaml::from_str::<RunSidecar>(&yaml).unwrap(), sidecar);
+
+        // Sidecars written before the field existed
+        let old: RunSidecar =
+            serde_yaml::from_str("segments:\n  Forest:\n    gold-date: 2024-05-01\n").unwrap();
+        assert_eq!(old.split_on("Forest"), None);
+        assert!(!serde_yaml::to_string(&old).unwrap().contains("split-on"));
+    }
+
+    #[test]
+    fn preflight_checklists_round_trip_and_default_to_empty() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_preflight(&["  Capture\n running ", "", " ", "Audio muted"]);
+        assert_eq!(sidecar.preflight, ["Capture running", "Audio muted"]);
+
+        let yaml = serde_yaml::to_string(&sidecar).unwrap();
+        assert_eq!(
+            yaml,
+            "segments: {}\npreflight:\n- Capture running\n- Audio muted\n"
+        );
+        assert_eq!(serde_yaml::from_str::<RunSidecar>(&yaml).unwrap(), sidecar);
+
+        // Sidecars written before the field existed
+        let old: RunSidecar = serde_yaml::from_str("segments:\n  Boss: {}\n").unwrap();
+        assert!(old.preflight.is_empty());
+        assert!(!serde_yaml::to_string(&old).unwrap().contains("preflight"));
+
+        sidecar.set_preflight(&[]);
+        assert_eq!(sidecar, RunSidecar::default());
+    }
+
+    #[test]
+    fn window_geometry_is_read_with_or_without_a_position() {
+        let sidecar: RunSidecar =
+            serde_yaml::from_str("window:\n  width: 420\n  height: 760\n  x: -1920\n  y: 0\n")
+                .unwrap();
+        let window = sidecar.window.unwrap();
+        assert_eq!((window.width, window.height), (Some(420), Some(760)));
+        assert_eq!((window.x, window.y), (Some(-1920), Some(0)));
+        assert_eq!(window.maximized, None);
+
+        let sidecar: RunSidecar = serde_yaml::from_str("window:\n  maximized: true\n").unwrap();
+        let yaml = serde_yaml::to_string(&sidecar).unwrap();
+        assert_eq!(yaml, "segments: {}\nwindow:\n  maximized: true\n");
+    }
+
+    #[test]
+    fn clock_jumps_are_kept_per_attempt() {
+        let mut sidecar = RunSidecar::default();
+        let jump = ClockJump {
+            at: "2026-03-02 21:14:05".to_owned(),
+            offset_ms: -3_400,
+        };
+        sidecar.record_clock_jump(12, jump.clone());
+        assert_eq!(sidecar.clock_jumps(12), std::slice::from_ref(&jump));
+        assert!(sidecar.clock_jumps(11).is_empty());
+        assert_eq!(
+            jump.describe(),
+            "Clock moved back 3.4 s at 2026-03-02 21:14:05"
+        );
+
+        let yaml = serde_yaml::to_string(&sidecar).unwrap();
+        assert!(yaml.contains("offset-ms: -3400"));
+        assert_eq!(serde_yaml::from_str::<RunSidecar>(&yaml).unwrap(), sidecar);
+    }
+
+    #[test]
+    fn attempt_tags_are_deduplicated_and_follow_deleted_attempts() {
+        let mut sidecar = RunSidecar::default();
+        assert!(sidecar.add_attempt_tag(2, " race "));
+        assert!(!sidecar.add_attempt_tag(2, "race"));
+        assert!(!sidecar.add_attempt_tag(3, "  "));
+        assert!(sidecar.add_attempt_tag(3, "sick day"));
+        assert!(sidecar.add_attempt_tag(4, "race"));
+        assert_eq!(sidecar.attempt_tags(2), ["race"]);
+        assert_eq!(sidecar.attempt_tags(3), ["sick day"]);
+        assert_eq!(sidecar.all_attempt_tags(), ["race", "sick day"]);
+
+        sidecar.record_clock_jump(
+            4,
+            ClockJump {
+                at: "2026-03-02 21:14:05".to_owned(),
+                offset_ms: 3_000,
+            },
+        );
+        sidecar.remove_attempt(3);
+        assert_eq!(sidecar.attempt_tags(2), ["race"]);
+        assert_eq!(sidecar.attempt_tags(3), ["race"]);
+        assert!(sidecar.attempt_tags(4).is_empty());
+        assert_eq!(sidecar.clock_jumps(3).len(), 1);
+        assert_eq!(sidecar.all_attempt_tags(), ["race"]);
+
+        sidecar.remove_attempt_tag(2, "race");
+        assert!(!sidecar.attempt_tags.contains_key(&2));
+
+        let yaml = serde_yaml::to_string(&sidecar).unwrap();
+        assert!(yaml.contains("attempt-tags:\n  3:\n  - race\n"));
+        assert_eq!(serde_yaml::from_str::<RunSidecar>(&yaml).unwrap(), sidecar);
+    }
+
+    #[test]
+    fn save_and_load_from_disk() {
+        let dir = std::env::temp_dir().join(format!("tuxsplit-sidecar-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let splits = dir.join("run.lss");
+
+        assert_eq!(RunSidecar::load(&splits), RunSidecar::default());
+
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_excluded("Credits", 2, true);
+        sidecar.save(&splits).unwrap();
+        assert_eq!(RunSidecar::load(&splits), sidecar);
+
+        fs::write(RunSidecar::path_for(&splits), "segments: [not, a, map]").unwrap();
+        assert_eq!(RunSidecar::load(&splits), RunSidecar::default());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,173 @@
+//! What becomes of the splits file of the config when TuxSplit starts.
+//!
+//! A file that was moved, or lives on a drive that is not mounted, must not be
+//! replaced silently by the placeholder run: saving would then write over it.
+//! The timer starts with the placeholder run detached from the path instead,
+//! and the user is asked what to do (see `context::offer_splits_recovery`).
+//! `--doctor` reports the same diagnosis.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use livesplit_core::Run;
+
+use crate::config::parse_splits;
+
+/// Why the configured splits file could not be opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SplitsProblem {
+    Missing,
+    PermissionDenied,
+    /// Any other error reading the file, with its message.
+    Unreadable(String),
+    /// The file was read but is no splits file TuxSplit knows, with the
+    /// message of the parser.
+    Corrupt(String),
+}
+
+impl SplitsProblem {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Missing => "the file does not exist".to_owned(),
+            Self::PermissionDenied => "you are not allowed to read the file".to_owned(),
+            Self::Unreadable(e) => format!("the file cannot be read: {e}"),
+            Self::Corrupt(e) => format!("the file could not be parsed: {e}"),
+        }
+    }
+
+    pub const fn hint(&self) -> &'static str {
+        match self {
+            Self::Missing => {
+                "It may have been moved or renamed, or be on a drive that is not mounted"
+            }
+            Self::PermissionDenied => "Check the permissions of the file and its folder",
+            Self::Unreadable(_) => "Check the path in config.yaml or load the splits again",
+            Self::Corrupt(_) => {
+                "Make sure the file is a LiveSplit (.lss) or other supported splits file"
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StartupAction {
+    /// No splits file is configured: the placeholder run is used.
+    StartEmpty,
+    Load(Box<Run>),
+    /// The configured file could not be opened. The placeholder run is used,
+    /// without being saved to `path`, until the user decides.
+    Recover {
+        path: PathBuf,
+        problem: SplitsProblem,
+    },
+}
+
+/// Reads the splits file at `path` with `read` and parses it.
+pub fn diagnose_splits(
+    path: &Path,
+    read: impl FnOnce(&Path) -> io::Result<Vec<u8>>,
+) -> Result<Run, SplitsProblem> {
+    let file = read(path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => SplitsProblem::Missing,
+        io::ErrorKind::PermissionDenied => SplitsProblem::PermissionDenied,
+        _ => SplitsProblem::Unreadable(e.to_string()),
+    })?;
+    parse_splits(&file, path).map_err(|e| SplitsProblem::Corrupt(e.to_string()))
+}
+
+/// What to start with, given the splits file of the config.
+pub fn startup_action(
+    splits: Option<&Path>,
+    read: impl FnOnce(&Path) -> io::Result<Vec<u8>>,
+) -> StartupAction {
+    let Some(path) = splits else {
+        return StartupAction::StartEmpty;
+    };
+    match diagnose_splits(path, read) {
+        Ok(run) => StartupAction::Load(Box::new(run)),
+        Err(problem) => StartupAction::Recover {
+            path: path.to_owned(),
+            problem,
+        },
+    }
+}
+
+#[cfg(test)]
+mod startup_tests {
+    use super::*;
+    use livesplit_core::Segment;
+    use livesplit_core::run::saver::livesplit::save_run;
+
+    const PATH: &str = "/mnt/usb/splits/any.lss";
+
+    fn action(read: impl FnOnce(&Path) -> io::Result<Vec<u8>>) -> StartupAction {
+        startup_action(Some(Path::new(PATH)), read)
+    }
+
+    fn problem(action: StartupAction) -> SplitsProblem {
+        match action {
+            StartupAction::Recover { path, problem } => {
+                assert_eq!(path, Path::new(PATH));
+                problem
+            }
+            other => panic!("expected a recovery, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn missing_and_unreadable_files_ask_the_user() {
+        assert_eq!(
+            problem(action(|_| Err(io::ErrorKind::NotFound.into()))),
+            SplitsProblem::Missing
+        );
+        assert_eq!(
+            problem(action(|_| Err(io::ErrorKind::PermissionDenied.into()))),
+            SplitsProblem::PermissionDenied
+        );
+        assert!(matches!(
+            problem(action(|_| Err(io::Error::other("input/output error")))),
+            SplitsProblem::Unreadable(e) if e == "input/output error"
+        ));
+
+        // Reading a real path that is not there gives the same diagnosis
+        let missing = std::env::temp_dir().join(format!(
+            "tuxsplit-startup-missing-{}.lss",
+            std::process::id()
+        ));
+        assert_eq!(
+            diagnose_splits(&missing, |path| std::fs::read(path)).unwrap_err(),
+            SplitsProblem::Missing
+        );
+    }
+
+    #[test]
+    fn corrupt_files_ask_the_user() {
+        let truncated = b"<?xml version=\"1.0\"?><Run version=\"1.7.0\"><GameName>Ga".to_vec();
+        assert!(matches!(
+            problem(action(|_| Ok(truncated))),
+            SplitsProblem::Corrupt(_)
+        ));
+        assert!(matches!(
+            problem(action(|_| Ok(vec![0xFF, 0x00, 0x13, 0x37]))),
+            SplitsProblem::Corrupt(_)
+        ));
+    }
+
+    #[test]
+    fn readable_files_load_and_no_file_starts_empty() {
+        let mut run = Run::new();
+        run.set_game_name("Game");
+        run.push_segment(Segment::new("Forest"));
+        let mut lss = String::new();
+        save_run(&run, &mut lss).unwrap();
+
+        match action(|_| Ok(lss.into_bytes())) {
+            StartupAction::Load(run) => assert_eq!(run.game_name(), "Game"),
+            other => panic!("expected the run, got {other:?}"),
+        }
+        assert!(matches!(
+            startup_action(None, |_| unreachable!()),
+            StartupAction::StartEmpty
+        ));
+    }
+}
@@ -0,0 +1,326 @@
+//! Helpers for writing TuxSplit's files to disk safely.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Writes `contents` to `path` atomically: the data goes to a temporary file in the
+/// same directory which is then renamed over the target, so a crash mid-write never
+/// leaves a truncated file behind.
+pub fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let temp = temp_path(path);
+
+    let result = (|| {
+        let mut file = fs::File::create(&temp)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Decides when a periodically checked value needs to be written again.
+///
+/// Writers bump a revision counter on every change; the autosave compares it with
+/// the revision it last wrote, so any number of changes between two checks results
+/// in a single write.
+#[derive(Debug, Default)]
+pub struct Autosave {
+    saved_revision: u64,
+}
+
+impl Autosave {
+    pub fn new(saved_revision: u64) -> Self {
+        Self { saved_revision }
+    }
+
+    pub fn needs_save(&self, revision: u64) -> bool {
+        revision != self.saved_revision
+    }
+
+    pub fn mark_saved(&mut self, revision: u64) {
+        self.saved_revision = revision;
+    }
+}
+
+/// Whether the run has changes that are not in its splits file yet.
+///
+/// Edits made through TuxSplit mark it directly; changes the timer makes on its
+/// own (splits, attempts saved on reset) are picked up from livesplit-core's
+/// modified flag, which must be cleared along with this one on save.
+#[derive(Debug, Default)]
+pub struct RunSaveState {
+    dirty: bool,
+}
+
+impl RunSaveState {
+    pub const fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Returns true if the run was clean until now.
+    pub fn mark_modified(&mut self) -> bool {
+        !std::mem::replace(&mut self.dirty, true)
+    }
+
+    /// The run now matches its file. Returns true if it was dirty.
+    pub fn mark_saved(&mut self) -> bool {
+        std::mem::replace(&mut self.dirty, false)
+    }
+
+    /// Follows livesplit-core's modified flag of the run.
+    pub fn observe(&mut self, run_modified: bool) -> bool {
+        run_modified && self.mark_modified()
+    }
+}
+
+/// What the instance lock file records about the instance holding it.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct InstanceLock {
+    pub pid: u32,
+    /// Splits file the instance had loaded when it last wrote the lock.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub splits: Option<PathBuf>,
+}
+
+/// Whether another instance holds the lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockState {
+    /// No lock, or one of this process.
+    Free,
+    /// The lock of an instance that is still running.
+    Held(InstanceLock),
+}
+
+impl InstanceLock {
+    /// Takes the lock at `path` for this instance, replacing whatever was there.
+    pub fn acquire(&self, path: &Path) -> std::io::Result<()> {
+        let yaml = serde_yaml::to_string(self).map_err(std::io::Error::other)?;
+        write_atomic(path, yaml)
+    }
+
+    /// Who holds the lock at `path`, with `is_alive` telling whether a process
+    /// is a running TuxSplit. Locks of exited processes, and unreadable ones,
+    /// are removed.
+    pub fn check(path: &Path, own_pid: u32, is_alive: impl Fn(u32) -> bool) -> LockState {
+        let Ok(contents) = fs::read(path) else {
+            return LockState::Free;
+        };
+        match serde_yaml::from_slice::<Self>(&contents) {
+            Ok(lock) if lock.pid == own_pid => LockState::Free,
+            Ok(lock) if is_alive(lock.pid) => LockState::Held(lock),
+            result => {
+                if let Ok(lock) = result {
+                    warn!("Removing the stale instance lock of process {}", lock.pid);
+                } else {
+                    warn!("Removing the unreadable instance lock {}", path.display());
+                }
+                let _ = fs::remove_file(path);
+                LockState::Free
+            }
+        }
+    }
+
+    /// Removes the lock at `path` if this process (`own_pid`) still holds it:
+    /// another instance may have taken it over.
+    pub fn release(path: &Path, own_pid: u32) {
+        let held = fs::read(path)
+            .ok()
+            .and_then(|contents| serde_yaml::from_slice::<Self>(&contents).ok())
+            .is_some_and(|lock| lock.pid == own_pid);
+        if held && let Err(e) = fs::remove_file(path) {
+            warn!("Could not remove the instance lock {}: {e}", path.display());
+        }
+    }
+}
+
+/// Whether `pid` is a running process of this executable. Where `/proc` does
+/// not tell which executable a process runs, any running process counts.
+pub fn is_running_instance(pid: u32) -> bool {
+    let proc_dir = Path::new("/proc").join(pid.to_string());
+    if !proc_dir.exists() {
+        return false;
+    }
+    let name = |path: &Path| path.file_name().map(ToOwned::to_owned);
+    match (fs::read_link(proc_dir.join("exe")), std::env::current_exe()) {
+        // Rebuilt executables show as "<name> (deleted)", so only names are compared
+        (Ok(theirs), Ok(ours)) => {
+            let theirs = name(&theirs).map(|n| n.to_string_lossy().replace(" (deleted)", ""));
+            let ours = name(&ours).map(|n| n.to_string_lossy().into_owned());
+            theirs == ours
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod storage_tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tuxsplit-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn atomic_write_replaces_contents_without_leftovers() {
+        let dir = temp_dir("atomic");
+        let path = dir.join("config.yaml");
+
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert!(!temp_path(&path).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn atomic_write_fails_cleanly_when_the_directory_is_missing() {
+        let path = std::env::temp_dir()
+            .join("tuxsplit-missing-dir-for-sure")
+            .join("config.yaml");
+        assert!(write_atomic(&path, "data").is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn many_changes_between_checks_produce_a_single_write() {
+        let mut autosave = Autosave::new(0);
+        let mut revision = 0;
+        let mut writes = 0;
+
+        let mut tick = |autosave: &mut Autosave, revision: u64| {
+            if autosave.needs_save(revision) {
+                writes += 1;
+                autosave.mark_saved(revision);
+            }
+        };
+
+        // Nothing changed yet
+        tick(&mut autosave, revision);
+
+        // A burst of changes (e.g. window resizing) before the next check
+        for _ in 0..25 {
+            revision += 1;
+        }
+        tick(&mut autosave, revision);
+        tick(&mut autosave, revision);
+
+        revision += 1;
+        tick(&mut autosave, revision);
+
+        assert_eq!(writes, 2);
+    }
+
+    #[test]
+    fn save_edit_save_and_finished_attempts() {
+        use livesplit_core::{Run, Segment, Timer};
+
+        let mut run = Run::new();
+        run.push_segment(Segment::new("Only"));
+        let mut timer = Timer::new(run).unwrap();
+        let mut state = RunSaveState::default();
+
+        let save = |timer: &mut Timer, state: &mut RunSaveState| {
+            timer.mark_as_unmodified();
+            state.mark_saved()
+        };
+
+        // Freshly loaded
+        assert!(!state.observe(timer.run().has_been_modified()));
+        assert!(!save(&mut timer, &mut state));
+
+        // Edited, then saved
+        assert!(state.mark_modified());
+        assert!(!state.mark_modified());
+        assert!(state.is_dirty());
+        assert!(save(&mut timer, &mut state));
+        assert!(!state.observe(timer.run().has_been_modified()));
+
+        // An attempt kept on reset
+        timer.start();
+        timer.split();
+        timer.reset(true);
+        assert!(state.observe(timer.run().has_been_modified()));
+        assert!(state.is_dirty());
+        assert!(save(&mut timer, &mut state));
+        assert!(!state.is_dirty());
+    }
+
+    #[test]
+    fn live_locks_are_reported_and_stale_ones_removed() {
+        let dir = temp_dir("instance-lock");
+        let path = dir.join("instance.lock");
+        let lock = InstanceLock {
+            pid: 4242,
+            splits: Some(PathBuf::from("/splits/any.lss")),
+        };
+        assert_eq!(InstanceLock::check(&path, 1, |_| true), LockState::Free);
+
+        lock.acquire(&path).unwrap();
+        assert_eq!(
+            InstanceLock::check(&path, 1, |pid| pid == 4242),
+            LockState::Held(lock.clone())
+        );
+        // Our own lock, left by a crash of a process with the same PID
+        assert_eq!(InstanceLock::check(&path, 4242, |_| true), LockState::Free);
+        assert!(path.exists());
+
+        // The holder exited without cleaning up
+        assert_eq!(InstanceLock::check(&path, 1, |_| false), LockState::Free);
+        assert!(!path.exists());
+
+        fs::write(&path, "not: [a lock").unwrap();
+        assert_eq!(InstanceLock::check(&path, 1, |_| true), LockState::Free);
+        assert!(!path.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn only_the_holder_releases_the_lock() {
+        let dir = temp_dir("instance-release");
+        let path = dir.join("instance.lock");
+        let ours = InstanceLock {
+            pid: 1,
+            splits: None,
+        };
+        ours.acquire(&path).unwrap();
+
+        // Taken over by another instance
+        InstanceLock {
+            pid: 2,
+            splits: None,
+        }
+        .acquire(&path)
+        .unwrap();
+        InstanceLock::release(&path, 1);
+        assert!(path.exists());
+
+        InstanceLock::release(&path, 2);
+        assert!(!path.exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn this_process_is_a_running_instance() {
+        assert!(is_running_instance(std::process::id()));
+        assert!(!is_running_instance(u32::MAX));
+    }
+}
@@ -0,0 +1,8 @@
+//! Fixtures shared by the unit tests.
+
+use livesplit_core::{Time, TimeSpan};
+
+/// A time of `seconds` in real time only.
+pub fn real(seconds: f64) -> Time {
+    Time::new().with_real_time(Some(TimeSpan::from_seconds(seconds)))
+}
@@ -0,0 +1,64 @@
+//! The About dialog: version and commit of the build, links to the project and,
+//! under Troubleshooting, the `--doctor` report to copy into bug reports. In safe
+//! mode the description says what is turned off.
+
+use adw::AboutDialog;
+use adw::prelude::*;
+use gtk4::{gio, glib};
+
+use crate::doctor;
+use crate::safe_mode;
+use crate::ui::debug_log;
+use crate::version;
+
+const APP_ID: &str = "io.github.tunixr.tuxsplit";
+const REPOSITORY: &str = "https://github.com/AntonioRodriguezRuiz/tuxsplit";
+/// Link of the "Debug Log" entry, opening the debug log window rather than a
+/// browser.
+const DEBUG_LOG_URI: &str = "tuxsplit:debug-log";
+
+pub fn present(parent: &adw::ApplicationWindow) {
+    let comments = if safe_mode::current().enabled() {
+        "A GTK-based LiveSplit timer application.\n\nRunning in safe mode: custom colors \
+         and fonts, hotkeys, the auto splitter, the remote control, hooks, webhooks and \
+         animations are turned off."
+    } else {
+        "A GTK-based LiveSplit timer application."
+    };
+    let dialog = AboutDialog::builder()
+        .application_name("TuxSplit")
+        .application_icon(APP_ID)
+        .version(version::describe())
+        .comments(comments)
+        .license_type(gtk4::License::MitX11)
+        .website(REPOSITORY)
+        .issue_url(format!("{REPOSITORY}/issues"))
+        .debug_info("Collecting the environment report…")
+        .debug_info_filename("tuxsplit-doctor.txt")
+        .build();
+    dialog.add_link("Debug Log", DEBUG_LOG_URI);
+    dialog.add_acknowledgement_section(
+        Some("Timing by"),
+        &["livesplit-core https://github.com/LiveSplit/livesplit-core"],
+    );
+
+    let parent_binding = parent.clone();
+    dialog.connect_activate_link(move |_, uri| {
+        if uri != DEBUG_LOG_URI {
+            return false;
+        }
+        if let Some(app) = parent_binding.application() {
+            debug_log::present(&app);
+        }
+        true
+    });
+    dialog.present(Some(parent));
+
+    // The checks read files and the splits, so they stay off the main loop
+    glib::MainContext::default().spawn_local(async move {
+        let report = gio::spawn_blocking(|| doctor::Report::collect_in_app().render())
+            .await
+            .unwrap_or_else(|_| "The environment report could not be collected.\n".to_owned());
+        dialog.set_debug_info(&report);
+    });
+}
@@ -0,0 +1,227 @@
+//! "Archive Run..." and "Archives..." menu actions: dated copies of the splits
+//! file (see `crate::archive`), opened as a copy to compare with or restored
+//! over the current splits.
+
+use adw::prelude::*;
+use adw::{ActionRow, AlertDialog};
+use gtk4::{Align, Button, ListBox, ScrolledWindow, SelectionMode};
+use livesplit_core::TimeSpan;
+
+use std::path::{Path, PathBuf};
+
+use crate::archive::{self, ArchiveEntry, ArchiveIndex};
+use crate::config::read_run;
+use crate::context::{TuxSplitContext, today};
+use crate::error::TuxSplitError;
+use crate::paths;
+use crate::ui::categories::confirm_unsaved;
+use crate::ui::error::show_error;
+use crate::ui::route;
+
+/// Archives the current splits file, once unsaved changes are saved or
+/// discarded, then lists the archives.
+pub fn present_archive_run(parent: &adw::ApplicationWindow) {
+    if TuxSplitContext::get_instance()
+        .config()
+        .general
+        .splits
+        .is_none()
+    {
+        present_no_splits(parent);
+        return;
+    }
+    let parent_binding = parent.clone();
+    confirm_unsaved(parent, move || match archive_current() {
+        Ok(_) => present_archives(&parent_binding),
+        Err(e) => show_error(&parent_binding, &e),
+    });
+}
+
+fn present_no_splits(parent: &adw::ApplicationWindow) {
+    let dialog = AlertDialog::builder()
+        .heading("Nothing to Archive")
+        .body("Save the splits to a file first.")
+        .default_response("ok")
+        .close_response("ok")
+        .build();
+    dialog.add_response("ok", "_OK");
+    dialog.present(Some(parent));
+}
+
+/// Copies the current splits file into the archives.
+fn archive_current() -> Result<ArchiveEntry, TuxSplitError> {
+    let ctx = TuxSplitContext::get_instance();
+    if ctx.read_only() {
+        return Err(TuxSplitError::ReadOnly);
+    }
+    let dir = archive::archives_dir(&paths::current().root);
+    let Some(splits) = ctx.config().general.splits.clone() else {
+        return Err(TuxSplitError::Io {
+            path: dir,
+            source: std::io::ErrorKind::NotFound.into(),
+        });
+    };
+    let run = ctx.get_run();
+    let method = ctx.timer().read().unwrap().current_timing_method();
+    let date = today().unwrap_or_else(|| "undated".to_owned());
+    archive::archive(&dir, &splits, &run, method, &date)
+        .map_err(|source| TuxSplitError::Io { path: dir, source })
+}
+
+/// Lists the archives, newest first, each with "Open a Copy" and "Restore".
+pub fn present_archives(parent: &adw::ApplicationWindow) {
+    let dir = archive::archives_dir(&paths::current().root);
+    let archives = ArchiveIndex::load(&dir).existing(&dir);
+
+    let dialog = AlertDialog::builder()
+        .heading("Archives")
+        .body(format!("Archived splits are kept in {}.", dir.display()))
+        .default_response("close")
+        .close_response("close")
+        .build();
+    dialog.add_response("close", "_Close");
+
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    for entry in &archives {
+        list.append(&archive_row(parent, &dialog, &dir, entry));
+    }
+    if archives.is_empty() {
+        list.append(
+            &ActionRow::builder()
+                .title("No archives yet")
+                .css_classes(["dim-label"])
+                .build(),
+        );
+    }
+    let scroller = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .min_content_height(300)
+        .child(&list)
+        .build();
+    dialog.set_extra_child(Some(&scroller));
+    dialog.present(Some(parent));
+}
+
+fn archive_row(
+    parent: &adw::ApplicationWindow,
+    dialog: &AlertDialog,
+    dir: &Path,
+    entry: &ArchiveEntry,
+) -> ActionRow {
+    let format = TuxSplitContext::get_instance()
+        .config()
+        .format
+        .split
+        .clone();
+    let pb = entry.personal_best_ms.map_or_else(
+        || "no PB".to_owned(),
+        |ms| {
+            format!(
+                "PB {}",
+                format.format_time_span(&TimeSpan::from_milliseconds(ms as f64))
+            )
+        },
+    );
+    let row = ActionRow::builder()
+        .title(glib::markup_escape_text(&format!("{} — {}", entry.game, entry.category)).as_str())
+        .subtitle(
+            glib::markup_escape_text(&format!(
+                "{} · {pb} · {} attempts",
+                entry.date, entry.attempts
+            ))
+            .as_str(),
+        )
+        .tooltip_text(&entry.file)
+        .build();
+
+    let path = entry.path_in(dir);
+    let open = Button::builder()
+        .label("Open a Copy")
+        .valign(Align::Center)
+        .build();
+    let parent_binding = parent.clone();
+    let open_path = path.clone();
+    open.connect_clicked(move |_| match read_run(&open_path) {
+        // The archive is only read: adopting copies times into the current run
+        Ok(run) => route::present_route_diff(&parent_binding, &open_path, run),
+        Err(e) => show_error(&parent_binding, &e),
+    });
+    row.add_suffix(&open);
+
+    let restore = Button::builder()
+        .label("Restore")
+        .valign(Align::Center)
+        .css_classes(["destructive-action"])
+        .build();
+    let parent_binding = parent.clone();
+    let dialog_binding = dialog.clone();
+    let entry = entry.clone();
+    let dir = dir.to_path_buf();
+    restore.connect_clicked(move |_| {
+        confirm_restore(&parent_binding, &dialog_binding, dir.clone(), entry.clone());
+    });
+    row.add_suffix(&restore);
+    row
+}
+
+/// Asks before writing the archived `entry` over the current splits, which are
+/// archived first (nothing is written while saving is turned off).
+fn confirm_restore(
+    parent: &adw::ApplicationWindow,
+    list_dialog: &AlertDialog,
+    dir: PathBuf,
+    entry: ArchiveEntry,
+) {
+    let Some(splits) = TuxSplitContext::get_instance()
+        .config()
+        .general
+        .splits
+        .clone()
+    else {
+        present_no_splits(parent);
+        return;
+    };
+    let dialog = AlertDialog::builder()
+        .heading("Restore Archive?")
+        .body(format!(
+            "The splits of {} are replaced by the archive of {}. They are archived \
+             first, so this can be undone from this list.",
+            splits.display(),
+            entry.date
+        ))
+        .default_response("cancel")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("restore", "_Restore");
+    dialog.set_response_appearance("restore", adw::ResponseAppearance::Destructive);
+
+    let parent_binding = parent.clone();
+    let list_binding = list_dialog.clone();
+    dialog.connect_response(Some("restore"), move |_, _| {
+        let parent = parent_binding.clone();
+        let list = list_binding.clone();
+        let dir = dir.clone();
+        let entry = entry.clone();
+        let splits = splits.clone();
+        confirm_unsaved(&parent_binding, move || {
+            let restored = archive_current().and_then(|_| {
+                archive::restore(&dir, &entry, &splits).map_err(|source| TuxSplitError::Io {
+                    path: splits.clone(),
+                    source,
+                })?;
+                TuxSplitContext::get_instance().load_splits(splits.clone())
+            });
+            match restored {
+                Ok(()) => {
+                    list.close();
+                }
+                Err(e) => show_error(&parent, &e),
+            }
+        });
+    });
+    dialog.present(Some(parent));
+}
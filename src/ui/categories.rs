@@ -0,0 +1,352 @@
+//! Category switcher: the header submenu listing the categories of the current
+//! game and the dialog managing them.
+
+use adw::prelude::*;
+use adw::{AlertDialog, EntryRow, PreferencesDialog, PreferencesGroup, PreferencesPage};
+use gtk4::{Button, gio};
+use livesplit_core::run::saver::livesplit::save_run;
+use tracing::warn;
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use crate::categories::{CategorySets, blank_category_run, new_splits_path, relative_to};
+use crate::context::{TuxSplitContext, recovery_needed};
+use crate::error::TuxSplitError;
+use crate::storage::write_atomic;
+use crate::ui::editor::{check_duplicate_names, check_sidecar};
+use crate::ui::error::show_error;
+
+/// Where the current run lives, as far as categories are concerned.
+struct Location {
+    dir: PathBuf,
+    splits: PathBuf,
+    game: String,
+    category: String,
+}
+
+impl Location {
+    /// `None` until a splits file is loaded: new categories need a directory.
+    fn current() -> Option<Self> {
+        let ctx = TuxSplitContext::get_instance();
+        let splits = ctx.config().general.splits.clone()?;
+        let dir = splits.parent()?.to_path_buf();
+        let timer = ctx.timer();
+        let t = timer.read().unwrap();
+        Some(Self {
+            dir,
+            splits,
+            game: t.run().game_name().to_owned(),
+            category: t.run().category_name().to_owned(),
+        })
+    }
+
+    fn load_sets(&self) -> CategorySets {
+        CategorySets::load(&self.dir)
+    }
+
+    fn save_sets(&self, sets: &CategorySets) {
+        if TuxSplitContext::get_instance().read_only() {
+            return;
+        }
+        if let Err(e) = sets.save(&self.dir) {
+            warn!(
+                "Could not save categories in {}: {e}",
+                CategorySets::path_in(&self.dir).display()
+            );
+        }
+    }
+}
+
+/// Refills `section` with a submenu, named after the game, listing its categories.
+/// `switch` is the stateful "switch-category" action; its state marks the
+/// current category.
+pub fn refresh_menu(section: &gio::Menu, switch: &gio::SimpleAction) {
+    section.remove_all();
+    let Some(location) = Location::current() else {
+        switch.set_state(&"".to_variant());
+        return;
+    };
+
+    let sets = location.load_sets();
+    let current = sets
+        .find_by_splits(&location.dir, &location.game, &location.splits)
+        .map(|category| category.name.clone())
+        .unwrap_or_default();
+    switch.set_state(&current.to_variant());
+
+    let submenu = gio::Menu::new();
+    let list = gio::Menu::new();
+    for category in sets.categories(&location.game) {
+        let item = gio::MenuItem::new(Some(&category.name), None);
+        item.set_action_and_target_value(
+            Some("app.switch-category"),
+            Some(&category.name.to_variant()),
+        );
+        list.append_item(&item);
+    }
+    let manage = gio::Menu::new();
+    manage.append(Some("Manage Categories..."), Some("app.manage-categories"));
+    submenu.append_section(None, &list);
+    submenu.append_section(None, &manage);
+
+    let title = if location.game.is_empty() {
+        "Categories"
+    } else {
+        location.game.as_str()
+    };
+    section.append_submenu(Some(title), &submenu);
+}
+
+/// Switches to the category `name` of the current game, saving or discarding
+/// unsaved changes first as the user chooses.
+pub fn switch_category(parent: &impl IsA<gtk4::Widget>, name: &str) {
+    let Some(location) = Location::current() else {
+        return;
+    };
+    let mut sets = location.load_sets();
+    let Some(target) = sets
+        .categories(&location.game)
+        .iter()
+        .find(|category| category.name == name)
+        .map(|category| category.resolve(&location.dir))
+    else {
+        return;
+    };
+    if target == location.splits {
+        return;
+    }
+
+    // Keep the file switched away from reachable from the menu
+    if sets
+        .ensure_listed(
+            &location.game,
+            &location.category,
+            relative_to(&location.dir, &location.splits),
+        )
+        .is_ok()
+    {
+        location.save_sets(&sets);
+    }
+
+    let parent_binding = parent.as_ref().clone();
+    confirm_unsaved(parent, move || {
+        match TuxSplitContext::get_instance().load_splits(target.clone()) {
+            Ok(()) => {
+                check_sidecar(&parent_binding);
+                check_duplicate_names(&parent_binding);
+            }
+            Err(e) => show_error(&parent_binding, &e),
+        }
+    });
+}
+
+/// Runs `then` once the current run is safe to replace: right away when nothing
+/// is unsaved, otherwise after the user chose to save or discard the changes.
+pub(crate) fn confirm_unsaved(parent: &impl IsA<gtk4::Widget>, then: impl Fn() + 'static) {
+    let unsaved = {
+        let ctx = TuxSplitContext::get_instance();
+        let timer = ctx.timer();
+        let t = timer.read().unwrap();
+        ctx.update_dirty(&t);
+        recovery_needed(t.current_phase(), ctx.run_dirty())
+    };
+    if !unsaved {
+        then();
+        return;
+    }
+
+    let dialog = AlertDialog::builder()
+        .heading("Save Changes?")
+        .body("The current splits have unsaved changes or an attempt in progress.")
+        .default_response("save")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("discard", "_Discard");
+    dialog.add_response("save", "_Save");
+    dialog.set_response_appearance("discard", adw::ResponseAppearance::Destructive);
+    dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+    let parent_binding = parent.as_ref().clone();
+    dialog.connect_response(None, move |_, response| match response {
+        // The changes are not replaced unless they were saved
+        "save" => match TuxSplitContext::get_instance().save_splits() {
+            Ok(()) => then(),
+            Err(e) => show_error(&parent_binding, &e),
+        },
+        "discard" => then(),
+        _ => {}
+    });
+    dialog.present(Some(parent));
+}
+
+/// Dialog to add, rename and remove the categories of the current game. `None`
+/// when no splits file is loaded.
+pub fn manage_dialog() -> Option<PreferencesDialog> {
+    let location = Rc::new(Location::current()?);
+
+    let dialog = PreferencesDialog::new();
+    dialog.set_title("Categories");
+    let page = PreferencesPage::new();
+    let group = PreferencesGroup::builder()
+        .title(glib::markup_escape_text(&location.game).as_str())
+        .description(
+            "Each category keeps its own splits file. Removing a category leaves its file on disk.",
+        )
+        .build();
+    page.add(&group);
+    dialog.add(&page);
+
+    let rows: Rc<RefCell<Vec<gtk4::Widget>>> = Rc::new(RefCell::new(Vec::new()));
+    let rebuild: Rc<RefCell<Box<dyn Fn()>>> = Rc::new(RefCell::new(Box::new(|| {})));
+    {
+        let group = group.clone();
+        let location = location.clone();
+        let rows = rows.clone();
+        let rebuild_binding = Rc::downgrade(&rebuild);
+        let dialog_binding = dialog.downgrade();
+        *rebuild.borrow_mut() = Box::new(move || {
+            for row in rows.borrow_mut().drain(..) {
+                group.remove(&row);
+            }
+            let (Some(rebuild), Some(dialog)) =
+                (rebuild_binding.upgrade(), dialog_binding.upgrade())
+            else {
+                return;
+            };
+
+            let mut sets = location.load_sets();
+            // The current file is a category of its own from the start
+            if sets
+                .ensure_listed(
+                    &location.game,
+                    &location.category,
+                    relative_to(&location.dir, &location.splits),
+                )
+                .is_ok()
+            {
+                location.save_sets(&sets);
+            }
+
+            for category in sets.categories(&location.game) {
+                let row = category_row(&dialog, &location, &category.name, &rebuild);
+                group.add(&row);
+                rows.borrow_mut().push(row.upcast());
+            }
+            let row = new_category_row(&dialog, &location, &rebuild);
+            group.add(&row);
+            rows.borrow_mut().push(row.upcast());
+        });
+    }
+    (rebuild.borrow())();
+
+    // Rows only hold weak references to the rebuild function, the dialog keeps it
+    dialog.connect_closed(move |_| {
+        let _ = &rebuild;
+    });
+
+    Some(dialog)
+}
+
+fn category_row(
+    dialog: &PreferencesDialog,
+    location: &Rc<Location>,
+    name: &str,
+    rebuild: &Rc<RefCell<Box<dyn Fn()>>>,
+) -> EntryRow {
+    let row = EntryRow::builder()
+        .title("Name")
+        .text(name)
+        .show_apply_button(true)
+        .build();
+
+    {
+        let dialog = dialog.clone();
+        let location = location.clone();
+        let rebuild = Rc::downgrade(rebuild);
+        let old = name.to_owned();
+        row.connect_apply(move |row| {
+            let mut sets = location.load_sets();
+            match sets.rename(&location.game, &old, &row.text()) {
+                Ok(()) => location.save_sets(&sets),
+                Err(e) => dialog.add_toast(adw::Toast::new(&e.to_string())),
+            }
+            if let Some(rebuild) = rebuild.upgrade() {
+                (rebuild.borrow())();
+            }
+        });
+    }
+
+    let remove = Button::builder()
+        .icon_name("user-trash-symbolic")
+        .tooltip_text("Remove Category")
+        .valign(gtk4::Align::Center)
+        .css_classes(["flat"])
+        .build();
+    {
+        let location = location.clone();
+        let rebuild = Rc::downgrade(rebuild);
+        let name = name.to_owned();
+        remove.connect_clicked(move |_| {
+            let mut sets = location.load_sets();
+            if sets.remove(&location.game, &name).is_ok() {
+                location.save_sets(&sets);
+            }
+            if let Some(rebuild) = rebuild.upgrade() {
+                (rebuild.borrow())();
+            }
+        });
+    }
+    row.add_suffix(&remove);
+
+    row
+}
+
+/// Entry creating a category from the current run: same segments, no times.
+fn new_category_row(
+    dialog: &PreferencesDialog,
+    location: &Rc<Location>,
+    rebuild: &Rc<RefCell<Box<dyn Fn()>>>,
+) -> EntryRow {
+    let row = EntryRow::builder()
+        .title("New Category")
+        .show_apply_button(true)
+        .build();
+
+    let dialog = dialog.clone();
+    let location = location.clone();
+    let rebuild = Rc::downgrade(rebuild);
+    row.connect_apply(move |row| {
+        let name = row.text().trim().to_owned();
+        if let Err(e) = create_category(&location, &name) {
+            dialog.add_toast(adw::Toast::new(&e));
+            return;
+        }
+        if let Some(rebuild) = rebuild.upgrade() {
+            (rebuild.borrow())();
+        }
+    });
+
+    row
+}
+
+fn create_category(location: &Location, name: &str) -> Result<(), String> {
+    if TuxSplitContext::get_instance().read_only() {
+        return Err(TuxSplitError::ReadOnly.to_string());
+    }
+    let mut sets = location.load_sets();
+    let path = new_splits_path(&location.dir, &location.game, name);
+    // Validates the name before anything is written
+    sets.add(&location.game, name, relative_to(&location.dir, &path))
+        .map_err(|e| e.to_string())?;
+
+    let run = TuxSplitContext::get_instance().get_run();
+    let run = blank_category_run(run, name).ok_or("The current run cannot be copied")?;
+    let mut buf = String::new();
+    save_run(&run, &mut buf).map_err(|e| e.to_string())?;
+    write_atomic(&path, buf).map_err(|e| format!("Could not write {}: {e}", path.display()))?;
+
+    location.save_sets(&sets);
+    Ok(())
+}
@@ -0,0 +1,305 @@
+//! Comparison drop-down of the header. The button shows the short label of the
+//! current comparison (see `format_label`), the list each comparison with a line
+//! of context from `comparison_subtitle`. A menu next to it picks the secondary
+//! comparison (`display.secondary-comparison`), shown dimmed in the splits.
+
+use glib::Properties;
+use glib::subclass::prelude::*;
+use gtk4::prelude::*;
+use gtk4::{DropDown, Label, ListItem, MenuButton, Orientation, SignalListItemFactory, gio};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::context::TuxSplitContext;
+use crate::formatters::label::format_label;
+use crate::utils::comparisons::comparison_subtitle;
+use crate::utils::timer_actions::TimerAction;
+
+mod imp {
+    use super::{
+        DerivedObjectProperties, ObjectExt, ObjectImpl, ObjectImplExt, ObjectSubclass, Properties,
+        RefCell,
+    };
+
+    #[derive(Default, Properties, Debug)]
+    #[properties(wrapper_type = super::ComparisonItem)]
+    pub struct ComparisonItem {
+        #[property(get, set)]
+        pub name: RefCell<String>,
+        /// Short name for the button, e.g. "PB".
+        #[property(get, set)]
+        pub label: RefCell<String>,
+        /// Context shown under the name in the list, empty when there is none.
+        #[property(get, set)]
+        pub subtitle: RefCell<String>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ComparisonItem {
+        const NAME: &'static str = "ComparisonItem";
+        type Type = super::ComparisonItem;
+        type ParentType = glib::Object;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for ComparisonItem {
+        fn constructed(&self) {
+            self.parent_constructed();
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct ComparisonItem(ObjectSubclass<imp::ComparisonItem>);
+}
+
+impl ComparisonItem {
+    pub fn new(name: &str, subtitle: String) -> Self {
+        glib::Object::builder()
+            .property("name", name)
+            .property("label", format_label(name))
+            .property("subtitle", subtitle)
+            .build()
+    }
+}
+
+/// Selecting a comparison switches the timer to it. The list is rebuilt when the
+/// run changes and follows comparison switches made elsewhere, e.g. by hotkey.
+#[derive(Clone)]
+pub struct ComparisonPicker {
+    container: gtk4::Box,
+    dropdown: DropDown,
+    items: gio::ListStore,
+    /// Set while the selection is changed to follow the timer.
+    syncing: Rc<Cell<bool>>,
+}
+
+impl ComparisonPicker {
+    pub fn new() -> Self {
+        let items = gio::ListStore::new::<ComparisonItem>();
+        let dropdown = DropDown::builder()
+            .model(&items)
+            .factory(&label_factory())
+            .list_factory(&two_line_factory())
+            .tooltip_text("Comparison")
+            .valign(gtk4::Align::Center)
+            .build();
+        let container = gtk4::Box::builder()
+            .css_classes(["linked"])
+            .valign(gtk4::Align::Center)
+            .build();
+        container.append(&dropdown);
+        container.append(&secondary_button());
+        let this = Self {
+            container,
+            dropdown,
+            items,
+            syncing: Rc::new(Cell::new(false)),
+        };
+        this.refresh();
+
+        let syncing = Rc::clone(&this.syncing);
+        this.dropdown.connect_selected_item_notify(move |dropdown| {
+            if syncing.get() {
+                return;
+            }
+            if let Some(item) = dropdown.selected_item().and_downcast::<ComparisonItem>() {
+                TuxSplitContext::get_instance().enqueue(TimerAction::SetComparison(item.name()));
+            }
+        });
+
+        let picker = this.clone();
+        TuxSplitContext::get_instance().connect_local("run-changed", false, move |_| {
+            picker.refresh();
+            None
+        });
+        let picker = this.clone();
+        TuxSplitContext::get_instance().connect_local("comparison-changed", false, move |_| {
+            picker.follow_timer();
+            None
+        });
+        this
+    }
+
+    pub fn widget(&self) -> &gtk4::Box {
+        &self.container
+    }
+
+    /// Rebuilds the items from the comparisons of the run.
+    fn refresh(&self) {
+        let ctx = TuxSplitContext::get_instance();
+        let format = ctx.config().format.split.clone();
+        let items: Vec<ComparisonItem> = {
+            let timer = ctx.timer();
+            let timer = timer.read().unwrap();
+            let method = timer.current_timing_method();
+            timer
+                .run()
+                .comparisons()
+                .map(|name| {
+                    ComparisonItem::new(
+                        name,
+                        comparison_subtitle(timer.run(), name, method, &format),
+                    )
+                })
+                .collect()
+        };
+        self.syncing.set(true);
+        self.items.splice(0, self.items.n_items(), &items);
+        self.syncing.set(false);
+        self.follow_timer();
+    }
+
+    /// Selects the current comparison of the timer.
+    fn follow_timer(&self) {
+        let current = {
+            let timer = TuxSplitContext::get_instance().timer();
+            let timer = timer.read().unwrap();
+            timer.current_comparison().to_owned()
+        };
+        let selected = self
+            .dropdown
+            .selected_item()
+            .and_downcast::<ComparisonItem>()
+            .map(|item| item.name());
+        if selected.as_deref() == Some(current.as_str()) {
+            return;
+        }
+        let position = (0..self.items.n_items()).find(|&position| {
+            self.items
+                .item(position)
+                .and_downcast::<ComparisonItem>()
+                .is_some_and(|item| item.name() == current)
+        });
+        if let Some(position) = position {
+            self.syncing.set(true);
+            self.dropdown.set_selected(position);
+            self.syncing.set(false);
+        }
+    }
+}
+
+/// Button of the menu picking the secondary comparison. Its items are the
+/// comparisons of the run but the current one, read on every opening.
+fn secondary_button() -> MenuButton {
+    let secondary = gio::Menu::new();
+    let menu = gio::Menu::new();
+    menu.append_submenu(Some("Secondary Comparison"), &secondary);
+    let button = MenuButton::builder()
+        .icon_name("view-dual-symbolic")
+        .tooltip_text("Secondary Comparison")
+        .menu_model(&menu)
+        .build();
+
+    let action = gio::SimpleAction::new_stateful(
+        "secondary-comparison",
+        Some(glib::VariantTy::STRING),
+        &"".to_variant(),
+    );
+    action.connect_activate(|action, parameter| {
+        let Some(name) = parameter.and_then(glib::Variant::get::<String>) else {
+            return;
+        };
+        action.set_state(&name.to_variant());
+        let ctx = TuxSplitContext::get_instance();
+        if let Ok(mut cfg) = ctx.config_mut() {
+            cfg.mark_dirty();
+            cfg.display.secondary_comparison = (!name.is_empty()).then_some(name);
+            drop(cfg);
+            ctx.emit_run_changed();
+        }
+    });
+    let group = gio::SimpleActionGroup::new();
+    group.add_action(&action);
+    button.insert_action_group("picker", Some(&group));
+
+    button.set_create_popup_func(move |_| {
+        let ctx = TuxSplitContext::get_instance();
+        let current = ctx
+            .config()
+            .display
+            .secondary_comparison()
+            .unwrap_or_default()
+            .to_owned();
+        action.set_state(&current.to_variant());
+
+        secondary.remove_all();
+        let none = gio::MenuItem::new(Some("None"), None);
+        none.set_action_and_target_value(
+            Some("picker.secondary-comparison"),
+            Some(&"".to_variant()),
+        );
+        secondary.append_item(&none);
+        let timer = ctx.timer();
+        let timer = timer.read().unwrap();
+        for name in timer
+            .run()
+            .comparisons()
+            .filter(|&name| name != timer.current_comparison())
+        {
+            let item = gio::MenuItem::new(Some(name), None);
+            item.set_action_and_target_value(
+                Some("picker.secondary-comparison"),
+                Some(&name.to_variant()),
+            );
+            secondary.append_item(&item);
+        }
+    });
+    button
+}
+
+/// The short label, for the button.
+fn label_factory() -> SignalListItemFactory {
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(|_, item| {
+        item.downcast_ref::<ListItem>()
+            .expect("list item")
+            .set_child(Some(&Label::new(None)));
+    });
+    factory.connect_bind(|_, item| {
+        let item = item.downcast_ref::<ListItem>().expect("list item");
+        let Some(comparison) = item.item().and_downcast::<ComparisonItem>() else {
+            return;
+        };
+        if let Some(label) = item.child().and_downcast::<Label>() {
+            label.set_label(&comparison.label());
+        }
+    });
+    factory
+}
+
+/// The full name over its context, for the list.
+fn two_line_factory() -> SignalListItemFactory {
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(|_, item| {
+        let lines = gtk4::Box::new(Orientation::Vertical, 2);
+        lines.append(&Label::builder().xalign(0.0).build());
+        lines.append(
+            &Label::builder()
+                .xalign(0.0)
+                .css_classes(["caption", "dim-label"])
+                .build(),
+        );
+        item.downcast_ref::<ListItem>()
+            .expect("list item")
+            .set_child(Some(&lines));
+    });
+    factory.connect_bind(|_, item| {
+        let item = item.downcast_ref::<ListItem>().expect("list item");
+        let Some(comparison) = item.item().and_downcast::<ComparisonItem>() else {
+            return;
+        };
+        let Some(lines) = item.child() else {
+            return;
+        };
+        let name = lines.first_child().and_downcast::<Label>();
+        let subtitle = lines.last_child().and_downcast::<Label>();
+        if let (Some(name), Some(subtitle)) = (name, subtitle) {
+            let text = comparison.subtitle();
+            name.set_label(&comparison.name());
+            subtitle.set_visible(!text.is_empty());
+            subtitle.set_label(&text);
+        }
+    });
+    factory
+}
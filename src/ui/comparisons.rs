@@ -0,0 +1,248 @@
+//! "Duplicate Comparison..." and "Create Goal Comparison..." menu actions: a
+//! custom comparison copied from another one, e.g. route-specific target times to
+//! tweak from the PB, or made of each segment at a percentile of its history.
+
+use adw::prelude::*;
+use adw::{AlertDialog, ComboRow, EntryRow, SpinRow};
+use gtk4::{ListBox, SelectionMode, StringList};
+use livesplit_core::run::ComparisonError;
+use livesplit_core::{Run, TimerPhase};
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::context::TuxSplitContext;
+use crate::utils::comparisons::{duplicate_comparison, goal_comparison};
+use crate::utils::naming::unique_name;
+use crate::utils::percentiles::{GOAL_PERCENTILE, goal_name, percentile_goal};
+
+/// Name offered for the copy.
+const DEFAULT_NAME: &str = "Target";
+
+/// Asks for the comparison to copy and the name of the copy, then adds it to the
+/// run and makes it the current comparison.
+pub fn present_duplicate_comparison(parent: &impl IsA<gtk4::Widget>) {
+    let ctx = TuxSplitContext::get_instance();
+    let (comparisons, current, phase) = {
+        let timer = ctx.timer();
+        let timer = timer.read().unwrap();
+        let comparisons: Vec<String> = timer.run().comparisons().map(str::to_owned).collect();
+        (
+            comparisons,
+            timer.current_comparison().to_owned(),
+            timer.current_phase(),
+        )
+    };
+
+    let dialog = AlertDialog::builder()
+        .heading("Duplicate Comparison")
+        .default_response("duplicate")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("duplicate", "_Duplicate");
+    dialog.set_response_appearance("duplicate", adw::ResponseAppearance::Suggested);
+    // Replacing the run would reset the attempt in progress
+    if phase == TimerPhase::NotRunning {
+        dialog.set_body(
+            "The copy becomes the current comparison, saved with the splits. Its times can be edited in the split editor.",
+        );
+    } else {
+        dialog.set_body("Reset the attempt in progress to add a comparison.");
+        dialog.set_response_enabled("duplicate", false);
+    }
+
+    let names: Vec<&str> = comparisons.iter().map(String::as_str).collect();
+    let source = ComboRow::builder()
+        .title("Copy From")
+        .model(&StringList::new(&names))
+        .selected(comparisons.iter().position(|c| *c == current).unwrap_or(0) as u32)
+        .build();
+    let name = EntryRow::builder()
+        .title("Name")
+        .text(unique_name(DEFAULT_NAME, names.iter().copied()))
+        .build();
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    list.append(&source);
+    list.append(&name);
+    dialog.set_extra_child(Some(&list));
+
+    {
+        let dialog = dialog.clone();
+        name.connect_changed(move |entry| {
+            let valid = !entry.text().trim().is_empty()
+                && !comparisons
+                    .iter()
+                    .any(|c| c.as_str() == entry.text().trim());
+            dialog.set_response_enabled("duplicate", valid && phase == TimerPhase::NotRunning);
+        });
+    }
+
+    let parent_binding = parent.as_ref().clone();
+    dialog.connect_response(Some("duplicate"), move |_, _| {
+        let Some(source) = source
+            .selected_item()
+            .and_downcast::<gtk4::StringObject>()
+            .map(|item| item.string())
+        else {
+            return;
+        };
+        let name = name.text().trim().to_owned();
+        let ctx = TuxSplitContext::get_instance();
+        add_comparison(
+            &parent_binding,
+            "Could Not Duplicate Comparison",
+            duplicate_comparison(ctx.get_run(), &source, &name),
+            name,
+        );
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// Asks for a percentile and the name of the comparison, showing the final time
+/// of the goal, then adds it to the run and makes it the current comparison.
+pub fn present_goal_comparison(parent: &impl IsA<gtk4::Widget>) {
+    let ctx = TuxSplitContext::get_instance();
+    let (comparisons, method, phase) = {
+        let timer = ctx.timer();
+        let timer = timer.read().unwrap();
+        let comparisons: Vec<String> = timer.run().comparisons().map(str::to_owned).collect();
+        (
+            comparisons,
+            timer.current_timing_method(),
+            timer.current_phase(),
+        )
+    };
+    let run = ctx.get_run();
+    let format = ctx.config().format.split.clone();
+
+    let dialog = AlertDialog::builder()
+        .heading("Create Goal Comparison")
+        .default_response("create")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("create", "C_reate");
+    dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+    if phase == TimerPhase::NotRunning {
+        dialog.set_body(
+            "Each segment at a percentile of its history: the 25th is a time reached \
+             in one attempt out of four. Segments with fewer than five times use their \
+             median.",
+        );
+    } else {
+        dialog.set_body("Reset the attempt in progress to add a comparison.");
+    }
+
+    let percentile = SpinRow::with_range(1.0, 99.0, 1.0);
+    percentile.set_title("Percentile");
+    percentile.set_value(f64::from(GOAL_PERCENTILE));
+    let name = EntryRow::builder().title("Name").build();
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    list.append(&percentile);
+    list.append(&name);
+    dialog.set_extra_child(Some(&list));
+
+    let offered = {
+        let comparisons = comparisons.clone();
+        move |percentile: u8| {
+            unique_name(
+                &goal_name(percentile),
+                comparisons.iter().map(String::as_str),
+            )
+        }
+    };
+    name.set_text(&offered(GOAL_PERCENTILE));
+
+    let validate = {
+        let dialog = dialog.clone();
+        let name = name.clone();
+        let percentile = percentile.clone();
+        let run = run.clone();
+        Rc::new(move || {
+            let goal = percentile_goal(&run, method, percentile.value() as u8);
+            percentile.set_subtitle(&goal.map_or_else(
+                || "Not every segment has history".to_owned(),
+                |goal| format!("Final time {}", format.format_time_span(&goal)),
+            ));
+            let text = name.text();
+            let valid =
+                !text.trim().is_empty() && !comparisons.iter().any(|c| c.as_str() == text.trim());
+            dialog.set_response_enabled(
+                "create",
+                valid && goal.is_some() && phase == TimerPhase::NotRunning,
+            );
+        })
+    };
+    validate();
+    {
+        let validate = Rc::clone(&validate);
+        name.connect_changed(move |_| validate());
+    }
+    // The name follows the percentile until it is edited
+    let last_offered = Rc::new(RefCell::new(name.text().to_string()));
+    let name_binding = name.clone();
+    percentile.connect_value_notify(move |row| {
+        let offered = offered(row.value() as u8);
+        if name_binding.text() == *last_offered.borrow() {
+            name_binding.set_text(&offered);
+        }
+        last_offered.replace(offered);
+        validate();
+    });
+
+    let parent_binding = parent.as_ref().clone();
+    dialog.connect_response(Some("create"), move |_, _| {
+        let name = name.text().trim().to_owned();
+        add_comparison(
+            &parent_binding,
+            "Could Not Create Goal Comparison",
+            goal_comparison(run.clone(), percentile.value() as u8, &name),
+            name,
+        );
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// Sets the run with the comparison `name` just added, making it the current
+/// comparison, or tells why it could not be added.
+fn add_comparison(
+    parent: &gtk4::Widget,
+    heading: &str,
+    run: Result<Run, ComparisonError>,
+    name: String,
+) {
+    match run {
+        Ok(run) => {
+            let ctx = TuxSplitContext::get_instance();
+            // Applied by `configure_timer` when the run is set
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.general.comparison = Some(name);
+            }
+            ctx.set_run(run);
+        }
+        Err(e) => {
+            let reason = match e {
+                ComparisonError::DuplicateName => "is already a comparison",
+                ComparisonError::NameStartsWithRace => "is reserved for races",
+            };
+            let error = AlertDialog::builder()
+                .heading(heading)
+                .body(format!("“{name}” {reason}."))
+                .default_response("close")
+                .close_response("close")
+                .build();
+            error.add_response("close", "_Close");
+            error.present(Some(parent));
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Debug log window (menu or `--debug`): the events of `utils::log_buffer`,
+//! filtered by level, with "Copy All" putting them on the clipboard together with
+//! the `--doctor` report for bug reports.
+//!
+//! New events are appended from an idle callback the log buffer schedules, at
+//! most one pending at a time, so a burst of logging does not stall the timer.
+//! "Measure Refresh" turns on the timings of `utils::frame_stats`, shown above
+//! the events a few times a second while measuring.
+
+use adw::prelude::*;
+use adw::{HeaderBar, Toast, ToastOverlay, ToolbarView};
+use gtk4::{
+    Box as GtkBox, Button, DropDown, Label, ListItem, ListView, NoSelection, Orientation,
+    ScrolledWindow, SignalListItemFactory, StringList, ToggleButton, gio,
+};
+use tracing::Level;
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::doctor;
+use crate::utils::frame_stats;
+use crate::utils::log_buffer::{LOG_CAPACITY, log_buffer};
+
+/// How often the timings are shown again while measuring.
+const STATS_INTERVAL_MS: u64 = 250;
+/// Levels of the filter, most severe first.
+const LEVELS: [(&str, Level); 4] = [
+    ("Errors", Level::ERROR),
+    ("Warnings", Level::WARN),
+    ("Info", Level::INFO),
+    ("Debug", Level::DEBUG),
+];
+/// Shown at first, everything the log keeps.
+const DEFAULT_LEVEL: u32 = 3;
+
+/// Whether an append of new events is scheduled already.
+static APPEND_PENDING: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static WINDOW: RefCell<Option<glib::WeakRef<adw::Window>>> = const { RefCell::new(None) };
+    /// Appends the events not shown yet to the open window.
+    static APPEND: RefCell<Option<Rc<dyn Fn()>>> = const { RefCell::new(None) };
+}
+
+/// Presents the debug log window, raising it when it is open already.
+pub fn present(app: &impl IsA<gtk4::Application>) {
+    if let Some(window) = WINDOW.with(|w| w.borrow().as_ref().and_then(glib::WeakRef::upgrade)) {
+        window.present();
+        return;
+    }
+
+    let lines = StringList::new(&[]);
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(|_, item| {
+        let label = Label::builder()
+            .xalign(0.0)
+            .selectable(true)
+            .css_classes(["monospace"])
+            .build();
+        item.downcast_ref::<ListItem>()
+            .expect("list item")
+            .set_child(Some(&label));
+    });
+    factory.connect_bind(|_, item| {
+        let item = item.downcast_ref::<ListItem>().expect("list item");
+        let line = item
+            .item()
+            .and_downcast::<gtk4::StringObject>()
+            .map(|line| line.string())
+            .unwrap_or_default();
+        if let Some(label) = item.child().and_downcast::<Label>() {
+            label.set_label(&line);
+        }
+    });
+    let list = ListView::builder()
+        .model(&NoSelection::new(Some(lines.clone())))
+        .factory(&factory)
+        .build();
+    let scroller = ScrolledWindow::builder().child(&list).vexpand(true).build();
+
+    let level = DropDown::from_strings(&LEVELS.map(|(name, _)| name));
+    level.set_selected(DEFAULT_LEVEL);
+    level.set_tooltip_text(Some("Least severe events shown"));
+    let copy = Button::builder()
+        .label("_Copy All")
+        .use_underline(true)
+        .build();
+
+    let measure = ToggleButton::builder()
+        .label("_Measure Refresh")
+        .use_underline(true)
+        .active(frame_stats::enabled())
+        .tooltip_text("Time each refresh of the timer, logged every 30 seconds")
+        .build();
+    let stats = Label::builder()
+        .xalign(0.0)
+        .selectable(true)
+        .css_classes(["monospace"])
+        .margin_start(12)
+        .margin_end(12)
+        .margin_top(6)
+        .margin_bottom(6)
+        .visible(frame_stats::enabled())
+        .build();
+    // Whether the timings are being shown again
+    let following = Rc::new(Cell::new(false));
+    measure.connect_toggled({
+        let stats = stats.clone();
+        let following = Rc::clone(&following);
+        move |measure| {
+            frame_stats::set_enabled(measure.is_active());
+            stats.set_visible(measure.is_active());
+            if measure.is_active() {
+                follow_stats(&stats, &following);
+            }
+        }
+    });
+    if measure.is_active() {
+        follow_stats(&stats, &following);
+    }
+
+    let header = HeaderBar::new();
+    header.pack_start(&level);
+    header.pack_start(&measure);
+    header.pack_end(&copy);
+    let content = GtkBox::new(Orientation::Vertical, 0);
+    content.append(&stats);
+    content.append(&scroller);
+    let toasts = ToastOverlay::new();
+    toasts.set_child(Some(&content));
+    let view = ToolbarView::builder().content(&toasts).build();
+    view.add_top_bar(&header);
+
+    let window = adw::Window::builder()
+        .application(app)
+        .title("Debug Log")
+        .default_width(800)
+        .default_height(500)
+        .content(&view)
+        .build();
+
+    // Sequence number of the next event to show
+    let next = Rc::new(Cell::new(0));
+    let refresh = {
+        let lines = lines.clone();
+        let level = level.clone();
+        let scroller = scroller.clone();
+        let list = list.clone();
+        let next = Rc::clone(&next);
+        move |rebuild: bool| {
+            if rebuild {
+                lines.splice(0, lines.n_items(), &[]);
+                next.set(0);
+            }
+            let events = log_buffer().since(next.get(), selected_level(&level));
+            next.set(log_buffer().next_seq());
+            if events.is_empty() {
+                return;
+            }
+            let adjustment = scroller.vadjustment();
+            let at_bottom = adjustment.value() + adjustment.page_size() >= adjustment.upper() - 1.0;
+            let rendered: Vec<String> = events.iter().map(|event| event.render()).collect();
+            let added: Vec<&str> = rendered.iter().map(String::as_str).collect();
+            lines.splice(lines.n_items(), 0, &added);
+            let excess = lines.n_items().saturating_sub(LOG_CAPACITY as u32);
+            lines.splice(0, excess, &[]);
+            // Follow new events unless scrolled up to read older ones
+            if at_bottom && lines.n_items() > 0 {
+                list.scroll_to(lines.n_items() - 1, gtk4::ListScrollFlags::NONE, None);
+            }
+        }
+    };
+    refresh(true);
+    {
+        let refresh = refresh.clone();
+        level.connect_selected_notify(move |_| refresh(true));
+    }
+    APPEND.with(|append| append.replace(Some(Rc::new(move || refresh(false)))));
+    log_buffer().set_listener(Some(Arc::new(schedule_append)));
+    window.connect_destroy(|_| {
+        log_buffer().set_listener(None);
+        APPEND.with(|append| append.take());
+    });
+
+    copy.connect_clicked(move |button| copy_all(button, &toasts));
+
+    window.present();
+    WINDOW.with(|w| w.replace(Some(window.downgrade())));
+}
+
+/// Schedules an append of the new events on the main loop, unless one is
+/// pending already. Called by the log buffer from any thread.
+fn schedule_append() {
+    if APPEND_PENDING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    glib::idle_add_once(|| {
+        APPEND_PENDING.store(false, Ordering::Release);
+        if let Some(append) = APPEND.with(|append| append.borrow().clone()) {
+            append();
+        }
+    });
+}
+
+/// Shows the timings again every `STATS_INTERVAL_MS` while they are measured,
+/// unless `following` tells they are already.
+fn follow_stats(stats: &Label, following: &Rc<Cell<bool>>) {
+    stats.set_label(&frame_stats::current().lines().join("\n"));
+    if following.replace(true) {
+        return;
+    }
+    let weak = stats.downgrade();
+    let following = Rc::clone(following);
+    glib::timeout_add_local(
+        std::time::Duration::from_millis(STATS_INTERVAL_MS),
+        move || {
+            let Some(stats) = weak.upgrade().filter(|_| frame_stats::enabled()) else {
+                following.set(false);
+                return glib::ControlFlow::Break;
+            };
+            stats.set_label(&frame_stats::current().lines().join("\n"));
+            glib::ControlFlow::Continue
+        },
+    );
+}
+
+fn selected_level(level: &DropDown) -> Level {
+    LEVELS
+        .get(level.selected() as usize)
+        .map_or(Level::DEBUG, |&(_, level)| level)
+}
+
+/// Copies every event kept and the environment report. The report reads the
+/// config and the splits, so it is collected on a background thread.
+fn copy_all(button: &Button, toasts: &ToastOverlay) {
+    let button = button.clone();
+    let toasts = toasts.clone();
+    button.set_sensitive(false);
+    glib::MainContext::default().spawn_local(async move {
+        let report = gio::spawn_blocking(|| doctor::Report::collect_in_app().render())
+            .await
+            .unwrap_or_else(|_| "The environment report could not be collected.\n".to_owned());
+        let mut text: String = log_buffer()
+            .events(Level::TRACE)
+            .iter()
+            .map(|event| event.render() + "\n")
+            .collect();
+        text.push_str("\n--- tuxsplit --doctor ---\n");
+        text.push_str(&report);
+        button.clipboard().set_text(&text);
+        button.set_sensitive(true);
+        toasts.add_toast(Toast::new("Log and environment report copied"));
+    });
+}
@@ -0,0 +1,175 @@
+//! Drops onto the main window: a splits file is opened, and a text list of
+//! segment names, one per line, offers to create a new run from them.
+
+use adw::prelude::*;
+use adw::{AlertDialog, EntryRow, PreferencesGroup};
+use gtk4::{FileDialog, FileFilter, gdk, gio};
+use livesplit_core::run::saver::livesplit::save_run;
+use livesplit_core::{Run, TimerPhase};
+use tracing::warn;
+
+use std::path::PathBuf;
+
+use crate::categories::run_from_names;
+use crate::context::TuxSplitContext;
+use crate::error::TuxSplitError;
+use crate::paths;
+use crate::storage::write_atomic;
+use crate::ui::categories::confirm_unsaved;
+use crate::ui::editor::{SplitEditor, check_duplicate_names, check_sidecar};
+use crate::ui::error::show_error;
+use crate::utils::paste::normalize_name_lines;
+
+/// Names listed in the new run dialog before the rest is summarized.
+const LISTED_NAMES: usize = 5;
+
+/// Accepts files and text dropped on `window`.
+pub fn install(window: &adw::ApplicationWindow) {
+    let target = gtk4::DropTarget::new(glib::Type::INVALID, gdk::DragAction::COPY);
+    // Files first: file managers offer their paths as text too
+    target.set_types(&[gdk::FileList::static_type(), String::static_type()]);
+
+    let window_binding = window.clone();
+    target.connect_drop(move |_, value, _, _| {
+        if let Ok(files) = value.get::<gdk::FileList>() {
+            let Some(path) = files.files().first().and_then(gio::File::path) else {
+                return false;
+            };
+            open_dropped_splits(&window_binding, path);
+            return true;
+        }
+        let Ok(text) = value.get::<String>() else {
+            return false;
+        };
+        // Text dropped by accident must not end an attempt
+        let phase = TuxSplitContext::get_instance()
+            .timer()
+            .read()
+            .unwrap()
+            .current_phase();
+        if matches!(phase, TimerPhase::Running | TimerPhase::Paused) {
+            return false;
+        }
+        let names = normalize_name_lines(&text);
+        if names.is_empty() {
+            return false;
+        }
+        present_new_run_from_names(&window_binding, names);
+        true
+    });
+    window.add_controller(target);
+}
+
+fn open_dropped_splits(parent: &adw::ApplicationWindow, path: PathBuf) {
+    let parent_binding = parent.clone();
+    confirm_unsaved(parent, move || {
+        match TuxSplitContext::get_instance().load_splits(path.clone()) {
+            Ok(()) => {
+                check_sidecar(&parent_binding);
+                check_duplicate_names(&parent_binding);
+            }
+            Err(e) => show_error(&parent_binding, &e),
+        }
+    });
+}
+
+/// Asks for the game and category of a new run with a segment per name.
+fn present_new_run_from_names(parent: &adw::ApplicationWindow, names: Vec<String>) {
+    let game = EntryRow::builder().title("Game").build();
+    let category = EntryRow::builder().title("Category").build();
+    let group = PreferencesGroup::new();
+    group.add(&game);
+    group.add(&category);
+
+    let mut listed = names[..names.len().min(LISTED_NAMES)].join(", ");
+    if names.len() > LISTED_NAMES {
+        listed.push_str(&format!(" and {} more", names.len() - LISTED_NAMES));
+    }
+    let dialog = AlertDialog::builder()
+        .heading("New Run from Text")
+        .body(format!(
+            "Create a run with {} segments: {listed}.",
+            names.len()
+        ))
+        .extra_child(&group)
+        .default_response("create")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("create", "C_reate");
+    dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+    dialog.set_response_enabled("create", false);
+    let update = {
+        let dialog = dialog.clone();
+        let game = game.clone();
+        let category = category.clone();
+        move |_: &EntryRow| {
+            let complete = !game.text().trim().is_empty() && !category.text().trim().is_empty();
+            dialog.set_response_enabled("create", complete);
+        }
+    };
+    game.connect_changed(update.clone());
+    category.connect_changed(update);
+
+    let parent_binding = parent.clone();
+    dialog.connect_response(Some("create"), move |_, _| {
+        let run = run_from_names(&game.text(), &category.text(), &names);
+        save_new_run_as(&parent_binding, run);
+    });
+    dialog.present(Some(parent));
+}
+
+/// Asks where to save `run`, then loads it once the current run is safe to
+/// replace and opens the editor on it.
+fn save_new_run_as(parent: &adw::ApplicationWindow, run: Run) {
+    let lss_filter = FileFilter::new();
+    lss_filter.set_name(Some("LiveSplit Splits (*.lss)"));
+    lss_filter.add_pattern("*.lss");
+    let filters = gio::ListStore::new::<FileFilter>();
+    filters.append(&lss_filter);
+
+    let name = format!("{} - {}.lss", run.game_name(), run.category_name());
+    let dir = TuxSplitContext::get_instance()
+        .config()
+        .general
+        .splits
+        .as_deref()
+        .and_then(|splits| splits.parent())
+        .map_or_else(|| paths::current().root.clone(), PathBuf::from);
+    let file_dialog = FileDialog::builder()
+        .title("Save New Run")
+        .modal(true)
+        .filters(&filters)
+        .initial_name(name.replace(['/', '\\'], "-"))
+        .initial_folder(&gio::File::for_path(dir))
+        .build();
+
+    let parent_binding = parent.clone();
+    file_dialog.save(Some(parent), None::<&gio::Cancellable>, move |result| {
+        let Some(path) = result.ok().and_then(|file| file.path()) else {
+            return;
+        };
+        let parent = parent_binding.clone();
+        confirm_unsaved(&parent_binding, move || {
+            let mut lss = String::new();
+            if let Err(e) = save_run(&run, &mut lss) {
+                warn!("Could not write the new run: {e}");
+                return;
+            }
+            if let Err(source) = write_atomic(&path, lss) {
+                show_error(
+                    &parent,
+                    &TuxSplitError::Io {
+                        path: path.clone(),
+                        source,
+                    },
+                );
+                return;
+            }
+            match TuxSplitContext::get_instance().load_splits(path.clone()) {
+                Ok(()) => SplitEditor::new().present(),
+                Err(e) => show_error(&parent, &e),
+            }
+        });
+    });
+}
@@ -36,10 +36,10 @@ mod imp {
             let obj = self.obj();
 
             // Build buttons
-            let save_button = Button::with_label("Save");
+            let save_button = Button::with_mnemonic("_Save");
             save_button.add_css_class("suggested-action");
 
-            let cancel_button = Button::with_label("Cancel");
+            let cancel_button = Button::with_mnemonic("_Cancel");
 
             // Emit the same signal with a parameter indicating which one was pressed
             {
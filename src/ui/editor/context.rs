@@ -5,19 +5,43 @@ use glib::prelude::*;
 use glib::subclass::prelude::*;
 
 use glib::{Properties, subclass::signal::Signal};
-use livesplit_core::{RunEditor, TimeSpan, Timer, TimingMethod};
-
-use crate::context::TuxSplitContext;
+use livesplit_core::comparison::personal_best;
+use livesplit_core::{Run, RunEditor, TimeSpan, Timer, TimingMethod};
+
+use crate::context::{TuxSplitContext, today};
+use crate::utils::comparisons::set_comparison_segment_time;
+use crate::utils::golds::GoldRecalculation;
+use crate::utils::segment_ops::{EditorStep, SegmentOp, SegmentOpError, plan_segment_op};
+use crate::utils::time_shift::{ShiftScope, apply_time_shift, plan_time_shift};
+
+/// Records in the sidecar the day each best segment of `run` was set.
+fn set_gold_dates(run: &Run, dates: impl IntoIterator<Item = (usize, Option<String>)>) {
+    let ctx = TuxSplitContext::get_instance();
+    let mut sidecar = ctx.sidecar_mut();
+    for (index, date) in dates {
+        if let Some(segment) = run.segments().get(index) {
+            sidecar.set_gold_date(segment.name(), index, date);
+        }
+    }
+}
 
 pub enum SegmentMoveDirection {
     Up,
     Down,
 }
 
+/// The editable time columns of the segments table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeColumn {
+    SplitTime,
+    SegmentTime,
+    Best,
+}
+
 mod imp {
     use super::{
         Cell, DerivedObjectProperties, ObjectImpl, ObjectImplExt, ObjectSubclass, OnceLock,
-        Properties, Signal, TimingMethod,
+        Properties, RefCell, Signal, TimingMethod, personal_best,
     };
 
     #[derive(Properties)]
@@ -25,12 +49,15 @@ mod imp {
     pub struct EditorContext {
         // Timing method used for edits: 0 = RealTime, 1 = GameTime
         pub timing_method: Cell<i32>,
+        // Comparison whose split and segment times are edited
+        pub comparison: RefCell<String>,
     }
 
     impl Default for EditorContext {
         fn default() -> Self {
             Self {
                 timing_method: Cell::new(0), // Default to RealTime
+                comparison: RefCell::new(personal_best::NAME.to_owned()),
             }
         }
     }
@@ -74,6 +101,8 @@ mod imp {
                     Signal::builder("run-changed").action().build(),
                     // Emitted whenever the timing method used for edits changes.
                     Signal::builder("timing-method-changed").action().build(),
+                    // Emitted whenever the comparison whose times are edited changes.
+                    Signal::builder("comparison-changed").action().build(),
                 ]
             })
         }
@@ -105,6 +134,21 @@ impl EditorContext {
         }
     }
 
+    /// Gets the comparison whose split and segment times are edited.
+    pub fn comparison(&self) -> String {
+        self.imp().comparison.borrow().clone()
+    }
+
+    /// Sets the comparison whose split and segment times are edited and emits a
+    /// change signal if it changed.
+    pub fn set_comparison(&self, comparison: &str) {
+        if *self.imp().comparison.borrow() == comparison {
+            return;
+        }
+        self.imp().comparison.replace(comparison.to_owned());
+        self.emit_by_name::<()>("comparison-changed", &[]);
+    }
+
     /// Emits the "run-changed" signal to notify listeners a mutation occurred.
     pub fn emit_run_changed(&self) {
         self.emit_by_name::<()>("run-changed", &[]);
@@ -128,8 +172,102 @@ impl EditorContext {
             return;
         }
 
+        ctx.sidecar_mut()
+            .rename_segment(run.segments()[index].name(), &name);
         run.segments_mut()[index].set_name(name);
-        ctx.set_run(run);
+        ctx.set_edited_run(run);
+
+        self.emit_run_changed();
+    }
+
+    /// Applies a batch of renames `(index, old, new)` in a single `RunEditor` pass,
+    /// emitting "run-changed" once. Out of bounds entries are ignored.
+    pub fn rename_segments(&self, plan: &[(usize, String, String)]) {
+        if plan.is_empty() {
+            return;
+        }
+
+        let ctx = TuxSplitContext::get_instance();
+
+        let mut run_editor = RunEditor::new(ctx.get_run()).ok().unwrap();
+        let len = run_editor.run().segments().len();
+        for (index, old_name, new_name) in plan {
+            if *index >= len {
+                continue;
+            }
+            run_editor.select_only(*index);
+            run_editor.active_segment().set_name(new_name.as_str());
+            ctx.sidecar_mut().rename_segment(old_name, new_name);
+        }
+
+        ctx.set_edited_run(run_editor.close());
+
+        self.emit_run_changed();
+    }
+
+    /// Renames segments that share a name to the names planned by
+    /// `plan_disambiguation`. Sidecar data is copied rather than moved, since the
+    /// first segment keeps the old name.
+    pub fn disambiguate_segments(&self, plan: &[(usize, String)]) {
+        if plan.is_empty() {
+            return;
+        }
+
+        let ctx = TuxSplitContext::get_instance();
+
+        let Ok(mut run_editor) = RunEditor::new(ctx.get_run()) else {
+            return;
+        };
+        for (index, new_name) in plan {
+            let Some(old_name) = run_editor
+                .run()
+                .segments()
+                .get(*index)
+                .map(|segment| segment.name().to_owned())
+            else {
+                continue;
+            };
+            run_editor.select_only(*index);
+            run_editor.active_segment().set_name(new_name.as_str());
+            ctx.sidecar_mut().copy_segment(&old_name, new_name);
+        }
+
+        ctx.set_edited_run(run_editor.close());
+
+        self.emit_run_changed();
+    }
+
+    /// Applies best segments recomputed by `recalculate_golds`. Entries without
+    /// a new time keep their best segment. "run-changed" is emitted once.
+    pub fn apply_recalculated_golds(&self, recalculated: &[GoldRecalculation]) {
+        let ctx = TuxSplitContext::get_instance();
+
+        let Ok(mut run_editor) = RunEditor::new(ctx.get_run()) else {
+            return;
+        };
+        let mut dates = Vec::new();
+        for recalculation in recalculated.iter().filter(|r| r.changes()) {
+            if recalculation.index >= run_editor.run().segments().len() {
+                continue;
+            }
+            run_editor.select_timing_method(recalculation.method);
+            run_editor.select_only(recalculation.index);
+            run_editor
+                .active_segment()
+                .set_best_segment_time(recalculation.new);
+            // Golds the history cannot date lose their date
+            dates.push((
+                recalculation.index,
+                recalculation.date.map(|date| date.to_string()),
+            ));
+        }
+        if dates.is_empty() {
+            return;
+        }
+
+        let run = run_editor.close();
+        set_gold_dates(&run, dates);
+        ctx.set_edited_run(run);
 
         self.emit_run_changed();
     }
@@ -137,7 +275,7 @@ impl EditorContext {
     /// Sets the split time at `index` in milliseconds for the current timing method.
     /// Returns true if the operation succeeded.
     ///
-    /// Uses `RunEditor` to set the "Personal Best" comparison time, mirroring table.rs.
+    /// Uses `RunEditor` to set the time of the edited comparison, mirroring table.rs.
     pub fn set_split_time_ms(&self, index: usize, ms: i64) {
         if ms < 0 {
             return;
@@ -153,12 +291,12 @@ impl EditorContext {
         run_editor.select_additionally(index);
         run_editor.select_timing_method(self.timing_method());
         run_editor.active_segment().set_comparison_time(
-            "Personal Best",
+            &self.comparison(),
             Some(TimeSpan::from_milliseconds(ms as f64)),
         );
         run_editor.unselect(index);
 
-        ctx.set_run(run_editor.close());
+        ctx.set_edited_run(run_editor.close());
 
         self.emit_run_changed();
     }
@@ -166,7 +304,8 @@ impl EditorContext {
     /// Sets the segment time at `index` in milliseconds for the current timing method.
     /// Returns true if the operation succeeded.
     ///
-    /// Uses `RunEditor.active_segment().set_segment_time()`, mirroring table.rs.
+    /// Uses `RunEditor.active_segment().set_segment_time()` for the PB, mirroring
+    /// table.rs, and `set_comparison_segment_time` for the other comparisons.
     pub fn set_segment_time_ms(&self, index: usize, ms: i64) {
         if ms < 0 {
             return;
@@ -174,19 +313,25 @@ impl EditorContext {
 
         let ctx = TuxSplitContext::get_instance();
 
-        let mut run_editor = RunEditor::new(ctx.get_run().to_owned()).ok().unwrap();
-        if index >= run_editor.run().segments().len() {
+        let mut run = ctx.get_run();
+        if index >= run.segments().len() {
             return;
         }
 
-        run_editor.select_additionally(index);
-        run_editor.select_timing_method(self.timing_method());
-        run_editor
-            .active_segment()
-            .set_segment_time(Some(TimeSpan::from_milliseconds(ms as f64)));
-        run_editor.unselect(index);
+        let comparison = self.comparison();
+        let time = TimeSpan::from_milliseconds(ms as f64);
+        if comparison == personal_best::NAME {
+            let mut run_editor = RunEditor::new(run).ok().unwrap();
+            run_editor.select_additionally(index);
+            run_editor.select_timing_method(self.timing_method());
+            run_editor.active_segment().set_segment_time(Some(time));
+            run_editor.unselect(index);
+            run = run_editor.close();
+        } else {
+            set_comparison_segment_time(&mut run, &comparison, self.timing_method(), index, time);
+        }
 
-        ctx.set_run(run_editor.close());
+        ctx.set_edited_run(run);
 
         self.emit_run_changed();
     }
@@ -212,41 +357,160 @@ impl EditorContext {
             .segment_mut(index)
             .best_segment_time_mut()
             .with_timing_method(method, Some(TimeSpan::from_milliseconds(ms as f64)));
+        set_gold_dates(&run, [(index, today())]);
 
-        ctx.set_run(run);
+        ctx.set_edited_run(run);
 
         self.emit_run_changed();
     }
 
-    /// Moves a given segment up/down by one position.
-    pub fn move_segment(&self, index: usize, direction: SegmentMoveDirection) {
+    /// Sets many times of one column at once, as (index, milliseconds) pairs, for the
+    /// current timing method. Negative and out of bounds entries are ignored.
+    ///
+    /// All values are applied to a single copy of the run, so "run-changed" is emitted once.
+    pub fn set_times_ms(&self, column: TimeColumn, values: &[(usize, i64)]) {
         let ctx = TuxSplitContext::get_instance();
+        let method = self.timing_method();
+        let comparison = self.comparison();
 
-        let mut run_editor = RunEditor::new(ctx.get_run().to_owned()).ok().unwrap();
-        run_editor.select_only(index);
+        let mut run = ctx.get_run();
+        let len = run.segments().len();
+        let values: Vec<(usize, TimeSpan)> = values
+            .iter()
+            .filter(|(index, ms)| *index < len && *ms >= 0)
+            .map(|&(index, ms)| (index, TimeSpan::from_milliseconds(ms as f64)))
+            .collect();
+        if values.is_empty() {
+            return;
+        }
 
-        match direction {
-            SegmentMoveDirection::Up => {
-                if run_editor.can_move_segments_up() {
-                    run_editor.move_segments_up();
-                } else {
-                    return;
-                }
+        if column == TimeColumn::Best {
+            for &(index, time) in &values {
+                *run.segment_mut(index).best_segment_time_mut() = run
+                    .segment_mut(index)
+                    .best_segment_time_mut()
+                    .with_timing_method(method, Some(time));
             }
-            SegmentMoveDirection::Down => {
-                if run_editor.can_move_segments_down() {
-                    run_editor.move_segments_down();
+            set_gold_dates(&run, values.iter().map(|&(index, _)| (index, today())));
+        } else if column == TimeColumn::SegmentTime && comparison != personal_best::NAME {
+            // Segment times depend on the previous split, so apply them top to bottom
+            for (index, time) in values {
+                set_comparison_segment_time(&mut run, &comparison, method, index, time);
+            }
+        } else {
+            let mut run_editor = RunEditor::new(run).ok().unwrap();
+            run_editor.select_timing_method(method);
+            // Segment times depend on the previous split, so apply them top to bottom
+            for (index, time) in values {
+                run_editor.select_only(index);
+                if column == TimeColumn::SplitTime {
+                    run_editor
+                        .active_segment()
+                        .set_comparison_time(&comparison, Some(time));
                 } else {
-                    return;
+                    run_editor.active_segment().set_segment_time(Some(time));
                 }
             }
+            run = run_editor.close();
+        }
+
+        ctx.set_edited_run(run);
+
+        self.emit_run_changed();
+    }
+
+    /// Flags the segment at `index` as excluded from gold detection and time save
+    /// estimates. The flag is kept in the run's sidecar, keyed by segment name.
+    pub fn set_segment_excluded(&self, index: usize, excluded: bool) {
+        let ctx = TuxSplitContext::get_instance();
+
+        let Some(name) = ctx
+            .get_run()
+            .segments()
+            .get(index)
+            .map(|segment| segment.name().to_owned())
+        else {
+            return;
+        };
+        if ctx.sidecar().is_excluded(&name) == excluded {
+            return;
         }
 
-        ctx.set_run(run_editor.close());
+        ctx.sidecar_mut().set_excluded(&name, index, excluded);
+        // The sidecar is written along with the splits
+        ctx.mark_run_modified();
+        self.emit_global_run_changed();
+
+        self.emit_run_changed();
+    }
+
+    /// Sets when to split at the segment at `index` (see
+    /// `RunSidecar::set_split_on`), kept in the run's sidecar like exclusions.
+    pub fn set_segment_split_on(&self, index: usize, text: &str) {
+        let ctx = TuxSplitContext::get_instance();
+
+        let Some(name) = ctx
+            .get_run()
+            .segments()
+            .get(index)
+            .map(|segment| segment.name().to_owned())
+        else {
+            return;
+        };
+        let before = ctx.sidecar().split_on(&name).map(str::to_owned);
+        ctx.sidecar_mut().set_split_on(&name, index, text);
+        if ctx.sidecar().split_on(&name) == before.as_deref() {
+            return;
+        }
+        ctx.mark_run_modified();
+        self.emit_global_run_changed();
 
         self.emit_run_changed();
     }
 
+    /// Replaces the run's pre-flight checklist with `items` (see
+    /// `RunSidecar::set_preflight`).
+    pub fn set_preflight(&self, items: &[&str]) {
+        let ctx = TuxSplitContext::get_instance();
+
+        let before = ctx.sidecar().preflight.clone();
+        ctx.sidecar_mut().set_preflight(items);
+        if ctx.sidecar().preflight == before {
+            return;
+        }
+        ctx.mark_run_modified();
+        self.emit_global_run_changed();
+    }
+
+    /// Removes or moves the `selected` segments as one change of the run (see
+    /// `plan_segment_op`). Returns the rows to select afterwards.
+    pub fn apply_segment_op(
+        &self,
+        selected: &[usize],
+        op: SegmentOp,
+    ) -> Result<Vec<usize>, SegmentOpError> {
+        let ctx = TuxSplitContext::get_instance();
+        let run = ctx.get_run();
+        let plan = plan_segment_op(selected, run.segments().len(), op)?;
+
+        let mut run_editor =
+            RunEditor::new(run).map_err(|e| SegmentOpError::EditorUnavailable(e.to_string()))?;
+        for step in plan.steps {
+            match step {
+                EditorStep::SelectOnly(index) => run_editor.select_only(index),
+                EditorStep::SelectAdditionally(index) => run_editor.select_additionally(index),
+                EditorStep::RemoveSegments => run_editor.remove_segments(),
+                EditorStep::MoveSegmentsUp => run_editor.move_segments_up(),
+                EditorStep::MoveSegmentsDown => run_editor.move_segments_down(),
+            }
+        }
+
+        ctx.set_edited_run(run_editor.close());
+
+        self.emit_run_changed();
+        Ok(plan.selection)
+    }
+
     pub fn add_segment(&self, index: usize, direction: SegmentMoveDirection) {
         let ctx = TuxSplitContext::get_instance();
 
@@ -262,24 +526,86 @@ impl EditorContext {
             }
         }
 
-        ctx.set_run(run_editor.close());
+        ctx.set_edited_run(run_editor.close());
 
         self.emit_run_changed();
     }
 
-    pub fn remove_segment(&self, index: usize) {
+    /// Shifts the times of `scope` by `offset` (see `utils::time_shift`), as one
+    /// change of the run. The run is kept as a snapshot first, so the shift can
+    /// be undone even when the editor is saved along with other changes.
+    pub fn shift_times(&self, offset: TimeSpan, scope: &ShiftScope) {
         let ctx = TuxSplitContext::get_instance();
+        let method = self.timing_method();
 
-        let mut run_editor = RunEditor::new(ctx.get_run()).ok().unwrap();
-        run_editor.select_only(index);
+        let run = ctx.get_run();
+        let edits = plan_time_shift(&run, method, offset.to_duration(), scope);
+        if edits.is_empty() {
+            return;
+        }
+        ctx.take_run_snapshot(&run, "Time shift");
 
-        if run_editor.can_remove_segments() {
-            run_editor.remove_segments();
-        } else {
+        ctx.set_edited_run(apply_time_shift(run, method, &edits));
+
+        self.emit_run_changed();
+    }
+
+    /// Sets the time the timer starts at. Negative offsets delay the first segment.
+    pub fn set_offset(&self, offset: TimeSpan) {
+        let ctx = TuxSplitContext::get_instance();
+
+        let Ok(mut run_editor) = RunEditor::new(ctx.get_run()) else {
+            return;
+        };
+        if run_editor.run().offset() == offset {
+            return;
+        }
+        run_editor.set_offset(offset);
+
+        ctx.set_edited_run(run_editor.close());
+
+        self.emit_run_changed();
+    }
+
+    /// Sets the attempt count shown for the run. The attempt history is left untouched.
+    pub fn set_attempt_count(&self, attempts: u32) {
+        let ctx = TuxSplitContext::get_instance();
+
+        let Ok(mut run_editor) = RunEditor::new(ctx.get_run()) else {
+            return;
+        };
+        if run_editor.run().attempt_count() == attempts {
+            return;
+        }
+        run_editor.set_attempt_count(attempts);
+
+        ctx.set_edited_run(run_editor.close());
+
+        self.emit_run_changed();
+    }
+
+    /// Sets the icons of segments from image data `(index, data)`, in a single
+    /// `RunEditor` pass. Out of bounds entries are ignored.
+    pub fn set_segment_icons(&self, icons: &[(usize, Vec<u8>)]) {
+        if icons.is_empty() {
+            return;
+        }
+
+        let ctx = TuxSplitContext::get_instance();
+
+        let Ok(mut run_editor) = RunEditor::new(ctx.get_run()) else {
             return;
+        };
+        let len = run_editor.run().segments().len();
+        for (index, data) in icons {
+            if *index >= len {
+                continue;
+            }
+            run_editor.select_only(*index);
+            run_editor.active_segment().set_icon(data);
         }
 
-        ctx.set_run(run_editor.close());
+        ctx.set_edited_run(run_editor.close());
 
         self.emit_run_changed();
     }
@@ -451,6 +777,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn time_setters_edit_the_picked_comparison() {
+        {
+            let mut run = Run::new();
+            run.push_segment(Segment::new("A"));
+            run.push_segment(Segment::new("B"));
+            run.add_custom_comparison("Target").unwrap();
+            TuxSplitContext::get_instance().set_run(run);
+        }
+        let ctx = EditorContext::new();
+        ctx.set_comparison("Target");
+
+        ctx.set_split_time_ms(0, 1000);
+        ctx.set_segment_time_ms(1, 2500);
+
+        let run = TuxSplitContext::get_instance().get_run();
+        let ms = |index: usize, comparison: &str| {
+            run.segment(index)
+                .comparison_timing_method(comparison, TimingMethod::RealTime)
+                .map(|time| time.to_duration().whole_milliseconds())
+        };
+        assert_eq!(ms(0, "Target"), Some(1000));
+        assert_eq!(ms(1, "Target"), Some(3500));
+        assert_eq!(ms(0, "Personal Best"), None);
+        assert_eq!(ms(1, "Personal Best"), None);
+    }
+
     #[test]
     fn best_time_setter_handles_negative_out_of_bounds_and_updates_method() {
         {
@@ -524,4 +877,214 @@ mod tests {
         ctx.set_split_time_ms(10, 100);
         assert_eq!(count.get(), 1);
     }
+
+    #[test]
+    fn selections_are_removed_and_moved_with_a_single_emission() {
+        {
+            let mut run = Run::new();
+            for name in ["A", "B", "C", "D", "E"] {
+                run.push_segment(Segment::new(name));
+            }
+            TuxSplitContext::get_instance().set_run(run);
+        }
+        let ctx = EditorContext::new();
+        let count = Rc::new(Cell::new(0));
+        let c2 = count.clone();
+        ctx.connect_local("run-changed", false, move |_v| {
+            c2.set(c2.get() + 1);
+            None
+        });
+        let names = || -> Vec<String> {
+            TuxSplitContext::get_instance()
+                .get_run()
+                .segments()
+                .iter()
+                .map(|segment| segment.name().to_owned())
+                .collect()
+        };
+
+        assert_eq!(
+            ctx.apply_segment_op(&[1, 3], SegmentOp::MoveDown),
+            Ok(vec![2, 4])
+        );
+        assert_eq!(names(), ["A", "C", "B", "E", "D"]);
+        assert_eq!(count.get(), 1);
+
+        assert_eq!(
+            ctx.apply_segment_op(&[0, 2, 4], SegmentOp::Remove),
+            Ok(vec![0])
+        );
+        assert_eq!(names(), ["C", "E"]);
+        assert_eq!(count.get(), 2);
+
+        // Refused: the run is left as it was, with no emission
+        assert_eq!(
+            ctx.apply_segment_op(&[0, 1], SegmentOp::Remove),
+            Err(SegmentOpError::RemovesEverySegment)
+        );
+        assert_eq!(names(), ["C", "E"]);
+        assert_eq!(count.get(), 2);
+    }
+
+    #[test]
+    fn batch_time_setter_applies_all_values_with_a_single_emission() {
+        {
+            let mut run = Run::new();
+            for name in ["A", "B", "C"] {
+                run.push_segment(Segment::new(name));
+            }
+            TuxSplitContext::get_instance().set_run(run);
+        }
+        let ctx = EditorContext::new();
+        ctx.set_timing_method(TimingMethod::RealTime);
+
+        let count = Rc::new(Cell::new(0));
+        let c2 = count.clone();
+        ctx.connect_local("run-changed", false, move |_v| {
+            c2.set(c2.get() + 1);
+            None
+        });
+
+        // Negative and out of bounds entries are dropped, the rest lands in one pass
+        ctx.set_times_ms(
+            TimeColumn::SplitTime,
+            &[(0, 1000), (1, -5), (2, 3000), (7, 100)],
+        );
+        assert_eq!(count.get(), 1);
+        {
+            let run = TuxSplitContext::get_instance().get_run();
+            let pb = |i: usize| {
+                run.segments()[i]
+                    .comparison_timing_method("Personal Best", TimingMethod::RealTime)
+                    .map(|t| t.to_duration().whole_milliseconds())
+            };
+            assert_eq!(pb(0), Some(1000));
+            assert_eq!(pb(1), None);
+            assert_eq!(pb(2), Some(3000));
+        }
+
+        ctx.set_times_ms(TimeColumn::Best, &[(0, 900), (1, 1200)]);
+        assert_eq!(count.get(), 2);
+        {
+            let run = TuxSplitContext::get_instance().get_run();
+            let best = |i: usize| {
+                run.segments()[i]
+                    .best_segment_time()
+                    .real_time
+                    .map(|t| t.to_duration().whole_milliseconds())
+            };
+            assert_eq!(best(0), Some(900));
+            assert_eq!(best(1), Some(1200));
+        }
+
+        // Nothing valid to apply: no emission
+        ctx.set_times_ms(TimeColumn::SegmentTime, &[(9, 100), (0, -1)]);
+        assert_eq!(count.get(), 2);
+    }
+
+    #[test]
+    fn recalculated_golds_are_applied_per_method_with_a_single_emission() {
+        {
+            let mut run = Run::new();
+            for name in ["A", "B"] {
+                let mut segment = Segment::new(name);
+                segment.set_best_segment_time(
+                    livesplit_core::Time::new()
+                        .with_real_time(Some(TimeSpan::from_seconds(50.0)))
+                        .with_game_time(Some(TimeSpan::from_seconds(45.0))),
+                );
+                run.push_segment(segment);
+            }
+            TuxSplitContext::get_instance().set_run(run);
+        }
+        let ctx = EditorContext::new();
+
+        let count = Rc::new(Cell::new(0));
+        let c2 = count.clone();
+        ctx.connect_local("run-changed", false, move |_v| {
+            c2.set(c2.get() + 1);
+            None
+        });
+
+        let recalculation = |index, method, new: Option<f64>| GoldRecalculation {
+            index,
+            method,
+            old: None,
+            new: new.map(TimeSpan::from_seconds),
+            attempt: Some(1),
+            date: None,
+        };
+        ctx.apply_recalculated_golds(&[
+            recalculation(0, TimingMethod::RealTime, Some(40.0)),
+            recalculation(0, TimingMethod::GameTime, Some(35.0)),
+            // No history: kept
+            recalculation(1, TimingMethod::RealTime, None),
+        ]);
+        assert_eq!(count.get(), 1);
+
+        let run = TuxSplitContext::get_instance().get_run();
+        let best = |i: usize, method| {
+            run.segments()[i].best_segment_time()[method].map(|t| t.total_seconds())
+        };
+        assert_eq!(best(0, TimingMethod::RealTime), Some(40.0));
+        assert_eq!(best(0, TimingMethod::GameTime), Some(35.0));
+        assert_eq!(best(1, TimingMethod::RealTime), Some(50.0));
+
+        // Only flagged entries: nothing to apply, no emission
+        ctx.apply_recalculated_golds(&[recalculation(1, TimingMethod::RealTime, None)]);
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn exclusion_flag_survives_reordering_and_renames() {
+        {
+            let mut run = Run::new();
+            run.push_segment(Segment::new("Intro"));
+            run.push_segment(Segment::new("Credits"));
+            TuxSplitContext::get_instance().set_run(run);
+        }
+        let ctx = EditorContext::new();
+        let global = TuxSplitContext::get_instance();
+
+        ctx.set_segment_excluded(1, true);
+        let flags =
+            |global: &TuxSplitContext| global.excluded_segments(global.get_run().segments());
+        assert_eq!(flags(&global), vec![false, true]);
+
+        assert_eq!(ctx.apply_segment_op(&[1], SegmentOp::MoveUp), Ok(vec![0]));
+        assert_eq!(flags(&global), vec![true, false]);
+
+        ctx.set_segment_name(0, "Ending".to_owned());
+        assert_eq!(flags(&global), vec![true, false]);
+
+        ctx.set_segment_excluded(0, false);
+        assert_eq!(flags(&global), vec![false, false]);
+    }
+
+    #[test]
+    fn offset_and_attempt_count_setters_emit_only_on_change() {
+        {
+            let mut run = Run::new();
+            run.push_segment(Segment::new("A"));
+            TuxSplitContext::get_instance().set_run(run);
+        }
+        let ctx = EditorContext::new();
+
+        let count = Rc::new(Cell::new(0));
+        let c2 = count.clone();
+        ctx.connect_local("run-changed", false, move |_v| {
+            c2.set(c2.get() + 1);
+            None
+        });
+
+        ctx.set_offset(TimeSpan::from_seconds(-5.0));
+        ctx.set_offset(TimeSpan::from_seconds(-5.0));
+        ctx.set_attempt_count(42);
+        ctx.set_attempt_count(42);
+        assert_eq!(count.get(), 2);
+
+        let run = TuxSplitContext::get_instance().get_run();
+        assert_eq!(run.offset(), TimeSpan::from_seconds(-5.0));
+        assert_eq!(run.attempt_count(), 42);
+    }
 }
@@ -0,0 +1,106 @@
+use adw::prelude::*;
+use adw::{ActionRow, AlertDialog};
+use gtk4::{Image, ListBox, ScrolledWindow, SelectionMode};
+use livesplit_core::{TimeSpan, TimingMethod};
+
+use crate::context::TuxSplitContext;
+use crate::formatters::TimeFormat;
+use crate::ui::editor::EditorContext;
+use crate::utils::golds::{GoldRecalculation, recalculate_golds};
+
+/// Presents the "Recalculate golds from history" dialog, previewing every best
+/// segment that changes before anything is applied.
+pub fn present_recalculate_golds_dialog(parent: &impl IsA<gtk4::Widget>, context: &EditorContext) {
+    let run = TuxSplitContext::get_instance().get_run();
+    let recalculated = recalculate_golds(&run);
+    let changes = recalculated.iter().filter(|r| r.changes()).count();
+
+    let dialog = AlertDialog::builder()
+        .heading("Recalculate Golds")
+        .body(if changes == 0 {
+            "Every best segment already matches the fastest time in its history.".to_owned()
+        } else {
+            format!(
+                "{changes} best segment times will be replaced with the fastest time in their history."
+            )
+        })
+        .default_response("apply")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("apply", "_Recalculate");
+    dialog.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+    dialog.set_response_enabled("apply", changes > 0);
+
+    let preview = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    let scroller = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .min_content_height(200)
+        .child(&preview)
+        .build();
+    let names: Vec<&str> = run.segments().iter().map(|s| s.name()).collect();
+    for recalculation in &recalculated {
+        preview.append(&preview_row(names[recalculation.index], recalculation));
+    }
+    if recalculated.is_empty() {
+        preview.append(
+            &ActionRow::builder()
+                .title("No segments affected")
+                .css_classes(["dim-label"])
+                .build(),
+        );
+    }
+    dialog.set_extra_child(Some(&scroller));
+
+    let context = context.clone();
+    dialog.connect_response(Some("apply"), move |_, _| {
+        context.apply_recalculated_golds(&recalculated);
+    });
+
+    dialog.present(Some(parent));
+}
+
+fn preview_row(name: &str, recalculation: &GoldRecalculation) -> ActionRow {
+    let method = match recalculation.method {
+        TimingMethod::RealTime => "Real Time",
+        TimingMethod::GameTime => "Game Time",
+    };
+    let title = format!(
+        "{}. {} ({method})",
+        recalculation.index + 1,
+        glib::markup_escape_text(name)
+    );
+
+    let row = ActionRow::builder().title(title).build();
+    match (recalculation.new, recalculation.attempt) {
+        (Some(new), Some(attempt)) => {
+            let date = recalculation
+                .date
+                .map_or_else(|| "undated".to_owned(), |date| date.to_string());
+            row.set_subtitle(&format!(
+                "{} → {} (attempt {attempt}, {date})",
+                format_gold(recalculation.old),
+                format_gold(Some(new))
+            ));
+        }
+        _ => {
+            row.set_subtitle(&format!(
+                "No valid time in the history, keeping {}",
+                format_gold(recalculation.old)
+            ));
+            let icon = Image::builder()
+                .icon_name("dialog-warning-symbolic")
+                .css_classes(["warning"])
+                .build();
+            row.add_suffix(&icon);
+        }
+    }
+    row
+}
+
+fn format_gold(time: Option<TimeSpan>) -> String {
+    TimeFormat::new(true, true, true, true, 3, false).format_time_span_opt(time)
+}
@@ -0,0 +1,151 @@
+use adw::prelude::*;
+use adw::{ActionRow, AlertDialog};
+use gtk4::gdk_pixbuf::Pixbuf;
+use gtk4::{FileDialog, Image, ListBox, ScrolledWindow, SelectionMode};
+use tracing::warn;
+
+use std::path::{Path, PathBuf};
+
+use crate::context::TuxSplitContext;
+use crate::ui::editor::EditorContext;
+use crate::utils::icons::{IconMatch, list_images, match_icons};
+
+/// Images wider or taller than this are scaled down before being embedded, to
+/// keep the splits file small.
+const MAX_ICON_SIZE: i32 = 128;
+
+/// Asks for a folder of images named after the segments, then previews which
+/// segments get an icon before anything is applied.
+pub fn present_import_icons_dialog(parent: &impl IsA<gtk4::Widget>, context: &EditorContext) {
+    let file_dialog = FileDialog::builder()
+        .title("Import Icons from Folder")
+        .modal(true)
+        .build();
+    let parent_binding = parent.as_ref().clone();
+    let context = context.clone();
+    file_dialog.select_folder(
+        parent.root().and_downcast::<gtk4::Window>().as_ref(),
+        None::<&gtk4::gio::Cancellable>,
+        move |result| {
+            if let Ok(folder) = result
+                && let Some(path) = folder.path()
+            {
+                present_preview(&parent_binding, &context, &path);
+            }
+        },
+    );
+}
+
+fn present_preview(parent: &gtk4::Widget, context: &EditorContext, folder: &Path) {
+    let files = list_images(folder).unwrap_or_else(|e| {
+        warn!("Could not read {}: {e}", folder.display());
+        Vec::new()
+    });
+    let run = TuxSplitContext::get_instance().get_run();
+    let names: Vec<&str> = run.segments().iter().map(|s| s.name()).collect();
+    let matches = match_icons(&names, &files);
+    let matched = matches.iter().flatten().count();
+
+    let dialog = AlertDialog::builder()
+        .heading("Import Icons")
+        .body(format!(
+            "{matched} of {} segments have an image named after them in {}. \
+             The others keep their icon.",
+            names.len(),
+            folder.display()
+        ))
+        .default_response("apply")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("apply", "_Import");
+    dialog.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+    dialog.set_response_enabled("apply", matched > 0);
+
+    let preview = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    let scroller = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .min_content_height(200)
+        .child(&preview)
+        .build();
+    for (index, (name, icon)) in names.iter().zip(&matches).enumerate() {
+        preview.append(&preview_row(index, name, icon.as_ref()));
+    }
+    dialog.set_extra_child(Some(&scroller));
+
+    let context = context.clone();
+    let chosen: Vec<(usize, PathBuf)> = matches
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, icon)| icon.map(|icon| (index, icon.file)))
+        .collect();
+    dialog.connect_response(Some("apply"), move |_, _| {
+        let icons: Vec<(usize, Vec<u8>)> = chosen
+            .iter()
+            .filter_map(|(index, file)| match load_icon(file) {
+                Ok(data) => Some((*index, data)),
+                Err(e) => {
+                    warn!("Could not load the icon {}: {e}", file.display());
+                    None
+                }
+            })
+            .collect();
+        context.set_segment_icons(&icons);
+    });
+
+    dialog.present(Some(parent));
+}
+
+fn preview_row(index: usize, name: &str, icon: Option<&IconMatch>) -> ActionRow {
+    let row = ActionRow::builder()
+        .title(format!("{}. {}", index + 1, glib::markup_escape_text(name)))
+        .build();
+    let Some(icon) = icon else {
+        row.set_subtitle("No matching image");
+        row.add_css_class("dim-label");
+        return row;
+    };
+
+    row.add_prefix(
+        &Image::builder()
+            .file(icon.file.to_string_lossy().as_ref())
+            .pixel_size(24)
+            .build(),
+    );
+    let file_name = |file: &Path| {
+        file.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    row.set_subtitle(&glib::markup_escape_text(&file_name(&icon.file)));
+    if !icon.ignored.is_empty() {
+        let ignored: Vec<String> = icon.ignored.iter().map(|file| file_name(file)).collect();
+        warn!(
+            "Several images match the segment {name}, using {}",
+            file_name(&icon.file)
+        );
+        let warning = Image::builder()
+            .icon_name("dialog-warning-symbolic")
+            .css_classes(["warning"])
+            .tooltip_text(format!("Also matching, ignored: {}", ignored.join(", ")))
+            .build();
+        row.add_suffix(&warning);
+    }
+    row
+}
+
+/// The data of the image at `path`, as PNG scaled to fit `MAX_ICON_SIZE` when it
+/// is larger, or else as it is on disk.
+fn load_icon(path: &Path) -> Result<Vec<u8>, String> {
+    let (_, width, height) =
+        Pixbuf::file_info(path).ok_or_else(|| "not a supported image".to_owned())?;
+    if width <= MAX_ICON_SIZE && height <= MAX_ICON_SIZE {
+        return std::fs::read(path).map_err(|e| e.to_string());
+    }
+    Pixbuf::from_file_at_scale(path, MAX_ICON_SIZE, MAX_ICON_SIZE, true)
+        .and_then(|pixbuf| pixbuf.save_to_bufferv("png", &[]))
+        .map_err(|e| e.to_string())
+}
@@ -1,27 +1,64 @@
 mod action_bar;
 mod context;
+mod golds;
+mod icons;
 mod model;
+mod naming;
+mod preview;
 mod row;
+mod shift;
 mod table;
 pub use context::EditorContext;
 pub use model::SegmentsModel;
+pub use naming::{check_duplicate_names, check_sidecar};
 
 use crate::context::TuxSplitContext;
+use crate::editor_draft::{self, DraftDecision};
+use crate::formatters::time::parse_signed_time;
+use crate::sidecar::RunSidecar;
+use crate::ui::editor::preview::TimerPreview;
 use crate::ui::editor::table::SegmentsEditor;
-use gtk4::{ActionBar, StringList};
+use crate::utils::auto_splitter_settings::SettingValue;
+use gtk4::{ActionBar, StringList, ToggleButton};
 use livesplit_core::{Run, TimeSpan};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use std::sync::{Arc, RwLock};
+use tracing::{info, warn};
 
 use adw::prelude::*;
 use adw::{
-    ComboRow, EntryRow, HeaderBar, PreferencesGroup, PreferencesPage, ToolbarView, ViewStack,
-    ViewSwitcher, Window,
+    AlertDialog, Banner, ComboRow, EntryRow, HeaderBar, PreferencesGroup, PreferencesPage, SpinRow,
+    SwitchRow, ToolbarView, ViewStack, ViewSwitcher, Window,
 };
 
+/// Puts the run and its sidecar back as they were when the editor was opened,
+/// saved or not (`dirty`).
+fn revert(run: &RwLock<Run>, sidecar: &RwLock<RunSidecar>, dirty: bool) {
+    let ctx = TuxSplitContext::get_instance();
+    let _batch = ctx.freeze_notifications();
+    let mut restored = sidecar.read().unwrap().clone();
+    // Auto-splitter settings are saved as they change, not with the edits
+    restored.auto_splitter_settings = ctx.sidecar().auto_splitter_settings.clone();
+    *ctx.sidecar_mut() = restored;
+    ctx.set_run(run.read().unwrap().clone());
+    if !dirty {
+        ctx.mark_saved();
+    }
+}
+
+/// Upper bound of the attempt count spin row.
+const MAX_ATTEMPT_COUNT: u32 = 999_999;
+
 #[derive(Clone)]
 pub struct SplitEditor {
     dialog: ToolbarView,
     run_snapshot: Arc<RwLock<Run>>,
+    sidecar_snapshot: Arc<RwLock<RunSidecar>>,
+    /// Whether the run had unsaved changes when the snapshots were taken.
+    dirty_snapshot: Rc<Cell<bool>>,
+    /// Shown while the edits restored from a draft are pending.
+    draft_banner: Banner,
 }
 
 impl SplitEditor {
@@ -35,9 +72,20 @@ impl SplitEditor {
             Arc::new(RwLock::new(run))
         };
 
+        let sidecar_snapshot = Arc::new(RwLock::new(ctx.sidecar().clone()));
+        let dirty_snapshot = Rc::new(Cell::new(ctx.run_dirty()));
+
+        let draft_banner = Banner::builder()
+            .title("Editing unsaved edits restored from a closed editor")
+            .revealed(false)
+            .build();
+
         let this = Self {
             dialog,
             run_snapshot,
+            sidecar_snapshot,
+            dirty_snapshot,
+            draft_banner,
         };
 
         let run_info = this.build_run_info_page();
@@ -58,10 +106,25 @@ impl SplitEditor {
             .build();
         headerbar.set_title_widget(Some(&switcher));
 
+        let preview = TimerPreview::new();
+        let preview_toggle = ToggleButton::builder()
+            .icon_name("sidebar-show-right-symbolic")
+            .tooltip_text("Timer Preview")
+            .active(preview.sidebar().reveals_child())
+            .build();
+        headerbar.pack_end(&preview_toggle);
+        let layout = gtk4::Box::new(gtk4::Orientation::Horizontal, 0);
+        content.set_hexpand(true);
+        layout.append(&content);
+        layout.append(preview.sidebar());
+        // Owned by the toggle, so the preview lives as long as the window
+        preview_toggle.connect_toggled(move |toggle| preview.set_shown(toggle.is_active()));
+
         let action_bar = this.build_cancel_banner();
 
         this.dialog.add_top_bar(&headerbar);
-        this.dialog.set_content(Some(&content));
+        this.dialog.add_top_bar(&this.draft_banner);
+        this.dialog.set_content(Some(&layout));
         this.dialog.add_bottom_bar(&action_bar);
         this.dialog.set_bottom_bar_style(adw::ToolbarStyle::Raised);
         this.dialog.set_extend_content_to_bottom_edge(true); // Content below action bar
@@ -84,12 +147,122 @@ impl SplitEditor {
     }
 
     pub fn present(&self) {
+        let (width, height) = {
+            let ctx = TuxSplitContext::get_instance();
+            let editor = &ctx.config().editor;
+            (editor.width.unwrap_or(800), editor.height.unwrap_or(700))
+        };
         let window = Window::builder()
             .height_request(700) // Arbitrary I know
             .width_request(800) // Arbitrary I know
+            .default_width(width)
+            .default_height(height)
             .build();
+        // Pending edits are kept as a draft and taken off the run
+        let run_snapshot = Arc::clone(&self.run_snapshot);
+        let sidecar_snapshot = Arc::clone(&self.sidecar_snapshot);
+        let dirty_snapshot = Rc::clone(&self.dirty_snapshot);
+        // Remembered apart from the main window
+        window.connect_close_request(move |window| {
+            let ctx = TuxSplitContext::get_instance();
+            let base = run_snapshot.read().unwrap().clone();
+            let run = ctx.get_run();
+            if run != base && !ctx.read_only() {
+                let splits = ctx.config().general.splits.clone();
+                match editor_draft::save(
+                    &crate::paths::current().root,
+                    splits.as_deref(),
+                    &base,
+                    &run,
+                ) {
+                    Ok(()) => info!("Kept the pending editor changes as a draft"),
+                    Err(e) => warn!("Could not write the editor draft: {e}"),
+                }
+                revert(&run_snapshot, &sidecar_snapshot, dirty_snapshot.get());
+            }
+            TuxSplitContext::get_instance().set_editor_open(false);
+            let (width, height) = window.default_size();
+            if let Ok(mut cfg) = TuxSplitContext::get_instance().config_mut() {
+                cfg.mark_dirty();
+                cfg.editor.width = Some(width);
+                cfg.editor.height = Some(height);
+            }
+            glib::Propagation::Proceed
+        });
         window.set_content(Some(self.dialog()));
+        TuxSplitContext::get_instance().set_editor_open(true);
         window.present();
+        self.offer_draft(&window);
+    }
+
+    /// Offers the draft left by a previous editor of this run, if there is one
+    /// and the splits file was not saved since.
+    fn offer_draft(&self, window: &Window) {
+        if TuxSplitContext::get_instance().read_only() {
+            return; // The draft belongs to the instance saving the files
+        }
+        let dir = &crate::paths::current().root;
+        let Some(draft) = editor_draft::load(dir) else {
+            return;
+        };
+        let splits = TuxSplitContext::get_instance()
+            .config()
+            .general
+            .splits
+            .clone();
+        let splits_time = splits
+            .as_deref()
+            .and_then(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok());
+        let decision = editor_draft::decide(
+            &draft.meta,
+            draft.written,
+            splits.as_deref(),
+            splits_time,
+            &self.run_snapshot.read().unwrap(),
+        );
+        match decision {
+            DraftDecision::Offer => {}
+            DraftDecision::OtherRun => return,
+            DraftDecision::Outdated => {
+                info!("Discarding the editor draft, the run changed since");
+                editor_draft::delete(dir);
+                return;
+            }
+        }
+
+        let edited = if draft.meta.edited.is_empty() {
+            "the run".to_owned()
+        } else {
+            draft.meta.edited.join(", ")
+        };
+        let dialog = AlertDialog::builder()
+            .heading("Restore Unsaved Edits?")
+            .body(format!(
+                "The editor was closed with changes to {edited} that were neither saved nor cancelled."
+            ))
+            .default_response("restore")
+            .close_response("later")
+            .build();
+        dialog.add_response("discard", "_Discard");
+        dialog.add_response("later", "_Not Now");
+        dialog.add_response("restore", "_Restore");
+        dialog.set_response_appearance("discard", adw::ResponseAppearance::Destructive);
+        dialog.set_response_appearance("restore", adw::ResponseAppearance::Suggested);
+
+        let banner = self.draft_banner.clone();
+        let run = RefCell::new(Some(draft.run));
+        dialog.connect_response(None, move |_, response| match response {
+            "restore" => {
+                if let Some(run) = run.take() {
+                    // Into the editing session: saving keeps it, cancelling drops it
+                    TuxSplitContext::get_instance().set_run(run);
+                    banner.set_revealed(true);
+                }
+            }
+            "discard" => editor_draft::delete(&crate::paths::current().root),
+            _ => {}
+        });
+        dialog.present(Some(window));
     }
 
     fn build_cancel_banner(&self) -> ActionBar {
@@ -104,29 +277,55 @@ impl SplitEditor {
 
         let save_button = gtk4::Button::builder()
             .css_classes(["suggested-action"])
-            .label("Save")
+            .label("_Save")
+            .use_underline(true)
             .focus_on_click(true)
             .build();
         let cancel_button = gtk4::Button::builder()
-            .label("Cancel")
+            .label("_Cancel")
+            .use_underline(true)
             .focus_on_click(true)
             .build();
 
         // Connect save button
         let snapshot_binding = self.run_snapshot.clone();
+        let sidecar_snapshot_binding = self.sidecar_snapshot.clone();
+        let dirty_snapshot_binding = self.dirty_snapshot.clone();
         let action_bar_binding = action_bar.clone();
+        let banner_binding = self.draft_banner.clone();
         save_button.connect_clicked(move |_| {
+            let ctx = TuxSplitContext::get_instance();
             if let Ok(mut snapshot) = snapshot_binding.try_write() {
-                *snapshot = TuxSplitContext::get_instance().get_run();
+                let run = ctx.get_run();
+                // Golds recalculation and the bulk edits become undoable as one
+                if *snapshot != run {
+                    ctx.take_run_snapshot(&snapshot, "Split editor changes");
+                }
+                *snapshot = run;
+            }
+            if let Ok(mut snapshot) = sidecar_snapshot_binding.try_write() {
+                *snapshot = ctx.sidecar().clone();
             }
+            dirty_snapshot_binding.set(ctx.run_dirty());
+            editor_draft::delete(&crate::paths::current().root);
+            banner_binding.set_revealed(false);
             action_bar_binding.set_revealed(false);
         });
 
         // Connect cancel button
         let snapshot_binding = Arc::clone(&self.run_snapshot);
+        let sidecar_snapshot_binding = Arc::clone(&self.sidecar_snapshot);
+        let dirty_snapshot_binding = Rc::clone(&self.dirty_snapshot);
         let action_bar_binding = action_bar.clone();
+        let banner_binding = self.draft_banner.clone();
         cancel_button.connect_clicked(move |_| {
-            TuxSplitContext::get_instance().set_run(snapshot_binding.read().unwrap().clone());
+            revert(
+                &snapshot_binding,
+                &sidecar_snapshot_binding,
+                dirty_snapshot_binding.get(),
+            );
+            editor_draft::delete(&crate::paths::current().root);
+            banner_binding.set_revealed(false);
             action_bar_binding.set_revealed(false);
         });
 
@@ -141,11 +340,13 @@ impl SplitEditor {
 
         let run_info_group = self.build_run_info_preferences();
         let timer_group = self.build_timer_preferences();
-        // let autosplit_group = self.build_autosplit_preferences();
+        let preflight_group = Self::build_preflight_preferences();
+        let autosplit_group = self.build_autosplit_preferences();
 
         page.add(&run_info_group);
         page.add(&timer_group);
-        // page.add(&autosplit_group);
+        page.add(&preflight_group);
+        page.add(&autosplit_group);
 
         page
     }
@@ -170,24 +371,27 @@ impl SplitEditor {
             name.connect_text_notify(move |entry| {
                 let new_name = entry.text().to_string();
                 let ctx = TuxSplitContext::get_instance();
+                // Refreshed once typing pauses
+                let _batch = ctx.freeze_notifications().debounced();
 
                 let mut run = ctx.get_run();
 
                 run.set_game_name(new_name);
 
-                ctx.set_run(run);
+                ctx.set_edited_run(run);
             });
         }
         {
             category.connect_text_notify(move |entry| {
                 let new_category = entry.text().to_string();
                 let ctx = TuxSplitContext::get_instance();
+                let _batch = ctx.freeze_notifications().debounced();
 
                 let mut run = ctx.get_run();
 
                 run.set_category_name(new_category);
 
-                ctx.set_run(run);
+                ctx.set_edited_run(run);
             });
         }
 
@@ -227,24 +431,24 @@ impl SplitEditor {
             .selected(initial_method)
             .build();
 
-        offset.connect_text_notify(move |entry| {
-            // Offset must be a valid f64 value
-            if entry.text().parse::<f64>().is_ok() {
-                entry.set_title("Start at");
-                entry.remove_css_class("error");
-                let new_offset = entry.text().parse::<f64>().unwrap();
-
-                let ctx = TuxSplitContext::get_instance();
-                let mut run = ctx.get_run();
-
-                run.set_offset(TimeSpan::from_seconds(new_offset));
-
-                ctx.set_run(run);
-            } else {
-                entry.set_title("Start at (entry must be a valid number)");
-                entry.add_css_class("error");
-            }
-        });
+        let editor_ctx = EditorContext::new();
+        {
+            let editor_ctx = editor_ctx.clone();
+            offset.connect_text_notify(move |entry| match parse_offset(&entry.text()) {
+                Ok(new_offset) => {
+                    entry.set_title("Start at");
+                    entry.remove_css_class("error");
+                    let _batch = TuxSplitContext::get_instance()
+                        .freeze_notifications()
+                        .debounced();
+                    editor_ctx.set_offset(new_offset);
+                }
+                Err(reason) => {
+                    entry.set_title(&format!("Start at ({reason})"));
+                    entry.add_css_class("error");
+                }
+            });
+        }
 
         timing_method.connect_selected_notify(move |r| {
             let ctx = TuxSplitContext::get_instance();
@@ -257,19 +461,87 @@ impl SplitEditor {
                     _ => (),
                 }
                 drop(t);
-                ctx.emit_by_name::<()>("run-changed", &[]);
+                ctx.emit_run_changed();
             }
         });
 
         group.add(&offset);
+        group.add(&build_attempt_count_row(editor_ctx, timer.run()));
         group.add(&timing_method);
 
         group
     }
 
+    fn build_preflight_preferences() -> PreferencesGroup {
+        let group = PreferencesGroup::builder()
+            .title("Pre-flight Checklist")
+            .description(
+                "Things to check before an attempt, one per line, ticked off before \
+                 starting when the checklist is turned on in the preferences",
+            )
+            .build();
+
+        let buffer = gtk4::TextBuffer::new(None);
+        buffer.set_text(
+            &TuxSplitContext::get_instance()
+                .sidecar()
+                .preflight
+                .join("\n"),
+        );
+        let text = gtk4::TextView::builder()
+            .buffer(&buffer)
+            .wrap_mode(gtk4::WrapMode::WordChar)
+            .top_margin(8)
+            .bottom_margin(8)
+            .left_margin(8)
+            .right_margin(8)
+            .height_request(96)
+            .css_classes(["card"])
+            .build();
+
+        let editor_ctx = EditorContext::new();
+        buffer.connect_changed(move |buffer| {
+            let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+            let _batch = TuxSplitContext::get_instance()
+                .freeze_notifications()
+                .debounced();
+            editor_ctx.set_preflight(&text.lines().collect::<Vec<_>>());
+        });
+
+        group.add(&text);
+        group
+    }
+
     fn build_autosplit_preferences(&self) -> PreferencesGroup {
-        // Logic to create autosplitter preferences UI component
-        unimplemented!()
+        let ctx = TuxSplitContext::get_instance();
+        let group = PreferencesGroup::builder()
+            .title("Auto Splitter")
+            .description("Settings of the auto-splitter script, kept for this run")
+            .visible(ctx.config().general.auto_splitter.is_some())
+            .build();
+
+        let settings = ctx.auto_splitter_settings();
+        if settings.is_empty() {
+            group.set_description(Some(
+                "The auto splitter has no settings, or has not registered them yet",
+            ));
+        }
+        for setting in settings {
+            let SettingValue::Bool(active) = setting.value else {
+                continue;
+            };
+            let row = SwitchRow::builder()
+                .title(&setting.description)
+                .use_markup(false)
+                .active(active)
+                .build();
+            row.connect_active_notify(move |row| {
+                TuxSplitContext::get_instance()
+                    .set_auto_splitter_setting(&setting.key, SettingValue::Bool(row.is_active()));
+            });
+            group.add(&row);
+        }
+        group
     }
 
     fn build_segment_editor_page(&self) -> PreferencesPage {
@@ -289,3 +561,97 @@ impl SplitEditor {
         page
     }
 }
+
+/// Largest run offset accepted by the editor, in either direction.
+const MAX_OFFSET: time::Duration = time::Duration::hours(24);
+
+/// Parses the run offset entry: plain seconds ("-1.5") or a signed time ("-0:05").
+/// On failure, returns why the entry was rejected.
+fn parse_offset(text: &str) -> Result<TimeSpan, &'static str> {
+    let offset = match text.trim().parse::<f64>() {
+        Ok(seconds) => {
+            time::Duration::checked_seconds_f64(seconds).ok_or("entry must be a valid time")?
+        }
+        _ => parse_signed_time(text).map_err(|_| "entry must be a valid time")?,
+    };
+    if offset.abs() > MAX_OFFSET {
+        return Err("cannot be more than 24 hours");
+    }
+    Ok(TimeSpan::from(offset))
+}
+
+/// Spin row for the run's attempt count.
+///
+/// Going below the number of attempts in the history asks for confirmation first,
+/// since the count then no longer matches the history (which is kept as is).
+fn build_attempt_count_row(editor_ctx: EditorContext, run: &Run) -> SpinRow {
+    let row = SpinRow::with_range(0.0, f64::from(MAX_ATTEMPT_COUNT), 1.0);
+    row.set_title("Attempts");
+    row.set_value(f64::from(run.attempt_count()));
+
+    row.connect_value_notify(move |row| {
+        let attempts = row.value() as u32;
+        let run = TuxSplitContext::get_instance().get_run();
+        let current = run.attempt_count();
+        let recorded = run.attempt_history().len();
+        if attempts == current || attempts as usize >= recorded {
+            editor_ctx.set_attempt_count(attempts);
+            return;
+        }
+
+        let dialog = AlertDialog::builder()
+            .heading("Lower the attempt count?")
+            .body(format!(
+                "The history of this run holds {recorded} attempts. Lowering the count \
+                 to {attempts} will not delete them."
+            ))
+            .default_response("cancel")
+            .close_response("cancel")
+            .build();
+        dialog.add_response("cancel", "_Cancel");
+        dialog.add_response("apply", "_Lower");
+        dialog.set_response_appearance("apply", adw::ResponseAppearance::Destructive);
+
+        let editor_ctx = editor_ctx.clone();
+        let spin = row.clone();
+        dialog.connect_response(None, move |_, response| {
+            if response == "apply" {
+                editor_ctx.set_attempt_count(attempts);
+            } else {
+                spin.set_value(f64::from(current));
+            }
+        });
+        dialog.present(Some(row));
+    });
+
+    row
+}
+
+#[cfg(test)]
+mod offset_tests {
+    use super::*;
+
+    #[test]
+    fn offsets_accept_seconds_and_signed_times() {
+        assert_eq!(parse_offset("-1.5"), Ok(TimeSpan::from_seconds(-1.5)));
+        assert_eq!(parse_offset("0"), Ok(TimeSpan::zero()));
+        assert_eq!(parse_offset("-0:05"), Ok(TimeSpan::from_seconds(-5.0)));
+        assert_eq!(parse_offset("1:00:00"), Ok(TimeSpan::from_seconds(3600.0)));
+    }
+
+    #[test]
+    fn offsets_are_capped_at_a_day() {
+        assert_eq!(parse_offset("86400"), Ok(TimeSpan::from_seconds(86400.0)));
+        assert!(parse_offset("-24:00:01").is_err());
+        assert!(parse_offset("90000").is_err());
+        assert!(parse_offset("inf").is_err());
+        assert!(parse_offset("soon").is_err());
+    }
+
+    #[test]
+    fn offsets_out_of_range_of_a_duration_are_rejected() {
+        assert_eq!(parse_offset("1e20"), Err("entry must be a valid time"));
+        assert_eq!(parse_offset("-1e300"), Err("entry must be a valid time"));
+        assert_eq!(parse_offset("NaN"), Err("entry must be a valid time"));
+    }
+}
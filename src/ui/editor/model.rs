@@ -3,11 +3,16 @@ use gtk4::prelude::*;
 use livesplit_core::{Segment, Timer, TimingMethod};
 use time::Duration as TimeDuration;
 
+use crate::context::TuxSplitContext;
 use crate::formatters::time::TimeFormat;
 use crate::ui::editor::row::SegmentRow;
+use crate::utils::comparisons::best_achieved_split_times;
+use crate::utils::pb_dates::{PbSplitDate, pb_split_dates};
+use crate::utils::reached::reached_per_segment;
 
 /// `SegmentsModel` owns the `ListStore` of `SegmentRow` and provides methods
-/// to build and refresh it from a Timer and a chosen `TimingMethod`.
+/// to build and refresh it from a Timer, a chosen `TimingMethod` and the
+/// comparison whose split and segment times are shown.
 ///
 /// Notes:
 /// - `build_from_timer` clears and recreates all rows.
@@ -36,73 +41,180 @@ impl SegmentsModel {
         self.store.clone()
     }
 
-    /// Clears and repopulates the store from the given Timer and `TimingMethod`.
-    pub fn build_from_timer(&self, timer: &Timer, timing_method: TimingMethod) {
+    /// Clears and repopulates the store from the given Timer, `TimingMethod` and
+    /// comparison.
+    pub fn build_from_timer(&self, timer: &Timer, timing_method: TimingMethod, comparison: &str) {
         self.store.remove_all();
 
         let mut formatter = TimeFormat::new(true, true, true, true, 3, false);
         let segments = timer.run().segments();
+        let best_pace = best_achieved_split_times(segments, timing_method);
+        let reached = reached_per_segment(timer.run());
+        let pb_dates = pb_split_dates(timer.run(), timing_method);
+        let ctx = TuxSplitContext::get_instance();
+        let excluded = ctx.excluded_segments(segments);
+        let sidecar = ctx.sidecar();
 
         for (index, segment) in segments.iter().enumerate() {
-            let (name, split_time, segment_time, best) =
-                compute_row_values(timing_method, &mut formatter, segments, index, segment);
+            let (name, split_time, segment_time, best) = compute_row_values(
+                timing_method,
+                comparison,
+                &mut formatter,
+                segments,
+                index,
+                segment,
+            );
+            let best_pace = format_best_pace(&mut formatter, best_pace[index]);
 
-            let row = SegmentRow::new(index as u32, name, split_time, segment_time, best);
+            let row = SegmentRow::new(
+                index as u32,
+                name,
+                split_time,
+                segment_time,
+                best,
+                best_pace,
+                excluded[index],
+            );
+            set_alternate_values(
+                &row,
+                timing_method,
+                comparison,
+                &mut formatter,
+                segments,
+                index,
+            );
+            row.set_reached(reached[index].format());
+            row.set_gold_date(sidecar.gold_date(segments, index).unwrap_or_default());
+            row.set_pb_date(format_pb_date(pb_dates.as_deref(), index));
+            row.set_split_on(sidecar.split_on_at(segments, index).unwrap_or_default());
             self.store.append(&row);
         }
     }
 
-    /// Updates existing rows in-place from the given Timer and `TimingMethod`.
+    /// Updates existing rows in-place from the given Timer, `TimingMethod` and
+    /// comparison.
     ///
     /// If the number of segments differs from the number of rows, this rebuilds the model.
-    pub fn refresh_from_timer(&self, timer: &Timer, timing_method: TimingMethod) {
+    pub fn refresh_from_timer(&self, timer: &Timer, timing_method: TimingMethod, comparison: &str) {
         let segments = timer.run().segments();
         let row_count = self.store.n_items() as usize;
 
         if row_count != segments.len() {
             // Segment count changed; rebuild
-            self.build_from_timer(timer, timing_method);
+            self.build_from_timer(timer, timing_method, comparison);
             return;
         }
 
         let mut formatter = TimeFormat::new(true, true, true, true, 3, false);
+        let best_pace = best_achieved_split_times(segments, timing_method);
+        let reached = reached_per_segment(timer.run());
+        let pb_dates = pb_split_dates(timer.run(), timing_method);
+        let ctx = TuxSplitContext::get_instance();
+        let excluded = ctx.excluded_segments(segments);
+        let sidecar = ctx.sidecar();
 
         for (index, item) in self.store.iter::<SegmentRow>().enumerate() {
             if let Ok(row) = item
                 && index < segments.len()
             {
                 let segment = &segments[index];
-                let (name, split_time, segment_time, best) =
-                    compute_row_values(timing_method, &mut formatter, segments, index, segment);
+                let (name, split_time, segment_time, best) = compute_row_values(
+                    timing_method,
+                    comparison,
+                    &mut formatter,
+                    segments,
+                    index,
+                    segment,
+                );
 
                 row.set_name(name);
                 row.set_split_time(split_time);
                 row.set_segment_time(segment_time);
+                if row.best() != best {
+                    // The warning was about the previous value
+                    row.set_gold_warning(String::new());
+                }
                 row.set_best(best);
+                row.set_best_pace(format_best_pace(&mut formatter, best_pace[index]));
+                row.set_reached(reached[index].format());
+                row.set_excluded(excluded[index]);
+                set_alternate_values(
+                    &row,
+                    timing_method,
+                    comparison,
+                    &mut formatter,
+                    segments,
+                    index,
+                );
+                row.set_gold_date(sidecar.gold_date(segments, index).unwrap_or_default());
+                row.set_pb_date(format_pb_date(pb_dates.as_deref(), index));
+                row.set_split_on(sidecar.split_on_at(segments, index).unwrap_or_default());
             }
         }
     }
 }
 
+/// The timing method the table is not editing.
+pub const fn other_method(method: TimingMethod) -> TimingMethod {
+    match method {
+        TimingMethod::RealTime => TimingMethod::GameTime,
+        TimingMethod::GameTime => TimingMethod::RealTime,
+    }
+}
+
+/// Fills the secondary times of `row` with those of the other timing method.
+fn set_alternate_values(
+    row: &SegmentRow,
+    timing_method: TimingMethod,
+    comparison: &str,
+    formatter: &mut TimeFormat,
+    segments: &[Segment],
+    index: usize,
+) {
+    let (_, split_time, segment_time, best) = compute_row_values(
+        other_method(timing_method),
+        comparison,
+        formatter,
+        segments,
+        index,
+        &segments[index],
+    );
+    row.set_alt_split_time(split_time);
+    row.set_alt_segment_time(segment_time);
+    row.set_alt_best(best);
+}
+
+fn format_pb_date(pb_dates: Option<&[Option<PbSplitDate>]>, index: usize) -> String {
+    pb_dates
+        .and_then(|dates| dates.get(index).copied().flatten())
+        .map_or(String::new(), PbSplitDate::format)
+}
+
+/// Formats a best achieved split time, leaving segments nobody has reached empty.
+fn format_best_pace(time_parser: &mut TimeFormat, best_pace: Option<TimeDuration>) -> String {
+    best_pace.map_or(String::new(), |dur| time_parser.format_duration(&dur))
+}
+
 /// Computes the display values for a single row, mirroring the logic used by the editor table.
 ///
 /// - name: segment name
-/// - `split_time`: segment's comparison time ("Personal Best") formatted
-/// - `segment_time`: delta between this segment's PB split and the last non-skipped PB split
+/// - `split_time`: segment's time in `comparison` formatted
+/// - `segment_time`: delta between this segment's split and the last non-skipped split of `comparison`
 /// - best: delta between this segment's "Best Segments" and the last non-zero best segment
 fn compute_row_values(
     timing_method: TimingMethod,
+    comparison: &str,
     time_parser: &mut TimeFormat,
     segments: &[Segment],
     index: usize,
     segment: &Segment,
 ) -> (String, String, String, String) {
-    // Find last non-skipped PB split
+    // Find last non-skipped split
     let mut last_non_skipped: Option<usize> = None;
     if index > 0 {
         for k in (0..index).rev() {
             if segments[k]
-                .comparison_timing_method("Personal Best", timing_method)
+                .comparison_timing_method(comparison, timing_method)
                 .unwrap_or_default()
                 .to_duration()
                 != TimeDuration::ZERO
@@ -131,7 +243,7 @@ fn compute_row_values(
 
     let previous_comparison_duration = last_non_skipped.map_or(TimeDuration::ZERO, |k| {
         segments[k]
-            .comparison_timing_method("Personal Best", timing_method)
+            .comparison_timing_method(comparison, timing_method)
             .unwrap_or_default()
             .to_duration()
     });
@@ -139,11 +251,11 @@ fn compute_row_values(
     let name = segment.name().to_string();
 
     let split_time = segment
-        .comparison_timing_method("Personal Best", timing_method)
+        .comparison_timing_method(comparison, timing_method)
         .map_or(String::new(), |t| time_parser.format_time_span(&t));
 
     let segment_time = segment
-        .comparison_timing_method("Personal Best", timing_method)
+        .comparison_timing_method(comparison, timing_method)
         .map_or(String::new(), |t| {
             let dur = t.to_duration().saturating_sub(previous_comparison_duration);
             time_parser.format_duration(&dur)
@@ -0,0 +1,330 @@
+use adw::prelude::*;
+use adw::{ActionRow, AlertDialog, ComboRow, EntryRow, PreferencesGroup, SpinRow};
+use gtk4::{ListBox, ScrolledWindow, SelectionMode};
+
+use crate::context::TuxSplitContext;
+use crate::ui::editor::EditorContext;
+use crate::utils::naming::{RenamePlan, plan_auto_number, plan_disambiguation, plan_find_replace};
+use crate::utils::sidecar_repair::{
+    Orphan, RepairPlan, describe_segment_meta, plan_sidecar_repair, reattach_candidates,
+};
+
+const DEFAULT_PATTERN: &str = "{name} {n}";
+
+/// Presents the "Auto-number" dialog for the given segment `indices`.
+///
+/// The plan is recomputed on every change and previewed before anything is applied.
+pub fn present_auto_number_dialog(
+    parent: &impl IsA<gtk4::Widget>,
+    context: &EditorContext,
+    names: Vec<String>,
+    indices: Vec<usize>,
+) {
+    let dialog = AlertDialog::builder()
+        .heading("Auto-number")
+        .body(scope_description(indices.len(), names.len()))
+        .default_response("apply")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("apply", "_Rename");
+    dialog.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+
+    let group = PreferencesGroup::new();
+    let pattern_row = EntryRow::builder()
+        .title("Pattern ({name}, {n})")
+        .text(DEFAULT_PATTERN)
+        .build();
+    let start_row = SpinRow::with_range(0.0, 9999.0, 1.0);
+    start_row.set_title("Start at");
+    start_row.set_value(1.0);
+    group.add(&pattern_row);
+    group.add(&start_row);
+
+    let (preview_scroller, preview) = build_preview_list();
+    let content = gtk4::Box::builder()
+        .orientation(gtk4::Orientation::Vertical)
+        .spacing(12)
+        .build();
+    content.append(&group);
+    content.append(&preview_scroller);
+    dialog.set_extra_child(Some(&content));
+
+    let compute = {
+        let pattern_row = pattern_row.clone();
+        let start_row = start_row.clone();
+        move || {
+            plan_auto_number(
+                &names,
+                &indices,
+                &pattern_row.text(),
+                start_row.value().round().max(0.0) as u32,
+            )
+        }
+    };
+
+    let refresh = {
+        let compute = compute.clone();
+        let dialog = dialog.clone();
+        let preview = preview.clone();
+        move || {
+            let plan = compute();
+            dialog.set_response_enabled("apply", !plan.is_empty());
+            fill_preview(&preview, &plan);
+        }
+    };
+    refresh();
+    {
+        let refresh = refresh.clone();
+        pattern_row.connect_changed(move |_| refresh());
+    }
+    start_row.connect_value_notify(move |_| refresh());
+
+    let context = context.clone();
+    dialog.connect_response(Some("apply"), move |_, _| {
+        context.rename_segments(&compute());
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// Presents the "Find & Replace" dialog for the given segment `indices`.
+///
+/// The search string is matched literally.
+pub fn present_find_replace_dialog(
+    parent: &impl IsA<gtk4::Widget>,
+    context: &EditorContext,
+    names: Vec<String>,
+    indices: Vec<usize>,
+) {
+    let dialog = AlertDialog::builder()
+        .heading("Find & Replace")
+        .body(scope_description(indices.len(), names.len()))
+        .default_response("apply")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("apply", "_Replace");
+    dialog.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+
+    let group = PreferencesGroup::new();
+    let find_row = EntryRow::builder().title("Find").build();
+    let replace_row = EntryRow::builder().title("Replace with").build();
+    group.add(&find_row);
+    group.add(&replace_row);
+
+    let (preview_scroller, preview) = build_preview_list();
+    let content = gtk4::Box::builder()
+        .orientation(gtk4::Orientation::Vertical)
+        .spacing(12)
+        .build();
+    content.append(&group);
+    content.append(&preview_scroller);
+    dialog.set_extra_child(Some(&content));
+
+    let compute = {
+        let find_row = find_row.clone();
+        let replace_row = replace_row.clone();
+        move || plan_find_replace(&names, &indices, &find_row.text(), &replace_row.text())
+    };
+
+    let refresh = {
+        let compute = compute.clone();
+        let dialog = dialog.clone();
+        let preview = preview.clone();
+        move || {
+            let plan = compute();
+            dialog.set_response_enabled("apply", !plan.is_empty());
+            fill_preview(&preview, &plan);
+        }
+    };
+    refresh();
+    {
+        let refresh = refresh.clone();
+        find_row.connect_changed(move |_| refresh());
+    }
+    replace_row.connect_changed(move |_| refresh());
+
+    let context = context.clone();
+    dialog.connect_response(Some("apply"), move |_, _| {
+        context.rename_segments(&compute());
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// Checks the loaded run for segments sharing a name and, if there are any,
+/// offers to number the repeats ("Boss (2)"). Name-keyed data such as the
+/// exclusion flags cannot tell such segments apart.
+pub fn check_duplicate_names(parent: &impl IsA<gtk4::Widget>) {
+    let names: Vec<String> = TuxSplitContext::get_instance()
+        .get_run()
+        .segments()
+        .iter()
+        .map(|segment| segment.name().to_owned())
+        .collect();
+    let plan = plan_disambiguation(&names);
+    if plan.is_empty() {
+        return;
+    }
+
+    let dialog = AlertDialog::builder()
+        .heading("Duplicate Segment Names")
+        .body(
+            "Some segments share a name, so their exclusion flags cannot be set apart. \
+             Number the repeats to make every name unique?",
+        )
+        .default_response("apply")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Keep Names");
+    dialog.add_response("apply", "_Rename");
+    dialog.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+
+    let (preview_scroller, preview) = build_preview_list();
+    let preview_plan: RenamePlan = plan
+        .iter()
+        .map(|(index, new)| (*index, names[*index].clone(), new.clone()))
+        .collect();
+    fill_preview(&preview, &preview_plan);
+    dialog.set_extra_child(Some(&preview_scroller));
+
+    dialog.connect_response(Some("apply"), move |_, _| {
+        EditorContext::new().disambiguate_segments(&plan);
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// Checks the sidecar of the loaded run against its segments. Attempt data past
+/// the history is pruned right away; data of segments renamed or removed in
+/// another tool is offered for review, each reattached to the segment it most
+/// likely belongs to or dropped.
+pub fn check_sidecar(parent: &impl IsA<gtk4::Widget>) {
+    let ctx = TuxSplitContext::get_instance();
+    let (plan, candidates) = {
+        let run = ctx.get_run();
+        let sidecar = ctx.sidecar();
+        (
+            plan_sidecar_repair(&run, &sidecar),
+            reattach_candidates(&run, &sidecar),
+        )
+    };
+    ctx.repair_sidecar(&plan.pruning_only());
+    if plan.orphans.is_empty() {
+        return;
+    }
+
+    let dialog = AlertDialog::builder()
+        .heading("Data of Missing Segments")
+        .body(
+            "The splits were changed since TuxSplit last saw them, and some segment \
+             data no longer matches a segment. Reattach each to the segment it \
+             belongs to, or drop it.",
+        )
+        .default_response("apply")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Not Now");
+    dialog.add_response("apply", "_Repair");
+    dialog.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+
+    let (scroller, list) = build_preview_list();
+    let mut options = vec!["Drop"];
+    options.extend(candidates.iter().map(String::as_str));
+    let options = gtk4::StringList::new(&options);
+    let rows: Vec<ComboRow> = plan
+        .orphans
+        .iter()
+        .map(|orphan| {
+            let selected = orphan
+                .target
+                .as_ref()
+                .and_then(|target| candidates.iter().position(|c| c == target))
+                .map_or(0, |position| position as u32 + 1);
+            let subtitle = ctx
+                .sidecar()
+                .segments
+                .get(&orphan.name)
+                .map(describe_segment_meta)
+                .unwrap_or_default();
+            // Names and notes are the user's text, not markup
+            let row = ComboRow::builder()
+                .title(&orphan.name)
+                .subtitle(subtitle)
+                .use_markup(false)
+                .model(&options)
+                .selected(selected)
+                .build();
+            list.append(&row);
+            row
+        })
+        .collect();
+    dialog.set_extra_child(Some(&scroller));
+
+    dialog.connect_response(Some("apply"), move |_, _| {
+        // Option 0 is "Drop", the rest are the candidates in order
+        let chosen = RepairPlan {
+            orphans: plan
+                .orphans
+                .iter()
+                .zip(&rows)
+                .map(|(orphan, row)| Orphan {
+                    name: orphan.name.clone(),
+                    target: (row.selected() as usize)
+                        .checked_sub(1)
+                        .and_then(|index| candidates.get(index))
+                        .cloned(),
+                })
+                .collect(),
+            stale_attempts: Vec::new(),
+        };
+        TuxSplitContext::get_instance().repair_sidecar(&chosen);
+    });
+
+    dialog.present(Some(parent));
+}
+
+fn scope_description(targets: usize, total: usize) -> String {
+    if targets == total {
+        format!("Applies to all {total} segments.")
+    } else if targets == 1 {
+        "Applies to the selected segment.".to_owned()
+    } else {
+        format!("Applies to the {targets} selected segments.")
+    }
+}
+
+fn build_preview_list() -> (ScrolledWindow, ListBox) {
+    let preview = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    let scroller = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .min_content_height(200)
+        .child(&preview)
+        .build();
+    (scroller, preview)
+}
+
+fn fill_preview(preview: &ListBox, plan: &RenamePlan) {
+    preview.remove_all();
+
+    if plan.is_empty() {
+        let row = ActionRow::builder()
+            .title("No segments affected")
+            .css_classes(["dim-label"])
+            .build();
+        preview.append(&row);
+        return;
+    }
+
+    for (index, old, new) in plan {
+        let row = ActionRow::builder()
+            .title(glib::markup_escape_text(new).as_str())
+            .subtitle(format!("{}. {}", index + 1, glib::markup_escape_text(old)))
+            .build();
+        preview.append(&row);
+    }
+}
@@ -0,0 +1,147 @@
+//! Miniature of the timer beside the split editor, to catch names that get
+//! ellipsized or lists that overflow before saving.
+//!
+//! The editor writes every committed edit to the run of the context, which emits
+//! "run-changed"; the preview is a `TuxSplitTimer::preview` redrawn on that
+//! signal only. It has no refresh loop, takes no input and is drawn at half size
+//! inside a fixed frame, with the same stylesheet as the main window.
+
+use adw::prelude::*;
+use gtk4::{Fixed, Frame, Label, Orientation::Vertical, Revealer, RevealerTransitionType};
+
+use crate::context::TuxSplitContext;
+use crate::ui::timer::TuxSplitTimer;
+
+/// Size of the frame the preview is drawn in.
+const FRAME_WIDTH: i32 = 220;
+const FRAME_HEIGHT: i32 = 340;
+/// How much smaller than the main timer the preview is drawn.
+const SCALE: f32 = 0.5;
+
+pub struct TimerPreview {
+    sidebar: Revealer,
+    /// Holds the "run-changed" handler redrawing the preview.
+    _timer: TuxSplitTimer,
+}
+
+impl TimerPreview {
+    pub fn new() -> Self {
+        let timer = TuxSplitTimer::preview();
+        let clamp = timer.clamped();
+        // Laid out at the size of the frame before scaling
+        clamp.set_size_request(
+            (FRAME_WIDTH as f32 / SCALE) as i32,
+            (FRAME_HEIGHT as f32 / SCALE) as i32,
+        );
+
+        let fixed = Fixed::new();
+        fixed.put(clamp, 0.0, 0.0);
+        fixed.set_child_transform(
+            clamp,
+            Some(&gtk4::gsk::Transform::new().scale(SCALE, SCALE)),
+        );
+
+        let frame = Frame::builder()
+            .child(&fixed)
+            .width_request(FRAME_WIDTH)
+            .height_request(FRAME_HEIGHT)
+            .overflow(gtk4::Overflow::Hidden)
+            .css_classes(["view"])
+            .build();
+
+        let title = Label::builder()
+            .label("Preview")
+            .xalign(0.0)
+            .css_classes(["heading"])
+            .build();
+        let content = gtk4::Box::builder()
+            .orientation(Vertical)
+            .spacing(6)
+            .margin_top(12)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        content.append(&title);
+        content.append(&frame);
+
+        let sidebar = Revealer::builder()
+            .child(&content)
+            .transition_type(RevealerTransitionType::SlideLeft)
+            .reveal_child(TuxSplitContext::get_instance().config().editor.show_preview)
+            .build();
+
+        Self {
+            sidebar,
+            _timer: timer,
+        }
+    }
+
+    pub fn sidebar(&self) -> &Revealer {
+        &self.sidebar
+    }
+
+    /// Shows or collapses the preview, remembering it in `editor.show_preview`.
+    pub fn set_shown(&self, shown: bool) {
+        self.sidebar.set_reveal_child(shown);
+        if let Ok(mut cfg) = TuxSplitContext::get_instance().config_mut()
+            && cfg.editor.show_preview != shown
+        {
+            cfg.mark_dirty();
+            cfg.editor.show_preview = shown;
+        }
+    }
+}
+
+#[cfg(test)]
+mod preview_tests {
+    use super::*;
+    use crate::ui::editor::EditorContext;
+    use livesplit_core::{Run, Segment};
+    use std::sync::Once;
+
+    static INIT: Once = Once::new();
+
+    fn gtk_test_init() {
+        INIT.call_once(|| {
+            gtk4::init().expect("Failed to init GTK");
+            let _ = adw::init();
+        });
+    }
+
+    /// Titles of the rows and texts of the labels below `widget`.
+    fn texts(widget: &gtk4::Widget) -> Vec<String> {
+        let mut texts = Vec::new();
+        if let Some(row) = widget.downcast_ref::<adw::PreferencesRow>() {
+            texts.push(row.title().to_string());
+        }
+        if let Some(label) = widget.downcast_ref::<Label>() {
+            texts.push(label.label().to_string());
+        }
+        let mut child = widget.first_child();
+        while let Some(widget) = child {
+            texts.extend(self::texts(&widget));
+            child = widget.next_sibling();
+        }
+        texts
+    }
+
+    #[gtk4::test]
+    fn the_preview_follows_renames_from_the_editor() {
+        gtk_test_init();
+        let mut run = Run::new();
+        run.set_game_name("Game");
+        run.push_segment(Segment::new("Forest"));
+        run.push_segment(Segment::new("Castle"));
+        let ctx = TuxSplitContext::get_instance();
+        ctx.set_run(run);
+
+        let preview = TimerPreview::new();
+        let shown = || texts(preview.sidebar().upcast_ref());
+        assert!(shown().iter().any(|text| text == "Forest"), "{:?}", shown());
+
+        EditorContext::new().set_segment_name(0, "Lost Woods".to_owned());
+        assert!(shown().iter().any(|text| text == "Lost Woods"));
+        assert!(!shown().iter().any(|text| text == "Forest"));
+    }
+}
@@ -24,6 +24,35 @@ mod imp {
         pub segment_time: RefCell<String>,
         #[property(get, set)]
         pub best: RefCell<String>,
+        #[property(get, set)]
+        pub best_pace: RefCell<String>,
+        /// Share of the attempts that reached the segment, e.g. "75%".
+        #[property(get, set)]
+        pub reached: RefCell<String>,
+        /// Split, segment and best segment times in the timing method the table
+        /// is not editing, shown with the others when both methods are displayed.
+        #[property(get, set)]
+        pub alt_split_time: RefCell<String>,
+        #[property(get, set)]
+        pub alt_segment_time: RefCell<String>,
+        #[property(get, set)]
+        pub alt_best: RefCell<String>,
+        #[property(get, set)]
+        pub excluded: RefCell<bool>,
+        /// Fastest time in the segment's history when the edited best segment is
+        /// faster than it, empty otherwise.
+        #[property(get, set)]
+        pub gold_warning: RefCell<String>,
+        /// Day the best segment was set (`YYYY-MM-DD`), empty when unknown.
+        #[property(get, set)]
+        pub gold_date: RefCell<String>,
+        /// Day the PB split time was achieved (`YYYY-MM-DD`), "edited" when no
+        /// attempt produced it, empty when unknown.
+        #[property(get, set)]
+        pub pb_date: RefCell<String>,
+        /// When to split at the segment, empty when not set.
+        #[property(get, set)]
+        pub split_on: RefCell<String>,
     }
 
     #[glib::object_subclass]
@@ -52,6 +81,8 @@ impl SegmentRow {
         split_time: String,
         segment_time: String,
         best: String,
+        best_pace: String,
+        excluded: bool,
     ) -> Self {
         glib::Object::builder()
             .property("index", index)
@@ -59,6 +90,8 @@ impl SegmentRow {
             .property("split_time", split_time)
             .property("segment_time", segment_time)
             .property("best", best)
+            .property("best_pace", best_pace)
+            .property("excluded", excluded)
             .build()
     }
 }
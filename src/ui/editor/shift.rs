@@ -0,0 +1,176 @@
+use adw::prelude::*;
+use adw::{AlertDialog, EntryRow, PreferencesGroup, SwitchRow};
+use gtk4::{Label, Orientation};
+use livesplit_core::{Run, TimeSpan, TimingMethod};
+use std::rc::Rc;
+
+use crate::context::TuxSplitContext;
+use crate::formatters::TimeFormat;
+use crate::ui::editor::EditorContext;
+use crate::utils::time_shift::{ShiftScope, ShiftTarget, TimeEdit, plan_time_shift};
+
+/// Presents the "Shift times" dialog: a signed offset, the times it applies to
+/// and a preview of the first and last time that change.
+pub fn present_shift_times_dialog(parent: &impl IsA<gtk4::Widget>, context: &EditorContext) {
+    let run = TuxSplitContext::get_instance().get_run();
+    let method = context.timing_method();
+
+    let dialog = AlertDialog::builder()
+        .heading("Shift Times")
+        .body(format!(
+            "Move the {} split times by the same offset, such as a capture delay. \
+             Segment times stay the same, except for the first segment.",
+            method_name(method)
+        ))
+        .default_response("shift")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("shift", "_Shift");
+    dialog.set_response_appearance("shift", adw::ResponseAppearance::Destructive);
+    dialog.set_response_enabled("shift", false);
+
+    let offset = EntryRow::builder()
+        .title("Offset (e.g. 0.35 or -0:01.5)")
+        .build();
+    let offset_group = PreferencesGroup::new();
+    offset_group.add(&offset);
+
+    let scope_group = PreferencesGroup::builder().title("Times to Shift").build();
+    let comparisons: Vec<(String, SwitchRow)> = run
+        .custom_comparisons()
+        .iter()
+        .map(|name| {
+            let row = SwitchRow::builder()
+                .title(glib::markup_escape_text(name))
+                .active(name == "Personal Best")
+                .build();
+            scope_group.add(&row);
+            (name.clone(), row)
+        })
+        .collect();
+    let best_segments = SwitchRow::builder()
+        .title("Best Segments")
+        .subtitle("Only the first segment's")
+        .build();
+    let history = SwitchRow::builder()
+        .title("Attempt History")
+        .subtitle("Final times and the first segment of each attempt")
+        .build();
+    scope_group.add(&best_segments);
+    scope_group.add(&history);
+    let switches: Vec<SwitchRow> = comparisons
+        .iter()
+        .map(|(_, row)| row.clone())
+        .chain([best_segments.clone(), history.clone()])
+        .collect();
+
+    let preview = Label::builder()
+        .xalign(0.0)
+        .wrap(true)
+        .css_classes(["dim-label"])
+        .build();
+
+    let content = gtk4::Box::new(Orientation::Vertical, 12);
+    content.append(&offset_group);
+    content.append(&scope_group);
+    content.append(&preview);
+    dialog.set_extra_child(Some(&content));
+
+    let offset_binding = offset.clone();
+    let state = Rc::new(move || {
+        let scope = ShiftScope {
+            comparisons: comparisons
+                .iter()
+                .filter(|(_, row)| row.is_active())
+                .map(|(name, _)| name.clone())
+                .collect(),
+            best_segments: best_segments.is_active(),
+            history: history.is_active(),
+        };
+        (super::parse_offset(&offset_binding.text()).ok(), scope)
+    });
+
+    let refresh = {
+        let state = Rc::clone(&state);
+        let dialog = dialog.clone();
+        Rc::new(move || {
+            let (offset, scope) = state();
+            let edits = offset
+                .map(|offset| plan_time_shift(&run, method, offset.to_duration(), &scope))
+                .unwrap_or_default();
+            dialog.set_response_enabled("shift", !edits.is_empty());
+            preview.set_label(&describe_edits(&run, offset.is_some(), &edits));
+        })
+    };
+    refresh();
+    {
+        let refresh = Rc::clone(&refresh);
+        offset.connect_changed(move |_| refresh());
+    }
+    for row in switches {
+        let refresh = Rc::clone(&refresh);
+        row.connect_active_notify(move |_| refresh());
+    }
+
+    let context = context.clone();
+    dialog.connect_response(Some("shift"), move |_, _| {
+        if let (Some(offset), scope) = state() {
+            context.shift_times(offset, &scope);
+        }
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// How many times change, and the first and last of them.
+fn describe_edits(run: &Run, valid_offset: bool, edits: &[TimeEdit]) -> String {
+    if !valid_offset {
+        return "Enter an offset in seconds or as a time.".to_owned();
+    }
+    let (Some(first), Some(last)) = (edits.first(), edits.last()) else {
+        return "No times change.".to_owned();
+    };
+    let mut text = format!(
+        "{} {} change.\nFirst: {}",
+        edits.len(),
+        if edits.len() == 1 { "time" } else { "times" },
+        describe_edit(run, first)
+    );
+    if edits.len() > 1 {
+        text.push_str(&format!("\nLast: {}", describe_edit(run, last)));
+    }
+    text
+}
+
+fn describe_edit(run: &Run, edit: &TimeEdit) -> String {
+    let name = |index: usize| {
+        run.segments()
+            .get(index)
+            .map_or_else(String::new, |segment| segment.name().to_owned())
+    };
+    let what = match &edit.target {
+        ShiftTarget::Split { comparison, index } => format!("{} of {comparison}", name(*index)),
+        ShiftTarget::BestSegment { index } => format!("best segment of {}", name(*index)),
+        ShiftTarget::AttemptSegment { attempt, index } => {
+            format!("{} in attempt #{attempt}", name(*index))
+        }
+        ShiftTarget::AttemptTime { attempt } => format!("final time of attempt #{attempt}"),
+    };
+    format!(
+        "{what}, {} → {}",
+        format_time(edit.before),
+        format_time(edit.after)
+    )
+}
+
+fn format_time(time: time::Duration) -> String {
+    TimeFormat::new(true, true, true, true, 3, false).format_time_span(&TimeSpan::from(time))
+}
+
+const fn method_name(method: TimingMethod) -> &'static str {
+    match method {
+        TimingMethod::RealTime => "Real Time",
+        TimingMethod::GameTime => "Game Time",
+    }
+}
@@ -1,19 +1,67 @@
+use livesplit_core::comparison::personal_best;
 use livesplit_core::{Run, TimingMethod};
+use std::cell::Cell;
 use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
 use gtk4::{Box as GtkBox, ColumnView, ColumnViewColumn, ScrolledWindow, prelude::*};
+use tracing::warn;
 
 use crate::context::TuxSplitContext;
-use crate::formatters::time::parse_hms;
-use crate::ui::editor::context::SegmentMoveDirection;
+use crate::formatters::time::{TimeFormat, parse_hms};
+use crate::sidecar::SPLIT_ON_MAX_CHARS;
+use crate::ui::editor::context::{SegmentMoveDirection, TimeColumn};
+use crate::ui::editor::model::other_method;
 use crate::ui::editor::row::SegmentRow;
 use crate::ui::editor::{EditorContext, SegmentsModel};
+use crate::ui::editor::{golds, icons, naming, shift};
+use crate::ui::practice;
+use crate::utils::comparisons::run_has_game_time;
+use crate::utils::fill::{FillMode, plan_fill_down};
+use crate::utils::golds::{GoldCheck, check_edited_gold};
+use crate::utils::grid::{CellMove, CellPosition, next_cell};
+use crate::utils::paste::{TimePastePlan, is_multiline_paste, plan_name_paste, plan_time_paste};
+use crate::utils::pb_dates::pb_split_dates;
+use crate::utils::segment_ops::{SegmentOp, SegmentOpError};
+
+/// Time entries never get narrower than this, so long segment names cannot clip them.
+const TIME_COLUMN_MIN_WIDTH: i32 = 110;
+
+/// Shown on best segments faster than anything in the segment's history.
+const SUSPICIOUS_GOLD_TOOLTIP: &str = "Faster than any recorded attempt — typo?";
+
+/// Columns made of entries, which keyboard navigation moves between.
+const EDITABLE_COLUMNS: [&str; 5] = ["name", "split-time", "segment-time", "best", "split-on"];
+/// Widest the name entries ask to be, in characters.
+const NAME_MAX_WIDTH_CHARS: i32 = 32;
+
+/// Read-only columns showing both timing methods, each standing in for the
+/// editable column of the same times.
+const BOTH_METHODS_COLUMNS: [(&str, &str); 3] = [
+    ("split-time-both", "split-time"),
+    ("segment-time-both", "segment-time"),
+    ("best-both", "best"),
+];
+
+const NO_GAME_TIME_HINT: &str = "This run has no game time to show";
+const NO_FINISHED_ATTEMPTS_HINT: &str = "No attempt of the history finished";
 
 pub struct SegmentsEditor {
     container: GtkBox,
     table: ColumnView,
-    model: gtk4::SingleSelection,
+    model: gtk4::MultiSelection,
+    /// Row and column of the time cell focused last, which "Fill down" starts from.
+    focused_time_cell: Cell<Option<(usize, TimeColumn)>>,
+    /// Shows both timing methods in read-only columns instead of the time entries.
+    both_methods: gtk4::ToggleButton,
+    /// Shows the day each PB split time was achieved.
+    pb_dates: gtk4::ToggleButton,
+    /// Picks the comparison whose split and segment times are edited, among the
+    /// PB and the custom comparisons.
+    comparisons: gtk4::DropDown,
+    comparison_names: gtk4::StringList,
+    /// Set while the picked comparison is changed to follow the run.
+    syncing_comparisons: Cell<bool>,
     timing_method: Arc<RwLock<TimingMethod>>,
     context: EditorContext,
     segments_model: SegmentsModel,
@@ -30,10 +78,10 @@ impl SegmentsEditor {
                 let shared = ctx.timer();
                 shared.read().unwrap().clone()
             };
-            segments_model.build_from_timer(&t, TimingMethod::RealTime);
+            segments_model.build_from_timer(&t, TimingMethod::RealTime, personal_best::NAME);
         }
         let model_store = segments_model.store();
-        let model = gtk4::SingleSelection::new(Some(model_store));
+        let model = gtk4::MultiSelection::new(Some(model_store));
 
         let table = ColumnView::builder()
             .reorderable(false)
@@ -50,18 +98,55 @@ impl SegmentsEditor {
             .build();
         scroller.set_child(Some(&table));
 
+        let both_methods = gtk4::ToggleButton::builder()
+            .label("Show Both Timing Methods")
+            .build();
+        let show_reached = gtk4::ToggleButton::builder()
+            .label("Show Reached %")
+            .tooltip_text("How often the attempts of the history reached each segment")
+            .active(ctx.config().editor.show_reached)
+            .build();
+        let pb_dates = gtk4::ToggleButton::builder().label("Show PB Dates").build();
+        let comparison_names = gtk4::StringList::new(&[]);
+        let comparisons = gtk4::DropDown::builder()
+            .model(&comparison_names)
+            .tooltip_text("Comparison of the split and segment times")
+            .build();
+        let toggles = GtkBox::builder()
+            .orientation(gtk4::Orientation::Horizontal)
+            .spacing(6)
+            .halign(gtk4::Align::End)
+            .build();
+        toggles.append(&comparisons);
+        toggles.append(&show_reached);
+        toggles.append(&pb_dates);
+        toggles.append(&both_methods);
+        let table_box = GtkBox::builder()
+            .orientation(gtk4::Orientation::Vertical)
+            .spacing(6)
+            .hexpand(true)
+            .build();
+        table_box.append(&toggles);
+        table_box.append(&scroller);
+
         let container = GtkBox::builder()
             .orientation(gtk4::Orientation::Horizontal)
             .spacing(12)
             .vexpand(true)
             .hexpand(true)
             .build();
-        container.append(&scroller);
+        container.append(&table_box);
 
         let this = Self {
             container,
             table,
             model,
+            focused_time_cell: Cell::new(None),
+            both_methods,
+            pb_dates,
+            comparisons,
+            comparison_names,
+            syncing_comparisons: Cell::new(false),
 
             timing_method,
             context,
@@ -71,6 +156,11 @@ impl SegmentsEditor {
         this.table.set_model(Some(&this.model));
         let reference_this = Rc::new(this);
         reference_this.setup_columns();
+        reference_this.setup_both_methods_toggle();
+        reference_this.setup_reached_toggle(&show_reached);
+        reference_this.setup_pb_dates_toggle();
+        reference_this.setup_comparison_dropdown();
+        reference_this.setup_structural_shortcuts();
 
         let controls = reference_this.build_controls();
         reference_this.container.append(&controls);
@@ -83,15 +173,40 @@ impl SegmentsEditor {
     }
 
     fn setup_columns(self: &Rc<SegmentsEditor>) {
-        let name_column = self.make_name_column();
+        let name_column = self.clone().make_name_column();
         let split_time_column = self.clone().make_split_time_column();
         let segment_time_column = self.clone().make_segment_time_column();
         let best_column = self.clone().make_best_segment_column();
+        let [split_time_both, segment_time_both, best_both] =
+            BOTH_METHODS_COLUMNS.map(|(id, _)| self.make_both_methods_column(id));
+        let best_pace_column = Self::make_best_pace_column();
+        let reached_column = Self::make_reached_column();
+        let pb_date_column = Self::make_pb_date_column();
+        let split_on_column = self.clone().make_split_on_column();
+        let excluded_column = self.make_excluded_column();
 
         self.table.append_column(&name_column);
         self.table.append_column(&split_time_column);
+        self.table.append_column(&split_time_both);
+        self.table.append_column(&pb_date_column);
         self.table.append_column(&segment_time_column);
+        self.table.append_column(&segment_time_both);
         self.table.append_column(&best_column);
+        self.table.append_column(&best_both);
+        self.table.append_column(&best_pace_column);
+        self.table.append_column(&reached_column);
+        self.table.append_column(&split_on_column);
+        self.table.append_column(&excluded_column);
+        self.restore_column_widths();
+        {
+            // Persist the widths the user resized the columns to when the editor closes
+            let weak_this = std::rc::Rc::downgrade(self);
+            self.table.connect_unrealize(move |_| {
+                if let Some(this) = weak_this.upgrade() {
+                    this.persist_column_widths();
+                }
+            });
+        }
         {
             let ctx = self.context.clone();
             let weak_this = std::rc::Rc::downgrade(self);
@@ -102,6 +217,16 @@ impl SegmentsEditor {
                 None
             });
         }
+        {
+            let weak_this = std::rc::Rc::downgrade(self);
+            self.context
+                .connect_local("comparison-changed", false, move |_values| {
+                    if let Some(this) = weak_this.upgrade() {
+                        this.update_data_model();
+                    }
+                    None
+                });
+        }
         {
             let ctx = self.context.clone();
             let weak_this = std::rc::Rc::downgrade(self);
@@ -117,6 +242,253 @@ impl SegmentsEditor {
         }
     }
 
+    fn setup_both_methods_toggle(self: &Rc<Self>) {
+        self.both_methods.set_active(
+            TuxSplitContext::get_instance()
+                .config()
+                .editor
+                .show_both_methods,
+        );
+        {
+            let weak_this = Rc::downgrade(self);
+            self.both_methods.connect_toggled(move |toggle| {
+                let ctx = TuxSplitContext::get_instance();
+                if toggle.is_sensitive()
+                    && let Ok(mut cfg) = ctx.config_mut()
+                    && cfg.editor.show_both_methods != toggle.is_active()
+                {
+                    cfg.mark_dirty();
+                    cfg.editor.show_both_methods = toggle.is_active();
+                }
+                if let Some(this) = weak_this.upgrade() {
+                    this.apply_both_methods();
+                }
+            });
+        }
+        {
+            // Game time may come with an edit or a new run
+            let weak_this = Rc::downgrade(self);
+            TuxSplitContext::get_instance().connect_local("run-changed", false, move |_| {
+                if let Some(this) = weak_this.upgrade() {
+                    this.apply_both_methods();
+                }
+                None
+            });
+        }
+        self.apply_both_methods();
+    }
+
+    fn setup_reached_toggle(&self, toggle: &gtk4::ToggleButton) {
+        let Some(column) = self
+            .columns()
+            .into_iter()
+            .find(|column| column.id().as_deref() == Some("reached"))
+        else {
+            return;
+        };
+        toggle.connect_toggled(move |toggle| {
+            column.set_visible(toggle.is_active());
+            if let Ok(mut cfg) = TuxSplitContext::get_instance().config_mut()
+                && cfg.editor.show_reached != toggle.is_active()
+            {
+                cfg.mark_dirty();
+                cfg.editor.show_reached = toggle.is_active();
+            }
+        });
+    }
+
+    fn setup_pb_dates_toggle(self: &Rc<Self>) {
+        self.pb_dates.set_active(
+            TuxSplitContext::get_instance()
+                .config()
+                .editor
+                .show_pb_dates,
+        );
+        {
+            let weak_this = Rc::downgrade(self);
+            self.pb_dates.connect_toggled(move |toggle| {
+                if toggle.is_sensitive()
+                    && let Ok(mut cfg) = TuxSplitContext::get_instance().config_mut()
+                    && cfg.editor.show_pb_dates != toggle.is_active()
+                {
+                    cfg.mark_dirty();
+                    cfg.editor.show_pb_dates = toggle.is_active();
+                }
+                if let Some(this) = weak_this.upgrade() {
+                    this.apply_pb_dates();
+                }
+            });
+        }
+        // An attempt may finish, or be deleted, while the editor is open
+        for signal in ["run-changed", "timing-method-changed"] {
+            let weak_this = Rc::downgrade(self);
+            self.context.connect_local(signal, false, move |_| {
+                if let Some(this) = weak_this.upgrade() {
+                    this.apply_pb_dates();
+                }
+                None
+            });
+        }
+        self.apply_pb_dates();
+    }
+
+    fn setup_comparison_dropdown(self: &Rc<Self>) {
+        {
+            let weak_this = Rc::downgrade(self);
+            self.comparisons.connect_selected_notify(move |dropdown| {
+                let Some(this) = weak_this.upgrade() else {
+                    return;
+                };
+                if this.syncing_comparisons.get() {
+                    return;
+                }
+                if let Some(name) = this.comparison_names.string(dropdown.selected()) {
+                    this.context.set_comparison(&name);
+                }
+            });
+        }
+        {
+            // Comparisons come and go with edits and new runs
+            let weak_this = Rc::downgrade(self);
+            TuxSplitContext::get_instance().connect_local("run-changed", false, move |_| {
+                if let Some(this) = weak_this.upgrade() {
+                    this.apply_comparisons();
+                }
+                None
+            });
+        }
+        self.apply_comparisons();
+    }
+
+    /// Lists the comparisons of the run, keeping the one edited unless the run
+    /// lost it. With only the PB there is nothing to pick, so the drop-down hides.
+    fn apply_comparisons(&self) {
+        let names: Vec<String> = TuxSplitContext::get_instance()
+            .timer()
+            .read()
+            .unwrap()
+            .run()
+            .custom_comparisons()
+            .to_vec();
+        let mut comparison = self.context.comparison();
+        if !names.contains(&comparison) {
+            comparison = personal_best::NAME.to_owned();
+        }
+
+        self.syncing_comparisons.set(true);
+        let listed: Vec<String> = (0..self.comparison_names.n_items())
+            .filter_map(|i| self.comparison_names.string(i).map(String::from))
+            .collect();
+        if listed != names {
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            self.comparison_names
+                .splice(0, self.comparison_names.n_items(), &names);
+        }
+        if let Some(position) = names.iter().position(|name| *name == comparison) {
+            self.comparisons.set_selected(position as u32);
+        }
+        self.syncing_comparisons.set(false);
+
+        self.comparisons.set_visible(names.len() > 1);
+        self.context.set_comparison(&comparison);
+    }
+
+    /// Shows the PB dates column as set, unless no attempt of the history
+    /// finished: the toggle then says there is nothing to date.
+    fn apply_pb_dates(&self) {
+        let ctx = TuxSplitContext::get_instance();
+        let method = *self.timing_method.read().unwrap();
+        let has_dates = pb_split_dates(ctx.timer().read().unwrap().run(), method).is_some();
+        let show = has_dates && ctx.config().editor.show_pb_dates;
+
+        self.pb_dates.set_sensitive(has_dates);
+        self.pb_dates
+            .set_tooltip_text((!has_dates).then_some(NO_FINISHED_ATTEMPTS_HINT));
+        if self.pb_dates.is_active() != show {
+            self.pb_dates.set_active(show);
+        }
+        if let Some(column) = self
+            .columns()
+            .into_iter()
+            .find(|column| column.id().as_deref() == Some("pb-date"))
+        {
+            column.set_visible(show);
+        }
+    }
+
+    /// Swaps the time entries for the read-only columns of both methods, or back.
+    /// Without any game time in the run there is nothing to pair, so the mode is
+    /// off and the toggle says why.
+    fn apply_both_methods(&self) {
+        let ctx = TuxSplitContext::get_instance();
+        let has_game_time = run_has_game_time(ctx.timer().read().unwrap().run());
+        let show_both = has_game_time && ctx.config().editor.show_both_methods;
+
+        self.both_methods.set_sensitive(has_game_time);
+        self.both_methods
+            .set_tooltip_text((!has_game_time).then_some(NO_GAME_TIME_HINT));
+        if self.both_methods.is_active() != show_both {
+            self.both_methods.set_active(show_both);
+        }
+
+        let is_both = |id: &str| BOTH_METHODS_COLUMNS.iter().any(|(both, _)| *both == id);
+        let is_editable = |id: &str| BOTH_METHODS_COLUMNS.iter().any(|(_, time)| *time == id);
+        let columns = self.columns();
+        let switching = columns.iter().any(|column| {
+            column.id().is_some_and(|id| is_both(&id)) && column.is_visible() != show_both
+        });
+        // A focused time entry commits before it is hidden
+        if switching
+            && show_both
+            && let Some(root) = self.table.root()
+        {
+            root.set_focus(None::<&gtk4::Widget>);
+        }
+        for column in columns {
+            let Some(id) = column.id() else { continue };
+            if is_both(&id) {
+                column.set_visible(show_both);
+            } else if is_editable(&id) {
+                column.set_visible(!show_both);
+            }
+        }
+    }
+
+    fn columns(&self) -> Vec<ColumnViewColumn> {
+        self.table
+            .columns()
+            .iter::<ColumnViewColumn>()
+            .filter_map(Result::ok)
+            .collect()
+    }
+
+    fn restore_column_widths(&self) {
+        let widths = TuxSplitContext::get_instance()
+            .config()
+            .editor
+            .resolved_column_widths();
+        for column in self.columns() {
+            column.set_resizable(true);
+            if let Some(id) = column.id()
+                && let Some(&width) = widths.get(id.as_str())
+            {
+                column.set_fixed_width(width);
+            }
+        }
+    }
+
+    fn persist_column_widths(&self) {
+        let widths = self
+            .columns()
+            .iter()
+            .filter_map(|column| Some((column.id()?.to_string(), column.fixed_width())))
+            .collect();
+        if let Ok(mut cfg) = TuxSplitContext::get_instance().config_mut() {
+            cfg.mark_dirty();
+            cfg.editor.column_widths = widths;
+        }
+    }
+
     fn update_data_model(&self) {
         let ctx = TuxSplitContext::get_instance();
         let timer = {
@@ -124,22 +496,38 @@ impl SegmentsEditor {
             shared.read().unwrap().clone()
         };
         let method = *self.timing_method.read().unwrap();
-        self.segments_model.refresh_from_timer(&timer, method);
+        self.segments_model
+            .refresh_from_timer(&timer, method, &self.context.comparison());
     }
 
-    fn make_name_column(&self) -> ColumnViewColumn {
-        let col = ColumnViewColumn::builder().title("Segment Name").build();
+    fn make_name_column(self: Rc<Self>) -> ColumnViewColumn {
+        let col = ColumnViewColumn::builder()
+            .id("name")
+            .title("Segment Name")
+            .expand(true)
+            .build();
         let factory = gtk4::SignalListItemFactory::new();
 
-        let context = self.context.clone();
-        let model = self.model.clone();
+        let self_shared = Rc::clone(&self);
 
         factory.connect_setup(move |_, list_item| {
             let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
-            let entry = gtk4::Entry::builder().hexpand(true).build();
+            // Long names scroll inside the entry instead of widening the column
+            let entry = gtk4::Entry::builder()
+                .hexpand(true)
+                .max_width_chars(NAME_MAX_WIDTH_CHARS)
+                .build();
             cell.set_child(Some(&entry));
 
-            SegmentsEditor::setup_name_cell_common(cell, &entry, &model, &context);
+            SegmentsEditor::setup_name_cell_common(
+                cell,
+                &entry,
+                &self_shared.model,
+                &self_shared.context,
+            );
+            SegmentsEditor::setup_name_cell_paste(cell, &entry, &self_shared.context);
+            SegmentsEditor::setup_cell_navigation(cell, &entry, &self_shared, "name");
+            SegmentsEditor::setup_practice_menu(cell, &entry);
         });
         factory.connect_bind(|_, list_item| {
             let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
@@ -152,6 +540,9 @@ impl SegmentsEditor {
                 row.bind_property("name", &entry, "text")
                     .flags(glib::BindingFlags::SYNC_CREATE)
                     .build();
+                row.bind_property("name", &entry, "tooltip-text")
+                    .flags(glib::BindingFlags::SYNC_CREATE)
+                    .build();
             }
         });
         col.set_factory(Some(&factory));
@@ -159,14 +550,20 @@ impl SegmentsEditor {
     }
 
     fn make_split_time_column(self: Rc<Self>) -> ColumnViewColumn {
-        let col = ColumnViewColumn::builder().title("Split Time").build();
+        let col = ColumnViewColumn::builder()
+            .id("split-time")
+            .title("Split Time")
+            .build();
         let factory = gtk4::SignalListItemFactory::new();
 
         let self_shared = Rc::clone(&self);
 
         factory.connect_setup(move |_, list_item| {
             let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
-            let entry = gtk4::Entry::builder().hexpand(true).build();
+            let entry = gtk4::Entry::builder()
+                .hexpand(true)
+                .width_request(TIME_COLUMN_MIN_WIDTH)
+                .build();
             cell.set_child(Some(&entry));
 
             SegmentsEditor::setup_time_cell_common(
@@ -195,14 +592,20 @@ impl SegmentsEditor {
     }
 
     fn make_segment_time_column(self: Rc<Self>) -> ColumnViewColumn {
-        let col = ColumnViewColumn::builder().title("Segment Time").build();
+        let col = ColumnViewColumn::builder()
+            .id("segment-time")
+            .title("Segment Time")
+            .build();
         let factory = gtk4::SignalListItemFactory::new();
 
         let self_shared = Rc::clone(&self);
 
         factory.connect_setup(move |_, list_item| {
             let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
-            let entry = gtk4::Entry::builder().hexpand(true).build();
+            let entry = gtk4::Entry::builder()
+                .hexpand(true)
+                .width_request(TIME_COLUMN_MIN_WIDTH)
+                .build();
             cell.set_child(Some(&entry));
 
             SegmentsEditor::setup_time_cell_common(
@@ -231,14 +634,20 @@ impl SegmentsEditor {
     }
 
     fn make_best_segment_column(self: Rc<Self>) -> ColumnViewColumn {
-        let col = ColumnViewColumn::builder().title("Best Segment").build();
+        let col = ColumnViewColumn::builder()
+            .id("best")
+            .title("Best Segment")
+            .build();
         let factory = gtk4::SignalListItemFactory::new();
 
         let self_shared = Rc::clone(&self);
 
         factory.connect_setup(move |_, list_item| {
             let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
-            let entry = gtk4::Entry::builder().hexpand(true).build();
+            let entry = gtk4::Entry::builder()
+                .hexpand(true)
+                .width_request(TIME_COLUMN_MIN_WIDTH)
+                .build();
             cell.set_child(Some(&entry));
 
             SegmentsEditor::setup_time_cell_common(
@@ -248,6 +657,16 @@ impl SegmentsEditor {
                 "best".to_string(),
                 SegmentsEditor::commit_best_time,
             );
+
+            let context = self_shared.context.clone();
+            let cell_binding = cell.clone();
+            entry.connect_icon_press(move |entry, position| {
+                if position == gtk4::EntryIconPosition::Secondary
+                    && let Some(row) = cell_binding.item().and_downcast::<SegmentRow>()
+                {
+                    SegmentsEditor::present_gold_warning(entry, &row, &context);
+                }
+            });
         });
         factory.connect_bind(|_, list_item| {
             let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
@@ -260,6 +679,332 @@ impl SegmentsEditor {
                 row.bind_property("best", &entry, "text")
                     .flags(glib::BindingFlags::SYNC_CREATE)
                     .build();
+                row.bind_property("gold-warning", &entry, "secondary-icon-name")
+                    .transform_to(|_, warning: String| {
+                        Some((!warning.is_empty()).then_some("dialog-warning-symbolic"))
+                    })
+                    .flags(glib::BindingFlags::SYNC_CREATE)
+                    .build();
+                row.bind_property("gold-date", &entry, "tooltip-text")
+                    .transform_to(|_, date: String| {
+                        Some((!date.is_empty()).then(|| format!("Set {date}")))
+                    })
+                    .flags(glib::BindingFlags::SYNC_CREATE)
+                    .build();
+                row.bind_property("gold-warning", &entry, "secondary-icon-tooltip-text")
+                    .transform_to(|_, warning: String| {
+                        Some((!warning.is_empty()).then_some(SUSPICIOUS_GOLD_TOOLTIP))
+                    })
+                    .flags(glib::BindingFlags::SYNC_CREATE)
+                    .build();
+            }
+        });
+        col.set_factory(Some(&factory));
+        col
+    }
+
+    // Read-only: the best achieved pace is derived from the attempt history
+    fn make_best_pace_column() -> ColumnViewColumn {
+        let col = ColumnViewColumn::builder()
+            .id("best-pace")
+            .title("Best Pace")
+            .visible(
+                TuxSplitContext::get_instance()
+                    .config()
+                    .display
+                    .show_best_pace,
+            )
+            .build();
+        let factory = gtk4::SignalListItemFactory::new();
+
+        factory.connect_setup(|_, list_item| {
+            let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
+            let label = gtk4::Label::builder()
+                .xalign(0.0)
+                .css_classes(["timer", "monospace", "dim-label"])
+                .build();
+            cell.set_child(Some(&label));
+        });
+        factory.connect_bind(|_, list_item| {
+            let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
+            let label = cell.child().unwrap().downcast::<gtk4::Label>().unwrap();
+
+            if let Some(item) = cell.item()
+                && let Ok(row) = item.downcast::<SegmentRow>()
+            {
+                label.set_label(&row.best_pace());
+                row.bind_property("best-pace", &label, "label")
+                    .flags(glib::BindingFlags::SYNC_CREATE)
+                    .build();
+            }
+        });
+        col.set_factory(Some(&factory));
+        col
+    }
+
+    // Read-only: how often the attempts of the history reached the segment
+    fn make_reached_column() -> ColumnViewColumn {
+        let col = ColumnViewColumn::builder()
+            .id("reached")
+            .title("Reached %")
+            .visible(TuxSplitContext::get_instance().config().editor.show_reached)
+            .build();
+        let factory = gtk4::SignalListItemFactory::new();
+
+        factory.connect_setup(|_, list_item| {
+            let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
+            let label = gtk4::Label::builder()
+                .xalign(1.0)
+                .css_classes(["caption", "numeric", "dim-label"])
+                .build();
+            cell.set_child(Some(&label));
+        });
+        factory.connect_bind(|_, list_item| {
+            let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
+            let label = cell.child().unwrap().downcast::<gtk4::Label>().unwrap();
+
+            if let Some(item) = cell.item()
+                && let Ok(row) = item.downcast::<SegmentRow>()
+            {
+                label.set_label(&row.reached());
+                row.bind_property("reached", &label, "label")
+                    .flags(glib::BindingFlags::SYNC_CREATE)
+                    .build();
+            }
+        });
+        col.set_factory(Some(&factory));
+        col
+    }
+
+    // Read-only: the day the PB attempt that produced the split time started
+    fn make_pb_date_column() -> ColumnViewColumn {
+        let col = ColumnViewColumn::builder()
+            .id("pb-date")
+            .title("PB Achieved")
+            .visible(false)
+            .build();
+        let factory = gtk4::SignalListItemFactory::new();
+
+        factory.connect_setup(|_, list_item| {
+            let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
+            let label = gtk4::Label::builder()
+                .xalign(0.0)
+                .css_classes(["caption", "numeric", "dim-label"])
+                .build();
+            cell.set_child(Some(&label));
+        });
+        factory.connect_bind(|_, list_item| {
+            let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
+            let label = cell.child().unwrap().downcast::<gtk4::Label>().unwrap();
+
+            if let Some(item) = cell.item()
+                && let Ok(row) = item.downcast::<SegmentRow>()
+            {
+                label.set_label(&row.pb_date());
+                row.bind_property("pb-date", &label, "label")
+                    .flags(glib::BindingFlags::SYNC_CREATE)
+                    .build();
+            }
+        });
+        col.set_factory(Some(&factory));
+        col
+    }
+
+    /// Read-only column with the times of both methods stacked, the edited one
+    /// on top ("RTA 1:02:03.4" over "IGT 58:12.0" while editing real time).
+    fn make_both_methods_column(&self, id: &str) -> ColumnViewColumn {
+        let (property, title) = match id {
+            "split-time-both" => ("split-time", "Split Time"),
+            "segment-time-both" => ("segment-time", "Segment Time"),
+            _ => ("best", "Best Segment"),
+        };
+        let col = ColumnViewColumn::builder()
+            .id(id)
+            .title(title)
+            .visible(false)
+            .build();
+        let factory = gtk4::SignalListItemFactory::new();
+
+        factory.connect_setup(|_, list_item| {
+            let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
+            let labels = GtkBox::builder()
+                .orientation(gtk4::Orientation::Vertical)
+                .width_request(TIME_COLUMN_MIN_WIDTH)
+                .build();
+            labels.append(
+                &gtk4::Label::builder()
+                    .xalign(0.0)
+                    .css_classes(["timer", "monospace"])
+                    .build(),
+            );
+            labels.append(
+                &gtk4::Label::builder()
+                    .xalign(0.0)
+                    .css_classes(["timer", "monospace", "dim-label"])
+                    .build(),
+            );
+            cell.set_child(Some(&labels));
+        });
+        let timing_method = self.timing_method.clone();
+        factory.connect_bind(move |_, list_item| {
+            let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
+            let labels = cell.child().unwrap();
+            let (Some(primary), Some(alternate)) = (
+                labels.first_child().and_downcast::<gtk4::Label>(),
+                labels.last_child().and_downcast::<gtk4::Label>(),
+            ) else {
+                return;
+            };
+
+            if let Some(item) = cell.item()
+                && let Ok(row) = item.downcast::<SegmentRow>()
+            {
+                // Refreshing after a method change sets both values again, so the
+                // prefixes follow the method
+                for (label, property, alternate) in [
+                    (&primary, property.to_owned(), false),
+                    (&alternate, format!("alt-{property}"), true),
+                ] {
+                    let timing_method = timing_method.clone();
+                    row.bind_property(&property, label, "label")
+                        .transform_to(move |_, value: String| {
+                            let method = *timing_method.read().unwrap();
+                            let method = if alternate {
+                                other_method(method)
+                            } else {
+                                method
+                            };
+                            Some(format!("{} {value}", method_abbreviation(method)))
+                        })
+                        .flags(glib::BindingFlags::SYNC_CREATE)
+                        .build();
+                }
+            }
+        });
+        col.set_factory(Some(&factory));
+        col
+    }
+
+    /// One line on when to split, shown under the current split while timing,
+    /// with the characters left counted while it is edited.
+    fn make_split_on_column(self: Rc<Self>) -> ColumnViewColumn {
+        let col = ColumnViewColumn::builder()
+            .id("split-on")
+            .title("Split On")
+            .expand(true)
+            .build();
+        let factory = gtk4::SignalListItemFactory::new();
+
+        let self_shared = Rc::clone(&self);
+        factory.connect_setup(move |_, list_item| {
+            let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
+            let entry = gtk4::Entry::builder()
+                .hexpand(true)
+                .max_length(SPLIT_ON_MAX_CHARS as i32)
+                .max_width_chars(NAME_MAX_WIDTH_CHARS)
+                .placeholder_text("e.g. door closes behind you")
+                .build();
+            let counter = gtk4::Label::builder()
+                .visible(false)
+                .css_classes(["caption", "numeric", "dim-label"])
+                .build();
+            let content = GtkBox::new(gtk4::Orientation::Horizontal, 6);
+            content.append(&entry);
+            content.append(&counter);
+            cell.set_child(Some(&content));
+
+            let counter_binding = counter.clone();
+            entry.connect_changed(move |e| {
+                counter_binding.set_label(&format!(
+                    "{}/{SPLIT_ON_MAX_CHARS}",
+                    e.text().chars().count()
+                ));
+            });
+
+            let cell_binding = cell.clone();
+            let model_binding = self_shared.model.clone();
+            let context_binding = self_shared.context.clone();
+            entry.connect_notify_local(Some("has-focus"), move |e, _| {
+                let focused = e.first_child().unwrap().has_focus();
+                counter.set_visible(focused);
+                let Some(row) = cell_binding.item().and_downcast::<SegmentRow>() else {
+                    return;
+                };
+                if focused {
+                    select_focused_row(&model_binding, row.index());
+                } else if e.text() != row.split_on() {
+                    context_binding.set_segment_split_on(row.index() as usize, &e.text());
+                }
+            });
+            SegmentsEditor::setup_cell_navigation(cell, &entry, &self_shared, "split-on");
+        });
+        factory.connect_bind(|_, list_item| {
+            let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
+            let Some(entry) = cell
+                .child()
+                .and_then(|content| content.first_child())
+                .and_downcast::<gtk4::Entry>()
+            else {
+                return;
+            };
+
+            if let Some(item) = cell.item()
+                && let Ok(row) = item.downcast::<SegmentRow>()
+            {
+                entry.set_text(&row.split_on());
+                row.bind_property("split-on", &entry, "text")
+                    .flags(glib::BindingFlags::SYNC_CREATE)
+                    .build();
+            }
+        });
+        col.set_factory(Some(&factory));
+        col
+    }
+
+    /// Checkbox column flagging segments (menus, credits...) that are left out of
+    /// gold detection and time save estimates.
+    fn make_excluded_column(&self) -> ColumnViewColumn {
+        let col = ColumnViewColumn::builder()
+            .id("exclude")
+            .title("Exclude")
+            .build();
+        let factory = gtk4::SignalListItemFactory::new();
+
+        let context = self.context.clone();
+        factory.connect_setup(move |_, list_item| {
+            let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
+            let check = gtk4::CheckButton::builder()
+                .halign(gtk4::Align::Center)
+                .tooltip_text("Exclude from golds and possible time save")
+                .build();
+            cell.set_child(Some(&check));
+
+            let context = context.clone();
+            let cell_binding = cell.clone();
+            check.connect_toggled(move |check| {
+                if let Some(item) = cell_binding.item()
+                    && let Ok(row) = item.downcast::<SegmentRow>()
+                    && row.excluded() != check.is_active()
+                {
+                    context.set_segment_excluded(row.index() as usize, check.is_active());
+                }
+            });
+        });
+        factory.connect_bind(|_, list_item| {
+            let cell = list_item.downcast_ref::<gtk4::ColumnViewCell>().unwrap();
+            let check = cell
+                .child()
+                .unwrap()
+                .downcast::<gtk4::CheckButton>()
+                .unwrap();
+
+            if let Some(item) = cell.item()
+                && let Ok(row) = item.downcast::<SegmentRow>()
+            {
+                check.set_active(row.excluded());
+                row.bind_property("excluded", &check, "active")
+                    .flags(glib::BindingFlags::SYNC_CREATE)
+                    .build();
             }
         });
         col.set_factory(Some(&factory));
@@ -270,7 +1015,7 @@ impl SegmentsEditor {
     fn setup_name_cell_common(
         cell: &gtk4::ColumnViewCell,
         entry: &gtk4::Entry,
-        model: &gtk4::SingleSelection,
+        model: &gtk4::MultiSelection,
         context: &EditorContext,
     ) {
         // Apply name on unfocus and select on focus
@@ -284,8 +1029,7 @@ impl SegmentsEditor {
                 if let Some(item) = cell_binding.item()
                     && let Some(row) = item.downcast_ref::<SegmentRow>()
                 {
-                    let index = row.index() as usize;
-                    model_binding.select_item(index as u32, true);
+                    select_focused_row(&model_binding, row.index());
                 }
             } else {
                 // Commit name change on unfocus
@@ -300,10 +1044,101 @@ impl SegmentsEditor {
         });
     }
 
+    // Pasting several lines into a name renames the rows from there down, the lines
+    // read like a dropped list of names. Single lines are inserted by hand, as for
+    // the times.
+    fn setup_name_cell_paste(
+        cell: &gtk4::ColumnViewCell,
+        entry: &gtk4::Entry,
+        context: &EditorContext,
+    ) {
+        let Some(text) = entry.first_child().and_downcast::<gtk4::Text>() else {
+            return;
+        };
+
+        let cell_binding = cell.clone();
+        let context_binding = context.clone();
+        text.connect_paste_clipboard(move |text| {
+            text.stop_signal_emission_by_name("paste-clipboard");
+
+            let text_binding = text.clone();
+            let cell_binding = cell_binding.clone();
+            let context_binding = context_binding.clone();
+            text.clipboard()
+                .read_text_async(None::<&gtk4::gio::Cancellable>, move |result| {
+                    let Ok(Some(pasted)) = result else {
+                        return;
+                    };
+
+                    if !is_multiline_paste(&pasted) {
+                        text_binding.delete_selection();
+                        let mut position = text_binding.position();
+                        text_binding
+                            .insert_text(pasted.trim_end_matches(['\r', '\n']), &mut position);
+                        text_binding.set_position(position);
+                        return;
+                    }
+
+                    if let Some(item) = cell_binding.item()
+                        && let Some(row) = item.downcast_ref::<SegmentRow>()
+                    {
+                        let names = SegmentsEditor::segment_names();
+                        let plan = plan_name_paste(&pasted, row.index() as usize, &names);
+                        context_binding.rename_segments(&plan);
+                    }
+                });
+        });
+    }
+
+    // Adds the practice of the row's segment to the context menu of its name
+    fn setup_practice_menu(cell: &gtk4::ColumnViewCell, entry: &gtk4::Entry) {
+        let menu = gtk4::gio::Menu::new();
+        menu.append(Some("Practice Statistics"), Some("segment.practice-stats"));
+        menu.append(Some("Practice This Segment"), Some("segment.practice"));
+        entry.set_extra_menu(Some(&menu));
+
+        let segment_name = {
+            let cell = cell.clone();
+            move || {
+                cell.item()
+                    .and_downcast::<SegmentRow>()
+                    .map(|row| row.name())
+            }
+        };
+        let group = gtk4::gio::SimpleActionGroup::new();
+        let stats = gtk4::gio::SimpleAction::new("practice-stats", None);
+        {
+            let entry = entry.downgrade();
+            let segment_name = segment_name.clone();
+            stats.connect_activate(move |_, _| {
+                if let Some(entry) = entry.upgrade()
+                    && let Some(name) = segment_name()
+                {
+                    practice::present_stats_popover(&entry, &name);
+                }
+            });
+        }
+        let practice = gtk4::gio::SimpleAction::new("practice", None);
+        {
+            let entry = entry.downgrade();
+            practice.connect_activate(move |_, _| {
+                if let Some(entry) = entry.upgrade()
+                    && let Some(name) = segment_name()
+                {
+                    practice::present_practice(&entry, &name);
+                }
+            });
+        }
+        group.add_action(&stats);
+        group.add_action(&practice);
+        entry.insert_action_group("segment", Some(&group));
+    }
+
     // Sets standardized handlers for time columns (Split/Segment/Best)
     // - Validates on change (adds/removes "error" CSS class)
     // - Commits on unfocus and refreshes the model
     // - Selects row on focus
+    // - Fills consecutive rows when a multi-line text is pasted
     fn setup_time_cell_common(
         cell: &gtk4::ColumnViewCell,
         entry: &gtk4::Entry,
@@ -311,6 +1146,14 @@ impl SegmentsEditor {
         property_name: String,
         commit: fn(&EditorContext, usize, i64),
     ) {
+        let column = match property_name.as_str() {
+            "split-time" => TimeColumn::SplitTime,
+            "segment-time" => TimeColumn::SegmentTime,
+            _ => TimeColumn::Best,
+        };
+        SegmentsEditor::setup_time_cell_paste(cell, entry, &editor.context, column);
+        SegmentsEditor::setup_cell_navigation(cell, entry, editor, &property_name);
+
         // Validation while typing
         entry.connect_changed(move |e| {
             e.remove_css_class("error");
@@ -333,8 +1176,10 @@ impl SegmentsEditor {
             {
                 if focused {
                     // Select the corresponding SegmentRow
-                    let index = row.index() as usize;
-                    self_binding.model.select_item(index as u32, true);
+                    select_focused_row(&self_binding.model, row.index());
+                    self_binding
+                        .focused_time_cell
+                        .set(Some((row.index() as usize, column)));
                 } else {
                     // Commit value if valid and if different from before
                     let value = e.text().to_string();
@@ -353,12 +1198,272 @@ impl SegmentsEditor {
                         let index = row.index() as usize;
                         let ms = dur.whole_milliseconds();
                         commit(&context_binding, index, ms as i64);
+                        if column == TimeColumn::Best {
+                            self_binding.flag_suspicious_gold(row, index, ms as i64);
+                        }
                     }
                 }
             }
         });
     }
 
+    // Spreadsheet-like keys on an entry cell. Moving focus away commits the cell
+    // through its has-focus handler.
+    // - Enter / Shift+Enter: same column, next / previous row
+    // - Tab / Shift+Tab: next / previous editable column
+    // - Escape: restore the bound value without committing
+    // - Ctrl+D on a time cell: commit it and fill down
+    fn setup_cell_navigation(
+        cell: &gtk4::ColumnViewCell,
+        entry: &gtk4::Entry,
+        editor: &Rc<SegmentsEditor>,
+        column_id: &str,
+    ) {
+        let controller = gtk4::EventControllerKey::new();
+        // Capture, so the entry does not activate on Enter or move focus on Tab itself
+        controller.set_propagation_phase(gtk4::PropagationPhase::Capture);
+
+        let weak_editor = Rc::downgrade(editor);
+        let cell_binding = cell.clone();
+        let entry_binding = entry.clone();
+        let column_id = column_id.to_owned();
+        controller.connect_key_pressed(move |_, key, _, state| {
+            let shift = state.contains(gtk4::gdk::ModifierType::SHIFT_MASK);
+            let ctrl = state.contains(gtk4::gdk::ModifierType::CONTROL_MASK);
+            if ctrl && matches!(key, gtk4::gdk::Key::d | gtk4::gdk::Key::D) {
+                if matches!(column_id.as_str(), "name" | "split-on") {
+                    return glib::Propagation::Proceed;
+                }
+                if let Some(root) = entry_binding.root() {
+                    root.set_focus(None::<&gtk4::Widget>);
+                }
+                if let Some(editor) = weak_editor.upgrade() {
+                    editor.fill_down();
+                }
+                return glib::Propagation::Stop;
+            }
+            let direction = match key {
+                gtk4::gdk::Key::Return | gtk4::gdk::Key::KP_Enter if shift => CellMove::Up,
+                gtk4::gdk::Key::Return | gtk4::gdk::Key::KP_Enter => CellMove::Down,
+                gtk4::gdk::Key::Tab if shift => CellMove::Previous,
+                gtk4::gdk::Key::ISO_Left_Tab => CellMove::Previous,
+                gtk4::gdk::Key::Tab => CellMove::Next,
+                gtk4::gdk::Key::Escape => {
+                    if let Some(row) = cell_binding.item().and_downcast::<SegmentRow>() {
+                        entry_binding.set_text(&bound_value(&row, &column_id));
+                    }
+                    return glib::Propagation::Stop;
+                }
+                _ => return glib::Propagation::Proceed,
+            };
+
+            if let Some(editor) = weak_editor.upgrade() {
+                editor.move_focus(
+                    &entry_binding,
+                    cell_binding.position(),
+                    &column_id,
+                    direction,
+                );
+            }
+            glib::Propagation::Stop
+        });
+        entry.add_controller(controller);
+    }
+
+    // Focuses the cell `direction` leads to from (`row`, `column_id`). Positions are
+    // those of the selection model, so only visible rows are reached. With nowhere to
+    // go, the entry just loses focus to commit its value.
+    fn move_focus(&self, entry: &gtk4::Entry, row: u32, column_id: &str, direction: CellMove) {
+        let columns = self.columns();
+        let editable: Vec<bool> = columns
+            .iter()
+            .map(|column| {
+                column.is_visible()
+                    && column
+                        .id()
+                        .is_some_and(|id| EDITABLE_COLUMNS.contains(&id.as_str()))
+            })
+            .collect();
+        let Some(column) = columns
+            .iter()
+            .position(|column| column.id().as_deref() == Some(column_id))
+        else {
+            return;
+        };
+
+        let from = CellPosition { row, column };
+        if let Some(target) = next_cell(from, direction, self.model.n_items(), &editable) {
+            self.table.scroll_to(
+                target.row,
+                Some(&columns[target.column]),
+                gtk4::ListScrollFlags::FOCUS,
+                None,
+            );
+        } else if let Some(root) = entry.root() {
+            root.set_focus(None::<&gtk4::Widget>);
+        }
+    }
+
+    // The clipboard can only be read asynchronously, so the default paste is always
+    // stopped and single-line text is inserted by hand once it arrives.
+    fn setup_time_cell_paste(
+        cell: &gtk4::ColumnViewCell,
+        entry: &gtk4::Entry,
+        context: &EditorContext,
+        column: TimeColumn,
+    ) {
+        let Some(text) = entry.first_child().and_downcast::<gtk4::Text>() else {
+            return;
+        };
+
+        let cell_binding = cell.clone();
+        let entry_binding = entry.clone();
+        let context_binding = context.clone();
+        text.connect_paste_clipboard(move |text| {
+            text.stop_signal_emission_by_name("paste-clipboard");
+
+            let text_binding = text.clone();
+            let cell_binding = cell_binding.clone();
+            let entry_binding = entry_binding.clone();
+            let context_binding = context_binding.clone();
+            text.clipboard()
+                .read_text_async(None::<&gtk4::gio::Cancellable>, move |result| {
+                    let Ok(Some(pasted)) = result else {
+                        return;
+                    };
+
+                    if !is_multiline_paste(&pasted) {
+                        text_binding.delete_selection();
+                        let mut position = text_binding.position();
+                        text_binding
+                            .insert_text(pasted.trim_end_matches(['\r', '\n']), &mut position);
+                        text_binding.set_position(position);
+                        return;
+                    }
+
+                    if let Some(item) = cell_binding.item()
+                        && let Some(row) = item.downcast_ref::<SegmentRow>()
+                    {
+                        let len = TuxSplitContext::get_instance().get_run().segments().len();
+                        let plan = plan_time_paste(&pasted, row.index() as usize, len);
+                        SegmentsEditor::present_paste_popover(
+                            &entry_binding,
+                            &context_binding,
+                            column,
+                            plan,
+                        );
+                    }
+                });
+        });
+    }
+
+    fn present_paste_popover(
+        entry: &gtk4::Entry,
+        context: &EditorContext,
+        column: TimeColumn,
+        plan: TimePastePlan,
+    ) {
+        let content = GtkBox::builder()
+            .orientation(gtk4::Orientation::Vertical)
+            .spacing(12)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(6)
+            .margin_end(6)
+            .build();
+        let summary = gtk4::Label::builder()
+            .label(plan.summary())
+            .wrap(true)
+            .max_width_chars(40)
+            .xalign(0.0)
+            .build();
+        let buttons = GtkBox::builder()
+            .orientation(gtk4::Orientation::Horizontal)
+            .spacing(6)
+            .halign(gtk4::Align::End)
+            .build();
+        let cancel_button = gtk4::Button::with_mnemonic("_Cancel");
+        let fill_button = gtk4::Button::builder()
+            .label("_Fill")
+            .use_underline(true)
+            .css_classes(["suggested-action"])
+            .sensitive(!plan.values.is_empty())
+            .build();
+        buttons.append(&cancel_button);
+        buttons.append(&fill_button);
+        content.append(&summary);
+        content.append(&buttons);
+
+        let popover = gtk4::Popover::builder().child(&content).build();
+        popover.set_parent(entry);
+        popover.connect_closed(|popover| popover.unparent());
+
+        {
+            let popover = popover.clone();
+            cancel_button.connect_clicked(move |_| popover.popdown());
+        }
+        {
+            let popover = popover.clone();
+            let context = context.clone();
+            fill_button.connect_clicked(move |_| {
+                context.set_times_ms(column, &plan.values);
+                popover.popdown();
+            });
+        }
+
+        popover.popup();
+    }
+
+    // Copies the committed value of the last focused time cell into the rows below
+    // it (the selected ones only, when several are), as one change of the run.
+    fn fill_down(&self) {
+        let Some((source, column)) = self.focused_time_cell.get() else {
+            return;
+        };
+        let Some(row) = self.model.item(source as u32).and_downcast::<SegmentRow>() else {
+            return;
+        };
+        let column_id = match column {
+            TimeColumn::SplitTime => "split-time",
+            TimeColumn::SegmentTime => "segment-time",
+            TimeColumn::Best => "best",
+        };
+        let Ok(value) = parse_hms(&bound_value(&row, column_id)) else {
+            return;
+        };
+        if value.is_negative() {
+            return;
+        }
+
+        let method = self.context.timing_method();
+        let comparison = self.context.comparison();
+        let current: Vec<Option<i64>> = TuxSplitContext::get_instance()
+            .get_run()
+            .segments()
+            .iter()
+            .map(|segment| {
+                segment.comparison(&comparison)[method].map(|time| time.total_milliseconds() as i64)
+            })
+            .collect();
+        let selection = self.model.selection();
+        let selected = (selection.size() > 1)
+            .then(|| SegmentsEditor::scope_indices(&self.model, current.len()));
+        let mode = if column == TimeColumn::SplitTime {
+            FillMode::Accumulate
+        } else {
+            FillMode::Copy
+        };
+
+        let values = plan_fill_down(
+            source,
+            value.whole_milliseconds() as i64,
+            &current,
+            selected.as_deref(),
+            mode,
+        );
+        self.context.set_times_ms(column, &values);
+    }
+
     // Small helpers to bridge into EditorContext
     fn commit_split_time(ctx: &EditorContext, index: usize, ms: i64) {
         ctx.set_split_time_ms(index, ms);
@@ -370,8 +1475,71 @@ impl SegmentsEditor {
         ctx.set_best_time_ms(index, ms);
     }
 
-    // Builds the editor controls (Move split up/down, Add split above, Remove split)
-    fn build_controls(&self) -> gtk4::Box {
+    // Warns (without refusing the value) when a best segment was entered faster
+    // than the segment was ever actually run. Runs after the commit refreshed the row.
+    fn flag_suspicious_gold(&self, row: &SegmentRow, index: usize, ms: i64) {
+        let run = TuxSplitContext::get_instance().get_run();
+        let Some(segment) = run.segments().get(index) else {
+            return;
+        };
+        let method = *self.timing_method.read().unwrap();
+        if let GoldCheck::SuspiciouslyFast { fastest_ms } = check_edited_gold(segment, ms, method) {
+            let mut formatter = TimeFormat::new(true, true, true, true, 3, false);
+            row.set_gold_warning(
+                formatter.format_duration(&time::Duration::milliseconds(fastest_ms)),
+            );
+        }
+    }
+
+    // Popover of the warning icon, offering to replace the best segment with the
+    // fastest time in the history.
+    fn present_gold_warning(entry: &gtk4::Entry, row: &SegmentRow, context: &EditorContext) {
+        let fastest = row.gold_warning();
+        let Ok(fastest_duration) = parse_hms(&fastest) else {
+            return;
+        };
+
+        let label = gtk4::Label::builder()
+            .label(format!(
+                "{SUSPICIOUS_GOLD_TOOLTIP}\nFastest in history: {fastest}"
+            ))
+            .xalign(0.0)
+            .build();
+        let button = gtk4::Button::builder()
+            .label("Use fastest from history")
+            .css_classes(["suggested-action"])
+            .build();
+        let content = gtk4::Box::builder()
+            .orientation(gtk4::Orientation::Vertical)
+            .spacing(6)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(6)
+            .margin_end(6)
+            .build();
+        content.append(&label);
+        content.append(&button);
+
+        let popover = gtk4::Popover::builder().child(&content).build();
+        popover.set_parent(entry);
+        popover.connect_closed(|popover| {
+            let popover = popover.clone();
+            glib::idle_add_local_once(move || popover.unparent());
+        });
+
+        let context = context.clone();
+        let index = row.index() as usize;
+        let popover_binding = popover.clone();
+        button.connect_clicked(move |_| {
+            context.set_best_time_ms(index, fastest_duration.whole_milliseconds() as i64);
+            popover_binding.popdown();
+        });
+
+        popover.popup();
+    }
+
+    // Builds the editor controls (Move splits up/down, Add split above, Remove splits)
+    fn build_controls(self: &Rc<Self>) -> gtk4::Box {
         let controls = gtk4::Box::builder()
             .orientation(gtk4::Orientation::Vertical)
             .valign(gtk4::Align::Fill)
@@ -390,32 +1558,26 @@ impl SegmentsEditor {
         {
             let move_up_button = gtk4::Button::builder()
                 .icon_name("move-up-symbolic")
+                .tooltip_text("Move up (Alt+Up)")
                 .build();
             {
-                let context = self.context.clone();
-                let model_binding = self.model.clone();
+                let weak_editor = Rc::downgrade(self);
                 move_up_button.connect_clicked(move |_| {
-                    context
-                        .move_segment(model_binding.selected() as usize, SegmentMoveDirection::Up);
-                    model_binding
-                        .set_selected(std::cmp::max(model_binding.selected().saturating_sub(1), 0));
+                    if let Some(editor) = weak_editor.upgrade() {
+                        editor.apply_segment_op(SegmentOp::MoveUp);
+                    }
                 });
             }
             let move_down_button = gtk4::Button::builder()
                 .icon_name("move-down-symbolic")
+                .tooltip_text("Move down (Alt+Down)")
                 .build();
             {
-                let context = self.context.clone();
-                let model_binding = self.model.clone();
+                let weak_editor = Rc::downgrade(self);
                 move_down_button.connect_clicked(move |_| {
-                    context.move_segment(
-                        model_binding.selected() as usize,
-                        SegmentMoveDirection::Down,
-                    );
-                    model_binding.set_selected(std::cmp::min(
-                        model_binding.selected() + 1,
-                        TuxSplitContext::get_instance().get_run().segments().len() as u32 - 1, // At least one segment will be present
-                    ));
+                    if let Some(editor) = weak_editor.upgrade() {
+                        editor.apply_segment_op(SegmentOp::MoveDown);
+                    }
                 });
             }
             move_group.append(&move_up_button);
@@ -437,10 +1599,10 @@ impl SegmentsEditor {
                 let context = self.context.clone();
                 let model_binding = self.model.clone();
                 add_split_up_button.connect_clicked(move |_| {
-                    let selected = model_binding.selected(); // We need to capture this before adding, as it will reset to 0
+                    let selected = selected_row(&model_binding); // We need to capture this before adding, as it will reset to 0
                     context.add_segment(selected as usize, SegmentMoveDirection::Up);
                     // We do not move the selection, as the new segment is added where the current one was
-                    model_binding.set_selected(selected);
+                    select_row(&model_binding, selected);
                 });
             }
             let add_split_down_button = gtk4::Button::builder()
@@ -450,12 +1612,15 @@ impl SegmentsEditor {
                 let context = self.context.clone();
                 let model_binding = self.model.clone();
                 add_split_down_button.connect_clicked(move |_| {
-                    let selected = model_binding.selected(); // We need to capture this before adding
+                    let selected = selected_row(&model_binding); // We need to capture this before adding
                     context.add_segment(selected as usize, SegmentMoveDirection::Down);
-                    model_binding.set_selected(std::cmp::min(
-                        selected + 1,
-                        TuxSplitContext::get_instance().get_run().segments().len() as u32 - 1, // At least one segment will be present
-                    ));
+                    select_row(
+                        &model_binding,
+                        std::cmp::min(
+                            selected + 1,
+                            TuxSplitContext::get_instance().get_run().segments().len() as u32 - 1, // At least one segment will be present
+                        ),
+                    );
                 });
             }
             add_group.append(&add_split_up_button);
@@ -464,27 +1629,226 @@ impl SegmentsEditor {
 
         let remove_split_button = gtk4::Button::builder()
             .icon_name("user-trash-symbolic")
+            .tooltip_text("Remove (Delete)")
             .css_classes(["destructive-action"])
             .build();
         {
-            let context = self.context.clone();
-            let model_binding = self.model.clone();
+            let weak_editor = Rc::downgrade(self);
             remove_split_button.connect_clicked(move |_| {
-                let selected = model_binding.selected();
-                context.remove_segment(selected as usize);
-                // We restore the selection
-                model_binding.set_selected(std::cmp::min(
-                    selected,
-                    TuxSplitContext::get_instance().get_run().segments().len() as u32 - 1, // At least one segment will be present
-                ));
+                if let Some(editor) = weak_editor.upgrade() {
+                    editor.apply_segment_op(SegmentOp::Remove);
+                }
+            });
+            // A run keeps at least one segment
+            let update_sensitivity = |button: &gtk4::Button, model: &gtk4::MultiSelection| {
+                button.set_sensitive(model.selection().size() < u64::from(model.n_items()));
+            };
+            update_sensitivity(&remove_split_button, &self.model);
+            let button = remove_split_button.clone();
+            self.model.connect_selection_changed(move |model, _, _| {
+                update_sensitivity(&button, model);
+            });
+        }
+
+        let naming_group = gtk4::Box::builder()
+            .orientation(gtk4::Orientation::Vertical)
+            .spacing(0)
+            .homogeneous(true)
+            .valign(gtk4::Align::Fill)
+            .css_classes(["button-group"])
+            .build();
+        {
+            let auto_number_button = gtk4::Button::builder()
+                .icon_name("view-list-ordered-symbolic")
+                .tooltip_text("Auto-number")
+                .build();
+            {
+                let context = self.context.clone();
+                let model_binding = self.model.clone();
+                auto_number_button.connect_clicked(move |button| {
+                    let names = SegmentsEditor::segment_names();
+                    let indices = SegmentsEditor::scope_indices(&model_binding, names.len());
+                    naming::present_auto_number_dialog(button, &context, names, indices);
+                });
+            }
+            let find_replace_button = gtk4::Button::builder()
+                .icon_name("edit-find-replace-symbolic")
+                .tooltip_text("Find & Replace")
+                .build();
+            {
+                let context = self.context.clone();
+                let model_binding = self.model.clone();
+                find_replace_button.connect_clicked(move |button| {
+                    let names = SegmentsEditor::segment_names();
+                    let indices = SegmentsEditor::scope_indices(&model_binding, names.len());
+                    naming::present_find_replace_dialog(button, &context, names, indices);
+                });
+            }
+            naming_group.append(&auto_number_button);
+            naming_group.append(&find_replace_button);
+        }
+
+        let fill_down_button = gtk4::Button::builder()
+            .icon_name("go-bottom-symbolic")
+            .tooltip_text("Fill down (Ctrl+D)")
+            .build();
+        {
+            let weak_editor = Rc::downgrade(self);
+            fill_down_button.connect_clicked(move |_| {
+                if let Some(editor) = weak_editor.upgrade() {
+                    editor.fill_down();
+                }
+            });
+        }
+
+        let recalculate_golds_button = gtk4::Button::builder()
+            .icon_name("view-refresh-symbolic")
+            .tooltip_text("Recalculate golds from history")
+            .build();
+        {
+            let context = self.context.clone();
+            recalculate_golds_button.connect_clicked(move |button| {
+                golds::present_recalculate_golds_dialog(button, &context);
+            });
+        }
+
+        let shift_times_button = gtk4::Button::builder()
+            .icon_name("media-seek-forward-symbolic")
+            .tooltip_text("Shift times...")
+            .build();
+        {
+            let context = self.context.clone();
+            shift_times_button.connect_clicked(move |button| {
+                shift::present_shift_times_dialog(button, &context);
+            });
+        }
+
+        let import_icons_button = gtk4::Button::builder()
+            .icon_name("image-x-generic-symbolic")
+            .tooltip_text("Import icons from folder...")
+            .build();
+        {
+            let context = self.context.clone();
+            import_icons_button.connect_clicked(move |button| {
+                icons::present_import_icons_dialog(button, &context);
             });
         }
 
         controls.append(&move_group);
         controls.append(&add_group);
+        controls.append(&naming_group);
+        controls.append(&fill_down_button);
+        controls.append(&recalculate_golds_button);
+        controls.append(&shift_times_button);
+        controls.append(&import_icons_button);
         controls.append(&remove_split_button);
         controls
     }
+
+    // Removes or moves every selected row, then selects the rows where they ended up.
+    fn apply_segment_op(&self, op: SegmentOp) {
+        let len = TuxSplitContext::get_instance().get_run().segments().len();
+        let selection = self.model.selection();
+        let selected: Vec<usize> = (0..len).filter(|&i| selection.contains(i as u32)).collect();
+        match self.context.apply_segment_op(&selected, op) {
+            Ok(rows) => select_rows(&self.model, &rows),
+            Err(SegmentOpError::EditorUnavailable(cause)) => {
+                warn!("Could not edit the segments: {cause}");
+            }
+            // Nothing to do for this selection
+            Err(_) => {}
+        }
+    }
+
+    // Delete removes the selected rows, Alt+Up/Down moves them. Entries handle
+    // Delete themselves while editing, so it only reaches the table from rows.
+    fn setup_structural_shortcuts(self: &Rc<Self>) {
+        let controller = gtk4::EventControllerKey::new();
+        let weak_editor = Rc::downgrade(self);
+        controller.connect_key_pressed(move |_, key, _, state| {
+            let alt = state.contains(gtk4::gdk::ModifierType::ALT_MASK);
+            let op = match key {
+                gtk4::gdk::Key::Delete | gtk4::gdk::Key::KP_Delete => SegmentOp::Remove,
+                gtk4::gdk::Key::Up | gtk4::gdk::Key::KP_Up if alt => SegmentOp::MoveUp,
+                gtk4::gdk::Key::Down | gtk4::gdk::Key::KP_Down if alt => SegmentOp::MoveDown,
+                _ => return glib::Propagation::Proceed,
+            };
+            if let Some(editor) = weak_editor.upgrade() {
+                editor.apply_segment_op(op);
+            }
+            glib::Propagation::Stop
+        });
+        self.table.add_controller(controller);
+    }
+
+    fn segment_names() -> Vec<String> {
+        TuxSplitContext::get_instance()
+            .get_run()
+            .segments()
+            .iter()
+            .map(|s| s.name().to_owned())
+            .collect()
+    }
+
+    // Rows a bulk utility applies to: the selected ones, or every segment when none
+    // is. The dialogs say which, and preview the result.
+    fn scope_indices(model: &impl IsA<gtk4::SelectionModel>, len: usize) -> Vec<usize> {
+        let selection = model.selection();
+        if !selection.is_empty() {
+            (0..len).filter(|&i| selection.contains(i as u32)).collect()
+        } else {
+            (0..len).collect()
+        }
+    }
+}
+
+/// Row the add controls act on: the first selected one.
+fn selected_row(model: &gtk4::MultiSelection) -> u32 {
+    let selection = model.selection();
+    if selection.is_empty() {
+        0
+    } else {
+        selection.minimum()
+    }
+}
+
+fn select_row(model: &gtk4::MultiSelection, row: u32) {
+    model.select_item(row, true);
+}
+
+fn select_rows(model: &gtk4::MultiSelection, rows: &[usize]) {
+    let selected = gtk4::Bitset::new_empty();
+    for &row in rows {
+        selected.add(row as u32);
+    }
+    model.set_selection(&selected, &gtk4::Bitset::new_range(0, model.n_items()));
+}
+
+/// Focusing a cell selects its row alone, unless it is part of the selection
+/// already, so that a multi-row selection survives picking a cell in it.
+fn select_focused_row(model: &gtk4::MultiSelection, row: u32) {
+    if !model.is_selected(row) {
+        select_row(model, row);
+    }
+}
+
+const fn method_abbreviation(method: TimingMethod) -> &'static str {
+    match method {
+        TimingMethod::RealTime => "RTA",
+        TimingMethod::GameTime => "IGT",
+    }
+}
+
+/// Value the model holds for a cell, which Escape restores.
+fn bound_value(row: &SegmentRow, column_id: &str) -> String {
+    match column_id {
+        "name" => row.name(),
+        "split-time" => row.split_time(),
+        "segment-time" => row.segment_time(),
+        "best" => row.best(),
+        "split-on" => row.split_on(),
+        _ => String::new(),
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,55 @@
+//! Error dialog for the operations the user runs from the UI.
+
+use adw::AlertDialog;
+use adw::prelude::*;
+use gtk4::{Expander, Label};
+use tracing::warn;
+
+use crate::error::TuxSplitError;
+
+/// Tells the user about `error` over `parent`: what failed and what to do about
+/// it, with the underlying errors in a collapsed "Technical Details" section.
+pub fn show_error(parent: &impl IsA<gtk4::Widget>, error: &TuxSplitError) {
+    let details = error.details();
+    warn!("{error}: {details}");
+
+    let body = match error.hint() {
+        Some(hint) => format!("{error}.\n{hint}"),
+        None => format!("{error}."),
+    };
+    let dialog = AlertDialog::builder()
+        .heading(error.title())
+        .body(body)
+        .default_response("close")
+        .close_response("close")
+        .build();
+    dialog.add_response("close", "_Close");
+
+    if !details.is_empty() {
+        let label = Label::builder()
+            .label(&details)
+            .wrap(true)
+            .selectable(true)
+            .xalign(0.0)
+            .css_classes(["monospace", "dim-label"])
+            .build();
+        let expander = Expander::builder()
+            .label("Technical Details")
+            .child(&label)
+            .build();
+        dialog.set_extra_child(Some(&expander));
+    }
+    dialog.present(Some(parent));
+}
+
+/// Tells the user that the splits cannot change while an attempt is going on.
+pub fn show_attempt_in_progress(parent: &impl IsA<gtk4::Widget>) {
+    let dialog = AlertDialog::builder()
+        .heading("Attempt in Progress")
+        .body("Reset the timer to change the splits.")
+        .default_response("ok")
+        .close_response("ok")
+        .build();
+    dialog.add_response("ok", "_OK");
+    dialog.present(Some(parent));
+}
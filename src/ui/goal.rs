@@ -0,0 +1,78 @@
+//! "Set Goal Time..." menu action and the goal display toggle (see `utils::goal`).
+
+use adw::prelude::*;
+use adw::{AlertDialog, EntryRow};
+use gtk4::{ListBox, SelectionMode};
+
+use crate::context::TuxSplitContext;
+use crate::utils::goal::parse_goal;
+
+/// Asks for the goal time of the attempt. Leaving the entry empty clears it.
+pub fn present_goal_dialog(parent: &impl IsA<gtk4::Widget>) {
+    let ctx = TuxSplitContext::get_instance();
+    let current = ctx.config().general.goal_time.clone();
+
+    let dialog = AlertDialog::builder()
+        .heading("Goal Time")
+        .body(
+            "The time left before the goal counts down below the timer, \
+             and finishing tells how far over or under it the attempt was.",
+        )
+        .default_response("set")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    if current.is_some() {
+        dialog.add_response("clear", "C_lear Goal");
+    }
+    dialog.add_response("set", "_Set");
+    dialog.set_response_appearance("set", adw::ResponseAppearance::Suggested);
+
+    let entry = EntryRow::builder()
+        .title("Goal (h:mm:ss)")
+        .text(current.unwrap_or_default())
+        .build();
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    list.append(&entry);
+    dialog.set_extra_child(Some(&list));
+
+    let valid = |text: &str| parse_goal(Some(text)).is_some();
+    dialog.set_response_enabled("set", valid(&entry.text()));
+    {
+        let dialog = dialog.clone();
+        entry.connect_changed(move |entry| {
+            let valid = valid(&entry.text());
+            dialog.set_response_enabled("set", valid);
+            if valid {
+                entry.remove_css_class("error");
+            } else {
+                entry.add_css_class("error");
+            }
+        });
+    }
+
+    dialog.connect_response(None, move |_, response| {
+        let goal = match response {
+            "set" => Some(entry.text().trim().to_owned()),
+            "clear" => None,
+            _ => return,
+        };
+        if let Ok(mut cfg) = TuxSplitContext::get_instance().config_mut() {
+            cfg.mark_dirty();
+            cfg.general.goal_time = goal;
+        }
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// Shows or hides the goal row, e.g. from its shortcut.
+pub fn toggle_goal_display() {
+    if let Ok(mut cfg) = TuxSplitContext::get_instance().config_mut() {
+        cfg.mark_dirty();
+        cfg.general.additional_info.show_goal = !cfg.general.additional_info.show_goal;
+    }
+}
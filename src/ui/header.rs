@@ -1,18 +1,46 @@
-use adw::{self, AboutDialog, AlertDialog};
+use adw::ToolbarView;
+use adw::{self, AlertDialog};
 use adw::{PreferencesDialog, prelude::*};
 use gtk4::{
     Align, Box as GtkBox, FileChooserDialog, FileFilter, Label, ListBox, MenuButton,
-    Orientation::Vertical, gio,
+    Orientation::Vertical, Overlay, gio,
 };
+use livesplit_core::TimerPhase;
+use std::rc::Rc;
 
+use crate::config::Headerbar;
 use crate::context::TuxSplitContext;
-use crate::ui::editor::SplitEditor;
+use crate::error::TuxSplitError;
+use crate::safe_mode;
+use crate::storage::write_atomic;
+use crate::ui::comparison_picker::ComparisonPicker;
+use crate::ui::editor::{SplitEditor, check_duplicate_names, check_sidecar};
+use crate::ui::error::show_error;
 use crate::ui::menu::TimerPreferencesDialog;
+use crate::ui::palette::{self, Command};
+use crate::ui::viewer;
+use crate::ui::{archive, categories, comparisons, debug_log, goal, history, import, route};
+use crate::utils::history_export::{self, HistoryFormat};
+use crate::utils::hotkeys::HOTKEY_ACTIONS;
+use crate::utils::splits_io;
+use crate::utils::timer_actions::{TimerAction, can_undo_all_pauses};
+
+/// Style of the menu button while it floats over the timer.
+const GEAR_CLASSES: [&str; 3] = ["osd", "circular", "chrome-gear"];
 
 /// `TuxSplitHeader`
-/// A top bar that renders the application title and a hamburger menu.
+/// A top bar that renders the application title with the current splits file
+/// (marked while it has unsaved changes), a hamburger menu, the comparison
+/// picker and the golds earned in the current attempt.
+///
+/// Follows `display.headerbar`: the compact bar only keeps the menu and the
+/// timer phase, and without a bar the menu moves to a gear button over the timer.
 pub struct TuxSplitHeader {
     header: adw::HeaderBar,
+    title: adw::WindowTitle,
+    phase: Label,
+    golds: Label,
+    comparison: ComparisonPicker,
     menu: TuxSplitMenu,
 }
 
@@ -22,17 +50,238 @@ impl TuxSplitHeader {
             .show_end_title_buttons(true)
             .build();
 
+        let title = adw::WindowTitle::new("TuxSplit", "");
+        header.set_title_widget(Some(&title));
+        Self::refresh_title(&title);
+
         let menu = TuxSplitMenu::new(parent);
         header.pack_start(menu.button());
+        let comparison = ComparisonPicker::new();
+        header.pack_start(comparison.widget());
+
+        let golds = Label::builder().visible(false).build();
+        golds.add_css_class("golds-counter");
+        header.pack_end(&golds);
+
+        // Edits of long histories are regenerated in the background
+        let regenerating = adw::Spinner::builder()
+            .tooltip_text("Updating comparisons…")
+            .visible(false)
+            .build();
+        header.pack_end(&regenerating);
+
+        let phase = Label::builder()
+            .css_classes(["phase-pill"])
+            .has_tooltip(true)
+            .build();
+        Self::refresh_phase(&phase);
+        // The time paused goes on while paused, so it is read when shown
+        phase.connect_query_tooltip(|_, _, _, _, tooltip| {
+            let text = Self::phase_tooltip();
+            tooltip.set_text(text.as_deref());
+            text.is_some()
+        });
+
+        let ctx = TuxSplitContext::get_instance();
+        let golds_binding = golds.clone();
+        ctx.connect_local("golds-changed", false, move |_| {
+            Self::refresh_golds(&golds_binding);
+            None
+        });
+        let phase_binding = phase.clone();
+        ctx.connect_local("phase-changed", false, move |_| {
+            Self::refresh_phase(&phase_binding);
+            None
+        });
+        ctx.connect_local("regeneration-changed", false, move |_| {
+            regenerating.set_visible(TuxSplitContext::get_instance().regenerating());
+            None
+        });
+        for signal in ["run-changed", "dirty-changed", "read-only-changed"] {
+            let title_binding = title.clone();
+            ctx.connect_local(signal, false, move |_| {
+                Self::refresh_title(&title_binding);
+                None
+            });
+        }
 
-        Self { header, menu }
+        Self {
+            header,
+            title,
+            phase,
+            golds,
+            comparison,
+            menu,
+        }
     }
 
-    pub fn header(&self) -> &adw::HeaderBar {
-        &self.header
+    /// Adds the chrome to the window: the header bar to `toolbar_view`, or the
+    /// menu over `overlay` without one, as `display.headerbar` says. Switching the
+    /// setting only rearranges the chrome.
+    pub fn install(self, toolbar_view: &ToolbarView, overlay: &Overlay) {
+        toolbar_view.add_top_bar(&self.header);
+
+        let this = Rc::new(self);
+        let toolbar_view = toolbar_view.clone();
+        let overlay = overlay.clone();
+        this.apply_mode(&toolbar_view, &overlay);
+        TuxSplitContext::get_instance().connect_local("headerbar-changed", false, move |_| {
+            this.apply_mode(&toolbar_view, &overlay);
+            None
+        });
+    }
+
+    fn apply_mode(&self, toolbar_view: &ToolbarView, overlay: &Overlay) {
+        let mode = TuxSplitContext::get_instance().config().display.headerbar;
+        let button = self.menu.button();
+
+        // The menu button carries the actions, so it is moved rather than rebuilt
+        let in_overlay = button.parent().is_some_and(|parent| parent == *overlay);
+        if mode == Headerbar::None && !in_overlay {
+            self.header.remove(button);
+            button.set_icon_name("emblem-system-symbolic");
+            button.set_halign(Align::End);
+            button.set_valign(Align::Start);
+            button.set_margin_top(6);
+            button.set_margin_end(6);
+            for class in GEAR_CLASSES {
+                button.add_css_class(class);
+            }
+            overlay.add_overlay(button);
+        } else if mode != Headerbar::None && in_overlay {
+            overlay.remove_overlay(button);
+            button.set_icon_name("open-menu-symbolic");
+            button.set_halign(Align::Fill);
+            button.set_valign(Align::Fill);
+            button.set_margin_top(0);
+            button.set_margin_end(0);
+            for class in GEAR_CLASSES {
+                button.remove_css_class(class);
+            }
+            self.header.pack_start(button);
+            // Back after the menu, as the bar was built
+            self.header.remove(self.comparison.widget());
+            self.header.pack_start(self.comparison.widget());
+        }
+        toolbar_view.set_reveal_top_bars(mode != Headerbar::None);
+
+        let compact = mode == Headerbar::Compact;
+        if compact {
+            self.header.set_title_widget(Some(&self.phase));
+            self.header.add_css_class("compact-header");
+        } else {
+            self.header.set_title_widget(Some(&self.title));
+            self.header.remove_css_class("compact-header");
+        }
+        self.comparison.widget().set_visible(!compact);
+        self.header.set_show_start_title_buttons(!compact);
+        self.header.set_show_end_title_buttons(!compact);
+        Self::refresh_golds(&self.golds);
+    }
+
+    /// Shows the timer phase, with the time spent paused in the attempt so far and
+    /// whether focus mode is on as the tooltip.
+    fn refresh_phase(label: &Label) {
+        let phase = TuxSplitContext::get_instance()
+            .timer()
+            .read()
+            .unwrap()
+            .current_phase();
+        label.set_label(match phase {
+            TimerPhase::NotRunning => "Ready",
+            TimerPhase::Running => "Running",
+            TimerPhase::Paused => "Paused",
+            TimerPhase::Ended => "Finished",
+        });
+    }
+
+    fn phase_tooltip() -> Option<String> {
+        let ctx = TuxSplitContext::get_instance();
+        let pause_time = ctx.timer().read().unwrap().get_pause_time();
+        let mut lines: Vec<String> = pause_time
+            .map(|pause_time| {
+                let paused = ctx.config().format.segment.format_time_span(&pause_time);
+                format!("Paused for {paused} in total")
+            })
+            .into_iter()
+            .collect();
+        if ctx.focus_mode().is_active() {
+            lines.push("Focus mode is on".to_owned());
+        }
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// Shows the splits file name, prefixed with "• " while the run has unsaved
+    /// changes and followed by "(read-only)" while saving is turned off and
+    /// "(safe mode)" in safe mode, with its full path as the tooltip.
+    fn refresh_title(title: &adw::WindowTitle) {
+        let ctx = TuxSplitContext::get_instance();
+        let path = ctx.config().general.splits.clone();
+        let name = path
+            .as_deref()
+            .and_then(|path| path.file_name())
+            .map_or_else(
+                || "No splits file".to_owned(),
+                |name| name.to_string_lossy().into_owned(),
+            );
+        let marker = if ctx.run_dirty() { "• " } else { "" };
+        let read_only = if ctx.read_only() { " (read-only)" } else { "" };
+        let safe_mode = if safe_mode::current().enabled() {
+            " (safe mode)"
+        } else {
+            ""
+        };
+        title.set_subtitle(&format!("{marker}{name}{read_only}{safe_mode}"));
+        title.set_tooltip_text(
+            path.as_deref()
+                .map(|path| path.to_string_lossy())
+                .as_deref(),
+        );
+    }
+
+    fn refresh_golds(label: &Label) {
+        let ctx = TuxSplitContext::get_instance();
+        let golds = ctx.golds();
+        // The compact bar has no room for them
+        let shown = ctx.config().display.headerbar == Headerbar::Full;
+        label.set_visible(shown && !golds.is_empty());
+        if golds.is_empty() {
+            return;
+        }
+
+        label.set_label(&format!("🏆 {}", golds.len()));
+        let timer = ctx.timer();
+        let t = timer.read().unwrap();
+        let segments = t.run().segments();
+        let names: Vec<String> = golds
+            .iter()
+            .filter_map(|&index| segments.get(index))
+            .map(|segment| format!("• {}", segment.name()))
+            .collect();
+        label.set_tooltip_text(Some(&format!("Golds this attempt:\n{}", names.join("\n"))));
     }
 }
 
+/// Window shortcuts of the menu actions, shown next to their menu entries and in
+/// the keybindings dialog. Entries keep the keys they use themselves.
+pub const ACCELERATORS: [(&str, &str, &str); 7] = [
+    ("load-splits", "Load Splits", "<Control>o"),
+    ("save-splits", "Save Splits", "<Control>s"),
+    ("edit-splits", "Edit Splits", "<Control>e"),
+    ("attempt-history", "Attempt History", "<Control>h"),
+    ("toggle-goal", "Toggle Goal Display", "<Control>g"),
+    ("settings", "Settings", "<Control>comma"),
+    ("keybindings", "Keybindings", "<Control>question"),
+];
+
+fn append_with_accel(section: &gio::Menu, label: &str, action: &str) {
+    let item = gio::MenuItem::new(Some(label), Some(&format!("app.{action}")));
+    if let Some((_, _, accel)) = ACCELERATORS.iter().find(|(name, _, _)| *name == action) {
+        item.set_attribute_value("accel", Some(&accel.to_variant()));
+    }
+    section.append_item(&item);
+}
+
 pub struct TuxSplitMenu {
     button: MenuButton,
 }
@@ -47,18 +296,59 @@ impl TuxSplitMenu {
         let menu = gio::Menu::new();
 
         let splits_section = gio::Menu::new();
-        splits_section.append(Some("Load Splits"), Some("app.load-splits"));
-        splits_section.append(Some("Save Splits"), Some("app.save-splits"));
-        splits_section.append(Some("Edit Splits"), Some("app.edit-splits"));
+        append_with_accel(&splits_section, "Load Splits", "load-splits");
+        #[cfg(feature = "speedrun-com")]
+        splits_section.append(
+            Some("New Run from speedrun.com..."),
+            Some("app.new-run-speedrun-com"),
+        );
+        append_with_accel(&splits_section, "Save Splits", "save-splits");
+        append_with_accel(&splits_section, "Edit Splits", "edit-splits");
+        append_with_accel(&splits_section, "Attempt History", "attempt-history");
+        splits_section.append(Some("Set Goal Time..."), Some("app.set-goal"));
+        splits_section.append(Some("Undo All Pauses"), Some("app.undo-all-pauses"));
+        splits_section.append(Some("Focus Mode"), Some("app.toggle-focus-mode"));
+        splits_section.append(
+            Some("Undo Last Data Change..."),
+            Some("app.undo-data-change"),
+        );
+        splits_section.append(Some("Compare with File..."), Some("app.compare-file"));
+        splits_section.append(Some("Import from CSV..."), Some("app.import-csv"));
+        splits_section.append(
+            Some("Duplicate Comparison..."),
+            Some("app.duplicate-comparison"),
+        );
+        splits_section.append(
+            Some("Create Goal Comparison..."),
+            Some("app.goal-comparison"),
+        );
+        splits_section.append(
+            Some("Export for LiveSplit One..."),
+            Some("app.export-splits-io"),
+        );
+        splits_section.append(Some("Export History..."), Some("app.export-history"));
+        splits_section.append(Some("Archive Run..."), Some("app.archive-run"));
+        splits_section.append(Some("Archives..."), Some("app.archives"));
+        #[cfg(feature = "splitsio-upload")]
+        splits_section.append(Some("Upload to splits.io..."), Some("app.upload-splits-io"));
+
+        // Filled with the categories of the current game
+        let categories_section = gio::Menu::new();
+
+        let window_section = gio::Menu::new();
+        window_section.append(Some("New Viewer Window"), Some("app.new-viewer"));
 
         let settings_section = gio::Menu::new();
-        settings_section.append(Some("Settings"), Some("app.settings"));
-        settings_section.append(Some("Keybindings"), Some("app.keybindings"));
+        append_with_accel(&settings_section, "Settings", "settings");
+        append_with_accel(&settings_section, "Keybindings", "keybindings");
 
         let about_section = gio::Menu::new();
+        about_section.append(Some("Debug Log"), Some("app.debug-log"));
         about_section.append(Some("About"), Some("app.about"));
 
         menu.append_section(None, &splits_section);
+        menu.append_section(None, &categories_section);
+        menu.append_section(None, &window_section);
         menu.append_section(None, &settings_section);
         menu.append_section(None, &about_section);
         button.set_menu_model(Some(&menu));
@@ -66,29 +356,252 @@ impl TuxSplitMenu {
         // Actions
         let group = gio::SimpleActionGroup::new();
         group.add_action(&Self::get_load_action(parent));
-        group.add_action(&Self::get_save_action());
+        #[cfg(feature = "speedrun-com")]
+        group.add_action(&Self::get_new_run_action(parent));
+        group.add_action(&Self::get_save_action(parent));
         group.add_action(&Self::get_edit_action());
+        group.add_action(&Self::get_history_action(parent));
+        group.add_action(&Self::get_set_goal_action(parent));
+        group.add_action(&Self::get_toggle_goal_action());
+        group.add_action(&Self::get_toggle_focus_mode_action());
+        let undo_all_pauses = Self::get_undo_all_pauses_action();
+        group.add_action(&undo_all_pauses);
+        let undo_data_change = Self::get_undo_data_change_action(parent);
+        group.add_action(&undo_data_change);
+        group.add_action(&Self::get_compare_file_action(parent));
+        group.add_action(&Self::get_import_csv_action(parent));
+        group.add_action(&Self::get_duplicate_comparison_action(parent));
+        group.add_action(&Self::get_goal_comparison_action(parent));
+        group.add_action(&Self::get_export_action(parent));
+        group.add_action(&Self::get_export_history_action(parent));
+        group.add_action(&Self::get_archive_run_action(parent));
+        group.add_action(&Self::get_archives_action(parent));
+        #[cfg(feature = "splitsio-upload")]
+        group.add_action(&Self::get_upload_action(parent));
+        let switch_category = Self::get_switch_category_action(parent);
+        group.add_action(&switch_category);
+        group.add_action(&Self::get_manage_categories_action(parent));
+        group.add_action(&Self::get_new_viewer_action(parent));
         group.add_action(&Self::get_settings_action(parent));
         group.add_action(&Self::get_keybinds_action(parent));
+        group.add_action(&Self::get_debug_log_action(parent));
         group.add_action(&Self::get_about_action(parent));
         button.insert_action_group("app", Some(&group));
+        Self::register_commands();
+
+        // Ctrl+Shift+P opens the command palette with the same actions
+        let shortcuts = gtk4::ShortcutController::new();
+        let parent_binding = parent.clone();
+        let palette_group = group.clone();
+        shortcuts.add_shortcut(gtk4::Shortcut::new(
+            gtk4::ShortcutTrigger::parse_string("<Control><Shift>p"),
+            Some(gtk4::CallbackAction::new(move |_, _| {
+                palette::present_palette(&parent_binding, &palette_group);
+                glib::Propagation::Stop
+            })),
+        ));
+        for (name, _, accel) in ACCELERATORS {
+            let group = group.clone();
+            shortcuts.add_shortcut(gtk4::Shortcut::new(
+                gtk4::ShortcutTrigger::parse_string(accel),
+                Some(gtk4::CallbackAction::new(move |_, _| {
+                    group.activate_action(name, None);
+                    glib::Propagation::Stop
+                })),
+            ));
+        }
+        // F10 opens the menu, also when it is out of sight behind the gear button
+        let button_binding = button.clone();
+        shortcuts.add_shortcut(gtk4::Shortcut::new(
+            gtk4::ShortcutTrigger::parse_string("F10"),
+            Some(gtk4::CallbackAction::new(move |_, _| {
+                button_binding.popup();
+                glib::Propagation::Stop
+            })),
+        ));
+        parent.add_controller(shortcuts);
+
+        // Categories change on disk as well, so the list is read on every opening
+        button.set_create_popup_func(move |_| {
+            categories::refresh_menu(&categories_section, &switch_category);
+            let ctx = TuxSplitContext::get_instance();
+            undo_data_change.set_enabled(ctx.can_undo_data_change());
+            undo_all_pauses.set_enabled(can_undo_all_pauses(&ctx.timer().read().unwrap()));
+        });
 
         Self { button }
     }
 
+    fn register_commands() {
+        let ctx = TuxSplitContext::get_instance();
+        let commands = [
+            Command::new("load-splits", "Load Splits", &["open", "file", "lss"]),
+            Command::new(
+                "new-run-speedrun-com",
+                "New Run from speedrun.com",
+                &["create", "game", "category", "src"],
+            ),
+            Command::new("save-splits", "Save Splits", &["write", "file"]).enabled_when(|| {
+                let ctx = TuxSplitContext::get_instance();
+                !ctx.read_only() && ctx.config().general.splits.is_some()
+            }),
+            Command::new(
+                "edit-splits",
+                "Edit Splits",
+                &["editor", "segments", "times"],
+            ),
+            Command::new(
+                "attempt-history",
+                "Attempt History",
+                &["attempts", "compare", "runs"],
+            ),
+            Command::new(
+                "set-goal",
+                "Set Goal Time",
+                &["target", "estimate", "marathon", "countdown"],
+            ),
+            Command::new(
+                "toggle-goal",
+                "Toggle Goal Display",
+                &["target", "countdown", "show", "hide"],
+            )
+            .enabled_when(|| {
+                TuxSplitContext::get_instance()
+                    .config()
+                    .general
+                    .goal_time
+                    .is_some()
+            }),
+            Command::new(
+                "toggle-focus-mode",
+                "Toggle Focus Mode",
+                &["minimal", "distraction", "hide", "splits", "big timer"],
+            ),
+            Command::new(
+                "undo-all-pauses",
+                "Undo All Pauses",
+                &["resume", "paused", "accident", "time"],
+            )
+            .enabled_when(|| {
+                can_undo_all_pauses(&TuxSplitContext::get_instance().timer().read().unwrap())
+            }),
+            Command::new(
+                "undo-data-change",
+                "Undo Last Data Change",
+                &["revert", "restore", "reset", "golds", "pb"],
+            )
+            .enabled_when(|| TuxSplitContext::get_instance().can_undo_data_change()),
+            Command::new(
+                "compare-file",
+                "Compare with File",
+                &["route", "diff", "adopt", "lss"],
+            ),
+            Command::new(
+                "import-csv",
+                "Import from CSV",
+                &["route", "spreadsheet", "sheets", "estimate", "segments"],
+            )
+            .enabled_when(|| {
+                TuxSplitContext::get_instance()
+                    .timer()
+                    .read()
+                    .unwrap()
+                    .current_phase()
+                    == TimerPhase::NotRunning
+            }),
+            Command::new(
+                "duplicate-comparison",
+                "Duplicate Comparison",
+                &["custom", "copy", "target", "route"],
+            ),
+            Command::new(
+                "goal-comparison",
+                "Create Goal Comparison",
+                &["custom", "percentile", "target", "realistic", "history"],
+            ),
+            Command::new(
+                "export-splits-io",
+                "Export for LiveSplit One",
+                &["splits.io", "json"],
+            ),
+            Command::new(
+                "export-history",
+                "Export History",
+                &["attempts", "csv", "json", "spreadsheet"],
+            ),
+            Command::new(
+                "archive-run",
+                "Archive Run",
+                &["backup", "snapshot", "copy", "history"],
+            )
+            .enabled_when(|| {
+                TuxSplitContext::get_instance()
+                    .config()
+                    .general
+                    .splits
+                    .is_some()
+            }),
+            Command::new(
+                "archives",
+                "Archives",
+                &["backup", "restore", "old", "route"],
+            ),
+            Command::new(
+                "upload-splits-io",
+                "Upload to splits.io",
+                &["share", "publish"],
+            ),
+            Command::new(
+                "manage-categories",
+                "Manage Categories",
+                &["category", "rename"],
+            ),
+            Command::new(
+                "new-viewer",
+                "New Viewer Window",
+                &["second screen", "stream"],
+            ),
+            Command::new(
+                "settings",
+                "Settings",
+                &["preferences", "options", "layout"],
+            ),
+            Command::new(
+                "keybindings",
+                "Keybindings",
+                &["hotkeys", "shortcuts", "keys"],
+            ),
+            Command::new(
+                "debug-log",
+                "Debug Log",
+                &["logs", "bug report", "doctor", "copy"],
+            ),
+            Command::new("about", "About TuxSplit", &["version", "license"]),
+        ];
+        for command in commands {
+            ctx.register_command(command);
+        }
+    }
+
     pub fn button(&self) -> &MenuButton {
         &self.button
     }
 
-    fn get_save_action() -> gio::SimpleAction {
+    fn get_save_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
         let action = gio::SimpleAction::new("save-splits", None);
         action.connect_activate(move |_, _| {
-            let ctx = TuxSplitContext::get_instance();
-            if let Ok(c) = ctx.config_mut() {
-                let shared_timer = ctx.timer();
-                let t = shared_timer.read().unwrap();
-                c.save_splits(&t);
+            if let Err(e) = TuxSplitContext::get_instance().save_splits() {
+                show_error(&parent_binding, &e);
+            }
+        });
+        // Another instance owns the files
+        let action_binding = action.downgrade();
+        TuxSplitContext::get_instance().connect_local("read-only-changed", false, move |_| {
+            if let Some(action) = action_binding.upgrade() {
+                action.set_enabled(!TuxSplitContext::get_instance().read_only());
             }
+            None
         });
         action
     }
@@ -105,41 +618,182 @@ impl TuxSplitMenu {
         action
     }
 
+    fn get_history_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("attempt-history", None);
+        action.connect_activate(move |_, _| {
+            history::present_history_dialog(&parent_binding);
+        });
+        action
+    }
+
+    fn get_set_goal_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("set-goal", None);
+        action.connect_activate(move |_, _| {
+            goal::present_goal_dialog(&parent_binding);
+        });
+        action
+    }
+
+    fn get_toggle_goal_action() -> gio::SimpleAction {
+        let action = gio::SimpleAction::new("toggle-goal", None);
+        action.connect_activate(|_, _| goal::toggle_goal_display());
+        action
+    }
+
+    /// Shows only the big timer and the current split, or everything again.
+    fn get_toggle_focus_mode_action() -> gio::SimpleAction {
+        let action = gio::SimpleAction::new("toggle-focus-mode", None);
+        action.connect_activate(|_, _| TuxSplitContext::get_instance().toggle_focus_mode());
+        action
+    }
+
+    /// Counts the time spent paused back into the attempt, for a pause by accident.
+    fn get_undo_all_pauses_action() -> gio::SimpleAction {
+        let action = gio::SimpleAction::new("undo-all-pauses", None);
+        action.connect_activate(|_, _| {
+            TuxSplitContext::get_instance().enqueue(TimerAction::UndoAllPauses);
+        });
+        action
+    }
+
+    fn get_undo_data_change_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("undo-data-change", None);
+        action.connect_activate(move |_, _| {
+            let ctx = TuxSplitContext::get_instance();
+            if !ctx.can_undo_data_change() {
+                return;
+            }
+            let Some((description, taken_at)) = ctx.latest_run_snapshot() else {
+                return;
+            };
+            let at = glib::DateTime::from_unix_local(taken_at.unix_timestamp())
+                .and_then(|at| at.format("%H:%M"))
+                .map(|at| at.to_string())
+                .unwrap_or_default();
+
+            let dialog = AlertDialog::builder()
+                .heading("Undo Last Data Change?")
+                .body(format!(
+                    "The splits go back to how they were before “{description}” at {at}. \
+                     Anything changed in them since then is lost too."
+                ))
+                .default_response("cancel")
+                .close_response("cancel")
+                .build();
+            dialog.add_response("cancel", "_Cancel");
+            dialog.add_response("undo", "_Undo");
+            dialog.set_response_appearance("undo", adw::ResponseAppearance::Destructive);
+            dialog.connect_response(Some("undo"), |_, _| {
+                TuxSplitContext::get_instance().undo_data_change();
+            });
+            dialog.present(Some(&parent_binding));
+        });
+        action
+    }
+
+    fn get_compare_file_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("compare-file", None);
+        action.connect_activate(move |_, _| {
+            route::present_compare_file(&parent_binding);
+        });
+        action
+    }
+
+    fn get_import_csv_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("import-csv", None);
+        action.connect_activate(move |_, _| import::present_import_csv(&parent_binding));
+        action
+    }
+
+    fn get_archive_run_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("archive-run", None);
+        action.connect_activate(move |_, _| archive::present_archive_run(&parent_binding));
+        action
+    }
+
+    fn get_archives_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("archives", None);
+        action.connect_activate(move |_, _| archive::present_archives(&parent_binding));
+        action
+    }
+
+    fn get_duplicate_comparison_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("duplicate-comparison", None);
+        action.connect_activate(move |_, _| {
+            comparisons::present_duplicate_comparison(&parent_binding);
+        });
+        action
+    }
+
+    fn get_goal_comparison_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("goal-comparison", None);
+        action.connect_activate(move |_, _| {
+            comparisons::present_goal_comparison(&parent_binding);
+        });
+        action
+    }
+
+    fn get_debug_log_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("debug-log", None);
+        action.connect_activate(move |_, _| {
+            if let Some(app) = parent_binding.application() {
+                debug_log::present(&app);
+            }
+        });
+        action
+    }
+
     fn get_load_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
         let parent_binding = parent.clone();
         let action = gio::SimpleAction::new("load-splits", None);
+        action.connect_activate(move |_, _| present_load_splits(&parent_binding));
+        action
+    }
+
+    fn get_export_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("export-splits-io", None);
         action.connect_activate(move |_, _| {
             let file_chooser = FileChooserDialog::new(
-                Some("Load Splits"),
+                Some("Export for LiveSplit One"),
                 Some(&parent_binding),
-                gtk4::FileChooserAction::Open,
+                gtk4::FileChooserAction::Save,
                 &[
-                    ("Open", gtk4::ResponseType::Ok),
+                    ("Export", gtk4::ResponseType::Ok),
                     ("Cancel", gtk4::ResponseType::Cancel),
                 ],
             );
 
-            let lss_filter = FileFilter::new();
-            let all_filter = FileFilter::new();
-            lss_filter.set_name(Some("LiveSplit Splits (*.lss)"));
-            all_filter.set_name(Some("All Files"));
-            lss_filter.add_pattern("*.lss");
-            all_filter.add_pattern("*");
-            file_chooser.add_filter(&lss_filter);
-            file_chooser.add_filter(&all_filter);
+            let json_filter = FileFilter::new();
+            json_filter.set_name(Some("Splits I/O JSON (*.json)"));
+            json_filter.add_pattern("*.json");
+            file_chooser.add_filter(&json_filter);
 
+            let run = TuxSplitContext::get_instance().get_run();
+            let name = format!("{} - {}.json", run.game_name(), run.category_name());
+            file_chooser.set_current_name(&name.replace('/', "-"));
+
+            let parent_binding = parent_binding.clone();
             file_chooser.connect_response(move |dialog, response| {
                 if response == gtk4::ResponseType::Ok
                     && let Some(file) = dialog.file()
                     && let Some(path) = file.path()
                 {
-                    let ctx = TuxSplitContext::get_instance();
-                    if let Ok(mut c) = ctx.config_mut() {
-                        c.set_splits_path(path);
-                        if let Some(run) = c.parse_run() {
-                            drop(c); // Set run needs write access to config
-                            ctx.set_run(run);
-                        }
+                    let result = splits_io::export_run(&run)
+                        .map_err(std::io::Error::other)
+                        .and_then(|json| write_atomic(&path, json));
+                    if let Err(source) = result {
+                        show_error(&parent_binding, &TuxSplitError::Io { path, source });
                     }
                 }
                 dialog.destroy();
@@ -151,37 +805,206 @@ impl TuxSplitMenu {
         action
     }
 
+    fn get_export_history_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("export-history", None);
+        action.connect_activate(move |_, _| {
+            let file_chooser = FileChooserDialog::new(
+                Some("Export History"),
+                Some(&parent_binding),
+                gtk4::FileChooserAction::Save,
+                &[
+                    ("_Export", gtk4::ResponseType::Ok),
+                    ("_Cancel", gtk4::ResponseType::Cancel),
+                ],
+            );
+
+            let csv_filter = FileFilter::new();
+            csv_filter.set_name(Some("CSV, attempt totals in a second file (*.csv)"));
+            csv_filter.add_pattern("*.csv");
+            file_chooser.add_filter(&csv_filter);
+            let json_filter = FileFilter::new();
+            json_filter.set_name(Some("JSON (*.json)"));
+            json_filter.add_pattern("*.json");
+            file_chooser.add_filter(&json_filter);
+
+            let run = TuxSplitContext::get_instance().get_run();
+            let name = format!("{} - {} history.csv", run.game_name(), run.category_name());
+            file_chooser.set_current_name(&name.replace('/', "-"));
+
+            let parent_binding = parent_binding.clone();
+            file_chooser.connect_response(move |dialog, response| {
+                if response == gtk4::ResponseType::Ok
+                    && let Some(file) = dialog.file()
+                    && let Some(mut path) = file.path()
+                {
+                    // The extension typed wins, then the filter picked
+                    #[allow(deprecated)]
+                    let filter = dialog.filter();
+                    let format = HistoryFormat::from_path(&path).unwrap_or_else(|| {
+                        let format = if filter.as_ref() == Some(&json_filter) {
+                            HistoryFormat::Json
+                        } else {
+                            HistoryFormat::Csv
+                        };
+                        path.set_extension(format.extension());
+                        format
+                    });
+                    let run = run.clone();
+                    let tags = TuxSplitContext::get_instance()
+                        .sidecar()
+                        .attempt_tags
+                        .clone();
+                    let name_length = TuxSplitContext::get_instance()
+                        .config()
+                        .display
+                        .split_name_length;
+                    let parent = parent_binding.clone();
+                    // Long histories take a while to go through, off the main thread
+                    glib::MainContext::default().spawn_local(async move {
+                        let result = gio::spawn_blocking(move || {
+                            let mut export = history_export::extract_history(&run);
+                            export.tag_attempts(&tags);
+                            if let Some(length) = name_length {
+                                export.shorten_segment_names(length);
+                            }
+                            let files = history_export::export_files(&export, &path, format)
+                                .map_err(|e| TuxSplitError::Io {
+                                    path: path.clone(),
+                                    source: std::io::Error::other(e),
+                                })?;
+                            for (path, contents) in files {
+                                write_atomic(&path, contents)
+                                    .map_err(|source| TuxSplitError::Io { path, source })?;
+                            }
+                            Ok(())
+                        })
+                        .await;
+                        match result {
+                            Ok(Err(e)) => show_error(&parent, &e),
+                            Err(_) => tracing::warn!("history export was interrupted"),
+                            Ok(Ok(())) => {}
+                        }
+                    });
+                }
+                dialog.destroy();
+            });
+
+            file_chooser.set_modal(true);
+            file_chooser.present();
+        });
+        action
+    }
+
+    #[cfg(feature = "speedrun-com")]
+    fn get_new_run_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("new-run-speedrun-com", None);
+        action.connect_activate(move |_, _| {
+            crate::ui::speedruncom::present_new_run(&parent_binding);
+        });
+        action
+    }
+
+    #[cfg(feature = "splitsio-upload")]
+    fn get_upload_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("upload-splits-io", None);
+        action.connect_activate(move |_, _| {
+            crate::ui::splitsio::confirm_upload(&parent_binding);
+        });
+        action
+    }
+
+    fn get_switch_category_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new_stateful(
+            "switch-category",
+            Some(glib::VariantTy::STRING),
+            &"".to_variant(),
+        );
+        action.connect_activate(move |_, parameter| {
+            if let Some(name) = parameter.and_then(glib::Variant::get::<String>) {
+                categories::switch_category(&parent_binding, &name);
+            }
+        });
+        action
+    }
+
+    fn get_manage_categories_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("manage-categories", None);
+        action.connect_activate(move |_, _| {
+            if let Some(dialog) = categories::manage_dialog() {
+                temporary_keybinds_disable(&dialog);
+                dialog.present(Some(&parent_binding));
+            }
+        });
+        action
+    }
+
+    fn get_new_viewer_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
+        let parent_binding = parent.clone();
+        let action = gio::SimpleAction::new("new-viewer", None);
+        action.connect_activate(move |_, _| {
+            if let Some(app) = parent_binding.application() {
+                viewer::open(&app);
+            }
+        });
+        action
+    }
+
     fn get_keybinds_action(parent: &adw::ApplicationWindow) -> gio::SimpleAction {
         let parent_for_keybinds = parent.clone();
         let action = gio::SimpleAction::new("keybindings", None);
         action.connect_activate(move |_, _| {
             let dialog = AlertDialog::builder()
                 .heading("Keybindings")
-                .body("Current keybinds are not modifiable yet.")
+                .body("Global hotkeys are set in the hotkeys section of config.yaml.")
                 .default_response("ok")
+                .close_response("ok")
                 .build();
 
             let keybinds_list = ListBox::new();
             keybinds_list.add_css_class("boxed-list");
-            let keybinds = vec![
-                ("Start / Split", "Numpad 1"),
-                ("Skip Split", "Numpad 2"),
-                ("Reset", "Numpad 3"),
-                ("Previous Comparison", "Numpad 4"),
-                ("Pause", "Numpad 5"),
-                ("Next Comparison", "Numpad 6"),
-                ("Undo", "Numpad 8"),
-            ];
-            for (action, key) in keybinds {
-                let key_label = Label::new(Some(key));
+            let hotkeys = TuxSplitContext::get_instance().config().hotkeys;
+            for (name, action) in HOTKEY_ACTIONS {
+                let key = hotkeys
+                    .key(name)
+                    .map_or_else(|| "Not bound".to_owned(), |key| key.to_string());
+                let key_label = Label::new(Some(&key));
                 let row = adw::ActionRow::builder().title(action).build();
                 row.add_suffix(&key_label);
                 keybinds_list.append(&row);
             }
 
-            dialog.set_extra_child(Some(&keybinds_list));
+            // Only while the window has focus
+            let window_list = ListBox::new();
+            window_list.add_css_class("boxed-list");
+            window_list.set_margin_top(12);
+            let window_keys = [
+                ("Menu", "F10".to_owned()),
+                ("Command Palette", "Ctrl+Shift+P".to_owned()),
+            ];
+            let accels = ACCELERATORS.iter().map(|(_, title, accel)| {
+                let label = gtk4::accelerator_parse(*accel)
+                    .map(|(key, mods)| gtk4::accelerator_get_label(key, mods).to_string())
+                    .unwrap_or_else(|| (*accel).to_owned());
+                (*title, label)
+            });
+            for (action, key) in window_keys.into_iter().chain(accels) {
+                let key_label = Label::new(Some(&key));
+                let row = adw::ActionRow::builder().title(action).build();
+                row.add_suffix(&key_label);
+                window_list.append(&row);
+            }
+            let lists = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+            lists.append(&keybinds_list);
+            lists.append(&window_list);
+
+            dialog.set_extra_child(Some(&lists));
 
-            dialog.add_response("ok", "Okay");
+            dialog.add_response("ok", "_Okay");
             dialog.present(Some(&parent_for_keybinds));
         });
         action
@@ -204,19 +1027,97 @@ impl TuxSplitMenu {
         let parent_for_about = parent.clone();
         let action = gio::SimpleAction::new("about", None);
         action.connect_activate(move |_, _| {
-            let about_dialog = AboutDialog::builder()
-                .application_name("TuxSplit")
-                .version("0.0.1")
-                .comments("A GTK-based LiveSplit timer application.")
-                .license_type(gtk4::License::MitX11)
-                .website("https://github.com/AntonioRodriguezRuiz/tuxsplit")
-                .build();
-            about_dialog.present(Some(&parent_for_about));
+            crate::ui::about::present(&parent_for_about);
         });
         action
     }
 }
 
+/// Lets the user pick a splits file and loads it.
+pub fn present_load_splits(parent: &adw::ApplicationWindow) {
+    let file_chooser = FileChooserDialog::new(
+        Some("Load Splits"),
+        Some(parent),
+        gtk4::FileChooserAction::Open,
+        &[
+            ("Open", gtk4::ResponseType::Ok),
+            ("Cancel", gtk4::ResponseType::Cancel),
+        ],
+    );
+
+    let lss_filter = FileFilter::new();
+    let all_filter = FileFilter::new();
+    lss_filter.set_name(Some("LiveSplit Splits (*.lss)"));
+    all_filter.set_name(Some("All Files"));
+    lss_filter.add_pattern("*.lss");
+    all_filter.add_pattern("*");
+    file_chooser.add_filter(&lss_filter);
+    file_chooser.add_filter(&all_filter);
+
+    let parent_binding = parent.clone();
+    file_chooser.connect_response(move |dialog, response| {
+        if response == gtk4::ResponseType::Ok
+            && let Some(file) = dialog.file()
+            && let Some(path) = file.path()
+        {
+            match TuxSplitContext::get_instance().load_splits(path) {
+                Ok(()) => {
+                    check_sidecar(&parent_binding);
+                    check_duplicate_names(&parent_binding);
+                }
+                Err(e) => show_error(&parent_binding, &e),
+            }
+        }
+        dialog.destroy();
+    });
+
+    file_chooser.set_modal(true);
+    file_chooser.present();
+}
+
+/// Lets the window be moved by dragging `handle` while there is no header bar.
+pub fn install_window_drag(handle: &impl IsA<gtk4::Widget>) {
+    let drag = gtk4::GestureDrag::new();
+    drag.connect_drag_update(|gesture, offset_x, offset_y| {
+        if TuxSplitContext::get_instance().config().display.headerbar != Headerbar::None {
+            return;
+        }
+        let (Some(widget), Some((start_x, start_y)), Some(device)) =
+            (gesture.widget(), gesture.start_point(), gesture.device())
+        else {
+            return;
+        };
+        if !widget.drag_check_threshold(0, 0, offset_x as i32, offset_y as i32) {
+            return;
+        }
+        let Some(native) = widget.native() else {
+            return;
+        };
+        let Some(toplevel) = native.surface().and_downcast::<gtk4::gdk::Toplevel>() else {
+            return;
+        };
+        let Some(point) = widget.compute_point(
+            &native,
+            &gtk4::graphene::Point::new(start_x as f32, start_y as f32),
+        ) else {
+            return;
+        };
+        let (surface_x, surface_y) = native.surface_transform();
+        let button = gesture.current_button() as i32;
+        let time = gesture.current_event_time();
+        gesture.set_state(gtk4::EventSequenceState::Claimed);
+        gesture.reset();
+        toplevel.begin_move(
+            &device,
+            button,
+            f64::from(point.x()) + surface_x,
+            f64::from(point.y()) + surface_y,
+            time,
+        );
+    });
+    handle.add_controller(drag);
+}
+
 fn temporary_keybinds_disable(widget: &PreferencesDialog) {
     // Disable and enable keybinds while settings are open
     let ctx = TuxSplitContext::get_instance();
@@ -0,0 +1,601 @@
+//! Attempt history viewer: lists the attempts of the run with their tags,
+//! filtered by tag over the statistics of the attempts shown and a realistic goal
+//! from the segment history, and compares any two of them segment by segment.
+//! Attempts can be deleted from the history.
+
+use adw::prelude::*;
+use adw::{ActionRow, AlertDialog, ViewStack, ViewSwitcher};
+use gtk4::{
+    Align, Button, DropDown, Entry, Grid, Label, ListBox, MenuButton, Orientation, Popover,
+    ScrolledWindow, SelectionMode, StringList,
+};
+use livesplit_core::{Run, TimeSpan, TimerPhase, TimingMethod};
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use crate::config::Config;
+use crate::context::TuxSplitContext;
+use crate::formatters::TimeFormat;
+use crate::ui::error::show_attempt_in_progress;
+use crate::utils::attempt_history::{AttemptStats, attempt_stats, remove_attempt};
+use crate::utils::attempts::{TimeLoss, TimeLosses, compare_attempts, time_losses};
+use crate::utils::comparisons::format_signed;
+use crate::utils::golds::attempt_date;
+use crate::utils::percentiles::{GOAL_PERCENTILE, goal_name, percentile_goal};
+
+/// Shown for segments an attempt has no time on.
+const NO_TIME: &str = "—";
+/// First entry of the tag filter.
+const ALL_ATTEMPTS: &str = "All Attempts";
+
+/// Presents the attempt history, newest first. Selecting two attempts enables
+/// "Compare".
+pub fn present_history_dialog(parent: &impl IsA<gtk4::Widget>) {
+    let dialog = AlertDialog::builder()
+        .heading("Attempt History")
+        .body("Select two attempts to compare their segments.")
+        .default_response("compare")
+        .close_response("close")
+        .build();
+    dialog.add_response("close", "_Close");
+    dialog.add_response("compare", "Com_pare");
+    dialog.set_response_appearance("compare", adw::ResponseAppearance::Suggested);
+    dialog.set_response_enabled("compare", false);
+
+    let view = HistoryView::new();
+    let content = gtk4::Box::new(Orientation::Vertical, 12);
+    let filter_row = gtk4::Box::new(Orientation::Horizontal, 6);
+    filter_row.append(&Label::new(Some("Show")));
+    filter_row.append(&view.filter);
+    content.append(&filter_row);
+    content.append(&view.summary);
+    let scroller = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .min_content_height(300)
+        .child(&view.list)
+        .build();
+    content.append(&scroller);
+    dialog.set_extra_child(Some(&content));
+
+    let dialog_binding = dialog.clone();
+    view.list.connect_selected_rows_changed(move |list| {
+        dialog_binding.set_response_enabled("compare", list.selected_rows().len() == 2);
+    });
+
+    let parent_binding = parent.as_ref().clone();
+    let view_binding = view.clone();
+    dialog.connect_response(Some("compare"), move |_, _| {
+        let shown = view_binding.shown.borrow();
+        let selected: Vec<i32> = view_binding
+            .list
+            .selected_rows()
+            .iter()
+            .filter_map(|row| usize::try_from(row.index()).ok())
+            .filter_map(|position| shown.get(position).copied())
+            .collect();
+        // Rows are newest first, so the newer attempt is compared against the older
+        if let [a, b] = selected[..] {
+            present_comparison_dialog(&parent_binding, a, b);
+        }
+    });
+
+    dialog.present(Some(parent));
+}
+
+/// The widgets of the history dialog, over its own copy of the run so deleting
+/// shows right away, even while the edited run is being regenerated.
+struct HistoryView {
+    run: RefCell<Run>,
+    method: TimingMethod,
+    format: TimeFormat,
+    filter: DropDown,
+    summary: Label,
+    list: ListBox,
+    /// Attempts of the rows of `list`, in order.
+    shown: RefCell<Vec<i32>>,
+}
+
+impl HistoryView {
+    fn new() -> Rc<Self> {
+        let ctx = TuxSplitContext::get_instance();
+        let view = Rc::new(Self {
+            run: RefCell::new(ctx.get_run()),
+            method: ctx.timer().read().unwrap().current_timing_method(),
+            format: ctx.config().format.split.clone(),
+            filter: DropDown::from_strings(&[ALL_ATTEMPTS]),
+            summary: Label::builder()
+                .xalign(0.0)
+                .wrap(true)
+                .css_classes(["dim-label"])
+                .build(),
+            list: ListBox::builder()
+                .selection_mode(SelectionMode::Multiple)
+                .css_classes(["boxed-list"])
+                .build(),
+            shown: RefCell::default(),
+        });
+        view.refresh();
+        let weak = Rc::downgrade(&view);
+        view.filter.connect_selected_notify(move |_| {
+            if let Some(view) = weak.upgrade() {
+                view.refresh_list();
+            }
+        });
+        view
+    }
+
+    /// The tag the list is filtered by, if any.
+    fn selected_tag(&self) -> Option<String> {
+        let tag = self
+            .filter
+            .selected_item()
+            .and_downcast::<gtk4::StringObject>()?;
+        (self.filter.selected() > 0).then(|| tag.string().into())
+    }
+
+    /// Refreshes the tags of the filter, keeping the one selected while it is
+    /// still in use, then the list.
+    fn refresh(self: &Rc<Self>) {
+        let selected = self.selected_tag();
+        let tags = TuxSplitContext::get_instance().sidecar().all_attempt_tags();
+        let current: Vec<String> = self
+            .filter
+            .model()
+            .and_downcast::<StringList>()
+            .map(|model| {
+                (1..model.n_items())
+                    .filter_map(|position| model.string(position))
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        if current != tags {
+            let mut entries = vec![ALL_ATTEMPTS];
+            entries.extend(tags.iter().map(String::as_str));
+            let position = selected
+                .and_then(|tag| tags.iter().position(|t| *t == tag))
+                .map_or(0, |position| position as u32 + 1);
+            // Selecting in the new model refreshes the list
+            self.filter.set_model(Some(&StringList::new(&entries)));
+            self.filter.set_selected(position);
+        }
+        self.refresh_list();
+    }
+
+    fn refresh_list(self: &Rc<Self>) {
+        let ctx = TuxSplitContext::get_instance();
+        let tag = self.selected_tag();
+        let run = self.run.borrow();
+        let shown: Vec<i32> = {
+            let sidecar = ctx.sidecar();
+            run.attempt_history()
+                .iter()
+                .rev()
+                .map(livesplit_core::run::Attempt::index)
+                .filter(|&index| {
+                    tag.as_ref()
+                        .is_none_or(|tag| sidecar.attempt_tags(index).contains(tag))
+                })
+                .collect()
+        };
+
+        self.list.remove_all();
+        for &index in &shown {
+            self.list.append(&self.attempt_row(&run, index));
+        }
+        if shown.is_empty() {
+            self.list.set_selection_mode(SelectionMode::None);
+            self.list.append(
+                &ActionRow::builder()
+                    .title("No attempts yet")
+                    .css_classes(["dim-label"])
+                    .build(),
+            );
+        } else {
+            self.list.set_selection_mode(SelectionMode::Multiple);
+        }
+        let goal = percentile_goal(&run, self.method, GOAL_PERCENTILE);
+        self.summary
+            .set_label(&self.describe(attempt_stats(&run, &shown, self.method), goal));
+        self.shown.replace(shown);
+    }
+
+    /// The statistics of the attempts shown, then the goal of the whole history.
+    fn describe(&self, stats: AttemptStats, goal: Option<TimeSpan>) -> String {
+        let mut text = format!("{} attempts, {} finished", stats.attempts, stats.finished);
+        if let Some(rate) = stats.finish_rate() {
+            text.push_str(&format!(" ({rate}%)"));
+        }
+        let format = |time: Option<TimeSpan>| time.map(|t| self.format.format_time_span(&t));
+        if let (Some(best), Some(average)) = (format(stats.best), format(stats.average)) {
+            text.push_str(&format!(" · best {best}, average {average}"));
+        }
+        if let Some(goal) = goal {
+            text.push_str(&format!(
+                "\n{}: {}",
+                goal_name(GOAL_PERCENTILE),
+                self.format.format_time_span(&goal)
+            ));
+        }
+        text
+    }
+
+    fn attempt_row(self: &Rc<Self>, run: &Run, index: i32) -> ActionRow {
+        let attempt = run
+            .attempt_history()
+            .iter()
+            .find(|attempt| attempt.index() == index);
+        let time = attempt
+            .and_then(|attempt| attempt.time()[self.method])
+            .map_or_else(
+                || "Reset".to_owned(),
+                |time| self.format.format_time_span(&time),
+            );
+        let date = attempt_date(run, index).map_or_else(|| "Undated".to_owned(), |d| d.to_string());
+
+        let row = ActionRow::builder()
+            .title(format!("Attempt #{index}"))
+            .subtitle(date)
+            .build();
+        let tags = TuxSplitContext::get_instance()
+            .sidecar()
+            .attempt_tags(index)
+            .to_vec();
+        for tag in tags {
+            row.add_suffix(&self.tag_chip(index, tag));
+        }
+        row.add_suffix(&self.add_tag_button(index));
+        row.add_suffix(
+            &Label::builder()
+                .label(time)
+                .css_classes(["numeric"])
+                .build(),
+        );
+        row.add_suffix(&self.delete_button(index));
+        row
+    }
+
+    /// A tag of `attempt`, removed when clicked.
+    fn tag_chip(self: &Rc<Self>, attempt: i32, tag: String) -> Button {
+        let chip = Button::builder()
+            .child(
+                &adw::ButtonContent::builder()
+                    .label(&tag)
+                    .icon_name("window-close-symbolic")
+                    .build(),
+            )
+            .valign(Align::Center)
+            .tooltip_text("Remove Tag")
+            .css_classes(["flat", "caption"])
+            .build();
+        let weak = Rc::downgrade(self);
+        chip.connect_clicked(move |_| {
+            let ctx = TuxSplitContext::get_instance();
+            ctx.sidecar_mut().remove_attempt_tag(attempt, &tag);
+            // The sidecar is written along with the splits
+            ctx.mark_run_modified();
+            refresh(&weak);
+        });
+        chip
+    }
+
+    /// Asks for a tag to add to `attempt`.
+    fn add_tag_button(self: &Rc<Self>, attempt: i32) -> MenuButton {
+        let entry = Entry::builder().placeholder_text("race, practice…").build();
+        let popover = Popover::builder().child(&entry).build();
+        let weak = Rc::downgrade(self);
+        let popover_binding = popover.clone();
+        entry.connect_activate(move |entry| {
+            let ctx = TuxSplitContext::get_instance();
+            let added = ctx.sidecar_mut().add_attempt_tag(attempt, &entry.text());
+            popover_binding.popdown();
+            if added {
+                ctx.mark_run_modified();
+                refresh(&weak);
+            }
+        });
+        MenuButton::builder()
+            .icon_name("list-add-symbolic")
+            .valign(Align::Center)
+            .tooltip_text("Add Tag")
+            .popover(&popover)
+            .css_classes(["flat"])
+            .build()
+    }
+
+    /// Deletes `attempt` from the history after confirmation. Not while an
+    /// attempt is running, as the timer would record it with a stale number.
+    fn delete_button(self: &Rc<Self>, attempt: i32) -> Button {
+        let button = Button::builder()
+            .icon_name("user-trash-symbolic")
+            .valign(Align::Center)
+            .tooltip_text("Delete Attempt")
+            .css_classes(["flat"])
+            .build();
+        let phase = TuxSplitContext::get_instance()
+            .timer()
+            .read()
+            .unwrap()
+            .current_phase();
+        button.set_sensitive(phase == TimerPhase::NotRunning);
+
+        let weak = Rc::downgrade(self);
+        button.connect_clicked(move |button| {
+            let dialog = AlertDialog::builder()
+                .heading(format!("Delete Attempt #{attempt}?"))
+                .body(
+                    "Its times and tags are removed from the history, and later attempts are \
+                     numbered one lower. The personal best and best segments stay as they are.",
+                )
+                .default_response("cancel")
+                .close_response("cancel")
+                .build();
+            dialog.add_response("cancel", "_Cancel");
+            dialog.add_response("delete", "_Delete");
+            dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+            let weak = weak.clone();
+            let parent = button.clone();
+            dialog.connect_response(Some("delete"), move |_, _| {
+                let Some(view) = weak.upgrade() else {
+                    return;
+                };
+                let ctx = TuxSplitContext::get_instance();
+                // An attempt may have started while the dialog was open
+                if ctx.timer().read().unwrap().current_phase() != TimerPhase::NotRunning {
+                    show_attempt_in_progress(&parent);
+                    return;
+                }
+                let Some(run) = remove_attempt(&view.run.borrow(), attempt) else {
+                    return;
+                };
+                let batch = ctx.freeze_notifications();
+                ctx.take_run_snapshot(&view.run.borrow(), "Attempt deletion");
+                ctx.sidecar_mut().remove_attempt(attempt);
+                ctx.set_edited_run(run.clone());
+                drop(batch);
+                view.run.replace(run);
+                view.refresh();
+            });
+            dialog.present(Some(button));
+        });
+        button
+    }
+}
+
+fn refresh(view: &Weak<HistoryView>) {
+    if let Some(view) = view.upgrade() {
+        view.refresh();
+    }
+}
+
+/// Presents the per-segment times of attempts `a` and `b` with the time `a` lost
+/// or gained on each, and those segments ranked by the time lost.
+fn present_comparison_dialog(parent: &gtk4::Widget, a: i32, b: i32) {
+    let ctx = TuxSplitContext::get_instance();
+    let run = ctx.get_run();
+    let method = ctx.timer().read().unwrap().current_timing_method();
+    let config = ctx.config();
+    let comparison = compare_attempts(&run, a, b, method);
+
+    let grid = Grid::builder()
+        .column_spacing(18)
+        .row_spacing(6)
+        .halign(Align::Center)
+        .build();
+    let headers = ["Segment", &format!("#{a}"), &format!("#{b}"), "Delta"];
+    for (column, header) in (0..).zip(headers) {
+        grid.attach(&cell(header, &["heading"]), column, 0, 1, 1);
+    }
+
+    let format_time = |time: Option<TimeSpan>| {
+        time.map_or_else(
+            || NO_TIME.to_owned(),
+            |time| config.format.split.format_time_span(&time),
+        )
+    };
+    let delta_cell = |delta: Option<TimeSpan>| {
+        delta.map_or_else(
+            || cell(NO_TIME, &["dim-label"]),
+            |delta| {
+                let delta = delta.to_duration();
+                cell(
+                    &format_signed(delta, &config),
+                    &["numeric", delta_class(delta)],
+                )
+            },
+        )
+    };
+
+    let segments = run.segments();
+    let mut row = 1;
+    for segment in &comparison.segments {
+        let name = span_name(&run, segment.first, segment.index);
+        grid.attach(&cell(&name, &[]), 0, row, 1, 1);
+        grid.attach(&cell(&format_time(segment.a), &["numeric"]), 1, row, 1, 1);
+        grid.attach(&cell(&format_time(segment.b), &["numeric"]), 2, row, 1, 1);
+        grid.attach(&delta_cell(segment.delta()), 3, row, 1, 1);
+        row += 1;
+    }
+    grid.attach(&cell("Total", &["heading"]), 0, row, 1, 1);
+    for (column, total) in [(1, comparison.total_a), (2, comparison.total_b)] {
+        grid.attach(
+            &cell(&format_time(Some(total)), &["numeric"]),
+            column,
+            row,
+            1,
+            1,
+        );
+    }
+    grid.attach(&delta_cell(Some(comparison.total_delta())), 3, row, 1, 1);
+
+    let best = |index: usize| segments[index].best_segment_time()[method];
+    let losses = time_losses(&comparison, best);
+    let pages = ViewStack::builder().vhomogeneous(false).build();
+    pages.add_titled(&scrolled(&grid), Some("segments"), "Segments");
+    pages.add_titled(
+        &scrolled(&losses_grid(&run, &losses, (a, b), &config)),
+        Some("losses"),
+        "Time Losses",
+    );
+    let switcher = ViewSwitcher::builder()
+        .stack(&pages)
+        .policy(adw::ViewSwitcherPolicy::Wide)
+        .build();
+    let content = gtk4::Box::new(Orientation::Vertical, 12);
+    content.append(&switcher);
+    content.append(&pages);
+
+    // Clock jumps recorded during either attempt make its real time unreliable
+    let mut body = "Segments either attempt skipped are compared together with the next \
+                    one, those either attempt did not reach are left out of the totals."
+        .to_owned();
+    let sidecar = ctx.sidecar();
+    for index in [a, b] {
+        for jump in sidecar.clock_jumps(index) {
+            body.push_str(&format!("\n#{index}: {}", jump.describe()));
+        }
+    }
+
+    let dialog = AlertDialog::builder()
+        .heading(format!("Attempt #{a} vs #{b}"))
+        .body(body)
+        .extra_child(&content)
+        .default_response("close")
+        .close_response("close")
+        .build();
+    dialog.add_response("close", "_Close");
+    dialog.present(Some(parent));
+}
+
+/// The segments of `losses` in two sections, time lost then time gained (with
+/// golds marked), over a total that matches the one of the comparison.
+fn losses_grid(run: &Run, losses: &TimeLosses, (a, b): (i32, i32), config: &Config) -> Grid {
+    let grid = Grid::builder()
+        .column_spacing(18)
+        .row_spacing(6)
+        .halign(Align::Center)
+        .build();
+    let headers = ["Segment", &format!("#{b}"), &format!("#{a}"), "Difference"];
+    for (column, header) in (0..).zip(headers) {
+        grid.attach(&cell(header, &["heading"]), column, 0, 1, 1);
+    }
+
+    let format_time = |time: &TimeSpan| config.format.split.format_time_span(time);
+    let mut row = 1;
+    let attach = |loss: &TimeLoss, row: i32| {
+        let name = span_name(run, loss.first, loss.index);
+        let name = if loss.gold {
+            cell(&format!("{name} (gold)"), &["goldsplit"])
+        } else {
+            cell(&name, &[])
+        };
+        let delta = loss.delta().to_duration();
+        grid.attach(&name, 0, row, 1, 1);
+        grid.attach(
+            &cell(&format_time(&loss.comparison), &["numeric"]),
+            1,
+            row,
+            1,
+            1,
+        );
+        grid.attach(
+            &cell(&format_time(&loss.actual), &["numeric"]),
+            2,
+            row,
+            1,
+            1,
+        );
+        grid.attach(
+            &cell(
+                &format_signed(delta, config),
+                &["numeric", delta_class(delta)],
+            ),
+            3,
+            row,
+            1,
+            1,
+        );
+    };
+    let sections = [
+        ("Time Lost", &losses.losses),
+        ("Time Gained", &losses.gains),
+    ];
+    for (title, section) in sections {
+        if section.is_empty() {
+            continue;
+        }
+        grid.attach(
+            &cell(title, &["caption-heading", "dim-label"]),
+            0,
+            row,
+            4,
+            1,
+        );
+        row += 1;
+        for loss in section {
+            attach(loss, row);
+            row += 1;
+        }
+    }
+
+    let total = losses.total().to_duration();
+    grid.attach(&cell("Total", &["heading"]), 0, row, 1, 1);
+    grid.attach(
+        &cell(
+            &format_signed(total, config),
+            &["numeric", delta_class(total)],
+        ),
+        3,
+        row,
+        1,
+        1,
+    );
+    if !losses.skipped.is_empty() {
+        let count = losses.skipped.len();
+        let note = if count == 1 {
+            "1 segment an attempt did not reach is left out".to_owned()
+        } else {
+            format!("{count} segments an attempt did not reach are left out")
+        };
+        grid.attach(&cell(&note, &["dim-label"]), 0, row + 1, 4, 1);
+    }
+    grid
+}
+
+/// Name of the segment at `index`, or of the segments from `first` to it when a
+/// skipped split merged them.
+fn span_name(run: &Run, first: usize, index: usize) -> String {
+    let segments = run.segments();
+    if first == index {
+        segments[index].name().to_owned()
+    } else {
+        format!("{} – {}", segments[first].name(), segments[index].name())
+    }
+}
+
+fn scrolled(child: &impl IsA<gtk4::Widget>) -> ScrolledWindow {
+    ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .min_content_height(300)
+        .child(child)
+        .build()
+}
+
+fn cell(text: &str, classes: &[&str]) -> Label {
+    Label::builder()
+        .label(text)
+        .xalign(0.0)
+        .css_classes(classes)
+        .build()
+}
+
+const fn delta_class(delta: time::Duration) -> &'static str {
+    if delta.is_negative() {
+        "greensplit"
+    } else if delta.is_positive() {
+        "redsplit"
+    } else {
+        "dim-label"
+    }
+}
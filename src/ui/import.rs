@@ -0,0 +1,263 @@
+//! "Import from CSV..." menu action: replaces the segments of the run with those
+//! of a route document (see `crate::import`), after previewing the first rows and
+//! choosing which columns hold the names and times.
+
+use adw::prelude::*;
+use adw::{AlertDialog, ComboRow, PreferencesGroup};
+use gtk4::{
+    Align, Box as GtkBox, FileDialog, FileFilter, Grid, Label, Orientation, ScrolledWindow,
+    StringList, gio,
+};
+use livesplit_core::TimerPhase;
+
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::context::TuxSplitContext;
+use crate::error::TuxSplitError;
+use crate::import::{self, ColumnMapping, CsvTable, ImportPlan, TimeKind};
+use crate::ui::error::{show_attempt_in_progress, show_error};
+
+/// Rows shown in the preview.
+const PREVIEW_ROWS: usize = 10;
+/// Unreadable cells listed after importing before the rest is summarized.
+const LISTED_CELLS: usize = 10;
+/// Entries of the "Time Is" row, in `TimeKind` order.
+const TIME_KINDS: [(&str, TimeKind); 2] = [
+    ("Segment Time", TimeKind::SegmentTime),
+    ("Split Time", TimeKind::SplitTime),
+];
+
+/// Asks for the CSV file to import.
+pub fn present_import_csv(parent: &adw::ApplicationWindow) {
+    let phase = TuxSplitContext::get_instance()
+        .timer()
+        .read()
+        .unwrap()
+        .current_phase();
+    if phase != TimerPhase::NotRunning {
+        show_attempt_in_progress(parent);
+        return;
+    }
+
+    let csv_filter = FileFilter::new();
+    csv_filter.set_name(Some("CSV Files (*.csv)"));
+    csv_filter.add_pattern("*.csv");
+    csv_filter.add_mime_type("text/csv");
+    let all_filter = FileFilter::new();
+    all_filter.set_name(Some("All Files"));
+    all_filter.add_pattern("*");
+    let filters = gio::ListStore::new::<FileFilter>();
+    filters.append(&csv_filter);
+    filters.append(&all_filter);
+    let file_dialog = FileDialog::builder()
+        .title("Import from CSV")
+        .modal(true)
+        .filters(&filters)
+        .build();
+
+    let parent_binding = parent.clone();
+    file_dialog.open(Some(parent), None::<&gio::Cancellable>, move |result| {
+        let Some(path) = result.ok().and_then(|file| file.path()) else {
+            return;
+        };
+        match read_table(&path) {
+            Ok(table) => present_preview(&parent_binding, &path, table),
+            Err(e) => show_error(&parent_binding, &e),
+        }
+    });
+}
+
+fn read_table(path: &Path) -> Result<CsvTable, TuxSplitError> {
+    let parse_error = |source: Box<dyn std::error::Error + Send + Sync>| TuxSplitError::RunParse {
+        path: path.to_owned(),
+        source,
+    };
+    let text = std::fs::read(path).map_err(|e| parse_error(e.into()))?;
+    let text = String::from_utf8_lossy(&text);
+    import::parse_csv(&text).map_err(|e| parse_error(e.into()))
+}
+
+/// The mapping chosen in the rows of the preview.
+fn selected_mapping(name: &ComboRow, time: &ComboRow, kind: &ComboRow) -> ColumnMapping {
+    let time_column = time.selected().checked_sub(1);
+    let (_, kind) = TIME_KINDS[kind.selected() as usize % TIME_KINDS.len()];
+    ColumnMapping {
+        name: name.selected() as usize,
+        time: time_column.map(|column| (column as usize, kind)),
+    }
+}
+
+fn summary(plan: &ImportPlan) -> String {
+    let mut summary = format!("{} segments", plan.segments.len());
+    if !plan.invalid.is_empty() {
+        summary.push_str(&format!(", {} unreadable times", plan.invalid.len()));
+    }
+    summary
+}
+
+/// The first rows of `table` with rows to map its columns, importing on
+/// confirmation.
+fn present_preview(parent: &adw::ApplicationWindow, path: &Path, table: CsvTable) {
+    let guess = ColumnMapping::guess(&table.headers);
+    let headers: Vec<&str> = table.headers.iter().map(String::as_str).collect();
+
+    let name_row = ComboRow::builder()
+        .title("Segment Name")
+        .model(&StringList::new(&headers))
+        .selected(guess.name as u32)
+        .build();
+    let time_columns = StringList::new(&["None"]);
+    time_columns.splice(1, 0, &headers);
+    let time_row = ComboRow::builder()
+        .title("Time")
+        .model(&time_columns)
+        .selected(guess.time.map_or(0, |(column, _)| column as u32 + 1))
+        .build();
+    let kind_names: Vec<&str> = TIME_KINDS.iter().map(|(name, _)| *name).collect();
+    let kind_row = ComboRow::builder()
+        .title("Time Is")
+        .model(&StringList::new(&kind_names))
+        .selected(
+            TIME_KINDS
+                .iter()
+                .position(|(_, kind)| guess.time.is_some_and(|(_, guessed)| guessed == *kind))
+                .unwrap_or(0) as u32,
+        )
+        .sensitive(guess.time.is_some())
+        .build();
+    let group = PreferencesGroup::new();
+    group.add(&name_row);
+    group.add(&time_row);
+    group.add(&kind_row);
+
+    let grid = Grid::builder()
+        .column_spacing(18)
+        .row_spacing(6)
+        .halign(Align::Center)
+        .build();
+    for (column, header) in (0..).zip(&table.headers) {
+        grid.attach(&cell(header, &["heading"]), column, 0, 1, 1);
+    }
+    for (row, (_, cells)) in (1..).zip(table.rows.iter().take(PREVIEW_ROWS)) {
+        for (column, text) in (0..).zip(cells) {
+            grid.attach(&cell(text, &[]), column, row, 1, 1);
+        }
+    }
+    let scroller = ScrolledWindow::builder()
+        .min_content_height(200)
+        .child(&grid)
+        .build();
+
+    let status = Label::builder()
+        .xalign(0.0)
+        .css_classes(["dim-label"])
+        .build();
+    let content = GtkBox::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(12)
+        .build();
+    content.append(&group);
+    content.append(&scroller);
+    content.append(&status);
+
+    let name = path.file_name().map_or_else(
+        || path.display().to_string(),
+        |n| n.to_string_lossy().into(),
+    );
+    let dialog = AlertDialog::builder()
+        .heading("Import from CSV")
+        .body(format!(
+            "The segments of the run are replaced by the rows of {name}. The first {PREVIEW_ROWS} rows are shown."
+        ))
+        .extra_child(&content)
+        .default_response("cancel")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("import", "_Import");
+    dialog.set_response_appearance("import", adw::ResponseAppearance::Destructive);
+
+    let table = Rc::new(table);
+    let refresh = {
+        let table = table.clone();
+        let (name_row, time_row, kind_row) = (name_row.clone(), time_row.clone(), kind_row.clone());
+        let dialog = dialog.downgrade();
+        move || {
+            let plan =
+                import::plan_import(&table, selected_mapping(&name_row, &time_row, &kind_row));
+            kind_row.set_sensitive(time_row.selected() > 0);
+            status.set_label(&summary(&plan));
+            if let Some(dialog) = dialog.upgrade() {
+                dialog.set_response_enabled("import", !plan.segments.is_empty());
+            }
+        }
+    };
+    refresh();
+    let refresh = Rc::new(refresh);
+    for row in [&name_row, &time_row, &kind_row] {
+        let refresh = refresh.clone();
+        row.connect_selected_notify(move |_| refresh());
+    }
+
+    let parent_binding = parent.clone();
+    dialog.connect_response(Some("import"), move |_, _| {
+        let plan = import::plan_import(&table, selected_mapping(&name_row, &time_row, &kind_row));
+        apply(&parent_binding, &plan);
+    });
+    dialog.present(Some(parent));
+}
+
+/// Replaces the segments of the run, undoably, and lists the cells that could
+/// not be read. An attempt may have started while the preview was open, so the
+/// phase is checked again.
+fn apply(parent: &adw::ApplicationWindow, plan: &ImportPlan) {
+    let ctx = TuxSplitContext::get_instance();
+    if ctx.timer().read().unwrap().current_phase() != TimerPhase::NotRunning {
+        show_attempt_in_progress(parent);
+        return;
+    }
+    // One refresh for the whole import, however it is carried out
+    let _batch = ctx.freeze_notifications();
+    let run = ctx.get_run();
+    let method = ctx.timer().read().unwrap().current_timing_method();
+    let Some(imported) = import::apply_import(run.clone(), plan, method) else {
+        return;
+    };
+    ctx.take_run_snapshot(&run, "CSV import");
+    ctx.set_run(imported);
+
+    if plan.invalid.is_empty() {
+        return;
+    }
+    let mut lines: Vec<String> = plan
+        .invalid
+        .iter()
+        .take(LISTED_CELLS)
+        .map(|(line, text)| format!("Row {line}: “{text}”"))
+        .collect();
+    if plan.invalid.len() > LISTED_CELLS {
+        lines.push(format!("and {} more", plan.invalid.len() - LISTED_CELLS));
+    }
+    let dialog = AlertDialog::builder()
+        .heading("Some Times Were Not Imported")
+        .body(format!(
+            "These cells are not times, so their segments have none:\n{}",
+            lines.join("\n")
+        ))
+        .default_response("ok")
+        .close_response("ok")
+        .build();
+    dialog.add_response("ok", "_OK");
+    dialog.present(Some(parent));
+}
+
+fn cell(text: &str, classes: &[&str]) -> Label {
+    Label::builder()
+        .label(text)
+        .xalign(0.0)
+        .ellipsize(gtk4::pango::EllipsizeMode::End)
+        .max_width_chars(24)
+        .css_classes(classes)
+        .build()
+}
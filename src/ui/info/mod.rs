@@ -1,15 +1,22 @@
 use crate::config::Config;
+use crate::context::TuxSplitContext;
 use crate::utils::comparisons::{
-    best_comparison_values, best_segment_duration, classify_split_label,
-    current_attempt_running_duration, format_signed, previous_comparison_values,
-    previous_split_combined_gold_and_prev_comparison, real_time_sob, segment_best_time,
+    PbPosition, best_comparison_values, best_possible_time, best_segment_duration,
+    classify_split_label, current_attempt_running_duration, format_signed, pb_position,
+    pb_split_times, possible_time_save, previous_comparison_values, segment_best_time,
     segment_comparison_time, segment_split_time,
 };
 
 use gtk4::{CenterBox, Label, Orientation::Horizontal, prelude::WidgetExt};
 
-use livesplit_core::Timer;
+use crate::formatters::clock::{finish_eta, format_eta};
+use crate::utils::goal::{format_remaining, is_behind, parse_goal, timer_remaining};
+use crate::utils::loads::LoadTimeTracker;
+
+use std::time::{Duration, Instant};
+
 use livesplit_core::analysis::{current_pace, pb_chance, total_playtime};
+use livesplit_core::{Timer, TimerPhase};
 
 pub enum AdditionalInfoKind {
     PrevSegmentDiff,
@@ -19,9 +26,13 @@ pub enum AdditionalInfoKind {
     CurrentPace,
     TotalPlaytime,
     PbChance,
+    FinishEta,
+    LoadTime,
+    PbPosition,
+    Goal,
 }
 
-pub static ALL_ADDITIONAL_INFOS: [AdditionalInfoKind; 7] = [
+pub static ALL_ADDITIONAL_INFOS: [AdditionalInfoKind; 11] = [
     AdditionalInfoKind::PrevSegmentDiff,
     AdditionalInfoKind::PrevSegmentBest,
     AdditionalInfoKind::BestPossibleTime,
@@ -29,6 +40,10 @@ pub static ALL_ADDITIONAL_INFOS: [AdditionalInfoKind; 7] = [
     AdditionalInfoKind::CurrentPace,
     AdditionalInfoKind::TotalPlaytime,
     AdditionalInfoKind::PbChance,
+    AdditionalInfoKind::FinishEta,
+    AdditionalInfoKind::LoadTime,
+    AdditionalInfoKind::PbPosition,
+    AdditionalInfoKind::Goal,
 ];
 
 pub trait AdditionalInfo {
@@ -37,6 +52,17 @@ pub trait AdditionalInfo {
         Self: Sized;
     fn update(&mut self, timer: &Timer, config: &Config);
     fn container(&self) -> &CenterBox;
+    /// Whether there is something to show. Rows without a value are hidden even
+    /// when enabled.
+    fn has_value(&self) -> bool {
+        true
+    }
+}
+
+/// Whether the segment at `index` is left out of gold detection, so the rows
+/// never show it as a gold.
+fn is_excluded(timer: &Timer, index: usize) -> bool {
+    TuxSplitContext::get_instance().excluded_segments(timer.run().segments())[index]
 }
 
 pub struct PrevSegmentDiffInfo {
@@ -74,6 +100,32 @@ pub struct TotalPlaytimeInfo {
     value: Label,
 }
 
+pub struct LoadTimeInfo {
+    container: CenterBox,
+    value: Label,
+    tracker: LoadTimeTracker,
+}
+
+pub struct PbPositionInfo {
+    container: CenterBox,
+    value: Label,
+    last_update: Option<Instant>,
+    last_phase: TimerPhase,
+}
+
+pub struct GoalInfo {
+    container: CenterBox,
+    value: Label,
+    has_goal: bool,
+}
+
+pub struct FinishEtaInfo {
+    container: CenterBox,
+    value: Label,
+    last_update: Option<Instant>,
+    last_phase: TimerPhase,
+}
+
 impl AdditionalInfo for PrevSegmentDiffInfo {
     fn new(timer: &Timer, config: &Config) -> Self {
         let container = CenterBox::builder().orientation(Horizontal).build();
@@ -137,6 +189,7 @@ impl AdditionalInfo for PrevSegmentDiffInfo {
                         diff,
                         gold_duration,
                         false,
+                        is_excluded(timer, index),
                     ));
                 }
             }
@@ -213,6 +266,7 @@ impl AdditionalInfo for PrevSegmentBestInfo {
                         diff,
                         gold_duration,
                         false,
+                        is_excluded(timer, index),
                     ));
                 }
             }
@@ -250,25 +304,7 @@ impl AdditionalInfo for BestPossibleTimeInfo {
         if timer.current_phase().is_not_running() {
             self.value.set_label("");
         } else if timer.current_phase().is_running() || timer.current_phase().is_paused() {
-            let segment = timer.current_split().unwrap_or(timer.run().segment(0));
-
-            let segment_best_duration = segment_best_time(segment, timer);
-
-            // Diff to SOB
-            let diff = current_attempt_running_duration(timer)
-                .checked_sub(segment_best_duration)
-                .unwrap_or_default();
-
-            // We will be adding only diff time to the best possible time when we are behind
-            let live_addition = if diff.is_positive() {
-                diff
-            } else {
-                time::Duration::ZERO
-            };
-
-            let best_possible_time = real_time_sob(timer)
-                .checked_add(live_addition)
-                .unwrap_or_default();
+            let best_possible_time = best_possible_time(timer);
             if best_possible_time == time::Duration::ZERO {
                 self.value.set_label("");
             } else {
@@ -322,19 +358,11 @@ impl AdditionalInfo for PossibleTimeSaveInfo {
         } else if timer.current_phase().is_running() || timer.current_phase().is_paused() {
             let index = timer.current_split_index().unwrap_or(0);
 
-            let (_, combined_gold, previous_comparion_time) =
-                previous_split_combined_gold_and_prev_comparison(timer, index);
-            let current_comparison_time = segment_comparison_time(
-                timer.current_split().unwrap_or(timer.run().segment(0)),
-                timer,
-            );
+            let excluded =
+                TuxSplitContext::get_instance().excluded_segments(timer.run().segments());
 
             // Diff from gold to comp. This is the possible time save
-            let gold_diff = current_comparison_time
-                .checked_sub(previous_comparion_time)
-                .unwrap_or_default()
-                .checked_sub(combined_gold)
-                .unwrap_or_default();
+            let gold_diff = possible_time_save(timer, index, &excluded);
 
             self.value.set_label(
                 config
@@ -456,3 +484,251 @@ impl AdditionalInfo for TotalPlaytimeInfo {
         &self.container
     }
 }
+
+impl FinishEtaInfo {
+    /// The ETA only moves when the attempt drifts from its prediction, so it is not
+    /// worth recomputing every frame.
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+}
+
+impl AdditionalInfo for FinishEtaInfo {
+    fn new(timer: &Timer, config: &Config) -> Self {
+        let container = CenterBox::builder().orientation(Horizontal).build();
+
+        let label = Label::builder()
+            .label("Finish Time:")
+            .css_classes(["heading"])
+            .build();
+        let value = Label::builder().label("").css_classes(["timer"]).build();
+
+        container.set_start_widget(Some(&label));
+        container.set_end_widget(Some(&value));
+
+        let mut res = Self {
+            container,
+            value,
+            last_update: None,
+            last_phase: timer.current_phase(),
+        };
+
+        res.update(timer, config); // Initialize with default timer state
+
+        res
+    }
+
+    fn update(&mut self, timer: &Timer, _config: &Config) {
+        let phase = timer.current_phase();
+        let due = self
+            .last_update
+            .is_none_or(|last| last.elapsed() >= Self::REFRESH_INTERVAL);
+        if !due && phase == self.last_phase {
+            return;
+        }
+        self.last_update = Some(Instant::now());
+        self.last_phase = phase;
+
+        if !(phase.is_running() || phase.is_paused()) {
+            self.value.set_label("");
+            return;
+        }
+
+        let snapshot = timer.snapshot();
+        let (predicted, _) = current_pace::calculate(&snapshot, timer.current_comparison());
+        let eta = predicted.and_then(|predicted| {
+            let remaining = predicted.to_duration() - current_attempt_running_duration(timer);
+            finish_eta(&glib::DateTime::now_local().ok()?, remaining)
+        });
+
+        self.value
+            .set_label(&eta.as_ref().and_then(format_eta).unwrap_or_default());
+    }
+
+    fn container(&self) -> &CenterBox {
+        &self.container
+    }
+
+    fn has_value(&self) -> bool {
+        !self.value.label().is_empty()
+    }
+}
+
+impl AdditionalInfo for LoadTimeInfo {
+    fn new(timer: &Timer, config: &Config) -> Self {
+        let container = CenterBox::builder().orientation(Horizontal).build();
+
+        let label = Label::builder()
+            .label("Loads:")
+            .css_classes(["heading"])
+            .build();
+        let value = Label::builder().label("").css_classes(["timer"]).build();
+
+        container.set_start_widget(Some(&label));
+        container.set_end_widget(Some(&value));
+
+        let mut res = Self {
+            container,
+            value,
+            tracker: LoadTimeTracker::default(),
+        };
+
+        res.update(timer, config); // Initialize with default timer state
+
+        res
+    }
+
+    fn update(&mut self, timer: &Timer, config: &Config) {
+        // Once ended, the times are those of the last split, so the loads freeze
+        let (real, game) = if timer.current_phase() == TimerPhase::NotRunning {
+            (None, None)
+        } else {
+            let time = timer.snapshot().current_time();
+            (time.real_time, time.game_time)
+        };
+
+        match self.tracker.observe(real, game) {
+            Some(load_time) => {
+                self.value
+                    .set_label(&config.format.segment.format_duration(&load_time.loads));
+                self.value.set_tooltip_text(Some(&load_time.summary()));
+            }
+            None => {
+                self.value.set_label("");
+                self.value.set_tooltip_text(None);
+            }
+        }
+    }
+
+    fn container(&self) -> &CenterBox {
+        &self.container
+    }
+
+    fn has_value(&self) -> bool {
+        !self.value.label().is_empty()
+    }
+}
+
+impl PbPositionInfo {
+    /// The PB moves from segment to segment every few minutes at most.
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+}
+
+impl AdditionalInfo for PbPositionInfo {
+    fn new(timer: &Timer, config: &Config) -> Self {
+        let container = CenterBox::builder().orientation(Horizontal).build();
+
+        let label = Label::builder()
+            .label("PB was:")
+            .css_classes(["heading"])
+            .build();
+        let value = Label::builder().label("").build();
+
+        container.set_start_widget(Some(&label));
+        container.set_end_widget(Some(&value));
+
+        let mut res = Self {
+            container,
+            value,
+            last_update: None,
+            last_phase: timer.current_phase(),
+        };
+
+        res.update(timer, config); // Initialize with default timer state
+
+        res
+    }
+
+    fn update(&mut self, timer: &Timer, _config: &Config) {
+        let phase = timer.current_phase();
+        let due = self
+            .last_update
+            .is_none_or(|last| last.elapsed() >= Self::REFRESH_INTERVAL);
+        if !due && phase == self.last_phase {
+            return;
+        }
+        self.last_update = Some(Instant::now());
+        self.last_phase = phase;
+
+        if !(phase.is_running() || phase.is_paused()) {
+            self.value.set_label("");
+            return;
+        }
+
+        let position = pb_position(
+            &pb_split_times(timer),
+            current_attempt_running_duration(timer),
+        );
+        let text = match position {
+            Some(PbPosition::Segment(index)) => timer
+                .run()
+                .segments()
+                .get(index)
+                .map(|segment| format!("{} (split {})", segment.name(), index + 1))
+                .unwrap_or_default(),
+            Some(PbPosition::Finished) => "PB had finished".to_owned(),
+            None => String::new(),
+        };
+        self.value.set_label(&text);
+    }
+
+    fn container(&self) -> &CenterBox {
+        &self.container
+    }
+
+    fn has_value(&self) -> bool {
+        !self.value.label().is_empty()
+    }
+}
+
+impl AdditionalInfo for GoalInfo {
+    fn new(timer: &Timer, config: &Config) -> Self {
+        let container = CenterBox::builder().orientation(Horizontal).build();
+
+        let label = Label::builder()
+            .label("Goal:")
+            .css_classes(["heading"])
+            .build();
+        let value = Label::builder().label("").css_classes(["timer"]).build();
+
+        container.set_start_widget(Some(&label));
+        container.set_end_widget(Some(&value));
+
+        let mut res = Self {
+            container,
+            value,
+            has_goal: false,
+        };
+
+        res.update(timer, config); // Initialize with default timer state
+
+        res
+    }
+
+    fn update(&mut self, timer: &Timer, config: &Config) {
+        let goal = parse_goal(config.general.goal_time.as_deref());
+        self.has_goal = goal.is_some();
+        let Some(goal) = goal else {
+            return;
+        };
+
+        let remaining = timer_remaining(goal, timer);
+        self.value
+            .set_label(&format_remaining(remaining, &config.format.split));
+        if is_behind(remaining) {
+            self.value.add_css_class("redsplit");
+        } else {
+            self.value.remove_css_class("redsplit");
+        }
+        self.value.set_tooltip_text(Some(&format!(
+            "Time left before the goal of {}",
+            config.format.split.format_duration(&goal)
+        )));
+    }
+
+    fn container(&self) -> &CenterBox {
+        &self.container
+    }
+
+    fn has_value(&self) -> bool {
+        self.has_goal
+    }
+}
@@ -1,10 +1,15 @@
 use adw::{
-    ComboRow, ExpanderRow, PreferencesDialog, PreferencesGroup, PreferencesPage, SpinRow,
-    SwitchRow, prelude::*,
+    ActionRow, AlertDialog, ComboRow, ExpanderRow, PreferencesDialog, PreferencesGroup,
+    PreferencesPage, SpinRow, SwitchRow, prelude::*,
 };
-use gtk4::{self as gtk, StringList};
+use gtk4::{self as gtk, FileDialog, FileFilter, StringList, pango};
 use livesplit_core::TimingMethod;
 
+use std::path::Path;
+
+use crate::config::{Animations, ColorOverrides, Headerbar};
+use crate::utils::lsl::{ImportedLayout, LayoutComponent, parse_layout};
+
 #[derive(Clone, Copy)]
 enum FormatTarget {
     Timer,
@@ -56,6 +61,7 @@ impl TimerPreferencesDialog {
         let timing_group = PreferencesGroup::builder().title("Timing").build();
         let timing_row = self.build_timing_method_row();
         timing_group.add(&timing_row);
+        timing_group.add(&Self::build_preflight_row());
         page.add(&timing_group);
 
         // Additional Info Visibility Group
@@ -77,6 +83,7 @@ impl TimerPreferencesDialog {
                         if let Ok(mut cfg) =
                             crate::context::TuxSplitContext::get_instance().config_mut()
                         {
+                            cfg.mark_dirty();
                             cfg.general.additional_info.$field = r.is_active();
                             drop(cfg);
                             crate::context::TuxSplitContext::get_instance().emit_run_changed();
@@ -128,13 +135,116 @@ impl TimerPreferencesDialog {
                 "Toggle visibility of the probability of achieving a Personal Best",
                 show_pb_chance
             );
+            add_switch!(
+                goal_row,
+                "Show Goal",
+                "Toggle visibility of the time left before the goal time, when one is set",
+                show_goal
+            );
         }
 
+        additional_info_group.add(&Self::build_eta_row());
+        additional_info_group.add(&Self::build_load_time_row());
+        additional_info_group.add(&Self::build_pb_position_row());
+
         page.add(&additional_info_group);
+        #[cfg(feature = "remote-control")]
+        page.add(&Self::build_remote_group());
 
         page
     }
 
+    #[cfg(feature = "remote-control")]
+    fn build_remote_group() -> PreferencesGroup {
+        let group = PreferencesGroup::builder()
+            .title("Remote Control")
+            .description(
+                "Lets another program on this computer undo, skip, pause and resume, \
+                 e.g. for a co-commentator correcting splits",
+            )
+            .build();
+        let ctx = crate::context::TuxSplitContext::get_instance();
+        let remote = ctx.config().remote.clone();
+
+        let enabled_row = SwitchRow::builder()
+            .title("Enable Remote Control")
+            .active(remote.enabled)
+            .build();
+        if !crate::safe_mode::current().remote_control() {
+            enabled_row.set_subtitle("Off in safe mode");
+            enabled_row.set_sensitive(false);
+        }
+        enabled_row.connect_active_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let active = r.is_active();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.remote.enabled = active;
+            }
+            if let Err(e) = ctx.set_remote_enabled(active, crate::safe_mode::current()) {
+                crate::ui::error::show_error(r, &e);
+                r.set_active(false);
+            }
+        });
+
+        let port_row = SpinRow::with_range(1024.0, f64::from(u16::MAX), 1.0);
+        port_row.set_title("Port");
+        port_row.set_value(f64::from(remote.port));
+        {
+            let enabled_row = enabled_row.clone();
+            port_row.connect_value_notify(move |r| {
+                let ctx = crate::context::TuxSplitContext::get_instance();
+                let port = r.value().round() as u16;
+                if let Ok(mut cfg) = ctx.config_mut() {
+                    cfg.mark_dirty();
+                    cfg.remote.port = port;
+                }
+                // Listen on the new port right away
+                if enabled_row.is_active()
+                    && let Err(e) = ctx.set_remote_enabled(true, crate::safe_mode::current())
+                {
+                    crate::ui::error::show_error(r, &e);
+                    enabled_row.set_active(false);
+                }
+            });
+        }
+
+        let token_row = ActionRow::builder()
+            .title("Token")
+            .subtitle(format!(
+                "Sent in the {} header, new every time TuxSplit starts",
+                crate::integrations::remote::TOKEN_HEADER
+            ))
+            .build();
+        group.add(&enabled_row);
+        group.add(&port_row);
+        group.add(&token_row);
+
+        let token = match ctx.remote_token() {
+            Ok(token) => token,
+            Err(e) => {
+                token_row.set_subtitle(&format!("{e}: {}", e.details()));
+                enabled_row.set_sensitive(false);
+                return group;
+            }
+        };
+        let token_label = gtk::Label::builder()
+            .label(&token)
+            .selectable(true)
+            .css_classes(["monospace"])
+            .build();
+        let copy_button = gtk::Button::builder()
+            .icon_name("edit-copy-symbolic")
+            .tooltip_text("Copy")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        copy_button.connect_clicked(move |b| b.clipboard().set_text(&token));
+        token_row.add_suffix(&token_label);
+        token_row.add_suffix(&copy_button);
+        group
+    }
+
     fn build_style_page(&self) -> PreferencesPage {
         let page = PreferencesPage::builder()
             .title("Style")
@@ -158,6 +268,7 @@ impl TimerPreferencesDialog {
         follow_from_row.connect_value_notify(move |r| {
             let value = r.value().round().clamp(0.0, 1000.0) as usize;
             if let Ok(mut cfg) = crate::context::TuxSplitContext::get_instance().config_mut() {
+                cfg.mark_dirty();
                 cfg.style.segments_scroll_follow_from = Some(value);
             }
         });
@@ -170,6 +281,7 @@ impl TimerPreferencesDialog {
         max_segments_row.connect_value_notify(move |r| {
             let value = r.value().round().clamp(1.0, 1000.0) as usize;
             if let Ok(mut cfg) = crate::context::TuxSplitContext::get_instance().config_mut() {
+                cfg.mark_dirty();
                 cfg.style.max_segments_displayed = Some(value);
             }
 
@@ -195,20 +307,374 @@ impl TimerPreferencesDialog {
             let ctx = crate::context::TuxSplitContext::get_instance();
             let active = r.is_active();
             if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
                 cfg.style.show_icons = Some(active);
                 drop(cfg);
-                ctx.emit_by_name::<()>("run-changed", &[]);
+                ctx.emit_run_changed();
+            }
+        });
+
+        // Show Best Pace
+        let show_best_pace_row = SwitchRow::builder()
+            .title("Show Best Achieved Pace")
+            .subtitle("Compare completed splits against the best time ever reached at that point")
+            .build();
+        show_best_pace_row.set_active(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .display
+                .show_best_pace,
+        );
+        show_best_pace_row.connect_active_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let active = r.is_active();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.display.show_best_pace = active;
+                drop(cfg);
+                ctx.emit_run_changed();
             }
         });
 
         segments_group.add(&max_segments_row);
         segments_group.add(&follow_from_row);
         segments_group.add(&show_icons_row);
+        segments_group.add(&show_best_pace_row);
+        segments_group.add(&Self::build_sob_drift_row());
+        segments_group.add(&Self::build_sparkline_row());
+
+        let timer_group = PreferencesGroup::builder().title("Timer").build();
+        timer_group.add(&Self::build_timer_font_row());
+        timer_group.add(&Self::build_segment_timer_row());
+        timer_group.add(&Self::build_animations_row());
+        timer_group.add(&Self::build_pb_dead_row());
+        timer_group.add(&Self::build_pace_row());
+
+        let layout_group = PreferencesGroup::builder().title("Layout").build();
+        layout_group.add(&Self::build_headerbar_row());
+        layout_group.add(&self.build_layout_import_row());
+
+        let viewer_group = PreferencesGroup::builder()
+            .title("Viewer Window")
+            .description("Read-only copies of the timer, opened from the main menu")
+            .build();
+        viewer_group.add(&Self::build_viewer_compact_row());
+        viewer_group.add(&Self::build_viewer_scale_row());
 
         page.add(&segments_group);
+        page.add(&timer_group);
+        page.add(&Self::build_state_colors_group());
+        page.add(&layout_group);
+        page.add(&viewer_group);
         page
     }
 
+    fn build_eta_row() -> SwitchRow {
+        let row = SwitchRow::builder()
+            .title("Show Finish Time")
+            .subtitle("Toggle visibility of the wall-clock time the run is predicted to end at")
+            .build();
+        row.set_active(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .display
+                .show_eta,
+        );
+        row.connect_active_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let active = r.is_active();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.display.show_eta = active;
+                drop(cfg);
+                ctx.emit_run_changed();
+            }
+        });
+        row
+    }
+
+    fn build_load_time_row() -> SwitchRow {
+        let row = SwitchRow::builder()
+            .title("Show Load Time")
+            .subtitle("Toggle visibility of the time lost to loads (real time minus game time)")
+            .build();
+        row.set_active(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .display
+                .show_load_time,
+        );
+        row.connect_active_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let active = r.is_active();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.display.show_load_time = active;
+                drop(cfg);
+                ctx.emit_run_changed();
+            }
+        });
+        row
+    }
+
+    fn build_pb_position_row() -> SwitchRow {
+        let row = SwitchRow::builder()
+            .title("Show PB Position")
+            .subtitle("Toggle visibility of the segment your PB was in at this time")
+            .build();
+        row.set_active(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .display
+                .show_pb_position,
+        );
+        row.connect_active_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let active = r.is_active();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.display.show_pb_position = active;
+                drop(cfg);
+                ctx.emit_run_changed();
+            }
+        });
+        row
+    }
+
+    fn build_viewer_compact_row() -> SwitchRow {
+        let row = SwitchRow::builder()
+            .title("Compact")
+            .subtitle("Hide the game and category above the splits")
+            .build();
+        row.set_active(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .viewer
+                .compact,
+        );
+        row.connect_active_notify(move |r| {
+            let active = r.is_active();
+            if let Ok(mut cfg) = crate::context::TuxSplitContext::get_instance().config_mut() {
+                cfg.mark_dirty();
+                cfg.viewer.compact = active;
+            }
+            crate::ui::viewer::apply_settings();
+        });
+        row
+    }
+
+    fn build_viewer_scale_row() -> SpinRow {
+        let row = SpinRow::with_range(0.5, 3.0, 0.1);
+        row.set_title("Scale");
+        row.set_digits(1);
+        row.set_value(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .viewer
+                .scale,
+        );
+        row.connect_value_notify(move |r| {
+            let value = r.value();
+            if let Ok(mut cfg) = crate::context::TuxSplitContext::get_instance().config_mut() {
+                cfg.mark_dirty();
+                cfg.viewer.scale = value;
+            }
+            crate::ui::viewer::apply_settings();
+        });
+        row
+    }
+
+    fn build_segment_timer_row() -> SwitchRow {
+        let row = SwitchRow::builder()
+            .title("Show Segment Timer")
+            .subtitle("Time spent in the current segment, under the timer")
+            .build();
+        row.set_active(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .display
+                .show_segment_timer,
+        );
+        row.connect_active_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let active = r.is_active();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.display.show_segment_timer = active;
+            }
+        });
+        row
+    }
+
+    fn build_preflight_row() -> SwitchRow {
+        let row = SwitchRow::builder()
+            .title("Pre-flight Checklist")
+            .subtitle("Tick off the checklist of the run before starting an attempt. Hold Shift to skip it")
+            .build();
+        row.set_active(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .general
+                .preflight,
+        );
+        row.connect_active_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let active = r.is_active();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.general.preflight = active;
+            }
+        });
+        row
+    }
+
+    fn build_pb_dead_row() -> SwitchRow {
+        let row = SwitchRow::builder()
+            .title("Announce Lost PB")
+            .subtitle("A notification once the attempt can no longer beat the PB")
+            .build();
+        row.set_active(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .notifications
+                .pb_dead,
+        );
+        row.connect_active_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let active = r.is_active();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.notifications.pb_dead = active;
+            }
+        });
+        row
+    }
+
+    fn build_pace_row() -> SwitchRow {
+        let row = SwitchRow::builder()
+            .title("Call Back on PB Pace")
+            .subtitle("A notification when the window is hidden and the last segments are ahead of the PB")
+            .build();
+        row.set_active(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .notifications
+                .urgency_on_pace,
+        );
+        row.connect_active_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let active = r.is_active();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.notifications.urgency_on_pace = active;
+            }
+        });
+        row
+    }
+
+    fn build_animations_row() -> ComboRow {
+        const MODES: [Animations; 3] = [Animations::Auto, Animations::On, Animations::Off];
+        let model = StringList::new(&["Follow System", "On", "Off"]);
+        let row = ComboRow::builder()
+            .title("Animations")
+            .subtitle("Split flashes and theme transitions")
+            .model(&model)
+            .build();
+        let current = crate::context::TuxSplitContext::get_instance()
+            .config()
+            .display
+            .animations;
+        row.set_selected(MODES.iter().position(|&m| m == current).unwrap_or(0) as u32);
+        row.connect_selected_notify(move |r| {
+            let Some(&mode) = MODES.get(r.selected() as usize) else {
+                return;
+            };
+            if let Ok(mut cfg) = crate::context::TuxSplitContext::get_instance().config_mut() {
+                cfg.mark_dirty();
+                cfg.display.animations = mode;
+            }
+        });
+        row
+    }
+
+    fn build_headerbar_row() -> ComboRow {
+        const MODES: [Headerbar; 3] = [Headerbar::Full, Headerbar::Compact, Headerbar::None];
+        let model = StringList::new(&["Full", "Compact", "None"]);
+        let row = ComboRow::builder()
+            .title("Header Bar")
+            .subtitle(
+                "Without one, the menu is behind a gear button and the run info moves the window",
+            )
+            .model(&model)
+            .build();
+        let current = crate::context::TuxSplitContext::get_instance()
+            .config()
+            .display
+            .headerbar;
+        row.set_selected(MODES.iter().position(|&m| m == current).unwrap_or(0) as u32);
+        row.connect_selected_notify(move |r| {
+            let Some(&mode) = MODES.get(r.selected() as usize) else {
+                return;
+            };
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.display.headerbar = mode;
+                drop(cfg);
+                ctx.emit_by_name::<()>("headerbar-changed", &[]);
+            }
+        });
+        row
+    }
+
+    fn build_sob_drift_row() -> SwitchRow {
+        let row = SwitchRow::builder()
+            .title("Show Sum of Best Drift")
+            .subtitle("Compare completed splits against the sum of your best segments up to them")
+            .build();
+        row.set_active(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .display
+                .show_sob_drift,
+        );
+        row.connect_active_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let active = r.is_active();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.display.show_sob_drift = active;
+                drop(cfg);
+                ctx.emit_run_changed();
+            }
+        });
+        row
+    }
+
+    fn build_sparkline_row() -> SwitchRow {
+        let row = SwitchRow::builder()
+            .title("Show Delta Sparkline")
+            .subtitle("Plot the delta of each split, shaded between your best and worst attempts")
+            .build();
+        row.set_active(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .display
+                .show_sparkline,
+        );
+        row.connect_active_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let active = r.is_active();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.display.show_sparkline = active;
+                drop(cfg);
+                ctx.emit_run_changed();
+            }
+        });
+        row
+    }
+
     fn build_format_page(&self) -> PreferencesPage {
         let page = PreferencesPage::builder()
             .title("Format")
@@ -245,12 +711,67 @@ impl TimerPreferencesDialog {
         );
         formats_group.add(&comparison_expander);
 
+        let deltas_group = PreferencesGroup::builder()
+            .title("Deltas")
+            .description("The split editor always shows full precision")
+            .build();
+        deltas_group.add(&Self::build_delta_digits_row());
+        deltas_group.add(&Self::build_delta_drop_zero_tenths_row());
+
         page.add(&formats_group);
+        page.add(&deltas_group);
         page
     }
 
     // ------------- Rows -------------
 
+    fn build_delta_digits_row() -> SpinRow {
+        let row = SpinRow::with_range(0.0, 3.0, 1.0);
+        row.set_title("Decimal places");
+        row.set_subtitle("Digits of the deltas against the comparison, truncated");
+        row.set_value(f64::from(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .display
+                .delta_digits,
+        ));
+        row.connect_value_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let value = r.value().round().clamp(0.0, 3.0) as u8;
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.display.delta_digits = value;
+                drop(cfg);
+                ctx.emit_run_changed_debounced();
+            }
+        });
+        row
+    }
+
+    fn build_delta_drop_zero_tenths_row() -> SwitchRow {
+        let row = SwitchRow::builder()
+            .title("Round Small Deltas")
+            .subtitle("Show deltas under a tenth of a second as ±0.0")
+            .build();
+        row.set_active(
+            crate::context::TuxSplitContext::get_instance()
+                .config()
+                .display
+                .delta_drop_zero_tenths,
+        );
+        row.connect_active_notify(move |r| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let active = r.is_active();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.display.delta_drop_zero_tenths = active;
+                drop(cfg);
+                ctx.emit_run_changed();
+            }
+        });
+        row
+    }
+
     fn build_timing_method_row(&self) -> ComboRow {
         let model = StringList::new(&["Real Time", "Game Time"]);
         let row = ComboRow::builder()
@@ -278,6 +799,7 @@ impl TimerPreferencesDialog {
             };
 
             if let Ok(mut cfg) = crate::context::TuxSplitContext::get_instance().config_mut() {
+                cfg.mark_dirty();
                 cfg.general.timing_method = Some(method);
             }
 
@@ -292,6 +814,209 @@ impl TimerPreferencesDialog {
         row
     }
 
+    fn build_timer_font_row() -> ActionRow {
+        let row = ActionRow::builder()
+            .title("Timer Font")
+            .subtitle("Digits always use tabular widths")
+            .build();
+
+        let font = crate::context::TuxSplitContext::get_instance()
+            .config()
+            .display
+            .timer_font
+            .clone()
+            .unwrap_or_default();
+        let button = gtk::FontDialogButton::builder()
+            .dialog(&gtk::FontDialog::builder().title("Timer Font").build())
+            .font_desc(&timer_font_desc(&font))
+            .level(gtk::FontLevel::Font)
+            .use_font(true)
+            .use_size(true)
+            .valign(gtk::Align::Center)
+            .build();
+
+        let reset = gtk::Button::builder()
+            .icon_name("edit-undo-symbolic")
+            .tooltip_text("Use the system monospace font")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+
+        button.connect_font_desc_notify(|button| {
+            let Some(desc) = button.font_desc() else {
+                return;
+            };
+            let Some(family) = desc.family() else {
+                return;
+            };
+            // Font dialogs give sizes in points, the config keeps pixels
+            let size = f64::from(desc.size()) / f64::from(pango::SCALE);
+            let size = if desc.is_size_absolute() {
+                size
+            } else {
+                crate::ui::style::points_to_pixels(size)
+            };
+            let size = match size.round() {
+                size if size >= 1.0 => size as u32,
+                _ => crate::config::TimerFont::default().size,
+            };
+            let font = crate::config::TimerFont {
+                family: family.to_string(),
+                size,
+            };
+
+            crate::ui::style::apply_timer_font(Some(&font));
+            if let Ok(mut cfg) = crate::context::TuxSplitContext::get_instance().config_mut() {
+                cfg.mark_dirty();
+                cfg.display.timer_font = Some(font);
+            }
+        });
+
+        let button_binding = button.clone();
+        reset.connect_clicked(move |_| {
+            // Setting the button's font stores the default as an explicit font,
+            // so the config is cleared afterwards.
+            button_binding.set_font_desc(&timer_font_desc(&crate::config::TimerFont::default()));
+
+            crate::ui::style::apply_timer_font(None);
+            if let Ok(mut cfg) = crate::context::TuxSplitContext::get_instance().config_mut() {
+                cfg.mark_dirty();
+                cfg.display.timer_font = None;
+            }
+        });
+
+        row.add_suffix(&button);
+        row.add_suffix(&reset);
+        row
+    }
+
+    fn build_state_colors_group() -> PreferencesGroup {
+        let group = PreferencesGroup::builder()
+            .title("State Colors")
+            .description("Named tuxsplit_* colors, for themes to refer to as well")
+            .build();
+        for (key, title, subtitle) in [
+            (
+                "ahead",
+                "Ahead",
+                "Deltas ahead of the comparison and the running timer",
+            ),
+            ("behind", "Behind", "Deltas behind the comparison"),
+            ("gold", "Gold", "Best segments and new personal bests"),
+            ("paused", "Paused", "The timer while paused or not running"),
+            ("finished", "Finished", "The final time of a finished run"),
+        ] {
+            group.add(&Self::build_state_color_row(key, title, subtitle));
+        }
+        group
+    }
+
+    fn build_state_color_row(key: &'static str, title: &str, subtitle: &str) -> ActionRow {
+        let row = ActionRow::builder().title(title).subtitle(subtitle).build();
+
+        let button = gtk::ColorDialogButton::builder()
+            .dialog(
+                &gtk::ColorDialog::builder()
+                    .title(format!("{title} Color"))
+                    .with_alpha(true)
+                    .build(),
+            )
+            .valign(gtk::Align::Center)
+            .build();
+        if let Some(rgba) = crate::ui::style::current_state_color(key) {
+            button.set_rgba(&rgba);
+        }
+
+        let reset = gtk::Button::builder()
+            .icon_name("edit-undo-symbolic")
+            .tooltip_text("Use the color of the style")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+
+        button.connect_rgba_notify(move |button| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            let Ok(mut cfg) = ctx.config_mut() else {
+                return;
+            };
+            let hex = crate::ui::style::rgba_to_hex(&button.rgba());
+            if cfg.display.colors.get(key) == Some(hex.as_str()) {
+                return;
+            }
+            cfg.mark_dirty();
+            cfg.display.colors.set(key, Some(hex));
+            crate::ui::style::apply_state_colors(&cfg.display.colors);
+        });
+
+        let button_binding = button.clone();
+        reset.connect_clicked(move |_| {
+            let ctx = crate::context::TuxSplitContext::get_instance();
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.mark_dirty();
+                cfg.display.colors.set(key, None);
+                crate::ui::style::apply_state_colors(&cfg.display.colors);
+            }
+            // Showing the color of the style stores it as an explicit color,
+            // so the config is cleared again afterwards.
+            if let Some(rgba) = crate::ui::style::current_state_color(key) {
+                button_binding.set_rgba(&rgba);
+            }
+            if let Ok(mut cfg) = ctx.config_mut() {
+                cfg.display.colors.set(key, None);
+                crate::ui::style::apply_state_colors(&cfg.display.colors);
+            }
+        });
+
+        row.add_suffix(&button);
+        row.add_suffix(&reset);
+        row
+    }
+
+    fn build_layout_import_row(&self) -> ActionRow {
+        let row = ActionRow::builder()
+            .title("Import LiveSplit layout...")
+            .subtitle("Use the colors and components of a LiveSplit .lsl layout")
+            .activatable(true)
+            .build();
+        row.add_suffix(&gtk::Image::from_icon_name("document-open-symbolic"));
+
+        let prefs = self.dialog.clone();
+        row.connect_activated(move |row| {
+            let parent = row.root().and_downcast::<gtk::Window>();
+
+            let lsl_filter = FileFilter::new();
+            let all_filter = FileFilter::new();
+            lsl_filter.set_name(Some("LiveSplit Layouts (*.lsl)"));
+            all_filter.set_name(Some("All Files"));
+            lsl_filter.add_pattern("*.lsl");
+            all_filter.add_pattern("*");
+            let filters = gtk::gio::ListStore::new::<FileFilter>();
+            filters.append(&lsl_filter);
+            filters.append(&all_filter);
+
+            let file_dialog = FileDialog::builder()
+                .title("Import LiveSplit Layout")
+                .modal(true)
+                .filters(&filters)
+                .build();
+
+            let prefs = prefs.clone();
+            file_dialog.open(
+                parent.as_ref(),
+                None::<&gtk::gio::Cancellable>,
+                move |result| {
+                    if let Ok(file) = result
+                        && let Some(path) = file.path()
+                    {
+                        import_layout(&prefs, &path);
+                    }
+                },
+            );
+        });
+
+        row
+    }
+
     fn build_format_expander(
         &self,
         title: &str,
@@ -335,6 +1060,7 @@ impl TimerPreferencesDialog {
         mode_row.connect_selected_notify(move |r| {
             let idx = r.selected();
             if let Ok(mut cfg) = crate::context::TuxSplitContext::get_instance().config_mut() {
+                cfg.mark_dirty();
                 let tf = match target {
                     FormatTarget::Timer => &mut cfg.format.timer,
                     FormatTarget::Split => &mut cfg.format.split,
@@ -366,6 +1092,7 @@ impl TimerPreferencesDialog {
         decimals_row.connect_value_notify(move |row| {
             let val = row.value().round().clamp(1.0, 3.0) as u8;
             if let Ok(mut cfg) = crate::context::TuxSplitContext::get_instance().config_mut() {
+                cfg.mark_dirty();
                 let tf = match target {
                     FormatTarget::Timer => &mut cfg.format.timer,
                     FormatTarget::Split => &mut cfg.format.split,
@@ -382,3 +1109,81 @@ impl TimerPreferencesDialog {
         expander
     }
 }
+
+/// Reads a `.lsl` file and applies its colors and component visibility.
+///
+/// On success the preferences dialog is closed, as its rows no longer reflect the
+/// configuration, and a summary is shown on the main window.
+fn import_layout(prefs: &PreferencesDialog, path: &Path) {
+    let parsed = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|source| parse_layout(&source).map_err(|e| e.to_string()));
+
+    let layout = match parsed {
+        Ok(layout) => layout,
+        Err(e) => {
+            let dialog = AlertDialog::builder()
+                .heading("Could not import layout")
+                .body(e)
+                .default_response("ok")
+                .close_response("ok")
+                .build();
+            dialog.add_response("ok", "_Okay");
+            dialog.present(Some(prefs));
+            return;
+        }
+    };
+
+    let ctx = crate::context::TuxSplitContext::get_instance();
+    if let Ok(mut cfg) = ctx.config_mut() {
+        cfg.mark_dirty();
+        let colors = ColorOverrides {
+            background: layout.colors.background.clone(),
+            ahead: layout.colors.ahead.clone(),
+            behind: layout.colors.behind.clone(),
+            gold: layout.colors.gold.clone(),
+        };
+        crate::ui::style::apply_color_overrides(Some(&colors));
+        cfg.style.colors = Some(colors);
+
+        let info = &mut cfg.general.additional_info;
+        info.show_prev_segment_diff = layout.has_component(LayoutComponent::PreviousSegment);
+        info.show_best_possible_time = layout.has_component(LayoutComponent::SumOfBest);
+        drop(cfg);
+        ctx.emit_run_changed();
+    }
+
+    let parent = prefs.root();
+    prefs.close();
+
+    let dialog = AlertDialog::builder()
+        .heading("Layout imported")
+        .body(import_summary(&layout))
+        .default_response("ok")
+        .close_response("ok")
+        .build();
+    dialog.add_response("ok", "_Okay");
+    dialog.present(parent.as_ref());
+}
+
+fn timer_font_desc(font: &crate::config::TimerFont) -> pango::FontDescription {
+    let mut desc = pango::FontDescription::new();
+    desc.set_family(&font.family);
+    desc.set_absolute_size(f64::from(font.size) * f64::from(pango::SCALE));
+    desc
+}
+
+fn import_summary(layout: &ImportedLayout) -> String {
+    let mut summary = format!(
+        "Applied the layout colors and {} of its components.",
+        layout.components.len()
+    );
+    if !layout.unsupported.is_empty() {
+        summary.push_str("\n\nThe following components are not supported and were skipped:");
+        for path in &layout.unsupported {
+            summary.push_str("\n• ");
+            summary.push_str(path.trim_end_matches(".dll"));
+        }
+    }
+    summary
+}
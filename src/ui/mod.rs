@@ -1,7 +1,28 @@
+pub mod about;
+pub mod archive;
+pub mod categories;
+pub mod comparison_picker;
+pub mod comparisons;
+pub mod debug_log;
+pub mod dnd;
 pub mod editor;
+pub mod error;
+pub mod goal;
 pub mod header;
+pub mod history;
+pub mod import;
 pub mod info;
 pub mod menu;
+pub mod palette;
+pub mod practice;
+pub mod preflight;
+pub mod route;
+#[cfg(feature = "speedrun-com")]
+pub mod speedruncom;
+#[cfg(feature = "splitsio-upload")]
+pub mod splitsio;
+pub mod style;
 pub mod timer;
+pub mod viewer;
 
 pub use header::TuxSplitHeader;
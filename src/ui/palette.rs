@@ -0,0 +1,190 @@
+//! Command palette (Ctrl+Shift+P): every action of the menu, searchable by name.
+//!
+//! Actions are listed from the menu's action group, so new ones show up on their
+//! own. Features describe theirs with a `Command` registered on the
+//! `TuxSplitContext`; unregistered actions get a title from their name.
+
+use adw::prelude::*;
+use adw::{ActionRow, HeaderBar, ToolbarView};
+use gtk4::{ListBox, ScrolledWindow, SearchEntry, SelectionMode, gio};
+
+use crate::context::TuxSplitContext;
+use crate::utils::fuzzy::fuzzy_score;
+
+/// How an action of the menu reads in the palette.
+#[derive(Debug, Clone, Copy)]
+pub struct Command {
+    /// Name of the action in the menu's action group, without the "app." prefix.
+    pub action: &'static str,
+    pub title: &'static str,
+    /// Other words the command is found by.
+    pub keywords: &'static [&'static str],
+    /// Whether the command can run right now. Disabled actions are greyed out.
+    pub enabled: fn() -> bool,
+}
+
+impl Command {
+    pub const fn new(
+        action: &'static str,
+        title: &'static str,
+        keywords: &'static [&'static str],
+    ) -> Self {
+        Self {
+            action,
+            title,
+            keywords,
+            enabled: || true,
+        }
+    }
+
+    pub const fn enabled_when(self, enabled: fn() -> bool) -> Self {
+        Self { enabled, ..self }
+    }
+}
+
+/// An entry of the palette list.
+struct Entry {
+    action: String,
+    title: String,
+    keywords: Vec<&'static str>,
+    enabled: bool,
+}
+
+/// Presents the palette over `parent` for the parameterless actions of `group`.
+pub fn present_palette(parent: &impl IsA<gtk4::Widget>, group: &gio::SimpleActionGroup) {
+    let entries = entries(group);
+
+    let search = SearchEntry::builder()
+        .placeholder_text("Search actions")
+        .hexpand(true)
+        .build();
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::Browse)
+        .css_classes(["boxed-list"])
+        .build();
+    let scroller = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .min_content_height(320)
+        .child(&list)
+        .margin_start(12)
+        .margin_end(12)
+        .margin_bottom(12)
+        .build();
+
+    let header = HeaderBar::builder().title_widget(&search).build();
+    let view = ToolbarView::builder().content(&scroller).build();
+    view.add_top_bar(&header);
+    let dialog = adw::Dialog::builder()
+        .title("Command Palette")
+        .content_width(420)
+        .child(&view)
+        .build();
+
+    let filter = {
+        let list = list.clone();
+        move |query: &str| {
+            list.remove_all();
+            let mut matches: Vec<(u32, &Entry)> = entries
+                .iter()
+                .filter_map(|entry| {
+                    fuzzy_score(query, &entry.title, &entry.keywords).map(|score| (score, entry))
+                })
+                .collect();
+            // Best first, and the ones that can run before the others
+            matches.sort_by_key(|(score, entry)| (!entry.enabled, std::cmp::Reverse(*score)));
+            for (_, entry) in matches {
+                let row = ActionRow::builder()
+                    .title(glib::markup_escape_text(&entry.title))
+                    .activatable(entry.enabled)
+                    .sensitive(entry.enabled)
+                    .name(&entry.action)
+                    .build();
+                list.append(&row);
+            }
+            if let Some(first) = list.row_at_index(0).filter(gtk4::ListBoxRow::is_sensitive) {
+                list.select_row(Some(&first));
+            }
+        }
+    };
+    filter("");
+    search.connect_search_changed(move |search| filter(&search.text()));
+
+    let run = {
+        let dialog = dialog.clone();
+        let group = group.clone();
+        move |row: &gtk4::ListBoxRow| {
+            if !row.is_sensitive() {
+                return;
+            }
+            let action = row.widget_name();
+            dialog.close();
+            group.activate_action(&action, None);
+        }
+    };
+    {
+        let run = run.clone();
+        list.connect_row_activated(move |_, row| run(row));
+    }
+    {
+        let list = list.clone();
+        search.connect_activate(move |_| {
+            if let Some(row) = list.selected_row() {
+                run(&row);
+            }
+        });
+    }
+    // Arrow keys move through the results without leaving the search entry
+    {
+        let list = list.clone();
+        search.connect_next_match(move |_| move_selection(&list, 1));
+    }
+    {
+        let list = list.clone();
+        search.connect_previous_match(move |_| move_selection(&list, -1));
+    }
+    search.set_key_capture_widget(Some(&view));
+
+    dialog.present(Some(parent));
+    search.grab_focus();
+}
+
+fn move_selection(list: &ListBox, step: i32) {
+    let current = list.selected_row().map_or(-1, |row| row.index());
+    if let Some(row) = list
+        .row_at_index(current + step)
+        .filter(gtk4::ListBoxRow::is_sensitive)
+    {
+        list.select_row(Some(&row));
+    }
+}
+
+fn entries(group: &gio::SimpleActionGroup) -> Vec<Entry> {
+    let commands = TuxSplitContext::get_instance().commands();
+    group
+        .list_actions()
+        .iter()
+        // Actions taking a value (like switching category) cannot run bare
+        .filter(|name| group.action_parameter_type(name).is_none())
+        .map(|name| {
+            let command = commands
+                .iter()
+                .find(|command| command.action == name.as_str());
+            Entry {
+                action: name.to_string(),
+                title: command.map_or_else(|| title_from_name(name), |c| c.title.to_owned()),
+                keywords: command.map(|c| c.keywords.to_vec()).unwrap_or_default(),
+                enabled: group.is_action_enabled(name) && command.is_none_or(|c| (c.enabled)()),
+            }
+        })
+        .collect()
+}
+
+/// "new-viewer" reads "New viewer".
+fn title_from_name(name: &str) -> String {
+    let words = name.replace('-', " ");
+    let mut chars = words.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
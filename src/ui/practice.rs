@@ -0,0 +1,243 @@
+//! Practicing a single segment: a stopwatch dialog whose splits go to the
+//! practice file of the run (see `crate::practice`) and never to its history,
+//! and the statistics of those times.
+
+use adw::AlertDialog;
+use adw::prelude::*;
+use gtk4::{Align, Box as GtkBox, Button, Label, Orientation, Popover};
+use livesplit_core::TimeSpan;
+use tracing::warn;
+
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::context::TuxSplitContext;
+use crate::formatters::TimeFormat;
+use crate::paths;
+use crate::practice::{self, PracticeRecord, RECENT_ATTEMPTS, segment_stats};
+
+/// Refresh interval of the running practice stopwatch.
+const STOPWATCH_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Practice file of the run being timed.
+fn current_practice_file() -> PathBuf {
+    let run = TuxSplitContext::get_instance().get_run();
+    practice::practice_file(&paths::current().root, run.game_name(), run.category_name())
+}
+
+fn format_ms(format: &TimeFormat, ms: i64) -> String {
+    format.format_time_span(&TimeSpan::from_milliseconds(ms as f64))
+}
+
+/// One line per statistic of `segment`, or a note that it was never practiced.
+fn stats_text(segment: &str) -> String {
+    let records = practice::load(&current_practice_file());
+    let Some(stats) = segment_stats(&records, segment) else {
+        return "Not practiced yet".to_owned();
+    };
+    let format = TuxSplitContext::get_instance()
+        .config()
+        .format
+        .segment
+        .clone();
+    format!(
+        "Attempts: {}\nBest: {}\nMedian: {}\nLast {RECENT_ATTEMPTS} average: {}",
+        stats.count,
+        format_ms(&format, stats.best_ms),
+        format_ms(&format, stats.median_ms),
+        format_ms(&format, stats.recent_average_ms),
+    )
+}
+
+fn stats_label(segment: &str) -> Label {
+    Label::builder()
+        .label(stats_text(segment))
+        .css_classes(["numeric"])
+        .xalign(0.0)
+        .build()
+}
+
+/// Popover on `anchor` with the practice statistics of `segment`, offering to
+/// practice it or to clear its practice data.
+pub fn present_stats_popover(anchor: &impl IsA<gtk4::Widget>, segment: &str) {
+    let content = GtkBox::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(12)
+        .margin_top(6)
+        .margin_bottom(6)
+        .margin_start(6)
+        .margin_end(6)
+        .build();
+    let heading = Label::builder()
+        .label(format!("Practice of {segment}"))
+        .css_classes(["heading"])
+        .xalign(0.0)
+        .build();
+    let stats = stats_label(segment);
+    let buttons = GtkBox::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .halign(Align::End)
+        .build();
+    let clear = Button::builder()
+        .label("_Clear Practice Data")
+        .use_underline(true)
+        .css_classes(["destructive-action"])
+        .build();
+    let practice = Button::builder()
+        .label("_Practice")
+        .use_underline(true)
+        .css_classes(["suggested-action"])
+        .build();
+    buttons.append(&clear);
+    buttons.append(&practice);
+    content.append(&heading);
+    content.append(&stats);
+    content.append(&buttons);
+
+    let popover = Popover::builder().child(&content).build();
+    popover.set_parent(anchor);
+    popover.connect_closed(|popover| popover.unparent());
+
+    {
+        let popover = popover.clone();
+        let anchor = anchor.as_ref().clone();
+        let segment = segment.to_owned();
+        practice.connect_clicked(move |_| {
+            popover.popdown();
+            present_practice(&anchor, &segment);
+        });
+    }
+    {
+        let segment = segment.to_owned();
+        clear.connect_clicked(move |button| confirm_clear(button, &segment, &stats));
+    }
+    popover.popup();
+}
+
+/// Asks before clearing the practice data of `segment`, then refreshes `stats`.
+fn confirm_clear(parent: &impl IsA<gtk4::Widget>, segment: &str, stats: &Label) {
+    let dialog = AlertDialog::builder()
+        .heading("Clear Practice Data?")
+        .body(format!(
+            "Every practice time of {segment} is deleted. The splits are not changed."
+        ))
+        .default_response("cancel")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("clear", "C_lear");
+    dialog.set_response_appearance("clear", adw::ResponseAppearance::Destructive);
+    let segment = segment.to_owned();
+    let stats = stats.clone();
+    dialog.connect_response(Some("clear"), move |_, _| {
+        if TuxSplitContext::get_instance().read_only() {
+            return;
+        }
+        let path = current_practice_file();
+        if let Err(e) = practice::clear_segment(&path, &segment) {
+            warn!("Could not clear practice data in {}: {e}", path.display());
+        }
+        stats.set_label(&stats_text(&segment));
+    });
+    dialog.present(Some(parent));
+}
+
+/// Stopwatch for practicing `segment`: each split is recorded as a practice
+/// time, with the statistics updated under it.
+pub fn present_practice(parent: &impl IsA<gtk4::Widget>, segment: &str) {
+    let format = TuxSplitContext::get_instance()
+        .config()
+        .format
+        .timer
+        .clone();
+    let time = Label::builder()
+        .label(format_ms(&format, 0))
+        .css_classes(["bigtimer", "numeric"])
+        .build();
+    let stats = stats_label(segment);
+    let start = Button::builder()
+        .label("_Start")
+        .use_underline(true)
+        .css_classes(["suggested-action", "pill"])
+        .halign(Align::Center)
+        .build();
+    let content = GtkBox::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(18)
+        .build();
+    content.append(&time);
+    content.append(&start);
+    content.append(&stats);
+
+    let dialog = AlertDialog::builder()
+        .heading(format!("Practice {segment}"))
+        .body("Practice times are kept apart from the attempt history.")
+        .extra_child(&content)
+        .default_response("close")
+        .close_response("close")
+        .build();
+    dialog.add_response("close", "_Close");
+
+    let started: Rc<Cell<Option<Instant>>> = Rc::new(Cell::new(None));
+    let segment = segment.to_owned();
+    {
+        let started = started.clone();
+        let time = time.clone();
+        start.connect_clicked(move |button| {
+            let Some(since) = started.take() else {
+                started.set(Some(Instant::now()));
+                button.set_label("_Split");
+                tick_stopwatch(&started, &time, &format);
+                return;
+            };
+            button.set_label("_Start");
+            let elapsed = since.elapsed();
+            time.set_label(&format.format_duration(&elapsed.try_into().unwrap_or_default()));
+            record(&segment, elapsed);
+            stats.set_label(&stats_text(&segment));
+        });
+    }
+    // Closing mid-attempt drops it
+    dialog.connect_closed(move |_| started.set(None));
+    dialog.present(Some(parent));
+}
+
+/// Refreshes `time` while the stopwatch runs.
+fn tick_stopwatch(started: &Rc<Cell<Option<Instant>>>, time: &Label, format: &TimeFormat) {
+    let started = Rc::downgrade(started);
+    let time = time.downgrade();
+    let format = format.clone();
+    glib::timeout_add_local(STOPWATCH_INTERVAL, move || {
+        let (Some(started), Some(time)) = (started.upgrade(), time.upgrade()) else {
+            return glib::ControlFlow::Break;
+        };
+        let Some(since) = started.get() else {
+            return glib::ControlFlow::Break;
+        };
+        let elapsed = since.elapsed().try_into().unwrap_or_default();
+        time.set_label(&format.format_duration(&elapsed));
+        glib::ControlFlow::Continue
+    });
+}
+
+fn record(segment: &str, elapsed: Duration) {
+    let date = glib::DateTime::now_local()
+        .and_then(|now| now.format("%F %T"))
+        .map(String::from)
+        .unwrap_or_default();
+    let record = PracticeRecord {
+        segment: segment.to_owned(),
+        time_ms: i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX),
+        date,
+    };
+    if TuxSplitContext::get_instance().read_only() {
+        return;
+    }
+    let path = current_practice_file();
+    if let Err(e) = practice::append(&path, &record) {
+        warn!("Could not record practice in {}: {e}", path.display());
+    }
+}
@@ -0,0 +1,144 @@
+//! Pre-flight checklist shown before an attempt starts, when `general.preflight`
+//! is on and the run has a checklist (see `RunSidecar::preflight`).
+//!
+//! Starts asked for by hotkeys or the UI are held back by the action queue (see
+//! `utils::timer_actions::ActionGate`); the checklist is shown on the main
+//! loop and queues the start itself once every item is ticked off. Holding
+//! Shift at the start press skips the checklist, and holding it in the checklist
+//! allows starting anyway. Nothing is kept between attempts.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use adw::prelude::*;
+use adw::{ActionRow, AlertDialog, ApplicationWindow};
+use gtk4::gdk::ModifierType;
+use gtk4::{CheckButton, EventControllerKey, ListBox, SelectionMode};
+
+use crate::context::TuxSplitContext;
+
+thread_local! {
+    static DIALOG: RefCell<Option<glib::WeakRef<AlertDialog>>> = const { RefCell::new(None) };
+}
+
+/// Whether an attempt may start with the items `ticked` so far, or with Shift
+/// held to skip the checklist.
+pub fn can_start(ticked: &[bool], shift: bool) -> bool {
+    shift || ticked.iter().all(|ticked| *ticked)
+}
+
+/// Shows the checklist over `window` whenever a start was held back for it,
+/// or starts right away when Shift is held.
+pub fn connect(window: &ApplicationWindow) {
+    let window = window.downgrade();
+    TuxSplitContext::get_instance().connect_local("preflight-requested", false, move |_| {
+        let window = window.upgrade()?;
+        if shift_held(&window) {
+            TuxSplitContext::get_instance().start_checked();
+        } else {
+            present(&window);
+        }
+        None
+    });
+}
+
+/// Whether Shift is held on the keyboard of `widget`. The start is requested
+/// within a tick of the press, so this tells whether it was held for the press.
+/// Compositors only share the modifiers with the focused window.
+fn shift_held(widget: &impl IsA<gtk4::Widget>) -> bool {
+    widget
+        .display()
+        .default_seat()
+        .and_then(|seat| seat.keyboard())
+        .is_some_and(|keyboard| keyboard.modifier_state().contains(ModifierType::SHIFT_MASK))
+}
+
+/// Presents the checklist of the run, with nothing ticked, unless it is open
+/// already.
+pub fn present(parent: &impl IsA<gtk4::Widget>) {
+    if DIALOG
+        .with(|d| d.borrow().as_ref().and_then(glib::WeakRef::upgrade))
+        .is_some()
+    {
+        return;
+    }
+    let items = TuxSplitContext::get_instance().sidecar().preflight.clone();
+
+    let dialog = AlertDialog::builder()
+        .heading("Pre-flight Checklist")
+        .body("Tick off every item to start the run. Hold Shift to start it anyway.")
+        .default_response("start")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Not Now");
+    dialog.add_response("start", "_Start Run");
+    dialog.set_response_appearance("start", adw::ResponseAppearance::Suggested);
+
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    let checks: Rc<Vec<CheckButton>> = Rc::new(
+        items
+            .iter()
+            .map(|item| {
+                let check = CheckButton::builder().valign(gtk4::Align::Center).build();
+                let row = ActionRow::builder()
+                    .title(item)
+                    .use_markup(false)
+                    .activatable_widget(&check)
+                    .build();
+                row.add_prefix(&check);
+                list.append(&row);
+                check
+            })
+            .collect(),
+    );
+    dialog.set_extra_child(Some(&list));
+
+    let shift = Rc::new(Cell::new(false));
+    let update = {
+        let dialog = dialog.downgrade();
+        let checks = checks.clone();
+        let shift = shift.clone();
+        move || {
+            let ticked: Vec<bool> = checks.iter().map(CheckButton::is_active).collect();
+            if let Some(dialog) = dialog.upgrade() {
+                dialog.set_response_enabled("start", can_start(&ticked, shift.get()));
+            }
+        }
+    };
+    update();
+    for check in checks.iter() {
+        let update = update.clone();
+        check.connect_toggled(move |_| update());
+    }
+    let keys = EventControllerKey::new();
+    keys.set_propagation_phase(gtk4::PropagationPhase::Capture);
+    keys.connect_modifiers(move |_, state| {
+        shift.set(state.contains(ModifierType::SHIFT_MASK));
+        update();
+        glib::Propagation::Proceed
+    });
+    dialog.add_controller(keys);
+
+    dialog.connect_response(Some("start"), |_, _| {
+        TuxSplitContext::get_instance().start_checked();
+    });
+
+    DIALOG.with(|d| d.replace(Some(dialog.downgrade())));
+    dialog.present(Some(parent));
+}
+
+#[cfg(test)]
+mod preflight_tests {
+    use super::*;
+
+    #[test]
+    fn every_item_is_ticked_off_or_shift_is_held() {
+        assert!(!can_start(&[true, false], false));
+        assert!(can_start(&[true, true], false));
+        assert!(can_start(&[false, false], true));
+        assert!(can_start(&[], false));
+    }
+}
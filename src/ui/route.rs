@@ -0,0 +1,243 @@
+//! "Compare with File..." menu action: the differences between the loaded run and
+//! another splits file, to review a route change and adopt the times worth keeping.
+
+use adw::AlertDialog;
+use adw::prelude::*;
+use gtk4::{Align, Button, FileChooserDialog, FileFilter, Grid, Label, ScrolledWindow};
+use livesplit_core::{Run, TimeSpan, TimerPhase, TimingMethod};
+
+use std::path::Path;
+
+use crate::config::read_run;
+use crate::context::{TuxSplitContext, today};
+use crate::ui::error::show_error;
+use crate::utils::comparisons::format_signed;
+use crate::utils::route_diff::{RouteEntry, TimeDiff, adopt_segment, diff_runs};
+
+/// Shown where one side has no time or no segment.
+const NO_TIME: &str = "—";
+
+/// Asks for the splits file to compare the loaded run with.
+pub fn present_compare_file(parent: &adw::ApplicationWindow) {
+    let file_chooser = FileChooserDialog::new(
+        Some("Compare with File"),
+        Some(parent),
+        gtk4::FileChooserAction::Open,
+        &[
+            ("Compare", gtk4::ResponseType::Ok),
+            ("Cancel", gtk4::ResponseType::Cancel),
+        ],
+    );
+
+    let lss_filter = FileFilter::new();
+    let all_filter = FileFilter::new();
+    lss_filter.set_name(Some("LiveSplit Splits (*.lss)"));
+    all_filter.set_name(Some("All Files"));
+    lss_filter.add_pattern("*.lss");
+    all_filter.add_pattern("*");
+    file_chooser.add_filter(&lss_filter);
+    file_chooser.add_filter(&all_filter);
+
+    let parent_binding = parent.clone();
+    file_chooser.connect_response(move |dialog, response| {
+        if response == gtk4::ResponseType::Ok
+            && let Some(path) = dialog.file().and_then(|file| file.path())
+        {
+            match read_run(&path) {
+                Ok(other) => present_route_diff(&parent_binding, &path, other),
+                Err(e) => show_error(&parent_binding, &e),
+            }
+        }
+        dialog.destroy();
+    });
+
+    file_chooser.set_modal(true);
+    file_chooser.present();
+}
+
+/// Presents the segments of both runs side by side, with an "Adopt" button on
+/// every matched segment whose times differ.
+pub(crate) fn present_route_diff(parent: &adw::ApplicationWindow, path: &Path, other: Run) {
+    let grid = Grid::builder()
+        .column_spacing(18)
+        .row_spacing(6)
+        .halign(Align::Center)
+        .build();
+    fill_grid(&grid, &other);
+
+    let scroller = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .min_content_height(300)
+        .child(&grid)
+        .build();
+
+    let name = path.file_name().map_or_else(
+        || path.display().to_string(),
+        |n| n.to_string_lossy().into(),
+    );
+    let dialog = AlertDialog::builder()
+        .heading("Route Changes")
+        .body(format!(
+            "Compared with {name}. Deltas are what your splits lose or gain on it; \
+             adopting copies its PB split and best segment into yours."
+        ))
+        .extra_child(&scroller)
+        .default_response("close")
+        .close_response("close")
+        .build();
+    dialog.add_response("close", "_Close");
+    dialog.present(Some(parent));
+}
+
+/// Fills `grid` with the differences between the loaded run and `other`, again
+/// after every adoption.
+fn fill_grid(grid: &Grid, other: &Run) {
+    while let Some(child) = grid.first_child() {
+        grid.remove(&child);
+    }
+
+    let ctx = TuxSplitContext::get_instance();
+    let ours = ctx.get_run();
+    let (method, phase) = {
+        let timer = ctx.timer();
+        let timer = timer.read().unwrap();
+        (timer.current_timing_method(), timer.current_phase())
+    };
+    // Replacing the run would reset the attempt in progress
+    let can_replace = phase == TimerPhase::NotRunning;
+
+    let headers = ["Segment", "Change", "PB Split", "Segment", "Best"];
+    for (column, header) in (0..).zip(headers) {
+        grid.attach(&cell(header, &["heading"]), column, 0, 1, 1);
+    }
+
+    for (row, entry) in (1..).zip(diff_runs(&ours, other, method)) {
+        match entry {
+            RouteEntry::Matched {
+                ours: index,
+                theirs,
+                renamed,
+                split,
+                segment,
+                gold,
+            } => {
+                let name = ours.segments()[index].name();
+                let (title, change) = if renamed {
+                    (
+                        format!("{name} → {}", other.segments()[theirs].name()),
+                        "Renamed",
+                    )
+                } else {
+                    (name.to_owned(), "")
+                };
+                grid.attach(&cell(&title, &[]), 0, row, 1, 1);
+                grid.attach(&cell(change, &["dim-label"]), 1, row, 1, 1);
+                grid.attach(&delta_cell(split), 2, row, 1, 1);
+                grid.attach(&delta_cell(segment), 3, row, 1, 1);
+                grid.attach(&delta_cell(gold), 4, row, 1, 1);
+                if entry.can_adopt() {
+                    grid.attach(
+                        &adopt_button(
+                            grid,
+                            other,
+                            (index, theirs),
+                            method,
+                            gold.differs(),
+                            can_replace,
+                        ),
+                        5,
+                        row,
+                        1,
+                        1,
+                    );
+                }
+            }
+            RouteEntry::Removed { ours: index } => {
+                grid.attach(&cell(ours.segments()[index].name(), &[]), 0, row, 1, 1);
+                grid.attach(&cell("Only in yours", &["dim-label"]), 1, row, 1, 1);
+            }
+            RouteEntry::Added { theirs } => {
+                grid.attach(&cell(other.segments()[theirs].name(), &[]), 0, row, 1, 1);
+                grid.attach(&cell("Only in theirs", &["dim-label"]), 1, row, 1, 1);
+            }
+        }
+    }
+}
+
+fn adopt_button(
+    grid: &Grid,
+    other: &Run,
+    (index, theirs): (usize, usize),
+    method: TimingMethod,
+    new_gold: bool,
+    can_replace: bool,
+) -> Button {
+    let button = Button::builder()
+        .label("Adopt")
+        .valign(Align::Center)
+        .sensitive(can_replace)
+        .css_classes(["flat"])
+        .build();
+    if !can_replace {
+        button.set_tooltip_text(Some("Reset the timer to change the splits"));
+    }
+    let grid = grid.clone();
+    let other = other.clone();
+    button.connect_clicked(move |_| {
+        let ctx = TuxSplitContext::get_instance();
+        let run = adopt_segment(ctx.get_run(), index, &other.segments()[theirs], method);
+        if new_gold && let Some(segment) = run.segments().get(index) {
+            ctx.sidecar_mut()
+                .set_gold_date(segment.name(), index, today());
+        }
+        ctx.set_run(run);
+        fill_grid(&grid, &other);
+    });
+    button
+}
+
+/// The time the loaded run loses or gains, with both times in the tooltip.
+fn delta_cell(diff: TimeDiff) -> Label {
+    let ctx = TuxSplitContext::get_instance();
+    let config = ctx.config();
+    let format = |time: Option<TimeSpan>| {
+        time.map_or_else(
+            || NO_TIME.to_owned(),
+            |time| config.format.split.format_time_span(&time),
+        )
+    };
+    let label = match diff.delta() {
+        Some(delta) => {
+            let delta = delta.to_duration();
+            cell(
+                &format_signed(delta, &config),
+                &["numeric", delta_class(delta)],
+            )
+        }
+        None => cell(NO_TIME, &["dim-label"]),
+    };
+    label.set_tooltip_text(Some(&format!(
+        "Yours: {}\nTheirs: {}",
+        format(diff.ours),
+        format(diff.theirs)
+    )));
+    label
+}
+
+fn cell(text: &str, classes: &[&str]) -> Label {
+    Label::builder()
+        .label(text)
+        .xalign(0.0)
+        .css_classes(classes)
+        .build()
+}
+
+const fn delta_class(delta: time::Duration) -> &'static str {
+    if delta.is_negative() {
+        "greensplit"
+    } else if delta.is_positive() {
+        "redsplit"
+    } else {
+        "dim-label"
+    }
+}
@@ -0,0 +1,236 @@
+//! "New Run from speedrun.com" menu action: looks the game up on a background
+//! thread, lets the user pick one of its categories and opens the new run in the
+//! editor. When speedrun.com cannot be asked, the names are typed in instead.
+
+use adw::prelude::*;
+use adw::{ActionRow, AlertDialog, EntryRow, PreferencesGroup, SpinRow};
+use gtk4::{ListBox, ScrolledWindow, SelectionMode, gio};
+use livesplit_core::Run;
+use livesplit_core::run::saver::livesplit::save_run;
+use tracing::warn;
+
+use std::path::PathBuf;
+
+use crate::categories::new_splits_path;
+use crate::context::TuxSplitContext;
+use crate::error::TuxSplitError;
+use crate::integrations::speedruncom::{self, Game, LookupError};
+use crate::paths;
+use crate::storage::write_atomic;
+use crate::ui::categories::confirm_unsaved;
+use crate::ui::editor::SplitEditor;
+use crate::ui::error::show_error;
+
+/// Placeholder segments of a new run unless the user asks for another number.
+const DEFAULT_SEGMENTS: f64 = 5.0;
+
+/// Asks for the game to look up and the number of placeholder segments.
+pub fn present_new_run(parent: &adw::ApplicationWindow) {
+    let game = EntryRow::builder().title("Game").build();
+    let segments = SpinRow::with_range(1.0, 200.0, 1.0);
+    segments.set_title("Segments");
+    segments.set_value(DEFAULT_SEGMENTS);
+    let group = PreferencesGroup::new();
+    group.add(&game);
+    group.add(&segments);
+
+    let dialog = AlertDialog::builder()
+        .heading("New Run from speedrun.com")
+        .body("The game and category names come from speedrun.com.")
+        .extra_child(&group)
+        .default_response("search")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("search", "_Search");
+    dialog.set_response_appearance("search", adw::ResponseAppearance::Suggested);
+    dialog.set_response_enabled("search", false);
+    {
+        let dialog = dialog.clone();
+        game.connect_changed(move |game| {
+            dialog.set_response_enabled("search", !game.text().trim().is_empty());
+        });
+    }
+    game.set_activates_default(true);
+
+    let parent_binding = parent.clone();
+    dialog.connect_response(Some("search"), move |_, _| {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let count = segments.value() as usize;
+        search(&parent_binding, game.text().trim().to_owned(), count);
+    });
+    dialog.present(Some(parent));
+}
+
+fn search(parent: &adw::ApplicationWindow, query: String, segments: usize) {
+    let parent = parent.clone();
+    glib::MainContext::default().spawn_local(async move {
+        let lookup = query.clone();
+        let result = gio::spawn_blocking(move || speedruncom::search_games(&lookup))
+            .await
+            .unwrap_or_else(|_| {
+                Err(LookupError::Network(
+                    "the search was interrupted".to_owned(),
+                ))
+            });
+        match result {
+            Ok(games)
+                if games
+                    .iter()
+                    .any(|g| g.full_game_categories().next().is_some()) =>
+            {
+                present_categories(&parent, games, segments);
+            }
+            Ok(_) => present_manual_form(
+                &parent,
+                &query,
+                segments,
+                &format!("No game named “{query}” was found on speedrun.com."),
+            ),
+            Err(e) => {
+                warn!("{e}");
+                present_manual_form(&parent, &query, segments, &e.to_string());
+            }
+        }
+    });
+}
+
+/// Lists the full game categories of every game found.
+fn present_categories(parent: &adw::ApplicationWindow, games: Vec<Game>, segments: usize) {
+    let list = ListBox::builder()
+        .selection_mode(SelectionMode::Single)
+        .css_classes(["boxed-list"])
+        .build();
+    let mut choices = Vec::new();
+    for (game_index, game) in games.iter().enumerate() {
+        for (category_index, category) in game.full_game_categories().enumerate() {
+            list.append(
+                &ActionRow::builder()
+                    .title(glib::markup_escape_text(&category.name))
+                    .subtitle(glib::markup_escape_text(game.name()))
+                    .build(),
+            );
+            choices.push((game_index, category_index));
+        }
+    }
+    let scroller = ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .min_content_height(300)
+        .child(&list)
+        .build();
+
+    let dialog = AlertDialog::builder()
+        .heading("Pick a Category")
+        .extra_child(&scroller)
+        .default_response("create")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("create", "C_reate");
+    dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+    dialog.set_response_enabled("create", false);
+    {
+        let dialog = dialog.clone();
+        list.connect_selected_rows_changed(move |list| {
+            dialog.set_response_enabled("create", list.selected_row().is_some());
+        });
+    }
+
+    let parent_binding = parent.clone();
+    dialog.connect_response(Some("create"), move |_, _| {
+        let Some(row) = list.selected_row() else {
+            return;
+        };
+        let Some(&(game, category)) = usize::try_from(row.index())
+            .ok()
+            .and_then(|index| choices.get(index))
+        else {
+            return;
+        };
+        let game = &games[game];
+        let category = game
+            .full_game_categories()
+            .nth(category)
+            .expect("listed category");
+        open_new_run(
+            &parent_binding,
+            speedruncom::new_run(game, category, segments),
+        );
+    });
+    dialog.present(Some(parent));
+}
+
+/// Asks for the game and category names, after `reason` for not finding them.
+fn present_manual_form(
+    parent: &adw::ApplicationWindow,
+    query: &str,
+    segments: usize,
+    reason: &str,
+) {
+    let game = EntryRow::builder().title("Game").text(query).build();
+    let category = EntryRow::builder().title("Category").build();
+    let group = PreferencesGroup::new();
+    group.add(&game);
+    group.add(&category);
+
+    let dialog = AlertDialog::builder()
+        .heading("New Run")
+        .body(format!("{reason}\nEnter the names of the new run instead."))
+        .extra_child(&group)
+        .default_response("create")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("create", "C_reate");
+    dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+    dialog.set_response_enabled("create", false);
+    let update = {
+        let dialog = dialog.clone();
+        let game = game.clone();
+        let category = category.clone();
+        move |_: &EntryRow| {
+            let complete = !game.text().trim().is_empty() && !category.text().trim().is_empty();
+            dialog.set_response_enabled("create", complete);
+        }
+    };
+    game.connect_changed(update.clone());
+    category.connect_changed(update);
+
+    let parent_binding = parent.clone();
+    dialog.connect_response(Some("create"), move |_, _| {
+        let run = speedruncom::manual_run(&game.text(), &category.text(), segments);
+        open_new_run(&parent_binding, run);
+    });
+    dialog.present(Some(parent));
+}
+
+/// Writes `run` to a new splits file beside the current one (or in the config
+/// directory), loads it once the current run is safe to replace, and opens the
+/// editor on it.
+fn open_new_run(parent: &adw::ApplicationWindow, run: Run) {
+    let parent_binding = parent.clone();
+    confirm_unsaved(parent, move || {
+        let dir = TuxSplitContext::get_instance()
+            .config()
+            .general
+            .splits
+            .as_deref()
+            .and_then(|splits| splits.parent())
+            .map_or_else(|| paths::current().root.clone(), PathBuf::from);
+        let path = new_splits_path(&dir, run.game_name(), run.category_name());
+
+        let mut buf = String::new();
+        if let Err(e) = save_run(&run, &mut buf) {
+            warn!("Could not write the new run: {e}");
+            return;
+        }
+        if let Err(source) = write_atomic(&path, buf) {
+            show_error(&parent_binding, &TuxSplitError::Io { path, source });
+            return;
+        }
+        match TuxSplitContext::get_instance().load_splits(path) {
+            Ok(()) => SplitEditor::new().present(),
+            Err(e) => show_error(&parent_binding, &e),
+        }
+    });
+}
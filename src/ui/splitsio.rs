@@ -0,0 +1,115 @@
+//! "Upload to splits.io" menu action: asks first, uploads on a background
+//! thread and shows the links of the uploaded run.
+
+use adw::prelude::*;
+use adw::{AlertDialog, Toast, ToastOverlay, ToolbarView};
+use gtk4::{Label, UriLauncher, gio};
+use tracing::warn;
+
+use crate::context::TuxSplitContext;
+use crate::integrations::splitsio::{self, UploadedRun};
+
+/// Asks whether to publish the current run, then uploads it.
+pub fn confirm_upload(parent: &adw::ApplicationWindow) {
+    let dialog = AlertDialog::builder()
+        .heading("Upload to splits.io?")
+        .body("The splits and attempt history are uploaded anonymously and can be viewed by anyone with the link.")
+        .default_response("upload")
+        .close_response("cancel")
+        .build();
+    dialog.add_response("cancel", "_Cancel");
+    dialog.add_response("upload", "_Upload");
+    dialog.set_response_appearance("upload", adw::ResponseAppearance::Suggested);
+
+    let parent_binding = parent.clone();
+    dialog.connect_response(Some("upload"), move |_, _| {
+        upload(&parent_binding);
+    });
+    dialog.present(Some(parent));
+}
+
+fn upload(parent: &adw::ApplicationWindow) {
+    let payload = match splitsio::exchange_payload(&TuxSplitContext::get_instance().get_run()) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!("{e}");
+            show_toast(parent, &Toast::new(&e.to_string()));
+            return;
+        }
+    };
+    show_toast(parent, &Toast::new("Uploading to splits.io..."));
+
+    let parent = parent.clone();
+    glib::MainContext::default().spawn_local(async move {
+        let result = gio::spawn_blocking(move || splitsio::upload(&payload))
+            .await
+            .unwrap_or_else(|_| {
+                Err(splitsio::UploadError::Network(
+                    "the upload was interrupted".to_owned(),
+                ))
+            });
+        match result {
+            Ok(uploaded) => show_links(&parent, &uploaded),
+            Err(e) => {
+                warn!("{e}");
+                let toast = Toast::builder()
+                    .title(e.to_string())
+                    .button_label("Retry")
+                    .build();
+                let parent_binding = parent.clone();
+                toast.connect_button_clicked(move |_| upload(&parent_binding));
+                show_toast(&parent, &toast);
+            }
+        }
+    });
+}
+
+fn show_links(parent: &adw::ApplicationWindow, uploaded: &UploadedRun) {
+    let link = Label::builder()
+        .label(&uploaded.claim_url)
+        .selectable(true)
+        .wrap(true)
+        .wrap_mode(gtk4::pango::WrapMode::Char)
+        .css_classes(["monospace"])
+        .build();
+    let dialog = AlertDialog::builder()
+        .heading("Uploaded to splits.io")
+        .body("Open the link while signed in to add the run to your account. Until then, anyone with it can claim the run.")
+        .extra_child(&link)
+        .default_response("open")
+        .close_response("close")
+        .build();
+    dialog.add_response("close", "_Close");
+    dialog.add_response("copy", "Copy _Link");
+    dialog.add_response("open", "_Open in Browser");
+    dialog.set_response_appearance("open", adw::ResponseAppearance::Suggested);
+
+    let parent_binding = parent.clone();
+    let url = uploaded.claim_url.clone();
+    dialog.connect_response(None, move |_, response| match response {
+        "copy" => parent_binding.clipboard().set_text(&url),
+        "open" => UriLauncher::new(&url).launch(
+            Some(&parent_binding),
+            None::<&gio::Cancellable>,
+            |result| {
+                if let Err(e) = result {
+                    warn!("Could not open the splits.io link: {e}");
+                }
+            },
+        ),
+        _ => {}
+    });
+    dialog.present(Some(parent));
+}
+
+/// Shows `toast` over the timer of the main window.
+fn show_toast(parent: &adw::ApplicationWindow, toast: &Toast) {
+    let overlay = parent
+        .content()
+        .and_downcast::<ToolbarView>()
+        .and_then(|view| view.content())
+        .and_downcast::<ToastOverlay>();
+    if let Some(overlay) = overlay {
+        overlay.add_toast(toast.clone());
+    }
+}
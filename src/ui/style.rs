@@ -0,0 +1,559 @@
+use std::cell::{Cell, RefCell};
+
+use gtk4::{
+    CssProvider,
+    gdk::{Display, RGBA},
+    prelude::*,
+};
+use tracing::{debug, warn};
+
+use crate::config::{ColorOverrides, Config, StateColors, TimerFont};
+use crate::safe_mode::SafeMode;
+use crate::utils::contrast::{MIN_CONTRAST, Rgb, contrast_ratio, ensure_contrast, parse_hex_rgba};
+
+thread_local! {
+    static OVERRIDES_PROVIDER: CssProvider = CssProvider::new();
+    static OVERRIDES_REGISTERED: Cell<bool> = const { Cell::new(false) };
+    static TIMER_FONT_PROVIDER: CssProvider = CssProvider::new();
+    static TIMER_FONT_REGISTERED: Cell<bool> = const { Cell::new(false) };
+    static VIEWER_PROVIDER: CssProvider = CssProvider::new();
+    static VIEWER_REGISTERED: Cell<bool> = const { Cell::new(false) };
+    static TIMER_FONT_SIZE: Cell<u32> = Cell::new(TimerFont::default().size);
+    static VIEWER_SCALE: Cell<f64> = const { Cell::new(1.0) };
+    static BASE_PROVIDER: CssProvider = CssProvider::new();
+    static BASE_REGISTERED: Cell<bool> = const { Cell::new(false) };
+    static BACKGROUND_OVERRIDE: Cell<Option<Rgb>> = const { Cell::new(None) };
+    static STATE_COLORS: RefCell<StateColors> = RefCell::new(StateColors::default());
+    static LAYOUT_COLORS: RefCell<ColorOverrides> = RefCell::new(ColorOverrides::default());
+}
+
+/// Class of viewer windows, whose text is scaled apart from the main window.
+pub const VIEWER_CLASS: &str = "viewer";
+
+/// Window backgrounds of the Adwaita light and dark styles.
+const LIGHT_BACKGROUND: &str = "#fafafb";
+const DARK_BACKGROUND: &str = "#222226";
+
+/// Default colors of the deltas and golds: the named color, then the light and
+/// dark variants.
+const DELTA_PALETTE: [(&str, &str, &str); 5] = [
+    ("tuxsplit_ahead", "#26a269", "#33d17a"),
+    ("tuxsplit_ahead_losing", "#2ec27e", "#26a269"),
+    ("tuxsplit_behind", "#c01c28", "#ed333b"),
+    ("tuxsplit_behind_gaining", "#e01b24", "#f66151"),
+    ("tuxsplit_gold", "#c88800", "#e5a50a"),
+];
+const DEFAULT_PAUSED: &str = "#888888";
+const DEFAULT_FINISHED: &str = "@window_fg_color";
+
+/// Generic families that fontconfig always resolves, even if no font is named so.
+const GENERIC_FAMILIES: [&str; 4] = ["monospace", "sans", "sans-serif", "serif"];
+
+/// Loads the colors, color overrides and timer font of `config` on top of the
+/// bundled stylesheet. Safe mode keeps the bundled stylesheet and the default
+/// font alone.
+pub fn apply_config(config: &Config, safe_mode: SafeMode) {
+    if !safe_mode.custom_css() {
+        apply_timer_font(None);
+        return;
+    }
+    apply_state_colors(&config.display.colors);
+    apply_color_overrides(config.style.colors.as_ref());
+    apply_timer_font(config.display.timer_font.as_ref());
+}
+
+/// Loads the given color overrides (of an imported layout) on top of the bundled
+/// stylesheet.
+///
+/// The provider is registered once and reloaded on every call, so passing `None`
+/// (or empty overrides) restores the default colors. The ahead, behind and gold
+/// colors end up in the named colors, under the ones of `display.colors`.
+pub fn apply_color_overrides(colors: Option<&ColorOverrides>) {
+    let Some(display) = Display::default() else {
+        return;
+    };
+    let css = colors.map(color_overrides_css).unwrap_or_default();
+
+    OVERRIDES_PROVIDER.with(|provider| {
+        OVERRIDES_REGISTERED.with(|registered| load_provider(&display, provider, registered, &css));
+    });
+    LAYOUT_COLORS.replace(colors.cloned().unwrap_or_default());
+
+    // The default colors are checked against the overridden background
+    let background = colors
+        .and_then(|colors| colors.background.as_deref())
+        .and_then(|background| gtk4::gdk::RGBA::parse(background).ok())
+        .map(|rgba| {
+            Rgb::new(
+                f64::from(rgba.red()),
+                f64::from(rgba.green()),
+                f64::from(rgba.blue()),
+            )
+        });
+    BACKGROUND_OVERRIDE.set(background);
+    apply_named_colors();
+}
+
+/// Sets the colors of `display.colors`, regenerating the named colors only.
+pub fn apply_state_colors(colors: &StateColors) {
+    STATE_COLORS.replace(colors.clone());
+    apply_named_colors();
+}
+
+/// Keeps the default colors readable on the light and dark styles, regenerating
+/// the named colors whenever the style changes.
+pub fn follow_color_scheme() {
+    adw::StyleManager::default().connect_dark_notify(|_| apply_named_colors());
+    apply_named_colors();
+}
+
+/// The color the state `key` of `StateColors` is shown in, unless it refers to
+/// another named color.
+pub fn current_state_color(key: &str) -> Option<RGBA> {
+    let prefix = format!("@define-color tuxsplit_{key} ");
+    current_named_colors_css()
+        .lines()
+        .find_map(|line| line.strip_prefix(&prefix)?.strip_suffix(';'))
+        .and_then(|value| RGBA::parse(value).ok())
+}
+
+fn current_named_colors_css() -> String {
+    STATE_COLORS.with_borrow(|colors| {
+        LAYOUT_COLORS.with_borrow(|layout| {
+            named_colors_css(
+                adw::StyleManager::default().is_dark(),
+                BACKGROUND_OVERRIDE.get(),
+                colors,
+                layout,
+            )
+        })
+    })
+}
+
+fn apply_named_colors() {
+    let Some(display) = Display::default() else {
+        return;
+    };
+    let css = current_named_colors_css();
+
+    // The base of every other provider, which only refer to the named colors
+    BASE_PROVIDER.with(|provider| {
+        provider.load_from_string(&css);
+        if !BASE_REGISTERED.replace(true) {
+            gtk4::style_context_add_provider_for_display(
+                &display,
+                provider,
+                gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
+            );
+        }
+    });
+}
+
+/// Loads the timer font on top of the bundled stylesheet.
+///
+/// `None` keeps the system monospace font. Either way, digits are forced to
+/// tabular widths so the timer does not jiggle while running. A family that is
+/// not installed is ignored with a warning.
+pub fn apply_timer_font(font: Option<&TimerFont>) {
+    let Some(display) = Display::default() else {
+        return;
+    };
+
+    let mut font = font.cloned().unwrap_or_default();
+    if !family_available(&font.family) {
+        warn!(
+            "Timer font family \"{}\" is not installed, using the system monospace font",
+            font.family
+        );
+        font.family = TimerFont::default().family;
+    }
+    let css = timer_font_css(&font);
+
+    TIMER_FONT_PROVIDER.with(|provider| {
+        TIMER_FONT_REGISTERED
+            .with(|registered| load_provider(&display, provider, registered, &css));
+    });
+
+    // The viewer sizes its timer digits from the font size
+    TIMER_FONT_SIZE.set(font.size);
+    apply_viewer_scale(VIEWER_SCALE.get());
+}
+
+/// Scales the text of viewer windows, the timer digits included.
+pub fn apply_viewer_scale(scale: f64) {
+    let Some(display) = Display::default() else {
+        return;
+    };
+    VIEWER_SCALE.set(scale);
+    let css = viewer_css(scale, TIMER_FONT_SIZE.get());
+
+    VIEWER_PROVIDER.with(|provider| {
+        VIEWER_REGISTERED.with(|registered| load_provider(&display, provider, registered, &css));
+    });
+}
+
+fn load_provider(display: &Display, provider: &CssProvider, registered: &Cell<bool>, css: &str) {
+    provider.load_from_string(css);
+    if !registered.replace(true) {
+        gtk4::style_context_add_provider_for_display(
+            display,
+            provider,
+            gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+        );
+    }
+}
+
+fn is_generic_family(family: &str) -> bool {
+    GENERIC_FAMILIES
+        .iter()
+        .any(|generic| generic.eq_ignore_ascii_case(family))
+}
+
+/// Whether pango (through fontconfig) knows a font family with this name.
+fn family_available(family: &str) -> bool {
+    is_generic_family(family)
+        || gtk4::Label::new(None)
+            .pango_context()
+            .list_families()
+            .iter()
+            .any(|f| f.name().eq_ignore_ascii_case(family))
+}
+
+/// CSS pixels for a font size in points, at CSS's 96 pixels to 72 points.
+pub fn points_to_pixels(points: f64) -> f64 {
+    points * 96.0 / 72.0
+}
+
+fn timer_font_css(font: &TimerFont) -> String {
+    let family = if is_generic_family(&font.family) {
+        "var(--monospace-font-family)".to_owned()
+    } else {
+        format!("{}, monospace", css_string(&font.family))
+    };
+    let size = font.size.max(1);
+
+    format!(
+        ".timer, .bigtimer, .smalltimer {{ font-family: {family}; font-feature-settings: \"tnum\"; }}\n\
+         .bigtimer {{ font-size: {size}px; }}\n\
+         .smalltimer {{ font-size: {}px; }}\n",
+        (size / 2).max(1)
+    )
+}
+
+fn viewer_css(scale: f64, timer_font_size: u32) -> String {
+    let scale = scale.clamp(0.25, 4.0);
+    let size = (f64::from(timer_font_size.max(1)) * scale).round().max(1.0);
+
+    format!(
+        ".{VIEWER_CLASS} {{ font-size: {}%; }}\n\
+         .{VIEWER_CLASS} .bigtimer {{ font-size: {size}px; }}\n\
+         .{VIEWER_CLASS} .smalltimer {{ font-size: {}px; }}\n",
+        (scale * 100.0).round(),
+        (size / 2.0).round().max(1.0)
+    )
+}
+
+/// Quotes a value as a CSS string, escaping quotes and backslashes and dropping
+/// control characters (which cannot appear in a CSS string).
+fn css_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c if c.is_control() => {}
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The `@define-color` lines of the state colors the stylesheet refers to.
+///
+/// Colors of `display.colors` come first, then the ones of an imported `layout`,
+/// and both are used as chosen. Otherwise the light or `dark` defaults are used,
+/// made readable on `background` (the style's own when `None`). The losing and
+/// gaining variants of a chosen ahead or behind color are that color, faded.
+fn named_colors_css(
+    dark: bool,
+    background: Option<Rgb>,
+    colors: &StateColors,
+    layout: &ColorOverrides,
+) -> String {
+    let background = background.unwrap_or_else(|| {
+        Rgb::from_hex(if dark {
+            DARK_BACKGROUND
+        } else {
+            LIGHT_BACKGROUND
+        })
+        .unwrap_or(Rgb::WHITE)
+    });
+    let chosen = |key: &str, layout: &Option<String>| {
+        colors
+            .get(key)
+            .and_then(hex_color_css)
+            .or_else(|| layout.clone())
+    };
+    let ahead = chosen("ahead", &layout.ahead);
+    let behind = chosen("behind", &layout.behind);
+    let faded = |color: &Option<String>, name: &str| {
+        color.as_ref().map(|_| format!("alpha(@{name}, 0.75)"))
+    };
+
+    let defined = [
+        ahead.clone(),
+        faded(&ahead, "tuxsplit_ahead"),
+        behind.clone(),
+        faded(&behind, "tuxsplit_behind"),
+        chosen("gold", &layout.gold),
+    ];
+    let mut css: String = DELTA_PALETTE
+        .iter()
+        .zip(defined)
+        .map(|((name, light, dark_variant), defined)| {
+            let color = defined.unwrap_or_else(|| {
+                let default =
+                    Rgb::from_hex(if dark { dark_variant } else { light }).unwrap_or(Rgb::BLACK);
+                let color = ensure_contrast(default, background, MIN_CONTRAST);
+                if color != default {
+                    debug!(
+                        "Adjusted {name} from {} to {} for a contrast of {:.2}",
+                        default.to_hex(),
+                        color.to_hex(),
+                        contrast_ratio(color, background)
+                    );
+                }
+                color.to_hex()
+            });
+            format!("@define-color {name} {color};\n")
+        })
+        .collect();
+
+    for (name, key, default) in [
+        ("tuxsplit_paused", "paused", DEFAULT_PAUSED),
+        ("tuxsplit_finished", "finished", DEFAULT_FINISHED),
+    ] {
+        let color = colors
+            .get(key)
+            .and_then(hex_color_css)
+            .unwrap_or_else(|| default.to_owned());
+        css.push_str(&format!("@define-color {name} {color};\n"));
+    }
+    css
+}
+
+/// A hex color as CSS: as is when opaque, as `rgba()` otherwise.
+fn hex_color_css(hex: &str) -> Option<String> {
+    let [red, green, blue, alpha] = parse_hex_rgba(hex)?;
+    Some(if alpha == u8::MAX {
+        format!("#{red:02x}{green:02x}{blue:02x}")
+    } else {
+        format!(
+            "rgba({red}, {green}, {blue}, {})",
+            (f64::from(alpha) / 255.0 * 1000.0).round() / 1000.0
+        )
+    })
+}
+
+/// The color of a color button as `#rrggbb`, or `#rrggbbaa` when translucent.
+pub fn rgba_to_hex(rgba: &RGBA) -> String {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let hex = format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(rgba.red()),
+        channel(rgba.green()),
+        channel(rgba.blue())
+    );
+    match channel(rgba.alpha()) {
+        u8::MAX => hex,
+        alpha => format!("{hex}{alpha:02x}"),
+    }
+}
+
+fn color_overrides_css(colors: &ColorOverrides) -> String {
+    colors
+        .background
+        .as_ref()
+        .map(|background| format!("window.background {{ background-color: {background}; }}\n"))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod style_tests {
+    use super::*;
+
+    #[test]
+    fn safe_mode_leaves_the_colors_of_the_config_out() {
+        let mut config = Config::default();
+        config.display.colors.gold = Some("#ffd700".to_owned());
+
+        apply_config(&config, SafeMode::new(true));
+        assert_eq!(
+            STATE_COLORS.with_borrow(Clone::clone),
+            StateColors::default()
+        );
+        apply_config(&config, SafeMode::default());
+        assert_eq!(
+            STATE_COLORS.with_borrow(Clone::clone),
+            config.display.colors
+        );
+    }
+
+    fn defaults(dark: bool, background: Option<Rgb>) -> String {
+        named_colors_css(
+            dark,
+            background,
+            &StateColors::default(),
+            &ColorOverrides::default(),
+        )
+    }
+
+    /// The value of the named color `name` in `css`.
+    fn value<'a>(css: &'a str, name: &str) -> &'a str {
+        let prefix = format!("@define-color {name} ");
+        css.lines()
+            .find_map(|line| line.strip_prefix(&prefix))
+            .and_then(|value| value.strip_suffix(';'))
+            .unwrap_or_else(|| panic!("{name} is not defined in {css}"))
+    }
+
+    #[test]
+    fn only_the_background_of_a_layout_is_a_rule() {
+        let colors = ColorOverrides {
+            background: Some("rgba(15, 15, 15, 1)".to_owned()),
+            ahead: Some("rgba(0, 204, 54, 1)".to_owned()),
+            ..ColorOverrides::default()
+        };
+        assert_eq!(
+            color_overrides_css(&colors),
+            "window.background { background-color: rgba(15, 15, 15, 1); }\n"
+        );
+        assert!(color_overrides_css(&ColorOverrides::default()).is_empty());
+    }
+
+    #[test]
+    fn delta_colors_follow_the_style_and_stay_readable() {
+        let light = defaults(false, None);
+        let dark = defaults(true, None);
+        assert_eq!(light.lines().count(), DELTA_PALETTE.len() + 2);
+        assert_ne!(light, dark);
+        // Already readable on its own background, so used as is
+        assert_eq!(value(&light, "tuxsplit_behind"), "#c01c28");
+        assert_eq!(value(&light, "tuxsplit_paused"), DEFAULT_PAUSED);
+        assert_eq!(value(&dark, "tuxsplit_finished"), "@window_fg_color");
+
+        for (css, background) in [(&light, LIGHT_BACKGROUND), (&dark, DARK_BACKGROUND)] {
+            let background = Rgb::from_hex(background).unwrap();
+            for (name, _, _) in DELTA_PALETTE {
+                let ratio = contrast_ratio(Rgb::from_hex(value(css, name)).unwrap(), background);
+                assert!(ratio >= MIN_CONTRAST, "{name} has a contrast of {ratio}");
+            }
+        }
+    }
+
+    #[test]
+    fn delta_colors_are_checked_against_an_overridden_background() {
+        // The bright green of the dark style is too light on a white background
+        let css = defaults(true, Some(Rgb::WHITE));
+        let green = value(&css, "tuxsplit_ahead");
+        assert_ne!(green, "#33d17a");
+        assert!(contrast_ratio(Rgb::from_hex(green).unwrap(), Rgb::WHITE) >= MIN_CONTRAST);
+    }
+
+    #[test]
+    fn chosen_colors_win_over_the_layout_and_the_defaults() {
+        let colors = StateColors {
+            ahead: Some("#00FF00".to_owned()),
+            paused: Some("#88888880".to_owned()),
+            finished: Some("#fff".to_owned()),
+            ..StateColors::default()
+        };
+        let layout = ColorOverrides {
+            ahead: Some("rgba(1, 2, 3, 1)".to_owned()),
+            behind: Some("rgba(200, 0, 0, 1)".to_owned()),
+            ..ColorOverrides::default()
+        };
+        let css = named_colors_css(false, None, &colors, &layout);
+        // Used as chosen, even if hard to read on the light style
+        assert_eq!(value(&css, "tuxsplit_ahead"), "#00ff00");
+        assert_eq!(
+            value(&css, "tuxsplit_ahead_losing"),
+            "alpha(@tuxsplit_ahead, 0.75)"
+        );
+        assert_eq!(value(&css, "tuxsplit_behind"), "rgba(200, 0, 0, 1)");
+        assert_eq!(
+            value(&css, "tuxsplit_gold"),
+            value(&defaults(false, None), "tuxsplit_gold")
+        );
+        assert_eq!(value(&css, "tuxsplit_paused"), "rgba(136, 136, 136, 0.502)");
+        assert_eq!(value(&css, "tuxsplit_finished"), "#ffffff");
+    }
+
+    #[test]
+    fn hex_colors_round_trip_through_color_buttons() {
+        let channel = |value: u8| f32::from(value) / 255.0;
+        for hex in ["#26a269", "#26a26980", "#00000000"] {
+            let [red, green, blue, alpha] = parse_hex_rgba(hex).unwrap();
+            let rgba = RGBA::new(channel(red), channel(green), channel(blue), channel(alpha));
+            assert_eq!(rgba_to_hex(&rgba), hex);
+        }
+        assert_eq!(rgba_to_hex(&RGBA::new(1.0, 0.0, 2.0, 1.0)), "#ff00ff");
+        assert_eq!(hex_color_css("green"), None);
+        assert_eq!(
+            hex_color_css("#1234"),
+            Some("rgba(17, 34, 51, 0.267)".to_owned())
+        );
+    }
+
+    #[test]
+    fn timer_font_quotes_family_and_forces_tabular_numbers() {
+        let font = TimerFont {
+            family: "DejaVu Sans Mono".to_owned(),
+            size: 40,
+        };
+        assert_eq!(
+            timer_font_css(&font),
+            ".timer, .bigtimer, .smalltimer { font-family: \"DejaVu Sans Mono\", monospace; font-feature-settings: \"tnum\"; }\n\
+             .bigtimer { font-size: 40px; }\n\
+             .smalltimer { font-size: 20px; }\n"
+        );
+    }
+
+    #[test]
+    fn font_sizes_in_points_are_converted_to_pixels() {
+        assert!((points_to_pixels(27.0) - 36.0).abs() < f64::EPSILON);
+        assert!((points_to_pixels(12.0) - 16.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn timer_font_defaults_to_system_monospace() {
+        let css = timer_font_css(&TimerFont::default());
+        assert!(css.contains("font-family: var(--monospace-font-family);"));
+        assert!(css.contains(".bigtimer { font-size: 36px; }"));
+        assert!(css.contains(".smalltimer { font-size: 18px; }"));
+    }
+
+    #[test]
+    fn viewer_scale_applies_to_text_and_timer_digits() {
+        assert_eq!(
+            viewer_css(1.5, 36),
+            ".viewer { font-size: 150%; }\n\
+             .viewer .bigtimer { font-size: 54px; }\n\
+             .viewer .smalltimer { font-size: 27px; }\n"
+        );
+        // Out of range scales are clamped
+        assert!(viewer_css(0.0, 36).contains(".viewer { font-size: 25%; }"));
+    }
+
+    #[test]
+    fn css_string_escapes_quotes_backslashes_and_control_characters() {
+        assert_eq!(css_string("Fira Code"), "\"Fira Code\"");
+        assert_eq!(css_string(r#"My "Font""#), r#""My \"Font\"""#);
+        assert_eq!(css_string(r"Back\slash"), r#""Back\\slash""#);
+        assert_eq!(css_string("Evil\n} * {"), "\"Evil} * {\"");
+    }
+}
@@ -1,27 +1,41 @@
 use crate::config::Config;
+use crate::context::TuxSplitContext;
+use crate::formatters::column::{WIDE_COLUMN_CHARS, column_chars, comparison_column};
+use crate::formatters::tooltip::{
+    comparison_tooltip_markup, gold_tooltip_line, pb_date_tooltip_line, reached_tooltip_line,
+    secondary_tooltip_line,
+};
+use crate::ui::timer::finish::{FINAL_TIME_CLASS, is_final_row};
+use crate::ui::timer::flash;
+use crate::ui::timer::sparkline::DeltaSparkline;
+use crate::utils::compaction::REDUCED_SPLITS;
 use crate::utils::comparisons::{
-    classify_split_label, current_attempt_running_duration, format_signed,
-    previous_split_combined_gold_and_prev_comparison, segment_comparison_time, segment_split_time,
+    best_achieved_split_times, classify_split_label, current_attempt_running_duration,
+    format_signed, previous_split_combined_gold_and_prev_comparison, segment_comparison_time,
+    segment_split_time, sum_of_best_split_times,
 };
+use crate::utils::naming::ellipsize_middle;
+use crate::utils::pb_dates::pb_split_dates;
+use crate::utils::reached::reached_per_segment;
 
 use adw::ActionRow;
 use adw::prelude::ActionRowExt;
-use glib::Propagation;
 use gtk4::ffi::GTK_ICON_LOOKUP_FORCE_REGULAR;
-use gtk4::{
-    Align, Box as GtkBox, EventControllerKey, Label, ListBox, Orientation, ScrolledWindow,
-    SelectionMode, gdk,
-};
+use gtk4::{Align, Box as GtkBox, Label, ListBox, Orientation, ScrolledWindow, SelectionMode};
 use gtk4::{CenterBox, prelude::*};
 
 use livesplit_core::{Timer, TimerPhase};
 
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
 /// The body of the Timer UI:
 ///
 /// It owns a vertical container and a `SegmentList` that renders the splits.
 pub struct TimerBody {
     container: GtkBox,
     segment_list: SegmentList,
+    sparkline: DeltaSparkline,
 }
 
 impl TimerBody {
@@ -34,9 +48,13 @@ impl TimerBody {
         let segment_list = SegmentList::new(timer, config);
         container.append(segment_list.container());
 
+        let sparkline = DeltaSparkline::new(timer, config);
+        container.append(sparkline.widget());
+
         Self {
             container,
             segment_list,
+            sparkline,
         }
     }
 
@@ -52,8 +70,68 @@ impl TimerBody {
         self.segment_list.last_segment_list()
     }
 
+    /// Row widget of the segment at `index`.
+    pub fn row_widget(&self, index: usize) -> Option<ActionRow> {
+        self.segment_list
+            .rows
+            .get(index)
+            .map(|row| row.row().clone())
+    }
+
+    /// Takes the "split on" descriptions of the segments, shown from the next
+    /// refresh of their rows.
+    pub fn set_split_on(&mut self, descriptions: Vec<Option<String>>) {
+        self.segment_list.split_on = descriptions;
+    }
+
+    /// Cuts the splits down to the current split and the ones around it (see
+    /// `utils::compaction`), or shows as many as the config asks for again.
+    pub fn set_reduced(&mut self, reduced: bool, timer: &Timer, config: &Config) {
+        self.segment_list.set_reduced(reduced, timer, config);
+    }
+
+    /// Height of the splits with `reduced` rows or not, the rest of the body
+    /// included.
+    pub fn natural_height(&self, timer: &Timer, config: &Config, reduced: bool) -> i32 {
+        let (_, natural, _, _) = self.container.measure(Orientation::Vertical, -1);
+        natural - self.segment_list.scroller.height_request()
+            + SegmentList::compute_scroller_height(timer, config, reduced)
+    }
+
     pub fn refresh(&mut self, timer: &Timer, config: &Config, force_rebuild: bool) {
         self.segment_list.update(timer, config, force_rebuild);
+        self.sparkline.refresh(timer, config, force_rebuild);
+    }
+}
+
+/// Paces a completed split is measured against, cached per row by `SegmentList`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SegmentPace {
+    /// Best split time ever achieved at the segment.
+    pub best: Option<time::Duration>,
+    /// Sum of the best segments up to the segment, `None` after a missing gold.
+    pub sum_of_best: Option<time::Duration>,
+    /// Left out of gold detection, so never shown as a gold.
+    pub excluded: bool,
+}
+
+impl SegmentPace {
+    /// Paces of every segment of the run. Only resets and edits change them, and
+    /// both rebuild or rebind the rows.
+    fn for_run(timer: &Timer) -> Vec<Self> {
+        let segments = timer.run().segments();
+        let method = timer.current_timing_method();
+        let excluded = TuxSplitContext::get_instance().excluded_segments(segments);
+        best_achieved_split_times(segments, method)
+            .into_iter()
+            .zip(sum_of_best_split_times(segments, method))
+            .zip(excluded)
+            .map(|((best, sum_of_best), excluded)| Self {
+                best,
+                sum_of_best,
+                excluded,
+            })
+            .collect()
     }
 }
 
@@ -64,6 +142,11 @@ pub struct SegmentList {
     list: ListBox,
     last_segment_list: ListBox,
     rows: Vec<SegmentRow>,
+    pace: Vec<SegmentPace>,
+    /// "Split on" description of each segment, see `split_on_subtitle`.
+    split_on: Vec<Option<String>>,
+    /// Whether only `REDUCED_SPLITS` rows are shown, see `set_reduced`.
+    reduced: bool,
     last_phase: TimerPhase,
     last_comparison: String,
 }
@@ -78,7 +161,7 @@ impl SegmentList {
             .css_classes(["splits-container", "no-background"])
             .build();
 
-        let height_request = SegmentList::compute_scroller_height(timer, config);
+        let height_request = SegmentList::compute_scroller_height(timer, config, false);
 
         let scroller = ScrolledWindow::builder()
             .hexpand(true)
@@ -89,14 +172,18 @@ impl SegmentList {
             .kinetic_scrolling(true)
             .build();
 
+        // Display only: rows are picked with the mouse, and keyboard focus stays
+        // with the controls
         let list = ListBox::builder()
             .selection_mode(SelectionMode::Single)
             .hexpand(true)
+            .can_focus(false)
             .css_classes(["split-boxed-list", "no-background"])
             .build();
         let last_segment_list = ListBox::builder()
             .selection_mode(SelectionMode::Single)
             .hexpand(true)
+            .can_focus(false)
             .css_classes(["last-split-boxed-list", "no-background"])
             .build();
 
@@ -110,6 +197,9 @@ impl SegmentList {
             list,
             last_segment_list,
             rows: Vec::new(),
+            pace: Vec::new(),
+            split_on: Vec::new(),
+            reduced: false,
             last_phase: timer.current_phase(),
             last_comparison: timer.current_comparison().to_owned(),
         };
@@ -155,7 +245,6 @@ impl SegmentList {
             && let Some(index) = selected_index
             && let Some(row) = self.list.row_at_index(index)
         {
-            self.list.grab_focus();
             self.list.select_row(Some(&row));
         }
 
@@ -164,7 +253,6 @@ impl SegmentList {
                 // Go to the beggining of the split list after a reset
                 self.update_scroll_position(timer, config);
             } else if phase.is_ended() {
-                self.last_segment_list.grab_focus();
                 self.last_segment_list
                     .select_row(Some(&self.last_segment_list.row_at_index(0).unwrap()));
             }
@@ -175,15 +263,30 @@ impl SegmentList {
         self.last_comparison = timer.current_comparison().to_string();
 
         // Update scroller height request
-        let height_request = SegmentList::compute_scroller_height(timer, config);
+        let height_request = SegmentList::compute_scroller_height(timer, config, self.reduced);
         self.scroller.set_height_request(height_request);
     }
 
+    fn set_reduced(&mut self, reduced: bool, timer: &Timer, config: &Config) {
+        if self.reduced == reduced {
+            return;
+        }
+        self.reduced = reduced;
+        self.scroller
+            .set_height_request(SegmentList::compute_scroller_height(timer, config, reduced));
+        self.update_scroll_position(timer, config);
+    }
+
     fn update_scroll_position(&mut self, timer: &Timer, config: &Config) {
         let adjustment = self.scroller.vadjustment();
 
         if let Some(cur) = timer.current_split_index() {
-            let follow_from = config.style.segments_scroll_follow_from.unwrap_or(7);
+            // Reduced, the current split is the middle row
+            let follow_from = if self.reduced {
+                REDUCED_SPLITS - 1
+            } else {
+                config.style.segments_scroll_follow_from.unwrap_or(7)
+            };
             let y = SegmentRow::get_natural_height() * (cur as i32 + 1 - follow_from as i32);
 
             if self.list.row_at_index(cur as i32).is_some() {
@@ -217,7 +320,9 @@ impl SegmentList {
                     && let Some(row) = self.rows.get_mut(i)
                 {
                     let seg = &timer.run().segments()[i];
-                    row.refresh(timer, config, Some(cur), i, seg);
+                    let pace = self.pace.get(i).copied().unwrap_or_default();
+                    row.refresh(timer, config, Some(cur), i, seg, pace);
+                    row.show_split_on(split_on_subtitle(Some(cur), i, &self.split_on));
                 }
             }
         }
@@ -244,46 +349,6 @@ impl SegmentList {
                 list_ref.unselect_all();
             }
         });
-
-        // Keyboard navigation
-        let list_for_down = self.list.clone();
-        let last_list_for_down = self.last_segment_list.clone();
-        let down_ctrl = EventControllerKey::new();
-        down_ctrl.connect_key_pressed(move |_, keyval, _, _| {
-            if keyval == gdk::Key::Down
-                && let Some(selected) = list_for_down.selected_row()
-                && selected.next_sibling().is_none()
-                && let Some(row) = last_list_for_down.row_at_index(0)
-            {
-                last_list_for_down.grab_focus();
-                last_list_for_down.select_row(Some(&row));
-                return Propagation::Stop;
-            }
-            Propagation::Proceed
-        });
-        self.list.add_controller(down_ctrl);
-
-        let list_for_up = self.list.clone();
-        let last_list_for_up = self.last_segment_list.clone();
-        let scroller_for_up = self.scroller.clone();
-        let up_ctrl = EventControllerKey::new();
-        up_ctrl.connect_key_pressed(move |_, keyval, _, _| {
-            if keyval == gdk::Key::Up
-                && let Some(selected) = last_list_for_up.selected_row()
-                && selected.index() == 0
-                && let Some(last) = list_for_up.last_child()
-                && let Ok(row) = last.downcast::<gtk4::ListBoxRow>()
-            {
-                list_for_up.grab_focus();
-                list_for_up.select_row(Some(&row));
-                scroller_for_up
-                    .vadjustment()
-                    .set_value(scroller_for_up.vadjustment().upper());
-                return Propagation::Stop;
-            }
-            Propagation::Proceed
-        });
-        self.last_segment_list.add_controller(up_ctrl);
     }
 
     fn update_selection_policy(&mut self, phase: TimerPhase) {
@@ -314,7 +379,38 @@ impl SegmentList {
     }
 
     fn rebuild_rows(&mut self, timer: &Timer, config: &Config) {
-        self.build_rows(timer, config);
+        match reconcile_rows(self.rows.len(), timer.run().len()) {
+            RowReconciliation::Reuse => self.rebind_rows(timer, config),
+            RowReconciliation::Rebuild => self.build_rows(timer, config),
+        }
+    }
+
+    /// Points the existing rows at the (possibly new) run without touching the
+    /// list boxes, so scroll position and selection survive the update.
+    fn rebind_rows(&mut self, timer: &Timer, config: &Config) {
+        self.pace = SegmentPace::for_run(timer);
+
+        let opt_current_segment_index = timer.current_split_index();
+        for (index, (row, segment)) in self.rows.iter_mut().zip(timer.run().segments()).enumerate()
+        {
+            let pace = self.pace.get(index).copied().unwrap_or_default();
+            row.rebind(
+                timer,
+                config,
+                opt_current_segment_index,
+                index,
+                segment,
+                pace,
+            );
+            row.show_split_on(split_on_subtitle(
+                opt_current_segment_index,
+                index,
+                &self.split_on,
+            ));
+        }
+
+        self.last_phase = timer.current_phase();
+        self.last_comparison = timer.current_comparison().to_string();
     }
 
     fn build_rows(&mut self, timer: &Timer, config: &Config) {
@@ -326,10 +422,25 @@ impl SegmentList {
         }
         self.rows.clear();
 
+        self.pace = SegmentPace::for_run(timer);
+
         // Create new rows once and append references to the ListBox
         let opt_current_segment_index = timer.current_split_index();
         for (index, segment) in timer.run().segments().iter().enumerate() {
-            let row = SegmentRow::new(timer, config, opt_current_segment_index, index, segment);
+            let pace = self.pace.get(index).copied().unwrap_or_default();
+            let row = SegmentRow::new(
+                timer,
+                config,
+                opt_current_segment_index,
+                index,
+                segment,
+                pace,
+            );
+            row.show_split_on(split_on_subtitle(
+                opt_current_segment_index,
+                index,
+                &self.split_on,
+            ));
             // Last segment will always be visible, so we render it separately
             if index < timer.run().len() - 1 {
                 self.list.append(row.row());
@@ -344,8 +455,11 @@ impl SegmentList {
         self.last_comparison = timer.current_comparison().to_string();
     }
 
-    fn compute_scroller_height(timer: &Timer, config: &Config) -> i32 {
-        let segments_requested = config.style.max_segments_displayed.unwrap_or(10);
+    fn compute_scroller_height(timer: &Timer, config: &Config, reduced: bool) -> i32 {
+        let mut segments_requested = config.style.max_segments_displayed.unwrap_or(10);
+        if reduced {
+            segments_requested = segments_requested.min(REDUCED_SPLITS);
+        }
 
         if segments_requested < timer.run().len() - 1 {
             SegmentRow::get_natural_height() * segments_requested as i32
@@ -355,10 +469,60 @@ impl SegmentList {
     }
 }
 
+/// How the rendered rows should follow a run change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowReconciliation {
+    /// Same number of segments: keep the widgets and refresh their contents.
+    Reuse,
+    /// The segment count changed: drop every row and build new ones.
+    Rebuild,
+}
+
+/// The subtitle of the row at `index`: its "split on" description while it is
+/// the current split, nothing otherwise, so splits, undos and skips move it
+/// along with the current split.
+pub fn split_on_subtitle(
+    current: Option<usize>,
+    index: usize,
+    descriptions: &[Option<String>],
+) -> &str {
+    if current != Some(index) {
+        return "";
+    }
+    descriptions
+        .get(index)
+        .and_then(Option::as_deref)
+        .unwrap_or_default()
+}
+
+pub fn reconcile_rows(existing: usize, incoming: usize) -> RowReconciliation {
+    if existing == incoming && existing > 0 {
+        RowReconciliation::Reuse
+    } else {
+        RowReconciliation::Rebuild
+    }
+}
+
+/// Classes describing how a split went. Rows reused across attempts or runs must
+/// drop them before being computed again.
+const SPLIT_STATE_CLASSES: [&str; 5] = [
+    "goldsplit",
+    "greensplit",
+    "lostgreensplit",
+    "gainedredsplit",
+    "redsplit",
+];
+
+/// Widest a segment title asks to be, in characters, before it is ellipsized.
+const TITLE_MAX_WIDTH_CHARS: i32 = 32;
+
 // SegmentRow: wraps a row widget and its value label so we can refresh without touching the ListBox
 pub struct SegmentRow {
     row: ActionRow,
+    icon: Option<gtk4::Image>,
     suffix: SegmentSuffix,
+    // Comparison tooltip, built on first hover and dropped when the row is rebound
+    tooltip: Rc<RefCell<Option<String>>>,
 }
 
 impl SegmentRow {
@@ -372,40 +536,161 @@ impl SegmentRow {
         opt_current_segment_index: Option<usize>,
         index: usize,
         segment: &livesplit_core::Segment,
+        pace: SegmentPace,
     ) -> Self {
         let row = ActionRow::builder()
-            .title(segment.name())
+            .title(row_title(config, segment.name()))
             .hexpand(true)
             .title_lines(1)
+            .subtitle_lines(1)
             .build();
+        ellipsize_title_middle(&row);
 
-        let icon = segment.icon();
-        let mut data = icon.data().to_vec();
-
-        if !data.is_empty() && config.style.show_icons.unwrap_or(true) {
-            if !data.ends_with(&[0x82]) {
-                // PNG data must end in AE 42 60 82 (IEND CRC)
-                // For some fucking reason, the data obtained from livesplit-core misses the last byte
-                data.push(0x82);
-            }
-            let bytes = glib::Bytes::from(&data);
-            let texture = gtk4::gdk::Texture::from_bytes(&bytes).unwrap();
-            let image = gtk4::Image::from_paintable(Some(&texture));
-            image.set_pixel_size(24); // Slightly bigger than font
-            row.add_prefix(&image);
+        let icon = Self::build_icon(config, segment);
+        if let Some(image) = &icon {
+            row.add_prefix(image);
         }
 
         if Some(index) == opt_current_segment_index {
             row.add_css_class("current-segment");
         }
-        let suffix = SegmentSuffix::new(timer, config, opt_current_segment_index, index, segment);
+        let suffix = SegmentSuffix::new(
+            timer,
+            config,
+            opt_current_segment_index,
+            index,
+            segment,
+            pace,
+        );
 
         row.add_suffix(suffix.container());
 
         // Add no transition for more responsive updates
         row.add_css_class("no-transition");
 
-        Self { row, suffix }
+        let tooltip = Rc::new(RefCell::new(None));
+        row.set_has_tooltip(true);
+        let tooltip_binding = tooltip.clone();
+        row.connect_query_tooltip(move |_, _, _, _, tooltip| {
+            let markup = tooltip_binding
+                .borrow_mut()
+                .get_or_insert_with(|| Self::comparison_tooltip(index))
+                .clone();
+            tooltip.set_markup(Some(&markup));
+            true
+        });
+
+        Self {
+            row,
+            icon,
+            suffix,
+            tooltip,
+        }
+    }
+
+    /// Markup listing the segment's split time in every comparison of the run,
+    /// plus the best pace ever achieved at that split.
+    fn comparison_tooltip(index: usize) -> String {
+        let ctx = TuxSplitContext::get_instance();
+        let config = ctx.config();
+        let shared = ctx.timer();
+        let timer = shared.read().unwrap();
+
+        let method = timer.current_timing_method();
+        let segments = timer.run().segments();
+        let Some(segment) = segments.get(index) else {
+            return String::new();
+        };
+        let format = |time: Option<livesplit_core::TimeSpan>| {
+            time.map(|span| config.format.segment.format_time_span(&span))
+        };
+
+        let mut times: Vec<(String, Option<String>)> = timer
+            .run()
+            .comparisons()
+            .map(|name| (name.to_owned(), format(segment.comparison(name)[method])))
+            .collect();
+        let best_pace = best_achieved_split_times(segments, method)
+            .get(index)
+            .copied()
+            .flatten();
+        times.push((
+            "Best Achieved".to_owned(),
+            best_pace.map(|dur| config.format.segment.format_duration(&dur)),
+        ));
+
+        let gold = format(segment.best_segment_time()[method]);
+        let reached = reached_per_segment(timer.run())[index];
+        let mut markup = comparison_tooltip_markup(segment.name(), &times);
+        if let Some(name) = secondary_comparison(&timer, &config) {
+            markup.push('\n');
+            markup.push_str(&secondary_tooltip_line(
+                name,
+                format(segment.comparison(name)[method]).as_deref(),
+            ));
+        }
+        let mut markup = format!(
+            "{markup}\n{}\n{}",
+            gold_tooltip_line(gold.as_deref(), ctx.sidecar().gold_date(segments, index)),
+            reached_tooltip_line(&reached)
+        );
+        if let Some(date) = pb_split_dates(timer.run(), method)
+            .and_then(|dates| dates.get(index).copied().flatten())
+        {
+            markup.push('\n');
+            markup.push_str(&pb_date_tooltip_line(date));
+        }
+        markup
+    }
+
+    fn build_icon(config: &Config, segment: &livesplit_core::Segment) -> Option<gtk4::Image> {
+        let mut data = segment.icon().data().to_vec();
+        if data.is_empty() || !config.style.show_icons.unwrap_or(true) {
+            return None;
+        }
+
+        if !data.ends_with(&[0x82]) {
+            // PNG data must end in AE 42 60 82 (IEND CRC)
+            // For some fucking reason, the data obtained from livesplit-core misses the last byte
+            data.push(0x82);
+        }
+        let bytes = glib::Bytes::from(&data);
+        let texture = gtk4::gdk::Texture::from_bytes(&bytes).unwrap();
+        let image = gtk4::Image::from_paintable(Some(&texture));
+        image.set_pixel_size(24); // Slightly bigger than font
+        Some(image)
+    }
+
+    /// Updates a reused row to show another segment (e.g. after the run was swapped).
+    pub fn rebind(
+        &mut self,
+        timer: &Timer,
+        config: &Config,
+        opt_current_segment_index: Option<usize>,
+        index: usize,
+        segment: &livesplit_core::Segment,
+        pace: SegmentPace,
+    ) {
+        adw::prelude::PreferencesRowExt::set_title(&self.row, &row_title(config, segment.name()));
+        self.tooltip.replace(None);
+        flash::clear(&self.row);
+
+        if let Some(old) = self.icon.take() {
+            self.row.remove(&old);
+        }
+        self.icon = Self::build_icon(config, segment);
+        if let Some(image) = &self.icon {
+            self.row.add_prefix(image);
+        }
+
+        self.refresh(
+            timer,
+            config,
+            opt_current_segment_index,
+            index,
+            segment,
+            pace,
+        );
     }
 
     pub fn refresh(
@@ -415,15 +700,38 @@ impl SegmentRow {
         opt_current_segment_index: Option<usize>,
         index: usize,
         segment: &livesplit_core::Segment,
+        pace: SegmentPace,
     ) {
+        self.suffix.column_chars.set(column_chars(self.row.width()));
         // Reset dynamic classes
         self.row.remove_css_class("current-segment");
         if Some(index) == opt_current_segment_index {
             self.row.add_css_class("current-segment");
         }
 
-        self.suffix
-            .compute_segment(timer, config, opt_current_segment_index, index, segment);
+        self.suffix.compute_segment(
+            timer,
+            config,
+            opt_current_segment_index,
+            index,
+            segment,
+            pace,
+        );
+
+        // Undoing the final split rebuilds the rows, which drops the class again
+        let is_final = is_final_row(timer.current_phase(), index, timer.run().len());
+        self.row.remove_css_class(FINAL_TIME_CLASS);
+        if is_final {
+            self.row.add_css_class(FINAL_TIME_CLASS);
+            self.suffix.compute_final_delta(timer, config, segment);
+        }
+    }
+
+    /// Shows `description` as the subtitle, which an empty one hides.
+    pub fn show_split_on(&self, description: &str) {
+        if self.row.subtitle().as_deref() != Some(description) {
+            self.row.set_subtitle(description);
+        }
     }
 
     fn get_natural_height() -> i32 {
@@ -438,11 +746,68 @@ impl SegmentRow {
     }
 }
 
+/// The secondary comparison of the config while it is a comparison of the run
+/// other than the current one.
+fn secondary_comparison<'a>(timer: &Timer, config: &'a Config) -> Option<&'a str> {
+    config.display.secondary_comparison().filter(|&name| {
+        name != timer.current_comparison() && timer.run().comparisons().any(|c| c == name)
+    })
+}
+
+/// The title of the row of a segment named `name`, shortened to
+/// `display.split-name-length` characters. The comparison tooltip of the row
+/// shows the full name.
+fn row_title<'a>(config: &Config, name: &'a str) -> std::borrow::Cow<'a, str> {
+    match config.display.split_name_length {
+        Some(length) => ellipsize_middle(name, length),
+        None => name.into(),
+    }
+}
+
+/// Cuts the middle of titles too wide for the row out instead of their end, and
+/// stops them from asking for more than `TITLE_MAX_WIDTH_CHARS` characters.
+fn ellipsize_title_middle(row: &ActionRow) {
+    let mut child = row.first_child();
+    while let Some(widget) = child {
+        if let Some(label) = widget.downcast_ref::<Label>()
+            && label.has_css_class("title")
+        {
+            label.set_ellipsize(gtk4::pango::EllipsizeMode::Middle);
+            label.set_max_width_chars(TITLE_MAX_WIDTH_CHARS);
+            return;
+        }
+        // Depth first, the title sits a few boxes down
+        child = widget
+            .first_child()
+            .or_else(|| next_in_tree(&widget, row.upcast_ref()));
+    }
+}
+
+/// The widget after `widget` in a depth-first walk below `root`, skipping
+/// `widget`'s own children.
+fn next_in_tree(widget: &gtk4::Widget, root: &gtk4::Widget) -> Option<gtk4::Widget> {
+    let mut current = widget.clone();
+    loop {
+        if let Some(sibling) = current.next_sibling() {
+            return Some(sibling);
+        }
+        current = current.parent().filter(|parent| parent != root)?;
+    }
+}
+
 // A segment suffix contains both the delta and the comparison labels, and renders them in a box, that is meant to be attached to a SegmentRow
+// Completed splits may also show captions under the delta with the difference against the best achieved pace
+// and against the sum of best pace
 pub struct SegmentSuffix {
     container: CenterBox,
     delta_label: Label,
+    best_pace_label: Label,
+    sob_drift_label: Label,
     comparison_label: Label,
+    /// Time in the secondary comparison, dimmed after `comparison_label`.
+    secondary_label: Label,
+    /// Characters the two may take, see `column_chars`.
+    column_chars: Cell<usize>,
 }
 
 impl SegmentSuffix {
@@ -452,6 +817,7 @@ impl SegmentSuffix {
         opt_current_segment_index: Option<usize>,
         index: usize,
         segment: &livesplit_core::Segment,
+        pace: SegmentPace,
     ) -> Self {
         let container = CenterBox::builder()
             .orientation(Orientation::Horizontal)
@@ -462,20 +828,60 @@ impl SegmentSuffix {
             .valign(Align::Center)
             .css_classes(["timer", "monospace"])
             .build();
+        let best_pace_label = Label::builder()
+            .halign(Align::Center)
+            .visible(false)
+            .css_classes(["timer", "monospace", "caption", "best-pace"])
+            .build();
+        let sob_drift_label = Label::builder()
+            .halign(Align::Center)
+            .visible(false)
+            .css_classes(["timer", "monospace", "caption", "sob-drift"])
+            .build();
+        let delta_box = GtkBox::builder()
+            .orientation(Orientation::Vertical)
+            .valign(Align::Center)
+            .build();
+        delta_box.append(&delta_label);
+        delta_box.append(&best_pace_label);
+        delta_box.append(&sob_drift_label);
         let comparison_label = Label::builder()
             .halign(Align::Center)
             .valign(Align::Center)
             .css_classes(["timer", "monospace", "comparison"])
             .build();
-        container.set_start_widget(Some(&delta_label));
-        container.set_end_widget(Some(&comparison_label));
+        let secondary_label = Label::builder()
+            .valign(Align::Center)
+            .visible(false)
+            .css_classes(["timer", "monospace", "comparison", "secondary"])
+            .build();
+        let comparison_box = GtkBox::builder()
+            .orientation(Orientation::Horizontal)
+            .halign(Align::Center)
+            .spacing(4)
+            .build();
+        comparison_box.append(&comparison_label);
+        comparison_box.append(&secondary_label);
+        container.set_start_widget(Some(&delta_box));
+        container.set_end_widget(Some(&comparison_box));
 
         let suffix = Self {
             container,
             delta_label,
+            best_pace_label,
+            sob_drift_label,
             comparison_label,
+            secondary_label,
+            column_chars: Cell::new(WIDE_COLUMN_CHARS),
         };
-        suffix.compute_segment(timer, config, opt_current_segment_index, index, segment);
+        suffix.compute_segment(
+            timer,
+            config,
+            opt_current_segment_index,
+            index,
+            segment,
+            pace,
+        );
 
         suffix
     }
@@ -492,6 +898,7 @@ impl SegmentSuffix {
         opt_current_segment_index: Option<usize>,
         index: usize,
         segment: &livesplit_core::Segment,
+        pace: SegmentPace,
     ) {
         let segment_comparison_time = segment_comparison_time(segment, timer);
         let (previous_split_time, gold_duration, previous_comparison_duration) =
@@ -512,8 +919,19 @@ impl SegmentSuffix {
                 .as_str(),
         );
         self.delta_label.set_label("");
+        for class in SPLIT_STATE_CLASSES {
+            self.delta_label.remove_css_class(class);
+        }
+        self.best_pace_label.set_visible(false);
+        self.sob_drift_label.set_visible(false);
         if let Some(current_segment_index) = opt_current_segment_index {
             if current_segment_index > index {
+                if config.display.show_best_pace {
+                    self.compute_best_pace(timer, config, segment, pace.best);
+                }
+                if config.display.show_sob_drift {
+                    self.compute_sob_drift(timer, config, segment, pace.sum_of_best);
+                }
                 self.compute_passed_segment(
                     timer,
                     config,
@@ -522,6 +940,7 @@ impl SegmentSuffix {
                     previous_split_time,
                     segment_comparison_duration,
                     gold_duration,
+                    pace.excluded,
                 );
             }
 
@@ -536,6 +955,26 @@ impl SegmentSuffix {
                 );
             }
         }
+        self.compute_secondary(timer, config, segment);
+    }
+
+    /// Follows the time the comparison label shows with the segment's time in
+    /// the secondary comparison, if there is one other than the current.
+    fn compute_secondary(&self, timer: &Timer, config: &Config, segment: &livesplit_core::Segment) {
+        let secondary = secondary_comparison(timer, config).map(|name| {
+            config
+                .format
+                .segment
+                .format_split_time(&segment.comparison(name), timer.current_timing_method())
+        });
+        let column = comparison_column(
+            &self.comparison_label.label(),
+            secondary.as_deref(),
+            self.column_chars.get(),
+        );
+        self.secondary_label
+            .set_label(column.secondary.as_deref().unwrap_or_default());
+        self.secondary_label.set_visible(column.secondary.is_some());
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -548,6 +987,7 @@ impl SegmentSuffix {
         previous_split_time: time::Duration,
         segment_comparison_duration: time::Duration,
         gold_duration: time::Duration,
+        excluded: bool,
     ) {
         let split_time = segment_split_time(segment, timer);
 
@@ -580,11 +1020,83 @@ impl SegmentSuffix {
                     diff,
                     gold_duration,
                     false,
+                    excluded,
                 ));
             }
         }
     }
 
+    /// Shows the final time against the personal best, whatever the current
+    /// comparison is.
+    fn compute_final_delta(
+        &self,
+        timer: &Timer,
+        config: &Config,
+        segment: &livesplit_core::Segment,
+    ) {
+        let method = timer.current_timing_method();
+        let (Some(split), Some(pb)) = (
+            segment.split_time()[method],
+            segment.personal_best_split_time()[method],
+        ) else {
+            return;
+        };
+
+        let diff = split.to_duration() - pb.to_duration();
+        for class in SPLIT_STATE_CLASSES {
+            self.delta_label.remove_css_class(class);
+        }
+        self.delta_label
+            .set_label(format_signed(diff, config).as_str());
+        self.delta_label.add_css_class(if diff.is_negative() {
+            "greensplit"
+        } else {
+            "redsplit"
+        });
+    }
+
+    fn compute_best_pace(
+        &self,
+        timer: &Timer,
+        config: &Config,
+        segment: &livesplit_core::Segment,
+        best_pace: Option<time::Duration>,
+    ) {
+        let split_time = segment_split_time(segment, timer);
+        if let Some(best_pace) = best_pace
+            && split_time != time::Duration::ZERO
+        {
+            let diff = split_time.checked_sub(best_pace).unwrap_or_default();
+            self.best_pace_label
+                .set_label(format_signed(diff, config).as_str());
+            self.best_pace_label.set_visible(true);
+        }
+    }
+
+    /// Time lost against sum of best pace up to a completed split, or a dash when
+    /// a gold is missing on the way there.
+    fn compute_sob_drift(
+        &self,
+        timer: &Timer,
+        config: &Config,
+        segment: &livesplit_core::Segment,
+        sum_of_best: Option<time::Duration>,
+    ) {
+        let split_time = segment_split_time(segment, timer);
+        if split_time == time::Duration::ZERO {
+            return;
+        }
+        let label = match sum_of_best {
+            Some(sum_of_best) => format_signed(
+                split_time.checked_sub(sum_of_best).unwrap_or_default(),
+                config,
+            ),
+            None => "—".to_owned(),
+        };
+        self.sob_drift_label.set_label(&label);
+        self.sob_drift_label.set_visible(true);
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn compute_current_segment(
         &self,
@@ -647,7 +1159,7 @@ mod segment_row_ui_tests {
         let mut config = Config::default();
 
         let segment = &timer.run().segments()[0];
-        let row = SegmentRow::new(&timer, &config, None, 0, segment);
+        let row = SegmentRow::new(&timer, &config, None, 0, segment, SegmentPace::default());
 
         assert_eq!(row.row().title().as_str(), "Split A");
         assert!(
@@ -656,6 +1168,34 @@ mod segment_row_ui_tests {
         );
     }
 
+    #[gtk4::test]
+    fn long_segment_names_fit_the_clamp() {
+        gtk_test_init();
+
+        let name = "Chapter 3: The Very Long Descriptive Title ".repeat(5);
+        let mut run = livesplit_core::Run::new();
+        run.push_segment(livesplit_core::Segment::new(&name[..200]));
+        let timer = livesplit_core::Timer::new(run).expect("timer");
+        let mut config = Config::default();
+
+        let segment = &timer.run().segments()[0];
+        let row = SegmentRow::new(&timer, &config, None, 0, segment, SegmentPace::default());
+        let clamp = adw::Clamp::builder()
+            .maximum_size(300)
+            .child(row.row())
+            .build();
+        let (minimum, _, _, _) = row.row().measure(Orientation::Horizontal, -1);
+        assert!(minimum <= 300, "row needs {minimum}px");
+        let (_, natural, _, _) = clamp.measure(Orientation::Horizontal, -1);
+        assert!(natural <= 300, "clamp takes {natural}px");
+        // The full name stays in the title, it is only drawn shorter
+        assert_eq!(row.row().title().chars().count(), 200);
+
+        config.display.split_name_length = Some(40);
+        let row = SegmentRow::new(&timer, &config, None, 0, segment, SegmentPace::default());
+        assert_eq!(row.row().title().chars().count(), 40);
+    }
+
     #[gtk4::test]
     fn segment_row_applies_current_segment_class_when_current() {
         gtk_test_init();
@@ -668,7 +1208,7 @@ mod segment_row_ui_tests {
         let mut config = Config::default();
 
         let segment = &timer.run().segments()[0];
-        let row = SegmentRow::new(&timer, &config, Some(0), 0, segment);
+        let row = SegmentRow::new(&timer, &config, Some(0), 0, segment, SegmentPace::default());
 
         assert_eq!(row.row().title().as_str(), "Split A");
         assert!(
@@ -676,4 +1216,183 @@ mod segment_row_ui_tests {
             "Expected current-segment class"
         );
     }
+
+    #[gtk4::test]
+    fn rebinding_a_row_shows_the_new_segment_without_stale_state_classes() {
+        gtk_test_init();
+
+        let mut run = livesplit_core::Run::new();
+        run.push_segment(livesplit_core::Segment::new("Split A"));
+        let timer = livesplit_core::Timer::new(run).expect("timer");
+        let config = Config::default();
+
+        let mut row = SegmentRow::new(
+            &timer,
+            &config,
+            Some(0),
+            0,
+            &timer.run().segments()[0],
+            SegmentPace::default(),
+        );
+        row.suffix.delta_label.add_css_class("redsplit");
+        row.suffix.delta_label.add_css_class("goldsplit");
+
+        let mut other = livesplit_core::Run::new();
+        other.push_segment(livesplit_core::Segment::new("Other B"));
+        let other_timer = livesplit_core::Timer::new(other).expect("timer");
+        row.rebind(
+            &other_timer,
+            &config,
+            None,
+            0,
+            &other_timer.run().segments()[0],
+            SegmentPace::default(),
+        );
+
+        assert_eq!(row.row().title().as_str(), "Other B");
+        assert!(!row.row().has_css_class("current-segment"));
+        for class in SPLIT_STATE_CLASSES {
+            assert!(
+                !row.suffix.delta_label.has_css_class(class),
+                "Unexpected leftover class {class}"
+            );
+        }
+    }
+
+    #[gtk4::test]
+    fn the_secondary_comparison_is_dimmed_after_the_current_one() {
+        gtk_test_init();
+
+        let mut run = livesplit_core::Run::new();
+        let mut segment = livesplit_core::Segment::new("Split A");
+        segment.set_personal_best_split_time(
+            livesplit_core::Time::new()
+                .with_real_time(Some(livesplit_core::TimeSpan::from_seconds(754.5))),
+        );
+        segment.set_best_segment_time(
+            livesplit_core::Time::new()
+                .with_real_time(Some(livesplit_core::TimeSpan::from_seconds(740.1))),
+        );
+        run.push_segment(segment);
+        let timer = livesplit_core::Timer::new(run).expect("timer");
+        let mut config = Config::default();
+        let segment = &timer.run().segments()[0];
+
+        let row = SegmentRow::new(&timer, &config, None, 0, segment, SegmentPace::default());
+        assert!(!row.suffix.secondary_label.is_visible());
+
+        config.display.secondary_comparison = Some("Best Segments".to_owned());
+        let row = SegmentRow::new(&timer, &config, None, 0, segment, SegmentPace::default());
+        assert!(row.suffix.secondary_label.is_visible());
+        assert!(row.suffix.secondary_label.has_css_class("secondary"));
+        assert!(row.suffix.secondary_label.label().starts_with("/ "));
+
+        // Not a comparison of the run: nothing to show
+        config.display.secondary_comparison = Some("Gone".to_owned());
+        let row = SegmentRow::new(&timer, &config, None, 0, segment, SegmentPace::default());
+        assert!(!row.suffix.secondary_label.is_visible());
+    }
+
+    #[gtk4::test]
+    fn tab_skips_the_splits_between_the_controls() {
+        gtk_test_init();
+
+        let mut run = livesplit_core::Run::new();
+        for name in ["Split A", "Split B", "Split C"] {
+            run.push_segment(livesplit_core::Segment::new(name));
+        }
+        let timer = livesplit_core::Timer::new(run).expect("timer");
+        let body = TimerBody::new(&timer, &Config::default());
+
+        let before = gtk4::Button::with_label("Before");
+        let after = gtk4::Button::with_label("After");
+        let content = GtkBox::new(Orientation::Vertical, 0);
+        content.append(&before);
+        content.append(body.container());
+        content.append(&after);
+        let window = gtk4::Window::builder().child(&content).build();
+        window.present();
+
+        let mut chain = Vec::new();
+        for _ in 0..2 {
+            window.child_focus(gtk4::DirectionType::TabForward);
+            chain.push(GtkWindowExt::focus(&window).expect("a focused widget"));
+        }
+        assert_eq!(chain, [before.upcast::<gtk4::Widget>(), after.upcast()]);
+        assert!(!body.list().can_focus());
+        assert!(!body.last_segment_list().can_focus());
+        window.destroy();
+    }
+}
+
+#[cfg(test)]
+mod split_on_tests {
+    use super::*;
+
+    fn descriptions() -> Vec<Option<String>> {
+        vec![
+            Some("door closes behind you".to_owned()),
+            None,
+            Some("boss dies".to_owned()),
+        ]
+    }
+
+    /// Subtitles of every row with `current` as the current split.
+    fn subtitles(current: Option<usize>, descriptions: &[Option<String>]) -> Vec<&str> {
+        (0..3)
+            .map(|index| split_on_subtitle(current, index, descriptions))
+            .collect()
+    }
+
+    #[test]
+    fn only_the_current_split_shows_its_description() {
+        let descriptions = descriptions();
+        assert_eq!(
+            subtitles(Some(0), &descriptions),
+            ["door closes behind you", "", ""]
+        );
+        // Not running
+        assert_eq!(subtitles(None, &descriptions), ["", "", ""]);
+        // Segments added since the descriptions were read have none
+        assert_eq!(split_on_subtitle(Some(5), 5, &descriptions), "");
+    }
+
+    #[test]
+    fn the_description_follows_splits_undos_and_skips() {
+        let descriptions = descriptions();
+        // Split from the first segment: the row without one is cleared too
+        assert_eq!(subtitles(Some(1), &descriptions), ["", "", ""]);
+        // Skip to the last
+        assert_eq!(subtitles(Some(2), &descriptions), ["", "", "boss dies"]);
+        // Undo twice
+        assert_eq!(subtitles(Some(1), &descriptions), ["", "", ""]);
+        assert_eq!(
+            subtitles(Some(0), &descriptions),
+            ["door closes behind you", "", ""]
+        );
+        // Finished
+        assert_eq!(subtitles(None, &descriptions), ["", "", ""]);
+    }
+}
+
+#[cfg(test)]
+mod reconcile_rows_tests {
+    use super::*;
+
+    #[test]
+    fn same_segment_count_reuses_rows() {
+        assert_eq!(reconcile_rows(5, 5), RowReconciliation::Reuse);
+    }
+
+    #[test]
+    fn fewer_or_more_segments_rebuild() {
+        assert_eq!(reconcile_rows(5, 3), RowReconciliation::Rebuild);
+        assert_eq!(reconcile_rows(3, 5), RowReconciliation::Rebuild);
+    }
+
+    #[test]
+    fn nothing_to_reuse_when_no_rows_exist() {
+        assert_eq!(reconcile_rows(0, 0), RowReconciliation::Rebuild);
+        assert_eq!(reconcile_rows(0, 4), RowReconciliation::Rebuild);
+    }
 }
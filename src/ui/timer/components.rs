@@ -0,0 +1,289 @@
+//! Rows of the current-split info area in the footer.
+//!
+//! Each row kind maps to a provider that computes what to show for the selected
+//! segment, so the widgets only have to lay the rows out.
+
+use crate::config::Config;
+use crate::formatters::label::format_label;
+
+use livesplit_core::Timer;
+use tracing::warn;
+
+/// At most this many rows fit the info area.
+pub const MAX_INFO_ROWS: usize = 4;
+
+/// Rows shown when the config does not list any.
+pub const DEFAULT_INFO_ROWS: [&str; 2] = ["best_segment", "comparison"];
+
+const AVERAGE_COMPARISON: &str = "Average Segments";
+const PERSONAL_BEST_COMPARISON: &str = "Personal Best";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfoRowKind {
+    /// Best segment time of the selected segment
+    BestSegment,
+    /// Segment time of the current comparison
+    Comparison,
+    /// Segment time of the personal best
+    PersonalBest,
+    /// Average segment time over the attempt history
+    Average,
+    /// Sum of the best segments from the selected one to the end
+    SumOfBestRemaining,
+}
+
+impl InfoRowKind {
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "best_segment" => Some(Self::BestSegment),
+            "comparison" => Some(Self::Comparison),
+            "personal_best" => Some(Self::PersonalBest),
+            "average" => Some(Self::Average),
+            "sum_of_best_remaining" => Some(Self::SumOfBestRemaining),
+            _ => None,
+        }
+    }
+
+    /// Parses the configured row ids, warning about (and skipping) unknown ones and
+    /// anything past `MAX_INFO_ROWS`. An empty list falls back to `DEFAULT_INFO_ROWS`.
+    pub fn parse_list(ids: &[String]) -> Vec<Self> {
+        if ids.is_empty() {
+            return DEFAULT_INFO_ROWS
+                .iter()
+                .filter_map(|id| Self::from_id(id))
+                .collect();
+        }
+
+        let mut kinds: Vec<Self> = ids
+            .iter()
+            .filter_map(|id| {
+                let kind = Self::from_id(id);
+                if kind.is_none() {
+                    warn!("Unknown info row \"{id}\" in display.info-rows, skipping it");
+                }
+                kind
+            })
+            .collect();
+        if kinds.len() > MAX_INFO_ROWS {
+            warn!("display.info-rows lists more than {MAX_INFO_ROWS} rows, ignoring the rest");
+            kinds.truncate(MAX_INFO_ROWS);
+        }
+        kinds
+    }
+
+    pub fn provide(self, timer: &Timer, config: &Config, index: usize) -> InfoRowValue {
+        match self {
+            Self::BestSegment => best_segment(timer, config, index),
+            Self::Comparison => comparison(timer, config, index),
+            Self::PersonalBest => personal_best(timer, config, index),
+            Self::Average => average(timer, config, index),
+            Self::SumOfBestRemaining => sum_of_best_remaining(timer, config, index),
+        }
+    }
+}
+
+/// What an info row shows. `classes` are added to the value label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfoRowValue {
+    pub label: String,
+    pub value: String,
+    pub classes: Vec<&'static str>,
+}
+
+impl InfoRowValue {
+    fn new(label: impl Into<String>, value: String, available: bool) -> Self {
+        Self {
+            label: label.into(),
+            value,
+            classes: if available { vec![] } else { vec!["dim-label"] },
+        }
+    }
+}
+
+pub fn best_segment(timer: &Timer, config: &Config, index: usize) -> InfoRowValue {
+    let method = timer.current_timing_method();
+    let best = timer.run().segment(index).best_segment_time();
+    InfoRowValue::new(
+        "Best",
+        config.format.comparison.format_split_time(&best, method),
+        best[method].is_some(),
+    )
+}
+
+pub fn comparison(timer: &Timer, config: &Config, index: usize) -> InfoRowValue {
+    comparison_segment_row(
+        timer,
+        config,
+        index,
+        timer.current_comparison(),
+        format_label(timer.current_comparison()),
+    )
+}
+
+pub fn personal_best(timer: &Timer, config: &Config, index: usize) -> InfoRowValue {
+    comparison_segment_row(timer, config, index, PERSONAL_BEST_COMPARISON, "PB")
+}
+
+pub fn average(timer: &Timer, config: &Config, index: usize) -> InfoRowValue {
+    comparison_segment_row(timer, config, index, AVERAGE_COMPARISON, "Average")
+}
+
+pub fn sum_of_best_remaining(timer: &Timer, config: &Config, index: usize) -> InfoRowValue {
+    let method = timer.current_timing_method();
+    let sum = timer.run().segments()[index..]
+        .iter()
+        .try_fold(time::Duration::ZERO, |sum, segment| {
+            Some(sum + segment.best_segment_time()[method]?.to_duration())
+        });
+    InfoRowValue::new(
+        "SoB Remaining",
+        config
+            .format
+            .comparison
+            .format_duration(&sum.unwrap_or_default()),
+        sum.is_some(),
+    )
+}
+
+/// Segment time of `comparison` at `index`: its split time minus the previous one.
+fn comparison_segment_row(
+    timer: &Timer,
+    config: &Config,
+    index: usize,
+    comparison: &str,
+    label: impl Into<String>,
+) -> InfoRowValue {
+    let method = timer.current_timing_method();
+    let segments = timer.run().segments();
+
+    let split = segments[index].comparison_timing_method(comparison, method);
+    let previous = if index > 0 {
+        segments[index - 1]
+            .comparison_timing_method(comparison, method)
+            .unwrap_or_default()
+            .to_duration()
+    } else {
+        time::Duration::ZERO
+    };
+    let segment_time = split
+        .unwrap_or_default()
+        .to_duration()
+        .checked_sub(previous)
+        .unwrap_or_default()
+        .abs();
+
+    InfoRowValue::new(
+        label,
+        config.format.comparison.format_duration(&segment_time),
+        split.is_some(),
+    )
+}
+
+#[cfg(test)]
+mod components_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{Run, Segment};
+
+    // Three segments: PB splits 10/25/-, golds 9/14/-. Creating the timer fixes the
+    // run, deriving golds from PB segments, so the last one has no PB split.
+    fn timer() -> Timer {
+        let mut run = Run::new();
+        for (name, pb, gold) in [
+            ("A", Some(10.0), Some(9.0)),
+            ("B", Some(25.0), Some(14.0)),
+            ("C", None, None),
+        ] {
+            let mut segment = Segment::new(name);
+            if let Some(pb) = pb {
+                segment.set_personal_best_split_time(real(pb));
+            }
+            if let Some(gold) = gold {
+                segment.set_best_segment_time(real(gold));
+            }
+            run.push_segment(segment);
+        }
+        Timer::new(run).unwrap()
+    }
+
+    #[test]
+    fn unknown_ids_are_skipped_and_the_list_is_capped() {
+        let ids: Vec<String> = ["best_segment", "median", "average", "personal_best"]
+            .into_iter()
+            .chain(["comparison", "sum_of_best_remaining"])
+            .map(str::to_owned)
+            .collect();
+        assert_eq!(
+            InfoRowKind::parse_list(&ids),
+            vec![
+                InfoRowKind::BestSegment,
+                InfoRowKind::Average,
+                InfoRowKind::PersonalBest,
+                InfoRowKind::Comparison,
+            ]
+        );
+        assert_eq!(
+            InfoRowKind::parse_list(&[]),
+            vec![InfoRowKind::BestSegment, InfoRowKind::Comparison]
+        );
+    }
+
+    #[test]
+    fn segment_rows_use_the_difference_of_split_times() {
+        let timer = timer();
+        let config = Config::default();
+
+        let pb = personal_best(&timer, &config, 1);
+        assert_eq!(pb.label, "PB");
+        assert_eq!(
+            pb.value,
+            config
+                .format
+                .comparison
+                .format_duration(&time::Duration::seconds(15))
+        );
+        assert!(pb.classes.is_empty());
+
+        // The current comparison defaults to the personal best
+        assert_eq!(comparison(&timer, &config, 1).value, pb.value);
+    }
+
+    #[test]
+    fn best_segment_and_missing_values_are_dimmed() {
+        let timer = timer();
+        let config = Config::default();
+
+        let best = best_segment(&timer, &config, 0);
+        assert_eq!(best.label, "Best");
+        assert!(best.classes.is_empty());
+        assert_eq!(best_segment(&timer, &config, 2).classes, vec!["dim-label"]);
+    }
+
+    #[test]
+    fn sum_of_best_remaining_needs_every_remaining_gold() {
+        let timer = timer();
+        let config = Config::default();
+
+        assert_eq!(
+            sum_of_best_remaining(&timer, &config, 2).classes,
+            vec!["dim-label"]
+        );
+        assert_eq!(
+            sum_of_best_remaining(&timer, &config, 0).classes,
+            vec!["dim-label"]
+        );
+
+        let mut run = timer.run().clone();
+        run.segment_mut(2).set_best_segment_time(real(20.0));
+        let timer = Timer::new(run).unwrap();
+        let sob = sum_of_best_remaining(&timer, &config, 1);
+        assert!(sob.classes.is_empty());
+        assert_eq!(
+            sob.value,
+            config
+                .format
+                .comparison
+                .format_duration(&time::Duration::seconds(34))
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! Tooltip of the big timer: a small graph of the cumulative delta of the
+//! current attempt at each completed split, to tell time bled steadily from time
+//! lost in one segment. The graph is rendered into a texture when the tooltip is
+//! first shown and kept until a split, skip, undo or reset changes the series.
+
+use gtk4::prelude::*;
+use gtk4::{Box as GtkBox, Label, Orientation::Vertical, Picture, cairo, gdk};
+
+use livesplit_core::Timer;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::utils::sparkline::{current_delta_series, splits_done};
+
+pub const WIDTH: i32 = 160;
+pub const HEIGHT: i32 = 48;
+const PADDING: f64 = 4.0;
+const POINT_RADIUS: f64 = 2.0;
+
+#[derive(Default)]
+struct GraphCache {
+    series: Vec<Option<time::Duration>>,
+    texture: Option<gdk::Texture>,
+}
+
+/// The delta graph tooltip of one widget.
+#[derive(Clone, Default)]
+pub struct DeltaGraphTooltip {
+    cache: Rc<RefCell<GraphCache>>,
+}
+
+impl DeltaGraphTooltip {
+    /// Shows the tooltip on `widget`.
+    pub fn attach(&self, widget: &impl IsA<gtk4::Widget>) {
+        widget.set_has_tooltip(true);
+        let cache = self.cache.clone();
+        widget.connect_query_tooltip(move |widget, _, _, _, tooltip| {
+            let mut cache = cache.borrow_mut();
+            if cache.series.iter().all(Option::is_none) {
+                tooltip.set_text(Some("No splits yet in this attempt"));
+                return true;
+            }
+            if cache.texture.is_none() {
+                let color = widget.color();
+                let rgba = (
+                    f64::from(color.red()),
+                    f64::from(color.green()),
+                    f64::from(color.blue()),
+                    f64::from(color.alpha()),
+                );
+                cache.texture = render(&cache.series, rgba).and_then(texture);
+            }
+            let Some(texture) = &cache.texture else {
+                return false;
+            };
+            let content = GtkBox::new(Vertical, 4);
+            content.append(&Picture::for_paintable(texture));
+            content.append(&Label::new(Some("Delta at each split")));
+            tooltip.set_custom(Some(&content));
+            true
+        });
+    }
+
+    /// Takes the series of the current attempt, dropping the rendered graph when
+    /// it changed.
+    pub fn update(&self, timer: &Timer) {
+        let segments = timer.run().segments();
+        let done = splits_done(
+            timer.current_phase(),
+            timer.current_split_index(),
+            segments.len(),
+        );
+        let series = current_delta_series(
+            segments,
+            done,
+            timer.current_comparison(),
+            timer.current_timing_method(),
+        );
+        let mut cache = self.cache.borrow_mut();
+        if cache.series != series {
+            cache.series = series;
+            cache.texture = None;
+        }
+    }
+}
+
+/// Draws `series` in `rgba` onto a `WIDTH` by `HEIGHT` surface: time lost
+/// upwards, with the zero line marked. Skipped splits break the line.
+pub fn render(
+    series: &[Option<time::Duration>],
+    (red, green, blue, alpha): (f64, f64, f64, f64),
+) -> Option<cairo::ImageSurface> {
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, WIDTH, HEIGHT).ok()?;
+    let cr = cairo::Context::new(&surface).ok()?;
+    let (width, height) = (f64::from(WIDTH), f64::from(HEIGHT));
+
+    let magnitude = series
+        .iter()
+        .flatten()
+        .map(|delta| delta.abs().as_seconds_f64())
+        .fold(1.0, f64::max);
+    let x_of = |index: usize| {
+        if series.len() <= 1 {
+            width / 2.0
+        } else {
+            PADDING + index as f64 * (width - 2.0 * PADDING) / (series.len() - 1) as f64
+        }
+    };
+    let y_of = |delta: time::Duration| {
+        height / 2.0 - delta.as_seconds_f64() / magnitude * (height / 2.0 - PADDING)
+    };
+
+    cr.set_source_rgba(red, green, blue, alpha * 0.4);
+    cr.set_line_width(1.0);
+    cr.set_dash(&[3.0, 2.0], 0.0);
+    cr.move_to(0.0, height / 2.0);
+    cr.line_to(width, height / 2.0);
+    cr.stroke().ok()?;
+
+    cr.set_source_rgba(red, green, blue, alpha);
+    cr.set_dash(&[], 0.0);
+    cr.set_line_width(1.5);
+    let points: Vec<(usize, time::Duration)> = series
+        .iter()
+        .enumerate()
+        .filter_map(|(index, delta)| Some((index, (*delta)?)))
+        .collect();
+    for stretch in points.chunk_by(|a, b| b.0 == a.0 + 1) {
+        if let [(index, delta)] = stretch {
+            cr.arc(
+                x_of(*index),
+                y_of(*delta),
+                POINT_RADIUS,
+                0.0,
+                std::f64::consts::TAU,
+            );
+            cr.fill().ok()?;
+        } else {
+            for (index, delta) in stretch {
+                cr.line_to(x_of(*index), y_of(*delta));
+            }
+            cr.stroke().ok()?;
+        }
+    }
+    drop(cr);
+    surface.flush();
+    Some(surface)
+}
+
+fn texture(mut surface: cairo::ImageSurface) -> Option<gdk::Texture> {
+    let stride = usize::try_from(surface.stride()).ok()?;
+    let bytes = glib::Bytes::from(&*surface.data().ok()?);
+    // Cairo's ARGB32 is premultiplied BGRA in memory on little endian systems
+    let format = if cfg!(target_endian = "little") {
+        gdk::MemoryFormat::B8g8r8a8Premultiplied
+    } else {
+        gdk::MemoryFormat::A8r8g8b8Premultiplied
+    };
+    Some(gdk::MemoryTexture::new(WIDTH, HEIGHT, format, &bytes, stride).upcast())
+}
+
+#[cfg(test)]
+mod delta_graph_tests {
+    use super::*;
+
+    #[test]
+    fn renders_any_number_of_points() {
+        let white = (1.0, 1.0, 1.0, 1.0);
+        let hundred: Vec<Option<time::Duration>> = (0..100)
+            .map(|index| (index % 7 != 3).then(|| time::Duration::milliseconds(index * 37 - 900)))
+            .collect();
+        for series in [vec![], vec![Some(time::Duration::seconds(-2))], hundred] {
+            let surface = render(&series, white).expect("surface");
+            assert_eq!((surface.width(), surface.height()), (WIDTH, HEIGHT));
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Styling of a finished run.
+//!
+//! Once the last split is done the big timer gets `finished`, the final split row
+//! `final-time`, and a new personal best briefly puts `new-pb` on the whole timer
+//! for themes to animate. Undoing the last split or resetting drops them again.
+
+use livesplit_core::{TimeSpan, TimerPhase};
+
+use std::time::{Duration, Instant};
+
+pub const FINAL_TIME_CLASS: &str = "final-time";
+pub const NEW_PB_CLASS: &str = "new-pb";
+/// Set on the big timer once the attempt can no longer beat the PB.
+pub const PB_DEAD_CLASS: &str = "pb-dead";
+
+/// How long `new-pb` stays on the timer after a personal best.
+pub const NEW_PB_DURATION: Duration = Duration::from_secs(5);
+
+/// Classes of the big timer in `phase`.
+pub fn timer_classes(phase: TimerPhase) -> &'static [&'static str] {
+    match phase {
+        TimerPhase::Running => &["timer", "active-timer"],
+        TimerPhase::Ended => &["timer", "inactive-timer", "finished"],
+        _ => &["timer", "inactive-timer"],
+    }
+}
+
+/// Whether the row at `index` is the final split of a finished run.
+pub fn is_final_row(phase: TimerPhase, index: usize, segment_count: usize) -> bool {
+    phase == TimerPhase::Ended && index + 1 == segment_count
+}
+
+/// Whether a finished run's final time beats the personal best. A run without a
+/// personal best always does.
+pub fn beats_personal_best(final_time: Option<TimeSpan>, personal_best: Option<TimeSpan>) -> bool {
+    match (final_time, personal_best) {
+        (Some(time), Some(pb)) => time < pb,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+/// Tracks whether the timer should carry `new-pb`, which is only set when the
+/// run enters the Ended phase.
+#[derive(Debug)]
+pub struct NewPbState {
+    last_phase: TimerPhase,
+    since: Option<Instant>,
+}
+
+impl NewPbState {
+    pub fn new(phase: TimerPhase) -> Self {
+        Self {
+            last_phase: phase,
+            since: None,
+        }
+    }
+
+    /// Follows the timer to `phase` and tells whether `new-pb` applies at `now`.
+    /// `is_pb` is only asked on the transition into Ended.
+    pub fn update(
+        &mut self,
+        phase: TimerPhase,
+        is_pb: impl FnOnce() -> bool,
+        now: Instant,
+    ) -> bool {
+        let entered_ended = phase == TimerPhase::Ended && self.last_phase != TimerPhase::Ended;
+        self.last_phase = phase;
+
+        if phase != TimerPhase::Ended {
+            self.since = None;
+        } else if entered_ended && is_pb() {
+            self.since = Some(now);
+        }
+
+        if self
+            .since
+            .is_some_and(|since| now.duration_since(since) >= NEW_PB_DURATION)
+        {
+            self.since = None;
+        }
+        self.since.is_some()
+    }
+}
+
+#[cfg(test)]
+mod finish_tests {
+    use super::*;
+
+    #[test]
+    fn only_the_ended_timer_is_finished() {
+        assert!(timer_classes(TimerPhase::Ended).contains(&"finished"));
+        for phase in [
+            TimerPhase::NotRunning,
+            TimerPhase::Running,
+            TimerPhase::Paused,
+        ] {
+            assert!(!timer_classes(phase).contains(&"finished"));
+        }
+        assert!(timer_classes(TimerPhase::Running).contains(&"active-timer"));
+    }
+
+    #[test]
+    fn final_row_is_the_last_segment_of_an_ended_run() {
+        assert!(is_final_row(TimerPhase::Ended, 2, 3));
+        assert!(!is_final_row(TimerPhase::Ended, 1, 3));
+        // Undoing the last split goes back to Running
+        assert!(!is_final_row(TimerPhase::Running, 2, 3));
+        assert!(!is_final_row(TimerPhase::NotRunning, 2, 3));
+    }
+
+    #[test]
+    fn personal_best_needs_a_faster_final_time() {
+        let s = |seconds| Some(TimeSpan::from_seconds(seconds));
+        assert!(beats_personal_best(s(99.0), s(100.0)));
+        assert!(!beats_personal_best(s(100.0), s(100.0)));
+        assert!(beats_personal_best(s(120.0), None));
+        assert!(!beats_personal_best(None, s(100.0)));
+    }
+
+    #[test]
+    fn new_pb_is_set_on_ending_and_cleared_by_undo_reset_and_time() {
+        let start = Instant::now();
+        let mut state = NewPbState::new(TimerPhase::NotRunning);
+
+        assert!(!state.update(TimerPhase::Running, || true, start));
+        assert!(state.update(TimerPhase::Ended, || true, start));
+        assert!(state.update(TimerPhase::Ended, || false, start + Duration::from_secs(1)));
+
+        // Undoing the final split
+        assert!(!state.update(TimerPhase::Running, || true, start));
+        assert!(state.update(TimerPhase::Ended, || true, start));
+        assert!(!state.update(TimerPhase::Ended, || true, start + NEW_PB_DURATION));
+
+        // Resetting after the class expired, then finishing slower than the PB
+        assert!(!state.update(TimerPhase::NotRunning, || true, start));
+        assert!(!state.update(TimerPhase::Running, || true, start));
+        assert!(!state.update(TimerPhase::Ended, || false, start));
+    }
+
+    #[test]
+    fn reset_clears_new_pb_right_away() {
+        let start = Instant::now();
+        let mut state = NewPbState::new(TimerPhase::Running);
+        assert!(state.update(TimerPhase::Ended, || true, start));
+        assert!(!state.update(TimerPhase::NotRunning, || true, start));
+    }
+}
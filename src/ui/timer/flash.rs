@@ -0,0 +1,186 @@
+//! Split feedback flash.
+//!
+//! Every tick of the timer views looks for a split, undo or skip that landed
+//! since the last one and puts `flash` on the affected row and the big timer
+//! for a moment. Ticks come more often than refreshes, so the acknowledgment
+//! shows up even when redrawing the splits is slow.
+
+use crate::context::TuxSplitContext;
+use crate::safe_mode;
+use crate::ui::timer::body::TimerBody;
+use crate::utils::flash::{FLASH_DURATION, FlashCoalescer, FlashEvent, SplitState, detect_event};
+
+use adw::ActionRow;
+use gtk4::Box as GtkBox;
+use gtk4::prelude::*;
+
+use livesplit_core::Timer;
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::time::{Duration, Instant};
+
+const FLASH_CLASS: &str = "flash";
+
+/// Classes a flash may leave on a widget.
+const FLASH_CLASSES: [&str; 4] = [
+    FLASH_CLASS,
+    FlashEvent::Split.css_class(),
+    FlashEvent::Undo.css_class(),
+    FlashEvent::Skip.css_class(),
+];
+
+/// Widgets of one timer that flash.
+struct FlashTarget {
+    body: Weak<RefCell<TimerBody>>,
+    big_timer: glib::WeakRef<GtkBox>,
+    flashed_row: glib::WeakRef<ActionRow>,
+}
+
+/// Every timer on screen flashes together, so they share one coalescer.
+#[derive(Default)]
+struct FlashTargets {
+    targets: Vec<FlashTarget>,
+    coalescer: FlashCoalescer,
+    /// Split state of the timer at the last tick.
+    last: Option<SplitState>,
+}
+
+thread_local! {
+    static TARGETS: RefCell<Option<FlashTargets>> = const { RefCell::new(None) };
+}
+
+/// Adds the widgets of a timer to the flashed ones.
+pub fn install(body: &Rc<RefCell<TimerBody>>, big_timer: &GtkBox) {
+    TARGETS.with(|targets| {
+        targets
+            .borrow_mut()
+            .get_or_insert_with(FlashTargets::default)
+            .targets
+            .push(FlashTarget {
+                body: Rc::downgrade(body),
+                big_timer: big_timer.downgrade(),
+                flashed_row: glib::WeakRef::new(),
+            });
+    });
+}
+
+fn split_state(timer: &Timer) -> SplitState {
+    SplitState {
+        phase: timer.current_phase(),
+        index: timer.current_split_index(),
+    }
+}
+
+/// Flashes the split, undo or skip that landed on `timer` since the last tick,
+/// once timers that flash are on screen.
+pub fn update(timer: &Timer) {
+    let state = split_state(timer);
+    let event = TARGETS.with(|targets| {
+        let mut targets = targets.borrow_mut();
+        let last = targets.as_mut()?.last.replace(state)?;
+        if last == state {
+            return None;
+        }
+        let method = timer.current_timing_method();
+        detect_event(last, state, |index| {
+            timer
+                .run()
+                .segments()
+                .get(index)
+                .is_some_and(|segment| segment.split_time()[method].is_some())
+        })
+    });
+    if let Some((event, row)) = event {
+        flash(event, row);
+    }
+}
+
+/// Flashes `row` and the big timer, or moves a running flash there.
+/// Skipped when animations are off.
+fn flash(event: FlashEvent, row: usize) {
+    if !TuxSplitContext::get_instance().animations_enabled(safe_mode::current()) {
+        return;
+    }
+    TARGETS.with(|targets| {
+        let mut targets = targets.borrow_mut();
+        let Some(targets) = targets.as_mut() else {
+            return;
+        };
+
+        // Closed viewers leave dead targets behind
+        targets
+            .targets
+            .retain(|target| target.body.strong_count() > 0);
+        for target in &targets.targets {
+            target.flash(event, row);
+        }
+
+        if targets.coalescer.trigger(Instant::now()) {
+            schedule_end(FLASH_DURATION);
+        }
+    });
+}
+
+impl FlashTarget {
+    fn flash(&self, event: FlashEvent, row: usize) {
+        // Only one row flashes at a time
+        if let Some(previous) = self.flashed_row.upgrade() {
+            clear(&previous);
+        }
+        let widget = self
+            .body
+            .upgrade()
+            .and_then(|body| body.try_borrow().ok()?.row_widget(row));
+        if let Some(widget) = &widget {
+            set(widget, event);
+        }
+        self.flashed_row.set(widget.as_ref());
+
+        if let Some(big_timer) = self.big_timer.upgrade() {
+            clear(&big_timer);
+            set(&big_timer, event);
+        }
+    }
+
+    fn end(&self) {
+        if let Some(row) = self.flashed_row.upgrade() {
+            clear(&row);
+        }
+        self.flashed_row.set(None);
+        if let Some(big_timer) = self.big_timer.upgrade() {
+            clear(&big_timer);
+        }
+    }
+}
+
+fn schedule_end(after: Duration) {
+    glib::timeout_add_local_once(after, || {
+        TARGETS.with(|targets| {
+            let mut targets = targets.borrow_mut();
+            let Some(targets) = targets.as_mut() else {
+                return;
+            };
+            if let Some(remaining) = targets.coalescer.poll(Instant::now()) {
+                schedule_end(remaining);
+                return;
+            }
+            for target in &targets.targets {
+                target.end();
+            }
+        });
+    });
+}
+
+fn set(widget: &impl IsA<gtk4::Widget>, event: FlashEvent) {
+    widget.add_css_class(FLASH_CLASS);
+    widget.add_css_class(event.css_class());
+}
+
+/// Drops every flash class from `widget`. Rows reused for another segment must
+/// go through this too, so a flash never outlives its row.
+pub fn clear(widget: &impl IsA<gtk4::Widget>) {
+    for class in FLASH_CLASSES {
+        widget.remove_css_class(class);
+    }
+}
@@ -1,15 +1,22 @@
 use crate::config::Config;
-use crate::formatters::label::format_label;
+use crate::formatters::time::{DIGIT_GROUP_CLASSES, TimeFormat};
 use crate::ui::info::{
     ALL_ADDITIONAL_INFOS, AdditionalInfo, AdditionalInfoKind, BestPossibleTimeInfo,
-    CurrentPaceInfo, PbChanceInfo, PossibleTimeSaveInfo, PrevSegmentBestInfo, PrevSegmentDiffInfo,
-    TotalPlaytimeInfo,
+    CurrentPaceInfo, FinishEtaInfo, GoalInfo, LoadTimeInfo, PbChanceInfo, PbPositionInfo,
+    PossibleTimeSaveInfo, PrevSegmentBestInfo, PrevSegmentDiffInfo, TotalPlaytimeInfo,
 };
+use crate::ui::timer::components::InfoRowKind;
+use crate::ui::timer::delta_graph::DeltaGraphTooltip;
+use crate::ui::timer::finish::timer_classes;
+use crate::ui::timer::focus_revealer;
+use crate::utils::comparisons::{current_segment_times, format_signed, timer_delta};
+use crate::utils::focus::{FocusMode, TimerComponent};
 
 use glib;
 use gtk4::prelude::{BoxExt as _, WidgetExt as _, *};
 use gtk4::{
-    Align, Box as GtkBox, CenterBox, Label, ListBox, Orientation::Horizontal, Orientation::Vertical,
+    Align, Box as GtkBox, CenterBox, Label, ListBox, Orientation::Horizontal,
+    Orientation::Vertical, Revealer,
 };
 
 use livesplit_core::{Timer, TimerPhase};
@@ -18,8 +25,13 @@ pub struct TimerFooter {
     container: GtkBox,
     timer_container: CenterBox,
     segment_comparison: SegmentComparison,
+    timers: GtkBox,
     running_timer: RunningTimer,
+    segment_timer: SegmentTimer,
+    focus_line: FocusLine,
     additional_info: AdditionalInfoFooter,
+    /// The parts focus mode hides or shows, see `utils::focus`.
+    revealers: Vec<(TimerComponent, Revealer)>,
 }
 
 impl TimerFooter {
@@ -33,7 +45,7 @@ impl TimerFooter {
             .orientation(Vertical)
             .halign(Align::Fill)
             .hexpand(true)
-            .spacing(12)
+            .spacing(super::FOOTER_SPACING)
             .build();
 
         let timer_container = CenterBox::builder()
@@ -44,39 +56,93 @@ impl TimerFooter {
         let segment_comparison =
             SegmentComparison::new(timer, config, primary_list, last_segment_list);
         let running_timer = RunningTimer::new(timer, config);
+        let segment_timer = SegmentTimer::new(timer, config);
 
-        timer_container.set_start_widget(Some(segment_comparison.container()));
-        timer_container.set_end_widget(Some(running_timer.container()));
+        let timers = GtkBox::builder()
+            .orientation(Vertical)
+            .valign(Align::Center)
+            .build();
+        let focus_line = FocusLine::new();
+        let revealers = vec![
+            (
+                TimerComponent::InfoRows,
+                focus_revealer(segment_comparison.container()),
+            ),
+            (
+                TimerComponent::SegmentTimer,
+                focus_revealer(segment_timer.container()),
+            ),
+            (
+                TimerComponent::FocusLine,
+                focus_revealer(focus_line.container()),
+            ),
+        ];
+        timers.append(running_timer.container());
+        timers.append(&revealers[1].1);
+        timers.append(&revealers[2].1);
+
+        timer_container.set_start_widget(Some(&revealers[0].1));
+        timer_container.set_end_widget(Some(&timers));
 
         let additional_info = AdditionalInfoFooter::new(timer, config);
 
-        let separator = gtk4::Separator::builder().build();
+        let rest = GtkBox::builder().orientation(Vertical).spacing(12).build();
+        rest.append(&gtk4::Separator::builder().build());
+        rest.append(&additional_info.container());
+        let mut revealers = revealers;
+        revealers.push((TimerComponent::AdditionalInfo, focus_revealer(&rest)));
         container.append(&timer_container);
-        container.append(&separator);
-        container.append(&additional_info.container());
+        container.append(&revealers[3].1);
 
         Self {
             container,
             timer_container,
             segment_comparison,
+            timers,
             running_timer,
+            segment_timer,
+            focus_line,
             additional_info,
+            revealers,
         }
     }
 
+    /// The parts of the footer focus mode hides or shows.
+    pub fn focus_revealers(&self) -> &[(TimerComponent, Revealer)] {
+        &self.revealers
+    }
+
     pub fn container(&self) -> &GtkBox {
         &self.container
     }
 
-    pub fn refresh(&mut self, timer: &Timer, config: &Config) {
-        self.segment_comparison.update(timer, config);
-        self.running_timer.update(timer, config);
-        self.additional_info.update(timer, config);
+    /// The running timer. Its children get their classes from the timer phase on
+    /// every refresh, so transient classes go on this wrapper.
+    pub fn big_timer(&self) -> &GtkBox {
+        self.running_timer.container()
+    }
+
+    /// Refreshes the parts on screen in `focus`.
+    pub fn refresh(&mut self, timer: &Timer, config: &Config, focus: FocusMode) {
+        focus.refresh(TimerComponent::InfoRows, || {
+            self.segment_comparison.update(timer, config);
+        });
+        focus.refresh(TimerComponent::BigTimer, || {
+            self.running_timer.update(timer, config);
+        });
+        focus.refresh(TimerComponent::SegmentTimer, || {
+            self.segment_timer.update(timer, config);
+        });
+        focus.refresh(TimerComponent::FocusLine, || {
+            self.focus_line.update(timer, config);
+        });
+        focus.refresh(TimerComponent::AdditionalInfo, || {
+            self.additional_info.update(timer, config);
+        });
 
         self.timer_container
-            .set_start_widget(Some(self.segment_comparison.container()));
-        self.timer_container
-            .set_end_widget(Some(self.running_timer.container()));
+            .set_start_widget(Some(&self.revealers[0].1));
+        self.timer_container.set_end_widget(Some(&self.timers));
     }
 }
 
@@ -94,6 +160,10 @@ impl AdditionalInfoFooter {
             Box::new(CurrentPaceInfo::new(timer, config)),
             Box::new(TotalPlaytimeInfo::new(timer, config)),
             Box::new(PbChanceInfo::new(timer, config)),
+            Box::new(FinishEtaInfo::new(timer, config)),
+            Box::new(LoadTimeInfo::new(timer, config)),
+            Box::new(PbPositionInfo::new(timer, config)),
+            Box::new(GoalInfo::new(timer, config)),
         ];
 
         // Initialize visibility based on config at creation time.
@@ -114,8 +184,12 @@ impl AdditionalInfoFooter {
                 AdditionalInfoKind::CurrentPace => vis_cfg.show_current_pace,
                 AdditionalInfoKind::TotalPlaytime => vis_cfg.show_total_playtime,
                 AdditionalInfoKind::PbChance => vis_cfg.show_pb_chance,
+                AdditionalInfoKind::FinishEta => config.display.show_eta,
+                AdditionalInfoKind::LoadTime => config.display.show_load_time,
+                AdditionalInfoKind::PbPosition => config.display.show_pb_position,
+                AdditionalInfoKind::Goal => vis_cfg.show_goal,
             };
-            info.container().set_visible(visible);
+            info.container().set_visible(visible && info.has_value());
         }
     }
 
@@ -135,16 +209,25 @@ impl AdditionalInfoFooter {
     }
 }
 
-/// Left pane in the footer:
+/// Left pane in the footer: the info rows configured in `display.info-rows` for
+/// the selected segment, by default
 /// - Best: <best split value>
 /// - <Comparison Label>: <per-segment comparison value>
+///
+/// More than two rows are laid out in two columns to keep the footer narrow.
 pub struct SegmentComparison {
     wrapper: GtkBox,
     primary_list_ref: glib::WeakRef<ListBox>, // Weak ref to main segments list
     last_list_ref: glib::WeakRef<ListBox>,    // Weak ref to last-segment list
-    best_value: Label,
-    comparison_label: Label,
-    comparison_value: Label,
+    row_ids: Vec<String>,
+    rows: Vec<InfoRow>,
+}
+
+struct InfoRow {
+    kind: InfoRowKind,
+    label: Label,
+    value: Label,
+    classes: Vec<&'static str>,
 }
 
 impl SegmentComparison {
@@ -154,30 +237,18 @@ impl SegmentComparison {
         primary_list: &ListBox,
         last_list: &ListBox,
     ) -> Self {
-        let build = GtkBox::builder().orientation(Vertical).build();
-        let wrapper = build;
-
-        let vbox = GtkBox::builder().orientation(Vertical).build();
-
-        let (best_box, best_value) = SegmentComparison::build_best();
-
-        let (comparison_box, comparison_label, comparison_value) =
-            SegmentComparison::build_comparison();
-
-        vbox.append(&best_box);
-        vbox.append(&comparison_box);
-        wrapper.append(&vbox);
+        let wrapper = GtkBox::builder().orientation(Vertical).build();
 
         let mut this = Self {
             wrapper,
             primary_list_ref: glib::WeakRef::new(),
             last_list_ref: glib::WeakRef::new(),
-            best_value,
-            comparison_label,
-            comparison_value,
+            row_ids: Vec::new(),
+            rows: Vec::new(),
         };
         this.primary_list_ref.set(Some(primary_list));
         this.last_list_ref.set(Some(last_list));
+        this.rebuild_rows(config);
         this.rebuild(timer, config);
         this
     }
@@ -186,9 +257,46 @@ impl SegmentComparison {
     }
 
     pub fn update(&mut self, timer: &Timer, config: &Config) {
+        if self.row_ids != config.display.info_rows {
+            self.rebuild_rows(config);
+        }
         self.rebuild(timer, config);
     }
 
+    // Recreates the row widgets from the configured row list
+    fn rebuild_rows(&mut self, config: &Config) {
+        while let Some(child) = self.wrapper.first_child() {
+            self.wrapper.remove(&child);
+        }
+
+        self.row_ids.clone_from(&config.display.info_rows);
+        self.rows = InfoRowKind::parse_list(&self.row_ids)
+            .into_iter()
+            .map(SegmentComparison::build_row)
+            .collect();
+
+        if self.rows.len() > 2 {
+            let grid = gtk4::Grid::builder()
+                .column_spacing(12)
+                .margin_top(6)
+                .build();
+            for (i, row) in self.rows.iter().enumerate() {
+                let row_box = row.label.parent().expect("row box");
+                grid.attach(&row_box, (i % 2) as i32, (i / 2) as i32, 1, 1);
+            }
+            self.wrapper.append(&grid);
+        } else {
+            let vbox = GtkBox::builder()
+                .orientation(Vertical)
+                .margin_top(6)
+                .build();
+            for row in &self.rows {
+                vbox.append(&row.label.parent().expect("row box"));
+            }
+            self.wrapper.append(&vbox);
+        }
+    }
+
     fn rebuild(&mut self, timer: &Timer, config: &Config) {
         // Compute which segment to display
         let segments = timer.run().segments();
@@ -210,96 +318,132 @@ impl SegmentComparison {
         }
         .min(segments.len().saturating_sub(1));
 
-        let segment = &segments[selected_index];
-
-        // Previous segment's comparison time (under current timing method)
-        let previous_comparison_time = if selected_index > 0 {
-            segments[selected_index - 1]
-                .comparison_timing_method(timer.current_comparison(), timer.current_timing_method())
-                .unwrap_or_default()
-                .to_duration()
-        } else {
-            time::Duration::ZERO
-        };
-
-        // Build values
-        let best_value_text = config
-            .format
-            .comparison
-            .format_split_time(&segment.best_segment_time(), timer.current_timing_method());
-
-        let comparison_label_text = format!("{}:", format_label(timer.current_comparison()));
-
-        let comparison_value_text = {
-            let segment_comparison_time = segment
-                .comparison_timing_method(timer.current_comparison(), timer.current_timing_method())
-                .unwrap_or_default()
-                .to_duration();
-
-            let per_segment_time = segment_comparison_time
-                .checked_sub(previous_comparison_time)
-                .unwrap_or_default()
-                .abs();
-            config.format.comparison.format_duration(&per_segment_time)
-        };
-
         // Update stored labels in place
-        if self.best_value.label().as_str() != best_value_text {
-            self.best_value.set_label(&best_value_text);
-        }
-        if self.comparison_label.label().as_str() != comparison_label_text {
-            self.comparison_label.set_label(&comparison_label_text);
-        }
-        if self.comparison_value.label().as_str() != comparison_value_text {
-            self.comparison_value.set_label(&comparison_value_text);
+        for row in &mut self.rows {
+            let provided = row.kind.provide(timer, config, selected_index);
+
+            let label_text = format!("{}:", provided.label);
+            if row.label.label().as_str() != label_text {
+                row.label.set_label(&label_text);
+            }
+            if row.value.label().as_str() != provided.value {
+                row.value.set_label(&provided.value);
+            }
+            if row.classes != provided.classes {
+                for class in &row.classes {
+                    row.value.remove_css_class(class);
+                }
+                for class in &provided.classes {
+                    row.value.add_css_class(class);
+                }
+                row.classes = provided.classes;
+            }
         }
     }
 
-    fn build_comparison() -> (GtkBox, Label, Label) {
-        let comparison_box = GtkBox::builder()
+    fn build_row(kind: InfoRowKind) -> InfoRow {
+        let row_box = GtkBox::builder()
             .orientation(Horizontal)
             .spacing(2)
             .halign(Align::Start)
             .build();
 
-        let comparison_label = Label::builder().label("PB:").build();
-        comparison_label.add_css_class("caption-heading");
+        let label = Label::builder().label("--").build();
+        label.add_css_class("caption-heading");
 
-        let comparison_value = Label::builder().label("--").build();
-        comparison_value.add_css_class("caption");
-        comparison_value.add_css_class("timer");
+        let value = Label::builder().label("--").build();
+        value.add_css_class("caption");
+        value.add_css_class("timer");
 
-        comparison_box.append(&comparison_label);
-        comparison_box.append(&comparison_value);
-        (comparison_box, comparison_label, comparison_value)
+        row_box.append(&label);
+        row_box.append(&value);
+        InfoRow {
+            kind,
+            label,
+            value,
+            classes: Vec::new(),
+        }
     }
+}
 
-    fn build_best() -> (GtkBox, Label) {
-        let best_box = GtkBox::builder()
+/// The current split and its live delta against the current comparison, under
+/// the running timer in focus mode.
+pub struct FocusLine {
+    wrapper: GtkBox,
+    name: Label,
+    delta: Label,
+    class: &'static str,
+}
+
+impl FocusLine {
+    pub fn new() -> Self {
+        let wrapper = GtkBox::builder()
             .orientation(Horizontal)
-            .margin_top(6)
-            .spacing(2)
-            .halign(Align::Start)
+            .spacing(12)
+            .halign(Align::End)
+            .css_classes(["focus-line"])
             .build();
-        let best_label = Label::builder().label("Best:").build();
-        best_label.add_css_class("caption-heading");
+        let name = Label::builder()
+            .ellipsize(gtk4::pango::EllipsizeMode::End)
+            .max_width_chars(24)
+            .css_classes(["heading"])
+            .build();
+        let delta = Label::builder().css_classes(["timer"]).build();
+        wrapper.append(&name);
+        wrapper.append(&delta);
+        Self {
+            wrapper,
+            name,
+            delta,
+            class: "",
+        }
+    }
 
-        let best_value = Label::builder().label("--").build();
-        best_value.add_css_class("caption");
-        best_value.add_css_class("timer");
+    pub fn container(&self) -> &GtkBox {
+        &self.wrapper
+    }
+
+    pub fn update(&mut self, timer: &Timer, config: &Config) {
+        let segments = timer.run().segments();
+        let index = timer
+            .current_split_index()
+            .unwrap_or(0)
+            .min(segments.len().saturating_sub(1));
+        let name = segments.get(index).map_or("", |segment| segment.name());
+        if self.name.label().as_str() != name {
+            self.name.set_label(name);
+        }
 
-        best_box.append(&best_label);
-        best_box.append(&best_value);
-        (best_box, best_value)
+        let delta = timer_delta(timer, timer.current_comparison());
+        let text = delta.map_or_else(String::new, |delta| format_signed(delta, config));
+        if self.delta.label().as_str() != text {
+            self.delta.set_label(&text);
+        }
+        let class = match delta {
+            Some(delta) if delta.is_negative() => "greensplit",
+            Some(delta) if delta.is_positive() => "redsplit",
+            _ => "",
+        };
+        if class != self.class {
+            if !self.class.is_empty() {
+                self.delta.remove_css_class(self.class);
+            }
+            if !class.is_empty() {
+                self.delta.add_css_class(class);
+            }
+            self.class = class;
+        }
     }
 }
 
-/// Right pane in the footer: the running timer display.
+/// Right pane in the footer: the running timer display. Each group of digits
+/// (see `TimeFormat::digit_groups`) is its own label with the group's class, so
+/// themes can style hours, separators and the fraction apart.
 pub struct RunningTimer {
     wrapper: GtkBox,
     timer_box: GtkBox,
-    hms_label: Label,
-    ms_label: Label,
+    digits: Vec<Label>,
+    delta_graph: DeltaGraphTooltip,
 }
 
 impl RunningTimer {
@@ -310,36 +454,38 @@ impl RunningTimer {
             .build();
 
         let timer_box = GtkBox::new(Horizontal, 0);
-        timer_box.add_css_class("timer");
-        if timer.current_phase() == TimerPhase::Running {
-            timer_box.add_css_class("active-timer");
-        } else {
-            timer_box.add_css_class("inactive-timer");
-        }
-
-        let formatted = config.format.timer.format_timer(timer);
-        let (left, right) = if let Some((l, r)) = formatted.rsplit_once('.') {
-            (format!("{l}."), r.to_owned())
-        } else {
-            (formatted.clone(), String::new())
-        };
-
-        let hms_label = Label::builder().label(left).build();
-        hms_label.add_css_class("bigtimer");
-
-        let ms_label = Label::builder().label(right).margin_top(14).build();
-        ms_label.add_css_class("smalltimer");
-
-        timer_box.append(&hms_label);
-        timer_box.append(&ms_label);
+        timer_box.set_css_classes(timer_classes(timer.current_phase()));
+
+        let digits: Vec<Label> = DIGIT_GROUP_CLASSES
+            .iter()
+            .map(|class| {
+                let label = Label::builder().visible(false).build();
+                label.add_css_class(class);
+                // The fraction is drawn smaller, on the baseline of the seconds
+                if *class == "digits-frac" {
+                    label.add_css_class("smalltimer");
+                    label.set_margin_top(14);
+                } else {
+                    label.add_css_class("bigtimer");
+                }
+                timer_box.append(&label);
+                label
+            })
+            .collect();
         wrapper.append(&timer_box);
 
-        Self {
+        let delta_graph = DeltaGraphTooltip::default();
+        delta_graph.update(timer);
+        delta_graph.attach(&wrapper);
+
+        let mut this = Self {
             wrapper,
             timer_box,
-            hms_label,
-            ms_label,
-        }
+            digits,
+            delta_graph,
+        };
+        this.rebuild(timer, config);
+        this
     }
 
     pub fn container(&self) -> &GtkBox {
@@ -348,27 +494,81 @@ impl RunningTimer {
 
     pub fn update(&mut self, timer: &Timer, config: &Config) {
         self.rebuild(timer, config);
+        self.delta_graph.update(timer);
     }
 
     fn rebuild(&mut self, timer: &Timer, config: &Config) {
-        self.timer_box.set_css_classes(match timer.current_phase() {
-            TimerPhase::Running => &["timer", "active-timer"],
-            _ => &["timer", "inactive-timer"],
-        });
+        self.timer_box
+            .set_css_classes(timer_classes(timer.current_phase()));
 
-        // Update labels only if changed
-        let formatted = config.format.timer.format_timer(timer);
-        let (left, right) = if let Some((l, r)) = formatted.rsplit_once('.') {
-            (format!("{l}."), r.to_owned())
-        } else {
-            (formatted.clone(), String::new())
-        };
+        // Only the labels whose digits changed are touched, to keep relayouts rare
+        let groups = config
+            .format
+            .timer
+            .digit_groups(&TimeFormat::timer_duration(timer));
+        for (label, group) in self.digits.iter().zip(groups) {
+            if label.label().as_str() != group.text {
+                label.set_label(&group.text);
+            }
+            if label.is_visible() != group.visible {
+                label.set_visible(group.visible);
+            }
+        }
+    }
+}
+
+/// Time spent in the current segment, under the running timer, colored against
+/// the current comparison's time for the segment. Shown with
+/// `display.show-segment-timer`.
+pub struct SegmentTimer {
+    label: Label,
+    class: &'static str,
+}
+
+impl SegmentTimer {
+    pub fn new(timer: &Timer, config: &Config) -> Self {
+        let label = Label::builder().halign(Align::End).build();
+        label.add_css_class("smalltimer");
+        label.add_css_class("timer");
 
-        if self.hms_label.label().as_str() != left {
-            self.hms_label.set_label(&left);
+        let mut this = Self { label, class: "" };
+        this.update(timer, config);
+        this
+    }
+
+    pub fn container(&self) -> &Label {
+        &self.label
+    }
+
+    pub fn update(&mut self, timer: &Timer, config: &Config) {
+        self.label.set_visible(config.display.show_segment_timer);
+        if !config.display.show_segment_timer {
+            return;
         }
-        if self.ms_label.label().as_str() != right {
-            self.ms_label.set_label(&right);
+
+        let (elapsed, comparison) = current_segment_times(timer);
+        let text = config.format.segment.format_duration(&elapsed);
+        if self.label.label().as_str() != text {
+            self.label.set_label(&text);
+        }
+
+        let in_attempt = matches!(
+            timer.current_phase(),
+            TimerPhase::Running | TimerPhase::Paused
+        );
+        let class = match comparison {
+            Some(comparison) if in_attempt && elapsed > comparison => "redsplit",
+            Some(_) if in_attempt => "greensplit",
+            _ => "",
+        };
+        if class != self.class {
+            if !self.class.is_empty() {
+                self.label.remove_css_class(self.class);
+            }
+            if !class.is_empty() {
+                self.label.add_css_class(class);
+            }
+            self.class = class;
         }
     }
 }
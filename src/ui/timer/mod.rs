@@ -1,33 +1,381 @@
 pub mod body;
+pub mod components;
+pub mod delta_graph;
+pub mod finish;
+pub mod flash;
 pub mod footer;
 pub mod header;
+pub mod sparkline;
 
 use crate::ui::timer::body::TimerBody;
+use crate::ui::timer::finish::{NEW_PB_CLASS, NewPbState, PB_DEAD_CLASS, beats_personal_best};
 use crate::ui::timer::footer::TimerFooter;
 use crate::ui::timer::header::TimerHeader;
 
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use std::time::Instant;
 
 use core::time::Duration;
 
 use adw::Clamp;
 use adw::prelude::*;
-use gtk4::{Align, Box as GtkBox, Orientation::Vertical};
+use gtk4::{Align, Box as GtkBox, Orientation::Vertical, Revealer, RevealerTransitionType};
 
+use livesplit_core::Timer;
+use tracing::debug;
+
+use crate::config::Config;
 use crate::context::TuxSplitContext;
+use crate::safe_mode;
+use crate::utils::compaction::{CollapseStep, Compaction, NaturalSizes, Size, compact};
+use crate::utils::focus::{FocusMode, TimerComponent};
+use crate::utils::frame_stats::{TickPhase, TickProbe};
+use crate::utils::refresh::RefreshRate;
 
-pub struct TuxSplitTimer {
+/// Widgets of one timer, refreshed by the shared tick.
+struct TimerView {
     clamp: Clamp,
     header: Rc<RefCell<TimerHeader>>,
     body: Rc<RefCell<TimerBody>>,
     footer: Rc<RefCell<TimerFooter>>,
-    refresh_source: Option<glib::SourceId>,
+    new_pb: RefCell<NewPbState>,
+    /// The parts focus mode hides or shows, the footer's included.
+    revealers: Vec<(TimerComponent, Revealer)>,
+    /// Whether the view follows focus mode: viewers and previews do not.
+    follows_focus: Cell<bool>,
+    /// Focus mode the revealers were last set for.
+    focus: Cell<FocusMode>,
+    /// Whether the view collapses parts of itself to fit its window: previews
+    /// are laid out by the editor instead.
+    compacts: Cell<bool>,
+    /// Compaction the revealers were last set for, with the size of the window
+    /// it was decided for (see `utils::compaction`).
+    compaction: Cell<Compaction>,
+    compacted_for: Cell<Option<Size>>,
+    /// `display.collapse_order` with its steps, parsed again only when it changes.
+    collapse_order: RefCell<(Vec<String>, Vec<CollapseStep>)>,
+    run_changed: Option<glib::SignalHandlerId>,
+}
+
+impl Drop for TimerView {
+    fn drop(&mut self) {
+        if let Some(id) = self.run_changed.take() {
+            TuxSplitContext::get_instance().disconnect(id);
+        }
+    }
+}
+
+impl TimerView {
+    /// Refreshes the components on screen in `focus`, the hidden ones being left
+    /// alone until they are shown again.
+    fn refresh(
+        &self,
+        t: &Timer,
+        c: &Config,
+        animations: bool,
+        pb_lost: bool,
+        focus: FocusMode,
+        probe: &mut TickProbe,
+    ) {
+        probe.skip();
+        let focus = if self.follows_focus.get() {
+            focus
+        } else {
+            FocusMode::default()
+        };
+        // Components shown again are rebuilt from scratch
+        let focus_changed = self.focus.replace(focus) != focus;
+        let compaction_changed = self.update_compaction(t, c, focus_changed);
+        if focus_changed || compaction_changed {
+            self.apply_focus(focus, animations);
+            self.body.borrow_mut().set_reduced(
+                self.compaction.get().under(focus).reduces_splits(),
+                t,
+                c,
+            );
+        }
+        focus.refresh(TimerComponent::RunInfo, || {
+            self.header.borrow_mut().refresh(t);
+        });
+        probe.lap(TickPhase::RunInfo);
+        focus.refresh(TimerComponent::Splits, || {
+            self.body.borrow_mut().refresh(t, c, focus_changed);
+        });
+        probe.lap(TickPhase::Splits);
+        self.footer.borrow_mut().refresh(t, c, focus);
+
+        let is_new_pb = self.new_pb.borrow_mut().update(
+            t.current_phase(),
+            || {
+                let method = t.current_timing_method();
+                t.run().segments().last().is_some_and(|last| {
+                    beats_personal_best(
+                        last.split_time()[method],
+                        last.personal_best_split_time()[method],
+                    )
+                })
+            },
+            Instant::now(),
+        );
+        set_class(&self.clamp, NEW_PB_CLASS, is_new_pb);
+        set_class(self.footer.borrow().big_timer(), PB_DEAD_CLASS, pb_lost);
+        // Theme transitions, like a pulse on `new-pb`, become instant
+        set_class(&self.clamp, NO_TRANSITION_CLASS, !animations);
+        probe.lap(TickPhase::Footer);
+    }
+
+    /// Hides the components outside focus mode's whitelist, or the ones
+    /// collapsed to fit the window without it, sliding them only with
+    /// animations on.
+    fn apply_focus(&self, focus: FocusMode, animations: bool) {
+        let transition = if animations {
+            RevealerTransitionType::SlideDown
+        } else {
+            RevealerTransitionType::None
+        };
+        let compaction = self.compaction.get().under(focus);
+        for (component, revealer) in &self.revealers {
+            revealer.set_transition_type(transition);
+            revealer.set_reveal_child(focus.shows(*component) && compaction.shows(*component));
+        }
+    }
+
+    /// Decides the compaction again when the window was resized, or `force`d.
+    /// Returns whether it changed.
+    fn update_compaction(&self, t: &Timer, c: &Config, force: bool) -> bool {
+        let Some(available) = self.available_size() else {
+            return false;
+        };
+        if !force && self.compacted_for.get() == Some(available) {
+            return false;
+        }
+        self.compacted_for.set(Some(available));
+        let mut order = self.collapse_order.borrow_mut();
+        if order.0 != c.display.collapse_order {
+            order.0.clone_from(&c.display.collapse_order);
+            order.1 = CollapseStep::parse_list(&order.0);
+        }
+        let compaction = compact(available, &self.natural_sizes(t, c), &order.1);
+        if compaction != self.compaction.get() {
+            debug!(
+                "Timer compacted for {}x{}: {}",
+                available.width,
+                available.height,
+                compaction.describe()
+            );
+        }
+        self.compaction.replace(compaction) != compaction
+    }
+
+    /// Room left for the timer in its window, once it is shown.
+    fn available_size(&self) -> Option<Size> {
+        if !self.compacts.get() {
+            return None;
+        }
+        let window = self.clamp.root().and_downcast::<gtk4::Window>()?;
+        let origin = self
+            .clamp
+            .compute_point(&window, &gtk4::graphene::Point::zero())?;
+        let size = Size::new(window.width(), window.height() - origin.y() as i32);
+        (window.width() > 0 && size.height > 0).then_some(size)
+    }
+
+    /// Sizes the components ask for, collapsed ones included. Widths are the
+    /// least they can take, since labels shrink with ellipses.
+    fn natural_sizes(&self, t: &Timer, c: &Config) -> NaturalSizes {
+        let measure = |widget: &gtk4::Widget| {
+            let (min_width, _, _, _) = widget.measure(gtk4::Orientation::Horizontal, -1);
+            let (_, height, _, _) = widget.measure(gtk4::Orientation::Vertical, -1);
+            Size::new(min_width, height)
+        };
+        let component = |component: TimerComponent| {
+            self.revealers
+                .iter()
+                .find(|(c, _)| *c == component)
+                .and_then(|(_, revealer)| revealer.child())
+                .map(|child| measure(&child))
+                .unwrap_or_default()
+        };
+        let splits = component(TimerComponent::Splits);
+        let body = self.body.borrow();
+        let footer = self.footer.borrow();
+        let timers = footer
+            .big_timer()
+            .parent()
+            .map(|timers| measure(&timers))
+            .unwrap_or_default();
+        let mut additional_info = component(TimerComponent::AdditionalInfo);
+        if additional_info.height > 0 {
+            additional_info.height += FOOTER_SPACING;
+        }
+        NaturalSizes {
+            run_info: component(TimerComponent::RunInfo),
+            splits: Size::new(splits.width, body.natural_height(t, c, false)),
+            reduced_splits: Size::new(splits.width, body.natural_height(t, c, true)),
+            info_rows: component(TimerComponent::InfoRows),
+            timers,
+            additional_info,
+            spacing: TIMER_SPACING,
+            margins: Size::new(2 * TIMER_MARGIN, 2 * TIMER_MARGIN),
+        }
+    }
+}
+
+/// Space around the parts of the timer.
+const TIMER_MARGIN: i32 = 24;
+/// Space between the parts of the timer.
+const TIMER_SPACING: i32 = 20;
+/// Space between the big timer and the rows under it.
+const FOOTER_SPACING: i32 = 12;
+
+const NO_TRANSITION_CLASS: &str = "no-transition";
+
+/// A revealer around `child`, for focus mode to hide it (see `utils::focus`).
+pub fn focus_revealer(child: &impl IsA<gtk4::Widget>) -> Revealer {
+    Revealer::builder()
+        .child(child)
+        .reveal_child(true)
+        .transition_type(RevealerTransitionType::None)
+        .build()
+}
+
+fn set_class(widget: &impl IsA<gtk4::Widget>, class: &str, set: bool) {
+    if set != widget.has_css_class(class) {
+        if set {
+            widget.add_css_class(class);
+        } else {
+            widget.remove_css_class(class);
+        }
+    }
+}
+
+/// How often the loop keeps the timer state in sync (golds, hooks, auto start,
+/// ...), whatever the rate the views are refreshed at.
+const TICK_INTERVAL: Duration = Duration::from_millis(16);
+
+/// One refresh loop for every timer on screen, so viewers do not copy the timer
+/// and look for golds again on each tick.
+#[derive(Default)]
+struct Ticker {
+    views: Vec<Rc<TimerView>>,
+    source: Option<glib::SourceId>,
+    /// Interval of `source`, the finer of `TICK_INTERVAL` and `rate`.
+    interval: Duration,
+    /// How often the views are refreshed, see `utils::refresh`.
+    rate: Option<RefreshRate>,
+    last_refresh: Option<Instant>,
+}
+
+impl Ticker {
+    /// Starts the loop, fast enough for the rate the config needs.
+    fn schedule(&mut self, rate: RefreshRate) {
+        self.set_rate(rate);
+        self.interval = rate.interval.min(TICK_INTERVAL);
+        self.source = Some(glib::timeout_add_local(self.interval, tick));
+    }
+
+    fn set_rate(&mut self, rate: RefreshRate) {
+        if self.rate != Some(rate) {
+            debug!("{}", rate.describe());
+            self.rate = Some(rate);
+        }
+    }
+
+    /// Whether the views are due a refresh at `now`. Half a tick early counts,
+    /// so a rate equal to the tick interval isn't halved by timer jitter.
+    fn refresh_due(&mut self, now: Instant) -> bool {
+        let interval = self.rate.map_or(Duration::ZERO, |rate| rate.interval);
+        let due = self
+            .last_refresh
+            .is_none_or(|last| now.duration_since(last) + self.interval / 2 >= interval);
+        if due {
+            self.last_refresh = Some(now);
+        }
+        due
+    }
+}
+
+thread_local! {
+    static TICKER: RefCell<Ticker> = RefCell::new(Ticker::default());
+}
+
+fn tick() -> glib::ControlFlow {
+    // Cloned out so refreshing a view may add or remove views
+    let (views, refresh) = TICKER.with(|ticker| {
+        let mut ticker = ticker.borrow_mut();
+        let refresh = ticker.refresh_due(Instant::now());
+        (ticker.views.clone(), refresh)
+    });
+
+    let mut probe = TickProbe::start();
+    let ctx = TuxSplitContext::get_instance();
+    let t = {
+        let shared = ctx.timer();
+        let timer = shared.read().unwrap();
+        probe.lap(TickPhase::Lock);
+        let t = timer.clone();
+        probe.lap(TickPhase::Snapshot);
+        t
+    };
+    let golds = ctx.update_golds(&t);
+    ctx.update_hooks(&t, &golds, safe_mode::current());
+    ctx.update_dirty(&t);
+    ctx.update_auto_start(&t);
+    ctx.update_clock(&t);
+    ctx.update_pb_cutoff(&t);
+    ctx.update_pace_alert(&t);
+    ctx.update_goal(&t);
+    ctx.update_anomalies(&t);
+    #[cfg(feature = "remote-control")]
+    ctx.update_remote();
+
+    ctx.update_reset_guard();
+    ctx.update_timer_state(&t);
+    ctx.update_focus_mode();
+    ctx.update_preflight();
+    flash::update(&t);
+    probe.lap(TickPhase::Bookkeeping);
+
+    let c = ctx.config();
+    if refresh {
+        let animations = ctx.animations_enabled(safe_mode::current());
+        let pb_lost = ctx.pb_lost();
+        let focus = ctx.focus_mode();
+        for view in &views {
+            view.refresh(&t, &c, animations, pb_lost, focus, &mut probe);
+        }
+    }
+    probe.finish();
+
+    // The precision on screen changes with the settings: follow it
+    let rate = RefreshRate::of_config(&c);
+    drop(c);
+    TICKER.with(|ticker| {
+        let mut ticker = ticker.borrow_mut();
+        ticker.set_rate(rate);
+        if ticker.source.is_none() || rate.interval.min(TICK_INTERVAL) == ticker.interval {
+            return glib::ControlFlow::Continue;
+        }
+        // Only a fixed rate finer than a tick needs a faster loop. Returning
+        // `Break` removes the current source
+        ticker.source.take();
+        ticker.schedule(rate);
+        glib::ControlFlow::Break
+    })
+}
+
+pub struct TuxSplitTimer {
+    view: Rc<TimerView>,
 }
 
 impl TuxSplitTimer {
     /// Create the timer widget (header/body/footer composed) but does NOT start refresh loop.
     pub fn new() -> Self {
+        Self::build(true)
+    }
+
+    fn build(flashes: bool) -> Self {
         let clamp = Clamp::builder().maximum_size(900).build();
 
         let container = GtkBox::builder()
@@ -35,11 +383,11 @@ impl TuxSplitTimer {
             .valign(Align::Center)
             .halign(Align::Fill)
             .hexpand(true)
-            .margin_top(24)
-            .margin_bottom(24)
-            .margin_start(24)
-            .margin_end(24)
-            .spacing(20)
+            .margin_top(TIMER_MARGIN)
+            .margin_bottom(TIMER_MARGIN)
+            .margin_start(TIMER_MARGIN)
+            .margin_end(TIMER_MARGIN)
+            .spacing(TIMER_SPACING)
             .build();
 
         let ctx = TuxSplitContext::get_instance();
@@ -49,80 +397,268 @@ impl TuxSplitTimer {
 
         let cfg = ctx.config();
         let body = Rc::new(RefCell::new(TimerBody::new(&timer_read, &cfg)));
+        body.borrow_mut().set_split_on(
+            ctx.sidecar()
+                .split_on_descriptions(timer_read.run().segments()),
+        );
         let footer = Rc::new(RefCell::new(TimerFooter::new(
             &timer_read,
             &cfg,
             body.borrow().list(),
             body.borrow().last_segment_list(),
         )));
+        let new_pb = RefCell::new(NewPbState::new(timer_read.current_phase()));
         drop(timer_read);
+        drop(cfg);
 
-        container.append(header.borrow().container());
-        container.append(body.borrow().container());
+        let mut revealers = vec![
+            (
+                TimerComponent::RunInfo,
+                focus_revealer(header.borrow().container()),
+            ),
+            (
+                TimerComponent::Splits,
+                focus_revealer(body.borrow().container()),
+            ),
+        ];
+        for (_, revealer) in &revealers {
+            container.append(revealer);
+        }
         container.append(footer.borrow().container());
+        revealers.extend(footer.borrow().focus_revealers().iter().cloned());
+        // Only the focus line starts hidden
+        for (component, revealer) in &revealers {
+            revealer.set_reveal_child(FocusMode::default().shows(*component));
+        }
 
         clamp.set_child(Some(&container));
+        if flashes {
+            flash::install(&body, footer.borrow().big_timer());
+        }
 
-        {
+        let view = Rc::new_cyclic(|view: &Weak<TimerView>| {
             // Connect global run-changed to force a rebuild of timer UI.
-            let body_binding = body.clone();
-            let footer_binding = footer.clone();
-            TuxSplitContext::get_instance().connect_local("run-changed", false, move |_| {
+            let view_binding = view.clone();
+            let run_changed = ctx.connect_local("run-changed", false, move |_| {
+                let view = view_binding.upgrade()?;
                 let ctx = TuxSplitContext::get_instance();
                 let t = {
                     let shared = ctx.timer();
                     shared.read().unwrap().clone()
                 };
                 let c = ctx.config();
-                body_binding.borrow_mut().refresh(&t, &c, true);
-                footer_binding.borrow_mut().refresh(&t, &c);
+                view.header.borrow_mut().refresh(&t);
+                let descriptions = ctx.sidecar().split_on_descriptions(t.run().segments());
+                view.body.borrow_mut().set_split_on(descriptions);
+                view.body.borrow_mut().refresh(&t, &c, true);
+                view.footer.borrow_mut().refresh(&t, &c, view.focus.get());
+                // The parts may ask for another size now
+                view.compacted_for.set(None);
                 None
             });
-        }
 
-        Self {
-            clamp,
-            header,
-            body,
-            footer,
-            refresh_source: None,
-        }
+            TimerView {
+                clamp,
+                header,
+                body,
+                footer,
+                new_pb,
+                revealers,
+                follows_focus: Cell::new(true),
+                focus: Cell::new(FocusMode::default()),
+                compacts: Cell::new(true),
+                compaction: Cell::new(Compaction::default()),
+                compacted_for: Cell::new(None),
+                collapse_order: RefCell::new((Vec::new(), CollapseStep::parse_list(&[]))),
+                run_changed: Some(run_changed),
+            }
+        });
+
+        Self { view }
+    }
+
+    /// A timer that only mirrors the run: it takes no clicks or keyboard focus.
+    pub fn read_only() -> Self {
+        let this = Self::new();
+        this.view.follows_focus.set(false);
+        this.view.clamp.set_can_target(false);
+        this.view.clamp.set_can_focus(false);
+        this
+    }
+
+    /// A read-only timer that only changes with the run, never ticking: a
+    /// preview of the splits while they are edited.
+    pub fn preview() -> Self {
+        let this = Self::build(false);
+        this.view.follows_focus.set(false);
+        this.view.compacts.set(false);
+        this.view.clamp.set_can_target(false);
+        this.view.clamp.set_can_focus(false);
+        this
     }
 
     pub fn clamped(&self) -> &Clamp {
-        &self.clamp
+        &self.view.clamp
+    }
+
+    /// The run info above the splits (game and category).
+    pub fn run_info(&self) -> GtkBox {
+        self.view.header.borrow().container().clone()
+    }
+
+    /// Hides the run info above the splits.
+    pub fn set_compact(&self, compact: bool) {
+        self.view.header.borrow().container().set_visible(!compact);
+        // Already out of the way of the automatic compaction
+        self.view.compacted_for.set(None);
     }
 
     pub fn start_refresh_loop(&mut self) {
-        if self.refresh_source.is_some() {
-            return; // Already running
+        TICKER.with(|ticker| {
+            let mut ticker = ticker.borrow_mut();
+            if ticker.views.iter().any(|view| Rc::ptr_eq(view, &self.view)) {
+                return; // Already running
+            }
+            ticker.views.push(self.view.clone());
+            if ticker.source.is_none() {
+                let rate = RefreshRate::of_config(&TuxSplitContext::get_instance().config());
+                ticker.schedule(rate);
+            }
+        });
+    }
+
+    pub fn stop_refresh_loop(&mut self) {
+        TICKER.with(|ticker| {
+            let mut ticker = ticker.borrow_mut();
+            ticker.views.retain(|view| !Rc::ptr_eq(view, &self.view));
+            if ticker.views.is_empty()
+                && let Some(id) = ticker.source.take()
+            {
+                id.remove();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tick_budget_tests {
+    use super::*;
+    use crate::utils::frame_stats::FrameStats;
+    use livesplit_core::{Run, Segment, Time, TimeSpan};
+    use std::sync::{Arc, Once, RwLock};
+
+    /// Longest a tick may take at the 95th percentile: half a frame at 60 Hz.
+    const BUDGET: Duration = Duration::from_millis(8);
+    const SEGMENTS: usize = 50;
+    const TICKS: usize = 300;
+
+    static INIT: Once = Once::new();
+
+    fn gtk_test_init() {
+        INIT.call_once(|| {
+            gtk4::init().expect("Failed to init GTK");
+            let _ = adw::init();
+        });
+    }
+
+    /// A run of `SEGMENTS` one-minute segments with a PB and golds.
+    fn synthetic_run() -> Run {
+        let mut run = Run::new();
+        run.set_game_name("Game");
+        run.set_category_name("Any%");
+        for index in 0..SEGMENTS {
+            let mut segment = Segment::new(format!("Segment {}", index + 1));
+            let pb = TimeSpan::from_seconds(60.0 * (index + 1) as f64);
+            segment.set_personal_best_split_time(Time::new().with_real_time(Some(pb)));
+            segment.set_best_segment_time(
+                Time::new().with_real_time(Some(TimeSpan::from_seconds(55.0))),
+            );
+            run.push_segment(segment);
         }
+        run
+    }
+
+    /// Refreshes the parts of a timer like `tick` does, a split every tenth
+    /// tick, and checks the time it takes. Timings depend on the machine, so it
+    /// only runs when asked for: `cargo test -- --ignored tick_budget`.
+    #[gtk4::test]
+    #[ignore = "benchmark, timings depend on the machine"]
+    fn a_tick_on_a_long_run_stays_within_budget() {
+        gtk_test_init();
 
-        let header_binding = self.header.clone();
-        let body_binding = self.body.clone();
-        let footer_binding = self.footer.clone();
+        let shared = Arc::new(RwLock::new(Timer::new(synthetic_run()).unwrap()));
+        shared.write().unwrap().start();
+        let config = Config::default();
+        let (mut header, mut body, mut footer) = {
+            let timer = shared.read().unwrap();
+            let header = TimerHeader::new(&timer);
+            let body = TimerBody::new(&timer, &config);
+            let footer = TimerFooter::new(&timer, &config, body.list(), body.last_segment_list());
+            (header, body, footer)
+        };
 
-        let source_id = glib::timeout_add_local(Duration::from_millis(16), move || {
-            let ctx = TuxSplitContext::get_instance();
+        let mut stats = FrameStats::default();
+        let start = Instant::now();
+        for tick in 0..TICKS {
+            if tick % 10 == 9 {
+                shared.write().unwrap().split();
+            }
+            let mut probe = TickProbe::new(true);
             let t = {
-                let shared = ctx.timer();
-                shared.read().unwrap().clone()
+                let timer = shared.read().unwrap();
+                probe.lap(TickPhase::Lock);
+                let t = timer.clone();
+                probe.lap(TickPhase::Snapshot);
+                t
             };
+            header.refresh(&t);
+            probe.lap(TickPhase::RunInfo);
+            body.refresh(&t, &config, false);
+            probe.lap(TickPhase::Splits);
+            footer.refresh(&t, &config, FocusMode::default());
+            probe.lap(TickPhase::Footer);
+            probe.record_into(&mut stats, start);
+        }
 
-            let c = ctx.config();
-            header_binding.borrow_mut().refresh(&t);
-            body_binding.borrow_mut().refresh(&t, &c, false);
-            footer_binding.borrow_mut().refresh(&t, &c);
+        let p95 = stats.ticks().percentile(95).unwrap();
+        assert!(
+            p95 <= BUDGET,
+            "p95 of {p95:?} over {BUDGET:?}:\n{}",
+            stats.lines().join("\n")
+        );
+    }
+}
 
-            glib::ControlFlow::Continue
-        });
+#[cfg(test)]
+mod ticker_tests {
+    use super::*;
 
-        self.refresh_source = Some(source_id);
+    fn ticker(rate_ms: u64) -> Ticker {
+        let rate = RefreshRate::for_components(&[], Some(rate_ms));
+        Ticker {
+            interval: rate.interval.min(TICK_INTERVAL),
+            rate: Some(rate),
+            ..Ticker::default()
+        }
     }
 
-    pub fn stop_refresh_loop(&mut self) {
-        if let Some(id) = self.refresh_source.take() {
-            id.remove();
-        }
+    #[test]
+    fn views_are_only_refreshed_at_the_rate() {
+        let mut ticker = ticker(250);
+        let start = Instant::now();
+        let due: Vec<bool> = (0..20)
+            .map(|tick| ticker.refresh_due(start + TICK_INTERVAL * tick))
+            .collect();
+        // Every tick runs, the views only every 16 of them
+        assert_eq!(due.iter().filter(|&&due| due).count(), 2);
+        assert!(due[0] && due[16]);
+    }
+
+    #[test]
+    fn a_late_tick_at_the_frame_rate_still_refreshes() {
+        let mut ticker = ticker(16);
+        let start = Instant::now();
+        assert!(ticker.refresh_due(start));
+        assert!(ticker.refresh_due(start + Duration::from_millis(15)));
     }
 }
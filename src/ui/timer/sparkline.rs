@@ -0,0 +1,205 @@
+use crate::config::Config;
+use crate::utils::sparkline::{
+    DeltaEnvelope, attempt_delta_series, current_delta_series, splits_done,
+};
+
+use gtk4::prelude::*;
+use gtk4::{DrawingArea, cairo};
+
+use livesplit_core::{Timer, TimerPhase};
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const HEIGHT: i32 = 48;
+const PADDING: f64 = 4.0;
+const POINT_RADIUS: f64 = 2.5;
+
+#[derive(Default)]
+struct SparklineData {
+    segment_count: usize,
+    envelope: DeltaEnvelope,
+    current: Vec<Option<time::Duration>>,
+}
+
+/// Small plot of the current attempt's delta against the comparison at each split,
+/// over the range between the best and worst attempts in the history.
+pub struct DeltaSparkline {
+    area: DrawingArea,
+    data: Rc<RefCell<SparklineData>>,
+    comparison: String,
+    last_phase: TimerPhase,
+}
+
+impl DeltaSparkline {
+    pub fn new(timer: &Timer, config: &Config) -> Self {
+        let area = DrawingArea::builder()
+            .content_height(HEIGHT)
+            .hexpand(true)
+            .css_classes(["delta-sparkline"])
+            .build();
+        let data = Rc::new(RefCell::new(SparklineData::default()));
+
+        {
+            let data = data.clone();
+            area.set_draw_func(move |area, cr, width, height| {
+                draw(
+                    area,
+                    cr,
+                    f64::from(width),
+                    f64::from(height),
+                    &data.borrow(),
+                );
+            });
+        }
+
+        let mut this = Self {
+            area,
+            data,
+            comparison: String::new(),
+            last_phase: timer.current_phase(),
+        };
+        this.refresh(timer, config, true);
+        this
+    }
+
+    pub fn widget(&self) -> &DrawingArea {
+        &self.area
+    }
+
+    /// Updates the current attempt's series, redrawing only when a split was added or
+    /// undone. `history_changed` also recomputes the historical envelope, which resets
+    /// do on their own since they append the attempt to the history.
+    pub fn refresh(&mut self, timer: &Timer, config: &Config, history_changed: bool) {
+        let phase = timer.current_phase();
+        let reset = phase.is_not_running() && !self.last_phase.is_not_running();
+        self.last_phase = phase;
+        let history_changed = history_changed || reset;
+
+        self.area.set_visible(config.display.show_sparkline);
+        if !config.display.show_sparkline {
+            return;
+        }
+
+        let segments = timer.run().segments();
+        let method = timer.current_timing_method();
+        let comparison = timer.current_comparison();
+        let comparison_changed = self.comparison != comparison;
+        let mut data = self.data.borrow_mut();
+
+        if history_changed || comparison_changed {
+            self.comparison = comparison.to_owned();
+            let series = attempt_delta_series(segments, comparison, method);
+            data.envelope = DeltaEnvelope::from_series(&series, segments.len());
+            data.segment_count = segments.len();
+        }
+
+        let done = splits_done(phase, timer.current_split_index(), segments.len());
+        let current = current_delta_series(segments, done, comparison, method);
+
+        if history_changed || comparison_changed || current != data.current {
+            data.current = current;
+            self.area.queue_draw();
+        }
+    }
+}
+
+fn draw(area: &DrawingArea, cr: &cairo::Context, width: f64, height: f64, data: &SparklineData) {
+    if data.segment_count == 0 {
+        return;
+    }
+
+    let current_magnitude = data.current.iter().flatten().map(|d| d.abs()).max();
+    let magnitude = data
+        .envelope
+        .magnitude()
+        .max(current_magnitude.unwrap_or_default())
+        .as_seconds_f64()
+        .max(1.0);
+
+    let x_of = |index: usize| {
+        if data.segment_count == 1 {
+            width / 2.0
+        } else {
+            PADDING + index as f64 * (width - 2.0 * PADDING) / (data.segment_count - 1) as f64
+        }
+    };
+    // Time lost plots upwards
+    let y_of = |delta: time::Duration| {
+        height / 2.0 - delta.as_seconds_f64() / magnitude * (height / 2.0 - PADDING)
+    };
+
+    let color = area.color();
+    let set_color = |alpha: f32| {
+        cr.set_source_rgba(
+            f64::from(color.red()),
+            f64::from(color.green()),
+            f64::from(color.blue()),
+            f64::from(color.alpha() * alpha),
+        );
+    };
+
+    // Historical envelope, one band per stretch of segments with data
+    set_color(0.12);
+    let points: Vec<(usize, time::Duration, time::Duration)> = data
+        .envelope
+        .best
+        .iter()
+        .zip(&data.envelope.worst)
+        .enumerate()
+        .filter_map(|(index, (best, worst))| Some((index, (*best)?, (*worst)?)))
+        .collect();
+    for band in points.chunk_by(|a, b| b.0 == a.0 + 1) {
+        if let [(index, best, worst)] = band {
+            cr.rectangle(
+                x_of(*index) - 1.0,
+                y_of(*worst),
+                2.0,
+                y_of(*best) - y_of(*worst),
+            );
+        } else {
+            for (index, _, worst) in band {
+                cr.line_to(x_of(*index), y_of(*worst));
+            }
+            for (index, best, _) in band.iter().rev() {
+                cr.line_to(x_of(*index), y_of(*best));
+            }
+            cr.close_path();
+        }
+        let _ = cr.fill();
+    }
+
+    // Zero line
+    set_color(0.3);
+    cr.set_line_width(1.0);
+    cr.move_to(0.0, height / 2.0);
+    cr.line_to(width, height / 2.0);
+    let _ = cr.stroke();
+
+    // Current attempt. Skipped splits break the line.
+    set_color(1.0);
+    cr.set_line_width(1.5);
+    let current: Vec<(usize, time::Duration)> = data
+        .current
+        .iter()
+        .enumerate()
+        .filter_map(|(index, delta)| Some((index, (*delta)?)))
+        .collect();
+    for stretch in current.chunk_by(|a, b| b.0 == a.0 + 1) {
+        if let [(index, delta)] = stretch {
+            cr.arc(
+                x_of(*index),
+                y_of(*delta),
+                POINT_RADIUS,
+                0.0,
+                std::f64::consts::TAU,
+            );
+            let _ = cr.fill();
+        } else {
+            for (index, delta) in stretch {
+                cr.line_to(x_of(*index), y_of(*delta));
+            }
+            let _ = cr.stroke();
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Read-only viewer windows mirroring the timer, e.g. on a stream capture screen.
+//!
+//! A viewer is a borderless window holding another `TuxSplitTimer` bound to the
+//! shared context, without header bar or controls; dragging it anywhere moves
+//! it. Closing a viewer leaves the app running, closing the main window closes
+//! every viewer. Whether one was open and its size are restored on next launch.
+
+use adw::prelude::*;
+use gtk4::WindowHandle;
+
+use std::cell::RefCell;
+
+use crate::context::TuxSplitContext;
+use crate::ui::style::{VIEWER_CLASS, apply_viewer_scale};
+use crate::ui::timer::TuxSplitTimer;
+
+struct OpenViewer {
+    window: adw::Window,
+    timer: TuxSplitTimer,
+}
+
+thread_local! {
+    static VIEWERS: RefCell<Vec<OpenViewer>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Opens a new viewer window.
+pub fn open(app: &impl IsA<gtk4::Application>) {
+    let ctx = TuxSplitContext::get_instance();
+    let settings = ctx.config().viewer.clone();
+    apply_viewer_scale(settings.scale);
+
+    let mut timer = TuxSplitTimer::read_only();
+    timer.set_compact(settings.compact);
+    timer.start_refresh_loop();
+
+    let handle = WindowHandle::builder().child(timer.clamped()).build();
+    let window = adw::Window::builder()
+        .application(app)
+        .title("TuxSplit Viewer")
+        .decorated(false)
+        .default_width(settings.width.unwrap_or(400))
+        .default_height(settings.height.unwrap_or(600))
+        .content(&handle)
+        .build();
+    window.add_css_class(VIEWER_CLASS);
+
+    // Closed by the user: not reopened on next launch
+    window.connect_close_request(|window| {
+        remember_size(window);
+        let last = VIEWERS.with(|viewers| viewers.borrow().len() <= 1);
+        if last && let Ok(mut cfg) = TuxSplitContext::get_instance().config_mut() {
+            cfg.mark_dirty();
+            cfg.viewer.open = false;
+        }
+        glib::Propagation::Proceed
+    });
+    window.connect_destroy(|window| {
+        let viewer = VIEWERS.with(|viewers| {
+            let mut viewers = viewers.borrow_mut();
+            let index = viewers.iter().position(|viewer| &viewer.window == window)?;
+            Some(viewers.remove(index))
+        });
+        if let Some(mut viewer) = viewer {
+            viewer.timer.stop_refresh_loop();
+        }
+    });
+
+    if let Ok(mut cfg) = ctx.config_mut() {
+        cfg.mark_dirty();
+        cfg.viewer.open = true;
+    }
+
+    window.present();
+    VIEWERS.with(|viewers| viewers.borrow_mut().push(OpenViewer { window, timer }));
+}
+
+/// Closes every viewer along with the main window. They stay marked open, so
+/// they come back on next launch.
+pub fn close_all() {
+    let viewers = VIEWERS.take();
+    for mut viewer in viewers {
+        remember_size(&viewer.window);
+        viewer.timer.stop_refresh_loop();
+        viewer.window.destroy();
+    }
+}
+
+/// Applies the viewer settings of the config to the open viewers.
+pub fn apply_settings() {
+    let settings = TuxSplitContext::get_instance().config().viewer.clone();
+    apply_viewer_scale(settings.scale);
+    VIEWERS.with(|viewers| {
+        for viewer in viewers.borrow().iter() {
+            viewer.timer.set_compact(settings.compact);
+        }
+    });
+}
+
+fn remember_size(window: &adw::Window) {
+    let (width, height) = window.default_size();
+    if let Ok(mut cfg) = TuxSplitContext::get_instance().config_mut() {
+        cfg.mark_dirty();
+        cfg.viewer.width = Some(width);
+        cfg.viewer.height = Some(height);
+    }
+}
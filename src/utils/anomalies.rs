@@ -0,0 +1,248 @@
+//! Segments of a finished attempt that took far longer than they usually do,
+//! which mostly means a split was forgotten.
+//!
+//! A segment is flagged when its time is more than `MAD_THRESHOLD` median
+//! absolute deviations above the median of its history. Segments with fewer than
+//! `MIN_SAMPLES` times in their history, and the ones the attempt skipped, are
+//! never flagged.
+
+use livesplit_core::{Run, TimeSpan, TimingMethod};
+
+use crate::formatters::TimeFormat;
+
+/// Times a segment needs in its history before it can be flagged.
+pub const MIN_SAMPLES: usize = 5;
+/// Deviations above the median a segment time must be to be flagged.
+pub const MAD_THRESHOLD: f64 = 3.0;
+/// Smallest deviation used, as a share of the median, so a segment that is
+/// always run in about the same time is not flagged for a small mistake.
+const MIN_RELATIVE_DEVIATION: f64 = 0.02;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnomalyKind {
+    /// The extra time is about as long as the next segment usually takes.
+    MissedSplit,
+    /// Slower than ever, but not by a whole segment: likely a real mistake.
+    UnusuallySlow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmentAnomaly {
+    pub index: usize,
+    /// Segment time of the attempt.
+    pub time: TimeSpan,
+    /// Median of the segment's history.
+    pub median: TimeSpan,
+    pub kind: AnomalyKind,
+}
+
+impl SegmentAnomaly {
+    /// One line about the segment called `name`, for the finish notice.
+    pub fn describe(&self, name: &str, format: &TimeFormat) -> String {
+        format!(
+            "{name}: {} (usually {}), {}",
+            format.format_time_span(&self.time),
+            format.format_time_span(&self.median),
+            match self.kind {
+                AnomalyKind::MissedSplit => "possibly a missed split",
+                AnomalyKind::UnusuallySlow => "unusually slow",
+            }
+        )
+    }
+}
+
+/// Flags the segments of an attempt, given the `history` of segment times of
+/// every segment and the `attempt`'s own segment times (`None` where skipped).
+pub fn detect_anomalies(
+    history: &[Vec<TimeSpan>],
+    attempt: &[Option<TimeSpan>],
+) -> Vec<SegmentAnomaly> {
+    let seconds =
+        |times: &[TimeSpan]| -> Vec<f64> { times.iter().map(TimeSpan::total_seconds).collect() };
+    let medians: Vec<Option<f64>> = history
+        .iter()
+        .map(|times| (times.len() >= MIN_SAMPLES).then(|| median(seconds(times))))
+        .collect();
+
+    attempt
+        .iter()
+        .enumerate()
+        .filter_map(|(index, time)| {
+            let time = (*time)?;
+            let times = history.get(index)?;
+            let median = (*medians.get(index)?)?;
+            let deviation = self::median(
+                seconds(times)
+                    .iter()
+                    .map(|time| (time - median).abs())
+                    .collect(),
+            )
+            .max(median * MIN_RELATIVE_DEVIATION);
+            let excess = time.total_seconds() - median;
+            if excess <= MAD_THRESHOLD * deviation {
+                return None;
+            }
+            let next_median = medians.get(index + 1).copied().flatten();
+            let kind = if next_median.is_some_and(|next| excess >= next / 2.0) {
+                AnomalyKind::MissedSplit
+            } else {
+                AnomalyKind::UnusuallySlow
+            };
+            Some(SegmentAnomaly {
+                index,
+                time,
+                median: TimeSpan::from_seconds(median),
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// Segment times of every segment's history in `method`.
+pub fn segment_histories(run: &Run, method: TimingMethod) -> Vec<Vec<TimeSpan>> {
+    run.segments()
+        .iter()
+        .map(|segment| {
+            segment
+                .segment_history()
+                .iter()
+                .filter_map(|(_, time)| time[method])
+                .collect()
+        })
+        .collect()
+}
+
+/// Segment times of the attempt on the timer, from its split times. Skipped
+/// segments have none, and neither has the segment after them, whose time covers
+/// the skipped ones too.
+pub fn attempt_segment_times(run: &Run, method: TimingMethod) -> Vec<Option<TimeSpan>> {
+    let splits: Vec<Option<TimeSpan>> = run
+        .segments()
+        .iter()
+        .map(|segment| segment.split_time()[method])
+        .collect();
+    splits
+        .iter()
+        .enumerate()
+        .map(|(index, split)| {
+            let previous = match index {
+                0 => TimeSpan::zero(),
+                _ => splits[index - 1]?,
+            };
+            Some((*split)? - previous)
+        })
+        .collect()
+}
+
+/// Median of `values`, which are not empty.
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        f64::midpoint(values[middle - 1], values[middle])
+    } else {
+        values[middle]
+    }
+}
+
+#[cfg(test)]
+mod anomalies_tests {
+    use super::*;
+    use livesplit_core::{Segment, Time};
+
+    fn spans(seconds: &[f64]) -> Vec<TimeSpan> {
+        seconds
+            .iter()
+            .copied()
+            .map(TimeSpan::from_seconds)
+            .collect()
+    }
+
+    fn attempt(seconds: &[Option<f64>]) -> Vec<Option<TimeSpan>> {
+        seconds
+            .iter()
+            .map(|time| time.map(TimeSpan::from_seconds))
+            .collect()
+    }
+
+    /// A minute, then half a minute, each run a handful of times.
+    fn history() -> Vec<Vec<TimeSpan>> {
+        vec![
+            spans(&[60.0, 61.0, 59.0, 62.0, 60.0, 61.0]),
+            spans(&[30.0, 31.0, 29.5, 30.5, 30.0]),
+        ]
+    }
+
+    #[test]
+    fn a_segment_covering_the_next_one_is_a_missed_split() {
+        let anomalies = detect_anomalies(&history(), &attempt(&[Some(92.0), Some(30.0)]));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].index, 0);
+        assert_eq!(anomalies[0].kind, AnomalyKind::MissedSplit);
+        assert_eq!(anomalies[0].median.total_seconds(), 60.5);
+
+        // Within the usual spread, and faster than ever, nothing is flagged
+        assert!(detect_anomalies(&history(), &attempt(&[Some(62.5), Some(25.0)])).is_empty());
+    }
+
+    #[test]
+    fn a_slow_but_legitimate_outlier_is_only_unusually_slow() {
+        // One slow attempt in the history does not move the median much
+        let history = vec![
+            spans(&[60.0, 61.0, 59.0, 62.0, 60.0, 90.0]),
+            spans(&[30.0, 31.0, 29.5, 30.5, 30.0]),
+        ];
+        let anomalies = detect_anomalies(&history, &attempt(&[Some(75.0), Some(30.0)]));
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].kind, AnomalyKind::UnusuallySlow);
+        assert_eq!(
+            anomalies[0].describe("Forest", &TimeFormat::default()),
+            format!(
+                "Forest: {} (usually {}), unusually slow",
+                TimeFormat::default().format_time_span(&TimeSpan::from_seconds(75.0)),
+                TimeFormat::default().format_time_span(&TimeSpan::from_seconds(60.5))
+            )
+        );
+
+        // The last segment has no next one to have missed
+        let anomalies = detect_anomalies(&history, &attempt(&[Some(60.0), Some(60.0)]));
+        assert_eq!(anomalies[0].index, 1);
+        assert_eq!(anomalies[0].kind, AnomalyKind::UnusuallySlow);
+    }
+
+    #[test]
+    fn small_histories_and_skipped_segments_are_never_flagged() {
+        let history = vec![
+            spans(&[60.0, 61.0, 59.0, 62.0]),
+            spans(&[30.0, 31.0, 29.5, 30.5, 30.0]),
+        ];
+        assert!(detect_anomalies(&history, &attempt(&[Some(600.0), None])).is_empty());
+        // A history of identical times still allows a little slack
+        let steady = vec![spans(&[60.0; 5])];
+        assert!(detect_anomalies(&steady, &attempt(&[Some(63.0)])).is_empty());
+        assert_eq!(detect_anomalies(&steady, &attempt(&[Some(64.0)])).len(), 1);
+    }
+
+    #[test]
+    fn segments_after_a_skip_have_no_time_of_their_own() {
+        let mut run = Run::new();
+        for (name, split) in [("One", Some(60.0)), ("Two", None), ("Three", Some(150.0))] {
+            let mut segment = Segment::new(name);
+            segment.set_split_time(Time::new().with_real_time(split.map(TimeSpan::from_seconds)));
+            for (attempt, time) in (1..).zip([58.0, 61.0]) {
+                segment.segment_history_mut().insert(
+                    attempt,
+                    Time::new().with_real_time(Some(TimeSpan::from_seconds(time))),
+                );
+            }
+            run.push_segment(segment);
+        }
+
+        let times = attempt_segment_times(&run, TimingMethod::RealTime);
+        assert_eq!(times, attempt(&[Some(60.0), None, None]));
+        assert_eq!(
+            segment_histories(&run, TimingMethod::RealTime)[2],
+            spans(&[58.0, 61.0])
+        );
+    }
+}
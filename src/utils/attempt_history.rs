@@ -0,0 +1,223 @@
+//! Deleting attempts from the history, and statistics over some of its attempts
+//! (e.g. those with a tag).
+//!
+//! Attempts are numbered from 1 in the order they were made, and the segment
+//! histories and the sidecar key their data by that number. Deleting an attempt
+//! moves every later one down by one, so the numbers stay contiguous;
+//! `remap_attempt_index` is how every map keyed by attempt follows.
+
+use std::collections::BTreeMap;
+
+use livesplit_core::run::Attempt;
+use livesplit_core::{Run, Time, TimeSpan, TimingMethod};
+
+/// The number of attempt `index` once attempt `removed` is deleted, `None` for
+/// the deleted one. Imported history (0 and below) is never renumbered.
+pub fn remap_attempt_index(index: i32, removed: i32) -> Option<i32> {
+    if index == removed {
+        None
+    } else if removed > 0 && index > removed {
+        Some(index - 1)
+    } else {
+        Some(index)
+    }
+}
+
+/// `map` with the entry of attempt `removed` dropped and later attempts
+/// renumbered.
+pub fn remap_attempt_keys<T>(map: BTreeMap<i32, T>, removed: i32) -> BTreeMap<i32, T> {
+    map.into_iter()
+        .filter_map(|(index, value)| Some((remap_attempt_index(index, removed)?, value)))
+        .collect()
+}
+
+/// `run` without attempt `index`: its entry of the attempt history and its
+/// segment times are dropped, later attempts renumbered and the attempt count
+/// lowered. Comparisons, the personal best and best segments are kept as they
+/// are. `None` when the history has no such attempt.
+pub fn remove_attempt(run: &Run, index: i32) -> Option<Run> {
+    if !run
+        .attempt_history()
+        .iter()
+        .any(|attempt| attempt.index() == index)
+    {
+        return None;
+    }
+    let mut run = run.clone();
+    rewrite_history(
+        &mut run,
+        |attempt| Some((remap_attempt_index(attempt.index(), index)?, attempt.time())),
+        |_, attempt, time| Some((remap_attempt_index(attempt, index)?, time)),
+    );
+    run.set_attempt_count(run.attempt_count().saturating_sub(1));
+    run.mark_as_modified();
+    Some(run)
+}
+
+/// Rewrites the history of `run`, which has no way to edit attempts in place.
+/// `attempt` gives the number and final time each attempt is kept with, and
+/// `segment` the same for the time of a segment (by index) in an attempt. `None`
+/// drops the entry.
+pub fn rewrite_history(
+    run: &mut Run,
+    attempt: impl Fn(&Attempt) -> Option<(i32, Time)>,
+    segment: impl Fn(usize, i32, Time) -> Option<(i32, Time)>,
+) {
+    let history = run.attempt_history().to_vec();
+    let segment_histories: Vec<Vec<(i32, Time)>> = run
+        .segments()
+        .iter()
+        .map(|segment| segment.segment_history().iter().copied().collect())
+        .collect();
+    run.clear_history();
+
+    for entry in &history {
+        let Some((index, time)) = attempt(entry) else {
+            continue;
+        };
+        run.add_attempt_with_index(
+            time,
+            index,
+            entry.started(),
+            entry.ended(),
+            entry.pause_time(),
+        );
+    }
+    for (position, (target, times)) in run
+        .segments_mut()
+        .iter_mut()
+        .zip(segment_histories)
+        .enumerate()
+    {
+        let segment_history = target.segment_history_mut();
+        for (index, time) in times {
+            if let Some((index, time)) = segment(position, index, time) {
+                segment_history.insert(index, time);
+            }
+        }
+    }
+}
+
+/// Statistics of some attempts of the history.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AttemptStats {
+    pub attempts: usize,
+    pub finished: usize,
+    pub best: Option<TimeSpan>,
+    /// Mean final time of the finished attempts.
+    pub average: Option<TimeSpan>,
+}
+
+impl AttemptStats {
+    /// Share of the attempts that finished, in whole percent.
+    pub fn finish_rate(&self) -> Option<usize> {
+        (self.attempts > 0).then(|| (self.finished * 100 + self.attempts / 2) / self.attempts)
+    }
+}
+
+/// Statistics of the attempts of `run` whose index is in `attempts`.
+pub fn attempt_stats(run: &Run, attempts: &[i32], method: TimingMethod) -> AttemptStats {
+    let times: Vec<Option<TimeSpan>> = run
+        .attempt_history()
+        .iter()
+        .filter(|attempt| attempts.contains(&attempt.index()))
+        .map(|attempt| attempt.time()[method])
+        .collect();
+    let finished: Vec<TimeSpan> = times.iter().flatten().copied().collect();
+    let total = finished
+        .iter()
+        .fold(TimeSpan::zero(), |total, &time| total + time);
+    AttemptStats {
+        attempts: times.len(),
+        finished: finished.len(),
+        best: finished
+            .iter()
+            .copied()
+            .min_by(|a, b| a.total_milliseconds().total_cmp(&b.total_milliseconds())),
+        average: (!finished.is_empty()).then(|| {
+            TimeSpan::from_milliseconds(total.total_milliseconds() / finished.len() as f64)
+        }),
+    }
+}
+
+#[cfg(test)]
+mod attempt_history_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::Segment;
+
+    /// Attempts 1 and 3 finished in 30s and 24s, attempt 2 was reset in the
+    /// second segment.
+    fn run() -> Run {
+        let mut run = Run::new();
+        for name in ["One", "Two"] {
+            run.push_segment(Segment::new(name));
+        }
+        let attempts: [(i32, &[f64]); 3] = [(1, &[10.0, 20.0]), (2, &[12.0]), (3, &[8.0, 16.0])];
+        for (index, times) in attempts {
+            for (segment, &time) in run.segments_mut().iter_mut().zip(times) {
+                segment.segment_history_mut().insert(index, real(time));
+            }
+            let finished = times.len() == 2;
+            let total = if finished {
+                real(times.iter().sum())
+            } else {
+                Time::new()
+            };
+            run.add_attempt_with_index(total, index, None, None, None);
+        }
+        run.set_attempt_count(3);
+        run
+    }
+
+    #[test]
+    fn later_attempts_move_down_and_imported_history_stays() {
+        assert_eq!(remap_attempt_index(2, 2), None);
+        assert_eq!(remap_attempt_index(1, 2), Some(1));
+        assert_eq!(remap_attempt_index(5, 2), Some(4));
+        assert_eq!(remap_attempt_index(-3, 2), Some(-3));
+        assert_eq!(remap_attempt_index(0, 2), Some(0));
+
+        let map = BTreeMap::from([(1, "a"), (2, "b"), (3, "c"), (-1, "imported")]);
+        assert_eq!(
+            remap_attempt_keys(map, 2),
+            BTreeMap::from([(1, "a"), (2, "c"), (-1, "imported")])
+        );
+    }
+
+    #[test]
+    fn removing_an_attempt_drops_its_times_and_renumbers_the_rest() {
+        let run = remove_attempt(&run(), 1).unwrap();
+        let indices: Vec<i32> = run.attempt_history().iter().map(|a| a.index()).collect();
+        assert_eq!(indices, [1, 2]);
+        assert_eq!(run.attempt_count(), 2);
+        assert_eq!(
+            run.attempt_history()[1].time().real_time,
+            Some(TimeSpan::from_seconds(24.0))
+        );
+        let first = run.segments()[0].segment_history();
+        assert_eq!(first.get(1), Some(real(12.0)));
+        assert_eq!(first.get(2), Some(real(8.0)));
+        assert_eq!(first.get(3), None);
+        assert_eq!(run.segments()[1].segment_history().get(1), None);
+
+        assert!(remove_attempt(&run, 7).is_none());
+    }
+
+    #[test]
+    fn statistics_cover_only_the_given_attempts() {
+        let run = run();
+        let method = TimingMethod::RealTime;
+        let all = attempt_stats(&run, &[1, 2, 3], method);
+        assert_eq!((all.attempts, all.finished), (3, 2));
+        assert_eq!(all.best, Some(TimeSpan::from_seconds(24.0)));
+        assert_eq!(all.average, Some(TimeSpan::from_seconds(27.0)));
+        assert_eq!(all.finish_rate(), Some(67));
+
+        let reset = attempt_stats(&run, &[2], method);
+        assert_eq!((reset.attempts, reset.finished), (1, 0));
+        assert_eq!((reset.best, reset.average), (None, None));
+        assert_eq!(attempt_stats(&run, &[], method), AttemptStats::default());
+        assert_eq!(AttemptStats::default().finish_rate(), None);
+    }
+}
@@ -0,0 +1,287 @@
+//! Side-by-side comparison of two attempts of the history.
+//!
+//! Segment times come from each segment's history, keyed by attempt index. The
+//! time after a split an attempt skipped covers the skipped segment too, so such
+//! segments are merged with the next one in both attempts. A segment an attempt
+//! never reached has no time, and is left out of the totals of both attempts so
+//! they stay comparable. `time_losses` ranks the remaining segments by the time
+//! one attempt lost to the other.
+
+use std::cmp::Reverse;
+
+use livesplit_core::{Run, TimeSpan, TimingMethod};
+
+/// One segment of two compared attempts, or several merged by a skipped split.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SegmentComparison {
+    /// First segment the times cover, before `index` when segments were merged.
+    pub first: usize,
+    pub index: usize,
+    pub a: Option<TimeSpan>,
+    pub b: Option<TimeSpan>,
+}
+
+impl SegmentComparison {
+    /// Time attempt A lost to attempt B on this segment (negative when A was
+    /// faster). `None` unless both attempts have a time.
+    pub fn delta(&self) -> Option<TimeSpan> {
+        Some(self.a? - self.b?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttemptComparison {
+    pub segments: Vec<SegmentComparison>,
+    /// Sum of A's times over the segments both attempts completed.
+    pub total_a: TimeSpan,
+    /// Sum of B's times over the segments both attempts completed.
+    pub total_b: TimeSpan,
+}
+
+impl AttemptComparison {
+    pub fn total_delta(&self) -> TimeSpan {
+        self.total_a - self.total_b
+    }
+}
+
+/// Compares the attempts with indices `a` and `b` segment by segment. A skipped
+/// segment is merged with the ones after it up to the next segment both attempts
+/// split, with the times of each attempt summed over them.
+pub fn compare_attempts(run: &Run, a: i32, b: i32, method: TimingMethod) -> AttemptComparison {
+    let segment_time = |segment: &livesplit_core::Segment, attempt: i32| {
+        segment
+            .segment_history()
+            .get(attempt)
+            .and_then(|time| time[method])
+    };
+    let skipped = |segment: &livesplit_core::Segment, attempt: i32| {
+        segment
+            .segment_history()
+            .get(attempt)
+            .is_some_and(|time| time[method].is_none())
+    };
+
+    let mut segments = Vec::new();
+    let mut first = 0;
+    let (mut sum_a, mut sum_b) = (TimeSpan::zero(), TimeSpan::zero());
+    let last = run.len().saturating_sub(1);
+    for (index, segment) in run.segments().iter().enumerate() {
+        let (time_a, time_b) = (segment_time(segment, a), segment_time(segment, b));
+        sum_a += time_a.unwrap_or_default();
+        sum_b += time_b.unwrap_or_default();
+        if index < last && (skipped(segment, a) || skipped(segment, b)) {
+            continue;
+        }
+        segments.push(SegmentComparison {
+            first,
+            index,
+            a: time_a.map(|_| sum_a),
+            b: time_b.map(|_| sum_b),
+        });
+        first = index + 1;
+        (sum_a, sum_b) = (TimeSpan::zero(), TimeSpan::zero());
+    }
+
+    let (total_a, total_b) = segments
+        .iter()
+        .filter_map(|segment| Some((segment.a?, segment.b?)))
+        .fold(
+            (TimeSpan::zero(), TimeSpan::zero()),
+            |(total_a, total_b), (a, b)| (total_a + a, total_b + b),
+        );
+
+    AttemptComparison {
+        segments,
+        total_a,
+        total_b,
+    }
+}
+
+/// A segment both attempts completed, as ranked by `time_losses`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeLoss {
+    /// First segment the times cover, see `SegmentComparison::first`.
+    pub first: usize,
+    pub index: usize,
+    /// Segment time of the attempt compared against (B).
+    pub comparison: TimeSpan,
+    /// Segment time of the attempt looked at (A).
+    pub actual: TimeSpan,
+    /// Whether A's time matches the best segment of the run. Never set on
+    /// merged segments, which have no best segment of their own.
+    pub gold: bool,
+}
+
+impl TimeLoss {
+    /// Time lost on the segment, negative when A gained time.
+    pub fn delta(&self) -> TimeSpan {
+        self.actual - self.comparison
+    }
+}
+
+/// Where attempt A lost and gained time against B.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeLosses {
+    /// Segments A was slower (or even) on, most time lost first.
+    pub losses: Vec<TimeLoss>,
+    /// Segments A was faster on, most time gained first.
+    pub gains: Vec<TimeLoss>,
+    /// Segments either attempt has no time on, which have no difference.
+    pub skipped: Vec<usize>,
+}
+
+impl TimeLosses {
+    /// Sum of every difference, which is the total delta of the comparison.
+    pub fn total(&self) -> TimeSpan {
+        self.losses
+            .iter()
+            .chain(&self.gains)
+            .fold(TimeSpan::zero(), |total, loss| total + loss.delta())
+    }
+}
+
+/// Ranks the segments of `comparison` by the time A lost on them. `best`
+/// gives the best segment time of the run at an index, to flag golds.
+pub fn time_losses(
+    comparison: &AttemptComparison,
+    best: impl Fn(usize) -> Option<TimeSpan>,
+) -> TimeLosses {
+    let mut losses = Vec::new();
+    let mut gains = Vec::new();
+    let mut skipped = Vec::new();
+    for segment in &comparison.segments {
+        let (Some(actual), Some(comparison)) = (segment.a, segment.b) else {
+            skipped.push(segment.index);
+            continue;
+        };
+        let loss = TimeLoss {
+            first: segment.first,
+            index: segment.index,
+            comparison,
+            actual,
+            gold: segment.first == segment.index
+                && best(segment.index).is_some_and(|best| actual <= best),
+        };
+        if actual < comparison {
+            gains.push(loss);
+        } else {
+            losses.push(loss);
+        }
+    }
+    // Stable, so ties keep the order of the route
+    losses.sort_by_key(|loss| Reverse(loss.delta()));
+    gains.sort_by_key(TimeLoss::delta);
+    TimeLosses {
+        losses,
+        gains,
+        skipped,
+    }
+}
+
+#[cfg(test)]
+mod attempts_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{Segment, Time};
+
+    /// Three segments over attempts 1 (A) and 2 (B). A skipped the second segment,
+    /// so its third segment time covers both.
+    fn run() -> Run {
+        let mut run = Run::new();
+        let history: [(&str, Option<f64>, Option<f64>); 3] = [
+            ("One", Some(10.0), Some(12.0)),
+            ("Two", None, Some(20.0)),
+            ("Three", Some(45.0), Some(30.0)),
+        ];
+        for (name, a, b) in history {
+            let mut segment = Segment::new(name);
+            let history = segment.segment_history_mut();
+            history.insert(1, a.map_or_else(Time::new, real));
+            history.insert(2, b.map_or_else(Time::new, real));
+            run.push_segment(segment);
+        }
+        run
+    }
+
+    #[test]
+    fn skipped_segments_are_merged_with_the_next_one_in_both_attempts() {
+        let comparison = compare_attempts(&run(), 1, 2, TimingMethod::RealTime);
+
+        let spans: Vec<(usize, usize, Option<f64>)> = comparison
+            .segments
+            .iter()
+            .map(|segment| {
+                let delta = segment.delta().map(|d| d.total_seconds());
+                (segment.first, segment.index, delta)
+            })
+            .collect();
+        assert_eq!(spans, [(0, 0, Some(-2.0)), (1, 2, Some(-5.0))]);
+        assert_eq!(comparison.segments[1].a, Some(TimeSpan::from_seconds(45.0)));
+        assert_eq!(comparison.segments[1].b, Some(TimeSpan::from_seconds(50.0)));
+
+        assert_eq!(comparison.total_a.total_seconds(), 55.0);
+        assert_eq!(comparison.total_b.total_seconds(), 62.0);
+        assert_eq!(comparison.total_delta().total_seconds(), -7.0);
+    }
+
+    #[test]
+    fn attempts_missing_from_the_history_have_no_times() {
+        let comparison = compare_attempts(&run(), 2, 7, TimingMethod::RealTime);
+        assert!(comparison.segments.iter().all(|s| s.b.is_none()));
+        assert_eq!(comparison.total_delta(), TimeSpan::zero());
+
+        // Game time was never recorded
+        let comparison = compare_attempts(&run(), 1, 2, TimingMethod::GameTime);
+        assert!(comparison.segments.iter().all(|s| s.delta().is_none()));
+    }
+
+    #[test]
+    fn losses_are_ranked_and_add_up_to_the_total_delta() {
+        let mut run = Run::new();
+        let history: [(&str, Option<f64>, Option<f64>); 5] = [
+            ("One", Some(10.0), Some(12.0)),
+            ("Two", None, Some(20.0)),
+            ("Three", Some(45.0), Some(30.0)),
+            ("Four", Some(8.0), Some(5.0)),
+            ("Five", Some(20.0), Some(26.5)),
+        ];
+        for (name, a, b) in history {
+            let mut segment = Segment::new(name);
+            let history = segment.segment_history_mut();
+            history.insert(1, a.map_or_else(Time::new, real));
+            history.insert(2, b.map_or_else(Time::new, real));
+            run.push_segment(segment);
+        }
+        let comparison = compare_attempts(&run, 1, 2, TimingMethod::RealTime);
+        // The first segment of A is the best one of the run
+        let best =
+            |index: usize| Some(TimeSpan::from_seconds([10.0, 18.0, 28.0, 5.0, 19.0][index]));
+        let losses = time_losses(&comparison, best);
+
+        let ranked = |losses: &[TimeLoss]| {
+            losses
+                .iter()
+                .map(|loss| (loss.index, loss.delta().total_seconds(), loss.gold))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ranked(&losses.losses), [(3, 3.0, false)]);
+        // Two and Three are ranked together, as A's time covers both
+        assert_eq!(
+            ranked(&losses.gains),
+            [(4, -6.5, false), (2, -5.0, false), (0, -2.0, true)]
+        );
+        assert_eq!(losses.gains[1].first, 1);
+        assert!(losses.skipped.is_empty());
+        assert_eq!(losses.total(), comparison.total_delta());
+        assert_eq!(losses.total().total_seconds(), -10.5);
+    }
+
+    #[test]
+    fn attempts_without_common_segments_have_nothing_to_rank() {
+        let comparison = compare_attempts(&run(), 1, 7, TimingMethod::RealTime);
+        let losses = time_losses(&comparison, |_| None);
+        assert!(losses.losses.is_empty() && losses.gains.is_empty());
+        assert_eq!(losses.skipped, [0, 2]);
+        assert_eq!(losses.total(), comparison.total_delta());
+    }
+}
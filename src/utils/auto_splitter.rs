@@ -0,0 +1,414 @@
+//! The auto-splitting runtime: runs the WebAssembly auto splitter of the config
+//! on its own thread, where it drives the timer.
+//!
+//! It works like the runtime of livesplit-core, which keeps the settings of the
+//! script to itself. This one loads the script with the settings kept for the
+//! run (see `utils::auto_splitter_settings`) and publishes the settings the
+//! script registers, for the split editor to show. Scripts only read a setting
+//! when they register it, so changing one loads the script again.
+//!
+//! A script stuck in `update` for longer than `WATCHDOG_TIMEOUT` is interrupted,
+//! which unloads it. Its resets are queued like those of the user (see
+//! `utils::timer_actions`), so the run is kept as a snapshot before them.
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use livesplit_auto_splitting::{
+    CreationError, InterruptHandle, Runtime as ScriptRuntime, SettingsStore,
+    Timer as AutoSplitTimer, TimerState,
+};
+use livesplit_core::{SharedTimer, TimerPhase};
+use tracing::{error, info, warn};
+
+use crate::utils::auto_splitter_settings::{SettingValue, SettingsMap, to_store};
+use crate::utils::timer_actions::{ActionQueue, TimerAction};
+
+/// Time a single `update` of the script may take before it is interrupted.
+const WATCHDOG_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("the auto-splitting runtime stopped")]
+    ThreadStopped,
+    #[error("the script could not be loaded")]
+    LoadFailed {
+        #[source]
+        source: CreationError,
+    },
+    #[error("the script could not be read")]
+    ReadFileFailed {
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// A setting the loaded script registered, with the value it runs with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptSetting {
+    pub key: String,
+    pub description: String,
+    pub default: SettingValue,
+    pub value: SettingValue,
+}
+
+enum Request {
+    Load {
+        script: Vec<u8>,
+        settings: SettingsStore,
+        reply: Sender<Result<(), Error>>,
+    },
+    /// Loads the script running again with other settings.
+    Reload(SettingsStore),
+}
+
+pub struct Runtime {
+    sender: Sender<Request>,
+    settings: Arc<Mutex<Vec<ScriptSetting>>>,
+    interrupt: Arc<Mutex<Option<InterruptHandle>>>,
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        if let Some(handle) = &*self
+            .interrupt
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            handle.interrupt();
+        }
+    }
+}
+
+impl Runtime {
+    /// Starts the runtime, without a script until one is loaded. Scripts reset
+    /// the attempt through `actions`.
+    pub fn new(timer: SharedTimer, actions: ActionQueue) -> Self {
+        let (sender, requests) = mpsc::channel();
+        let (deadlines, watched) = mpsc::channel();
+        let settings = Arc::default();
+        let interrupt = Arc::default();
+
+        let mut worker = Worker {
+            timer,
+            actions,
+            loaded: None,
+            next_step: Instant::now(),
+            published: 0,
+            settings: Arc::clone(&settings),
+            interrupt: Arc::clone(&interrupt),
+            deadlines,
+        };
+        thread::Builder::new()
+            .name("Auto Splitting Runtime".into())
+            .spawn(move || worker.run(&requests))
+            .expect("Failed to spawn the auto-splitting runtime thread");
+
+        let watched_interrupt = Arc::clone(&interrupt);
+        thread::Builder::new()
+            .name("Auto Splitting Watchdog".into())
+            .spawn(move || watchdog(&watched, &watched_interrupt))
+            .expect("Failed to spawn the auto-splitting watchdog thread");
+
+        Self {
+            sender,
+            settings,
+            interrupt,
+        }
+    }
+
+    /// Loads the script at `script` with `settings`, replacing the one running,
+    /// and waits until it is loaded or failed to.
+    pub fn load_script_blocking(
+        &self,
+        script: PathBuf,
+        settings: &SettingsMap,
+    ) -> Result<(), Error> {
+        let script = fs::read(script).map_err(|source| Error::ReadFileFailed { source })?;
+        let (reply, result) = mpsc::channel();
+        self.sender
+            .send(Request::Load {
+                script,
+                settings: to_store(settings),
+                reply,
+            })
+            .map_err(|_| Error::ThreadStopped)?;
+        result.recv().map_err(|_| Error::ThreadStopped)?
+    }
+
+    /// Loads the script running again with `settings`, without waiting. When
+    /// that fails the script keeps running as it was.
+    pub fn reload_settings(&self, settings: &SettingsMap) {
+        if self
+            .sender
+            .send(Request::Reload(to_store(settings)))
+            .is_err()
+        {
+            warn!("The auto-splitting runtime stopped, settings not applied");
+        }
+    }
+
+    /// The settings the running script registered so far, in order.
+    pub fn settings(&self) -> Vec<ScriptSetting> {
+        self.settings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+struct Loaded {
+    runtime: ScriptRuntime<Timer>,
+    script: Vec<u8>,
+}
+
+struct Worker {
+    timer: SharedTimer,
+    actions: ActionQueue,
+    loaded: Option<Loaded>,
+    next_step: Instant,
+    /// Number of settings published the last time.
+    published: usize,
+    settings: Arc<Mutex<Vec<ScriptSetting>>>,
+    interrupt: Arc<Mutex<Option<InterruptHandle>>>,
+    /// When the `update` in progress is interrupted, `None` between updates.
+    deadlines: Sender<Option<Instant>>,
+}
+
+impl Worker {
+    fn run(&mut self, requests: &Receiver<Request>) {
+        loop {
+            let request = if self.loaded.is_some() {
+                let wait = self.next_step.saturating_duration_since(Instant::now());
+                match requests.recv_timeout(wait) {
+                    Ok(request) => request,
+                    Err(RecvTimeoutError::Timeout) => {
+                        self.step();
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            } else {
+                match requests.recv() {
+                    Ok(request) => request,
+                    Err(_) => return,
+                }
+            };
+            match request {
+                Request::Load {
+                    script,
+                    settings,
+                    reply,
+                } => {
+                    let result = self.load(script, settings);
+                    let _ = reply.send(result);
+                }
+                Request::Reload(settings) => {
+                    let Some(script) = self.loaded.as_ref().map(|loaded| loaded.script.clone())
+                    else {
+                        continue;
+                    };
+                    if let Err(e) = self.load(script, settings) {
+                        warn!("Could not apply the auto-splitter settings: {e}");
+                    }
+                }
+            }
+        }
+    }
+
+    fn load(&mut self, script: Vec<u8>, settings: SettingsStore) -> Result<(), Error> {
+        let timer = Timer {
+            timer: self.timer.clone(),
+            actions: self.actions.clone(),
+        };
+        let runtime = ScriptRuntime::new(&script, timer, settings)
+            .map_err(|source| Error::LoadFailed { source })?;
+        let reloaded = self.loaded.is_some();
+        self.set_interrupt(Some(runtime.interrupt_handle()));
+        self.loaded = Some(Loaded { runtime, script });
+        self.next_step = Instant::now();
+        self.publish();
+        info!(
+            "{} the auto splitter",
+            if reloaded { "Reloaded" } else { "Loaded" }
+        );
+        Ok(())
+    }
+
+    fn step(&mut self) {
+        let Some(loaded) = &mut self.loaded else {
+            return;
+        };
+        let _ = self.deadlines.send(Some(Instant::now() + WATCHDOG_TIMEOUT));
+        let result = loaded.runtime.update();
+        let _ = self.deadlines.send(None);
+        match result {
+            Ok(tick_rate) => {
+                self.next_step += tick_rate;
+                // Settings are registered from `update`, usually the first ones
+                if loaded.runtime.user_settings().len() != self.published {
+                    self.publish();
+                }
+            }
+            Err(e) => {
+                error!("Unloaded the auto splitter after it failed: {e:?}");
+                self.loaded = None;
+                self.set_interrupt(None);
+                self.publish();
+            }
+        }
+    }
+
+    fn publish(&mut self) {
+        let settings = self
+            .loaded
+            .as_ref()
+            .map(|loaded| script_settings(&loaded.runtime))
+            .unwrap_or_default();
+        self.published = self
+            .loaded
+            .as_ref()
+            .map_or(0, |loaded| loaded.runtime.user_settings().len());
+        *self.settings.lock().unwrap_or_else(PoisonError::into_inner) = settings;
+    }
+
+    fn set_interrupt(&self, handle: Option<InterruptHandle>) {
+        *self
+            .interrupt
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = handle;
+    }
+}
+
+/// The settings `runtime` registered, with the values of its store over their
+/// defaults. Settings of a type not kept in the sidecar are left out.
+fn script_settings(runtime: &ScriptRuntime<Timer>) -> Vec<ScriptSetting> {
+    let store = runtime.settings_store();
+    runtime
+        .user_settings()
+        .iter()
+        .filter_map(|setting| {
+            let default = SettingValue::from_runtime(&setting.default_value)?;
+            let value = store
+                .get(&setting.key)
+                .and_then(SettingValue::from_runtime)
+                .filter(|value| value.same_kind(&default))
+                .unwrap_or_else(|| default.clone());
+            Some(ScriptSetting {
+                key: setting.key.to_string(),
+                description: setting.description.to_string(),
+                default,
+                value,
+            })
+        })
+        .collect()
+}
+
+/// Interrupts the script when an `update` runs past its deadline.
+fn watchdog(deadlines: &Receiver<Option<Instant>>, interrupt: &Mutex<Option<InterruptHandle>>) {
+    let mut deadline: Option<Instant> = None;
+    loop {
+        let next = match deadline {
+            None => deadlines.recv().ok(),
+            Some(at) => {
+                match deadlines.recv_timeout(at.saturating_duration_since(Instant::now())) {
+                    Ok(next) => Some(next),
+                    Err(RecvTimeoutError::Timeout) => {
+                        warn!("The auto splitter stopped responding, interrupting it");
+                        if let Some(handle) =
+                            &*interrupt.lock().unwrap_or_else(PoisonError::into_inner)
+                        {
+                            handle.interrupt();
+                        }
+                        Some(None)
+                    }
+                    Err(RecvTimeoutError::Disconnected) => None,
+                }
+            }
+        };
+        match next {
+            Some(next) => deadline = next,
+            None => return,
+        }
+    }
+}
+
+/// The timer as the script sees it. `SharedTimer` is an `Arc`, so the trait
+/// cannot be implemented on it directly.
+struct Timer {
+    timer: SharedTimer,
+    actions: ActionQueue,
+}
+
+impl AutoSplitTimer for Timer {
+    fn state(&self) -> TimerState {
+        match self
+            .timer
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .current_phase()
+        {
+            TimerPhase::NotRunning => TimerState::NotRunning,
+            TimerPhase::Running => TimerState::Running,
+            TimerPhase::Paused => TimerState::Paused,
+            TimerPhase::Ended => TimerState::Ended,
+        }
+    }
+
+    fn start(&mut self) {
+        self.timer
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .start();
+    }
+
+    fn split(&mut self) {
+        self.timer
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .split();
+    }
+
+    fn reset(&mut self) {
+        self.actions.send_scripted(TimerAction::Reset {
+            update_splits: true,
+        });
+    }
+
+    fn set_game_time(&mut self, time: time::Duration) {
+        self.timer
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_game_time(time.into());
+    }
+
+    fn pause_game_time(&mut self) {
+        self.timer
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pause_game_time();
+    }
+
+    fn resume_game_time(&mut self) {
+        self.timer
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .resume_game_time();
+    }
+
+    fn set_variable(&mut self, name: &str, value: &str) {
+        self.timer
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_custom_variable(name, value);
+    }
+
+    fn log(&mut self, message: fmt::Arguments<'_>) {
+        info!("Auto splitter: {message}");
+    }
+}
@@ -0,0 +1,211 @@
+//! Auto-splitter settings kept per run.
+//!
+//! Scripts register settings (which splits to trigger, mostly) that only make
+//! sense for one game, so the values are stored in the run's sidecar under the
+//! path of the script rather than in the config. When a script is loaded again
+//! the stored values are merged into the settings it registers: keys it no
+//! longer has are dropped, keys it added keep their defaults.
+//!
+//! The runtime (see `utils::auto_splitter`) only knows boolean settings so far;
+//! numbers and strings are kept in the sidecar for the scripts that will have
+//! them.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use livesplit_auto_splitting::{self as auto_splitting, SettingsStore};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::sidecar::RunSidecar;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum SettingValue {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl SettingValue {
+    /// Whether both values are of the same type of setting.
+    pub fn same_kind(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// The value of a setting of the runtime, if it is of a type kept here.
+    pub fn from_runtime(value: &auto_splitting::SettingValue) -> Option<Self> {
+        match value {
+            auto_splitting::SettingValue::Bool(value) => Some(Self::Bool(*value)),
+            _ => None,
+        }
+    }
+
+    fn to_runtime(&self) -> Option<auto_splitting::SettingValue> {
+        match self {
+            Self::Bool(value) => Some(auto_splitting::SettingValue::Bool(*value)),
+            Self::Number(_) | Self::String(_) => None,
+        }
+    }
+}
+
+/// Setting values by key.
+pub type SettingsMap = BTreeMap<String, SettingValue>;
+
+/// The settings store to load a script with, holding `settings`. Values of a
+/// type the runtime does not know are left out.
+pub fn to_store(settings: &SettingsMap) -> SettingsStore {
+    let mut store = SettingsStore::new();
+    for (key, value) in settings {
+        match value.to_runtime() {
+            Some(value) => store.set(key.as_str().into(), value),
+            None => debug!("Not passing auto-splitter setting {key}, the runtime has no such type"),
+        }
+    }
+    store
+}
+
+/// The settings a script registers with `defaults`, taking the stored value of
+/// every key still registered with the same type.
+pub fn merge(stored: &SettingsMap, defaults: &SettingsMap) -> SettingsMap {
+    for (key, value) in stored {
+        match defaults.get(key) {
+            None => debug!("Dropping auto-splitter setting {key}, no longer in the script"),
+            Some(default) if !default.same_kind(value) => {
+                debug!("Dropping auto-splitter setting {key}, its type changed");
+            }
+            Some(_) => {}
+        }
+    }
+    defaults
+        .iter()
+        .map(|(key, default)| {
+            let value = stored
+                .get(key)
+                .filter(|value| value.same_kind(default))
+                .unwrap_or(default);
+            (key.clone(), value.clone())
+        })
+        .collect()
+}
+
+/// Settings of `script` to apply once it is loaded for the run of `sidecar`.
+pub fn restore(sidecar: &RunSidecar, script: &Path, defaults: &SettingsMap) -> SettingsMap {
+    sidecar
+        .auto_splitter_settings
+        .get(script)
+        .map_or_else(|| defaults.clone(), |stored| merge(stored, defaults))
+}
+
+/// Records the settings of `script` after one of them changed.
+pub fn store(sidecar: &mut RunSidecar, script: &Path, settings: SettingsMap) {
+    if settings.is_empty() {
+        sidecar.auto_splitter_settings.remove(script);
+    } else {
+        sidecar
+            .auto_splitter_settings
+            .insert(script.to_owned(), settings);
+    }
+}
+
+#[cfg(test)]
+mod auto_splitter_settings_tests {
+    use super::*;
+
+    fn settings(entries: &[(&str, SettingValue)]) -> SettingsMap {
+        entries
+            .iter()
+            .map(|(key, value)| ((*key).to_owned(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn values_round_trip_through_the_sidecar() {
+        let script = Path::new("/splitters/game.wasm");
+        let values = settings(&[
+            ("split_boss", SettingValue::Bool(false)),
+            ("route", SettingValue::String("glitchless".to_owned())),
+            ("delay", SettingValue::Number(1.5)),
+            ("count", SettingValue::Number(3.0)),
+        ]);
+        let mut sidecar = RunSidecar::default();
+        store(&mut sidecar, script, values.clone());
+
+        let yaml = serde_yaml::to_string(&sidecar).unwrap();
+        let parsed: RunSidecar = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed, sidecar);
+        assert_eq!(restore(&parsed, script, &values), values);
+
+        // Written by hand: integers are numbers too, quoted numbers stay strings
+        let parsed: SettingsMap =
+            serde_yaml::from_str("a: true\nb: 2\nc: \"2\"\nd: text\n").unwrap();
+        assert_eq!(
+            parsed,
+            settings(&[
+                ("a", SettingValue::Bool(true)),
+                ("b", SettingValue::Number(2.0)),
+                ("c", SettingValue::String("2".to_owned())),
+                ("d", SettingValue::String("text".to_owned())),
+            ])
+        );
+    }
+
+    #[test]
+    fn only_values_the_runtime_knows_go_into_its_store() {
+        let store = to_store(&settings(&[
+            ("split_boss", SettingValue::Bool(false)),
+            ("route", SettingValue::String("glitchless".to_owned())),
+            ("delay", SettingValue::Number(1.5)),
+        ]));
+        let values: SettingsMap = store
+            .iter()
+            .filter_map(|(key, value)| Some((key.to_owned(), SettingValue::from_runtime(value)?)))
+            .collect();
+        assert_eq!(
+            values,
+            settings(&[("split_boss", SettingValue::Bool(false))])
+        );
+    }
+
+    #[test]
+    fn merge_follows_the_keys_of_the_script() {
+        let stored = settings(&[
+            ("split_boss", SettingValue::Bool(false)),
+            ("removed", SettingValue::Bool(true)),
+            ("retyped", SettingValue::Bool(true)),
+        ]);
+        let defaults = settings(&[
+            ("split_boss", SettingValue::Bool(true)),
+            ("added", SettingValue::Bool(true)),
+            ("retyped", SettingValue::String("any%".to_owned())),
+        ]);
+        assert_eq!(
+            merge(&stored, &defaults),
+            settings(&[
+                ("split_boss", SettingValue::Bool(false)),
+                ("added", SettingValue::Bool(true)),
+                ("retyped", SettingValue::String("any%".to_owned())),
+            ])
+        );
+    }
+
+    #[test]
+    fn settings_are_kept_per_script() {
+        let mut sidecar = RunSidecar::default();
+        let defaults = settings(&[("split", SettingValue::Bool(true))]);
+        store(
+            &mut sidecar,
+            Path::new("a.wasm"),
+            settings(&[("split", SettingValue::Bool(false))]),
+        );
+
+        assert_eq!(restore(&sidecar, Path::new("b.wasm"), &defaults), defaults);
+        assert_eq!(
+            restore(&sidecar, Path::new("a.wasm"), &defaults),
+            settings(&[("split", SettingValue::Bool(false))])
+        );
+
+        store(&mut sidecar, Path::new("a.wasm"), SettingsMap::new());
+        assert!(sidecar.auto_splitter_settings.is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+//! Starting and resetting the timer from the auto-splitter's game time.
+//!
+//! Some auto-splitters only drive the splits. With `auto-start`, game time
+//! starting to run while no attempt is going starts one; with `auto-reset`, game
+//! time going away during an attempt resets it.
+
+use livesplit_core::{Timer, TimerPhase};
+
+use crate::config::AutoSplitter;
+
+/// What the auto-splitter's game time looked like on one tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameTimeState {
+    pub phase: TimerPhase,
+    pub initialized: bool,
+    pub paused: bool,
+}
+
+impl GameTimeState {
+    pub fn of(timer: &Timer) -> Self {
+        Self {
+            phase: timer.current_phase(),
+            initialized: timer.is_game_time_initialized(),
+            paused: timer.is_game_time_paused(),
+        }
+    }
+
+    const fn game_time_running(self) -> bool {
+        self.initialized && !self.paused
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoAction {
+    Start,
+    Reset,
+}
+
+/// The action the change from `prev` to `new` calls for. Ticks where the phase
+/// itself changed are left alone: starting and resetting touch game time too,
+/// and must not trigger the opposite action.
+pub fn detect_action(
+    prev: GameTimeState,
+    new: GameTimeState,
+    flags: &AutoSplitter,
+) -> Option<AutoAction> {
+    if prev.phase != new.phase {
+        return None;
+    }
+    if new.phase == TimerPhase::NotRunning {
+        (flags.auto_start && !prev.game_time_running() && new.game_time_running())
+            .then_some(AutoAction::Start)
+    } else {
+        (flags.auto_reset && prev.initialized && !new.initialized).then_some(AutoAction::Reset)
+    }
+}
+
+#[cfg(test)]
+mod auto_start_tests {
+    use super::*;
+    use crate::utils::timer_actions::{TimerAction, apply};
+    use livesplit_core::{Run, Segment};
+
+    const BOTH: AutoSplitter = AutoSplitter {
+        auto_start: true,
+        auto_reset: true,
+        confirm_reset: false,
+        reset_cooldown_secs: 0,
+    };
+
+    fn state(phase: TimerPhase, initialized: bool, paused: bool) -> GameTimeState {
+        GameTimeState {
+            phase,
+            initialized,
+            paused,
+        }
+    }
+
+    #[test]
+    fn game_time_starting_to_run_starts_the_timer() {
+        let idle = state(TimerPhase::NotRunning, false, false);
+        let loading = state(TimerPhase::NotRunning, true, true);
+        let running = state(TimerPhase::NotRunning, true, false);
+
+        assert_eq!(detect_action(idle, running, &BOTH), Some(AutoAction::Start));
+        assert_eq!(
+            detect_action(loading, running, &BOTH),
+            Some(AutoAction::Start)
+        );
+        assert_eq!(detect_action(idle, loading, &BOTH), None);
+        assert_eq!(detect_action(running, running, &BOTH), None);
+    }
+
+    #[test]
+    fn game_time_going_away_resets_the_attempt() {
+        for phase in [TimerPhase::Running, TimerPhase::Paused, TimerPhase::Ended] {
+            assert_eq!(
+                detect_action(state(phase, true, false), state(phase, false, false), &BOTH),
+                Some(AutoAction::Reset)
+            );
+        }
+        // Pausing game time for a load is not a reset
+        let running = state(TimerPhase::Running, true, false);
+        let loading = state(TimerPhase::Running, true, true);
+        assert_eq!(detect_action(running, loading, &BOTH), None);
+    }
+
+    #[test]
+    fn phase_changes_never_trigger_actions() {
+        // Starting deinitializes game time, resetting initializes it again
+        let before_start = state(TimerPhase::NotRunning, true, false);
+        let after_start = state(TimerPhase::Running, false, false);
+        assert_eq!(detect_action(before_start, after_start, &BOTH), None);
+        assert_eq!(detect_action(after_start, before_start, &BOTH), None);
+    }
+
+    #[test]
+    fn disabled_flags_do_nothing() {
+        let flags = AutoSplitter::default();
+        assert_eq!(
+            detect_action(
+                state(TimerPhase::NotRunning, false, false),
+                state(TimerPhase::NotRunning, true, false),
+                &flags
+            ),
+            None
+        );
+        assert_eq!(
+            detect_action(
+                state(TimerPhase::Running, true, false),
+                state(TimerPhase::Running, false, false),
+                &flags
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn a_reset_arms_the_auto_start_again() {
+        let mut run = Run::new();
+        run.push_segment(Segment::new("S1"));
+        let mut timer = Timer::new(run).unwrap();
+        let mut prev = GameTimeState::of(&timer);
+        let mut observe = |timer: &Timer| {
+            let new = GameTimeState::of(timer);
+            let action = detect_action(prev, new, &BOTH);
+            prev = new;
+            action
+        };
+
+        timer.initialize_game_time();
+        assert_eq!(observe(&timer), Some(AutoAction::Start));
+        apply(&mut timer, TimerAction::Start);
+        assert_eq!(observe(&timer), None);
+        apply(
+            &mut timer,
+            TimerAction::Reset {
+                update_splits: false,
+            },
+        );
+        assert_eq!(observe(&timer), None);
+
+        timer.initialize_game_time();
+        assert_eq!(observe(&timer), Some(AutoAction::Start));
+    }
+}
@@ -0,0 +1,177 @@
+//! Detection of system clock jumps (suspend, NTP corrections) during an attempt:
+//! the wall clock moving by a different amount than the monotonic clock between
+//! two ticks.
+
+use std::time::{Instant, SystemTime};
+
+use livesplit_core::TimerPhase;
+use time::Duration;
+
+/// Default of `general: clock-jump-threshold-secs`.
+pub const DEFAULT_CLOCK_JUMP_THRESHOLD_SECS: f64 = 2.0;
+
+/// Both clocks read at the same tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSample {
+    pub wall: SystemTime,
+    pub mono: Instant,
+}
+
+impl ClockSample {
+    pub fn now() -> Self {
+        Self {
+            wall: SystemTime::now(),
+            mono: Instant::now(),
+        }
+    }
+}
+
+/// How far the wall clock moved beyond the monotonic clock from `prev` to
+/// `next` (negative when it went back), when that is more than `threshold`.
+pub fn clock_jump(prev: ClockSample, next: ClockSample, threshold: Duration) -> Option<Duration> {
+    let wall = match next.wall.duration_since(prev.wall) {
+        Ok(forward) => Duration::try_from(forward).unwrap_or(Duration::MAX),
+        Err(backward) => -Duration::try_from(backward.duration()).unwrap_or(Duration::MAX),
+    };
+    let divergence = wall - next.mono.saturating_duration_since(prev.mono);
+    (divergence.abs() > threshold).then_some(divergence)
+}
+
+/// A clock jump seen while the attempt was running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jump {
+    pub offset: Duration,
+    /// The first jump of the attempt, the one the user is told about.
+    pub first: bool,
+}
+
+/// Follows both clocks from tick to tick.
+#[derive(Debug, Default)]
+pub struct ClockJumpTracker {
+    last: Option<ClockSample>,
+    warned: bool,
+}
+
+impl ClockJumpTracker {
+    /// Feeds the clocks of this tick with the timer's phase. Jumps only count
+    /// while the timer runs; a new attempt starts without having warned.
+    pub fn observe(
+        &mut self,
+        sample: ClockSample,
+        phase: TimerPhase,
+        threshold: Duration,
+    ) -> Option<Jump> {
+        let prev = self.last.replace(sample)?;
+        if phase == TimerPhase::NotRunning {
+            self.warned = false;
+        }
+        if phase != TimerPhase::Running {
+            return None;
+        }
+        let offset = clock_jump(prev, sample, threshold)?;
+        let first = !self.warned;
+        self.warned = true;
+        Some(Jump { offset, first })
+    }
+}
+
+#[cfg(test)]
+mod clock_tests {
+    use super::*;
+    use std::time::Duration as StdDuration;
+
+    const THRESHOLD: Duration = Duration::seconds(2);
+
+    fn start() -> ClockSample {
+        ClockSample::now()
+    }
+
+    /// `start` after `mono` on the monotonic clock and `wall` on the wall clock.
+    fn later(start: ClockSample, mono: StdDuration, wall: Duration) -> ClockSample {
+        let wall = if wall.is_negative() {
+            start.wall - wall.unsigned_abs()
+        } else {
+            start.wall + wall.unsigned_abs()
+        };
+        ClockSample {
+            wall,
+            mono: start.mono + mono,
+        }
+    }
+
+    #[test]
+    fn suspend_moves_the_wall_clock_ahead() {
+        let start = start();
+        // A 16 ms tick during which the machine slept for ten minutes
+        let next = later(
+            start,
+            StdDuration::from_millis(16),
+            Duration::minutes(10) + Duration::milliseconds(16),
+        );
+        assert_eq!(
+            clock_jump(start, next, THRESHOLD),
+            Some(Duration::minutes(10))
+        );
+    }
+
+    #[test]
+    fn small_slews_are_ignored() {
+        let start = start();
+        let next = later(
+            start,
+            StdDuration::from_secs(1),
+            Duration::milliseconds(1_500),
+        );
+        assert_eq!(clock_jump(start, next, THRESHOLD), None);
+        let next = later(start, StdDuration::from_secs(1), Duration::ZERO);
+        assert_eq!(clock_jump(start, next, THRESHOLD), None);
+    }
+
+    #[test]
+    fn clock_going_back_is_a_negative_jump() {
+        let start = start();
+        let next = later(start, StdDuration::from_millis(16), Duration::seconds(-30));
+        let offset = clock_jump(start, next, THRESHOLD).unwrap();
+        assert!(offset.is_negative());
+        assert_eq!(offset, Duration::seconds(-30) - Duration::milliseconds(16));
+    }
+
+    #[test]
+    fn tracker_warns_once_per_running_attempt() {
+        let mut tracker = ClockJumpTracker::default();
+        let tick = StdDuration::from_millis(16);
+        let jump = Duration::seconds(5);
+        let mut sample = start();
+
+        assert_eq!(
+            tracker.observe(sample, TimerPhase::Running, THRESHOLD),
+            None
+        );
+        sample = later(sample, tick, jump);
+        let first = tracker
+            .observe(sample, TimerPhase::Running, THRESHOLD)
+            .unwrap();
+        assert!(first.first);
+        sample = later(sample, tick, jump);
+        let second = tracker
+            .observe(sample, TimerPhase::Running, THRESHOLD)
+            .unwrap();
+        assert!(!second.first);
+
+        // Paused and reset attempts have no real time to spoil
+        sample = later(sample, tick, jump);
+        assert_eq!(tracker.observe(sample, TimerPhase::Paused, THRESHOLD), None);
+        sample = later(sample, tick, jump);
+        assert_eq!(
+            tracker.observe(sample, TimerPhase::NotRunning, THRESHOLD),
+            None
+        );
+        sample = later(sample, tick, jump);
+        assert!(
+            tracker
+                .observe(sample, TimerPhase::Running, THRESHOLD)
+                .unwrap()
+                .first
+        );
+    }
+}
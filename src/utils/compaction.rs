@@ -0,0 +1,407 @@
+//! Automatic compaction of the timer when its window is too small for it, e.g.
+//! a strip at the edge of the screen.
+//!
+//! Parts of the timer are collapsed one step at a time in the configured order
+//! (`display.collapse-order`) until what is left fits the window, and are shown
+//! again as space returns. The splits are cut down to the current split and
+//! the ones around it rather than hidden; when nothing in the order is left,
+//! only the big timer stays. The decision only depends on the size available
+//! and the sizes the parts ask for, hidden ones included, so it does not flip
+//! back and forth as parts come and go.
+//!
+//! Focus mode takes precedence: compaction is off while it is on, so toggling
+//! it never fights the window size.
+
+use tracing::warn;
+
+use crate::utils::focus::{FocusMode, TimerComponent};
+
+/// Rows of the splits once they are cut down: the current split and the ones
+/// before and after it.
+pub const REDUCED_SPLITS: usize = 3;
+
+/// Steps collapsed when the config does not list any.
+pub const DEFAULT_COLLAPSE_ORDER: [&str; 4] =
+    ["info_rows", "additional_info", "run_info", "splits"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollapseStep {
+    /// Rows about the selected segment, left of the big timer.
+    InfoRows,
+    /// Rows under the timer.
+    AdditionalInfo,
+    /// Game and category above the splits.
+    RunInfo,
+    /// Cut the splits down to `REDUCED_SPLITS` rows.
+    Splits,
+}
+
+impl CollapseStep {
+    pub const ALL: [Self; 4] = [
+        Self::InfoRows,
+        Self::AdditionalInfo,
+        Self::RunInfo,
+        Self::Splits,
+    ];
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "info_rows" => Some(Self::InfoRows),
+            "additional_info" => Some(Self::AdditionalInfo),
+            "run_info" => Some(Self::RunInfo),
+            "splits" => Some(Self::Splits),
+            _ => None,
+        }
+    }
+
+    /// Parses the configured step ids, warning about (and skipping) unknown
+    /// ones. An empty list falls back to `DEFAULT_COLLAPSE_ORDER`.
+    pub fn parse_list(ids: &[String]) -> Vec<Self> {
+        if ids.is_empty() {
+            return DEFAULT_COLLAPSE_ORDER
+                .iter()
+                .filter_map(|id| Self::from_id(id))
+                .collect();
+        }
+
+        ids.iter()
+            .filter_map(|id| {
+                let step = Self::from_id(id);
+                if step.is_none() {
+                    warn!("Unknown step \"{id}\" in display.collapse-order, skipping it");
+                }
+                step
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Size {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Size {
+    pub const fn new(width: i32, height: i32) -> Self {
+        Self { width, height }
+    }
+}
+
+/// Sizes the parts of the timer ask for, whether they are shown or not.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NaturalSizes {
+    pub run_info: Size,
+    pub splits: Size,
+    /// The splits cut down to `REDUCED_SPLITS` rows.
+    pub reduced_splits: Size,
+    pub info_rows: Size,
+    /// The big timer and what is under it, right of the info rows.
+    pub timers: Size,
+    pub additional_info: Size,
+    /// Space between two parts stacked on top of each other.
+    pub spacing: i32,
+    /// Space around all of them.
+    pub margins: Size,
+}
+
+/// What is collapsed to fit the window. The default collapses nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Compaction {
+    info_rows: bool,
+    additional_info: bool,
+    run_info: bool,
+    reduced_splits: bool,
+    /// Nothing in the order was enough: only the big timer is left.
+    timer_only: bool,
+}
+
+impl Compaction {
+    pub const fn is_collapsed(self, step: CollapseStep) -> bool {
+        match step {
+            CollapseStep::InfoRows => self.info_rows,
+            CollapseStep::AdditionalInfo => self.additional_info,
+            CollapseStep::RunInfo => self.run_info,
+            CollapseStep::Splits => self.reduced_splits,
+        }
+    }
+
+    const fn collapse(&mut self, step: CollapseStep) {
+        match step {
+            CollapseStep::InfoRows => self.info_rows = true,
+            CollapseStep::AdditionalInfo => self.additional_info = true,
+            CollapseStep::RunInfo => self.run_info = true,
+            CollapseStep::Splits => self.reduced_splits = true,
+        }
+    }
+
+    pub const fn is_timer_only(self) -> bool {
+        self.timer_only
+    }
+
+    /// Whether the splits are cut down to `REDUCED_SPLITS` rows.
+    pub const fn reduces_splits(self) -> bool {
+        self.reduced_splits && !self.timer_only
+    }
+
+    /// What is collapsed, for the log.
+    pub fn describe(self) -> String {
+        if self.is_timer_only() {
+            return "only the big timer shown".to_owned();
+        }
+        let collapsed: Vec<&str> = CollapseStep::ALL
+            .iter()
+            .filter(|step| self.is_collapsed(**step))
+            .map(|step| match step {
+                CollapseStep::InfoRows => "info rows hidden",
+                CollapseStep::AdditionalInfo => "additional info hidden",
+                CollapseStep::RunInfo => "run info hidden",
+                CollapseStep::Splits => "splits reduced",
+            })
+            .collect();
+        if collapsed.is_empty() {
+            "nothing collapsed".to_owned()
+        } else {
+            collapsed.join(", ")
+        }
+    }
+
+    /// No compaction while focus mode is on, which hides what it wants itself.
+    pub const fn under(self, focus: FocusMode) -> Self {
+        if focus.is_active() {
+            Self {
+                info_rows: false,
+                additional_info: false,
+                run_info: false,
+                reduced_splits: false,
+                timer_only: false,
+            }
+        } else {
+            self
+        }
+    }
+
+    /// Whether `component` is left on screen. Focus mode decides for the
+    /// components it shows or hides on top of this.
+    pub const fn shows(self, component: TimerComponent) -> bool {
+        match component {
+            TimerComponent::BigTimer | TimerComponent::FocusLine => true,
+            _ if self.timer_only => false,
+            TimerComponent::RunInfo => !self.run_info,
+            TimerComponent::InfoRows => !self.info_rows,
+            TimerComponent::AdditionalInfo => !self.additional_info,
+            TimerComponent::Splits | TimerComponent::SegmentTimer => true,
+        }
+    }
+
+    /// Size of the timer with this compaction.
+    pub fn size(self, natural: &NaturalSizes) -> Size {
+        let shown = |component, size: Size| {
+            if self.shows(component) {
+                size
+            } else {
+                Size::default()
+            }
+        };
+        let splits = if self.reduces_splits() {
+            natural.reduced_splits
+        } else {
+            natural.splits
+        };
+        // The info rows sit left of the timers
+        let info_rows = shown(TimerComponent::InfoRows, natural.info_rows);
+        let timers = Size::new(
+            info_rows.width + natural.timers.width,
+            info_rows.height.max(natural.timers.height),
+        );
+        let stack = [
+            shown(TimerComponent::RunInfo, natural.run_info),
+            shown(TimerComponent::Splits, splits),
+            timers,
+            shown(TimerComponent::AdditionalInfo, natural.additional_info),
+        ];
+        let parts = stack.iter().filter(|size| size.height > 0).count() as i32;
+        Size::new(
+            stack
+                .iter()
+                .map(|size| size.width)
+                .max()
+                .unwrap_or_default()
+                + natural.margins.width,
+            stack.iter().map(|size| size.height).sum::<i32>()
+                + natural.spacing * (parts - 1).max(0)
+                + natural.margins.height,
+        )
+    }
+
+    fn fits(self, natural: &NaturalSizes, available: Size) -> bool {
+        let size = self.size(natural);
+        size.width <= available.width && size.height <= available.height
+    }
+}
+
+/// The least compaction, collapsing the steps of `order` one after the other,
+/// that fits the timer of `natural` sizes into `available`.
+pub fn compact(available: Size, natural: &NaturalSizes, order: &[CollapseStep]) -> Compaction {
+    let mut compaction = Compaction::default();
+    for step in order {
+        if compaction.fits(natural, available) {
+            return compaction;
+        }
+        compaction.collapse(*step);
+    }
+    if !compaction.fits(natural, available) {
+        compaction.timer_only = true;
+    }
+    compaction
+}
+
+#[cfg(test)]
+mod compaction_tests {
+    use super::*;
+
+    const ALL_COMPONENTS: [TimerComponent; 7] = [
+        TimerComponent::RunInfo,
+        TimerComponent::Splits,
+        TimerComponent::InfoRows,
+        TimerComponent::BigTimer,
+        TimerComponent::SegmentTimer,
+        TimerComponent::FocusLine,
+        TimerComponent::AdditionalInfo,
+    ];
+
+    /// 40 + 20 + 300 + 20 + 80 + 20 + 100 + 2 * 24 = 628 pixels high, and 408
+    /// wide with the info rows beside the timers.
+    fn natural() -> NaturalSizes {
+        NaturalSizes {
+            run_info: Size::new(200, 40),
+            splits: Size::new(300, 300),
+            reduced_splits: Size::new(300, 120),
+            info_rows: Size::new(160, 80),
+            timers: Size::new(200, 60),
+            additional_info: Size::new(250, 100),
+            spacing: 20,
+            margins: Size::new(48, 48),
+        }
+    }
+
+    fn default_order() -> Vec<CollapseStep> {
+        CollapseStep::parse_list(&[])
+    }
+
+    fn collapsed(compaction: Compaction) -> Vec<CollapseStep> {
+        CollapseStep::ALL
+            .into_iter()
+            .filter(|step| compaction.is_collapsed(*step))
+            .collect()
+    }
+
+    #[test]
+    fn steps_collapse_in_order_as_the_window_shrinks() {
+        let natural = natural();
+        let order = default_order();
+        let at = |height| compact(Size::new(600, height), &natural, &order);
+        assert_eq!(at(628), Compaction::default());
+        assert_eq!(Compaction::default().size(&natural), Size::new(408, 628));
+
+        // The info rows are taller than the timers beside them
+        assert_eq!(collapsed(at(627)), [CollapseStep::InfoRows]);
+        assert_eq!(at(627).size(&natural).height, 608);
+        assert_eq!(
+            collapsed(at(607)),
+            [CollapseStep::InfoRows, CollapseStep::AdditionalInfo]
+        );
+        assert_eq!(collapsed(at(488)).len(), 2);
+        assert_eq!(collapsed(at(487)).len(), 3);
+        assert_eq!(collapsed(at(428)).len(), 3);
+        assert_eq!(collapsed(at(427)).len(), 4);
+        assert!(at(427).reduces_splits());
+        assert!(!at(248).is_timer_only());
+        assert!(at(247).is_timer_only());
+        assert_eq!(at(247).size(&natural), Size::new(248, 108));
+
+        assert_eq!(at(628).describe(), "nothing collapsed");
+        assert_eq!(
+            at(487).describe(),
+            "info rows hidden, additional info hidden, run info hidden"
+        );
+        assert_eq!(at(247).describe(), "only the big timer shown");
+    }
+
+    #[test]
+    fn growing_the_window_again_expands_in_reverse() {
+        let natural = natural();
+        let order = default_order();
+        let mut previous = compact(Size::new(600, 0), &natural, &order);
+        assert!(previous.is_timer_only());
+        for height in 0..700 {
+            let compaction = compact(Size::new(600, height), &natural, &order);
+            // Never collapses more with more room
+            assert!(collapsed(compaction).len() <= collapsed(previous).len());
+            assert!(!compaction.is_timer_only() || previous.is_timer_only());
+            previous = compaction;
+        }
+        assert_eq!(previous, Compaction::default());
+    }
+
+    #[test]
+    fn narrow_windows_drop_the_info_rows_beside_the_timer() {
+        let natural = natural();
+        let compaction = compact(Size::new(400, 1000), &natural, &default_order());
+        assert_eq!(collapsed(compaction), [CollapseStep::InfoRows]);
+        assert_eq!(compaction.size(&natural).width, 348);
+        // Narrower than the splits: nothing but the timer is narrow enough
+        assert!(compact(Size::new(340, 1000), &natural, &default_order()).is_timer_only());
+    }
+
+    #[test]
+    fn the_order_comes_from_the_config() {
+        let order = CollapseStep::parse_list(&[
+            "splits".to_owned(),
+            "big_timer".to_owned(),
+            "run_info".to_owned(),
+        ]);
+        assert_eq!(order, [CollapseStep::Splits, CollapseStep::RunInfo]);
+
+        let natural = natural();
+        let compaction = compact(Size::new(600, 450), &natural, &order);
+        assert_eq!(collapsed(compaction), [CollapseStep::Splits]);
+        // Steps left out of the order are only collapsed with everything else
+        let compaction = compact(Size::new(600, 300), &natural, &order);
+        assert!(compaction.is_timer_only());
+        assert!(!compaction.is_collapsed(CollapseStep::InfoRows));
+        assert!(!compaction.shows(TimerComponent::InfoRows));
+    }
+
+    #[test]
+    fn only_the_big_timer_is_left_last() {
+        let timer_only = compact(Size::new(0, 0), &natural(), &default_order());
+        for component in ALL_COMPONENTS {
+            assert_eq!(
+                timer_only.shows(component),
+                matches!(
+                    component,
+                    TimerComponent::BigTimer | TimerComponent::FocusLine
+                ),
+                "{component:?}"
+            );
+        }
+        assert!(!timer_only.reduces_splits());
+    }
+
+    #[test]
+    fn focus_mode_takes_precedence() {
+        let timer_only = compact(Size::new(0, 0), &natural(), &default_order());
+        let focus = FocusMode::new(true);
+        assert_eq!(timer_only.under(focus), Compaction::default());
+        assert_eq!(timer_only.under(FocusMode::default()), timer_only);
+        // Focus mode alone decides what is shown
+        for component in ALL_COMPONENTS {
+            assert_eq!(
+                focus.shows(component) && timer_only.under(focus).shows(component),
+                focus.shows(component)
+            );
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::config::Config;
+use crate::formatters::TimeFormat;
 use livesplit_core::{Timer, analysis::sum_of_segments::best::calculate as calculate_sob};
 
 pub fn current_attempt_running_duration(timer: &Timer) -> time::Duration {
@@ -24,18 +25,48 @@ pub fn current_attempt_running_duration(timer: &Timer) -> time::Duration {
         .unwrap_or_default()
 }
 
-pub fn real_time_sob(timer: &Timer) -> time::Duration {
-    let mut predictions = vec![None; timer.run().len() + 1];
-    let predictions = &mut predictions[..];
-    calculate_sob(
+/// Best final time the attempt in progress can still reach: the sum of best from
+/// where it is, plus the time the live timer has already run past it.
+pub fn best_possible_time(timer: &Timer) -> time::Duration {
+    best_possible_from(
         timer.run().segments(),
-        predictions,
-        false,
-        true,
+        timer.current_split_index().unwrap_or(0),
+        current_attempt_running_duration(timer),
         timer.current_timing_method(),
     )
     .unwrap_or_default()
-    .to_duration()
+}
+
+/// `best_possible_time` of an attempt at `current_index` with `attempt_time` on
+/// the clock: the current segment ends no earlier than the last split that was not
+/// skipped plus its best, nor than now, and the golds of the rest follow it.
+/// `None` while a segment left has no best segment time.
+pub fn best_possible_from(
+    segments: &[livesplit_core::Segment],
+    current_index: usize,
+    attempt_time: time::Duration,
+    method: livesplit_core::TimingMethod,
+) -> Option<time::Duration> {
+    let last_split = last_split_index(segments, current_index, method);
+    let start = last_split.map_or(0, |index| index + 1);
+    let baseline = last_split
+        .and_then(|index| segments[index].split_time()[method])
+        .map_or(time::Duration::ZERO, |time| time.to_duration());
+
+    let rest = &segments[start.min(segments.len())..];
+    let mut predictions = vec![None; rest.len() + 1];
+    let best_rest = calculate_sob(rest, &mut predictions, false, false, method)?.to_duration();
+
+    // Only time lost is added, being ahead does not make the golds ahead faster
+    let lost = predictions
+        .get(current_index + 1 - start)
+        .copied()
+        .flatten()
+        .filter(|_| current_index < segments.len())
+        .map_or(time::Duration::ZERO, |end| {
+            (attempt_time - baseline - end.time.to_duration()).max(time::Duration::ZERO)
+        });
+    Some(baseline + best_rest + lost)
 }
 
 pub fn best_segment_duration(segment: &livesplit_core::Segment, timer: &Timer) -> time::Duration {
@@ -138,7 +169,302 @@ pub fn best_comparison_values(timer: &Timer, index: usize) -> (time::Duration, t
     }
 }
 
+/// Computes, for every segment, the best split time ever reached at that point:
+/// the minimum cumulative split time over the attempt history and the PB.
+///
+/// Attempts are followed through the segment histories in order. An attempt stops
+/// counting at the first segment it has no history entry for (it was reset there),
+/// and a skipped split carries its time over to the next recorded one.
+/// Segments nobody ever reached yield `None`.
+pub fn best_achieved_split_times(
+    segments: &[livesplit_core::Segment],
+    method: livesplit_core::TimingMethod,
+) -> Vec<Option<time::Duration>> {
+    use std::collections::HashMap;
+
+    let mut running: HashMap<i32, time::Duration> = HashMap::new();
+    let mut best = Vec::with_capacity(segments.len());
+
+    for (index, segment) in segments.iter().enumerate() {
+        let mut next = HashMap::new();
+        let mut best_here: Option<time::Duration> = None;
+
+        for (attempt, segment_time) in segment.segment_history().iter() {
+            let previous = if index == 0 {
+                time::Duration::ZERO
+            } else if let Some(previous) = running.get(attempt) {
+                *previous
+            } else {
+                continue;
+            };
+
+            let cumulative = match segment_time[method] {
+                Some(segment_time) => {
+                    let cumulative = previous
+                        .checked_add(segment_time.to_duration())
+                        .unwrap_or(previous);
+                    best_here = Some(best_here.map_or(cumulative, |b| b.min(cumulative)));
+                    cumulative
+                }
+                None => previous,
+            };
+            next.insert(*attempt, cumulative);
+        }
+
+        if let Some(pb) = segment.personal_best_split_time()[method] {
+            let pb = pb.to_duration();
+            best_here = Some(best_here.map_or(pb, |b| b.min(pb)));
+        }
+
+        running = next;
+        best.push(best_here);
+    }
+
+    best
+}
+
+/// Sum of best pace: for every segment, the total of the best segment times up to
+/// and including it.
+///
+/// A segment without a best segment time leaves the sum unknown, so it and every
+/// segment after it yield `None`.
+pub fn sum_of_best_split_times(
+    segments: &[livesplit_core::Segment],
+    method: livesplit_core::TimingMethod,
+) -> Vec<Option<time::Duration>> {
+    let mut total = Some(time::Duration::ZERO);
+    segments
+        .iter()
+        .map(|segment| {
+            total = total
+                .zip(segment.best_segment_time()[method])
+                .and_then(|(total, best)| total.checked_add(best.to_duration()));
+            total
+        })
+        .collect()
+}
+
+/// Delta against `comparison` of the attempt on `timer`. This is the delta at
+/// the last split, or the live delta once the current segment runs past the
+/// comparison's split. `None` without a split to compare.
+pub fn timer_delta(timer: &Timer, comparison: &str) -> Option<time::Duration> {
+    let method = timer.current_timing_method();
+    let segments = timer.run().segments();
+    let index = timer.current_split_index()?.min(segments.len());
+    let last = segments[..index].iter().rev().find_map(|segment| {
+        let split = segment.split_time()[method]?;
+        let compared = segment.comparison(comparison)[method]?;
+        Some(split.to_duration() - compared.to_duration())
+    });
+    let live = segments.get(index).and_then(|segment| {
+        let current = timer.snapshot().current_time()[method]?;
+        let compared = segment.comparison(comparison)[method]?;
+        Some(current.to_duration() - compared.to_duration())
+    });
+    live.filter(|&live| live > time::Duration::ZERO && last.is_none_or(|last| live > last))
+        .or(last)
+}
+
+/// `run` with a new custom comparison `name` holding the split times of the
+/// `source` comparison, in both timing methods, to be tweaked from there.
+///
+/// Fails when `name` is already a comparison or starts with `[Race]`.
+pub fn duplicate_comparison(
+    run: livesplit_core::Run,
+    source: &str,
+    name: &str,
+) -> Result<livesplit_core::Run, livesplit_core::run::ComparisonError> {
+    let times: Vec<livesplit_core::Time> = run
+        .segments()
+        .iter()
+        .map(|segment| segment.comparison(source))
+        .collect();
+    add_comparison(run, name, &times)
+}
+
+/// `run` with a new custom comparison `name` holding the goal at `percentile` of
+/// each segment's history (see `crate::utils::percentiles`), in both timing
+/// methods. Splits from the first segment without history on are left empty.
+///
+/// Fails when `name` is already a comparison or starts with `[Race]`.
+pub fn goal_comparison(
+    run: livesplit_core::Run,
+    percentile: u8,
+    name: &str,
+) -> Result<livesplit_core::Run, livesplit_core::run::ComparisonError> {
+    use crate::utils::percentiles::percentile_splits;
+    use livesplit_core::{Time, TimingMethod};
+
+    let real = percentile_splits(&run, TimingMethod::RealTime, percentile);
+    let game = percentile_splits(&run, TimingMethod::GameTime, percentile);
+    let times: Vec<Time> = real
+        .into_iter()
+        .zip(game)
+        .map(|(real, game)| Time::new().with_real_time(real).with_game_time(game))
+        .collect();
+    add_comparison(run, name, &times)
+}
+
+/// `run` with a new custom comparison `name` holding `times`, one per segment,
+/// set through the run editor.
+fn add_comparison(
+    mut run: livesplit_core::Run,
+    name: &str,
+    times: &[livesplit_core::Time],
+) -> Result<livesplit_core::Run, livesplit_core::run::ComparisonError> {
+    use livesplit_core::{RunEditor, TimingMethod};
+
+    let Ok(mut editor) = RunEditor::new(run.clone()) else {
+        // Nothing to set without segments
+        run.add_custom_comparison(name)?;
+        return Ok(run);
+    };
+    editor.add_comparison(name)?;
+    for method in [TimingMethod::RealTime, TimingMethod::GameTime] {
+        editor.select_timing_method(method);
+        for (index, time) in times.iter().enumerate() {
+            editor.select_only(index);
+            editor
+                .active_segment()
+                .set_comparison_time(name, time[method]);
+        }
+    }
+    Ok(editor.close())
+}
+
+/// Second line of `comparison` in the comparison picker: when the PB was set and
+/// its final time, the sum of best segments, how many attempts the averages are
+/// taken over, or the final split time of any other comparison. Empty when the
+/// run has nothing to show for it.
+pub fn comparison_subtitle(
+    run: &livesplit_core::Run,
+    comparison: &str,
+    method: livesplit_core::TimingMethod,
+    format: &TimeFormat,
+) -> String {
+    use livesplit_core::comparison::{average_segments, best_segments, personal_best};
+
+    let final_split = |comparison: &str| {
+        run.segments()
+            .last()
+            .and_then(|last| last.comparison(comparison)[method])
+    };
+    match comparison {
+        personal_best::NAME => {
+            let Some(time) = final_split(comparison) else {
+                return String::new();
+            };
+            let time = format.format_time_span(&time);
+            crate::utils::pb_dates::pb_attempt(run, method)
+                .and_then(|attempt| crate::utils::golds::attempt_date(run, attempt))
+                .map_or_else(|| time.clone(), |date| format!("{date} · {time}"))
+        }
+        best_segments::NAME => run
+            .segments()
+            .iter()
+            .try_fold(time::Duration::ZERO, |sum, segment| {
+                Some(sum + segment.best_segment_time()[method]?.to_duration())
+            })
+            .map(|sum| format!("Sum of best {}", format.format_duration(&sum)))
+            .unwrap_or_default(),
+        average_segments::NAME => {
+            let attempts = run
+                .attempt_history()
+                .iter()
+                .filter(|attempt| {
+                    run.segments().iter().any(|segment| {
+                        segment
+                            .segment_history()
+                            .get(attempt.index())
+                            .is_some_and(|time| time[method].is_some())
+                    })
+                })
+                .count();
+            match attempts {
+                0 => String::new(),
+                1 => "Over 1 attempt".to_owned(),
+                n => format!("Over {n} attempts"),
+            }
+        }
+        _ => final_split(comparison)
+            .map(|time| format!("Final split {}", format.format_time_span(&time)))
+            .unwrap_or_default(),
+    }
+}
+
+/// Sets the segment time at `index` of the comparison `name` to `time`, for
+/// `method`. Like the run editor does for the PB, the splits after it move along
+/// so their segment times stay the same.
+pub fn set_comparison_segment_time(
+    run: &mut livesplit_core::Run,
+    name: &str,
+    method: livesplit_core::TimingMethod,
+    index: usize,
+    time: livesplit_core::TimeSpan,
+) {
+    let segments = run.segments_mut();
+    if index >= segments.len() {
+        return;
+    }
+    let previous = segments[..index]
+        .iter()
+        .rev()
+        .find_map(|segment| segment.comparison(name)[method])
+        .unwrap_or_default();
+    let split = previous + time;
+    let shift = split - segments[index].comparison(name)[method].unwrap_or(previous);
+    segments[index].comparison_mut(name)[method] = Some(split);
+    for segment in &mut segments[index + 1..] {
+        if let Some(later) = &mut segment.comparison_mut(name)[method] {
+            *later += shift;
+        }
+    }
+}
+
+/// Whether anything in `run` was ever timed in game time: a personal best or best
+/// segment, or a segment of the history.
+pub fn run_has_game_time(run: &livesplit_core::Run) -> bool {
+    use livesplit_core::TimingMethod::GameTime;
+    run.segments().iter().any(|segment| {
+        segment.personal_best_split_time()[GameTime].is_some()
+            || segment.best_segment_time()[GameTime].is_some()
+            || segment
+                .segment_history()
+                .iter()
+                .any(|(_, time)| time[GameTime].is_some())
+    })
+}
+
+/// Formats a delta with the split format and the delta precision of the config.
 pub fn format_signed(diff: time::Duration, config: &Config) -> String {
+    format_delta(
+        diff,
+        &config.format.split,
+        config.display.delta_digits,
+        config.display.delta_drop_zero_tenths,
+    )
+}
+
+/// Formats a delta with its sign at `digits` decimal places (0-3), truncated
+/// toward zero. The sign is the one of the exact delta, so a small loss reads
+/// "-0.0" and never "+0.0". With `drop_zero_tenths`, deltas under a tenth of a
+/// second read "±0.0".
+pub fn format_delta(
+    diff: time::Duration,
+    format: &TimeFormat,
+    digits: u8,
+    drop_zero_tenths: bool,
+) -> String {
+    let mut format = format.clone();
+    format.show_decimals = digits > 0;
+    format.set_decimal_places(digits.max(1));
+
+    let abs = diff.abs();
+    if drop_zero_tenths && abs < time::Duration::milliseconds(100) {
+        let zero = format.format_segment_time(&time::Duration::ZERO);
+        return format!("±{zero}");
+    }
+
     let sign = if diff.is_positive() {
         "+"
     } else if diff.is_negative() {
@@ -146,22 +472,25 @@ pub fn format_signed(diff: time::Duration, config: &Config) -> String {
     } else {
         "~"
     };
-    let abs = diff.abs();
-    let formatted = config.format.split.format_segment_time(&abs);
+    let formatted = format.format_segment_time(&abs);
     format!("{sign}{formatted}")
 }
 
+/// Style class of a split. Segments flagged `excluded` are never golds.
 pub fn classify_split_label(
     comparison_duration: time::Duration,
     split_duration: time::Duration,
     diff: time::Duration,
     goldsplit_duration: time::Duration,
     running: bool,
+    excluded: bool,
 ) -> &'static str {
     if running {
         return "";
     }
-    if split_duration < goldsplit_duration || goldsplit_duration == time::Duration::ZERO {
+    if !excluded
+        && (split_duration < goldsplit_duration || goldsplit_duration == time::Duration::ZERO)
+    {
         "goldsplit"
     } else if diff.is_negative() {
         if split_duration <= comparison_duration {
@@ -217,6 +546,187 @@ pub fn previous_split_combined_gold_and_prev_comparison(
     (previous_split_time, combined_gold, previous_comparison_time)
 }
 
+/// Time that could be saved on the segment at `index` by matching its gold: the
+/// comparison's segment duration minus the combined gold (including directly
+/// preceding skipped segments). Segments flagged in `excluded` have nothing to save.
+pub fn possible_time_save(timer: &Timer, index: usize, excluded: &[bool]) -> time::Duration {
+    let segments = timer.run().segments();
+    if index >= segments.len() || excluded.get(index).copied().unwrap_or(false) {
+        return time::Duration::ZERO;
+    }
+
+    let (_, combined_gold, previous_comparison_time) =
+        previous_split_combined_gold_and_prev_comparison(timer, index);
+    let current_comparison_time = segment_comparison_time(&segments[index], timer);
+
+    current_comparison_time
+        .checked_sub(previous_comparison_time)
+        .unwrap_or_default()
+        .checked_sub(combined_gold)
+        .unwrap_or_default()
+}
+
+/// Index of the last split before `current_index` that was not skipped.
+fn last_split_index(
+    segments: &[livesplit_core::Segment],
+    current_index: usize,
+    method: livesplit_core::TimingMethod,
+) -> Option<usize> {
+    segments[..current_index.min(segments.len())]
+        .iter()
+        .rposition(|segment| segment.split_time()[method].is_some())
+}
+
+/// Time spent in the current segment: `attempt_time` minus the last split time
+/// that was not skipped, or the attempt time itself before the first split.
+/// Never negative, so it stays at zero while a negative run offset counts down.
+pub fn segment_elapsed(
+    segments: &[livesplit_core::Segment],
+    current_index: usize,
+    attempt_time: time::Duration,
+    method: livesplit_core::TimingMethod,
+) -> time::Duration {
+    let baseline = last_split_index(segments, current_index, method)
+        .and_then(|index| segments[index].split_time()[method])
+        .map_or(time::Duration::ZERO, |time| time.to_duration());
+    (attempt_time - baseline).max(time::Duration::ZERO)
+}
+
+/// How long `comparison` took for the current segment, measured from the same
+/// split `segment_elapsed` starts at, so skipped segments are included.
+pub fn segment_comparison_duration(
+    segments: &[livesplit_core::Segment],
+    current_index: usize,
+    comparison: &str,
+    method: livesplit_core::TimingMethod,
+) -> Option<time::Duration> {
+    let end = segments
+        .get(current_index)?
+        .comparison_timing_method(comparison, method)?
+        .to_duration();
+    let start = match last_split_index(segments, current_index, method) {
+        Some(index) => segments[index]
+            .comparison_timing_method(comparison, method)?
+            .to_duration(),
+        None => time::Duration::ZERO,
+    };
+    Some(end - start)
+}
+
+/// Elapsed time of the current segment and the current comparison's time for it.
+pub fn current_segment_times(timer: &Timer) -> (time::Duration, Option<time::Duration>) {
+    let method = timer.current_timing_method();
+    let segments = timer.run().segments();
+    let Some(index) = timer.current_split_index() else {
+        return (time::Duration::ZERO, None);
+    };
+    let attempt_time = timer.snapshot().current_time()[method]
+        .map_or(time::Duration::ZERO, |time| time.to_duration());
+    (
+        segment_elapsed(segments, index, attempt_time, method),
+        segment_comparison_duration(segments, index, timer.current_comparison(), method),
+    )
+}
+
+/// Where the personal best run was at some point of its attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PbPosition {
+    /// In the segment at this index.
+    Segment(usize),
+    /// Past its final split.
+    Finished,
+}
+
+/// Where the PB was `elapsed` into its attempt, from its split times (`None` for
+/// skipped splits). The PB is in the segment after the last split it reached by
+/// then; `None` without a PB.
+pub fn pb_position(
+    pb_splits: &[Option<time::Duration>],
+    elapsed: time::Duration,
+) -> Option<PbPosition> {
+    let final_time = pb_splits.last().copied().flatten()?;
+    if elapsed >= final_time {
+        return Some(PbPosition::Finished);
+    }
+    let timed: Vec<(usize, time::Duration)> = pb_splits
+        .iter()
+        .enumerate()
+        .filter_map(|(index, split)| Some((index, (*split)?)))
+        .collect();
+    // Split times only grow, so the splits reached are a prefix
+    let reached = timed.partition_point(|&(_, split)| split <= elapsed);
+    Some(PbPosition::Segment(
+        reached.checked_sub(1).map_or(0, |last| timed[last].0 + 1),
+    ))
+}
+
+/// Split times of the PB of the timer's run in its timing method.
+pub fn pb_split_times(timer: &Timer) -> Vec<Option<time::Duration>> {
+    let method = timer.current_timing_method();
+    timer
+        .run()
+        .segments()
+        .iter()
+        .map(|segment| segment.personal_best_split_time()[method].map(|time| time.to_duration()))
+        .collect()
+}
+
+#[cfg(test)]
+mod format_delta_tests {
+    use super::*;
+    use time::Duration;
+
+    fn delta(millis: i64, digits: u8, drop_zero_tenths: bool) -> String {
+        format_delta(
+            Duration::milliseconds(millis),
+            &TimeFormat::default(),
+            digits,
+            drop_zero_tenths,
+        )
+    }
+
+    #[test]
+    fn digits_truncate_toward_zero() {
+        let cases = [
+            (1_278, 0, "+1"),
+            (1_278, 1, "+1.2"),
+            (1_278, 2, "+1.27"),
+            (1_278, 3, "+1.278"),
+            (-1_278, 1, "-1.2"),
+            (75_550, 1, "+1:15.5"),
+        ];
+        for (millis, digits, expected) in cases {
+            assert_eq!(
+                delta(millis, digits, false),
+                expected,
+                "{millis} ms at {digits}"
+            );
+        }
+    }
+
+    #[test]
+    fn small_deltas_keep_their_sign() {
+        assert_eq!(delta(-40, 1, false), "-0.0");
+        assert_eq!(delta(40, 1, false), "+0.0");
+        assert_eq!(delta(-40, 2, false), "-0.04");
+        assert_eq!(delta(0, 1, false), "~0.0");
+    }
+
+    #[test]
+    fn dropping_zero_tenths_only_affects_sub_tenth_deltas() {
+        assert_eq!(delta(-40, 1, true), "±0.0");
+        assert_eq!(delta(99, 2, true), "±0.00");
+        assert_eq!(delta(0, 1, true), "±0.0");
+        assert_eq!(delta(100, 1, true), "+0.1");
+        assert_eq!(delta(-100, 2, true), "-0.10");
+    }
+
+    #[test]
+    fn out_of_range_digits_are_clamped() {
+        assert_eq!(delta(1_278, 7, false), "+1.278");
+    }
+}
+
 #[cfg(test)]
 mod classify_split_labels_tests {
     use super::*;
@@ -229,7 +739,7 @@ mod classify_split_labels_tests {
         let diff = Duration::ZERO;
         let gold = Duration::ZERO;
 
-        let class = classify_split_label(comparison, split_duration, diff, gold, false);
+        let class = classify_split_label(comparison, split_duration, diff, gold, false, false);
         assert!(class == "goldsplit", "Expected goldsplit: got {class:?}",);
     }
 
@@ -240,7 +750,7 @@ mod classify_split_labels_tests {
         let diff = Duration::seconds(-2);
         let gold = Duration::seconds(9);
 
-        let class = classify_split_label(comparison, split_duration, diff, gold, false);
+        let class = classify_split_label(comparison, split_duration, diff, gold, false, false);
         assert!(class == "goldsplit", "Expected goldsplit: got {class:?}",);
     }
 
@@ -252,13 +762,33 @@ mod classify_split_labels_tests {
         let diff = Duration::ZERO;
         let gold = Duration::ZERO;
 
-        let class = classify_split_label(comparison, split_duration, diff, gold, false);
+        let class = classify_split_label(comparison, split_duration, diff, gold, false, false);
         assert!(
             class == "goldsplit",
             "Expected goldsplit when gold duration is zero and not running: got {class:?}",
         );
     }
 
+    #[test]
+    fn classify_excluded_segments_never_gold() {
+        let comparison = Duration::seconds(10);
+        let split_duration = Duration::seconds(8);
+        let diff = Duration::seconds(-2);
+        let gold = Duration::seconds(9);
+
+        let class = classify_split_label(comparison, split_duration, diff, gold, false, true);
+        assert_eq!(class, "greensplit");
+        let class = classify_split_label(
+            comparison,
+            split_duration,
+            diff,
+            Duration::ZERO,
+            false,
+            true,
+        );
+        assert_eq!(class, "greensplit");
+    }
+
     #[test]
     fn classify_gainedred_when_not_running_and_behind_and_ahead_comparison() {
         let comparison = Duration::seconds(10);
@@ -266,7 +796,7 @@ mod classify_split_labels_tests {
         let diff = Duration::seconds(1);
         let gold = Duration::seconds(8);
 
-        let class = classify_split_label(comparison, split_duration, diff, gold, false);
+        let class = classify_split_label(comparison, split_duration, diff, gold, false, false);
         assert!(
             class == "gainedredsplit",
             "Expected redsplit when behind and gaining: got {class:?}",
@@ -280,7 +810,7 @@ mod classify_split_labels_tests {
         let diff = Duration::seconds(1);
         let gold = Duration::seconds(9);
 
-        let class = classify_split_label(comparison, split_duration, diff, gold, false);
+        let class = classify_split_label(comparison, split_duration, diff, gold, false, false);
         assert!(
             class == "redsplit",
             "Expected redsplit when behind and not gaining: got {class:?}",
@@ -294,7 +824,7 @@ mod classify_split_labels_tests {
         let diff = Duration::seconds(-1);
         let gold = Duration::seconds(8);
 
-        let class = classify_split_label(comparison, split_duration, diff, gold, false);
+        let class = classify_split_label(comparison, split_duration, diff, gold, false, false);
         assert!(
             class == "greensplit",
             "Expected greensplit when ahead and not losing against comparison_duration: got {class:?}",
@@ -308,7 +838,7 @@ mod classify_split_labels_tests {
         let diff = Duration::seconds(-1); // still ahead overall vs segment comparison target
         let gold = Duration::seconds(8);
 
-        let class = classify_split_label(comparison, split_duration, diff, gold, false);
+        let class = classify_split_label(comparison, split_duration, diff, gold, false, false);
         assert!(
             class == "lostgreensplit",
             "Expected lostgreensplit when ahead (negative diff) but split exceeds comparison_duration: got {class:?}",
@@ -322,7 +852,7 @@ mod classify_split_labels_tests {
         let diff = Duration::ZERO;
         let gold = Duration::seconds(5);
 
-        let class = classify_split_label(comparison, split_duration, diff, gold, false);
+        let class = classify_split_label(comparison, split_duration, diff, gold, false, false);
         assert!(
             class.is_empty(),
             "Expected no red/green class when diff is zero: got {class:?}",
@@ -333,13 +863,10 @@ mod classify_split_labels_tests {
 #[cfg(test)]
 mod skipped_segments_context_tests {
     use super::*;
-    use livesplit_core::{Run, Segment, Time, TimeSpan, Timer};
+    use crate::test_support::real;
+    use livesplit_core::{Run, Segment, Timer};
     use time::Duration;
 
-    fn time_rt(seconds: i64) -> Time {
-        Time::new().with_real_time(Some(TimeSpan::from_seconds(seconds as f64)))
-    }
-
     #[test]
     fn index_1_with_prev_0_skipped_prev_time_zero_and_gold_is_prev_plus_current() {
         // Setup: 2 segments, segment 0 skipped (no split), segment 1 current.
@@ -350,12 +877,12 @@ mod skipped_segments_context_tests {
         run.set_category_name("Any%");
 
         let mut s0 = Segment::new("S0");
-        s0.set_best_segment_time(time_rt(1));
+        s0.set_best_segment_time(real(1.0));
         // Skipped: leave split time empty (ZERO)
 
         let mut s1 = Segment::new("S1");
-        s1.set_best_segment_time(time_rt(2));
-        s1.set_personal_best_split_time(time_rt(25)); // PB cumulative time at segment 1
+        s1.set_best_segment_time(real(2.0));
+        s1.set_personal_best_split_time(real(25.0)); // PB cumulative time at segment 1
 
         run.push_segment(s0);
         run.push_segment(s1);
@@ -400,16 +927,16 @@ mod skipped_segments_context_tests {
         run.set_category_name("Any%");
 
         let mut s0 = Segment::new("S0");
-        s0.set_best_segment_time(time_rt(1));
+        s0.set_best_segment_time(real(1.0));
         // skipped
 
         let mut s1 = Segment::new("S1");
-        s1.set_best_segment_time(time_rt(2));
+        s1.set_best_segment_time(real(2.0));
         // skipped
 
         let mut s2 = Segment::new("S2");
-        s2.set_best_segment_time(time_rt(3));
-        s2.set_personal_best_split_time(time_rt(55)); // cumulative PB time at segment 2
+        s2.set_best_segment_time(real(3.0));
+        s2.set_personal_best_split_time(real(55.0)); // cumulative PB time at segment 2
 
         run.push_segment(s0);
         run.push_segment(s1);
@@ -456,17 +983,17 @@ mod skipped_segments_context_tests {
         run.set_category_name("Any%");
 
         let mut s0 = Segment::new("S0");
-        s0.set_best_segment_time(time_rt(1));
-        s0.set_personal_best_split_time(time_rt(10));
-        s0.set_split_time(time_rt(10)); // non-skipped
+        s0.set_best_segment_time(real(1.0));
+        s0.set_personal_best_split_time(real(10.0));
+        s0.set_split_time(real(10.0)); // non-skipped
 
         let mut s1 = Segment::new("S1");
-        s1.set_best_segment_time(time_rt(2));
+        s1.set_best_segment_time(real(2.0));
         // skipped: leave split time empty (ZERO)
 
         let mut s2 = Segment::new("S2");
-        s2.set_best_segment_time(time_rt(3));
-        s2.set_personal_best_split_time(time_rt(55));
+        s2.set_best_segment_time(real(3.0));
+        s2.set_personal_best_split_time(real(55.0));
 
         run.push_segment(s0);
         run.push_segment(s1);
@@ -503,3 +1030,674 @@ mod skipped_segments_context_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod best_achieved_pace_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{Segment, Time, TimingMethod};
+    use time::Duration;
+
+    #[test]
+    fn best_pace_at_split_3_comes_from_a_non_pb_attempt() {
+        // Attempt 1 is the PB: 10 / 20 / 30 / 40
+        // Attempt 2 reset after split 3 but was ahead there: 9 / 19 / 27
+        // Attempt 3 died on split 1
+        let mut segments: Vec<Segment> = ["S1", "S2", "S3", "S4"]
+            .into_iter()
+            .map(Segment::new)
+            .collect();
+
+        for (index, segment) in segments.iter_mut().enumerate() {
+            segment.set_personal_best_split_time(real(10.0 * (index + 1) as f64));
+            segment.segment_history_mut().insert(1, real(10.0));
+        }
+        segments[0].segment_history_mut().insert(2, real(9.0));
+        segments[1].segment_history_mut().insert(2, real(10.0));
+        segments[2].segment_history_mut().insert(2, real(8.0));
+        segments[0].segment_history_mut().insert(3, real(12.0));
+
+        let best = best_achieved_split_times(&segments, TimingMethod::RealTime);
+
+        assert_eq!(
+            best,
+            vec![
+                Some(Duration::seconds(9)),
+                Some(Duration::seconds(19)),
+                Some(Duration::seconds(27)),
+                Some(Duration::seconds(40)),
+            ]
+        );
+    }
+
+    #[test]
+    fn skipped_split_carries_into_the_next_recorded_time() {
+        let mut segments: Vec<Segment> = ["S1", "S2", "S3"].into_iter().map(Segment::new).collect();
+        segments[0].segment_history_mut().insert(1, real(10.0));
+        segments[1].segment_history_mut().insert(1, Time::new());
+        segments[2].segment_history_mut().insert(1, real(15.0));
+
+        let best = best_achieved_split_times(&segments, TimingMethod::RealTime);
+
+        assert_eq!(
+            best,
+            vec![
+                Some(Duration::seconds(10)),
+                None,
+                Some(Duration::seconds(25))
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_history_falls_back_to_pb_or_none() {
+        let mut segments: Vec<Segment> = ["S1", "S2"].into_iter().map(Segment::new).collect();
+        segments[1].set_personal_best_split_time(real(42.0));
+
+        let best = best_achieved_split_times(&segments, TimingMethod::RealTime);
+        assert_eq!(best, vec![None, Some(Duration::seconds(42))]);
+
+        assert!(best_achieved_split_times(&[], TimingMethod::RealTime).is_empty());
+    }
+
+    #[test]
+    fn attempts_that_start_mid_run_are_ignored() {
+        // Imported segment history may hold entries for later segments only.
+        let mut segments: Vec<Segment> = ["S1", "S2"].into_iter().map(Segment::new).collect();
+        segments[0].segment_history_mut().insert(1, real(10.0));
+        segments[1].segment_history_mut().insert(1, real(10.0));
+        segments[1].segment_history_mut().insert(-1, real(1.0));
+
+        let best = best_achieved_split_times(&segments, TimingMethod::RealTime);
+        assert_eq!(
+            best,
+            vec![Some(Duration::seconds(10)), Some(Duration::seconds(20))]
+        );
+    }
+}
+
+#[cfg(test)]
+mod sob_drift_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{Segment, TimingMethod};
+    use time::Duration;
+
+    fn segments(golds: &[Option<i64>]) -> Vec<Segment> {
+        golds
+            .iter()
+            .map(|gold| {
+                let mut segment = Segment::new("S");
+                if let Some(seconds) = gold {
+                    segment.set_best_segment_time(real(*seconds as f64));
+                }
+                segment
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pace_adds_up_the_golds() {
+        let pace = sum_of_best_split_times(
+            &segments(&[Some(10), Some(20), Some(5)]),
+            TimingMethod::RealTime,
+        );
+        assert_eq!(
+            pace,
+            vec![
+                Some(Duration::seconds(10)),
+                Some(Duration::seconds(30)),
+                Some(Duration::seconds(35)),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_gold_leaves_the_rest_unknown() {
+        let pace = sum_of_best_split_times(
+            &segments(&[Some(10), None, Some(5)]),
+            TimingMethod::RealTime,
+        );
+        assert_eq!(pace, vec![Some(Duration::seconds(10)), None, None]);
+        // Golds of the other timing method do not count
+        assert_eq!(
+            sum_of_best_split_times(&segments(&[Some(10)]), TimingMethod::GameTime),
+            vec![None]
+        );
+    }
+
+    #[test]
+    fn empty_run_has_no_pace() {
+        assert!(sum_of_best_split_times(&[], TimingMethod::RealTime).is_empty());
+    }
+}
+
+#[cfg(test)]
+mod duplicate_comparison_tests {
+    use super::*;
+    use livesplit_core::run::ComparisonError;
+    use livesplit_core::{Run, Segment, Time, TimeSpan, TimingMethod};
+
+    fn run() -> Run {
+        let mut run = Run::new();
+        for (name, real, game) in [("S1", 10.0, 9.0), ("S2", 25.0, 22.0)] {
+            let mut segment = Segment::new(name);
+            segment.set_personal_best_split_time(
+                Time::new()
+                    .with_real_time(Some(TimeSpan::from_seconds(real)))
+                    .with_game_time(Some(TimeSpan::from_seconds(game))),
+            );
+            run.push_segment(segment);
+        }
+        run
+    }
+
+    fn seconds(run: &Run, comparison: &str, method: TimingMethod) -> Vec<Option<f64>> {
+        run.segments()
+            .iter()
+            .map(|segment| segment.comparison(comparison)[method].map(|t| t.total_seconds()))
+            .collect()
+    }
+
+    #[test]
+    fn copy_holds_the_source_times_in_both_methods() {
+        let run = duplicate_comparison(run(), "Personal Best", "Target").unwrap();
+
+        assert!(run.custom_comparisons().iter().any(|name| name == "Target"));
+        assert_eq!(
+            seconds(&run, "Target", TimingMethod::RealTime),
+            [Some(10.0), Some(25.0)]
+        );
+        assert_eq!(
+            seconds(&run, "Target", TimingMethod::GameTime),
+            [Some(9.0), Some(22.0)]
+        );
+        // The source is left alone
+        assert_eq!(
+            seconds(&run, "Personal Best", TimingMethod::RealTime),
+            [Some(10.0), Some(25.0)]
+        );
+    }
+
+    #[test]
+    fn taken_and_race_names_are_rejected() {
+        assert!(matches!(
+            duplicate_comparison(run(), "Personal Best", "Personal Best"),
+            Err(ComparisonError::DuplicateName)
+        ));
+        assert!(matches!(
+            duplicate_comparison(run(), "Personal Best", "[Race] Target"),
+            Err(ComparisonError::NameStartsWithRace)
+        ));
+    }
+
+    #[test]
+    fn goal_comparison_holds_the_percentile_splits_in_both_methods() {
+        let mut run = run();
+        // Three attempts: the medians are 10s and 15s real, 9s and 14s game
+        for (attempt, (first, second)) in [(8.0, 15.0), (10.0, 14.0), (12.0, 17.0)]
+            .into_iter()
+            .enumerate()
+        {
+            for (segment, real) in run.segments_mut().iter_mut().zip([first, second]) {
+                segment.segment_history_mut().insert(
+                    attempt as i32 + 1,
+                    Time::new()
+                        .with_real_time(Some(TimeSpan::from_seconds(real)))
+                        .with_game_time(Some(TimeSpan::from_seconds(real - 1.0))),
+                );
+            }
+        }
+        let run = goal_comparison(run, 25, "Goal (p25)").unwrap();
+        assert_eq!(
+            seconds(&run, "Goal (p25)", TimingMethod::RealTime),
+            [Some(10.0), Some(25.0)]
+        );
+        assert_eq!(
+            seconds(&run, "Goal (p25)", TimingMethod::GameTime),
+            [Some(9.0), Some(23.0)]
+        );
+        assert!(matches!(
+            goal_comparison(run, 25, "Goal (p25)"),
+            Err(ComparisonError::DuplicateName)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod comparison_segment_time_tests {
+    use super::*;
+    use livesplit_core::{Run, Segment, TimeSpan, TimingMethod};
+
+    const TARGETS: &str = "Targets";
+
+    /// A run whose "Targets" comparison has the given real time splits.
+    fn run(splits: &[Option<f64>]) -> Run {
+        let mut run = Run::new();
+        run.add_custom_comparison(TARGETS).unwrap();
+        for (index, split) in splits.iter().enumerate() {
+            let mut segment = Segment::new(format!("S{index}"));
+            segment.comparison_mut(TARGETS).real_time = split.map(TimeSpan::from_seconds);
+            run.push_segment(segment);
+        }
+        run
+    }
+
+    fn splits(run: &Run) -> Vec<Option<f64>> {
+        run.segments()
+            .iter()
+            .map(|segment| {
+                segment.comparison(TARGETS)[TimingMethod::RealTime].map(|t| t.total_seconds())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn later_splits_keep_their_segment_times() {
+        let mut run = run(&[Some(10.0), Some(25.0), Some(40.0)]);
+        let time = TimeSpan::from_seconds(20.0);
+        set_comparison_segment_time(&mut run, TARGETS, TimingMethod::RealTime, 1, time);
+        assert_eq!(splits(&run), [Some(10.0), Some(30.0), Some(45.0)]);
+        // The PB is left alone
+        assert!(
+            run.segments()
+                .iter()
+                .all(|segment| segment.personal_best_split_time().real_time.is_none())
+        );
+    }
+
+    #[test]
+    fn segments_after_a_skipped_split_start_from_the_last_time() {
+        let mut run = run(&[Some(10.0), None, Some(40.0), Some(50.0)]);
+        let time = TimeSpan::from_seconds(5.0);
+        set_comparison_segment_time(&mut run, TARGETS, TimingMethod::RealTime, 2, time);
+        assert_eq!(splits(&run), [Some(10.0), None, Some(15.0), Some(25.0)]);
+
+        // Filling in the skipped split moves the rest by its segment time
+        set_comparison_segment_time(&mut run, TARGETS, TimingMethod::RealTime, 1, time);
+        assert_eq!(
+            splits(&run),
+            [Some(10.0), Some(15.0), Some(20.0), Some(30.0)]
+        );
+    }
+}
+
+#[cfg(test)]
+mod comparison_subtitle_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{Run, Segment, Time, TimingMethod};
+
+    const METHOD: TimingMethod = TimingMethod::RealTime;
+
+    fn format() -> TimeFormat {
+        TimeFormat::new(false, true, true, true, 1, false)
+    }
+
+    /// Two segments with a PB of 25s and golds of 9s and 14s, the PB set in
+    /// attempt 2 on 2024-05-04 after a reset attempt 1.
+    fn run() -> Run {
+        let mut run = Run::new();
+        for (name, pb, gold) in [("S1", 10.0, 9.0), ("S2", 25.0, 14.0)] {
+            let mut segment = Segment::new(name);
+            segment.set_personal_best_split_time(real(pb));
+            segment.set_best_segment_time(real(gold));
+            run.push_segment(segment);
+        }
+        run.segments_mut()[0]
+            .segment_history_mut()
+            .insert(1, real(12.0));
+        for (segment, time) in run.segments_mut().iter_mut().zip([10.0, 15.0]) {
+            segment.segment_history_mut().insert(2, real(time));
+        }
+        let ended = livesplit_core::AtomicDateTime::new(
+            time::Date::from_calendar_date(2024, time::Month::May, 4)
+                .unwrap()
+                .with_hms(12, 0, 0)
+                .unwrap()
+                .assume_utc(),
+            true,
+        );
+        run.add_attempt_with_index(Time::new(), 1, None, None, None);
+        run.add_attempt_with_index(real(25.0), 2, None, Some(ended), None);
+        run
+    }
+
+    fn subtitle(run: &Run, comparison: &str) -> String {
+        comparison_subtitle(run, comparison, METHOD, &format())
+    }
+
+    #[test]
+    fn each_kind_of_comparison_has_its_context() {
+        let mut run = duplicate_comparison(run(), "Personal Best", "Target").unwrap();
+        assert_eq!(subtitle(&run, "Personal Best"), "2024-05-04 · 25.0");
+        assert_eq!(subtitle(&run, "Best Segments"), "Sum of best 23.0");
+        assert_eq!(subtitle(&run, "Average Segments"), "Over 2 attempts");
+        assert_eq!(subtitle(&run, "Target"), "Final split 25.0");
+
+        // An undated PB still shows its time
+        run.clear_history();
+        assert_eq!(subtitle(&run, "Personal Best"), "25.0");
+        assert_eq!(subtitle(&run, "Average Segments"), "");
+    }
+
+    #[test]
+    fn missing_data_leaves_the_subtitle_empty() {
+        let mut run = run();
+        // No time in the other timing method
+        assert_eq!(
+            comparison_subtitle(&run, "Personal Best", TimingMethod::GameTime, &format()),
+            ""
+        );
+        run.segments_mut()[1].set_best_segment_time(Time::new());
+        run.segments_mut()[1].set_personal_best_split_time(Time::new());
+        assert_eq!(subtitle(&run, "Best Segments"), "");
+        assert_eq!(subtitle(&run, "Personal Best"), "");
+        assert_eq!(subtitle(&run, "Latest Run"), "");
+        assert_eq!(subtitle(&Run::new(), "Personal Best"), "");
+    }
+}
+
+#[cfg(test)]
+mod game_time_tests {
+    use super::*;
+    use livesplit_core::{Run, Segment, Time, TimeSpan};
+
+    #[test]
+    fn game_time_anywhere_in_the_run_counts() {
+        let mut run = Run::new();
+        let mut segment = Segment::new("S1");
+        segment.set_personal_best_split_time(
+            Time::new().with_real_time(Some(TimeSpan::from_seconds(10.0))),
+        );
+        run.push_segment(segment);
+        run.push_segment(Segment::new("S2"));
+        assert!(!run_has_game_time(&run));
+
+        // Only an old attempt of the last segment was timed in game time
+        run.segments_mut()[1].segment_history_mut().insert(
+            4,
+            Time::new().with_game_time(Some(TimeSpan::from_seconds(8.0))),
+        );
+        assert!(run_has_game_time(&run));
+    }
+}
+
+#[cfg(test)]
+mod possible_time_save_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{Run, Segment};
+    use time::Duration;
+
+    // Golds 10s/20s, PB segments 12s/25s
+    fn timer() -> Timer {
+        let mut run = Run::new();
+        for (name, gold, pb) in [("A", 10, 12), ("B", 20, 37)] {
+            let mut segment = Segment::new(name);
+            segment.set_best_segment_time(real(gold as f64));
+            segment.set_personal_best_split_time(real(pb as f64));
+            run.push_segment(segment);
+        }
+        Timer::new(run).expect("timer")
+    }
+
+    #[test]
+    fn time_save_is_comparison_segment_minus_gold() {
+        let timer = timer();
+        assert_eq!(possible_time_save(&timer, 0, &[]), Duration::seconds(2));
+        // A has no split time yet, so B is measured together with it: 37s - (10s + 20s)
+        assert_eq!(possible_time_save(&timer, 1, &[]), Duration::seconds(7));
+    }
+
+    #[test]
+    fn excluded_and_out_of_range_segments_have_no_time_save() {
+        let timer = timer();
+        assert_eq!(
+            possible_time_save(&timer, 1, &[false, true]),
+            Duration::ZERO
+        );
+        assert_eq!(
+            possible_time_save(&timer, 0, &[false, true]),
+            Duration::seconds(2)
+        );
+        assert_eq!(possible_time_save(&timer, 5, &[]), Duration::ZERO);
+    }
+}
+
+#[cfg(test)]
+mod segment_timer_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{Segment, Time, TimingMethod};
+    use time::Duration;
+
+    const PB: &str = "Personal Best";
+
+    /// Segments with PB splits every 10s, split at the given times (`None` skips).
+    fn segments(splits: &[Option<i64>]) -> Vec<Segment> {
+        (0..4)
+            .map(|i| {
+                let mut segment = Segment::new(format!("S{i}"));
+                segment.set_personal_best_split_time(real(10.0 * (i + 1) as f64));
+                if let Some(Some(split)) = splits.get(i as usize) {
+                    segment.set_split_time(real(*split as f64));
+                }
+                segment
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_segment_counts_from_the_start() {
+        let segments = segments(&[]);
+        let method = TimingMethod::RealTime;
+        assert_eq!(
+            segment_elapsed(&segments, 0, Duration::seconds(7), method),
+            Duration::seconds(7)
+        );
+        assert_eq!(
+            segment_comparison_duration(&segments, 0, PB, method),
+            Some(Duration::seconds(10))
+        );
+    }
+
+    #[test]
+    fn negative_offsets_hold_the_segment_timer_at_zero() {
+        let segments = segments(&[]);
+        assert_eq!(
+            segment_elapsed(&segments, 0, Duration::seconds(-3), TimingMethod::RealTime),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn later_segments_count_from_the_previous_split() {
+        let segments = segments(&[Some(12), Some(21)]);
+        let method = TimingMethod::RealTime;
+        assert_eq!(
+            segment_elapsed(&segments, 2, Duration::seconds(25), method),
+            Duration::seconds(4)
+        );
+        assert_eq!(
+            segment_comparison_duration(&segments, 2, PB, method),
+            Some(Duration::seconds(10))
+        );
+    }
+
+    #[test]
+    fn skipped_splits_extend_the_segment_back_to_the_last_split() {
+        let one_skipped = segments(&[Some(12), None]);
+        let method = TimingMethod::RealTime;
+        assert_eq!(
+            segment_elapsed(&one_skipped, 2, Duration::seconds(31), method),
+            Duration::seconds(19)
+        );
+        // Compared against the PB from S0 to S2
+        assert_eq!(
+            segment_comparison_duration(&one_skipped, 2, PB, method),
+            Some(Duration::seconds(20))
+        );
+
+        // Everything before skipped: counted from the start
+        let all_skipped = segments(&[None, None]);
+        assert_eq!(
+            segment_elapsed(&all_skipped, 2, Duration::seconds(31), method),
+            Duration::seconds(31)
+        );
+    }
+
+    #[test]
+    fn missing_comparison_times_give_no_comparison() {
+        let mut segments = segments(&[Some(12)]);
+        segments[0].set_personal_best_split_time(Time::new());
+        assert_eq!(
+            segment_comparison_duration(&segments, 1, PB, TimingMethod::RealTime),
+            None
+        );
+    }
+}
+
+#[cfg(test)]
+mod pb_position_tests {
+    use super::*;
+    use time::Duration;
+
+    fn splits(seconds: &[Option<i64>]) -> Vec<Option<Duration>> {
+        seconds
+            .iter()
+            .map(|split| split.map(Duration::seconds))
+            .collect()
+    }
+
+    #[test]
+    fn position_is_the_segment_after_the_last_split_reached() {
+        let pb = splits(&[Some(60), Some(150), Some(200)]);
+        assert_eq!(
+            pb_position(&pb, Duration::seconds(90)),
+            Some(PbPosition::Segment(1))
+        );
+        // Exactly at a split, the PB has moved on to the next segment
+        assert_eq!(
+            pb_position(&pb, Duration::seconds(150)),
+            Some(PbPosition::Segment(2))
+        );
+    }
+
+    #[test]
+    fn before_the_first_split_is_the_first_segment() {
+        let pb = splits(&[Some(60), Some(150), Some(200)]);
+        assert_eq!(
+            pb_position(&pb, Duration::ZERO),
+            Some(PbPosition::Segment(0))
+        );
+        // Negative offsets count down before the start
+        assert_eq!(
+            pb_position(&pb, Duration::seconds(-5)),
+            Some(PbPosition::Segment(0))
+        );
+    }
+
+    #[test]
+    fn past_the_final_time_the_pb_had_finished() {
+        let pb = splits(&[Some(60), Some(150), Some(200)]);
+        assert_eq!(
+            pb_position(&pb, Duration::seconds(200)),
+            Some(PbPosition::Finished)
+        );
+        assert_eq!(
+            pb_position(&pb, Duration::hours(1)),
+            Some(PbPosition::Finished)
+        );
+    }
+
+    #[test]
+    fn skipped_splits_are_passed_over() {
+        let pb = splits(&[Some(60), None, Some(200)]);
+        // Somewhere in the skipped segment or the one after
+        assert_eq!(
+            pb_position(&pb, Duration::seconds(100)),
+            Some(PbPosition::Segment(1))
+        );
+        assert_eq!(pb_position(&splits(&[None, None]), Duration::ZERO), None);
+        assert_eq!(pb_position(&[], Duration::ZERO), None);
+    }
+}
+
+#[cfg(test)]
+mod best_possible_time_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{Segment, Time, TimingMethod};
+    use time::Duration;
+
+    /// Three segments with 10s golds, split at the given times (`None` skips).
+    fn segments(splits: &[Option<i64>]) -> Vec<Segment> {
+        (0..3)
+            .map(|i| {
+                let mut segment = Segment::new(format!("S{i}"));
+                segment.set_best_segment_time(real(10.0));
+                if let Some(Some(split)) = splits.get(i) {
+                    segment.set_split_time(real(*split as f64));
+                }
+                segment
+            })
+            .collect()
+    }
+
+    fn best(splits: &[Option<i64>], current: usize, elapsed: i64) -> Option<Duration> {
+        best_possible_from(
+            &segments(splits),
+            current,
+            Duration::seconds(elapsed),
+            TimingMethod::RealTime,
+        )
+    }
+
+    #[test]
+    fn before_the_start_it_is_the_sum_of_best() {
+        assert_eq!(best(&[], 0, 0), Some(Duration::seconds(30)));
+    }
+
+    #[test]
+    fn time_lost_at_a_split_is_not_counted_again_live() {
+        // Golds 10s + 10s, A split at 15s, 22s on the clock
+        assert_eq!(
+            best_possible_from(
+                &segments(&[Some(15)])[..2],
+                1,
+                Duration::seconds(22),
+                TimingMethod::RealTime
+            ),
+            Some(Duration::seconds(25))
+        );
+        // S0 split 5s behind its gold, S1 not yet behind
+        assert_eq!(best(&[Some(15)], 1, 22), Some(Duration::seconds(35)));
+        // S1 now 3s past its gold too
+        assert_eq!(best(&[Some(15)], 1, 28), Some(Duration::seconds(38)));
+    }
+
+    #[test]
+    fn skipped_segments_keep_their_golds_until_the_next_split() {
+        assert_eq!(best(&[Some(10), None], 2, 25), Some(Duration::seconds(30)));
+        assert_eq!(best(&[Some(10), None], 2, 35), Some(Duration::seconds(35)));
+    }
+
+    #[test]
+    fn a_finished_attempt_is_its_final_time() {
+        assert_eq!(
+            best(&[Some(12), Some(25), Some(33)], 3, 33),
+            Some(Duration::seconds(33))
+        );
+    }
+
+    #[test]
+    fn a_missing_gold_ahead_leaves_it_unknown() {
+        let mut segments = segments(&[Some(15)]);
+        segments[2].set_best_segment_time(Time::new());
+        assert_eq!(
+            best_possible_from(&segments, 1, Duration::seconds(22), TimingMethod::RealTime),
+            None
+        );
+    }
+}
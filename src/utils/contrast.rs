@@ -0,0 +1,270 @@
+//! Contrast between text and background colors, after WCAG 2.
+//!
+//! Colors that fall short of a ratio are nudged in lightness, keeping their hue
+//! and saturation, towards black on light backgrounds and towards white on dark
+//! ones.
+
+/// Contrast asked of normal text by WCAG level AA.
+pub const MIN_CONTRAST: f64 = 4.5;
+
+/// Lightness moved on each step while looking for enough contrast.
+const LIGHTNESS_STEP: f64 = 0.01;
+
+/// An opaque sRGB color, channels from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+}
+
+impl Rgb {
+    pub const BLACK: Self = Self::new(0.0, 0.0, 0.0);
+    pub const WHITE: Self = Self::new(1.0, 1.0, 1.0);
+
+    pub const fn new(r: f64, g: f64, b: f64) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Parses `#rgb` or `#rrggbb`.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits = hex.strip_prefix('#')?;
+        let channel = |text: &str| {
+            u8::from_str_radix(text, 16)
+                .ok()
+                .map(|v| f64::from(v) / 255.0)
+        };
+        match digits.len() {
+            3 => {
+                let mut channels = digits.chars().map(|c| channel(&c.to_string().repeat(2)));
+                Some(Self::new(
+                    channels.next()??,
+                    channels.next()??,
+                    channels.next()??,
+                ))
+            }
+            6 if digits.is_ascii() => Some(Self::new(
+                channel(&digits[0..2])?,
+                channel(&digits[2..4])?,
+                channel(&digits[4..6])?,
+            )),
+            _ => None,
+        }
+    }
+
+    /// `#rrggbb`, channels rounded to the nearest of 256 steps.
+    pub fn to_hex(self) -> String {
+        let byte = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            byte(self.r),
+            byte(self.g),
+            byte(self.b)
+        )
+    }
+
+    /// Hue (0 to 1), saturation and lightness.
+    fn to_hsl(self) -> (f64, f64, f64) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let lightness = (max + min) / 2.0;
+        let chroma = max - min;
+        if chroma == 0.0 {
+            return (0.0, 0.0, lightness);
+        }
+        let saturation = chroma / (1.0 - (2.0 * lightness - 1.0).abs());
+        let hue = if max == self.r {
+            ((self.g - self.b) / chroma).rem_euclid(6.0)
+        } else if max == self.g {
+            (self.b - self.r) / chroma + 2.0
+        } else {
+            (self.r - self.g) / chroma + 4.0
+        };
+        (hue / 6.0, saturation, lightness)
+    }
+
+    fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Self {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let sector = hue * 6.0;
+        let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+        let (r, g, b) = match sector as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = lightness - chroma / 2.0;
+        Self::new(r + m, g + m, b + m)
+    }
+}
+
+/// The forms `parse_hex_rgba` reads, for messages about colors that are not.
+pub const HEX_COLOR_FORMS: &str = "#rgb, #rgba, #rrggbb or #rrggbbaa";
+
+/// Parses `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` into red, green, blue and
+/// alpha bytes. Colors without alpha are opaque.
+pub fn parse_hex_rgba(hex: &str) -> Option<[u8; 4]> {
+    let digits = hex.strip_prefix('#')?;
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let byte = |text: &str| u8::from_str_radix(text, 16).ok();
+    let channels: Vec<u8> = match digits.len() {
+        3 | 4 => digits
+            .chars()
+            .map(|c| byte(&c.to_string().repeat(2)))
+            .collect::<Option<_>>()?,
+        6 | 8 => (0..digits.len())
+            .step_by(2)
+            .map(|i| byte(&digits[i..i + 2]))
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    Some([
+        channels[0],
+        channels[1],
+        channels[2],
+        channels.get(3).copied().unwrap_or(u8::MAX),
+    ])
+}
+
+/// Relative luminance, 0 for black and 1 for white.
+pub fn relative_luminance(color: Rgb) -> f64 {
+    let linear = |v: f64| {
+        if v <= 0.040_45 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(color.r) + 0.7152 * linear(color.g) + 0.0722 * linear(color.b)
+}
+
+/// Contrast ratio between two colors, from 1 (same luminance) to 21.
+pub fn contrast_ratio(a: Rgb, b: Rgb) -> f64 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// `foreground`, darkened or lightened until it reaches `min` against
+/// `background`. Black or white are the last resort.
+pub fn ensure_contrast(foreground: Rgb, background: Rgb, min: f64) -> Rgb {
+    if contrast_ratio(foreground, background) >= min {
+        return foreground;
+    }
+    let darken = contrast_ratio(Rgb::BLACK, background) >= contrast_ratio(Rgb::WHITE, background);
+    let step = if darken {
+        -LIGHTNESS_STEP
+    } else {
+        LIGHTNESS_STEP
+    };
+    let (hue, saturation, mut lightness) = foreground.to_hsl();
+    while (0.0..=1.0).contains(&(lightness + step)) {
+        lightness += step;
+        let candidate = Rgb::from_hsl(hue, saturation, lightness);
+        if contrast_ratio(candidate, background) >= min {
+            return candidate;
+        }
+    }
+    if darken { Rgb::BLACK } else { Rgb::WHITE }
+}
+
+#[cfg(test)]
+mod contrast_tests {
+    use super::*;
+
+    fn hex(hex: &str) -> Rgb {
+        Rgb::from_hex(hex).unwrap()
+    }
+
+    #[test]
+    fn hex_colors_with_and_without_alpha() {
+        assert_eq!(parse_hex_rgba("#26a269"), Some([0x26, 0xa2, 0x69, 0xff]));
+        assert_eq!(parse_hex_rgba("#26A26980"), Some([0x26, 0xa2, 0x69, 0x80]));
+        assert_eq!(parse_hex_rgba("#f0a"), Some([0xff, 0x00, 0xaa, 0xff]));
+        assert_eq!(parse_hex_rgba("#f0a8"), Some([0xff, 0x00, 0xaa, 0x88]));
+        for invalid in ["26a269", "#26a26", "#ggg", "#26a2690", "red", "#", "#+1a"] {
+            assert_eq!(parse_hex_rgba(invalid), None, "{invalid}");
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 0.01,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn hex_colors_parse_and_print() {
+        assert_eq!(hex("#fff"), Rgb::WHITE);
+        assert_eq!(hex("#33d17a").to_hex(), "#33d17a");
+        assert_eq!(Rgb::from_hex("33d17a"), None);
+        assert_eq!(Rgb::from_hex("#33d17"), None);
+        assert_eq!(Rgb::from_hex("#gggggg"), None);
+    }
+
+    #[test]
+    fn ratios_match_known_pairs() {
+        assert_close(relative_luminance(Rgb::WHITE), 1.0);
+        assert_close(relative_luminance(Rgb::BLACK), 0.0);
+        assert_close(contrast_ratio(Rgb::BLACK, Rgb::WHITE), 21.0);
+        assert_close(contrast_ratio(Rgb::WHITE, Rgb::BLACK), 21.0);
+        // The lightest gray passing AA on white, and the one just failing it
+        assert_close(contrast_ratio(hex("#767676"), Rgb::WHITE), 4.54);
+        assert_close(contrast_ratio(hex("#777777"), Rgb::WHITE), 4.48);
+        assert_close(contrast_ratio(hex("#ed333b"), hex("#222226")), 3.88);
+    }
+
+    #[test]
+    fn hsl_round_trips() {
+        for color in ["#33d17a", "#ed333b", "#e5a50a", "#3584e4", "#808080"] {
+            let (h, s, l) = hex(color).to_hsl();
+            assert_eq!(Rgb::from_hsl(h, s, l).to_hex(), color);
+        }
+    }
+
+    #[test]
+    fn readable_colors_are_left_alone() {
+        let red = hex("#c01c28");
+        assert_eq!(ensure_contrast(red, Rgb::WHITE, MIN_CONTRAST), red);
+    }
+
+    #[test]
+    fn green_on_light_is_darkened_until_readable() {
+        let green = hex("#33d17a");
+        let background = hex("#fafafb");
+        let adjusted = ensure_contrast(green, background, MIN_CONTRAST);
+        assert!(contrast_ratio(adjusted, background) >= MIN_CONTRAST);
+        assert!(relative_luminance(adjusted) < relative_luminance(green));
+        // Same hue, just darker
+        assert_close(adjusted.to_hsl().0, green.to_hsl().0);
+        // Nudged no further than needed
+        let (h, s, l) = adjusted.to_hsl();
+        let lighter = Rgb::from_hsl(h, s, l + LIGHTNESS_STEP);
+        assert!(contrast_ratio(lighter, background) < MIN_CONTRAST);
+    }
+
+    #[test]
+    fn red_on_dark_is_lightened_until_readable() {
+        let red = hex("#ed333b");
+        let background = hex("#222226");
+        let adjusted = ensure_contrast(red, background, MIN_CONTRAST);
+        assert!(contrast_ratio(adjusted, background) >= MIN_CONTRAST);
+        assert!(relative_luminance(adjusted) > relative_luminance(red));
+    }
+
+    #[test]
+    fn unreachable_ratios_end_on_black_or_white() {
+        assert_eq!(
+            ensure_contrast(hex("#808080"), Rgb::WHITE, 22.0),
+            Rgb::BLACK
+        );
+        assert_eq!(
+            ensure_contrast(hex("#808080"), Rgb::BLACK, 22.0),
+            Rgb::WHITE
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! "Fill down" of one time column of the segments table.
+
+/// How the filled value carries over from row to row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillMode {
+    /// Every row gets the value as is (segment and best segment times).
+    Copy,
+    /// Every row gets the time before it plus the value (split times, which
+    /// must keep growing).
+    Accumulate,
+}
+
+/// Times to set below row `source`, whose time is `value` milliseconds, as
+/// (row index, milliseconds).
+///
+/// `current` holds the column's times for every row (`None` when unset). With
+/// `selection`, only the selected rows below `source` are filled; when
+/// accumulating, the rows left alone still count as the time before the next one.
+pub fn plan_fill_down(
+    source: usize,
+    value: i64,
+    current: &[Option<i64>],
+    selection: Option<&[usize]>,
+    mode: FillMode,
+) -> Vec<(usize, i64)> {
+    let mut values = Vec::new();
+    let mut previous = value;
+    for (row, time) in current.iter().enumerate().skip(source + 1) {
+        if selection.is_some_and(|selected| !selected.contains(&row)) {
+            if let Some(time) = time {
+                previous = *time;
+            }
+            continue;
+        }
+        let filled = match mode {
+            FillMode::Copy => value,
+            FillMode::Accumulate => previous + value,
+        };
+        values.push((row, filled));
+        previous = filled;
+    }
+    values
+}
+
+#[cfg(test)]
+mod fill_tests {
+    use super::*;
+
+    const CURRENT: [Option<i64>; 5] = [Some(1_000), Some(2_000), None, Some(9_000), Some(12_000)];
+
+    #[test]
+    fn segment_and_best_times_are_copied() {
+        assert_eq!(
+            plan_fill_down(1, 30_000, &CURRENT, None, FillMode::Copy),
+            [(2, 30_000), (3, 30_000), (4, 30_000)]
+        );
+        // Nothing below the last row
+        assert!(plan_fill_down(4, 30_000, &CURRENT, None, FillMode::Copy).is_empty());
+    }
+
+    #[test]
+    fn split_times_keep_adding_the_value() {
+        assert_eq!(
+            plan_fill_down(0, 1_000, &CURRENT, None, FillMode::Accumulate),
+            [(1, 2_000), (2, 3_000), (3, 4_000), (4, 5_000)]
+        );
+    }
+
+    #[test]
+    fn only_selected_rows_below_the_source_are_filled() {
+        let selection = [0, 1, 2, 4];
+        assert_eq!(
+            plan_fill_down(1, 500, &CURRENT, Some(&selection), FillMode::Copy),
+            [(2, 500), (4, 500)]
+        );
+        // The unselected row keeps its split, and the next one builds on it
+        assert_eq!(
+            plan_fill_down(1, 500, &CURRENT, Some(&selection), FillMode::Accumulate),
+            [(2, 1_000), (4, 9_500)]
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Bookkeeping behind the split feedback flash: which timer changes deserve one,
+//! and how flashes arriving in quick succession merge into a single one.
+
+use livesplit_core::TimerPhase;
+
+use std::time::{Duration, Instant};
+
+/// How long a flash stays on after the last event feeding it.
+pub const FLASH_DURATION: Duration = Duration::from_millis(150);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlashEvent {
+    Split,
+    Undo,
+    Skip,
+}
+
+impl FlashEvent {
+    /// Class set next to `flash` so themes can tell the events apart.
+    pub const fn css_class(self) -> &'static str {
+        match self {
+            Self::Split => "flash-split",
+            Self::Undo => "flash-undo",
+            Self::Skip => "flash-skip",
+        }
+    }
+}
+
+/// The part of the timer state that split, undo and skip change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitState {
+    pub phase: TimerPhase,
+    pub index: Option<usize>,
+}
+
+/// The event that took the timer from `before` to `after`, with the row it
+/// affected: the segment just left for splits and skips, the one returned to for
+/// undos. `has_split_time` tells whether a segment got a split time, which is
+/// what separates a split from a skip. Resets and run swaps yield `None`.
+pub fn detect_event(
+    before: SplitState,
+    after: SplitState,
+    has_split_time: impl FnOnce(usize) -> bool,
+) -> Option<(FlashEvent, usize)> {
+    if after.phase == TimerPhase::NotRunning {
+        return None;
+    }
+    match (before.index, after.index) {
+        // Starting the run is the split key as well
+        (None, Some(0)) => Some((FlashEvent::Split, 0)),
+        (Some(before), Some(after)) if after == before + 1 => {
+            let event = if has_split_time(before) {
+                FlashEvent::Split
+            } else {
+                FlashEvent::Skip
+            };
+            Some((event, before))
+        }
+        (Some(before), Some(after)) if after + 1 == before => Some((FlashEvent::Undo, after)),
+        _ => None,
+    }
+}
+
+/// Keeps at most one flash alive. Events arriving while it shows push its end
+/// back instead of queueing flashes of their own.
+#[derive(Debug, Default)]
+pub struct FlashCoalescer {
+    until: Option<Instant>,
+}
+
+impl FlashCoalescer {
+    /// Keeps the flash on until `FLASH_DURATION` after `now`. Returns true when no
+    /// flash was showing, i.e. the caller has to schedule `poll`.
+    pub fn trigger(&mut self, now: Instant) -> bool {
+        let idle = self.until.is_none();
+        self.until = Some(now + FLASH_DURATION);
+        idle
+    }
+
+    /// Called when the scheduled wait is over: how much longer to wait if the
+    /// flash was extended meanwhile, or `None` once it has ended.
+    pub fn poll(&mut self, now: Instant) -> Option<Duration> {
+        let until = self.until?;
+        if now < until {
+            return Some(until - now);
+        }
+        self.until = None;
+        None
+    }
+}
+
+#[cfg(test)]
+mod flash_tests {
+    use super::*;
+
+    fn running(index: usize) -> SplitState {
+        SplitState {
+            phase: TimerPhase::Running,
+            index: Some(index),
+        }
+    }
+
+    #[test]
+    fn splits_skips_and_undos_are_told_apart() {
+        assert_eq!(
+            detect_event(running(1), running(2), |_| true),
+            Some((FlashEvent::Split, 1))
+        );
+        assert_eq!(
+            detect_event(running(1), running(2), |_| false),
+            Some((FlashEvent::Skip, 1))
+        );
+        assert_eq!(
+            detect_event(running(2), running(1), |_| true),
+            Some((FlashEvent::Undo, 1))
+        );
+    }
+
+    #[test]
+    fn start_and_final_split_flash_their_rows() {
+        let not_running = SplitState {
+            phase: TimerPhase::NotRunning,
+            index: None,
+        };
+        let ended = SplitState {
+            phase: TimerPhase::Ended,
+            index: Some(3),
+        };
+        assert_eq!(
+            detect_event(not_running, running(0), |_| true),
+            Some((FlashEvent::Split, 0))
+        );
+        assert_eq!(
+            detect_event(running(2), ended, |_| true),
+            Some((FlashEvent::Split, 2))
+        );
+        assert_eq!(
+            detect_event(ended, running(2), |_| true),
+            Some((FlashEvent::Undo, 2))
+        );
+    }
+
+    #[test]
+    fn resets_and_jumps_do_not_flash() {
+        let reset = SplitState {
+            phase: TimerPhase::NotRunning,
+            index: None,
+        };
+        assert_eq!(detect_event(running(2), reset, |_| true), None);
+        assert_eq!(detect_event(running(0), running(3), |_| true), None);
+        assert_eq!(detect_event(running(2), running(2), |_| true), None);
+    }
+
+    #[test]
+    fn events_within_the_window_extend_a_single_flash() {
+        let start = Instant::now();
+        let mut flash = FlashCoalescer::default();
+
+        assert!(flash.trigger(start));
+        let later = start + Duration::from_millis(100);
+        assert!(!flash.trigger(later));
+
+        // The wait scheduled by the first event ends early and is extended
+        assert_eq!(
+            flash.poll(start + FLASH_DURATION),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(flash.poll(later + FLASH_DURATION), None);
+
+        // Over and idle: the next event schedules again
+        assert!(flash.trigger(later + FLASH_DURATION));
+    }
+
+    #[test]
+    fn polling_an_idle_coalescer_is_a_noop() {
+        let mut flash = FlashCoalescer::default();
+        assert_eq!(flash.poll(Instant::now()), None);
+    }
+}
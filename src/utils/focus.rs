@@ -0,0 +1,147 @@
+//! Focus mode: the timer shows only the big timer and one line with the current
+//! split and its live delta, for attempts that need no distraction.
+//!
+//! The mode is applied over the components of the timer: those outside
+//! `FOCUS_WHITELIST` are hidden and left out of the refresh loop until it is
+//! turned off. It lasts for the session only. The `toggle_focus_mode` hotkey
+//! fires on the hook thread, so its presses are counted in `FocusToggles` and
+//! taken on the next tick.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// The parts of the timer, from top to bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerComponent {
+    /// Game and category above the splits.
+    RunInfo,
+    Splits,
+    /// Rows about the selected segment, left of the big timer.
+    InfoRows,
+    BigTimer,
+    /// Time in the current segment, under the big timer.
+    SegmentTimer,
+    /// Current split and live delta, under the big timer. Only in focus mode.
+    FocusLine,
+    /// Rows under the timer, like the current pace.
+    AdditionalInfo,
+}
+
+/// Components still shown in focus mode.
+pub const FOCUS_WHITELIST: [TimerComponent; 2] =
+    [TimerComponent::BigTimer, TimerComponent::FocusLine];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FocusMode {
+    active: bool,
+}
+
+impl FocusMode {
+    pub const fn new(active: bool) -> Self {
+        Self { active }
+    }
+
+    pub const fn is_active(self) -> bool {
+        self.active
+    }
+
+    pub const fn toggled(self) -> Self {
+        Self::new(!self.active)
+    }
+
+    /// Whether `component` is on screen.
+    pub fn shows(self, component: TimerComponent) -> bool {
+        if component == TimerComponent::FocusLine {
+            return self.active;
+        }
+        !self.active || FOCUS_WHITELIST.contains(&component)
+    }
+
+    /// Runs `refresh` only if `component` is on screen.
+    pub fn refresh(self, component: TimerComponent, refresh: impl FnOnce()) {
+        if self.shows(component) {
+            refresh();
+        }
+    }
+}
+
+/// Presses of the focus mode hotkey not yet taken by the main loop.
+#[derive(Debug, Clone, Default)]
+pub struct FocusToggles(Arc<AtomicU32>);
+
+impl FocusToggles {
+    pub fn press(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Takes the presses so far. Returns whether they toggle the mode, as an odd
+    /// number of them does.
+    pub fn take(&self) -> bool {
+        self.0.swap(0, Ordering::Relaxed) % 2 == 1
+    }
+}
+
+#[cfg(test)]
+mod focus_tests {
+    use super::*;
+    use std::cell::Cell;
+
+    const COMPONENTS: [TimerComponent; 7] = [
+        TimerComponent::RunInfo,
+        TimerComponent::Splits,
+        TimerComponent::InfoRows,
+        TimerComponent::BigTimer,
+        TimerComponent::SegmentTimer,
+        TimerComponent::FocusLine,
+        TimerComponent::AdditionalInfo,
+    ];
+
+    /// How many times each component was refreshed over `ticks` ticks in `mode`.
+    fn refreshes(mode: FocusMode, ticks: usize) -> Vec<usize> {
+        let counts: Vec<Cell<usize>> = COMPONENTS.iter().map(|_| Cell::new(0)).collect();
+        for _ in 0..ticks {
+            for (component, count) in COMPONENTS.iter().zip(&counts) {
+                mode.refresh(*component, || count.set(count.get() + 1));
+            }
+        }
+        counts.into_iter().map(Cell::into_inner).collect()
+    }
+
+    #[test]
+    fn hidden_components_are_not_refreshed_in_focus_mode() {
+        // Everything but the focus line without focus mode
+        assert_eq!(refreshes(FocusMode::default(), 3), [3, 3, 3, 3, 3, 0, 3]);
+        // Only the big timer and the focus line with it
+        assert_eq!(refreshes(FocusMode::new(true), 3), [0, 0, 0, 3, 0, 3, 0]);
+        for component in COMPONENTS {
+            assert_eq!(
+                FocusMode::new(true).shows(component),
+                FOCUS_WHITELIST.contains(&component)
+            );
+        }
+    }
+
+    #[test]
+    fn toggling_restores_everything() {
+        let mode = FocusMode::default().toggled();
+        assert!(mode.is_active());
+        assert_eq!(mode.toggled(), FocusMode::default());
+        assert_eq!(
+            refreshes(mode.toggled(), 1),
+            refreshes(FocusMode::default(), 1)
+        );
+    }
+
+    #[test]
+    fn presses_toggle_in_pairs() {
+        let toggles = FocusToggles::default();
+        assert!(!toggles.take());
+        toggles.press();
+        assert!(toggles.clone().take());
+        assert!(!toggles.take());
+        // Pressed twice between ticks: on and off again
+        toggles.press();
+        toggles.press();
+        assert!(!toggles.take());
+    }
+}
@@ -0,0 +1,389 @@
+//! Opt-in timing of the refresh loop (`--perf`, or "Measure Refresh" in the
+//! debug log window), to find what makes a tick slow when the desktop stutters.
+//!
+//! Each tick is split into phases: taking the timer lock, copying the timer,
+//! the context's bookkeeping and the refresh of each part of the timer. Their
+//! times go into small histograms of fixed size, for the mean, 95th percentile
+//! and maximum since the last summary, which is logged every
+//! `SUMMARY_INTERVAL`. When off, a tick only checks `enabled` once: its
+//! `TickProbe` holds no start time and every lap returns right away.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::info;
+
+/// Command line flag turning the measurements on from the start.
+pub const FLAG: &str = "--perf";
+/// How often the statistics are logged and started over.
+pub const SUMMARY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Buckets of a histogram, a quarter of an octave each from 1 µs: the last one
+/// starts at about 60 ms and takes anything slower.
+const BUCKETS: usize = 64;
+const BUCKETS_PER_OCTAVE: f64 = 4.0;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static STATS: LazyLock<Mutex<FrameStats>> = LazyLock::new(Mutex::default);
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Turns the measurements on or off. Statistics start over either way.
+pub fn set_enabled(enabled: bool) {
+    if ENABLED.swap(enabled, Ordering::Relaxed) != enabled {
+        *STATS.lock().unwrap() = FrameStats::default();
+        info!(
+            "Refresh measurements {}",
+            if enabled { "on" } else { "off" }
+        );
+    }
+}
+
+/// The statistics since the last summary.
+pub fn current() -> FrameStats {
+    STATS.lock().unwrap().clone()
+}
+
+/// The parts of a tick, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickPhase {
+    /// Waiting for the timer's lock.
+    Lock,
+    /// Copying the timer to refresh from.
+    Snapshot,
+    /// Golds, hooks, autosave and the other per-tick updates of the context.
+    Bookkeeping,
+    RunInfo,
+    Splits,
+    /// Big timer, segment timer and the rows around them.
+    Footer,
+}
+
+impl TickPhase {
+    pub const ALL: [Self; 6] = [
+        Self::Lock,
+        Self::Snapshot,
+        Self::Bookkeeping,
+        Self::RunInfo,
+        Self::Splits,
+        Self::Footer,
+    ];
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Lock => "lock",
+            Self::Snapshot => "snapshot",
+            Self::Bookkeeping => "bookkeeping",
+            Self::RunInfo => "run info",
+            Self::Splits => "splits",
+            Self::Footer => "footer",
+        }
+    }
+}
+
+/// Durations counted in buckets of growing width, so percentiles are within a
+/// fifth of the real value. The mean and maximum are exact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    counts: [u32; BUCKETS],
+    count: u32,
+    sum: Duration,
+    max: Duration,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            counts: [0; BUCKETS],
+            count: 0,
+            sum: Duration::ZERO,
+            max: Duration::ZERO,
+        }
+    }
+}
+
+impl Histogram {
+    pub fn record(&mut self, duration: Duration) {
+        self.counts[bucket(duration)] += 1;
+        self.count += 1;
+        self.sum += duration;
+        self.max = self.max.max(duration);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| self.sum / self.count)
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    /// Upper bound of the bucket holding the `percentile` (0 to 100), never past
+    /// the maximum.
+    pub fn percentile(&self, percentile: u8) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = (u64::from(self.count) * u64::from(percentile.min(100))).div_ceil(100);
+        let mut seen = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            seen += u64::from(*count);
+            if seen >= rank.max(1) && index < BUCKETS - 1 {
+                return Some(bucket_bound(index).min(self.max));
+            }
+        }
+        // In the last bucket, which has no bound
+        Some(self.max)
+    }
+
+    /// "0.42/0.90/3.10 ms": mean, 95th percentile and maximum.
+    fn describe(&self) -> String {
+        let ms = |time: Option<Duration>| time.unwrap_or_default().as_secs_f64() * 1000.0;
+        format!(
+            "{:.2}/{:.2}/{:.2} ms",
+            ms(self.mean()),
+            ms(self.percentile(95)),
+            ms(self.max())
+        )
+    }
+}
+
+fn bucket(duration: Duration) -> usize {
+    let micros = duration.as_secs_f64() * 1e6;
+    (((micros + 1.0).log2() * BUCKETS_PER_OCTAVE).floor() as usize).min(BUCKETS - 1)
+}
+
+fn bucket_bound(index: usize) -> Duration {
+    Duration::from_secs_f64((((index + 1) as f64 / BUCKETS_PER_OCTAVE).exp2() - 1.0) / 1e6)
+}
+
+/// Histograms of the phases and of whole ticks since `since`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    phases: [Histogram; TickPhase::ALL.len()],
+    ticks: Histogram,
+    since: Option<Instant>,
+}
+
+impl FrameStats {
+    /// Adds the phase `times` of a tick that ended `now`. Once
+    /// `SUMMARY_INTERVAL` has passed, starts over and returns the summary of
+    /// the ticks so far.
+    pub fn record(
+        &mut self,
+        times: &[Duration; TickPhase::ALL.len()],
+        now: Instant,
+    ) -> Option<String> {
+        for (histogram, time) in self.phases.iter_mut().zip(times) {
+            histogram.record(*time);
+        }
+        self.ticks.record(times.iter().sum());
+        let since = *self.since.get_or_insert(now);
+        if now.duration_since(since) < SUMMARY_INTERVAL {
+            return None;
+        }
+        let summary = self.summary(now.duration_since(since));
+        *self = Self {
+            since: Some(now),
+            ..Self::default()
+        };
+        Some(summary)
+    }
+
+    pub fn phase(&self, phase: TickPhase) -> &Histogram {
+        &self.phases[phase as usize]
+    }
+
+    pub fn ticks(&self) -> &Histogram {
+        &self.ticks
+    }
+
+    /// One line for the log over `elapsed`.
+    pub fn summary(&self, elapsed: Duration) -> String {
+        let phases: Vec<String> = TickPhase::ALL
+            .iter()
+            .map(|phase| format!("{} {}", phase.label(), self.phase(*phase).describe()))
+            .collect();
+        format!(
+            "Refresh, {} ticks in {} s (mean/p95/max): tick {}, {}",
+            self.ticks().count(),
+            elapsed.as_secs(),
+            self.ticks().describe(),
+            phases.join(", ")
+        )
+    }
+
+    /// A line per phase and one for whole ticks, for the debug log window.
+    pub fn lines(&self) -> Vec<String> {
+        std::iter::once(format!(
+            "{:<12} {} ({} ticks)",
+            "tick",
+            self.ticks().describe(),
+            self.ticks().count()
+        ))
+        .chain(
+            TickPhase::ALL
+                .iter()
+                .map(|phase| format!("{:<12} {}", phase.label(), self.phase(*phase).describe())),
+        )
+        .collect()
+    }
+}
+
+/// Times the phases of one tick, each lap ending the phase that ran since the
+/// previous one. Does nothing when the measurements were off as it started.
+#[derive(Debug)]
+pub struct TickProbe {
+    last: Option<Instant>,
+    times: [Duration; TickPhase::ALL.len()],
+}
+
+impl TickProbe {
+    pub fn start() -> Self {
+        Self::new(enabled())
+    }
+
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            last: enabled.then(Instant::now),
+            times: [Duration::ZERO; TickPhase::ALL.len()],
+        }
+    }
+
+    /// Adds the time since the previous lap to `phase`.
+    pub fn lap(&mut self, phase: TickPhase) {
+        if let Some(last) = &mut self.last {
+            let now = Instant::now();
+            self.times[phase as usize] += now - *last;
+            *last = now;
+        }
+    }
+
+    /// Doesn't time what ran since the previous lap, like work outside any phase.
+    pub fn skip(&mut self) {
+        if let Some(last) = &mut self.last {
+            *last = Instant::now();
+        }
+    }
+
+    /// Adds the tick to the statistics, logging their summary when it is due.
+    pub fn finish(self) {
+        let summary = self.record_into(&mut STATS.lock().unwrap(), Instant::now());
+        if let Some(summary) = summary {
+            info!("{summary}");
+        }
+    }
+
+    /// Adds the tick, ended `now`, to `stats` if it was measured. See
+    /// `FrameStats::record`.
+    pub fn record_into(self, stats: &mut FrameStats, now: Instant) -> Option<String> {
+        self.last?;
+        stats.record(&self.times, now)
+    }
+}
+
+#[cfg(test)]
+mod frame_stats_tests {
+    use super::*;
+
+    fn micros(micros: u64) -> Duration {
+        Duration::from_micros(micros)
+    }
+
+    #[test]
+    fn histograms_keep_exact_means_and_close_percentiles() {
+        let mut histogram = Histogram::default();
+        assert_eq!(histogram.mean(), None);
+        assert_eq!(histogram.percentile(95), None);
+
+        // 95 fast ticks and 5 slow ones
+        for _ in 0..95 {
+            histogram.record(micros(100));
+        }
+        for _ in 0..5 {
+            histogram.record(micros(20_000));
+        }
+        assert_eq!(histogram.count(), 100);
+        assert_eq!(histogram.mean(), Some(micros(1_095)));
+        assert_eq!(histogram.max(), Some(micros(20_000)));
+        let p95 = histogram.percentile(95).unwrap();
+        assert!(p95 >= micros(100) && p95 <= micros(120), "{p95:?}");
+        // Past the 95th tick, a slow one, capped at the maximum
+        assert_eq!(histogram.percentile(96), Some(micros(20_000)));
+        assert_eq!(histogram.percentile(100), Some(micros(20_000)));
+    }
+
+    #[test]
+    fn buckets_are_a_quarter_octave_wide_and_bounded() {
+        for micros in [0, 1, 7, 100, 1_500, 16_000, 60_000] {
+            let duration = Duration::from_micros(micros);
+            let index = bucket(duration);
+            assert!(bucket_bound(index) >= duration);
+            assert!(index == 0 || bucket_bound(index - 1) <= duration);
+        }
+        assert_eq!(bucket(Duration::from_secs(10)), BUCKETS - 1);
+
+        // Slower than the last bucket: still counted, reported as the maximum
+        let mut histogram = Histogram::default();
+        histogram.record(Duration::from_secs(10));
+        assert_eq!(histogram.percentile(50), Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn stats_are_summarized_and_started_over_every_interval() {
+        let start = Instant::now();
+        let mut stats = FrameStats::default();
+        let mut times = [Duration::ZERO; 6];
+        times[TickPhase::Lock as usize] = micros(10);
+        times[TickPhase::Splits as usize] = micros(400);
+
+        assert_eq!(stats.record(&times, start), None);
+        assert_eq!(stats.record(&times, start + Duration::from_secs(29)), None);
+        assert_eq!(stats.ticks().count(), 2);
+        assert_eq!(stats.ticks().mean(), Some(micros(410)));
+        assert_eq!(stats.phase(TickPhase::Splits).max(), Some(micros(400)));
+        assert_eq!(stats.lines().len(), 7);
+
+        let summary = stats.record(&times, start + SUMMARY_INTERVAL).unwrap();
+        assert!(summary.starts_with("Refresh, 3 ticks in 30 s (mean/p95/max): tick 0.41/"));
+        assert!(summary.contains("splits 0.40/"), "{summary}");
+        assert_eq!(stats.ticks().count(), 0);
+        // The next interval starts with the summary
+        assert_eq!(stats.record(&times, start + Duration::from_secs(59)), None);
+        assert!(
+            stats
+                .record(&times, start + Duration::from_secs(60))
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn probes_time_nothing_when_off() {
+        let mut stats = FrameStats::default();
+        let mut probe = TickProbe::new(false);
+        std::thread::sleep(micros(200));
+        probe.lap(TickPhase::Lock);
+        assert_eq!(probe.times, [Duration::ZERO; 6]);
+        assert_eq!(probe.record_into(&mut stats, Instant::now()), None);
+        assert_eq!(stats.ticks().count(), 0);
+
+        let mut probe = TickProbe::new(true);
+        std::thread::sleep(micros(200));
+        probe.lap(TickPhase::Lock);
+        probe.lap(TickPhase::Lock);
+        std::thread::sleep(micros(200));
+        probe.skip();
+        probe.lap(TickPhase::Footer);
+        probe.record_into(&mut stats, Instant::now());
+        assert_eq!(stats.ticks().count(), 1);
+        assert!(stats.phase(TickPhase::Lock).max() >= Some(micros(200)));
+        assert!(stats.phase(TickPhase::Footer).max() < Some(micros(200)));
+    }
+}
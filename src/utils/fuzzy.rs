@@ -0,0 +1,94 @@
+//! Fuzzy matching of the command palette's query against command titles.
+
+/// How well `query` matches a command with `title` and `keywords`, higher being
+/// better, or `None` when it does not match. Case is ignored. An empty query
+/// matches everything equally.
+///
+/// A title starting with the query ranks highest, then one of its words
+/// starting with it, then the query anywhere in it, then its letters in order
+/// (the closer together, the better). Keywords count a little less than the
+/// title.
+pub fn fuzzy_score(query: &str, title: &str, keywords: &[&str]) -> Option<u32> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let keyword_scores = keywords
+        .iter()
+        .filter_map(|keyword| score(&query, keyword).map(|s| s.saturating_sub(50)));
+    score(&query, title).into_iter().chain(keyword_scores).max()
+}
+
+fn score(query: &str, candidate: &str) -> Option<u32> {
+    let candidate = candidate.to_lowercase();
+    if candidate.starts_with(query) {
+        return Some(1000);
+    }
+    if candidate
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(query))
+    {
+        return Some(800);
+    }
+    if candidate.contains(query) {
+        return Some(600);
+    }
+
+    // Letters in order, penalized by the characters skipped between them
+    let mut chars = candidate.chars();
+    let mut gaps = 0u32;
+    let mut started = false;
+    for wanted in query.chars() {
+        loop {
+            let c = chars.next()?;
+            if c == wanted {
+                started = true;
+                break;
+            }
+            if started {
+                gaps += 1;
+            }
+        }
+    }
+    Some(400u32.saturating_sub(gaps * 10).max(1))
+}
+
+#[cfg(test)]
+mod fuzzy_tests {
+    use super::*;
+
+    #[test]
+    fn letters_in_order_match() {
+        assert!(fuzzy_score("svspl", "Save Splits", &[]).is_some());
+        assert!(fuzzy_score("atmpt hst", "Attempt History", &[]).is_some());
+        assert_eq!(fuzzy_score("lpss", "Save Splits", &[]), None);
+        assert_eq!(fuzzy_score("xyz", "Save Splits", &["file"]), None);
+        // Case does not matter, and nothing is filtered without a query
+        assert!(fuzzy_score("SAVE", "save splits", &[]).is_some());
+        assert_eq!(fuzzy_score("  ", "Save Splits", &[]), Some(0));
+    }
+
+    #[test]
+    fn prefixes_rank_above_scattered_letters() {
+        let prefix = fuzzy_score("sa", "Save Splits", &[]).unwrap();
+        let word = fuzzy_score("sp", "Save Splits", &[]).unwrap();
+        let inside = fuzzy_score("plit", "Save Splits", &[]).unwrap();
+        let scattered = fuzzy_score("sst", "Save Splits", &[]).unwrap();
+        assert!(prefix > word);
+        assert!(word > inside);
+        assert!(inside > scattered);
+
+        // Letters close together beat letters far apart
+        let close = fuzzy_score("spl", "Load Splits", &[]).unwrap();
+        let far = fuzzy_score("ldt", "Load Splits", &[]).unwrap();
+        assert!(close > far);
+    }
+
+    #[test]
+    fn keywords_match_just_below_the_title() {
+        let keyword = fuzzy_score("open", "Load Splits", &["open", "file"]).unwrap();
+        let title = fuzzy_score("open", "Open Viewer", &[]).unwrap();
+        assert!(title > keyword);
+        assert!(keyword > fuzzy_score("opn", "Open Viewer", &[]).unwrap());
+    }
+}
@@ -0,0 +1,159 @@
+//! Goal mode: a target time for the whole attempt, e.g. a marathon estimate.
+//!
+//! The main timer keeps counting up; the goal row counts the time left down from
+//! the goal and keeps going below zero once it is missed. Everything is derived
+//! from the timer snapshot, so pauses and the run offset are accounted for.
+
+use livesplit_core::{Timer, TimerPhase};
+use time::Duration;
+
+use crate::formatters::time::{TimeFormat, parse_pasted_time};
+
+/// The goal in `general.goal-time`, e.g. "8:00:00" or "45:30". Unset, empty or
+/// unreadable values (and zero) mean no goal.
+pub fn parse_goal(text: Option<&str>) -> Option<Duration> {
+    parse_pasted_time(text?)
+        .ok()
+        .filter(|goal| goal.is_positive())
+}
+
+/// Time left before `elapsed` reaches `goal`, negative once past it.
+pub fn remaining(goal: Duration, elapsed: Duration) -> Duration {
+    goal - elapsed
+}
+
+/// Time left on the attempt of `timer`: the whole goal before it starts, frozen
+/// on the final time once it ends.
+pub fn timer_remaining(goal: Duration, timer: &Timer) -> Duration {
+    if timer.current_phase() == TimerPhase::NotRunning {
+        return goal;
+    }
+    let elapsed = timer.snapshot().current_time()[timer.current_timing_method()]
+        .map(|time| time.to_duration())
+        .unwrap_or_default();
+    remaining(goal, elapsed)
+}
+
+/// The remaining time, with a minus sign once the goal is missed, however
+/// little by.
+pub fn format_remaining(remaining: Duration, format: &TimeFormat) -> String {
+    let text = format.format_duration(&remaining.abs());
+    if remaining.is_negative() {
+        format!("-{text}")
+    } else {
+        text
+    }
+}
+
+/// Whether the time left is shown as behind.
+pub fn is_behind(remaining: Duration) -> bool {
+    remaining.is_negative()
+}
+
+/// How a finished attempt did against the goal, for the toast shown at the end.
+pub fn finish_message(remaining: Duration, format: &TimeFormat) -> String {
+    let by = format.format_duration(&remaining.abs());
+    if remaining.is_negative() {
+        format!("Finished {by} over the goal")
+    } else if remaining.is_zero() {
+        "Finished right on the goal".to_owned()
+    } else {
+        format!("Finished {by} under the goal")
+    }
+}
+
+#[cfg(test)]
+mod goal_tests {
+    use super::*;
+    use livesplit_core::{Run, Segment, TimeSpan};
+
+    fn format() -> TimeFormat {
+        TimeFormat::default()
+    }
+
+    fn millis(millis: i64) -> Duration {
+        Duration::milliseconds(millis)
+    }
+
+    #[test]
+    fn goals_parse_leniently_and_zero_means_none() {
+        assert_eq!(parse_goal(Some("8:00:00")), Some(Duration::hours(8)));
+        assert_eq!(parse_goal(Some(" 45:30 ")), Some(Duration::seconds(2730)));
+        assert_eq!(parse_goal(Some("1:02.5")), Some(millis(62_500)));
+        assert_eq!(parse_goal(Some("0:00")), None);
+        assert_eq!(parse_goal(Some("soon")), None);
+        assert_eq!(parse_goal(Some("")), None);
+        assert_eq!(parse_goal(None), None);
+    }
+
+    #[test]
+    fn remaining_time_crosses_zero_into_negative() {
+        let goal = Duration::seconds(60);
+        assert_eq!(
+            remaining(goal, Duration::seconds(45)),
+            Duration::seconds(15)
+        );
+        assert_eq!(remaining(goal, goal), Duration::ZERO);
+        assert_eq!(
+            remaining(goal, Duration::seconds(75)),
+            Duration::seconds(-15)
+        );
+        assert!(!is_behind(Duration::ZERO));
+        assert!(is_behind(millis(-1)));
+    }
+
+    #[test]
+    fn formatting_around_zero_only_signs_missed_goals() {
+        let format = format();
+        let plain = |millis: i64| format.format_duration(&Duration::milliseconds(millis));
+
+        assert_eq!(format_remaining(millis(1_500), &format), plain(1_500));
+        assert_eq!(format_remaining(Duration::ZERO, &format), plain(0));
+        // Past zero by less than the precision still reads as missed
+        assert_eq!(
+            format_remaining(millis(-40), &format),
+            format!("-{}", plain(40))
+        );
+        assert_eq!(
+            format_remaining(millis(-75_300), &format),
+            format!("-{}", plain(75_300))
+        );
+        assert!(format_remaining(millis(-75_300), &format).starts_with("-1:15"));
+    }
+
+    #[test]
+    fn finish_reports_over_or_under() {
+        let format = format();
+        let by = format.format_duration(&Duration::seconds(90));
+        assert_eq!(
+            finish_message(Duration::seconds(90), &format),
+            format!("Finished {by} under the goal")
+        );
+        assert_eq!(
+            finish_message(Duration::seconds(-90), &format),
+            format!("Finished {by} over the goal")
+        );
+        assert_eq!(
+            finish_message(Duration::ZERO, &format),
+            "Finished right on the goal"
+        );
+    }
+
+    #[test]
+    fn timer_remaining_follows_the_attempt() {
+        let mut run = Run::new();
+        run.push_segment(Segment::new("S1"));
+        run.set_offset(TimeSpan::from_seconds(-5.0));
+        let mut timer = Timer::new(run).unwrap();
+        let goal = Duration::seconds(60);
+
+        assert_eq!(timer_remaining(goal, &timer), goal);
+        timer.start();
+        timer.pause();
+        // Counting from the offset, so more than the goal is left at first
+        let left = timer_remaining(goal, &timer);
+        assert!(left > goal && left <= Duration::seconds(65), "{left}");
+        // Paused, the time left stays put
+        assert_eq!(timer_remaining(goal, &timer), left);
+    }
+}
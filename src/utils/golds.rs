@@ -0,0 +1,620 @@
+use livesplit_core::{Run, Segment, Time, TimeSpan, TimerPhase, TimingMethod};
+
+/// A change in the golds of the current attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoldEvent {
+    /// The segment at this index was completed faster than its best segment.
+    Earned(usize),
+    /// The split that produced the gold at this index was undone.
+    Undone(usize),
+    /// The attempt was reset, dropping every gold.
+    Cleared,
+}
+
+/// Follows the current attempt and reports which segments beat their best segment.
+///
+/// The best segments are snapshotted when the attempt starts, so golds are always
+/// judged against the bests that were in place before the attempt.
+#[derive(Debug, Default)]
+pub struct GoldTracker {
+    snapshot: Vec<Time>,
+    golds: Vec<usize>,
+}
+
+impl GoldTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indices of the segments that produced a gold in the current attempt.
+    pub fn golds(&self) -> &[usize] {
+        &self.golds
+    }
+
+    /// Updates the tracker with the current state of the timer, returning what changed.
+    /// Segments flagged in `excluded` never produce golds.
+    pub fn observe(
+        &mut self,
+        phase: TimerPhase,
+        segments: &[Segment],
+        excluded: &[bool],
+        method: TimingMethod,
+    ) -> Vec<GoldEvent> {
+        if phase == TimerPhase::NotRunning {
+            self.snapshot.clear();
+            if self.golds.is_empty() {
+                return Vec::new();
+            }
+            self.golds.clear();
+            return vec![GoldEvent::Cleared];
+        }
+
+        if self.snapshot.len() != segments.len() {
+            self.snapshot = segments.iter().map(Segment::best_segment_time).collect();
+            self.golds.clear();
+        }
+
+        let current = gold_indices(segments, &self.snapshot, excluded, method);
+        let mut events: Vec<GoldEvent> = self
+            .golds
+            .iter()
+            .filter(|index| !current.contains(index))
+            .map(|&index| GoldEvent::Undone(index))
+            .collect();
+        events.extend(
+            current
+                .iter()
+                .filter(|index| !self.golds.contains(index))
+                .map(|&index| GoldEvent::Earned(index)),
+        );
+
+        self.golds = current;
+        events
+    }
+}
+
+/// Returns the indices of the completed segments whose duration beats the matching
+/// best segment in `best_before`, or that had no best segment at all.
+///
+/// Like livesplit-core, a segment right after a skipped split has no duration of its
+/// own and can never be a gold. Segments flagged in `excluded` are never golds.
+pub fn gold_indices(
+    segments: &[Segment],
+    best_before: &[Time],
+    excluded: &[bool],
+    method: TimingMethod,
+) -> Vec<usize> {
+    let mut golds = Vec::new();
+    let mut previous_split = Some(time::Duration::ZERO);
+
+    for (index, segment) in segments.iter().enumerate() {
+        let split = segment.split_time()[method].map(|t| t.to_duration());
+
+        if let (Some(split), Some(previous)) = (split, previous_split)
+            && !excluded.get(index).copied().unwrap_or(false)
+        {
+            let duration = split - previous;
+            let best = best_before
+                .get(index)
+                .and_then(|time| time[method])
+                .map(|t| t.to_duration());
+            if best.is_none_or(|best| duration < best) {
+                golds.push(index);
+            }
+        }
+
+        previous_split = split;
+    }
+
+    golds
+}
+
+/// How a best segment typed in the editor compares with the segment's history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoldCheck {
+    /// At least as slow as some recorded attempt.
+    Ok,
+    /// Faster than every recorded attempt, likely a typo. Holds the fastest time
+    /// in the history, in milliseconds.
+    SuspiciouslyFast { fastest_ms: i64 },
+    /// The segment was never completed, so there is nothing to compare with.
+    NoHistory,
+}
+
+/// Checks an edited best segment of `candidate_ms` against the fastest time the
+/// segment was ever completed in.
+pub fn check_edited_gold(segment: &Segment, candidate_ms: i64, method: TimingMethod) -> GoldCheck {
+    let fastest = segment
+        .segment_history()
+        .iter()
+        .filter_map(|(_, time)| time[method])
+        .map(|time| time.to_duration().whole_milliseconds() as i64)
+        .min();
+
+    match fastest {
+        None => GoldCheck::NoHistory,
+        Some(fastest_ms) if candidate_ms < fastest_ms => GoldCheck::SuspiciouslyFast { fastest_ms },
+        Some(_) => GoldCheck::Ok,
+    }
+}
+
+/// Best segment of one segment and timing method, recomputed from its history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GoldRecalculation {
+    pub index: usize,
+    pub method: TimingMethod,
+    pub old: Option<TimeSpan>,
+    /// Fastest valid time in the history. `None` when there is none, in which
+    /// case the current best segment is kept.
+    pub new: Option<TimeSpan>,
+    /// Attempt the new best segment comes from.
+    pub attempt: Option<i32>,
+    /// Day that attempt ended, when the attempt history has it.
+    pub date: Option<time::Date>,
+}
+
+impl GoldRecalculation {
+    /// Whether applying this changes the run.
+    pub fn changes(&self) -> bool {
+        self.new.is_some() && self.new != self.old
+    }
+}
+
+/// The times of segment `index` in its history, in `method`, with the attempt
+/// of each. Skipped segments and times of zero or less are not valid times, and
+/// neither is the time after a skipped split, which covers the skipped segment
+/// too.
+pub fn valid_segment_times(run: &Run, index: usize, method: TimingMethod) -> Vec<(i32, TimeSpan)> {
+    let previous = index.checked_sub(1).map(|previous| run.segment(previous));
+    let after_skip = |attempt: i32| {
+        previous.is_some_and(|previous| {
+            previous
+                .segment_history()
+                .get(attempt)
+                .is_some_and(|time| time[method].is_none())
+        })
+    };
+    run.segment(index)
+        .segment_history()
+        .iter()
+        .filter(|&&(attempt, _)| !after_skip(attempt))
+        .filter_map(|&(attempt, time)| Some((attempt, time[method]?)))
+        .filter(|(_, time)| *time > TimeSpan::zero())
+        .collect()
+}
+
+/// Recomputes every best segment, for both timing methods, as the fastest of
+/// its `valid_segment_times`. Only differences are returned, along with best
+/// segments that have no history to back them, which are kept as they are.
+pub fn recalculate_golds(run: &Run) -> Vec<GoldRecalculation> {
+    let mut recalculated = Vec::new();
+    for (index, segment) in run.segments().iter().enumerate() {
+        for method in [TimingMethod::RealTime, TimingMethod::GameTime] {
+            let fastest = valid_segment_times(run, index, method)
+                .into_iter()
+                // The first attempt wins ties
+                .fold(
+                    None,
+                    |fastest: Option<(i32, TimeSpan)>, (attempt, time)| match fastest {
+                        Some((_, best)) if best <= time => fastest,
+                        _ => Some((attempt, time)),
+                    },
+                );
+
+            let old = segment.best_segment_time()[method];
+            let attempt = fastest.map(|(attempt, _)| attempt);
+            let recalculation = GoldRecalculation {
+                index,
+                method,
+                old,
+                new: fastest.map(|(_, time)| time),
+                attempt,
+                date: attempt.and_then(|attempt| attempt_date(run, attempt)),
+            };
+            if recalculation.changes() || (fastest.is_none() && old.is_some()) {
+                recalculated.push(recalculation);
+            }
+        }
+    }
+    recalculated
+}
+
+/// Local day the attempt with `index` ended, or started if it never did, so it
+/// compares with the dates golds set today are stored with.
+pub fn attempt_date(run: &Run, index: i32) -> Option<time::Date> {
+    let attempt = run
+        .attempt_history()
+        .iter()
+        .find(|attempt| attempt.index() == index)?;
+    local_date(attempt.ended().or(attempt.started())?.time)
+}
+
+/// Day `at` falls on in the local time zone.
+pub fn local_date(at: time::OffsetDateTime) -> Option<time::Date> {
+    let local = glib::DateTime::from_unix_local(at.unix_timestamp()).ok()?;
+    let month = time::Month::try_from(u8::try_from(local.month()).ok()?).ok()?;
+    time::Date::from_calendar_date(
+        local.year(),
+        month,
+        u8::try_from(local.day_of_month()).ok()?,
+    )
+    .ok()
+}
+
+/// Golds of the current attempt, which only become best segments if the attempt
+/// is saved when it is reset.
+#[derive(Debug, Default)]
+pub struct PendingGolds {
+    golds: Vec<PendingGold>,
+}
+
+#[derive(Debug)]
+struct PendingGold {
+    index: usize,
+    name: String,
+    best_before: Time,
+}
+
+impl PendingGolds {
+    /// Follows the events of a `GoldTracker`. Once the attempt is reset, returns
+    /// the indices of the golds that were saved, i.e. whose best segment changed.
+    pub fn observe(&mut self, events: &[GoldEvent], segments: &[Segment]) -> Vec<usize> {
+        let mut saved = Vec::new();
+        for event in events {
+            match *event {
+                GoldEvent::Earned(index) => {
+                    if let Some(segment) = segments.get(index) {
+                        self.golds.retain(|gold| gold.index != index);
+                        self.golds.push(PendingGold {
+                            index,
+                            name: segment.name().to_owned(),
+                            best_before: segment.best_segment_time(),
+                        });
+                    }
+                }
+                GoldEvent::Undone(index) => self.golds.retain(|gold| gold.index != index),
+                GoldEvent::Cleared => {
+                    saved.extend(self.golds.drain(..).filter_map(|gold| {
+                        let segment = segments.get(gold.index)?;
+                        (segment.name() == gold.name
+                            && segment.best_segment_time() != gold.best_before)
+                            .then_some(gold.index)
+                    }));
+                }
+            }
+        }
+        saved.sort_unstable();
+        saved
+    }
+}
+
+#[cfg(test)]
+mod golds_tests {
+    use super::*;
+    use crate::test_support::real;
+
+    // Three segments with golds of 10s each
+    fn segments() -> Vec<Segment> {
+        ["A", "B", "C"]
+            .into_iter()
+            .map(|name| {
+                let mut segment = Segment::new(name);
+                segment.set_best_segment_time(real(10.0));
+                segment
+            })
+            .collect()
+    }
+
+    #[test]
+    fn faster_segments_are_golds_slower_ones_are_not() {
+        let mut segments = segments();
+        segments[0].set_split_time(real(9.0)); // 9s, gold
+        segments[1].set_split_time(real(20.0)); // 11s
+        segments[2].set_split_time(real(28.0)); // 8s, gold
+
+        let snapshot: Vec<Time> = segments.iter().map(Segment::best_segment_time).collect();
+        assert_eq!(
+            gold_indices(&segments, &snapshot, &[], TimingMethod::RealTime),
+            vec![0, 2]
+        );
+    }
+
+    #[test]
+    fn segments_without_best_are_golds_and_skips_break_the_chain() {
+        let mut segments = segments();
+        segments[0].set_best_segment_time(Time::new());
+        segments[0].set_split_time(real(30.0)); // no best yet -> gold
+        // B skipped
+        segments[2].set_split_time(real(35.0)); // 5s since A, but spans a skip -> not gold
+
+        let snapshot: Vec<Time> = segments.iter().map(Segment::best_segment_time).collect();
+        assert_eq!(
+            gold_indices(&segments, &snapshot, &[], TimingMethod::RealTime),
+            vec![0]
+        );
+    }
+
+    #[test]
+    fn tracker_counts_undoes_and_clears() {
+        let mut tracker = GoldTracker::new();
+        let mut segments = segments();
+        let rt = TimingMethod::RealTime;
+
+        assert!(
+            tracker
+                .observe(TimerPhase::Running, &segments, &[], rt)
+                .is_empty()
+        );
+
+        segments[0].set_split_time(real(8.0));
+        assert_eq!(
+            tracker.observe(TimerPhase::Running, &segments, &[], rt),
+            vec![GoldEvent::Earned(0)]
+        );
+
+        // Same state again: nothing new
+        assert!(
+            tracker
+                .observe(TimerPhase::Running, &segments, &[], rt)
+                .is_empty()
+        );
+
+        segments[1].set_split_time(real(15.0));
+        assert_eq!(
+            tracker.observe(TimerPhase::Running, &segments, &[], rt),
+            vec![GoldEvent::Earned(1)]
+        );
+        assert_eq!(tracker.golds(), &[0, 1]);
+
+        // Undo the last split
+        segments[1].clear_split_time();
+        assert_eq!(
+            tracker.observe(TimerPhase::Running, &segments, &[], rt),
+            vec![GoldEvent::Undone(1)]
+        );
+        assert_eq!(tracker.golds(), &[0]);
+
+        // Reset
+        segments[0].clear_split_time();
+        assert_eq!(
+            tracker.observe(TimerPhase::NotRunning, &segments, &[], rt),
+            vec![GoldEvent::Cleared]
+        );
+        assert!(tracker.golds().is_empty());
+        assert!(
+            tracker
+                .observe(TimerPhase::NotRunning, &segments, &[], rt)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn golds_are_judged_against_the_snapshot_taken_at_start() {
+        let mut tracker = GoldTracker::new();
+        let mut segments = segments();
+        let rt = TimingMethod::RealTime;
+
+        tracker.observe(TimerPhase::Running, &segments, &[], rt);
+
+        // Bests changing during the attempt do not move the goalposts
+        segments[0].set_best_segment_time(real(5.0));
+        segments[0].set_split_time(real(8.0));
+        assert_eq!(
+            tracker.observe(TimerPhase::Running, &segments, &[], rt),
+            vec![GoldEvent::Earned(0)]
+        );
+    }
+
+    #[test]
+    fn excluded_segments_never_produce_golds() {
+        let mut segments = segments();
+        segments[0].set_split_time(real(9.0)); // gold
+        segments[1].set_split_time(real(12.0)); // gold, but excluded
+        segments[2].set_split_time(real(15.0)); // gold, still counts after an excluded one
+
+        let snapshot: Vec<Time> = segments.iter().map(Segment::best_segment_time).collect();
+        assert_eq!(
+            gold_indices(
+                &segments,
+                &snapshot,
+                &[false, true, false],
+                TimingMethod::RealTime
+            ),
+            vec![0, 2]
+        );
+
+        let mut tracker = GoldTracker::new();
+        assert_eq!(
+            tracker.observe(
+                TimerPhase::Running,
+                &segments,
+                &[true, true, true],
+                TimingMethod::RealTime
+            ),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn edited_golds_are_checked_against_history() {
+        let mut segment = Segment::new("A");
+        assert_eq!(
+            check_edited_gold(&segment, 5_000, TimingMethod::RealTime),
+            GoldCheck::NoHistory
+        );
+
+        segment.segment_history_mut().insert(1, real(12.0));
+        segment.segment_history_mut().insert(2, real(10.0));
+        segment.segment_history_mut().insert(3, Time::new()); // skipped
+
+        assert_eq!(
+            check_edited_gold(&segment, 10_000, TimingMethod::RealTime),
+            GoldCheck::Ok
+        );
+        assert_eq!(
+            check_edited_gold(&segment, 9_990, TimingMethod::RealTime),
+            GoldCheck::SuspiciouslyFast { fastest_ms: 10_000 }
+        );
+        // Game time was never recorded
+        assert_eq!(
+            check_edited_gold(&segment, 1, TimingMethod::GameTime),
+            GoldCheck::NoHistory
+        );
+    }
+
+    fn history_run(histories: &[&[(i32, Time)]]) -> Run {
+        let mut run = Run::new();
+        for (i, history) in histories.iter().enumerate() {
+            let mut segment = Segment::new(format!("S{i}"));
+            segment.set_best_segment_time(real(100.0));
+            for &(attempt, time) in *history {
+                segment.segment_history_mut().insert(attempt, time);
+            }
+            run.push_segment(segment);
+        }
+        run
+    }
+
+    #[test]
+    fn golds_are_the_fastest_history_entry_skips_ignored() {
+        let run = history_run(&[
+            &[(1, real(30.0)), (2, Time::new()), (3, real(25.0))],
+            &[(1, real(40.0)), (2, Time::new()), (3, real(40.0))],
+        ]);
+        let real_time: Vec<_> = recalculate_golds(&run)
+            .into_iter()
+            .filter(|r| r.method == TimingMethod::RealTime)
+            .collect();
+
+        assert_eq!(real_time.len(), 2);
+        assert_eq!(real_time[0].index, 0);
+        assert_eq!(real_time[0].old, Some(TimeSpan::from_seconds(100.0)));
+        assert_eq!(real_time[0].new, Some(TimeSpan::from_seconds(25.0)));
+        assert_eq!(real_time[0].attempt, Some(3));
+        // Ties go to the earlier attempt
+        assert_eq!(real_time[1].attempt, Some(1));
+    }
+
+    #[test]
+    fn times_after_a_skipped_split_are_not_golds() {
+        // Attempt 2 skipped S0, so its S1 time covers both segments
+        let run = history_run(&[
+            &[(1, real(30.0)), (2, Time::new())],
+            &[(1, real(40.0)), (2, real(35.0))],
+        ]);
+        let real_time: Vec<_> = recalculate_golds(&run)
+            .into_iter()
+            .filter(|r| r.method == TimingMethod::RealTime)
+            .collect();
+
+        assert_eq!(real_time[1].index, 1);
+        assert_eq!(real_time[1].new, Some(TimeSpan::from_seconds(40.0)));
+        assert_eq!(real_time[1].attempt, Some(1));
+    }
+
+    #[test]
+    fn timing_methods_are_recalculated_separately() {
+        let both = |rt: f64, gt: f64| {
+            Time::new()
+                .with_real_time(Some(TimeSpan::from_seconds(rt)))
+                .with_game_time(Some(TimeSpan::from_seconds(gt)))
+        };
+        let mut run = history_run(&[&[(1, both(30.0, 20.0)), (2, both(28.0, 22.0))]]);
+        run.segment_mut(0).set_best_segment_time(both(28.0, 25.0));
+
+        let recalculated = recalculate_golds(&run);
+        // Real time is already right
+        assert_eq!(recalculated.len(), 1);
+        assert_eq!(recalculated[0].method, TimingMethod::GameTime);
+        assert_eq!(recalculated[0].new, Some(TimeSpan::from_seconds(20.0)));
+        assert_eq!(recalculated[0].attempt, Some(1));
+    }
+
+    #[test]
+    fn zero_durations_are_invalid_and_empty_histories_keep_their_gold() {
+        let run = history_run(&[&[(1, real(0.0)), (2, real(35.0))], &[(1, real(0.0))], &[]]);
+        let recalculated: Vec<_> = recalculate_golds(&run)
+            .into_iter()
+            .filter(|r| r.method == TimingMethod::RealTime)
+            .collect();
+
+        assert_eq!(recalculated[0].new, Some(TimeSpan::from_seconds(35.0)));
+        for flagged in &recalculated[1..] {
+            assert_eq!(flagged.new, None);
+            assert!(!flagged.changes());
+        }
+        assert_eq!(recalculated.len(), 3);
+    }
+
+    #[test]
+    fn recalculated_golds_are_dated_by_their_attempt() {
+        use livesplit_core::AtomicDateTime;
+        use time::{Date, Month};
+
+        let mut run = history_run(&[&[(1, real(30.0))], &[(2, real(40.0))], &[(3, real(50.0))]]);
+        let at = |year, month, day| {
+            let date = Date::from_calendar_date(year, month, day).unwrap();
+            Some(AtomicDateTime::new(
+                date.with_hms(12, 0, 0).unwrap().assume_utc(),
+                false,
+            ))
+        };
+        run.add_attempt_with_index(
+            Time::new(),
+            1,
+            at(2023, Month::August, 14),
+            at(2023, Month::August, 15),
+            None,
+        );
+        // Attempts that never ended are dated by their start
+        run.add_attempt_with_index(Time::new(), 2, at(2021, Month::March, 2), None, None);
+        // Attempt 3 is missing from the history
+
+        let dates: Vec<_> = recalculate_golds(&run)
+            .into_iter()
+            .filter(|r| r.method == TimingMethod::RealTime)
+            .map(|r| r.date.map(|date| date.to_string()))
+            .collect();
+        assert_eq!(
+            dates,
+            [
+                Some("2023-08-15".to_owned()),
+                Some("2021-03-02".to_owned()),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn attempt_dates_are_local_like_the_dates_of_new_golds() {
+        let now = time::OffsetDateTime::now_utc();
+        assert_eq!(
+            local_date(now).map(|date| date.to_string()),
+            crate::context::today()
+        );
+    }
+
+    #[test]
+    fn pending_golds_are_saved_only_if_the_reset_kept_them() {
+        let mut segments = segments();
+        let mut pending = PendingGolds::default();
+
+        pending.observe(
+            &[
+                GoldEvent::Earned(0),
+                GoldEvent::Earned(1),
+                GoldEvent::Earned(2),
+            ],
+            &segments,
+        );
+        pending.observe(&[GoldEvent::Undone(2)], &segments);
+
+        // Saved on reset: segment 0 changed, segment 1 was equalled
+        segments[0].set_best_segment_time(real(9.0));
+        assert_eq!(pending.observe(&[GoldEvent::Cleared], &segments), vec![0]);
+
+        // Discarded attempts keep their bests and save nothing
+        pending.observe(&[GoldEvent::Earned(1)], &segments);
+        assert!(pending.observe(&[GoldEvent::Cleared], &segments).is_empty());
+    }
+}
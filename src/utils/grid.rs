@@ -0,0 +1,146 @@
+//! Spreadsheet-like movement between the cells of the segment editor table.
+
+/// Where a navigation key sends the focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellMove {
+    /// Enter: same column, next row
+    Down,
+    /// Shift+Enter: same column, previous row
+    Up,
+    /// Tab: next editable column, wrapping to the next row
+    Next,
+    /// Shift+Tab: previous editable column, wrapping to the previous row
+    Previous,
+}
+
+/// A cell of the table: `row` is the position among visible rows, `column` the
+/// index among the table's columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellPosition {
+    pub row: u32,
+    pub column: usize,
+}
+
+/// Cell focus moves to from `from`, or `None` when there is nowhere to go (first
+/// or last cell). `editable` tells for every column whether it can take focus;
+/// read-only and hidden columns are skipped.
+pub fn next_cell(
+    from: CellPosition,
+    direction: CellMove,
+    visible_rows: u32,
+    editable: &[bool],
+) -> Option<CellPosition> {
+    let is_editable = |column: usize| editable.get(column).copied().unwrap_or(false);
+
+    match direction {
+        CellMove::Down => (from.row + 1 < visible_rows).then_some(CellPosition {
+            row: from.row + 1,
+            column: from.column,
+        }),
+        CellMove::Up => from.row.checked_sub(1).map(|row| CellPosition {
+            row,
+            column: from.column,
+        }),
+        CellMove::Next => {
+            if let Some(column) = (from.column + 1..editable.len()).find(|&c| is_editable(c)) {
+                return Some(CellPosition {
+                    row: from.row,
+                    column,
+                });
+            }
+            let column = (0..editable.len()).find(|&c| is_editable(c))?;
+            (from.row + 1 < visible_rows).then_some(CellPosition {
+                row: from.row + 1,
+                column,
+            })
+        }
+        CellMove::Previous => {
+            if let Some(column) = (0..from.column.min(editable.len()))
+                .rev()
+                .find(|&c| is_editable(c))
+            {
+                return Some(CellPosition {
+                    row: from.row,
+                    column,
+                });
+            }
+            let column = (0..editable.len()).rev().find(|&c| is_editable(c))?;
+            let row = from.row.checked_sub(1)?;
+            Some(CellPosition { row, column })
+        }
+    }
+}
+
+#[cfg(test)]
+mod grid_tests {
+    use super::*;
+
+    // name, split, segment, best, best pace (read-only), exclude (checkbox)
+    const EDITABLE: [bool; 6] = [true, true, true, true, false, false];
+
+    fn at(row: u32, column: usize) -> CellPosition {
+        CellPosition { row, column }
+    }
+
+    #[test]
+    fn enter_moves_down_and_shift_enter_up_in_the_same_column() {
+        assert_eq!(
+            next_cell(at(0, 2), CellMove::Down, 3, &EDITABLE),
+            Some(at(1, 2))
+        );
+        assert_eq!(next_cell(at(2, 2), CellMove::Down, 3, &EDITABLE), None);
+        assert_eq!(
+            next_cell(at(1, 2), CellMove::Up, 3, &EDITABLE),
+            Some(at(0, 2))
+        );
+        assert_eq!(next_cell(at(0, 2), CellMove::Up, 3, &EDITABLE), None);
+    }
+
+    #[test]
+    fn tab_skips_read_only_columns_and_wraps_rows() {
+        assert_eq!(
+            next_cell(at(0, 1), CellMove::Next, 3, &EDITABLE),
+            Some(at(0, 2))
+        );
+        assert_eq!(
+            next_cell(at(0, 3), CellMove::Next, 3, &EDITABLE),
+            Some(at(1, 0))
+        );
+        assert_eq!(next_cell(at(2, 3), CellMove::Next, 3, &EDITABLE), None);
+    }
+
+    #[test]
+    fn shift_tab_goes_back_and_wraps_to_the_last_editable_column() {
+        assert_eq!(
+            next_cell(at(1, 2), CellMove::Previous, 3, &EDITABLE),
+            Some(at(1, 1))
+        );
+        assert_eq!(
+            next_cell(at(1, 0), CellMove::Previous, 3, &EDITABLE),
+            Some(at(0, 3))
+        );
+        assert_eq!(next_cell(at(0, 0), CellMove::Previous, 3, &EDITABLE), None);
+    }
+
+    #[test]
+    fn only_visible_rows_are_reachable() {
+        // With a filter showing two rows, positions never go past the second one
+        assert_eq!(next_cell(at(1, 1), CellMove::Down, 2, &EDITABLE), None);
+        assert_eq!(next_cell(at(1, 3), CellMove::Next, 2, &EDITABLE), None);
+        assert_eq!(next_cell(at(0, 0), CellMove::Down, 0, &EDITABLE), None);
+    }
+
+    #[test]
+    fn hidden_columns_are_skipped() {
+        let editable = [true, false, false, true];
+        assert_eq!(
+            next_cell(at(0, 0), CellMove::Next, 1, &editable),
+            Some(at(0, 3))
+        );
+        assert_eq!(
+            next_cell(at(0, 3), CellMove::Previous, 1, &editable),
+            Some(at(0, 0))
+        );
+        assert_eq!(next_cell(at(0, 0), CellMove::Next, 1, &[false; 4]), None);
+    }
+}
@@ -0,0 +1,469 @@
+//! Attempt history export for spreadsheets and scripts.
+//!
+//! Every attempt gets one row per segment it reached, taken from the segment
+//! histories: a skipped segment has a row without times (the next segment's
+//! time covers it), and segments after a reset have none. A second table sums
+//! up each attempt. CSV puts the tables in two files, JSON in one document.
+//! Attempt tags of the sidecar are added to the summaries when there are any.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use livesplit_core::{AtomicDateTime, Run, TimeSpan};
+use serde::Serialize;
+use time::UtcOffset;
+
+use crate::utils::naming::ellipsize_middle;
+
+/// One segment of one attempt.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SegmentRow {
+    pub attempt: i32,
+    pub started: Option<String>,
+    pub ended: Option<String>,
+    pub segment: String,
+    pub real_time_ms: Option<i64>,
+    pub game_time_ms: Option<i64>,
+    /// Whether the attempt reached the end rather than being reset.
+    pub completed: bool,
+}
+
+/// Totals of one attempt.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AttemptSummary {
+    pub attempt: i32,
+    pub started: Option<String>,
+    pub ended: Option<String>,
+    pub completed: bool,
+    /// Final time, only for completed attempts.
+    pub real_time_ms: Option<i64>,
+    pub game_time_ms: Option<i64>,
+    pub pause_time_ms: Option<i64>,
+    /// Segments the attempt reached, skipped ones included.
+    pub segments_reached: usize,
+    /// Sum of the segment times recorded, also known for reset attempts.
+    pub played_real_time_ms: Option<i64>,
+    pub played_game_time_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryExport {
+    pub segments: Vec<SegmentRow>,
+    pub attempts: Vec<AttemptSummary>,
+}
+
+impl HistoryExport {
+    /// Adds the tags of the sidecar (by attempt index) to the summaries.
+    pub fn tag_attempts(&mut self, tags: &BTreeMap<i32, Vec<String>>) {
+        for summary in &mut self.attempts {
+            summary.tags = tags.get(&summary.attempt).cloned().unwrap_or_default();
+        }
+    }
+
+    /// Shortens segment names longer than `max_chars` like the splits do (see
+    /// `display.split-name-length`).
+    pub fn shorten_segment_names(&mut self, max_chars: usize) {
+        for row in &mut self.segments {
+            if let Cow::Owned(short) = ellipsize_middle(&row.segment, max_chars) {
+                row.segment = short;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryFormat {
+    Csv,
+    Json,
+}
+
+impl HistoryFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+
+    /// The format a file name asks for, if its extension is a known one.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+fn millis(time: Option<TimeSpan>) -> Option<i64> {
+    time.map(|time| time.total_milliseconds().round() as i64)
+}
+
+/// RFC 3339 in UTC, to the second.
+fn timestamp(date: Option<AtomicDateTime>) -> Option<String> {
+    let date = date?.time.to_offset(UtcOffset::UTC);
+    Some(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        date.year(),
+        u8::from(date.month()),
+        date.day(),
+        date.hour(),
+        date.minute(),
+        date.second()
+    ))
+}
+
+/// Collects the history of `run`, attempts in the order they were made.
+pub fn extract_history(run: &Run) -> HistoryExport {
+    let mut segments = Vec::new();
+    let mut attempts = Vec::new();
+
+    for attempt in run.attempt_history() {
+        let index = attempt.index();
+        let started = timestamp(attempt.started());
+        let ended = timestamp(attempt.ended());
+        let final_time = attempt.time();
+        let completed = final_time.real_time.is_some() || final_time.game_time.is_some();
+
+        let mut reached = 0;
+        let mut played_real = None;
+        let mut played_game = None;
+        for segment in run.segments() {
+            let Some(time) = segment.segment_history().get(index) else {
+                continue;
+            };
+            reached += 1;
+            if let Some(real) = time.real_time {
+                played_real = Some(played_real.unwrap_or_else(TimeSpan::zero) + real);
+            }
+            if let Some(game) = time.game_time {
+                played_game = Some(played_game.unwrap_or_else(TimeSpan::zero) + game);
+            }
+            segments.push(SegmentRow {
+                attempt: index,
+                started: started.clone(),
+                ended: ended.clone(),
+                segment: segment.name().to_owned(),
+                real_time_ms: millis(time.real_time),
+                game_time_ms: millis(time.game_time),
+                completed,
+            });
+        }
+
+        attempts.push(AttemptSummary {
+            attempt: index,
+            started,
+            ended,
+            completed,
+            real_time_ms: millis(final_time.real_time),
+            game_time_ms: millis(final_time.game_time),
+            pause_time_ms: millis(attempt.pause_time()),
+            segments_reached: reached,
+            played_real_time_ms: millis(played_real),
+            played_game_time_ms: millis(played_game),
+            tags: Vec::new(),
+        });
+    }
+
+    HistoryExport { segments, attempts }
+}
+
+/// Quotes a CSV field when it holds a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+fn csv_table(header: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let mut csv = header.join(",");
+    csv.push('\n');
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn optional<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+pub fn segments_csv(export: &HistoryExport) -> String {
+    csv_table(
+        &[
+            "attempt",
+            "started",
+            "ended",
+            "segment",
+            "real_time_ms",
+            "game_time_ms",
+            "completed",
+        ],
+        export.segments.iter().map(|row| {
+            vec![
+                row.attempt.to_string(),
+                optional(&row.started),
+                optional(&row.ended),
+                row.segment.clone(),
+                optional(&row.real_time_ms),
+                optional(&row.game_time_ms),
+                row.completed.to_string(),
+            ]
+        }),
+    )
+}
+
+/// The attempt summaries, with a `tags` column (tags separated by semicolons)
+/// when an attempt has tags.
+pub fn attempts_csv(export: &HistoryExport) -> String {
+    let tagged = export.attempts.iter().any(|row| !row.tags.is_empty());
+    let mut header = vec![
+        "attempt",
+        "started",
+        "ended",
+        "completed",
+        "real_time_ms",
+        "game_time_ms",
+        "pause_time_ms",
+        "segments_reached",
+        "played_real_time_ms",
+        "played_game_time_ms",
+    ];
+    if tagged {
+        header.push("tags");
+    }
+    csv_table(
+        &header,
+        export.attempts.iter().map(|row| {
+            let mut fields = vec![
+                row.attempt.to_string(),
+                optional(&row.started),
+                optional(&row.ended),
+                row.completed.to_string(),
+                optional(&row.real_time_ms),
+                optional(&row.game_time_ms),
+                optional(&row.pause_time_ms),
+                row.segments_reached.to_string(),
+                optional(&row.played_real_time_ms),
+                optional(&row.played_game_time_ms),
+            ];
+            if tagged {
+                fields.push(row.tags.join(";"));
+            }
+            fields
+        }),
+    )
+}
+
+/// Where the attempt totals of a CSV export go: next to the segment rows, e.g.
+/// `history-attempts.csv` for `history.csv`.
+pub fn summary_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!("{stem}-attempts.csv"))
+}
+
+/// The files an export to `path` writes, with their contents.
+pub fn export_files(
+    export: &HistoryExport,
+    path: &Path,
+    format: HistoryFormat,
+) -> serde_json::Result<Vec<(PathBuf, String)>> {
+    Ok(match format {
+        HistoryFormat::Csv => vec![
+            (path.to_path_buf(), segments_csv(export)),
+            (summary_path(path), attempts_csv(export)),
+        ],
+        HistoryFormat::Json => vec![(path.to_path_buf(), serde_json::to_string_pretty(export)?)],
+    })
+}
+
+#[cfg(test)]
+mod history_export_tests {
+    use super::*;
+    use livesplit_core::{Segment, Time};
+    use time::OffsetDateTime;
+
+    /// Attempt, segment, real and game time, completed.
+    type Row<'a> = (i32, &'a str, Option<i64>, Option<i64>, bool);
+
+    fn time(real: f64, game: Option<f64>) -> Time {
+        Time::new()
+            .with_real_time(Some(TimeSpan::from_seconds(real)))
+            .with_game_time(game.map(TimeSpan::from_seconds))
+    }
+
+    fn at(seconds: i64) -> Option<AtomicDateTime> {
+        Some(AtomicDateTime::new(
+            OffsetDateTime::from_unix_timestamp(seconds).unwrap(),
+            false,
+        ))
+    }
+
+    /// Attempt 1 finishes after skipping "Two"; attempt 2 is reset in "Two".
+    fn run() -> Run {
+        let mut run = Run::new();
+        let history: [(&str, Option<Time>, Option<Time>); 3] = [
+            ("One", Some(time(10.0, Some(9.5))), Some(time(11.0, None))),
+            ("Two, the long one", Some(Time::new()), None),
+            ("Three", Some(time(45.0, Some(44.0))), None),
+        ];
+        for (name, first, second) in history {
+            let mut segment = Segment::new(name);
+            if let Some(first) = first {
+                segment.segment_history_mut().insert(1, first);
+            }
+            if let Some(second) = second {
+                segment.segment_history_mut().insert(2, second);
+            }
+            run.push_segment(segment);
+        }
+        run.add_attempt_with_index(time(55.0, Some(53.5)), 1, at(0), at(60), None);
+        run.add_attempt_with_index(
+            Time::new(),
+            2,
+            at(100),
+            at(130),
+            Some(TimeSpan::from_seconds(2.0)),
+        );
+        run
+    }
+
+    #[test]
+    fn skipped_segments_keep_a_row_and_resets_stop_early() {
+        let export = extract_history(&run());
+        let rows: Vec<Row> = export
+            .segments
+            .iter()
+            .map(|r| {
+                (
+                    r.attempt,
+                    r.segment.as_str(),
+                    r.real_time_ms,
+                    r.game_time_ms,
+                    r.completed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (1, "One", Some(10_000), Some(9_500), true),
+                (1, "Two, the long one", None, None, true),
+                (1, "Three", Some(45_000), Some(44_000), true),
+                (2, "One", Some(11_000), None, false),
+            ]
+        );
+        assert_eq!(
+            export.segments[0].started.as_deref(),
+            Some("1970-01-01T00:00:00Z")
+        );
+        assert_eq!(
+            export.segments[3].ended.as_deref(),
+            Some("1970-01-01T00:02:10Z")
+        );
+    }
+
+    #[test]
+    fn summaries_total_finished_and_reset_attempts() {
+        let export = extract_history(&run());
+        let [finished, reset] = export.attempts.as_slice() else {
+            panic!("expected two attempts, got {:?}", export.attempts);
+        };
+
+        assert!(finished.completed);
+        assert_eq!(finished.real_time_ms, Some(55_000));
+        assert_eq!(finished.game_time_ms, Some(53_500));
+        assert_eq!(finished.segments_reached, 3);
+        assert_eq!(finished.played_real_time_ms, Some(55_000));
+
+        assert!(!reset.completed);
+        assert_eq!(reset.real_time_ms, None);
+        assert_eq!(reset.pause_time_ms, Some(2_000));
+        assert_eq!(reset.segments_reached, 1);
+        assert_eq!(reset.played_real_time_ms, Some(11_000));
+        assert_eq!(reset.played_game_time_ms, None);
+    }
+
+    #[test]
+    fn csv_quotes_names_and_leaves_missing_times_empty() {
+        let csv = segments_csv(&extract_history(&run()));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "attempt,started,ended,segment,real_time_ms,game_time_ms,completed"
+        );
+        assert_eq!(
+            lines[2],
+            "1,1970-01-01T00:00:00Z,1970-01-01T00:01:00Z,\"Two, the long one\",,,true"
+        );
+        assert_eq!(lines.len(), 5);
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+
+        let summary = attempts_csv(&extract_history(&run()));
+        assert_eq!(
+            summary.lines().nth(2),
+            Some("2,1970-01-01T00:01:40Z,1970-01-01T00:02:10Z,false,,,2000,1,11000,")
+        );
+    }
+
+    #[test]
+    fn tags_are_exported_only_when_attempts_have_some() {
+        let mut export = extract_history(&run());
+        let json = serde_json::to_value(&export).unwrap();
+        assert!(json["attempts"][0].get("tags").is_none());
+
+        export.tag_attempts(&BTreeMap::from([(
+            2,
+            vec!["race".to_owned(), "sick day".to_owned()],
+        )]));
+        let summary = attempts_csv(&export);
+        let lines: Vec<&str> = summary.lines().collect();
+        assert!(lines[0].ends_with(",played_game_time_ms,tags"));
+        assert!(lines[1].ends_with(",55000,53500,"));
+        assert!(lines[2].ends_with(",11000,,race;sick day"));
+        let json = serde_json::to_value(&export).unwrap();
+        assert_eq!(json["attempts"][1]["tags"][1], "sick day");
+    }
+
+    #[test]
+    fn long_names_are_shortened_like_the_splits() {
+        let mut export = extract_history(&run());
+        export.shorten_segment_names(9);
+        let names: Vec<&str> = export.segments.iter().map(|r| r.segment.as_str()).collect();
+        assert_eq!(names, ["One", "Two,…one", "Three", "One"]);
+    }
+
+    #[test]
+    fn csv_exports_write_the_totals_next_to_the_rows() {
+        let export = extract_history(&run());
+        let path = Path::new("/tmp/Game - Any%.csv");
+        let files = export_files(&export, path, HistoryFormat::Csv).unwrap();
+        let paths: Vec<&Path> = files.iter().map(|(path, _)| path.as_path()).collect();
+        assert_eq!(paths, [path, Path::new("/tmp/Game - Any%-attempts.csv")]);
+
+        let files =
+            export_files(&export, Path::new("/tmp/history.json"), HistoryFormat::Json).unwrap();
+        assert_eq!(files.len(), 1);
+        let json: serde_json::Value = serde_json::from_str(&files[0].1).unwrap();
+        assert_eq!(json["segments"].as_array().unwrap().len(), 4);
+        assert_eq!(json["attempts"][1]["completed"], false);
+        assert_eq!(json["segments"][1]["real_time_ms"], serde_json::Value::Null);
+
+        assert_eq!(
+            HistoryFormat::from_path(Path::new("a.JSON")),
+            Some(HistoryFormat::Json)
+        );
+        assert_eq!(HistoryFormat::from_path(Path::new("a.txt")), None);
+    }
+}
@@ -0,0 +1,300 @@
+//! Command hooks: local commands run on timer events, e.g. to switch OBS scenes
+//! or log splits to other tooling.
+//!
+//! Commands are parsed like a shell would, run without a shell, and describe
+//! the event through `TUXSPLIT_*` environment variables. They run in the
+//! background; a failing hook is logged and never affects the timer.
+
+use std::collections::VecDeque;
+use std::ffi::OsStr;
+use std::time::{Duration, Instant};
+
+use gtk4::gio;
+use livesplit_core::{Timer, TimerPhase};
+use tracing::{debug, warn};
+
+use crate::utils::flash::{FlashEvent, SplitState, detect_event};
+use crate::utils::golds::GoldEvent;
+
+/// At most this many hooks are started per `SPAWN_WINDOW`, so undoing and
+/// redoing splits quickly does not start a storm of processes.
+pub const MAX_SPAWNS: usize = 4;
+pub const SPAWN_WINDOW: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    Start,
+    Split,
+    Gold,
+    Reset,
+    Finish,
+}
+
+impl HookKind {
+    /// Value of `TUXSPLIT_EVENT`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Split => "split",
+            Self::Gold => "gold",
+            Self::Reset => "reset",
+            Self::Finish => "finish",
+        }
+    }
+}
+
+/// An event with the segment it is about, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HookEvent {
+    pub kind: HookKind,
+    pub index: Option<usize>,
+}
+
+impl HookEvent {
+    const fn new(kind: HookKind, index: Option<usize>) -> Self {
+        Self { kind, index }
+    }
+}
+
+/// The hooks to run for the timer going from `before` to `after`, given the
+/// gold events of the same update. The final split yields both a split and a
+/// finish; skips and undos yield nothing.
+pub fn detect_hook_events(
+    before: SplitState,
+    after: SplitState,
+    has_split_time: impl FnOnce(usize) -> bool,
+    golds: &[GoldEvent],
+) -> Vec<HookEvent> {
+    let mut events = Vec::new();
+    let running = |state: SplitState| state.phase != TimerPhase::NotRunning;
+    if !running(before) && running(after) {
+        events.push(HookEvent::new(HookKind::Start, Some(0)));
+    } else if running(before) && !running(after) {
+        events.push(HookEvent::new(HookKind::Reset, before.index));
+    } else if let Some((FlashEvent::Split, index)) = detect_event(before, after, has_split_time) {
+        events.push(HookEvent::new(HookKind::Split, Some(index)));
+    }
+    events.extend(golds.iter().filter_map(|gold| match gold {
+        GoldEvent::Earned(index) => Some(HookEvent::new(HookKind::Gold, Some(*index))),
+        _ => None,
+    }));
+    if before.phase != TimerPhase::Ended && after.phase == TimerPhase::Ended {
+        events.push(HookEvent::new(HookKind::Finish, before.index));
+    }
+    events
+}
+
+/// The environment describing `event`, read from `timer` after it happened.
+/// Variables without a value (e.g. the time of a reset) are left out:
+///
+/// - `TUXSPLIT_EVENT`: start, split, gold, reset or finish
+/// - `TUXSPLIT_SPLIT_INDEX`: the segment, from 0
+/// - `TUXSPLIT_SEGMENT_NAME`
+/// - `TUXSPLIT_TIME_MS`: split time of the segment, in the current timing method
+/// - `TUXSPLIT_DELTA_MS`: that time minus the current comparison's, negative
+///   when ahead
+pub fn hook_env(event: &HookEvent, timer: &Timer) -> Vec<(&'static str, String)> {
+    let mut env = vec![("TUXSPLIT_EVENT", event.kind.name().to_owned())];
+    let Some(index) = event.index else {
+        return env;
+    };
+    env.push(("TUXSPLIT_SPLIT_INDEX", index.to_string()));
+    let Some(segment) = timer.run().segments().get(index) else {
+        return env;
+    };
+    env.push(("TUXSPLIT_SEGMENT_NAME", segment.name().to_owned()));
+    if matches!(event.kind, HookKind::Start | HookKind::Reset) {
+        return env;
+    }
+
+    let method = timer.current_timing_method();
+    let Some(time) = segment.split_time()[method] else {
+        return env;
+    };
+    env.push((
+        "TUXSPLIT_TIME_MS",
+        time.to_duration().whole_milliseconds().to_string(),
+    ));
+    if let Some(comparison) = segment.comparison(timer.current_comparison())[method] {
+        let delta = time - comparison;
+        env.push((
+            "TUXSPLIT_DELTA_MS",
+            delta.to_duration().whole_milliseconds().to_string(),
+        ));
+    }
+    env
+}
+
+/// Limits hooks to `MAX_SPAWNS` per `SPAWN_WINDOW`.
+#[derive(Debug, Default)]
+pub struct HookLimiter {
+    recent: VecDeque<Instant>,
+}
+
+impl HookLimiter {
+    /// Whether a hook may start at `now`, counting it if so.
+    pub fn allow(&mut self, now: Instant) -> bool {
+        while self
+            .recent
+            .front()
+            .is_some_and(|&start| now.duration_since(start) >= SPAWN_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        if self.recent.len() >= MAX_SPAWNS {
+            return false;
+        }
+        self.recent.push_back(now);
+        true
+    }
+}
+
+/// Starts `command` with `env` added to the environment, without waiting for it.
+/// Its exit status is checked in the background, and a failure logged with the
+/// first line it wrote to stderr.
+pub fn spawn(command: &str, env: &[(&'static str, String)]) {
+    let argv = match glib::shell_parse_argv(command) {
+        Ok(argv) => argv,
+        Err(e) => {
+            warn!("Could not parse the hook {command:?}: {e}");
+            return;
+        }
+    };
+    let launcher = gio::SubprocessLauncher::new(
+        gio::SubprocessFlags::STDOUT_SILENCE | gio::SubprocessFlags::STDERR_PIPE,
+    );
+    for (key, value) in env {
+        launcher.setenv(key, value, true);
+    }
+    let args: Vec<&OsStr> = argv.iter().map(|arg| arg.as_os_str()).collect();
+    let process = match launcher.spawn(&args) {
+        Ok(process) => process,
+        Err(e) => {
+            warn!("Could not run the hook {command:?}: {e}");
+            return;
+        }
+    };
+    debug!("Started the hook {command:?}");
+
+    let command = command.to_owned();
+    glib::MainContext::default().spawn_local(async move {
+        let stderr = match process.communicate_utf8_future(None).await {
+            Ok((_, stderr)) => stderr.unwrap_or_default(),
+            Err(e) => {
+                warn!("Could not wait for the hook {command:?}: {e}");
+                return;
+            }
+        };
+        if process.has_exited() && process.exit_status() == 0 {
+            return;
+        }
+        let first_line = stderr.lines().next().unwrap_or_default();
+        if process.has_exited() {
+            warn!(
+                "The hook {command:?} exited with {}: {first_line}",
+                process.exit_status()
+            );
+        } else {
+            warn!("The hook {command:?} was killed: {first_line}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod hooks_tests {
+    use super::*;
+    use livesplit_core::{Run, Segment, TimeSpan, TimingMethod};
+
+    fn state(phase: TimerPhase, index: Option<usize>) -> SplitState {
+        SplitState { phase, index }
+    }
+
+    fn kinds(events: &[HookEvent]) -> Vec<HookKind> {
+        events.iter().map(|event| event.kind).collect()
+    }
+
+    #[test]
+    fn timer_changes_map_to_hooks() {
+        let idle = state(TimerPhase::NotRunning, None);
+        let at = |index| state(TimerPhase::Running, Some(index));
+        let ended = state(TimerPhase::Ended, Some(2));
+
+        assert_eq!(
+            detect_hook_events(idle, at(0), |_| true, &[]),
+            vec![HookEvent::new(HookKind::Start, Some(0))]
+        );
+        assert_eq!(
+            detect_hook_events(at(0), at(1), |_| true, &[GoldEvent::Earned(0)]),
+            vec![
+                HookEvent::new(HookKind::Split, Some(0)),
+                HookEvent::new(HookKind::Gold, Some(0)),
+            ]
+        );
+        assert_eq!(
+            kinds(&detect_hook_events(at(1), ended, |_| true, &[])),
+            vec![HookKind::Split, HookKind::Finish]
+        );
+        assert_eq!(
+            detect_hook_events(at(1), idle, |_| true, &[GoldEvent::Cleared]),
+            vec![HookEvent::new(HookKind::Reset, Some(1))]
+        );
+
+        // Skips, undos and ticks without changes run nothing
+        assert!(detect_hook_events(at(0), at(1), |_| false, &[]).is_empty());
+        assert!(detect_hook_events(at(1), at(0), |_| true, &[GoldEvent::Undone(0)]).is_empty());
+        assert!(detect_hook_events(at(1), at(1), |_| true, &[]).is_empty());
+    }
+
+    #[test]
+    fn the_environment_describes_the_split() {
+        let mut run = Run::new();
+        let mut first = Segment::new("Forest");
+        first.comparison_mut("Personal Best")[TimingMethod::RealTime] =
+            Some(TimeSpan::from_seconds(62.0));
+        run.push_segment(first);
+        run.push_segment(Segment::new("Castle"));
+        run.segments_mut()[0].split_time_mut()[TimingMethod::RealTime] =
+            Some(TimeSpan::from_seconds(60.5));
+        let env = |event: HookEvent, run: &Run| {
+            let timer = Timer::new(run.clone()).unwrap();
+            hook_env(&event, &timer)
+        };
+
+        assert_eq!(
+            env(HookEvent::new(HookKind::Split, Some(0)), &run),
+            vec![
+                ("TUXSPLIT_EVENT", "split".to_owned()),
+                ("TUXSPLIT_SPLIT_INDEX", "0".to_owned()),
+                ("TUXSPLIT_SEGMENT_NAME", "Forest".to_owned()),
+                ("TUXSPLIT_TIME_MS", "60500".to_owned()),
+                ("TUXSPLIT_DELTA_MS", "-1500".to_owned()),
+            ]
+        );
+        // No comparison, no delta
+        run.segments_mut()[1].split_time_mut()[TimingMethod::RealTime] =
+            Some(TimeSpan::from_seconds(90.0));
+        let finish = env(HookEvent::new(HookKind::Finish, Some(1)), &run);
+        assert_eq!(
+            finish.last().unwrap(),
+            &("TUXSPLIT_TIME_MS", "90000".to_owned())
+        );
+        // Resets and starts carry no time
+        assert_eq!(env(HookEvent::new(HookKind::Reset, Some(1)), &run).len(), 3);
+        assert_eq!(
+            env(HookEvent::new(HookKind::Reset, None), &run),
+            vec![("TUXSPLIT_EVENT", "reset".to_owned())]
+        );
+    }
+
+    #[test]
+    fn bursts_of_hooks_are_limited() {
+        let mut limiter = HookLimiter::default();
+        let start = Instant::now();
+        for _ in 0..MAX_SPAWNS {
+            assert!(limiter.allow(start));
+        }
+        assert!(!limiter.allow(start + Duration::from_millis(500)));
+        // Once the window has passed, hooks run again
+        assert!(limiter.allow(start + SPAWN_WINDOW));
+    }
+}
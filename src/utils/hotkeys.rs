@@ -0,0 +1,455 @@
+//! Lenient parsing of the `hotkeys` section of the config.
+//!
+//! livesplit-core deserializes the whole section at once, so a single typo'd key
+//! name used to throw away the entire config. Here every binding is parsed on its
+//! own: a bad one is left unbound and reported, the others still apply. Actions
+//! missing from the section keep their default key.
+//!
+//! Pausing can be one key that toggles (`toggle_pause`, called `pause` in older
+//! configs) or separate keys for pausing and resuming. livesplit-core's
+//! `HotkeyConfig` only has the toggle, so the other two are kept next to it, as
+//! is the key of focus mode (see `utils::focus`), which is no timer action.
+
+use std::collections::HashMap;
+
+use livesplit_core::HotkeyConfig;
+use livesplit_core::hotkey::{Hook, Hotkey};
+use serde::Serialize;
+use serde::ser::{SerializeMap, Serializer};
+use serde_yaml::Value;
+use tracing::warn;
+
+use crate::utils::focus::FocusToggles;
+use crate::utils::reset_guard::UserActions;
+use crate::utils::timer_actions::{ActionQueue, TimerAction};
+
+/// Config names of the hotkey actions, with the names shown to users.
+pub const HOTKEY_ACTIONS: [(&str, &str); 12] = [
+    ("split", "Start / Split"),
+    ("reset", "Reset"),
+    ("undo", "Undo Split"),
+    ("skip", "Skip Split"),
+    ("toggle_pause", "Pause / Resume"),
+    ("pause_only", "Pause"),
+    ("resume", "Resume"),
+    ("undo_all_pauses", "Undo All Pauses"),
+    ("previous_comparison", "Previous Comparison"),
+    ("next_comparison", "Next Comparison"),
+    ("toggle_timing_method", "Toggle Timing Method"),
+    ("toggle_focus_mode", "Toggle Focus Mode"),
+];
+
+/// Names older configs used, with the action they stand for now.
+const LEGACY_ACTIONS: [(&str, &str); 1] = [("pause", "toggle_pause")];
+
+/// The key of every hotkey action. Only the toggle is bound to pausing by
+/// default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkeys {
+    /// livesplit-core's bindings, its `pause` being the toggle.
+    pub timer: HotkeyConfig,
+    pub pause_only: Option<Hotkey>,
+    pub resume: Option<Hotkey>,
+    pub focus_mode: Option<Hotkey>,
+}
+
+impl Hotkeys {
+    /// The key of the action with that config name.
+    pub fn key(&self, action: &str) -> Option<Hotkey> {
+        let mut hotkeys = *self;
+        slot(&mut hotkeys, action).and_then(|slot| *slot)
+    }
+}
+
+/// Written under the current action names, so saving migrates older configs.
+impl Serialize for Hotkeys {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(HOTKEY_ACTIONS.len()))?;
+        for (name, _) in HOTKEY_ACTIONS {
+            map.serialize_entry(name, &self.key(name))?;
+        }
+        map.end()
+    }
+}
+
+/// A problem found in the `hotkeys` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotkeyDiagnostic {
+    /// The section is not a mapping and was ignored as a whole.
+    Malformed,
+    /// The key of `action` could not be read; the action is unbound.
+    UnknownKey { action: String, key: String },
+    /// Not a hotkey action.
+    UnknownAction(String),
+    /// `key` was given to several actions. Only `kept`, the last one, has it.
+    Duplicate {
+        key: String,
+        unbound: String,
+        kept: String,
+    },
+}
+
+impl std::fmt::Display for HotkeyDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "hotkeys must be a map of actions to keys"),
+            Self::UnknownKey { action, key } => {
+                write!(f, "{action}: unknown key \"{key}\", left unbound")
+            }
+            Self::UnknownAction(action) => write!(f, "{action}: not a hotkey action"),
+            Self::Duplicate { key, unbound, kept } => {
+                write!(f, "{key} is bound to {unbound} and {kept}, keeping {kept}")
+            }
+        }
+    }
+}
+
+fn slot<'a>(hotkeys: &'a mut Hotkeys, action: &str) -> Option<&'a mut Option<Hotkey>> {
+    let config = &mut hotkeys.timer;
+    Some(match action {
+        "split" => &mut config.split,
+        "reset" => &mut config.reset,
+        "undo" => &mut config.undo,
+        "skip" => &mut config.skip,
+        "toggle_pause" => &mut config.pause,
+        "pause_only" => &mut hotkeys.pause_only,
+        "resume" => &mut hotkeys.resume,
+        "undo_all_pauses" => &mut config.undo_all_pauses,
+        "previous_comparison" => &mut config.previous_comparison,
+        "next_comparison" => &mut config.next_comparison,
+        "toggle_timing_method" => &mut config.toggle_timing_method,
+        "toggle_focus_mode" => &mut hotkeys.focus_mode,
+        _ => return None,
+    })
+}
+
+/// Puts the keys that could not be read back into `section`, the `hotkeys` of a
+/// config being saved, where their action is still unbound, so saving keeps
+/// what the user wrote for them to fix.
+pub fn restore_unread_keys(section: &mut Value, diagnostics: &[HotkeyDiagnostic]) {
+    let Some(entries) = section.as_mapping_mut() else {
+        return;
+    };
+    for diagnostic in diagnostics {
+        if let HotkeyDiagnostic::UnknownKey { action, key } = diagnostic
+            && let Some(slot) = entries.get_mut(action.as_str())
+            && slot.is_null()
+        {
+            *slot = Value::String(key.clone());
+        }
+    }
+}
+
+/// Reads the `hotkeys` section (`None` when the config has none), binding what
+/// it can and reporting the rest.
+pub fn parse_hotkeys(section: Option<&Value>) -> (Hotkeys, Vec<HotkeyDiagnostic>) {
+    let mut config = Hotkeys::default();
+    let mut diagnostics = Vec::new();
+    let entries = match section {
+        None | Some(Value::Null) => return (config, diagnostics),
+        Some(Value::Mapping(entries)) => entries,
+        Some(_) => return (config, vec![HotkeyDiagnostic::Malformed]),
+    };
+
+    // Actions in the order their key was settled: defaults first, then the
+    // section's entries, so the last entry wins duplicates
+    let mut order: Vec<&'static str> = Vec::new();
+    let mut explicit: Vec<&'static str> = Vec::new();
+    for (action, key) in entries {
+        let action = action.as_str().unwrap_or_default();
+        let action = LEGACY_ACTIONS
+            .iter()
+            .find(|(legacy, _)| *legacy == action)
+            .map_or(action, |&(_, current)| current);
+        let Some(&(name, _)) = HOTKEY_ACTIONS.iter().find(|(name, _)| *name == action) else {
+            diagnostics.push(HotkeyDiagnostic::UnknownAction(action.to_owned()));
+            continue;
+        };
+        let hotkey = match key {
+            Value::Null => None,
+            Value::String(key) => key.parse::<Hotkey>().map_or_else(
+                |()| {
+                    diagnostics.push(HotkeyDiagnostic::UnknownKey {
+                        action: name.to_owned(),
+                        key: key.clone(),
+                    });
+                    None
+                },
+                Some,
+            ),
+            other => {
+                diagnostics.push(HotkeyDiagnostic::UnknownKey {
+                    action: name.to_owned(),
+                    key: serde_yaml::to_string(other)
+                        .unwrap_or_default()
+                        .trim()
+                        .to_owned(),
+                });
+                None
+            }
+        };
+        if let Some(slot) = slot(&mut config, name) {
+            *slot = hotkey;
+        }
+        explicit.retain(|&a| a != name);
+        explicit.push(name);
+    }
+    order.extend(
+        HOTKEY_ACTIONS
+            .iter()
+            .map(|&(name, _)| name)
+            .filter(|name| !explicit.contains(name)),
+    );
+    order.extend(explicit);
+
+    let mut owners: HashMap<Hotkey, &'static str> = HashMap::new();
+    for action in order {
+        let Some(hotkey) = slot(&mut config, action).and_then(|slot| *slot) else {
+            continue;
+        };
+        if let Some(previous) = owners.insert(hotkey, action) {
+            if let Some(slot) = slot(&mut config, previous) {
+                *slot = None;
+            }
+            diagnostics.push(HotkeyDiagnostic::Duplicate {
+                key: hotkey.to_string(),
+                unbound: previous.to_owned(),
+                kept: action.to_owned(),
+            });
+        }
+    }
+
+    (config, diagnostics)
+}
+
+/// Display names of the actions without a key.
+pub fn unbound_actions(config: &Hotkeys) -> Vec<&'static str> {
+    HOTKEY_ACTIONS
+        .iter()
+        .filter(|(name, _)| config.key(name).is_none())
+        .map(|&(_, label)| label)
+        .collect()
+}
+
+/// What the key of a hotkey action does to the timer, as livesplit-core's
+/// `HotkeySystem` would. Focus mode leaves the timer alone.
+pub fn hotkey_action(action: &str) -> Option<TimerAction> {
+    Some(match action {
+        "split" => TimerAction::SplitOrStart,
+        "reset" => TimerAction::Reset {
+            update_splits: true,
+        },
+        "undo" => TimerAction::Undo,
+        "skip" => TimerAction::Skip,
+        "toggle_pause" => TimerAction::TogglePauseOrStart,
+        "pause_only" => TimerAction::Pause,
+        "resume" => TimerAction::Resume,
+        "undo_all_pauses" => TimerAction::UndoAllPauses,
+        "previous_comparison" => TimerAction::PreviousComparison,
+        "next_comparison" => TimerAction::NextComparison,
+        "toggle_timing_method" => TimerAction::ToggleTimingMethod,
+        _ => return None,
+    })
+}
+
+/// The global hotkeys. Every key queues its action with the other timer changes
+/// (see `utils::timer_actions`) rather than writing to the timer from the hook
+/// thread, and resets are recorded in `UserActions` so that they can be told
+/// apart from the auto-splitter's. The focus mode key is counted in
+/// `FocusToggles`.
+pub struct QueuedHotkeys {
+    config: Hotkeys,
+    queue: ActionQueue,
+    actions: UserActions,
+    focus: FocusToggles,
+    hook: Option<Hook>,
+}
+
+impl QueuedHotkeys {
+    pub fn new(config: Hotkeys, queue: ActionQueue, actions: UserActions) -> Self {
+        Self {
+            config,
+            queue,
+            actions,
+            focus: FocusToggles::default(),
+            hook: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_focus_toggles(mut self, focus: FocusToggles) -> Self {
+        self.focus = focus;
+        self
+    }
+
+    /// Binds the keys. A key that cannot be bound is left out with a warning.
+    pub fn activate(&mut self) -> Result<(), livesplit_core::hotkey::Error> {
+        if self.hook.is_some() {
+            return Ok(());
+        }
+        let hook = Hook::new()?;
+        for &(name, _) in &HOTKEY_ACTIONS {
+            let Some(hotkey) = self.config.key(name) else {
+                continue;
+            };
+            let registered = if name == "toggle_focus_mode" {
+                let focus = self.focus.clone();
+                hook.register(hotkey, move || focus.press())
+            } else {
+                let Some(action) = hotkey_action(name) else {
+                    continue;
+                };
+                let queue = self.queue.clone();
+                let actions = self.actions.clone();
+                hook.register(hotkey, move || {
+                    if matches!(action, TimerAction::Reset { .. }) {
+                        actions.record();
+                    }
+                    queue.send(action.clone());
+                })
+            };
+            if let Err(e) = registered {
+                warn!("Could not bind {hotkey} to {name}: {e}");
+            }
+        }
+        self.hook = Some(hook);
+        Ok(())
+    }
+
+    pub fn deactivate(&mut self) {
+        self.hook = None;
+    }
+}
+
+#[cfg(test)]
+mod hotkeys_tests {
+    use super::*;
+    use livesplit_core::hotkey::KeyCode;
+
+    fn parse(yaml: &str) -> (Hotkeys, Vec<HotkeyDiagnostic>) {
+        let value: Value = serde_yaml::from_str(yaml).unwrap();
+        parse_hotkeys(value.get("hotkeys"))
+    }
+
+    #[test]
+    fn a_typo_only_unbinds_its_own_action() {
+        let (config, diagnostics) = parse("hotkeys:\n  split: Space\n  reset: Nmpad3\n");
+        assert_eq!(config.timer.split, Some(KeyCode::Space.into()));
+        assert_eq!(config.timer.reset, None);
+        // Untouched actions keep their defaults
+        assert_eq!(config.timer.undo, HotkeyConfig::default().undo);
+        assert_eq!(
+            diagnostics,
+            [HotkeyDiagnostic::UnknownKey {
+                action: "reset".to_owned(),
+                key: "Nmpad3".to_owned(),
+            }]
+        );
+        assert_eq!(unbound_actions(&config)[0], "Reset");
+    }
+
+    #[test]
+    fn missing_section_uses_the_defaults() {
+        let (config, diagnostics) = parse("display:\n  show-eta: true\n");
+        assert_eq!(config, Hotkeys::default());
+        assert!(diagnostics.is_empty());
+
+        let (config, diagnostics) = parse("hotkeys:\n");
+        assert_eq!(config, Hotkeys::default());
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn duplicate_keys_go_to_the_last_action() {
+        let (config, diagnostics) = parse("hotkeys:\n  split: Space\n  toggle_pause: Space\n");
+        assert_eq!(config.timer.split, None);
+        assert_eq!(config.timer.pause, Some(KeyCode::Space.into()));
+        assert_eq!(
+            diagnostics,
+            [HotkeyDiagnostic::Duplicate {
+                key: "Space".to_owned(),
+                unbound: "split".to_owned(),
+                kept: "toggle_pause".to_owned(),
+            }]
+        );
+
+        // Explicit bindings take keys from defaults
+        let (config, diagnostics) = parse("hotkeys:\n  reset: Numpad1\n");
+        assert_eq!(config.timer.reset, Some(KeyCode::Numpad1.into()));
+        assert_eq!(config.timer.split, None);
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn unknown_actions_and_explicit_nulls() {
+        let (config, diagnostics) =
+            parse("hotkeys:\n  splt: Space\n  undo_all_pauses: Ctrl + KeyZ\n  skip: null\n");
+        assert_eq!(
+            diagnostics,
+            [HotkeyDiagnostic::UnknownAction("splt".to_owned())]
+        );
+        assert!(config.timer.undo_all_pauses.is_some());
+        assert_eq!(config.timer.skip, None);
+
+        let (config, diagnostics) = parse("hotkeys: Space\n");
+        assert_eq!(config, Hotkeys::default());
+        assert_eq!(diagnostics, [HotkeyDiagnostic::Malformed]);
+    }
+
+    #[test]
+    fn the_old_pause_key_becomes_the_toggle() {
+        let (config, diagnostics) = parse("hotkeys:\n  pause: KeyP\n");
+        assert!(diagnostics.is_empty());
+        assert_eq!(config.key("toggle_pause"), Some(KeyCode::KeyP.into()));
+        assert_eq!(config.pause_only, None);
+        assert_eq!(config.resume, None);
+
+        // Saved under the new name, which reads back the same
+        let saved = serde_yaml::to_string(&config).unwrap();
+        assert!(saved.contains("toggle_pause: KeyP"), "{saved}");
+        assert!(!saved.lines().any(|line| line.starts_with("pause:")));
+        let value: Value = serde_yaml::from_str(&saved).unwrap();
+        assert_eq!(parse_hotkeys(Some(&value)), (config, Vec::new()));
+    }
+
+    #[test]
+    fn focus_mode_has_no_key_by_default() {
+        assert_eq!(Hotkeys::default().key("toggle_focus_mode"), None);
+        let (config, diagnostics) = parse("hotkeys:\n  toggle_focus_mode: KeyF\n");
+        assert!(diagnostics.is_empty());
+        assert_eq!(config.focus_mode, Some(KeyCode::KeyF.into()));
+        let saved = serde_yaml::to_string(&config).unwrap();
+        assert!(saved.contains("toggle_focus_mode: KeyF"), "{saved}");
+    }
+
+    #[test]
+    fn pausing_and_resuming_can_have_keys_of_their_own() {
+        let (config, diagnostics) =
+            parse("hotkeys:\n  toggle_pause: null\n  pause_only: KeyP\n  resume: KeyR\n");
+        assert!(diagnostics.is_empty());
+        assert_eq!(config.timer.pause, None);
+        assert_eq!(config.key("pause_only"), Some(KeyCode::KeyP.into()));
+        assert_eq!(config.key("resume"), Some(KeyCode::KeyR.into()));
+        assert_eq!(hotkey_action("pause_only"), Some(TimerAction::Pause));
+        assert_eq!(hotkey_action("resume"), Some(TimerAction::Resume));
+
+        // Without them, only the toggle pauses
+        let defaults = Hotkeys::default();
+        assert!(defaults.key("toggle_pause").is_some());
+        let unbound = unbound_actions(&defaults);
+        assert!(unbound.contains(&"Pause") && unbound.contains(&"Resume"));
+    }
+
+    #[test]
+    fn every_hotkey_action_but_focus_mode_queues_a_timer_action() {
+        for (name, _) in HOTKEY_ACTIONS {
+            assert_eq!(
+                hotkey_action(name).is_some(),
+                name != "toggle_focus_mode",
+                "{name}"
+            );
+        }
+        assert_eq!(hotkey_action("split"), Some(TimerAction::SplitOrStart));
+        assert_eq!(hotkey_action("dance"), None);
+    }
+}
@@ -0,0 +1,143 @@
+//! Matching a folder of images to segments by name, for the editor's bulk icon
+//! import.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Extensions of the images looked at, compared case-insensitively.
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "bmp", "webp"];
+
+/// The image chosen for a segment. `ignored` holds the other files that matched
+/// the same name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IconMatch {
+    pub file: PathBuf,
+    pub ignored: Vec<PathBuf>,
+}
+
+/// Lowercased name without spaces or punctuation: "Boss 1 - Ganon!" -> "boss1ganon".
+pub fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The image files directly in `dir`, sorted by file name.
+pub fn list_images(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut images: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_image(path))
+        .collect();
+    images.sort();
+    Ok(images)
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(extension))
+        })
+}
+
+/// The image of every segment, matched by normalized file stem. When several
+/// files match a segment, the alphabetically first is chosen. Segments whose
+/// name normalizes to nothing never match.
+pub fn match_icons(names: &[&str], files: &[PathBuf]) -> Vec<Option<IconMatch>> {
+    names
+        .iter()
+        .map(|name| {
+            let name = normalize_name(name);
+            if name.is_empty() {
+                return None;
+            }
+            let mut candidates: Vec<&PathBuf> = files
+                .iter()
+                .filter(|file| {
+                    file.file_stem()
+                        .is_some_and(|stem| normalize_name(&stem.to_string_lossy()) == name)
+                })
+                .collect();
+            candidates.sort_by_key(|file| file.file_name().map(|n| n.to_ascii_lowercase()));
+            let (first, rest) = candidates.split_first()?;
+            Some(IconMatch {
+                file: (*first).clone(),
+                ignored: rest.iter().map(|&file| file.clone()).collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod icons_tests {
+    use super::*;
+
+    fn files(names: &[&str]) -> Vec<PathBuf> {
+        names
+            .iter()
+            .map(|name| Path::new("/icons").join(name))
+            .collect()
+    }
+
+    #[test]
+    fn names_are_compared_without_case_spaces_or_punctuation() {
+        assert_eq!(normalize_name("Boss 1 - Ganon!"), "boss1ganon");
+        assert_eq!(normalize_name("  "), "");
+        assert_eq!(normalize_name("Éclair_Zone"), "éclairzone");
+    }
+
+    #[test]
+    fn segments_match_files_by_stem() {
+        let matches = match_icons(
+            &["Forest Temple", "Water Temple", "Credits"],
+            &files(&["forest_temple.PNG", "water-temple.jpg", "notes.png"]),
+        );
+        assert_eq!(
+            matches,
+            vec![
+                Some(IconMatch {
+                    file: PathBuf::from("/icons/forest_temple.PNG"),
+                    ignored: Vec::new(),
+                }),
+                Some(IconMatch {
+                    file: PathBuf::from("/icons/water-temple.jpg"),
+                    ignored: Vec::new(),
+                }),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn two_files_for_one_segment_pick_the_first_alphabetically() {
+        let matches = match_icons(&["Boss"], &files(&["boss.png", "Boss.gif", "b o s s.png"]));
+        assert_eq!(
+            matches,
+            vec![Some(IconMatch {
+                file: PathBuf::from("/icons/b o s s.png"),
+                ignored: files(&["Boss.gif", "boss.png"]),
+            })]
+        );
+    }
+
+    #[test]
+    fn unnamed_segments_never_match() {
+        assert_eq!(
+            match_icons(&["", "---"], &files(&[".png"])),
+            vec![None, None]
+        );
+    }
+
+    #[test]
+    fn only_images_are_listed() {
+        assert!(is_image(Path::new("a.PNG")));
+        assert!(is_image(Path::new("a.webp")));
+        assert!(!is_image(Path::new("a.txt")));
+        assert!(!is_image(Path::new("png")));
+    }
+}
@@ -0,0 +1,96 @@
+//! Time lost to loads in the current attempt: how far game time, which leaves
+//! the loads out, is behind real time.
+
+use livesplit_core::TimeSpan;
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadTime {
+    pub loads: time::Duration,
+    /// Part of the attempt's real time spent loading, from 0 to 1.
+    pub share: f64,
+}
+
+impl LoadTime {
+    /// Tooltip of the load time.
+    pub fn summary(&self) -> String {
+        format!("{:.1}% of the attempt spent loading", self.share * 100.0)
+    }
+}
+
+/// Follows the load time from tick to tick. Game time ahead of real time means
+/// the auto-splitter set it wrong; that is warned about once per attempt.
+#[derive(Debug, Default)]
+pub struct LoadTimeTracker {
+    warned: bool,
+}
+
+impl LoadTimeTracker {
+    /// The load time of an attempt at `real` and `game` time. `None` without game
+    /// time, or when no attempt is going (`game` should be `None` then).
+    pub fn observe(&mut self, real: Option<TimeSpan>, game: Option<TimeSpan>) -> Option<LoadTime> {
+        let Some(game) = game else {
+            self.warned = false;
+            return None;
+        };
+        let real = real?.to_duration();
+        let mut loads = real - game.to_duration();
+        if loads.is_negative() {
+            if !self.warned {
+                warn!(
+                    "Game time is ahead of real time by {}, showing no loads",
+                    -loads
+                );
+                self.warned = true;
+            }
+            loads = time::Duration::ZERO;
+        }
+        let share = if real.is_positive() {
+            (loads.as_seconds_f64() / real.as_seconds_f64()).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        Some(LoadTime { loads, share })
+    }
+}
+
+#[cfg(test)]
+mod loads_tests {
+    use super::*;
+
+    fn seconds(seconds: f64) -> Option<TimeSpan> {
+        Some(TimeSpan::from_seconds(seconds))
+    }
+
+    #[test]
+    fn loads_are_real_time_minus_game_time() {
+        let mut tracker = LoadTimeTracker::default();
+        let load_time = tracker.observe(seconds(200.0), seconds(150.0)).unwrap();
+        assert_eq!(load_time.loads, time::Duration::seconds(50));
+        assert_eq!(load_time.summary(), "25.0% of the attempt spent loading");
+    }
+
+    #[test]
+    fn game_time_ahead_is_clamped_and_warned_once() {
+        let mut tracker = LoadTimeTracker::default();
+        let load_time = tracker.observe(seconds(100.0), seconds(101.5)).unwrap();
+        assert_eq!(load_time.loads, time::Duration::ZERO);
+        assert_eq!(load_time.share, 0.0);
+        assert!(tracker.warned);
+
+        // Still ahead: no new warning, but it comes back for the next attempt
+        tracker.observe(seconds(101.0), seconds(102.5));
+        assert!(tracker.warned);
+        tracker.observe(seconds(0.0), None);
+        assert!(!tracker.warned);
+    }
+
+    #[test]
+    fn no_game_time_hides_the_loads() {
+        let mut tracker = LoadTimeTracker::default();
+        assert_eq!(tracker.observe(seconds(100.0), None), None);
+        // At the very start nothing was loaded yet
+        let load_time = tracker.observe(seconds(0.0), seconds(0.0)).unwrap();
+        assert_eq!(load_time.share, 0.0);
+    }
+}
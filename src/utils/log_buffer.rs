@@ -0,0 +1,268 @@
+//! In-memory copy of the latest tracing events, for the debug log window: a GUI
+//! app's stdout is out of reach of most users reporting bugs.
+//!
+//! `RingBufferLayer` is installed next to the stdout formatter in `main.rs` and
+//! keeps the last `LOG_CAPACITY` events. Every event gets a sequence number, so
+//! the window can fetch only what it has not shown yet. The window learns of
+//! new events through the listener of the buffer (see `set_listener`).
+
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+
+/// Events kept for the debug log window.
+pub const LOG_CAPACITY: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEvent {
+    /// Position among every event recorded, evicted ones included.
+    pub seq: u64,
+    /// Local time of day, `HH:MM:SS`.
+    pub time: String,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl LogEvent {
+    /// The event as a line of the log.
+    pub fn render(&self) -> String {
+        format!(
+            "{} {:>5} {}: {}",
+            self.time, self.level, self.target, self.message
+        )
+    }
+}
+
+/// Called after an event is recorded, on the thread that recorded it.
+pub type Listener = Arc<dyn Fn() + Send + Sync>;
+
+pub struct LogBuffer {
+    capacity: usize,
+    state: Mutex<State>,
+    listener: Mutex<Option<Listener>>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    events: VecDeque<LogEvent>,
+    next_seq: u64,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(State::default()),
+            listener: Mutex::new(None),
+        }
+    }
+
+    /// Sets the listener told of every event recorded, replacing the last one.
+    pub fn set_listener(&self, listener: Option<Listener>) {
+        if let Ok(mut current) = self.listener.lock() {
+            *current = listener;
+        }
+    }
+
+    /// Records an event, evicting the oldest one when full.
+    pub fn push(&self, time: String, level: Level, target: String, message: String) {
+        {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            if state.events.len() == self.capacity {
+                state.events.pop_front();
+            }
+            state.events.push_back(LogEvent {
+                seq,
+                time,
+                level,
+                target,
+                message,
+            });
+        }
+        // Cloned out, so a listener that logs does not deadlock
+        let listener = self.listener.lock().ok().and_then(|l| l.clone());
+        if let Some(listener) = listener {
+            listener();
+        }
+    }
+
+    /// Events kept with a sequence number from `seq` on, at `level` or more severe.
+    pub fn since(&self, seq: u64, level: Level) -> Vec<LogEvent> {
+        let Ok(state) = self.state.lock() else {
+            return Vec::new();
+        };
+        state
+            .events
+            .iter()
+            .filter(|event| event.seq >= seq && event.level <= level)
+            .cloned()
+            .collect()
+    }
+
+    /// Every event kept at `level` or more severe.
+    pub fn events(&self, level: Level) -> Vec<LogEvent> {
+        self.since(0, level)
+    }
+
+    /// Sequence number the next event will get.
+    pub fn next_seq(&self) -> u64 {
+        self.state.lock().map_or(0, |state| state.next_seq)
+    }
+}
+
+/// The buffer of the app, filled by the layer `main.rs` installs.
+pub fn log_buffer() -> &'static Arc<LogBuffer> {
+    static BUFFER: OnceLock<Arc<LogBuffer>> = OnceLock::new();
+    BUFFER.get_or_init(|| Arc::new(LogBuffer::new(LOG_CAPACITY)))
+}
+
+/// Tracing layer recording events at `max_level` or more severe into a buffer.
+pub struct RingBufferLayer {
+    buffer: Arc<LogBuffer>,
+    max_level: Level,
+}
+
+impl RingBufferLayer {
+    pub const fn new(buffer: Arc<LogBuffer>, max_level: Level) -> Self {
+        Self { buffer, max_level }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RingBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if *metadata.level() > self.max_level {
+            return;
+        }
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let time = glib::DateTime::now_local()
+            .and_then(|now| now.format("%T"))
+            .map(String::from)
+            .unwrap_or_default();
+        self.buffer.push(
+            time,
+            *metadata.level(),
+            metadata.target().to_owned(),
+            visitor.message,
+        );
+    }
+}
+
+/// The message of an event followed by its other fields, like the stdout output.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let fields = std::mem::take(&mut self.message);
+            let _ = write!(self.message, "{value:?}{fields}");
+        } else {
+            let _ = write!(self.message, " {}={value:?}", field.name());
+        }
+    }
+}
+
+#[cfg(test)]
+mod log_buffer_tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn push(buffer: &LogBuffer, level: Level, message: &str) {
+        buffer.push(
+            "12:00:00".to_owned(),
+            level,
+            "tuxsplit".to_owned(),
+            message.to_owned(),
+        );
+    }
+
+    fn messages(events: &[LogEvent]) -> Vec<&str> {
+        events.iter().map(|event| event.message.as_str()).collect()
+    }
+
+    #[test]
+    fn oldest_events_are_evicted_at_capacity() {
+        let buffer = LogBuffer::new(3);
+        for message in ["a", "b", "c", "d", "e"] {
+            push(&buffer, Level::INFO, message);
+        }
+        let events = buffer.events(Level::TRACE);
+        assert_eq!(messages(&events), ["c", "d", "e"]);
+        assert_eq!(events[0].seq, 2);
+        assert_eq!(buffer.next_seq(), 5);
+        // Only what came after the last event seen
+        assert_eq!(messages(&buffer.since(4, Level::TRACE)), ["e"]);
+        assert!(buffer.since(5, Level::TRACE).is_empty());
+    }
+
+    #[test]
+    fn events_are_filtered_by_level() {
+        let buffer = LogBuffer::new(10);
+        push(&buffer, Level::DEBUG, "debug");
+        push(&buffer, Level::WARN, "warn");
+        push(&buffer, Level::ERROR, "error");
+        push(&buffer, Level::INFO, "info");
+
+        assert_eq!(
+            messages(&buffer.events(Level::INFO)),
+            ["warn", "error", "info"]
+        );
+        assert_eq!(messages(&buffer.events(Level::WARN)), ["warn", "error"]);
+        assert_eq!(buffer.events(Level::TRACE).len(), 4);
+    }
+
+    #[test]
+    fn the_listener_is_told_of_every_event() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let buffer = LogBuffer::new(2);
+        let told = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&told);
+        buffer.set_listener(Some(Arc::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })));
+        for message in ["a", "b", "c"] {
+            push(&buffer, Level::INFO, message);
+        }
+        assert_eq!(told.load(Ordering::SeqCst), 3);
+
+        buffer.set_listener(None);
+        push(&buffer, Level::INFO, "d");
+        assert_eq!(told.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn layer_records_events_up_to_its_level() {
+        let buffer = Arc::new(LogBuffer::new(10));
+        let subscriber = tracing_subscriber::registry()
+            .with(RingBufferLayer::new(Arc::clone(&buffer), Level::DEBUG));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::trace!("too chatty");
+            tracing::debug!(segment = 3, "split");
+            tracing::warn!("late");
+        });
+
+        let events = buffer.events(Level::TRACE);
+        assert_eq!(messages(&events), ["split segment=3", "late"]);
+        assert_eq!(events[1].level, Level::WARN);
+        assert!(
+            events[0]
+                .render()
+                .ends_with("DEBUG tuxsplit::utils::log_buffer::log_buffer_tests: split segment=3")
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Best-effort reader for LiveSplit `.lsl` layout files.
+//!
+//! Only the pieces TuxSplit can make use of are extracted: the layout colors
+//! (converted to CSS `rgba()` values) and the list of components, split into the
+//! ones we can map onto our own UI and the ones we cannot.
+
+use quick_xml::Reader;
+use quick_xml::events::Event;
+
+/// A component of a LiveSplit layout that has a TuxSplit counterpart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutComponent {
+    Title,
+    Splits,
+    PreviousSegment,
+    SumOfBest,
+    Timer,
+}
+
+impl LayoutComponent {
+    /// Maps a LiveSplit component path (e.g. `LiveSplit.Splits.dll`) to a component.
+    pub fn from_path(path: &str) -> Option<Self> {
+        match path.trim() {
+            "LiveSplit.Title.dll" => Some(Self::Title),
+            "LiveSplit.Splits.dll" | "LiveSplit.Subsplits.dll" => Some(Self::Splits),
+            "LiveSplit.PreviousSegment.dll" => Some(Self::PreviousSegment),
+            "LiveSplit.SumOfBest.dll" => Some(Self::SumOfBest),
+            "LiveSplit.Timer.dll" | "LiveSplit.DetailedTimer.dll" => Some(Self::Timer),
+            _ => None,
+        }
+    }
+}
+
+/// Layout colors as CSS color values. Missing or malformed colors are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LayoutColors {
+    pub background: Option<String>,
+    pub ahead: Option<String>,
+    pub behind: Option<String>,
+    pub gold: Option<String>,
+}
+
+/// The result of reading a `.lsl` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportedLayout {
+    pub colors: LayoutColors,
+    pub components: Vec<LayoutComponent>,
+    /// Paths of the components that have no TuxSplit counterpart.
+    pub unsupported: Vec<String>,
+}
+
+impl ImportedLayout {
+    pub fn has_component(&self, component: LayoutComponent) -> bool {
+        self.components.contains(&component)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutImportError {
+    /// The document is not well-formed XML.
+    Xml(String),
+    /// The document is XML, but its root is not a `<Layout>` element.
+    NotALayout,
+}
+
+impl std::fmt::Display for LayoutImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Xml(e) => write!(f, "Could not read layout file: {e}"),
+            Self::NotALayout => write!(f, "The file is not a LiveSplit layout"),
+        }
+    }
+}
+
+/// Parses the contents of a `.lsl` file.
+pub fn parse_layout(source: &str) -> Result<ImportedLayout, LayoutImportError> {
+    let mut reader = Reader::from_str(source);
+    reader.config_mut().trim_text(true);
+
+    let mut layout = ImportedLayout::default();
+    let mut path: Vec<String> = Vec::new();
+    let mut seen_root = false;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                if path.is_empty() {
+                    if name != "Layout" {
+                        return Err(LayoutImportError::NotALayout);
+                    }
+                    seen_root = true;
+                }
+                path.push(name);
+            }
+            Ok(Event::End(_)) => {
+                path.pop();
+            }
+            Ok(Event::Text(t)) => {
+                let text = t
+                    .unescape()
+                    .map_err(|e| LayoutImportError::Xml(e.to_string()))?;
+                let path: Vec<&str> = path.iter().map(String::as_str).collect();
+                match path.as_slice() {
+                    ["Layout", "Settings", setting] => {
+                        let slot = match *setting {
+                            "BackgroundColor" => &mut layout.colors.background,
+                            "AheadGainingTimeColor" => &mut layout.colors.ahead,
+                            "BehindLosingTimeColor" => &mut layout.colors.behind,
+                            "BestSegmentColor" => &mut layout.colors.gold,
+                            _ => continue,
+                        };
+                        *slot = argb_hex_to_css(&text);
+                    }
+                    ["Layout", "Components", "Component", "Path"] => {
+                        match LayoutComponent::from_path(&text) {
+                            Some(component) => layout.components.push(component),
+                            None => layout.unsupported.push(text.trim().to_owned()),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(LayoutImportError::Xml(e.to_string())),
+        }
+    }
+
+    if !seen_root {
+        return Err(LayoutImportError::NotALayout);
+    }
+    Ok(layout)
+}
+
+/// Converts a LiveSplit color (`AARRGGBB`, or `RRGGBB` for opaque colors) into a
+/// CSS `rgba()` value. Returns `None` if the value is not a valid hex color.
+pub fn argb_hex_to_css(value: &str) -> Option<String> {
+    let hex = value.trim().trim_start_matches('#');
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let argb = match hex.len() {
+        8 => u32::from_str_radix(hex, 16).ok()?,
+        6 => 0xFF00_0000 | u32::from_str_radix(hex, 16).ok()?,
+        _ => return None,
+    };
+
+    let [a, r, g, b] = argb.to_be_bytes();
+    let alpha = format!("{:.3}", f64::from(a) / 255.0);
+    let alpha = alpha.trim_end_matches('0').trim_end_matches('.');
+    Some(format!("rgba({r}, {g}, {b}, {alpha})"))
+}
+
+#[cfg(test)]
+mod lsl_tests {
+    use super::*;
+
+    const DEFAULT_LAYOUT: &str = include_str!("../../tests/fixtures/layouts/default.lsl");
+    const CUSTOM_LAYOUT: &str = include_str!("../../tests/fixtures/layouts/custom.lsl");
+
+    #[test]
+    fn argb_hex_converts_to_css_rgba() {
+        assert_eq!(
+            argb_hex_to_css("FF0F0F0F").as_deref(),
+            Some("rgba(15, 15, 15, 1)")
+        );
+        assert_eq!(
+            argb_hex_to_css("80FF0000").as_deref(),
+            Some("rgba(255, 0, 0, 0.502)")
+        );
+        assert_eq!(
+            argb_hex_to_css("00000000").as_deref(),
+            Some("rgba(0, 0, 0, 0)")
+        );
+        assert_eq!(
+            argb_hex_to_css(" 16A6FF ").as_deref(),
+            Some("rgba(22, 166, 255, 1)")
+        );
+    }
+
+    #[test]
+    fn argb_hex_rejects_malformed_values() {
+        assert_eq!(argb_hex_to_css(""), None);
+        assert_eq!(argb_hex_to_css("FFF"), None);
+        assert_eq!(argb_hex_to_css("GG000000"), None);
+        assert_eq!(argb_hex_to_css("+F000000"), None);
+    }
+
+    #[test]
+    fn default_layout_colors_and_components() {
+        let layout = parse_layout(DEFAULT_LAYOUT).expect("valid layout");
+
+        assert_eq!(
+            layout.colors,
+            LayoutColors {
+                background: Some("rgba(15, 15, 15, 1)".to_owned()),
+                ahead: Some("rgba(0, 204, 54, 1)".to_owned()),
+                behind: Some("rgba(204, 18, 0, 1)".to_owned()),
+                gold: Some("rgba(216, 175, 31, 1)".to_owned()),
+            }
+        );
+        assert_eq!(
+            layout.components,
+            vec![
+                LayoutComponent::Title,
+                LayoutComponent::Splits,
+                LayoutComponent::Timer,
+                LayoutComponent::PreviousSegment,
+                LayoutComponent::SumOfBest,
+            ]
+        );
+        assert!(layout.unsupported.is_empty());
+    }
+
+    #[test]
+    fn custom_layout_reports_unsupported_components_and_bad_colors() {
+        let layout = parse_layout(CUSTOM_LAYOUT).expect("valid layout");
+
+        assert_eq!(layout.colors.background, None, "malformed color is ignored");
+        assert_eq!(
+            layout.colors.ahead.as_deref(),
+            Some("rgba(41, 204, 84, 0.8)")
+        );
+        assert_eq!(layout.colors.behind, None, "missing color stays unset");
+        assert_eq!(layout.colors.gold.as_deref(), Some("rgba(255, 215, 0, 1)"));
+
+        assert_eq!(
+            layout.components,
+            vec![LayoutComponent::Splits, LayoutComponent::Timer]
+        );
+        assert!(!layout.has_component(LayoutComponent::SumOfBest));
+        assert_eq!(
+            layout.unsupported,
+            vec![
+                "LiveSplit.Graph.dll".to_owned(),
+                "LiveSplit.Text.dll".to_owned()
+            ]
+        );
+    }
+
+    #[test]
+    fn component_settings_do_not_override_layout_colors() {
+        let source = r#"<Layout><Settings><BestSegmentColor>FFD8AF1F</BestSegmentColor></Settings>
+            <Components><Component><Path>LiveSplit.Splits.dll</Path>
+            <Settings><BestSegmentColor>FF000000</BestSegmentColor></Settings>
+            </Component></Components></Layout>"#;
+        let layout = parse_layout(source).expect("valid layout");
+        assert_eq!(layout.colors.gold.as_deref(), Some("rgba(216, 175, 31, 1)"));
+    }
+
+    #[test]
+    fn non_layout_documents_are_rejected() {
+        assert_eq!(
+            parse_layout("<Run><GameName>Game</GameName></Run>"),
+            Err(LayoutImportError::NotALayout)
+        );
+        assert_eq!(parse_layout(""), Err(LayoutImportError::NotALayout));
+        assert!(matches!(
+            parse_layout("<Layout><Settings></Layout>"),
+            Err(LayoutImportError::Xml(_))
+        ));
+    }
+}
@@ -1 +1,45 @@
+pub mod anomalies;
+pub mod attempt_history;
+pub mod attempts;
+pub mod auto_splitter;
+pub mod auto_splitter_settings;
+pub mod auto_start;
+pub mod clock;
+pub mod compaction;
 pub mod comparisons;
+pub mod contrast;
+pub mod fill;
+pub mod flash;
+pub mod focus;
+pub mod frame_stats;
+pub mod fuzzy;
+pub mod goal;
+pub mod golds;
+pub mod grid;
+pub mod history_export;
+pub mod hooks;
+pub mod hotkeys;
+pub mod icons;
+pub mod loads;
+pub mod log_buffer;
+pub mod lsl;
+pub mod naming;
+pub mod notify_batch;
+pub mod paste;
+pub mod pb_cutoff;
+pub mod pb_dates;
+pub mod pb_pace;
+pub mod percentiles;
+pub mod reached;
+pub mod refresh;
+pub mod regeneration;
+pub mod reset_guard;
+pub mod route_diff;
+pub mod run_snapshots;
+pub mod segment_ops;
+pub mod sidecar_repair;
+pub mod sparkline;
+pub mod splits_io;
+pub mod time_shift;
+pub mod timer_actions;
+pub mod window_placement;
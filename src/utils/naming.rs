@@ -0,0 +1,320 @@
+use std::borrow::Cow;
+
+/// A planned rename: (segment index, old name, new name).
+pub type RenamePlan = Vec<(usize, String, String)>;
+
+/// Strips a trailing number (and the whitespace before it) from a segment name.
+/// "Boss 3" -> "Boss", "Level10" -> "Level", "Intro" -> "Intro".
+pub fn strip_numeric_suffix(name: &str) -> &str {
+    let trimmed = name.trim_end();
+    let without_digits = trimmed.trim_end_matches(|c: char| c.is_ascii_digit());
+    if without_digits.len() == trimmed.len() {
+        return trimmed;
+    }
+    without_digits.trim_end()
+}
+
+/// Plans an auto-numbering of the given segments.
+///
+/// `pattern` supports two tokens:
+/// - `{name}` -> the segment name (with its numeric suffix removed when `{n}` is used)
+/// - `{n}`    -> the running number, starting at `start`
+///
+/// Segments are numbered in the order given by `indices`. Rows whose name would not
+/// change are left out of the plan.
+pub fn plan_auto_number(
+    names: &[String],
+    indices: &[usize],
+    pattern: &str,
+    start: u32,
+) -> RenamePlan {
+    let renumbers = pattern.contains("{n}");
+    let mut plan = Vec::new();
+
+    let targets = indices
+        .iter()
+        .filter_map(|&index| names.get(index).map(|name| (index, name)));
+    for (offset, (index, old)) in targets.enumerate() {
+        let base = if renumbers {
+            strip_numeric_suffix(old)
+        } else {
+            old.as_str()
+        };
+        let number = start.saturating_add(offset as u32);
+        let new = pattern
+            .replace("{name}", base)
+            .replace("{n}", &number.to_string())
+            .trim()
+            .to_owned();
+
+        if !new.is_empty() && new != *old {
+            plan.push((index, old.clone(), new));
+        }
+    }
+
+    plan
+}
+
+/// Plans a literal find & replace over the given segments.
+///
+/// `find` is matched literally (no pattern syntax), so characters such as
+/// `(`, `+` or `.` need no escaping. An empty `find` yields an empty plan.
+pub fn plan_find_replace(
+    names: &[String],
+    indices: &[usize],
+    find: &str,
+    replace: &str,
+) -> RenamePlan {
+    if find.is_empty() {
+        return Vec::new();
+    }
+
+    indices
+        .iter()
+        .filter_map(|&index| {
+            let old = names.get(index)?;
+            if !old.contains(find) {
+                return None;
+            }
+            let new = old.replace(find, replace);
+            (new != *old).then(|| (index, old.clone(), new))
+        })
+        .collect()
+}
+
+/// Strips a trailing copy number such as " (2)" from a segment name.
+/// "Boss (2)" -> "Boss", "Boss" -> "Boss", "(2)" -> "(2)".
+fn strip_copy_suffix(name: &str) -> &str {
+    let Some(open) = name.strip_suffix(')').and_then(|rest| rest.rfind(" (")) else {
+        return name;
+    };
+    let number = &name[open + 2..name.len() - 1];
+    if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) {
+        &name[..open]
+    } else {
+        name
+    }
+}
+
+/// Plans renames `(index, new name)` that make every segment name unique.
+///
+/// The first segment with a given name keeps it; later ones get the lowest free
+/// copy number, "Boss (2)", "Boss (3)"... Names that already carry a copy number
+/// are numbered from their base name, so a second "Boss (2)" becomes "Boss (3)".
+pub fn plan_disambiguation(names: &[String]) -> Vec<(usize, String)> {
+    use std::collections::HashSet;
+
+    let mut taken: HashSet<String> = names.iter().cloned().collect();
+    let mut seen = HashSet::new();
+    let mut plan = Vec::new();
+
+    for (index, name) in names.iter().enumerate() {
+        if seen.insert(name.as_str()) {
+            continue;
+        }
+        let base = strip_copy_suffix(name);
+        let new = (2u32..)
+            .map(|n| format!("{base} ({n})").trim().to_owned())
+            .find(|candidate| !taken.contains(candidate))
+            .expect("copy numbers are unbounded");
+        taken.insert(new.clone());
+        plan.push((index, new));
+    }
+
+    plan
+}
+
+/// `name`, or its lowest free copy number ("Target (2)") when it is taken.
+pub fn unique_name<'a>(name: &str, taken: impl IntoIterator<Item = &'a str>) -> String {
+    use std::collections::HashSet;
+
+    let taken: HashSet<&str> = taken.into_iter().collect();
+    let name = name.trim();
+    if !taken.contains(name) {
+        return name.to_owned();
+    }
+    let base = strip_copy_suffix(name);
+    (2u32..)
+        .map(|n| format!("{base} ({n})").trim().to_owned())
+        .find(|candidate| !taken.contains(candidate.as_str()))
+        .expect("copy numbers are unbounded")
+}
+
+/// Shortens `name` to `max_chars` characters by replacing its middle with "…",
+/// so both the start and the end (often a number) stay readable. Names that fit
+/// are returned as they are.
+pub fn ellipsize_middle(name: &str, max_chars: usize) -> Cow<'_, str> {
+    let count = name.chars().count();
+    if count <= max_chars {
+        return Cow::Borrowed(name);
+    }
+    let kept = max_chars.saturating_sub(1);
+    let head: String = name.chars().take(kept.div_ceil(2)).collect();
+    let tail: String = name.chars().skip(count - kept / 2).collect();
+    Cow::Owned(format!("{}…{}", head.trim_end(), tail.trim_start()))
+}
+
+#[cfg(test)]
+mod naming_tests {
+    use super::*;
+
+    #[test]
+    fn long_names_lose_their_middle() {
+        let name = "Chapter 3: The Very Long Descriptive Title (All Collectibles, No Damage)";
+        let short = ellipsize_middle(name, 20);
+        assert_eq!(short, "Chapter 3:…o Damage)");
+        assert!(short.chars().count() <= 20);
+        assert_eq!(ellipsize_middle("Boss 3", 20), "Boss 3");
+        assert!(matches!(ellipsize_middle("Boss 3", 6), Cow::Borrowed(_)));
+        assert_eq!(ellipsize_middle("Boss 3", 5), "Bo…3");
+    }
+
+    #[test]
+    fn ellipsizing_counts_characters_not_bytes() {
+        assert_eq!(ellipsize_middle("ÉÉÉÉÉÉÉÉ", 8), "ÉÉÉÉÉÉÉÉ");
+        assert_eq!(ellipsize_middle("ÉÉÉÉÉÉÉÉ", 5), "ÉÉ…ÉÉ");
+        assert_eq!(ellipsize_middle("日本語のとても長い名前", 4), "日本…前");
+        assert_eq!(ellipsize_middle("Long name", 1), "…");
+        assert_eq!(ellipsize_middle("Long name", 0), "…");
+    }
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|&s| s.to_owned()).collect()
+    }
+
+    #[test]
+    fn strip_suffix_handles_spaced_glued_and_missing_numbers() {
+        assert_eq!(strip_numeric_suffix("Boss 3"), "Boss");
+        assert_eq!(strip_numeric_suffix("Level10"), "Level");
+        assert_eq!(strip_numeric_suffix("Intro"), "Intro");
+        assert_eq!(strip_numeric_suffix("Boss 3  "), "Boss");
+        assert_eq!(strip_numeric_suffix("2049"), "");
+    }
+
+    #[test]
+    fn auto_number_rewrites_existing_suffixes() {
+        let n = names(&["Boss 3", "Boss 4", "Boss 10"]);
+        let plan = plan_auto_number(&n, &[0, 1, 2], "{name} {n}", 4);
+        assert_eq!(
+            plan,
+            vec![
+                (0, "Boss 3".to_owned(), "Boss 4".to_owned()),
+                (1, "Boss 4".to_owned(), "Boss 5".to_owned()),
+                (2, "Boss 10".to_owned(), "Boss 6".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn auto_number_skips_unchanged_rows_and_respects_subset() {
+        let n = names(&["Boss 1", "Forest", "Boss 3"]);
+        let plan = plan_auto_number(&n, &[0, 2], "{name} {n}", 1);
+        assert_eq!(plan, vec![(2, "Boss 3".to_owned(), "Boss 2".to_owned())]);
+    }
+
+    #[test]
+    fn auto_number_without_number_token_appends_literally() {
+        let n = names(&["Boss 3", "Forest"]);
+        let plan = plan_auto_number(&n, &[0, 1], "{name} (NG+)", 1);
+        assert_eq!(
+            plan,
+            vec![
+                (0, "Boss 3".to_owned(), "Boss 3 (NG+)".to_owned()),
+                (1, "Forest".to_owned(), "Forest (NG+)".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn auto_number_number_only_names_and_out_of_range_indices() {
+        let n = names(&["7", "Split"]);
+        let plan = plan_auto_number(&n, &[0, 5, 1], "{name} {n}", 1);
+        assert_eq!(
+            plan,
+            vec![
+                (0, "7".to_owned(), "1".to_owned()),
+                (1, "Split".to_owned(), "Split 2".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn auto_number_prefix_pattern() {
+        let n = names(&["Intro", "Boss"]);
+        let plan = plan_auto_number(&n, &[0, 1], "{n}. {name}", 1);
+        assert_eq!(plan[0].2, "1. Intro");
+        assert_eq!(plan[1].2, "2. Boss");
+    }
+
+    #[test]
+    fn find_replace_treats_special_characters_literally() {
+        let n = names(&["Boss (NG+)", "Boss (NG)", "a.b*c"]);
+        let plan = plan_find_replace(&n, &[0, 1, 2], "(NG+)", "[NG+]");
+        assert_eq!(
+            plan,
+            vec![(0, "Boss (NG+)".to_owned(), "Boss [NG+]".to_owned())]
+        );
+
+        let plan = plan_find_replace(&n, &[0, 1, 2], ".", "-");
+        assert_eq!(plan, vec![(2, "a.b*c".to_owned(), "a-b*c".to_owned())]);
+    }
+
+    #[test]
+    fn find_replace_replaces_every_occurrence_and_ignores_empty_search() {
+        let n = names(&["Boss Boss", "Forest"]);
+        let plan = plan_find_replace(&n, &[0, 1], "Boss", "Miniboss");
+        assert_eq!(
+            plan,
+            vec![(0, "Boss Boss".to_owned(), "Miniboss Miniboss".to_owned())]
+        );
+        assert!(plan_find_replace(&n, &[0, 1], "", "x").is_empty());
+    }
+
+    #[test]
+    fn find_replace_with_identical_replacement_is_a_noop() {
+        let n = names(&["Boss"]);
+        assert!(plan_find_replace(&n, &[0], "Boss", "Boss").is_empty());
+    }
+
+    #[test]
+    fn disambiguation_numbers_every_repeat_after_the_first() {
+        let n = names(&["Boss", "Forest", "Boss", "Boss"]);
+        assert_eq!(
+            plan_disambiguation(&n),
+            vec![(2, "Boss (2)".to_owned()), (3, "Boss (3)".to_owned())]
+        );
+        assert!(plan_disambiguation(&names(&["Boss", "Forest"])).is_empty());
+    }
+
+    #[test]
+    fn disambiguation_skips_numbers_already_in_use() {
+        let n = names(&["Boss (2)", "Boss", "Boss"]);
+        assert_eq!(plan_disambiguation(&n), vec![(2, "Boss (3)".to_owned())]);
+
+        let n = names(&["Boss (2)", "Boss (2)", "Boss (3)"]);
+        assert_eq!(plan_disambiguation(&n), vec![(1, "Boss (4)".to_owned())]);
+    }
+
+    #[test]
+    fn copy_suffix_needs_a_number_in_parentheses() {
+        assert_eq!(strip_copy_suffix("Boss (12)"), "Boss");
+        assert_eq!(strip_copy_suffix("Boss (NG+)"), "Boss (NG+)");
+        assert_eq!(strip_copy_suffix("Boss ()"), "Boss ()");
+        assert_eq!(strip_copy_suffix("(2)"), "(2)");
+    }
+
+    #[test]
+    fn unique_name_takes_the_lowest_free_copy_number() {
+        assert_eq!(unique_name("Target", ["Personal Best"]), "Target");
+        assert_eq!(unique_name("Target", ["Target"]), "Target (2)");
+        assert_eq!(
+            unique_name("Target", ["Target", "Target (2)", "Target (4)"]),
+            "Target (3)"
+        );
+        // A copy of a copy is numbered from the base name
+        assert_eq!(
+            unique_name("Target (2)", ["Target", "Target (2)"]),
+            "Target (3)"
+        );
+    }
+}
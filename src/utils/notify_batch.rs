@@ -0,0 +1,170 @@
+//! Coalescing of "run-changed" emissions, so that a bulk edit refreshes the
+//! listeners once rather than once per change.
+//!
+//! While notifications are frozen, any number of them is remembered as one, sent
+//! when the outermost freeze thaws. Debounced notifications are sent once no new
+//! one came for a while; each is numbered, and only the latest is sent when its
+//! delay ends. A notification sent right away makes the pending debounced one
+//! unnecessary.
+
+#[derive(Debug, Default)]
+pub struct NotifyBatch {
+    /// Freezes in effect, nested.
+    depth: u32,
+    /// Whether a notification came during the freeze.
+    pending: bool,
+    /// Number of the latest debounced notification.
+    generation: u64,
+}
+
+impl NotifyBatch {
+    pub fn freeze(&mut self) {
+        self.depth += 1;
+    }
+
+    /// Ends a freeze. Returns whether to notify now: it ended the outermost
+    /// freeze and notifications came during it.
+    pub fn thaw(&mut self) -> bool {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth > 0 || !self.pending {
+            return false;
+        }
+        self.pending = false;
+        self.sent();
+        true
+    }
+
+    pub const fn is_frozen(&self) -> bool {
+        self.depth > 0
+    }
+
+    /// A notification to send. Returns whether to send it now, rather than on
+    /// thaw.
+    pub fn notify(&mut self) -> bool {
+        if self.is_frozen() {
+            self.pending = true;
+            return false;
+        }
+        self.sent();
+        true
+    }
+
+    /// A notification to send once no other comes for a while. Returns its
+    /// number, for `debounce_elapsed` when the delay ends.
+    pub fn debounce(&mut self) -> u64 {
+        self.generation += 1;
+        self.generation
+    }
+
+    /// Whether the debounced notification `generation` is still to be sent when
+    /// its delay ends. It is then sent as `notify` says.
+    pub const fn debounce_elapsed(&self, generation: u64) -> bool {
+        generation == self.generation
+    }
+
+    /// Listeners are up to date, so no debounced notification is waiting.
+    const fn sent(&mut self) {
+        self.generation += 1;
+    }
+}
+
+#[cfg(test)]
+mod notify_batch_tests {
+    use super::*;
+
+    /// Sends a notification, counting those the listeners see.
+    fn notify(batch: &mut NotifyBatch, seen: &mut u32) {
+        if batch.notify() {
+            *seen += 1;
+        }
+    }
+
+    fn thaw(batch: &mut NotifyBatch, seen: &mut u32) {
+        if batch.thaw() {
+            *seen += 1;
+        }
+    }
+
+    #[test]
+    fn notifications_pass_through_unless_frozen() {
+        let mut batch = NotifyBatch::default();
+        let mut seen = 0;
+        notify(&mut batch, &mut seen);
+        notify(&mut batch, &mut seen);
+        assert_eq!(seen, 2);
+
+        batch.freeze();
+        notify(&mut batch, &mut seen);
+        notify(&mut batch, &mut seen);
+        assert_eq!(seen, 2);
+        thaw(&mut batch, &mut seen);
+        assert_eq!(seen, 3);
+
+        // A freeze without notifications sends nothing
+        batch.freeze();
+        thaw(&mut batch, &mut seen);
+        assert_eq!(seen, 3);
+        // Nor does an unbalanced thaw
+        thaw(&mut batch, &mut seen);
+        assert_eq!(seen, 3);
+        assert!(!batch.is_frozen());
+    }
+
+    #[test]
+    fn nested_freezes_send_once_on_the_outermost_thaw() {
+        let mut batch = NotifyBatch::default();
+        let mut seen = 0;
+        batch.freeze();
+        notify(&mut batch, &mut seen);
+        batch.freeze();
+        notify(&mut batch, &mut seen);
+        thaw(&mut batch, &mut seen);
+        assert_eq!(seen, 0);
+        batch.freeze();
+        thaw(&mut batch, &mut seen);
+        notify(&mut batch, &mut seen);
+        assert!(batch.is_frozen());
+        thaw(&mut batch, &mut seen);
+        assert_eq!(seen, 1);
+    }
+
+    #[test]
+    fn a_hundred_edits_refresh_the_listeners_once() {
+        let mut batch = NotifyBatch::default();
+        let mut seen = 0;
+        batch.freeze();
+        for edit in 0..100 {
+            // Some edits are made of smaller ones, freezing again
+            if edit % 10 == 0 {
+                batch.freeze();
+                notify(&mut batch, &mut seen);
+                thaw(&mut batch, &mut seen);
+            }
+            notify(&mut batch, &mut seen);
+        }
+        thaw(&mut batch, &mut seen);
+        assert_eq!(seen, 1);
+    }
+
+    #[test]
+    fn only_the_latest_debounced_notification_is_sent() {
+        let mut batch = NotifyBatch::default();
+        let first = batch.debounce();
+        let second = batch.debounce();
+        assert!(!batch.debounce_elapsed(first));
+        assert!(batch.debounce_elapsed(second));
+
+        // Sending one right away makes the pending one unnecessary
+        let pending = batch.debounce();
+        assert!(batch.notify());
+        assert!(!batch.debounce_elapsed(pending));
+
+        // So does thawing with notifications
+        let pending = batch.debounce();
+        batch.freeze();
+        assert!(!batch.notify());
+        assert!(batch.debounce_elapsed(pending));
+        assert!(batch.thaw());
+        assert!(!batch.debounce_elapsed(pending));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::formatters::time::parse_pasted_time;
+
+/// The outcome of reading pasted text as a column of times.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimePastePlan {
+    /// Times to apply, as (row index, milliseconds).
+    pub values: Vec<(usize, i64)>,
+    /// Lines that could not be read as a time, as (1-based line number, text).
+    pub invalid: Vec<(usize, String)>,
+    /// Number of lines that fall past the last row and are ignored.
+    pub overflow: usize,
+}
+
+/// Returns true if `text` should be treated as a column of times rather than
+/// inserted into the focused cell. A trailing line break does not count.
+pub fn is_multiline_paste(text: &str) -> bool {
+    text.trim_end_matches(['\r', '\n']).contains('\n')
+}
+
+/// Reads pasted or dropped text as a list of segment names, one per line:
+/// surrounding whitespace, blank lines and list markers ("1. ", "2) ", "- ",
+/// "* ", "• ") are dropped.
+pub fn normalize_name_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| strip_list_marker(line.trim()).trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// `line` without a leading numbering or bullet followed by whitespace. Names
+/// starting with a number, like "1-1 Forest", are kept whole.
+fn strip_list_marker(line: &str) -> &str {
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let marker = if digits > 0 {
+        line[digits..].strip_prefix(['.', ')']).map(|_| digits + 1)
+    } else {
+        ['-', '*', '•']
+            .into_iter()
+            .find(|bullet| line.starts_with(*bullet))
+            .map(char::len_utf8)
+    };
+    match marker {
+        Some(len) if line[len..].starts_with(char::is_whitespace) => &line[len..],
+        _ => line,
+    }
+}
+
+/// Maps each pasted line onto consecutive rows starting at `start`.
+///
+/// Blank lines leave their row untouched, unreadable lines are reported in
+/// `invalid` (their row is left untouched too), and lines past `row_count` are
+/// only counted.
+pub fn plan_time_paste(text: &str, start: usize, row_count: usize) -> TimePastePlan {
+    let mut plan = TimePastePlan::default();
+
+    for (offset, line) in text.trim_end_matches(['\r', '\n']).lines().enumerate() {
+        let row = start + offset;
+        if row >= row_count {
+            plan.overflow += 1;
+            continue;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_pasted_time(line) {
+            Ok(dur) if !dur.is_negative() => {
+                plan.values.push((row, dur.whole_milliseconds() as i64));
+            }
+            _ => plan.invalid.push((offset + 1, line.trim().to_owned())),
+        }
+    }
+
+    plan
+}
+
+/// Renames consecutive rows starting at `start` after the names pasted, read
+/// like dropped ones (see `normalize_name_lines`). Names past the last of
+/// `names` are left out, as are rows already named so. Returns
+/// (row, old name, new name) entries.
+pub fn plan_name_paste(text: &str, start: usize, names: &[String]) -> Vec<(usize, String, String)> {
+    normalize_name_lines(text)
+        .into_iter()
+        .zip(start..names.len())
+        .filter(|(new, row)| names[*row] != *new)
+        .map(|(new, row)| (row, names[row].clone(), new))
+        .collect()
+}
+
+impl TimePastePlan {
+    /// A short, human readable description of what applying the plan will do.
+    pub fn summary(&self) -> String {
+        let mut lines = vec![match self.values.len() {
+            0 => "No rows will be filled.".to_owned(),
+            1 => "1 row will be filled.".to_owned(),
+            n => format!("{n} rows will be filled."),
+        }];
+
+        if !self.invalid.is_empty() {
+            let listed: Vec<String> = self
+                .invalid
+                .iter()
+                .map(|(line, text)| format!("line {line} (\"{text}\")"))
+                .collect();
+            lines.push(format!("Could not read {}.", listed.join(", ")));
+        }
+
+        match self.overflow {
+            0 => {}
+            1 => lines.push("1 line past the last split will be ignored.".to_owned()),
+            n => lines.push(format!("{n} lines past the last split will be ignored.")),
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod paste_tests {
+    use super::*;
+
+    #[test]
+    fn pasted_names_rename_the_rows_from_the_cell_down() {
+        let names: Vec<String> = ["A", "B", "C"].map(str::to_owned).to_vec();
+        assert_eq!(
+            plan_name_paste("1. B\n\n2. Forest\n3. Summit\n", 1, &names),
+            [(2, "C".to_owned(), "Forest".to_owned()),]
+        );
+        assert_eq!(
+            plan_name_paste("- One\n- Two\n", 0, &names),
+            [
+                (0, "A".to_owned(), "One".to_owned()),
+                (1, "B".to_owned(), "Two".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn multiline_detection_ignores_trailing_newline() {
+        assert!(!is_multiline_paste("1:00.0"));
+        assert!(!is_multiline_paste("1:00.0\n"));
+        assert!(!is_multiline_paste("1:00.0\r\n"));
+        assert!(is_multiline_paste("1:00.0\n2:00.0"));
+        assert!(is_multiline_paste("1:00.0\r\n2:00.0\r\n"));
+    }
+
+    #[test]
+    fn mixed_valid_and_invalid_lines() {
+        let plan = plan_time_paste("1:00.5\nBoss\n\n2:30\r\n3,25\n", 1, 10);
+
+        assert_eq!(plan.values, vec![(1, 60_500), (4, 150_000), (5, 3_250)]);
+        assert_eq!(plan.invalid, vec![(2, "Boss".to_owned())]);
+        assert_eq!(plan.overflow, 0);
+        assert_eq!(
+            plan.summary(),
+            "3 rows will be filled.\nCould not read line 2 (\"Boss\")."
+        );
+    }
+
+    #[test]
+    fn paste_overrunning_the_last_row_is_truncated() {
+        let plan = plan_time_paste("10\n20\n30\n40\n", 2, 4);
+
+        assert_eq!(plan.values, vec![(2, 10_000), (3, 20_000)]);
+        assert!(plan.invalid.is_empty());
+        assert_eq!(plan.overflow, 2);
+        assert_eq!(
+            plan.summary(),
+            "2 rows will be filled.\n2 lines past the last split will be ignored."
+        );
+    }
+
+    #[test]
+    fn name_lists_lose_blank_lines_and_list_markers() {
+        let names = normalize_name_lines(
+            "  1. Forsaken City\r\n\n2) Old Site\n- Celestial Resort\n• Golden Ridge\n\t* Mirror Temple  \n",
+        );
+        assert_eq!(
+            names,
+            [
+                "Forsaken City",
+                "Old Site",
+                "Celestial Resort",
+                "Golden Ridge",
+                "Mirror Temple"
+            ]
+        );
+        // Numbers belonging to the name stay
+        assert_eq!(
+            normalize_name_lines("1-1 Forest\n2.5D Section\n10 Boss\n-"),
+            ["1-1 Forest", "2.5D Section", "10 Boss", "-"]
+        );
+        assert!(normalize_name_lines(" \n\n").is_empty());
+    }
+
+    #[test]
+    fn nothing_readable() {
+        let plan = plan_time_paste("a\nb", 0, 5);
+        assert!(plan.values.is_empty());
+        assert_eq!(plan.invalid.len(), 2);
+        assert!(plan.summary().starts_with("No rows will be filled."));
+    }
+}
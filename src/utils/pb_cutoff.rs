@@ -0,0 +1,196 @@
+//! Whether the attempt in progress can still beat the personal best.
+//!
+//! The PB is lost once the best possible time (see
+//! `comparisons::best_possible_time`) is no faster than it. A segment still ahead
+//! without a best segment time leaves that unknown, which never counts as lost.
+//! `PbCutoff` follows the outlook from tick to tick so that the timer can be marked
+//! and the loss announced once per attempt.
+
+use livesplit_core::{Timer, TimerPhase};
+
+use crate::utils::comparisons::best_possible_time;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PbOutlook {
+    Possible,
+    Lost,
+    /// No personal best, or golds missing ahead.
+    Unknown,
+}
+
+/// Outlook of an attempt that can finish in `best_possible` at best, against the
+/// final time of the personal best. Tying the PB does not beat it.
+pub fn pb_outlook(
+    best_possible: Option<time::Duration>,
+    personal_best: Option<time::Duration>,
+) -> PbOutlook {
+    match (best_possible, personal_best) {
+        (Some(best), Some(pb)) if best >= pb => PbOutlook::Lost,
+        (Some(_), Some(_)) => PbOutlook::Possible,
+        _ => PbOutlook::Unknown,
+    }
+}
+
+/// Outlook of the attempt in progress on `timer`.
+pub fn timer_pb_outlook(timer: &Timer) -> PbOutlook {
+    let method = timer.current_timing_method();
+    let segments = timer.run().segments();
+    let ahead = timer
+        .current_split_index()
+        .map_or(segments, |index| &segments[index.min(segments.len())..]);
+    let golds_known = ahead
+        .iter()
+        .all(|segment| segment.best_segment_time()[method].is_some());
+    let personal_best = segments
+        .last()
+        .and_then(|last| last.personal_best_split_time()[method])
+        .map(|pb| pb.to_duration());
+
+    pb_outlook(
+        golds_known.then(|| best_possible_time(timer)),
+        personal_best,
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PbCutoffEvent {
+    /// The PB can no longer be beaten. `first` is unset when an undo brought it
+    /// back within reach earlier in the same attempt.
+    Lost { first: bool },
+    /// Within reach again, after an undo or a reset.
+    Regained,
+}
+
+#[derive(Debug, Default)]
+pub struct PbCutoff {
+    lost: bool,
+    announced: bool,
+}
+
+impl PbCutoff {
+    /// Follows the timer to `phase`. `outlook` is only asked while an attempt is
+    /// going; a finished attempt keeps the state it ended with.
+    pub fn observe(
+        &mut self,
+        phase: TimerPhase,
+        outlook: impl FnOnce() -> PbOutlook,
+    ) -> Option<PbCutoffEvent> {
+        let lost = match phase {
+            TimerPhase::NotRunning => {
+                self.announced = false;
+                false
+            }
+            TimerPhase::Running | TimerPhase::Paused => outlook() == PbOutlook::Lost,
+            TimerPhase::Ended => return None,
+        };
+        if lost == self.lost {
+            return None;
+        }
+        self.lost = lost;
+        if !lost {
+            return Some(PbCutoffEvent::Regained);
+        }
+        let first = !self.announced;
+        self.announced = true;
+        Some(PbCutoffEvent::Lost { first })
+    }
+
+    pub const fn is_lost(&self) -> bool {
+        self.lost
+    }
+}
+
+#[cfg(test)]
+mod pb_cutoff_tests {
+    use super::*;
+    use time::Duration;
+
+    fn seconds(seconds: i64) -> Option<Duration> {
+        Some(Duration::seconds(seconds))
+    }
+
+    #[test]
+    fn pb_is_lost_once_the_best_possible_time_reaches_it() {
+        assert_eq!(pb_outlook(seconds(99), seconds(100)), PbOutlook::Possible);
+        assert_eq!(pb_outlook(seconds(100), seconds(100)), PbOutlook::Lost);
+        assert_eq!(pb_outlook(seconds(101), seconds(100)), PbOutlook::Lost);
+    }
+
+    #[test]
+    fn missing_golds_or_pb_are_unknown_rather_than_lost() {
+        assert_eq!(pb_outlook(None, seconds(100)), PbOutlook::Unknown);
+        assert_eq!(pb_outlook(seconds(500), None), PbOutlook::Unknown);
+
+        let mut cutoff = PbCutoff::default();
+        assert_eq!(
+            cutoff.observe(TimerPhase::Running, || PbOutlook::Unknown),
+            None
+        );
+        assert!(!cutoff.is_lost());
+    }
+
+    #[test]
+    fn loss_is_announced_once_per_attempt() {
+        let mut cutoff = PbCutoff::default();
+        assert_eq!(
+            cutoff.observe(TimerPhase::Running, || PbOutlook::Possible),
+            None
+        );
+        assert_eq!(
+            cutoff.observe(TimerPhase::Running, || PbOutlook::Lost),
+            Some(PbCutoffEvent::Lost { first: true })
+        );
+        assert_eq!(cutoff.observe(TimerPhase::Paused, || PbOutlook::Lost), None);
+        assert!(cutoff.is_lost());
+
+        // An undo brings it back, and losing it again is not announced twice
+        assert_eq!(
+            cutoff.observe(TimerPhase::Running, || PbOutlook::Possible),
+            Some(PbCutoffEvent::Regained)
+        );
+        assert_eq!(
+            cutoff.observe(TimerPhase::Running, || PbOutlook::Lost),
+            Some(PbCutoffEvent::Lost { first: false })
+        );
+    }
+
+    #[test]
+    fn reset_clears_the_loss_for_the_next_attempt() {
+        let mut cutoff = PbCutoff::default();
+        cutoff.observe(TimerPhase::Running, || PbOutlook::Lost);
+        // Finishing keeps the mark, the outlook is not asked again
+        assert_eq!(cutoff.observe(TimerPhase::Ended, || unreachable!()), None);
+        assert!(cutoff.is_lost());
+
+        assert_eq!(
+            cutoff.observe(TimerPhase::NotRunning, || unreachable!()),
+            Some(PbCutoffEvent::Regained)
+        );
+        assert!(!cutoff.is_lost());
+        assert_eq!(
+            cutoff.observe(TimerPhase::Running, || PbOutlook::Lost),
+            Some(PbCutoffEvent::Lost { first: true })
+        );
+    }
+
+    #[test]
+    fn timer_outlook_needs_the_golds_ahead() {
+        use livesplit_core::{Run, Segment, Time, TimeSpan};
+
+        let time = |seconds: f64| Time::new().with_real_time(Some(TimeSpan::from_seconds(seconds)));
+        let mut run = Run::new();
+        // The middle split was skipped in the PB, leaving its segment without a
+        // best segment time
+        for split in [Some(10.0), None, Some(30.0)] {
+            let mut segment = Segment::new("S");
+            if let Some(split) = split {
+                segment.set_personal_best_split_time(time(split));
+                segment.set_best_segment_time(time(split));
+            }
+            run.push_segment(segment);
+        }
+        let mut timer = Timer::new(run).unwrap();
+        timer.start();
+        assert_eq!(timer_pb_outlook(&timer), PbOutlook::Unknown);
+    }
+}
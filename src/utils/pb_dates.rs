@@ -0,0 +1,209 @@
+//! When the personal best split times were achieved.
+//!
+//! The PB attempt is the earliest attempt of the history that finished in the
+//! final time of the personal best. Each PB split time is dated by that attempt
+//! when the attempt's segment history adds up to it; a split time that was typed
+//! in the editor, or a PB whose final time no attempt matches, is "edited".
+
+use livesplit_core::{Run, TimeSpan, TimingMethod};
+
+/// When a PB split time was achieved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PbSplitDate {
+    /// Day the PB attempt started.
+    Achieved(time::Date),
+    /// No attempt of the history produced the time.
+    Edited,
+}
+
+impl PbSplitDate {
+    pub fn format(self) -> String {
+        match self {
+            Self::Achieved(date) => date.to_string(),
+            Self::Edited => "edited".to_owned(),
+        }
+    }
+}
+
+/// Compared to the millisecond, as the split times are shown.
+fn millis(time: TimeSpan) -> i128 {
+    time.to_duration().whole_milliseconds()
+}
+
+/// Index of the earliest attempt that finished in the PB final time.
+pub fn pb_attempt(run: &Run, method: TimingMethod) -> Option<i32> {
+    let final_time = run.segments().last()?.personal_best_split_time()[method]?;
+    run.attempt_history()
+        .iter()
+        .filter(|attempt| attempt.time()[method].is_some_and(|t| millis(t) == millis(final_time)))
+        .map(|attempt| attempt.index())
+        .min()
+}
+
+/// Per segment, when its PB split time was achieved: `None` for segments without
+/// one, or when the PB attempt is undated. `None` for the whole run when no
+/// attempt of the history finished, leaving nothing to date.
+pub fn pb_split_dates(run: &Run, method: TimingMethod) -> Option<Vec<Option<PbSplitDate>>> {
+    let history = run.attempt_history();
+    if !history
+        .iter()
+        .any(|attempt| attempt.time()[method].is_some())
+    {
+        return None;
+    }
+    let attempt = pb_attempt(run, method).and_then(|index| {
+        let attempt = history.iter().find(|attempt| attempt.index() == index)?;
+        Some((index, attempt.started().or(attempt.ended())))
+    });
+
+    // Split times of the PB attempt, from its segment history
+    let mut total = Some(TimeSpan::zero());
+    let dates = run
+        .segments()
+        .iter()
+        .map(|segment| {
+            let pb = segment.personal_best_split_time()[method]?;
+            let Some((index, started)) = attempt else {
+                return Some(PbSplitDate::Edited);
+            };
+            total = total.and_then(|total| {
+                // Skipped segments have no time, the next one carries theirs
+                let time = segment.segment_history().get(index)?;
+                Some(time[method].map_or(total, |time| total + time))
+            });
+            if total.is_some_and(|total| millis(total) == millis(pb)) {
+                started.map(|date| PbSplitDate::Achieved(date.time.date()))
+            } else {
+                Some(PbSplitDate::Edited)
+            }
+        })
+        .collect();
+    Some(dates)
+}
+
+#[cfg(test)]
+mod pb_dates_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{AtomicDateTime, Segment, Time};
+    use time::{Date, Month};
+
+    fn day(day: u8) -> time::Date {
+        Date::from_calendar_date(2024, Month::May, day).unwrap()
+    }
+
+    /// A run whose attempts have these segment times, started on May 1st, 2nd...
+    /// with the PB of the fastest finished one.
+    fn run_with(attempts: &[&[Option<f64>]]) -> Run {
+        let mut run = Run::new();
+        for name in ["A", "B", "C"] {
+            run.push_segment(Segment::new(name));
+        }
+        for (attempt, times) in (1..).zip(attempts) {
+            let mut total = 0.0;
+            for (segment, time) in run.segments_mut().iter_mut().zip(*times) {
+                total += time.unwrap_or_default();
+                segment
+                    .segment_history_mut()
+                    .insert(attempt, time.map_or_else(Time::new, real));
+            }
+            let finished = times.len() == 3 && times[2].is_some();
+            let started = AtomicDateTime::new(day(attempt as u8).midnight().assume_utc(), false);
+            run.add_attempt_with_index(
+                if finished { real(total) } else { Time::new() },
+                attempt,
+                Some(started),
+                None,
+                None,
+            );
+        }
+        let fastest = run
+            .attempt_history()
+            .iter()
+            .filter_map(|attempt| Some((attempt.time().real_time?, attempt.index())))
+            .min_by_key(|(time, index)| (millis(*time), *index));
+        if let Some((_, index)) = fastest {
+            let mut total = TimeSpan::zero();
+            for segment in run.segments_mut() {
+                let time = segment.segment_history().get(index).unwrap().real_time;
+                total += time.unwrap_or_default();
+                segment.personal_best_split_time_mut().real_time = time.map(|_| total);
+            }
+        }
+        run
+    }
+
+    fn dates(run: &Run) -> Option<Vec<Option<PbSplitDate>>> {
+        pb_split_dates(run, TimingMethod::RealTime)
+    }
+
+    #[test]
+    fn splits_are_dated_by_the_pb_attempt() {
+        let run = run_with(&[
+            &[Some(10.0), Some(20.0), Some(30.0)],
+            &[Some(9.0), Some(19.0), Some(29.0)],
+            &[Some(5.0)],
+        ]);
+        let achieved = Some(PbSplitDate::Achieved(day(2)));
+        assert_eq!(dates(&run), Some(vec![achieved; 3]));
+        assert_eq!(PbSplitDate::Achieved(day(2)).format(), "2024-05-02");
+    }
+
+    #[test]
+    fn the_earliest_of_attempts_with_the_same_time_is_the_pb() {
+        let run = run_with(&[
+            &[Some(10.0), Some(20.0), Some(30.0)],
+            &[Some(15.0), Some(15.0), Some(30.0)],
+            &[Some(10.0), Some(20.0), Some(30.0)],
+        ]);
+        assert_eq!(pb_attempt(&run, TimingMethod::RealTime), Some(1));
+        assert_eq!(
+            dates(&run),
+            Some(vec![Some(PbSplitDate::Achieved(day(1))); 3])
+        );
+    }
+
+    #[test]
+    fn edited_split_times_are_not_dated() {
+        let mut run = run_with(&[&[Some(10.0), None, Some(30.0)]]);
+        // The skipped segment has no PB time, the next one carries it
+        assert_eq!(
+            dates(&run),
+            Some(vec![
+                Some(PbSplitDate::Achieved(day(1))),
+                None,
+                Some(PbSplitDate::Achieved(day(1))),
+            ])
+        );
+        run.segments_mut()[0]
+            .personal_best_split_time_mut()
+            .real_time = Some(TimeSpan::from_seconds(8.0));
+        assert_eq!(
+            dates(&run),
+            Some(vec![
+                Some(PbSplitDate::Edited),
+                None,
+                Some(PbSplitDate::Achieved(day(1))),
+            ])
+        );
+
+        // A final time no attempt finished in
+        run.segments_mut()[2]
+            .personal_best_split_time_mut()
+            .real_time = Some(TimeSpan::from_seconds(28.0));
+        assert_eq!(
+            dates(&run),
+            Some(vec![
+                Some(PbSplitDate::Edited),
+                None,
+                Some(PbSplitDate::Edited)
+            ])
+        );
+    }
+
+    #[test]
+    fn runs_without_finished_attempts_have_nothing_to_date() {
+        assert_eq!(dates(&run_with(&[&[Some(10.0)]])), None);
+        assert_eq!(dates(&Run::new()), None);
+    }
+}
@@ -0,0 +1,132 @@
+//! Whether a late attempt is on pace for a personal best, to call the runner back
+//! to the window when it is hidden (`notifications: urgency-on-pace`).
+//!
+//! An attempt is on pace once it is in its last segments while ahead of the PB.
+//! `PaceAlert` follows that from tick to tick. Being ahead means at least
+//! `PACE_MARGIN` under the PB, and falling behind means being over it. So an
+//! attempt hovering around even raises the alert once, rather than on every tick.
+
+use livesplit_core::comparison::personal_best;
+use livesplit_core::{Timer, TimerPhase};
+use time::Duration;
+
+use crate::utils::comparisons::timer_delta;
+
+/// How far under the PB an attempt has to be to count as ahead.
+pub const PACE_MARGIN: Duration = Duration::milliseconds(500);
+
+/// Delta against the PB of the attempt on `timer`, see `timer_delta`.
+pub fn timer_pb_delta(timer: &Timer) -> Option<Duration> {
+    timer_delta(timer, personal_best::NAME)
+}
+
+/// Segments left in the attempt on `timer`, the current one included.
+pub fn timer_segments_left(timer: &Timer) -> usize {
+    let segments = timer.run().len();
+    timer
+        .current_split_index()
+        .map_or(segments, |index| segments.saturating_sub(index))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaceEvent {
+    /// On PB pace with this many segments to go.
+    Raised { segments_left: usize },
+    /// Off pace again, or the attempt ended.
+    Cleared,
+}
+
+#[derive(Debug, Default)]
+pub struct PaceAlert {
+    raised: bool,
+}
+
+impl PaceAlert {
+    /// Follows the timer to `phase`, with `segments_left` and the PB `delta` of
+    /// the attempt. The alert is raised in the last `last_segments` segments,
+    /// but never while the window is `focused`.
+    pub fn observe(
+        &mut self,
+        phase: TimerPhase,
+        segments_left: usize,
+        delta: Option<Duration>,
+        last_segments: usize,
+        focused: bool,
+    ) -> Option<PaceEvent> {
+        let going = matches!(phase, TimerPhase::Running | TimerPhase::Paused);
+        if self.raised {
+            let behind = delta.is_none_or(|delta| delta > Duration::ZERO);
+            if !going || behind {
+                self.raised = false;
+                return Some(PaceEvent::Cleared);
+            }
+            return None;
+        }
+        let late = segments_left > 0 && segments_left <= last_segments;
+        let ahead = delta.is_some_and(|delta| delta <= -PACE_MARGIN);
+        if going && late && ahead && !focused {
+            self.raised = true;
+            return Some(PaceEvent::Raised { segments_left });
+        }
+        None
+    }
+
+    pub const fn is_raised(&self) -> bool {
+        self.raised
+    }
+}
+
+#[cfg(test)]
+mod pb_pace_tests {
+    use super::*;
+
+    const RUNNING: TimerPhase = TimerPhase::Running;
+
+    fn ms(millis: i64) -> Option<Duration> {
+        Some(Duration::milliseconds(millis))
+    }
+
+    #[test]
+    fn raised_once_ahead_in_the_last_segments_and_not_while_focused() {
+        let mut alert = PaceAlert::default();
+        // Too early in the run
+        assert_eq!(alert.observe(RUNNING, 5, ms(-3000), 3, false), None);
+        // Ahead, but the window is in front
+        assert_eq!(alert.observe(RUNNING, 3, ms(-3000), 3, true), None);
+        assert!(!alert.is_raised());
+        assert_eq!(
+            alert.observe(RUNNING, 3, ms(-3000), 3, false),
+            Some(PaceEvent::Raised { segments_left: 3 })
+        );
+        assert_eq!(alert.observe(RUNNING, 2, ms(-2000), 3, false), None);
+        assert_eq!(
+            alert.observe(TimerPhase::Ended, 0, ms(-2000), 3, false),
+            Some(PaceEvent::Cleared)
+        );
+        assert_eq!(
+            alert.observe(TimerPhase::NotRunning, 3, None, 3, false),
+            None
+        );
+    }
+
+    #[test]
+    fn hovering_around_even_does_not_retrigger() {
+        let mut alert = PaceAlert::default();
+        // Ahead by less than the margin is not enough
+        assert_eq!(alert.observe(RUNNING, 2, ms(-200), 3, false), None);
+        assert!(alert.observe(RUNNING, 2, ms(-600), 3, false).is_some());
+        // Back to even keeps the alert without raising it again
+        for delta in [-100, 0, -400, 0] {
+            assert_eq!(alert.observe(RUNNING, 2, ms(delta), 3, false), None);
+        }
+        assert_eq!(
+            alert.observe(RUNNING, 2, ms(100), 3, false),
+            Some(PaceEvent::Cleared)
+        );
+        // Barely ahead again is not enough to raise it once more
+        for delta in [-100, 100, -300] {
+            assert_eq!(alert.observe(RUNNING, 2, ms(delta), 3, false), None);
+        }
+        assert!(alert.observe(RUNNING, 1, ms(-500), 3, false).is_some());
+    }
+}
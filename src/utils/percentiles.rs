@@ -0,0 +1,221 @@
+//! Realistic goal times from the segment history: each segment at a percentile
+//! of its own times, e.g. the 25th for a time reached in one attempt out of four.
+//!
+//! Segments with fewer than `MIN_SAMPLES` times use their median instead, since a
+//! percentile of a handful of times mostly picks one of them. Only the valid
+//! times of a segment count (see `golds::valid_segment_times`).
+
+use livesplit_core::{Run, TimeSpan, TimingMethod};
+
+use crate::utils::golds::valid_segment_times;
+
+/// Times a segment needs in its history for a percentile other than the median.
+pub const MIN_SAMPLES: usize = 5;
+/// Percentile of the goal shown in the attempt history.
+pub const GOAL_PERCENTILE: u8 = 25;
+
+/// The `percentile` of `values` (0 to 100), interpolated between the two closest
+/// ranks. `None` without values.
+pub fn percentile(values: &[f64], percentile: u8) -> Option<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let last = sorted.len().checked_sub(1)?;
+    let rank = f64::from(percentile.min(100)) / 100.0 * last as f64;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    Some(sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64))
+}
+
+/// The `valid_segment_times` of every segment in `method`.
+pub fn segment_samples(run: &Run, method: TimingMethod) -> Vec<Vec<TimeSpan>> {
+    (0..run.len())
+        .map(|index| {
+            valid_segment_times(run, index, method)
+                .into_iter()
+                .map(|(_, time)| time)
+                .collect()
+        })
+        .collect()
+}
+
+/// The `percentile` of `times`, or their median when there are fewer than
+/// `MIN_SAMPLES`.
+pub fn segment_percentile(times: &[TimeSpan], percentile: u8) -> Option<TimeSpan> {
+    let seconds: Vec<f64> = times.iter().map(TimeSpan::total_seconds).collect();
+    let percentile = if seconds.len() < MIN_SAMPLES {
+        50
+    } else {
+        percentile
+    };
+    self::percentile(&seconds, percentile).map(TimeSpan::from_seconds)
+}
+
+/// Split times of every segment at the `percentile` of its history in `method`.
+/// Splits from the first segment without history on have none.
+pub fn percentile_splits(run: &Run, method: TimingMethod, percentile: u8) -> Vec<Option<TimeSpan>> {
+    let mut total = Some(TimeSpan::zero());
+    segment_samples(run, method)
+        .iter()
+        .map(|times| {
+            total = total
+                .zip(segment_percentile(times, percentile))
+                .map(|(total, time)| total + time);
+            total
+        })
+        .collect()
+}
+
+/// Final time of the goal at `percentile`, if every segment has history.
+pub fn percentile_goal(run: &Run, method: TimingMethod, percentile: u8) -> Option<TimeSpan> {
+    percentile_splits(run, method, percentile)
+        .last()
+        .copied()
+        .flatten()
+}
+
+/// Name offered for the comparison of the goal at `percentile`.
+pub fn goal_name(percentile: u8) -> String {
+    format!("Goal (p{percentile})")
+}
+
+#[cfg(test)]
+mod percentiles_tests {
+    use super::*;
+    use livesplit_core::{Segment, Time};
+
+    fn spans(seconds: &[f64]) -> Vec<TimeSpan> {
+        seconds
+            .iter()
+            .copied()
+            .map(TimeSpan::from_seconds)
+            .collect()
+    }
+
+    fn seconds(time: Option<TimeSpan>) -> Option<f64> {
+        time.map(|time| time.total_seconds())
+    }
+
+    /// A run whose segment histories hold `times`, one list per segment, in
+    /// attempts 1, 2 and so on; `None` is a skipped segment. Game time is twice
+    /// the real time.
+    fn run(times: &[&[Option<f64>]]) -> Run {
+        let mut run = Run::new();
+        for (index, history) in times.iter().enumerate() {
+            let mut segment = Segment::new(format!("Segment {index}"));
+            for (attempt, time) in history.iter().enumerate() {
+                let time = Time::new()
+                    .with_real_time(time.map(TimeSpan::from_seconds))
+                    .with_game_time(time.map(|time| TimeSpan::from_seconds(time * 2.0)));
+                segment
+                    .segment_history_mut()
+                    .insert(attempt as i32 + 1, time);
+            }
+            run.push_segment(segment);
+        }
+        run
+    }
+
+    #[test]
+    fn percentiles_interpolate_between_ranks() {
+        // Ranks 0 to 4: p25 is rank 1, p10 is 40% of the way from rank 0 to 1
+        let values = [50.0, 10.0, 40.0, 20.0, 30.0];
+        assert_eq!(percentile(&values, 25), Some(20.0));
+        assert_eq!(percentile(&values, 10), Some(14.0));
+        assert_eq!(percentile(&values, 50), Some(30.0));
+        assert_eq!(percentile(&values, 100), Some(50.0));
+        // Sorted 59, 60, 60, 61, 61, 62: p25 is rank 1.25, p50 rank 2.5
+        let values = [60.0, 61.0, 59.0, 62.0, 60.0, 61.0];
+        assert_eq!(percentile(&values, 25), Some(60.0));
+        assert_eq!(percentile(&values, 50), Some(60.5));
+        assert_eq!(percentile(&[], 25), None);
+    }
+
+    #[test]
+    fn small_histories_fall_back_to_the_median() {
+        assert_eq!(
+            seconds(segment_percentile(&spans(&[30.0, 10.0, 20.0]), 25)),
+            Some(20.0)
+        );
+        assert_eq!(
+            seconds(segment_percentile(&spans(&[10.0, 40.0, 20.0, 30.0]), 25)),
+            Some(25.0)
+        );
+        // From five times on, the percentile is used
+        assert_eq!(
+            seconds(segment_percentile(
+                &spans(&[10.0, 40.0, 20.0, 30.0, 50.0]),
+                25
+            )),
+            Some(20.0)
+        );
+        assert_eq!(segment_percentile(&[], 25), None);
+    }
+
+    #[test]
+    fn times_of_zero_or_less_are_left_out() {
+        let run = run(&[&[Some(10.0), Some(0.0), Some(-2.0), Some(12.0)]]);
+        assert_eq!(
+            segment_samples(&run, TimingMethod::RealTime)[0],
+            spans(&[10.0, 12.0])
+        );
+    }
+
+    #[test]
+    fn skipped_segments_and_the_ones_after_them_are_left_out() {
+        // Attempt 3 skipped the first segment, so its second one covers both
+        let run = run(&[
+            &[
+                Some(10.0),
+                Some(12.0),
+                None,
+                Some(11.0),
+                Some(14.0),
+                Some(13.0),
+            ],
+            &[
+                Some(20.0),
+                Some(22.0),
+                Some(45.0),
+                Some(21.0),
+                Some(24.0),
+                Some(23.0),
+            ],
+        ]);
+        let samples = segment_samples(&run, TimingMethod::RealTime);
+        assert_eq!(samples[0], spans(&[10.0, 12.0, 11.0, 14.0, 13.0]));
+        assert_eq!(samples[1], spans(&[20.0, 22.0, 21.0, 24.0, 23.0]));
+
+        // Sorted 10..14 and 20..24, p25 is rank 1 of each
+        let splits = percentile_splits(&run, TimingMethod::RealTime, 25);
+        assert_eq!(
+            splits.into_iter().map(seconds).collect::<Vec<_>>(),
+            [Some(11.0), Some(32.0)]
+        );
+    }
+
+    #[test]
+    fn goals_follow_the_timing_method_and_need_every_segment() {
+        let run = run(&[
+            &[Some(10.0), Some(20.0), Some(30.0)],
+            &[Some(5.0), Some(7.0), Some(6.0), Some(8.0), Some(9.0)],
+        ]);
+        // Medians of 10, 20, 30 and p25 of 5 to 9
+        assert_eq!(
+            seconds(percentile_goal(&run, TimingMethod::RealTime, 25)),
+            Some(26.0)
+        );
+        assert_eq!(
+            seconds(percentile_goal(&run, TimingMethod::GameTime, 25)),
+            Some(52.0)
+        );
+
+        let run = self::run(&[&[Some(10.0)], &[None]]);
+        let splits = percentile_splits(&run, TimingMethod::RealTime, 25);
+        assert_eq!(
+            splits.into_iter().map(seconds).collect::<Vec<_>>(),
+            [Some(10.0), None]
+        );
+        assert_eq!(percentile_goal(&run, TimingMethod::RealTime, 25), None);
+        assert_eq!(goal_name(GOAL_PERCENTILE), "Goal (p25)");
+    }
+}
@@ -0,0 +1,163 @@
+//! How often the attempts of the history got to each segment.
+//!
+//! A segment counts as reached by an attempt when the attempt has a time for it
+//! in the segment's history; skipped segments have none. Segments added to the
+//! route later are only measured against the attempts since their first time in
+//! the history, so they are not diluted by attempts that could not reach them.
+
+use livesplit_core::Run;
+
+/// Shown for segments without attempts to measure.
+const NO_ATTEMPTS: &str = "—";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reached {
+    /// Attempts with a time on the segment.
+    pub reached: usize,
+    /// Attempts that could have reached it.
+    pub attempts: usize,
+}
+
+impl Reached {
+    pub fn percent(&self) -> Option<f64> {
+        (self.attempts > 0).then(|| self.reached as f64 * 100.0 / self.attempts as f64)
+    }
+
+    /// The percentage rounded to a whole number, e.g. "42%", or a dash.
+    pub fn format(&self) -> String {
+        self.percent().map_or_else(
+            || NO_ATTEMPTS.to_owned(),
+            |percent| format!("{percent:.0}%"),
+        )
+    }
+}
+
+/// Reached counts of every segment of `run`.
+pub fn reached_per_segment(run: &Run) -> Vec<Reached> {
+    let mut attempts: Vec<i32> = run
+        .attempt_history()
+        .iter()
+        .map(livesplit_core::run::Attempt::index)
+        .collect();
+    attempts.sort_unstable();
+
+    run.segments()
+        .iter()
+        .map(|segment| {
+            // Indices below 1 hold times imported without an attempt
+            let recorded: Vec<i32> = segment
+                .segment_history()
+                .iter()
+                .filter(|(index, time)| {
+                    *index >= 1 && (time.real_time.is_some() || time.game_time.is_some())
+                })
+                .map(|(index, _)| *index)
+                .collect();
+            let since = segment
+                .segment_history()
+                .iter()
+                .map(|(index, _)| *index)
+                .filter(|&index| index >= 1)
+                .min();
+            let counted = |index: &i32| since.is_none_or(|since| *index >= since);
+            let total = attempts.iter().filter(|index| counted(index)).count();
+            Reached {
+                reached: recorded
+                    .iter()
+                    .filter(|index| counted(index) && attempts.binary_search(index).is_ok())
+                    .count(),
+                attempts: total,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod reached_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{Segment, Time};
+
+    /// A run of `attempts` attempts, the segments given with the attempts that
+    /// reached them and the one they first appear in.
+    fn run(attempts: i32, segments: &[(&str, &[i32], i32)]) -> Run {
+        let mut run = Run::new();
+        for index in 1..=attempts {
+            run.add_attempt_with_index(Time::new(), index, None, None, None);
+        }
+        for (name, reached, first) in segments {
+            let mut segment = Segment::new(*name);
+            let history = segment.segment_history_mut();
+            for index in *first..=attempts {
+                // Attempts since the first that did not reach it skipped it
+                let time = if reached.contains(&index) {
+                    real(10.0)
+                } else {
+                    Time::new()
+                };
+                if reached.contains(&index) || index == *first {
+                    history.insert(index, time);
+                }
+            }
+            run.push_segment(segment);
+        }
+        run
+    }
+
+    fn percents(run: &Run) -> Vec<String> {
+        reached_per_segment(run)
+            .iter()
+            .map(Reached::format)
+            .collect()
+    }
+
+    #[test]
+    fn reached_segments_are_counted_over_every_attempt() {
+        let run = run(
+            4,
+            &[
+                ("One", &[1, 2, 3, 4], 1),
+                ("Two", &[1, 2, 3], 1),
+                ("Three", &[2], 1),
+            ],
+        );
+        assert_eq!(percents(&run), ["100%", "75%", "25%"]);
+        assert_eq!(
+            reached_per_segment(&run)[1],
+            Reached {
+                reached: 3,
+                attempts: 4
+            }
+        );
+    }
+
+    #[test]
+    fn segments_added_later_count_from_their_first_attempt() {
+        let added = run(
+            10,
+            &[
+                ("One", &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10], 1),
+                ("New", &[7, 9, 10], 7),
+            ],
+        );
+        assert_eq!(
+            reached_per_segment(&added)[1],
+            Reached {
+                reached: 3,
+                attempts: 4
+            }
+        );
+
+        // A segment no attempt has reached yet counts against every attempt
+        let mut unreached = run(2, &[("One", &[1, 2], 1)]);
+        unreached.push_segment(Segment::new("Never"));
+        assert_eq!(percents(&unreached), ["100%", "0%"]);
+    }
+
+    #[test]
+    fn runs_without_attempts_show_a_dash() {
+        let run = run(0, &[("One", &[], 1)]);
+        assert_eq!(percents(&run), ["—"]);
+        assert_eq!(reached_per_segment(&run)[0].percent(), None);
+    }
+}
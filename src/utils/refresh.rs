@@ -0,0 +1,259 @@
+//! How often the timer needs refreshing: no more than the finest precision on
+//! screen can show. A layout that only shows whole seconds is refreshed four
+//! times a second rather than at the frame rate.
+//!
+//! `general.refresh-rate-ms` replaces the derived rate with a fixed one.
+
+use core::time::Duration;
+
+use crate::config::Config;
+use crate::formatters::TimeFormat;
+use crate::utils::goal::parse_goal;
+
+/// Bounds of `general.refresh-rate-ms`.
+const MIN_FIXED_MS: u64 = 5;
+const MAX_FIXED_MS: u64 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Precision {
+    Seconds,
+    Deciseconds,
+    Centiseconds,
+    Milliseconds,
+}
+
+impl Precision {
+    /// Finest precision `format` shows. Dynamic formats drop their decimals over
+    /// a minute, but show them below it.
+    pub const fn of_format(format: &TimeFormat) -> Self {
+        if format.show_decimals {
+            Self::of_digits(format.decimal_places)
+        } else {
+            Self::Seconds
+        }
+    }
+
+    /// Precision of `digits` decimal places.
+    pub const fn of_digits(digits: u8) -> Self {
+        match digits {
+            0 => Self::Seconds,
+            1 => Self::Deciseconds,
+            2 => Self::Centiseconds,
+            _ => Self::Milliseconds,
+        }
+    }
+
+    /// Refresh interval that keeps the last digit moving. Finer than
+    /// centiseconds is refreshed at the frame rate, as the screen shows no more.
+    pub const fn interval(self) -> Duration {
+        match self {
+            Self::Seconds => Duration::from_millis(250),
+            Self::Deciseconds => Duration::from_millis(100),
+            Self::Centiseconds | Self::Milliseconds => Duration::from_millis(16),
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Seconds => "seconds",
+            Self::Deciseconds => "deciseconds",
+            Self::Centiseconds => "centiseconds",
+            Self::Milliseconds => "milliseconds",
+        }
+    }
+}
+
+/// A part of the timer whose text changes while the timer runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiveComponent {
+    pub name: &'static str,
+    pub precision: Precision,
+}
+
+/// The components of the timer shown with `config` that change with time.
+pub fn live_components(config: &Config) -> Vec<LiveComponent> {
+    let mut components = vec![
+        LiveComponent {
+            name: "timer",
+            precision: Precision::of_format(&config.format.timer),
+        },
+        // The delta of the current split
+        LiveComponent {
+            name: "delta",
+            precision: Precision::of_digits(config.display.delta_digits),
+        },
+    ];
+    if config.display.show_segment_timer {
+        components.push(LiveComponent {
+            name: "segment timer",
+            precision: Precision::of_format(&config.format.segment),
+        });
+    }
+    if config.display.show_load_time {
+        components.push(LiveComponent {
+            name: "load time",
+            precision: Precision::of_format(&config.format.segment),
+        });
+    }
+    let info = &config.general.additional_info;
+    if info.show_best_possible_time || info.show_current_pace || info.show_total_playtime {
+        components.push(LiveComponent {
+            name: "additional info",
+            precision: Precision::of_format(&config.format.comparison),
+        });
+    }
+    // The time left to the goal counts down with the timer
+    if info.show_goal && parse_goal(config.general.goal_time.as_deref()).is_some() {
+        components.push(LiveComponent {
+            name: "goal",
+            precision: Precision::of_format(&config.format.split),
+        });
+    }
+    components
+}
+
+/// How often the timer is refreshed, and why.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshRate {
+    pub interval: Duration,
+    /// Precision the interval was derived from, `None` when it is fixed by
+    /// `general.refresh-rate-ms`.
+    pub precision: Option<Precision>,
+}
+
+impl RefreshRate {
+    /// Refresh rate for `components`, unless `fixed_ms` sets one.
+    pub fn for_components(components: &[LiveComponent], fixed_ms: Option<u64>) -> Self {
+        if let Some(ms) = fixed_ms {
+            return Self {
+                interval: Duration::from_millis(ms.clamp(MIN_FIXED_MS, MAX_FIXED_MS)),
+                precision: None,
+            };
+        }
+        let precision = components
+            .iter()
+            .map(|component| component.precision)
+            .max()
+            .unwrap_or(Precision::Seconds);
+        Self {
+            interval: precision.interval(),
+            precision: Some(precision),
+        }
+    }
+
+    pub fn of_config(config: &Config) -> Self {
+        Self::for_components(&live_components(config), config.general.refresh_rate_ms)
+    }
+
+    /// The debug log line announcing the rate.
+    pub fn describe(&self) -> String {
+        let ms = self.interval.as_millis();
+        match self.precision {
+            Some(precision) => format!(
+                "Refreshing the timer every {ms} ms, for {} on screen",
+                precision.name()
+            ),
+            None => {
+                format!("Refreshing the timer every {ms} ms, as set in general.refresh-rate-ms")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod refresh_tests {
+    use super::*;
+    use crate::formatters::TimeFormatPreset;
+
+    fn seconds_only() -> Config {
+        let mut config = Config::default();
+        config.format.timer = TimeFormat::from_preset(TimeFormatPreset::NoDecimals);
+        config.format.comparison = TimeFormat::from_preset(TimeFormatPreset::NoDecimals);
+        config.display.delta_digits = 0;
+        config
+    }
+
+    #[test]
+    fn the_finest_precision_on_screen_sets_the_rate() {
+        let rate = RefreshRate::of_config(&Config::default());
+        assert_eq!(rate.precision, Some(Precision::Centiseconds));
+        assert_eq!(rate.interval, Duration::from_millis(16));
+
+        let mut config = seconds_only();
+        assert_eq!(
+            RefreshRate::of_config(&config).interval,
+            Duration::from_millis(250)
+        );
+
+        // Deltas with a decimal need tenths
+        config.display.delta_digits = 1;
+        assert_eq!(
+            RefreshRate::of_config(&config).precision,
+            Some(Precision::Deciseconds)
+        );
+    }
+
+    #[test]
+    fn hidden_components_do_not_count() {
+        let mut config = seconds_only();
+        config.format.segment = TimeFormat::new(true, true, true, true, 3, false);
+        assert_eq!(
+            RefreshRate::of_config(&config).precision,
+            Some(Precision::Seconds)
+        );
+        config.display.show_segment_timer = true;
+        assert_eq!(
+            RefreshRate::of_config(&config).precision,
+            Some(Precision::Milliseconds)
+        );
+        assert!(
+            live_components(&config)
+                .iter()
+                .any(|component| component.name == "segment timer")
+        );
+    }
+
+    #[test]
+    fn the_load_time_and_goal_rows_count_when_shown() {
+        let mut config = seconds_only();
+        config.format.segment = TimeFormat::new(true, true, true, true, 2, false);
+        config.display.show_load_time = true;
+        assert_eq!(
+            RefreshRate::of_config(&config).precision,
+            Some(Precision::Centiseconds)
+        );
+
+        let mut config = seconds_only();
+        config.format.split = TimeFormat::new(true, true, true, true, 1, false);
+        assert_eq!(
+            RefreshRate::of_config(&config).precision,
+            Some(Precision::Seconds)
+        );
+        // Only shown once there is a goal to count down to
+        config.general.goal_time = Some("1:00:00".to_owned());
+        assert_eq!(
+            RefreshRate::of_config(&config).precision,
+            Some(Precision::Deciseconds)
+        );
+        config.general.additional_info.show_goal = false;
+        assert_eq!(
+            RefreshRate::of_config(&config).precision,
+            Some(Precision::Seconds)
+        );
+    }
+
+    #[test]
+    fn a_fixed_rate_overrides_the_derived_one() {
+        let mut config = seconds_only();
+        config.general.refresh_rate_ms = Some(33);
+        let rate = RefreshRate::of_config(&config);
+        assert_eq!(rate.interval, Duration::from_millis(33));
+        assert_eq!(rate.precision, None);
+        assert!(rate.describe().contains("general.refresh-rate-ms"));
+
+        // Out of range rates are clamped rather than spinning or freezing
+        let fixed = |ms| RefreshRate::for_components(&[], Some(ms)).interval;
+        assert_eq!(fixed(0), Duration::from_millis(5));
+        assert_eq!(fixed(60_000), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,298 @@
+//! Regenerating comparisons away from the UI thread.
+//!
+//! Balanced PB, Average Segments and the other generated comparisons walk the
+//! whole attempt history, which takes seconds on runs with tens of thousands of
+//! history entries. Edits of such runs are regenerated on a worker, and the
+//! timer is handed generators that replay the result once instead of computing
+//! it again (see `precompute_comparisons`).
+//!
+//! `Regeneration` keeps the bookkeeping: a generation counter, so a result that
+//! was superseded or cancelled is dropped, and at most one worker at a time, with
+//! the edits made meanwhile coalesced into the latest.
+
+use livesplit_core::comparison::{ComparisonGenerator, default_generators};
+use livesplit_core::run::Attempt;
+use livesplit_core::{Run, Segment, Time};
+
+/// Segment history entries from which edits are regenerated on a worker.
+/// Smaller runs regenerate faster than a frame.
+pub const BACKGROUND_HISTORY_ENTRIES: usize = 20_000;
+
+/// Total segment history entries of `run`.
+pub fn history_entries(run: &Run) -> usize {
+    run.segments()
+        .iter()
+        .map(|segment| segment.segment_history().iter().len())
+        .sum()
+}
+
+pub fn needs_background(run: &Run) -> bool {
+    history_entries(run) >= BACKGROUND_HISTORY_ENTRIES
+}
+
+/// A comparison generator that replays comparison times computed beforehand the
+/// first time it runs, and generates them itself from then on.
+#[derive(Clone, Debug)]
+struct Precomputed {
+    inner: Box<dyn ComparisonGenerator>,
+    /// Per segment, taken on the first `generate`.
+    times: Option<Vec<Time>>,
+}
+
+impl ComparisonGenerator for Precomputed {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn generate(&mut self, segments: &mut [Segment], attempts: &[Attempt]) {
+        match self.times.take() {
+            Some(times) if times.len() == segments.len() => {
+                for (segment, time) in segments.iter_mut().zip(times) {
+                    *segment.comparison_mut(self.inner.name()) = time;
+                }
+            }
+            _ => self.inner.generate(segments, attempts),
+        }
+    }
+}
+
+/// Regenerates the comparisons of `run`, then replaces its generators so that
+/// the timer regenerating them on `set_run` only copies the result. Meant for a
+/// worker thread.
+pub fn precompute_comparisons(mut run: Run) -> Run {
+    // Generators wrapped by an earlier pass are unwrapped by name
+    let defaults = default_generators();
+    let generators: Vec<Box<dyn ComparisonGenerator>> = run
+        .comparison_generators()
+        .iter()
+        .map(|generator| {
+            defaults
+                .iter()
+                .find(|default| default.name() == generator.name())
+                .unwrap_or(generator)
+                .clone()
+        })
+        .collect();
+    *run.comparison_generators_mut() = generators;
+    run.fix_splits();
+    run.regenerate_comparisons();
+
+    let generators = std::mem::take(run.comparison_generators_mut());
+    let precomputed = generators
+        .into_iter()
+        .map(|inner| {
+            let times = run
+                .segments()
+                .iter()
+                .map(|segment| segment.comparison(inner.name()))
+                .collect();
+            Box::new(Precomputed {
+                inner,
+                times: Some(times),
+            }) as Box<dyn ComparisonGenerator>
+        })
+        .collect();
+    *run.comparison_generators_mut() = precomputed;
+    run
+}
+
+/// Work for a worker: regenerate `input`, answering for `generation`.
+#[derive(Debug)]
+pub struct Job<T> {
+    pub generation: u64,
+    pub input: T,
+}
+
+/// What to do with the result of a finished job.
+#[derive(Debug)]
+pub struct Completion<T> {
+    /// The latest request when the result is still wanted, to fall back to if
+    /// the worker failed.
+    pub apply: Option<T>,
+    /// A request made while the job ran, to start now.
+    pub next: Option<Job<T>>,
+}
+
+#[derive(Debug)]
+pub struct Regeneration<T> {
+    generation: u64,
+    /// Generation of the job a worker is running.
+    in_flight: Option<u64>,
+    /// The latest request, until its result is applied or it is cancelled.
+    latest: Option<T>,
+    /// Whether `latest` waits for the job in flight to finish.
+    queued: bool,
+}
+
+impl<T> Default for Regeneration<T> {
+    fn default() -> Self {
+        Self {
+            generation: 0,
+            in_flight: None,
+            latest: None,
+            queued: false,
+        }
+    }
+}
+
+impl<T: Clone> Regeneration<T> {
+    /// Asks for `input` to be regenerated, superseding earlier requests. Returns
+    /// the job to start, or `None` when one is running already: `input` is then
+    /// started once it finishes.
+    pub fn request(&mut self, input: T) -> Option<Job<T>> {
+        self.generation += 1;
+        self.latest = Some(input.clone());
+        if self.in_flight.is_some() {
+            self.queued = true;
+            return None;
+        }
+        self.in_flight = Some(self.generation);
+        Some(Job {
+            generation: self.generation,
+            input,
+        })
+    }
+
+    /// Records that the job of `generation` finished. Its result is only
+    /// applied when nothing was requested or cancelled since it started.
+    pub fn complete(&mut self, generation: u64) -> Completion<T> {
+        if self.in_flight == Some(generation) {
+            self.in_flight = None;
+        }
+        let current = generation == self.generation && self.in_flight.is_none();
+        let apply = if current { self.latest.take() } else { None };
+
+        let next = if self.queued && self.in_flight.is_none() {
+            self.queued = false;
+            self.in_flight = Some(self.generation);
+            self.latest.clone().map(|input| Job {
+                generation: self.generation,
+                input,
+            })
+        } else {
+            None
+        };
+        Completion { apply, next }
+    }
+
+    /// Drops the pending requests, so a job still running is not applied.
+    /// Returns whether there were any.
+    pub fn cancel(&mut self) -> bool {
+        self.generation += 1;
+        self.queued = false;
+        self.latest.take().is_some()
+    }
+
+    /// The latest request not applied yet.
+    pub const fn latest(&self) -> Option<&T> {
+        self.latest.as_ref()
+    }
+
+    pub const fn is_running(&self) -> bool {
+        self.latest.is_some()
+    }
+}
+
+#[cfg(test)]
+mod regeneration_tests {
+    use super::*;
+    use livesplit_core::{TimeSpan, TimingMethod};
+
+    #[test]
+    fn edits_made_while_a_job_runs_are_coalesced_into_the_latest() {
+        let mut regeneration = Regeneration::default();
+        let first = regeneration.request("a").unwrap();
+        assert!(regeneration.request("b").is_none());
+        assert!(regeneration.request("c").is_none());
+        assert_eq!(regeneration.latest(), Some(&"c"));
+
+        // "a" is stale: only "c" is started, skipping "b"
+        let completion = regeneration.complete(first.generation);
+        assert_eq!(completion.apply, None);
+        let next = completion.next.unwrap();
+        assert_eq!(next.input, "c");
+
+        let completion = regeneration.complete(next.generation);
+        assert_eq!(completion.apply, Some("c"));
+        assert!(completion.next.is_none());
+        assert!(!regeneration.is_running());
+    }
+
+    #[test]
+    fn late_and_cancelled_results_are_discarded() {
+        let mut regeneration = Regeneration::default();
+        let first = regeneration.request(1).unwrap();
+        // A different run was loaded while it ran
+        assert!(regeneration.cancel());
+        let completion = regeneration.complete(first.generation);
+        assert_eq!(completion.apply, None);
+        assert!(completion.next.is_none());
+        assert!(!regeneration.cancel());
+
+        // Requested after the cancel, but before the old job finished
+        let mut regeneration = Regeneration::default();
+        let old = regeneration.request(1).unwrap();
+        regeneration.cancel();
+        assert!(regeneration.request(2).is_none());
+        let completion = regeneration.complete(old.generation);
+        assert_eq!(completion.apply, None);
+        let new = completion.next.unwrap();
+
+        // Completions arriving twice or out of order change nothing
+        let repeated = regeneration.complete(old.generation);
+        assert_eq!(repeated.apply, None);
+        assert!(regeneration.is_running());
+        assert_eq!(regeneration.complete(new.generation).apply, Some(2));
+        assert_eq!(regeneration.complete(new.generation).apply, None);
+    }
+
+    #[test]
+    fn precomputed_comparisons_match_regenerating_on_the_timer() {
+        let mut run = Run::new();
+        for name in ["A", "B"] {
+            run.push_segment(Segment::new(name));
+        }
+        for attempt in 1..=3 {
+            let mut total = TimeSpan::zero();
+            for (index, segment) in run.segments_mut().iter_mut().enumerate() {
+                let time = TimeSpan::from_seconds(f64::from(10 * attempt + index as i32));
+                total += time;
+                segment
+                    .segment_history_mut()
+                    .insert(attempt, Time::new().with_real_time(Some(time)));
+            }
+            run.add_attempt_with_index(
+                Time::new().with_real_time(Some(total)),
+                attempt,
+                None,
+                None,
+                None,
+            );
+        }
+
+        let mut expected = run.clone();
+        expected.fix_splits();
+        expected.regenerate_comparisons();
+        let mut precomputed = precompute_comparisons(precomputed_twice(run));
+        precomputed.regenerate_comparisons();
+        for (ours, theirs) in precomputed.segments().iter().zip(expected.segments()) {
+            for generator in expected.comparison_generators() {
+                assert_eq!(
+                    ours.comparison(generator.name())[TimingMethod::RealTime],
+                    theirs.comparison(generator.name())[TimingMethod::RealTime],
+                );
+            }
+        }
+        // Wrapped once, however often it went through a worker
+        assert_eq!(
+            precomputed.comparison_generators().len(),
+            expected.comparison_generators().len()
+        );
+    }
+
+    fn precomputed_twice(run: Run) -> Run {
+        let mut run = precompute_comparisons(run);
+        run.regenerate_comparisons();
+        run
+    }
+}
@@ -0,0 +1,198 @@
+//! Guard against auto-splitter scripts resetting the attempt on their own.
+//!
+//! The auto-splitting runtime queues its resets with `send_scripted`, and while
+//! resets are guarded the action dispatcher holds them for the main loop (see
+//! `utils::timer_actions`). The main loop decides on them here and queues the
+//! ones allowed, so a guarded reset never reaches the timer.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::AutoSplitter;
+
+/// How long after a user reset a reset still counts as theirs.
+pub const USER_ACTION_WINDOW: Duration = Duration::from_secs(1);
+
+/// When the user last reset the attempt. Shared with the reset hotkey, which
+/// records on its own thread.
+#[derive(Debug, Clone, Default)]
+pub struct UserActions(Arc<Mutex<Option<Instant>>>);
+
+impl UserActions {
+    pub fn record(&self) {
+        if let Ok(mut last) = self.0.lock() {
+            *last = Some(Instant::now());
+        }
+    }
+
+    pub fn last(&self) -> Option<Instant> {
+        self.0.lock().ok().and_then(|last| *last)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetDecision {
+    /// The reset stands.
+    Allow,
+    /// Undone: a scripted reset already went through within the cooldown.
+    Block,
+    /// Undone until the user confirms it.
+    Defer,
+}
+
+/// What to do with a reset that happened at `now`. Resets within
+/// `USER_ACTION_WINDOW` of a user reset are always allowed; the others are
+/// scripted, and held for confirmation with `confirm-reset`, or limited to one per
+/// `reset-cooldown-secs` since `last_scripted_reset`.
+pub fn decide_reset(
+    now: Instant,
+    last_user_action: Option<Instant>,
+    last_scripted_reset: Option<Instant>,
+    flags: &AutoSplitter,
+) -> ResetDecision {
+    if by_user(now, last_user_action) {
+        return ResetDecision::Allow;
+    }
+    if flags.confirm_reset {
+        return ResetDecision::Defer;
+    }
+    let cooldown = Duration::from_secs(flags.reset_cooldown_secs.into());
+    let cooling_down =
+        last_scripted_reset.is_some_and(|at| now.saturating_duration_since(at) < cooldown);
+    if cooling_down {
+        ResetDecision::Block
+    } else {
+        ResetDecision::Allow
+    }
+}
+
+fn by_user(now: Instant, last_user_action: Option<Instant>) -> bool {
+    last_user_action.is_some_and(|at| now.saturating_duration_since(at) <= USER_ACTION_WINDOW)
+}
+
+/// Decides on the resets of the auto splitter held for the main loop.
+#[derive(Debug, Default)]
+pub struct ResetGuard {
+    last_scripted_reset: Option<Instant>,
+    /// When a scripted reset was last blocked or deferred.
+    last_held: Option<Instant>,
+}
+
+impl ResetGuard {
+    /// Decides on a scripted reset held at `now`. Scripts keep asking while
+    /// the attempt goes on, so another one within `USER_ACTION_WINDOW` of a
+    /// reset blocked or deferred returns `None`, to be dropped quietly.
+    pub fn decide(
+        &mut self,
+        now: Instant,
+        last_user_action: Option<Instant>,
+        flags: &AutoSplitter,
+    ) -> Option<ResetDecision> {
+        if self
+            .last_held
+            .is_some_and(|at| now.saturating_duration_since(at) <= USER_ACTION_WINDOW)
+        {
+            return None;
+        }
+        let decision = decide_reset(now, last_user_action, self.last_scripted_reset, flags);
+        match decision {
+            ResetDecision::Allow if !by_user(now, last_user_action) => {
+                self.last_scripted_reset = Some(now);
+            }
+            ResetDecision::Allow => {}
+            ResetDecision::Block | ResetDecision::Defer => self.last_held = Some(now),
+        }
+        Some(decision)
+    }
+}
+
+#[cfg(test)]
+mod reset_guard_tests {
+    use super::*;
+
+    fn flags(confirm_reset: bool, reset_cooldown_secs: u32) -> AutoSplitter {
+        AutoSplitter {
+            confirm_reset,
+            reset_cooldown_secs,
+            ..AutoSplitter::default()
+        }
+    }
+
+    #[test]
+    fn user_resets_are_always_allowed() {
+        let now = Instant::now();
+        let pressed = now.checked_sub(Duration::from_millis(300));
+        for flags in [flags(true, 0), flags(false, 60)] {
+            assert_eq!(
+                decide_reset(now, pressed, Some(now), &flags),
+                ResetDecision::Allow
+            );
+        }
+    }
+
+    #[test]
+    fn scripted_resets_wait_for_confirmation() {
+        let now = Instant::now();
+        let long_ago = now.checked_sub(Duration::from_secs(5));
+        assert_eq!(
+            decide_reset(now, long_ago, None, &flags(true, 0)),
+            ResetDecision::Defer
+        );
+        assert_eq!(
+            decide_reset(now, None, None, &flags(true, 30)),
+            ResetDecision::Defer
+        );
+    }
+
+    #[test]
+    fn scripted_resets_are_rate_limited() {
+        let now = Instant::now();
+        let flags = flags(false, 30);
+        assert_eq!(decide_reset(now, None, None, &flags), ResetDecision::Allow);
+
+        let recent = now.checked_sub(Duration::from_secs(10));
+        assert_eq!(
+            decide_reset(now, None, recent, &flags),
+            ResetDecision::Block
+        );
+
+        let old = now.checked_sub(Duration::from_secs(30));
+        assert_eq!(decide_reset(now, None, old, &flags), ResetDecision::Allow);
+
+        // Without a cooldown, nothing is limited
+        assert_eq!(
+            decide_reset(now, None, recent, &AutoSplitter::default()),
+            ResetDecision::Allow
+        );
+    }
+
+    #[test]
+    fn guard_limits_scripted_resets_and_drops_repeats() {
+        let mut guard = ResetGuard::default();
+        let flags = flags(false, 60);
+        let now = Instant::now();
+
+        // The first scripted reset goes through
+        assert_eq!(guard.decide(now, None, &flags), Some(ResetDecision::Allow));
+        let later = now + Duration::from_secs(10);
+        assert_eq!(
+            guard.decide(later, None, &flags),
+            Some(ResetDecision::Block)
+        );
+        // Asked again right after, nothing new to report
+        assert_eq!(
+            guard.decide(later + Duration::from_millis(100), None, &flags),
+            None
+        );
+        // The user resetting is not held back
+        let pressed = later + Duration::from_secs(5);
+        assert_eq!(
+            guard.decide(pressed, Some(pressed), &flags),
+            Some(ResetDecision::Allow)
+        );
+        assert_eq!(
+            guard.decide(now + Duration::from_secs(61), None, &flags),
+            Some(ResetDecision::Allow)
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Differences between the loaded run and another splits file of the same game,
+//! to review a route change.
+//!
+//! Segments are matched by name first (the n-th segment of a name with the n-th
+//! one of the other file), then, among those left, by position, which is how a
+//! renamed segment is told from an added and a removed one.
+
+use livesplit_core::{Run, RunEditor, Segment, TimeSpan, TimingMethod};
+
+/// A time of the loaded run ("ours") and of the other file ("theirs").
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeDiff {
+    pub ours: Option<TimeSpan>,
+    pub theirs: Option<TimeSpan>,
+}
+
+impl TimeDiff {
+    /// Time the loaded run loses to the other file (negative when it is faster).
+    /// `None` unless both have a time.
+    pub fn delta(&self) -> Option<TimeSpan> {
+        Some(self.ours? - self.theirs?)
+    }
+
+    pub fn differs(&self) -> bool {
+        self.ours != self.theirs
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RouteEntry {
+    /// A segment of both runs, renamed when the names differ.
+    Matched {
+        ours: usize,
+        theirs: usize,
+        renamed: bool,
+        split: TimeDiff,
+        segment: TimeDiff,
+        gold: TimeDiff,
+    },
+    /// A segment only the loaded run has.
+    Removed { ours: usize },
+    /// A segment only the other file has.
+    Added { theirs: usize },
+}
+
+impl RouteEntry {
+    /// Whether adopting would change anything in the loaded run.
+    pub fn can_adopt(&self) -> bool {
+        matches!(self, Self::Matched { split, gold, .. } if split.differs() || gold.differs())
+    }
+}
+
+/// The segments of `ours` in order, with those only `theirs` has placed after the
+/// segment they follow in `theirs`.
+pub fn diff_runs(ours: &Run, theirs: &Run, method: TimingMethod) -> Vec<RouteEntry> {
+    let matches = match_segments(ours.segments(), theirs.segments());
+    let our_splits = split_times(ours.segments(), method);
+    let their_splits = split_times(theirs.segments(), method);
+    let our_segments = segment_times(&our_splits);
+    let their_segments = segment_times(&their_splits);
+
+    let mut keyed: Vec<(OrderKey, RouteEntry)> = matches
+        .iter()
+        .enumerate()
+        .map(|(index, matched)| {
+            let entry = match *matched {
+                Some(theirs_index) => RouteEntry::Matched {
+                    ours: index,
+                    theirs: theirs_index,
+                    renamed: ours.segments()[index].name()
+                        != theirs.segments()[theirs_index].name(),
+                    split: TimeDiff {
+                        ours: our_splits[index],
+                        theirs: their_splits[theirs_index],
+                    },
+                    segment: TimeDiff {
+                        ours: our_segments[index],
+                        theirs: their_segments[theirs_index],
+                    },
+                    gold: TimeDiff {
+                        ours: ours.segments()[index].best_segment_time()[method],
+                        theirs: theirs.segments()[theirs_index].best_segment_time()[method],
+                    },
+                },
+                None => RouteEntry::Removed { ours: index },
+            };
+            ((Some(index), false, 0), entry)
+        })
+        .collect();
+
+    let mut follows = None;
+    for theirs_index in 0..theirs.segments().len() {
+        if let Some(index) = matches.iter().position(|m| *m == Some(theirs_index)) {
+            follows = Some(index);
+        } else {
+            keyed.push((
+                (follows, true, theirs_index),
+                RouteEntry::Added {
+                    theirs: theirs_index,
+                },
+            ));
+        }
+    }
+    keyed.sort_by_key(|(key, _)| *key);
+    keyed.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// Our segment an entry follows, whether it was added after it, and its position
+/// in theirs.
+type OrderKey = (Option<usize>, bool, usize);
+
+/// For every segment of `ours`, the index of its segment in `theirs`.
+fn match_segments(ours: &[Segment], theirs: &[Segment]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; ours.len()];
+    let mut taken = vec![false; theirs.len()];
+
+    for (index, segment) in ours.iter().enumerate() {
+        let found = theirs.iter().enumerate().position(|(theirs_index, other)| {
+            !taken[theirs_index] && other.name() == segment.name()
+        });
+        if let Some(theirs_index) = found {
+            matches[index] = Some(theirs_index);
+            taken[theirs_index] = true;
+        }
+    }
+    for (index, matched) in matches.iter_mut().enumerate() {
+        if matched.is_none() && taken.get(index) == Some(&false) {
+            *matched = Some(index);
+            taken[index] = true;
+        }
+    }
+    matches
+}
+
+fn split_times(segments: &[Segment], method: TimingMethod) -> Vec<Option<TimeSpan>> {
+    segments
+        .iter()
+        .map(|segment| segment.personal_best_split_time()[method])
+        .collect()
+}
+
+/// Time of each segment in the PB: its split minus the last split before it
+/// (skipped splits carry over to the next one).
+fn segment_times(splits: &[Option<TimeSpan>]) -> Vec<Option<TimeSpan>> {
+    let mut previous = TimeSpan::zero();
+    splits
+        .iter()
+        .map(|split| {
+            let split = (*split)?;
+            let time = split - previous;
+            previous = split;
+            Some(time)
+        })
+        .collect()
+}
+
+/// `ours` with the PB split time and best segment of its segment at `index` set
+/// to those of `theirs`.
+pub fn adopt_segment(ours: Run, index: usize, theirs: &Segment, method: TimingMethod) -> Run {
+    let Ok(mut editor) = RunEditor::new(ours.clone()) else {
+        return ours;
+    };
+    if index >= editor.run().len() {
+        return ours;
+    }
+    editor.select_timing_method(method);
+    editor.select_only(index);
+    let mut segment = editor.active_segment();
+    segment.set_split_time(theirs.personal_best_split_time()[method]);
+    segment.set_best_segment_time(theirs.best_segment_time()[method]);
+    editor.close()
+}
+
+#[cfg(test)]
+mod route_diff_tests {
+    use super::*;
+    use crate::test_support::real;
+
+    /// A run with a segment per (name, PB split seconds, gold seconds).
+    fn run(segments: &[(&str, f64, f64)]) -> Run {
+        let mut run = Run::new();
+        for &(name, split, gold) in segments {
+            let mut segment = Segment::new(name);
+            segment.set_personal_best_split_time(real(split));
+            segment.set_best_segment_time(real(gold));
+            run.push_segment(segment);
+        }
+        run
+    }
+
+    fn seconds(time: Option<TimeSpan>) -> Option<f64> {
+        time.map(|time| time.total_seconds())
+    }
+
+    #[test]
+    fn reordered_segments_are_matched_by_name() {
+        let ours = run(&[
+            ("Forest", 60.0, 55.0),
+            ("Cave", 150.0, 85.0),
+            ("Boss", 200.0, 48.0),
+        ]);
+        let theirs = run(&[
+            ("Cave", 80.0, 78.0),
+            ("Forest", 140.0, 57.0),
+            ("Boss", 185.0, 44.0),
+        ]);
+        let diff = diff_runs(&ours, &theirs, TimingMethod::RealTime);
+
+        assert_eq!(diff.len(), 3);
+        let RouteEntry::Matched {
+            ours: 0,
+            theirs: 1,
+            renamed: false,
+            split,
+            segment,
+            gold,
+        } = diff[0]
+        else {
+            panic!("Forest not matched: {:?}", diff[0]);
+        };
+        assert_eq!(seconds(split.delta()), Some(-80.0));
+        // Forest follows Cave in their route
+        assert_eq!(seconds(segment.theirs), Some(60.0));
+        assert_eq!(seconds(gold.delta()), Some(-2.0));
+        assert!(matches!(
+            diff[1],
+            RouteEntry::Matched {
+                ours: 1,
+                theirs: 0,
+                ..
+            }
+        ));
+        assert!(matches!(
+            diff[2],
+            RouteEntry::Matched {
+                ours: 2,
+                theirs: 2,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn extra_segments_on_either_side() {
+        let ours = run(&[
+            ("Forest", 60.0, 55.0),
+            ("Skip Me", 90.0, 28.0),
+            ("Boss", 150.0, 55.0),
+        ]);
+        let theirs = run(&[
+            ("Forest", 60.0, 55.0),
+            ("Boss", 110.0, 50.0),
+            ("Credits", 130.0, 20.0),
+            ("Post Game", 180.0, 50.0),
+        ]);
+        let diff = diff_runs(&ours, &theirs, TimingMethod::RealTime);
+
+        assert!(matches!(
+            diff[0],
+            RouteEntry::Matched {
+                ours: 0,
+                theirs: 0,
+                ..
+            }
+        ));
+        assert!(!diff[0].can_adopt());
+        // "Skip Me" is at the position of their "Boss", which has a match already
+        assert_eq!(diff[1], RouteEntry::Removed { ours: 1 });
+        assert!(matches!(
+            diff[2],
+            RouteEntry::Matched {
+                ours: 2,
+                theirs: 1,
+                ..
+            }
+        ));
+        assert!(diff[2].can_adopt());
+        assert_eq!(diff[3], RouteEntry::Added { theirs: 2 });
+        assert_eq!(diff[4], RouteEntry::Added { theirs: 3 });
+
+        // Their segment added before any match comes first
+        let theirs = run(&[("Intro", 10.0, 10.0), ("Forest", 70.0, 55.0)]);
+        let diff = diff_runs(&ours, &theirs, TimingMethod::RealTime);
+        assert_eq!(diff[0], RouteEntry::Added { theirs: 0 });
+    }
+
+    #[test]
+    fn renamed_segments_fall_back_to_their_position() {
+        let ours = run(&[("Forest", 60.0, 55.0), ("Cave", 150.0, 85.0)]);
+        let theirs = run(&[("Forest", 60.0, 55.0), ("Crystal Cave", 140.0, 80.0)]);
+        let diff = diff_runs(&ours, &theirs, TimingMethod::RealTime);
+
+        assert_eq!(diff.len(), 2);
+        let RouteEntry::Matched {
+            renamed: true,
+            segment,
+            ..
+        } = diff[1]
+        else {
+            panic!("Cave not renamed: {:?}", diff[1]);
+        };
+        assert_eq!(seconds(segment.delta()), Some(10.0));
+    }
+
+    #[test]
+    fn adopting_copies_the_split_and_gold() {
+        let ours = run(&[("Forest", 60.0, 55.0), ("Cave", 150.0, 85.0)]);
+        let theirs = run(&[("Forest", 58.0, 52.0), ("Cave", 140.0, 80.0)]);
+
+        let adopted = adopt_segment(ours, 0, &theirs.segments()[0], TimingMethod::RealTime);
+        let forest = &adopted.segments()[0];
+        assert_eq!(
+            seconds(forest.personal_best_split_time().real_time),
+            Some(58.0)
+        );
+        assert_eq!(seconds(forest.best_segment_time().real_time), Some(52.0));
+        // The other segment keeps its times
+        assert_eq!(
+            seconds(adopted.segments()[1].personal_best_split_time().real_time),
+            Some(150.0)
+        );
+
+        let diff = diff_runs(&adopted, &theirs, TimingMethod::RealTime);
+        assert!(!diff[0].can_adopt());
+        assert!(diff[1].can_adopt());
+    }
+}
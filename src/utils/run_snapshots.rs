@@ -0,0 +1,132 @@
+//! Copies of the run taken right before changes that rewrite its data: a reset
+//! that updates the splits, or saving the split editor. The latest can be put
+//! back with "Undo Last Data Change" until the app is closed.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use livesplit_core::Run;
+use time::OffsetDateTime;
+
+/// Snapshots kept, the oldest being dropped first.
+pub const CAPACITY: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct RunSnapshot {
+    pub run: Run,
+    /// What was done after the snapshot was taken, e.g. "Reset with splits updated".
+    pub description: String,
+    pub taken_at: OffsetDateTime,
+}
+
+#[derive(Debug, Default)]
+pub struct RunSnapshots {
+    /// Oldest first.
+    snapshots: VecDeque<RunSnapshot>,
+}
+
+/// Shared with the timer action dispatcher, which takes the snapshots of resets.
+pub type SharedRunSnapshots = Arc<Mutex<RunSnapshots>>;
+
+impl RunSnapshots {
+    pub fn push(&mut self, run: Run, description: impl Into<String>, taken_at: OffsetDateTime) {
+        if self.snapshots.len() == CAPACITY {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(RunSnapshot {
+            run,
+            description: description.into(),
+            taken_at,
+        });
+    }
+
+    /// Records the run as it is now.
+    pub fn take(&mut self, run: &Run, description: impl Into<String>) {
+        self.push(run.clone(), description, OffsetDateTime::now_utc());
+    }
+
+    /// The snapshot "Undo Last Data Change" would put back.
+    pub fn latest(&self) -> Option<&RunSnapshot> {
+        self.snapshots.back()
+    }
+
+    /// Removes the latest snapshot to put it back. The one before it becomes
+    /// the latest.
+    pub fn pop(&mut self) -> Option<RunSnapshot> {
+        self.snapshots.pop_back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod run_snapshots_tests {
+    use super::*;
+    use livesplit_core::Segment;
+
+    fn run(name: &str) -> Run {
+        let mut run = Run::new();
+        run.set_category_name(name);
+        run.push_segment(Segment::new("S1"));
+        run
+    }
+
+    fn at(seconds: i64) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(seconds).unwrap()
+    }
+
+    #[test]
+    fn latest_snapshot_comes_back_first() {
+        let mut snapshots = RunSnapshots::default();
+        assert!(snapshots.latest().is_none());
+        snapshots.push(run("a"), "Reset with splits updated", at(1));
+        snapshots.push(run("b"), "Split editor changes", at(2));
+
+        let latest = snapshots.latest().unwrap();
+        assert_eq!(latest.run.category_name(), "b");
+        assert_eq!(latest.description, "Split editor changes");
+        assert_eq!(latest.taken_at, at(2));
+    }
+
+    #[test]
+    fn oldest_snapshots_are_dropped_past_capacity() {
+        let mut snapshots = RunSnapshots::default();
+        for i in 0..CAPACITY + 2 {
+            snapshots.push(run(&i.to_string()), "Reset", at(i as i64));
+        }
+        assert_eq!(snapshots.len(), CAPACITY);
+
+        let mut restored = Vec::new();
+        while let Some(snapshot) = snapshots.pop() {
+            restored.push(snapshot.run.category_name().to_owned());
+        }
+        assert_eq!(restored, ["6", "5", "4", "3", "2"]);
+    }
+
+    #[test]
+    fn restoring_leaves_the_earlier_snapshots_in_order() {
+        let mut snapshots = RunSnapshots::default();
+        snapshots.push(run("a"), "first", at(1));
+        snapshots.push(run("b"), "second", at(2));
+        snapshots.push(run("c"), "third", at(3));
+
+        let restored = snapshots.pop().unwrap();
+        assert_eq!(restored.description, "third");
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots.latest().unwrap().description, "second");
+
+        // A change after the undo goes on top of what is left
+        snapshots.push(run("d"), "fourth", at(4));
+        assert_eq!(snapshots.pop().unwrap().description, "fourth");
+        assert_eq!(snapshots.pop().unwrap().description, "second");
+        assert_eq!(snapshots.pop().unwrap().description, "first");
+        assert!(snapshots.pop().is_none());
+        assert!(snapshots.is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+//! Removing and moving the selected rows of the segments table, planned as the
+//! `RunEditor` steps that make the change in one go.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentOp {
+    Remove,
+    MoveUp,
+    MoveDown,
+}
+
+/// One call on a `RunEditor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorStep {
+    SelectOnly(usize),
+    SelectAdditionally(usize),
+    RemoveSegments,
+    MoveSegmentsUp,
+    MoveSegmentsDown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentOpPlan {
+    /// Steps to apply, in order.
+    pub steps: Vec<EditorStep>,
+    /// Rows to select once the steps are applied, ascending.
+    pub selection: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SegmentOpError {
+    NothingSelected,
+    /// The run editor could not be opened on the run, with why.
+    EditorUnavailable(String),
+    /// A run keeps at least one segment.
+    RemovesEverySegment,
+    /// Every selected row is already against the edge it would move past.
+    NothingToMove,
+}
+
+/// Plans `op` on the `selected` rows of a run of `len` segments. Rows out of
+/// range are ignored.
+///
+/// Moves keep the order of the selected rows. Rows already at the top (or bottom),
+/// with the ones selected next to them, stay where they are while the rest of
+/// the selection moves. Removing selects the row that takes the place of the
+/// first removed one.
+pub fn plan_segment_op(
+    selected: &[usize],
+    len: usize,
+    op: SegmentOp,
+) -> Result<SegmentOpPlan, SegmentOpError> {
+    let mut selected: Vec<usize> = selected.iter().copied().filter(|&i| i < len).collect();
+    selected.sort_unstable();
+    selected.dedup();
+    let Some(&first) = selected.first() else {
+        return Err(SegmentOpError::NothingSelected);
+    };
+
+    match op {
+        SegmentOp::Remove => {
+            if selected.len() >= len {
+                return Err(SegmentOpError::RemovesEverySegment);
+            }
+            let remaining = len - selected.len();
+            Ok(SegmentOpPlan {
+                steps: with_selection(&selected, EditorStep::RemoveSegments),
+                selection: vec![first.min(remaining - 1)],
+            })
+        }
+        SegmentOp::MoveUp => {
+            // The block from the first row on cannot go any higher
+            let pinned = selected
+                .iter()
+                .enumerate()
+                .take_while(|&(position, &row)| position == row)
+                .count();
+            let moved = &selected[pinned..];
+            if moved.is_empty() {
+                return Err(SegmentOpError::NothingToMove);
+            }
+            Ok(SegmentOpPlan {
+                steps: with_selection(moved, EditorStep::MoveSegmentsUp),
+                selection: selected[..pinned]
+                    .iter()
+                    .copied()
+                    .chain(moved.iter().map(|row| row - 1))
+                    .collect(),
+            })
+        }
+        SegmentOp::MoveDown => {
+            let pinned = selected
+                .iter()
+                .rev()
+                .enumerate()
+                .take_while(|&(position, &row)| row == len - 1 - position)
+                .count();
+            let moved = &selected[..selected.len() - pinned];
+            if moved.is_empty() {
+                return Err(SegmentOpError::NothingToMove);
+            }
+            Ok(SegmentOpPlan {
+                steps: with_selection(moved, EditorStep::MoveSegmentsDown),
+                selection: moved
+                    .iter()
+                    .map(|row| row + 1)
+                    .chain(selected[selected.len() - pinned..].iter().copied())
+                    .collect(),
+            })
+        }
+    }
+}
+
+/// Selects `rows` (not empty) in the editor, then does `step`.
+fn with_selection(rows: &[usize], step: EditorStep) -> Vec<EditorStep> {
+    let mut steps = vec![EditorStep::SelectOnly(rows[0])];
+    steps.extend(
+        rows[1..]
+            .iter()
+            .map(|&row| EditorStep::SelectAdditionally(row)),
+    );
+    steps.push(step);
+    steps
+}
+
+#[cfg(test)]
+mod segment_ops_tests {
+    use super::*;
+    use EditorStep::*;
+
+    fn plan(selected: &[usize], op: SegmentOp) -> Result<SegmentOpPlan, SegmentOpError> {
+        plan_segment_op(selected, 6, op)
+    }
+
+    #[test]
+    fn non_contiguous_selections_move_and_are_removed_together() {
+        let up = plan(&[4, 1, 2], SegmentOp::MoveUp).unwrap();
+        assert_eq!(
+            up.steps,
+            [
+                SelectOnly(1),
+                SelectAdditionally(2),
+                SelectAdditionally(4),
+                MoveSegmentsUp
+            ]
+        );
+        assert_eq!(up.selection, [0, 1, 3]);
+
+        let down = plan(&[1, 3], SegmentOp::MoveDown).unwrap();
+        assert_eq!(down.selection, [2, 4]);
+        assert_eq!(down.steps.last(), Some(&MoveSegmentsDown));
+
+        let remove = plan(&[3, 1, 3], SegmentOp::Remove).unwrap();
+        assert_eq!(
+            remove.steps,
+            [SelectOnly(1), SelectAdditionally(3), RemoveSegments]
+        );
+        assert_eq!(remove.selection, [1]);
+        // Removing the last rows selects the new last one
+        assert_eq!(plan(&[4, 5], SegmentOp::Remove).unwrap().selection, [3]);
+    }
+
+    #[test]
+    fn rows_against_the_edge_stay_while_the_rest_moves() {
+        let up = plan(&[0, 1, 3], SegmentOp::MoveUp).unwrap();
+        assert_eq!(up.steps, [SelectOnly(3), MoveSegmentsUp]);
+        assert_eq!(up.selection, [0, 1, 2]);
+        assert_eq!(
+            plan(&[0, 1], SegmentOp::MoveUp),
+            Err(SegmentOpError::NothingToMove)
+        );
+
+        let down = plan(&[2, 5], SegmentOp::MoveDown).unwrap();
+        assert_eq!(down.steps, [SelectOnly(2), MoveSegmentsDown]);
+        assert_eq!(down.selection, [3, 5]);
+        assert_eq!(
+            plan(&[4, 5], SegmentOp::MoveDown),
+            Err(SegmentOpError::NothingToMove)
+        );
+        // The first row can still move down, and the last one up
+        assert_eq!(plan(&[0], SegmentOp::MoveDown).unwrap().selection, [1]);
+        assert_eq!(plan(&[5], SegmentOp::MoveUp).unwrap().selection, [4]);
+    }
+
+    #[test]
+    fn a_run_keeps_at_least_one_segment() {
+        assert_eq!(
+            plan(&[0, 1, 2, 3, 4, 5], SegmentOp::Remove),
+            Err(SegmentOpError::RemovesEverySegment)
+        );
+        assert_eq!(
+            plan_segment_op(&[0], 1, SegmentOp::Remove),
+            Err(SegmentOpError::RemovesEverySegment)
+        );
+        assert_eq!(
+            plan(&[], SegmentOp::Remove),
+            Err(SegmentOpError::NothingSelected)
+        );
+        // Rows past the end count as not selected
+        assert_eq!(
+            plan(&[9], SegmentOp::MoveUp),
+            Err(SegmentOpError::NothingSelected)
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Keeping the sidecar (see `crate::sidecar`) in line with its run when the
+//! splits file was edited in another tool.
+//!
+//! Segment data is keyed by name, so a segment renamed elsewhere leaves its data
+//! behind. Each such orphan is matched to one of the segments without data of
+//! their own by name similarity, helped by the position its gold date was
+//! recorded at, or else dropped. Attempt data keyed past the last attempt of
+//! the history, which was cut short, is pruned. `plan_sidecar_repair` only
+//! plans; the orphans are meant to be reviewed before `apply_sidecar_repair`.
+
+use livesplit_core::Run;
+
+use crate::sidecar::{RunSidecar, SegmentMeta};
+
+/// Similarity a name needs to take over an orphan's data.
+pub const MIN_SIMILARITY: f64 = 0.5;
+/// Added to the similarity of the segment at the position the orphan was last
+/// written at.
+const POSITION_BONUS: f64 = 0.3;
+
+/// Data of a segment that is no longer in the run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Orphan {
+    pub name: String,
+    /// Segment to move the data to, or `None` to drop it.
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairPlan {
+    pub orphans: Vec<Orphan>,
+    /// Attempts past the history that still have tags or clock jumps.
+    pub stale_attempts: Vec<i32>,
+}
+
+impl RepairPlan {
+    pub fn is_empty(&self) -> bool {
+        self.orphans.is_empty() && self.stale_attempts.is_empty()
+    }
+
+    /// The plan without its orphans, which can be applied without review.
+    pub fn pruning_only(&self) -> Self {
+        Self {
+            orphans: Vec::new(),
+            stale_attempts: self.stale_attempts.clone(),
+        }
+    }
+}
+
+/// Segments of `run` without data in `sidecar`, in run order: where orphans can
+/// be reattached.
+pub fn reattach_candidates(run: &Run, sidecar: &RunSidecar) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for segment in run.segments() {
+        let name = segment.name();
+        if !sidecar.segments.contains_key(name) && !names.iter().any(|n| n == name) {
+            names.push(name.to_owned());
+        }
+    }
+    names
+}
+
+/// What to do about the sidecar entries `run` has no use for.
+pub fn plan_sidecar_repair(run: &Run, sidecar: &RunSidecar) -> RepairPlan {
+    let segments = run.segments();
+    let orphans: Vec<(&String, &SegmentMeta)> = sidecar
+        .segments
+        .iter()
+        .filter(|(name, _)| !segments.iter().any(|s| s.name() == name.as_str()))
+        .collect();
+    let candidates = reattach_candidates(run, sidecar);
+
+    // Best pairs first, each orphan and candidate taken once
+    let mut pairs: Vec<(f64, usize, usize)> = Vec::new();
+    for (o, (name, meta)) in orphans.iter().enumerate() {
+        for (c, candidate) in candidates.iter().enumerate() {
+            let at_old_position = meta
+                .index
+                .and_then(|index| segments.get(index))
+                .is_some_and(|segment| segment.name() == candidate);
+            let score = name_similarity(name, candidate)
+                + if at_old_position { POSITION_BONUS } else { 0.0 };
+            if score >= MIN_SIMILARITY {
+                pairs.push((score, o, c));
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+    let mut targets: Vec<Option<usize>> = vec![None; orphans.len()];
+    let mut taken = vec![false; candidates.len()];
+    for (_, o, c) in pairs {
+        if targets[o].is_none() && !taken[c] {
+            targets[o] = Some(c);
+            taken[c] = true;
+        }
+    }
+
+    let last_attempt = run
+        .attempt_history()
+        .iter()
+        .map(livesplit_core::run::Attempt::index)
+        .max()
+        .unwrap_or(0);
+    let mut stale_attempts: Vec<i32> = sidecar
+        .attempt_tags
+        .keys()
+        .chain(sidecar.clock_jumps.keys())
+        .copied()
+        .filter(|&attempt| attempt > last_attempt)
+        .collect();
+    stale_attempts.sort_unstable();
+    stale_attempts.dedup();
+
+    RepairPlan {
+        orphans: orphans
+            .iter()
+            .zip(targets)
+            .map(|((name, _), target)| Orphan {
+                name: (*name).clone(),
+                target: target.map(|c| candidates[c].clone()),
+            })
+            .collect(),
+        stale_attempts,
+    }
+}
+
+/// Moves or drops the orphans and prunes the stale attempts of `plan`. An
+/// orphan is only moved to a segment without data, so reattaching two orphans
+/// to the same segment keeps the first.
+pub fn apply_sidecar_repair(sidecar: &mut RunSidecar, plan: &RepairPlan) {
+    for orphan in &plan.orphans {
+        let Some(meta) = sidecar.segments.remove(&orphan.name) else {
+            continue;
+        };
+        if let Some(target) = &orphan.target {
+            sidecar.segments.entry(target.clone()).or_insert(meta);
+        }
+    }
+    for attempt in &plan.stale_attempts {
+        sidecar.attempt_tags.remove(attempt);
+        sidecar.clock_jumps.remove(attempt);
+    }
+}
+
+/// What is stored for a segment, e.g. "Excluded · gold on 2024-05-01".
+pub fn describe_segment_meta(meta: &SegmentMeta) -> String {
+    let mut parts = Vec::new();
+    if meta.excluded {
+        parts.push("Excluded".to_owned());
+    }
+    if let Some(date) = &meta.gold_date {
+        parts.push(format!("gold on {date}"));
+    }
+    if let Some(split_on) = &meta.split_on {
+        parts.push(format!("split on “{split_on}”"));
+    }
+    parts.join(" · ")
+}
+
+/// How alike two segment names are, from 0 to 1: the better of their edit
+/// distance and the words they share, case aside.
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let a = a.to_lowercase();
+    let b = b.to_lowercase();
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    let edits = 1.0 - edit_distance(&a, &b) as f64 / longest as f64;
+
+    let words = |name: &str| -> Vec<String> {
+        let mut words: Vec<String> = name
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_owned)
+            .collect();
+        words.sort();
+        words.dedup();
+        words
+    };
+    let (a, b) = (words(&a), words(&b));
+    let shared = a.iter().filter(|word| b.contains(word)).count();
+    let all = a.len() + b.len() - shared;
+    let common = if all == 0 {
+        0.0
+    } else {
+        shared as f64 / all as f64
+    };
+    edits.max(common)
+}
+
+/// Characters to insert, delete or replace to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod sidecar_repair_tests {
+    use super::*;
+    use crate::sidecar::ClockJump;
+    use livesplit_core::{Segment, Time};
+
+    fn run(names: &[&str], attempts: i32) -> Run {
+        let mut run = Run::new();
+        for name in names {
+            run.push_segment(Segment::new(*name));
+        }
+        for index in 1..=attempts {
+            run.add_attempt_with_index(Time::new(), index, None, None, None);
+        }
+        run
+    }
+
+    fn orphan(name: &str, target: Option<&str>) -> Orphan {
+        Orphan {
+            name: name.to_owned(),
+            target: target.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn similar_names_score_high() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(name_similarity("Cave Skip", "cave skip"), 1.0);
+        // One word of two in common
+        assert_eq!(name_similarity("Boss", "Final Boss"), 0.5);
+        assert!(name_similarity("Boss", "Credits") < MIN_SIMILARITY);
+    }
+
+    #[test]
+    fn renamed_segments_get_their_data_back() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_excluded("Boss", 1, true);
+        sidecar.set_split_on("Cave Skip", 2, "ledge grab");
+        sidecar.set_excluded("Menu", 0, true);
+        sidecar.set_excluded("Intro", 0, true);
+        let run = run(&["Intro", "Final Boss", "cave skip", "Credits"], 0);
+
+        let plan = plan_sidecar_repair(&run, &sidecar);
+        assert_eq!(
+            plan.orphans,
+            [
+                orphan("Boss", Some("Final Boss")),
+                orphan("Cave Skip", Some("cave skip")),
+                // Nothing like it left: dropped
+                orphan("Menu", None),
+            ]
+        );
+        assert!(plan.stale_attempts.is_empty());
+
+        apply_sidecar_repair(&mut sidecar, &plan);
+        assert!(sidecar.is_excluded("Final Boss"));
+        assert_eq!(sidecar.split_on("cave skip"), Some("ledge grab"));
+        assert!(sidecar.is_excluded("Intro"));
+        assert_eq!(sidecar.segments.len(), 3);
+        assert!(plan_sidecar_repair(&run, &sidecar).is_empty());
+    }
+
+    #[test]
+    fn reordered_segments_need_nothing_and_renames_follow_their_own_name() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_excluded("Credits", 1, true);
+        sidecar.set_gold_date("Intro", 0, Some("2024-05-01".to_owned()));
+        assert!(plan_sidecar_repair(&run(&["Credits", "Intro"], 0), &sidecar).is_empty());
+
+        // Renamed and swapped: the names decide over the order
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_split_on("Water Temple", 0, "boss key");
+        sidecar.set_split_on("Fire Temple", 1, "hammer");
+        let run = run(
+            &["Fire Temple (Glitchless)", "Water Temple (Glitchless)"],
+            0,
+        );
+        let plan = plan_sidecar_repair(&run, &sidecar);
+        assert_eq!(
+            plan.orphans,
+            [
+                orphan("Fire Temple", Some("Fire Temple (Glitchless)")),
+                orphan("Water Temple", Some("Water Temple (Glitchless)")),
+            ]
+        );
+        assert_eq!(
+            reattach_candidates(&run, &sidecar),
+            ["Fire Temple (Glitchless)", "Water Temple (Glitchless)"]
+        );
+    }
+
+    #[test]
+    fn the_old_position_breaks_ties_between_unlike_names() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_gold_date("Level 2", 1, Some("2024-05-01".to_owned()));
+        // "Level 3" is as close a name, but the entry was written second
+        let run = run(&["Level 1b", "Level 2b", "Level 3"], 0);
+        let plan = plan_sidecar_repair(&run, &sidecar);
+        assert_eq!(plan.orphans, [orphan("Level 2", Some("Level 2b"))]);
+
+        // Without a similar name, the position alone is not enough
+        let run = self::run(&["Intro", "Dungeon"], 0);
+        let plan = plan_sidecar_repair(&run, &sidecar);
+        assert_eq!(plan.orphans, [orphan("Level 2", None)]);
+    }
+
+    #[test]
+    fn attempts_past_a_truncated_history_are_pruned() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.add_attempt_tag(2, "race");
+        sidecar.add_attempt_tag(5, "practice");
+        let jump = ClockJump {
+            at: "2026-03-02 21:14:05".to_owned(),
+            offset_ms: 3_000,
+        };
+        sidecar.record_clock_jump(3, jump.clone());
+        sidecar.record_clock_jump(5, jump);
+        let run = run(&["Intro"], 3);
+
+        let plan = plan_sidecar_repair(&run, &sidecar);
+        assert!(plan.orphans.is_empty());
+        assert_eq!(plan.stale_attempts, [5]);
+
+        let run = self::run(&["Intro"], 2);
+        let plan = plan_sidecar_repair(&run, &sidecar);
+        assert_eq!(plan.stale_attempts, [3, 5]);
+        apply_sidecar_repair(&mut sidecar, &plan);
+        assert_eq!(sidecar.attempt_tags(2), ["race"]);
+        assert!(sidecar.attempt_tags(5).is_empty());
+        assert!(sidecar.clock_jumps.is_empty());
+    }
+
+    #[test]
+    fn a_reviewed_plan_keeps_the_first_orphan_of_a_segment() {
+        let mut sidecar = RunSidecar::default();
+        sidecar.set_excluded("Menu", 0, true);
+        sidecar.set_split_on("Title", 0, "press start");
+        sidecar.add_attempt_tag(4, "race");
+        let run = run(&["Start"], 1);
+
+        let plan = RepairPlan {
+            orphans: vec![
+                orphan("Menu", Some("Start")),
+                orphan("Title", Some("Start")),
+            ],
+            stale_attempts: vec![4],
+        };
+        assert_eq!(plan.pruning_only().orphans, []);
+        apply_sidecar_repair(&mut sidecar, &plan);
+        assert!(sidecar.is_excluded("Start"));
+        assert_eq!(sidecar.split_on("Start"), None);
+        assert_eq!(sidecar.segments.len(), 1);
+        assert!(plan_sidecar_repair(&run, &sidecar).is_empty());
+        assert_eq!(
+            describe_segment_meta(&sidecar.segments["Start"]),
+            "Excluded"
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Data behind the delta sparkline: how far ahead or behind a comparison every
+//! attempt was at each split.
+
+use livesplit_core::{Segment, TimerPhase, TimingMethod};
+
+/// Cumulative delta against `comparison` at every segment, for each attempt in the
+/// history (in attempt order).
+///
+/// An attempt's series ends at the segment it was reset on. Skipped splits, and
+/// segments the comparison has no time for, yield `None` without ending the series.
+/// Imported history (ids 0 and below) belongs to no attempt and is left out.
+pub fn attempt_delta_series(
+    segments: &[Segment],
+    comparison: &str,
+    method: TimingMethod,
+) -> Vec<Vec<Option<time::Duration>>> {
+    use std::collections::BTreeMap;
+
+    // Running split time of every attempt still alive at the current segment
+    let mut running: BTreeMap<i32, time::Duration> = BTreeMap::new();
+    let mut series: BTreeMap<i32, Vec<Option<time::Duration>>> = BTreeMap::new();
+
+    for (index, segment) in segments.iter().enumerate() {
+        let target = segment.comparison(comparison)[method].map(|t| t.to_duration());
+        let mut next = BTreeMap::new();
+
+        for (attempt, segment_time) in segment.segment_history().iter() {
+            if *attempt <= 0 {
+                continue;
+            }
+            let previous = if index == 0 {
+                time::Duration::ZERO
+            } else if let Some(previous) = running.get(attempt) {
+                *previous
+            } else {
+                continue;
+            };
+
+            let split = segment_time[method].map(|t| previous + t.to_duration());
+            next.insert(*attempt, split.unwrap_or(previous));
+
+            let delta = split.zip(target).map(|(split, target)| split - target);
+            series.entry(*attempt).or_default().push(delta);
+        }
+
+        running = next;
+    }
+
+    series.into_values().collect()
+}
+
+/// Splits done in an attempt in `phase`, at `current_split_index`, of a run of
+/// `segment_count` segments.
+pub fn splits_done(
+    phase: TimerPhase,
+    current_split_index: Option<usize>,
+    segment_count: usize,
+) -> usize {
+    match phase {
+        TimerPhase::NotRunning => 0,
+        TimerPhase::Ended => segment_count,
+        _ => current_split_index.unwrap_or(0),
+    }
+}
+
+/// Cumulative delta against `comparison` for the splits done so far in the current
+/// attempt.
+pub fn current_delta_series(
+    segments: &[Segment],
+    splits_done: usize,
+    comparison: &str,
+    method: TimingMethod,
+) -> Vec<Option<time::Duration>> {
+    segments
+        .iter()
+        .take(splits_done)
+        .map(|segment| {
+            let split = segment.split_time()[method]?;
+            let target = segment.comparison(comparison)[method]?;
+            Some(split.to_duration() - target.to_duration())
+        })
+        .collect()
+}
+
+/// Best and worst delta reached at each segment over a set of attempts.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeltaEnvelope {
+    pub best: Vec<Option<time::Duration>>,
+    pub worst: Vec<Option<time::Duration>>,
+}
+
+impl DeltaEnvelope {
+    pub fn from_series(series: &[Vec<Option<time::Duration>>], segment_count: usize) -> Self {
+        let mut envelope = Self {
+            best: vec![None; segment_count],
+            worst: vec![None; segment_count],
+        };
+
+        for attempt in series {
+            for (index, delta) in attempt.iter().enumerate().take(segment_count) {
+                let Some(delta) = *delta else {
+                    continue;
+                };
+                let best = &mut envelope.best[index];
+                *best = Some(best.map_or(delta, |b| b.min(delta)));
+                let worst = &mut envelope.worst[index];
+                *worst = Some(worst.map_or(delta, |w| w.max(delta)));
+            }
+        }
+
+        envelope
+    }
+
+    /// Largest absolute delta in the envelope, used to scale the plot.
+    pub fn magnitude(&self) -> time::Duration {
+        self.best
+            .iter()
+            .chain(&self.worst)
+            .flatten()
+            .map(|d| d.abs())
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod sparkline_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::Time;
+    use time::Duration;
+
+    const PB: &str = "Personal Best";
+
+    /// Segments with PB split times `pb` and, per attempt, the segment times it
+    /// recorded (`None` = skipped; a shorter list = reset).
+    fn segments(pb: &[i64], attempts: &[(i32, &[Option<i64>])]) -> Vec<Segment> {
+        pb.iter()
+            .enumerate()
+            .map(|(index, &split)| {
+                let mut segment = Segment::new(format!("S{index}"));
+                segment.set_personal_best_split_time(real(split as f64));
+                for (id, times) in attempts {
+                    if let Some(time) = times.get(index) {
+                        let time = time.map_or_else(Time::new, |time| real(time as f64));
+                        segment.segment_history_mut().insert(*id, time);
+                    }
+                }
+                segment
+            })
+            .collect()
+    }
+
+    fn s(seconds: i64) -> Option<Duration> {
+        Some(Duration::seconds(seconds))
+    }
+
+    #[test]
+    fn full_attempts_accumulate_deltas() {
+        let segments = segments(&[10, 20, 30], &[(1, &[Some(12), Some(9), Some(10)])]);
+        assert_eq!(
+            attempt_delta_series(&segments, PB, TimingMethod::RealTime),
+            vec![vec![s(2), s(1), s(1)]]
+        );
+    }
+
+    #[test]
+    fn resets_end_the_series_mid_run() {
+        let segments = segments(
+            &[10, 20, 30],
+            &[(1, &[Some(11)]), (2, &[Some(9), Some(10)])],
+        );
+        assert_eq!(
+            attempt_delta_series(&segments, PB, TimingMethod::RealTime),
+            vec![vec![s(1)], vec![s(-1), s(-1)]]
+        );
+    }
+
+    #[test]
+    fn imported_history_is_left_out() {
+        let segments = segments(
+            &[10, 20, 30],
+            &[
+                (0, &[Some(8)]),
+                (-3, &[Some(12), Some(9)]),
+                (1, &[Some(11)]),
+            ],
+        );
+        assert_eq!(
+            attempt_delta_series(&segments, PB, TimingMethod::RealTime),
+            vec![vec![s(1)]]
+        );
+    }
+
+    #[test]
+    fn skipped_splits_carry_time_without_a_point() {
+        let segments = segments(&[10, 20, 30], &[(1, &[Some(10), None, Some(25)])]);
+        assert_eq!(
+            attempt_delta_series(&segments, PB, TimingMethod::RealTime),
+            vec![vec![s(0), None, s(5)]]
+        );
+    }
+
+    #[test]
+    fn envelope_tracks_best_and_worst_per_segment() {
+        let series = vec![vec![s(2), s(4)], vec![s(-1)], vec![None, s(-3)]];
+        let envelope = DeltaEnvelope::from_series(&series, 3);
+        assert_eq!(envelope.best, vec![s(-1), s(-3), None]);
+        assert_eq!(envelope.worst, vec![s(2), s(4), None]);
+        assert_eq!(envelope.magnitude(), Duration::seconds(4));
+        assert_eq!(
+            DeltaEnvelope::from_series(&[], 2).magnitude(),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn splits_done_follow_the_phase() {
+        assert_eq!(splits_done(TimerPhase::NotRunning, None, 3), 0);
+        assert_eq!(splits_done(TimerPhase::Running, Some(0), 3), 0);
+        assert_eq!(splits_done(TimerPhase::Paused, Some(2), 3), 2);
+        assert_eq!(splits_done(TimerPhase::Ended, Some(3), 3), 3);
+    }
+
+    #[test]
+    fn current_attempt_only_counts_splits_done() {
+        let mut segments = segments(&[10, 20, 30], &[]);
+        segments[0].set_split_time(real(8.0));
+        segments[1].set_split_time(real(23.0));
+        assert_eq!(
+            current_delta_series(&segments, 2, PB, TimingMethod::RealTime),
+            vec![s(-2), s(3)]
+        );
+        assert_eq!(
+            current_delta_series(&segments, 1, PB, TimingMethod::RealTime),
+            vec![s(-2)]
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Writer for the Splits I/O exchange format, the JSON splits format LiveSplit
+//! One (and livesplit-core's composite parser) can import.
+//!
+//! livesplit-core only ships a saver for `.lss`, so the document is built here
+//! from the run. Both timing methods are written wherever the format has room
+//! for them.
+
+use livesplit_core::{Run, Time};
+use serde::Serialize;
+
+const SCHEMA_VERSION: &str = "v1.0.0";
+const RUNNER_VARIABLE: &str = "Runner";
+
+#[derive(Serialize)]
+struct Splits<'a> {
+    #[serde(rename = "_schemaVersion")]
+    schema_version: &'static str,
+    timer: Timer,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    game: Option<Name<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category: Option<Name<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    runners: Option<Vec<Runner<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    links: Option<Links<'a>>,
+    attempts: Attempts,
+    segments: Vec<Segment<'a>>,
+}
+
+#[derive(Serialize)]
+struct Timer {
+    longname: &'static str,
+    shortname: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+struct Name<'a> {
+    longname: &'a str,
+}
+
+#[derive(Serialize)]
+struct Runner<'a> {
+    shortname: &'a str,
+    longname: &'a str,
+}
+
+#[derive(Serialize)]
+struct Links<'a> {
+    #[serde(rename = "speedruncomID")]
+    speedruncom_id: &'a str,
+}
+
+#[derive(Serialize)]
+struct Attempts {
+    total: u32,
+    histories: Vec<Attempt>,
+}
+
+#[derive(Serialize)]
+struct Attempt {
+    #[serde(rename = "attemptNumber")]
+    attempt_number: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration: Option<Duration>,
+}
+
+#[derive(Serialize)]
+struct Segment<'a> {
+    name: &'a str,
+    #[serde(rename = "endedAt", skip_serializing_if = "Option::is_none")]
+    ended_at: Option<Duration>,
+    #[serde(rename = "bestDuration", skip_serializing_if = "Option::is_none")]
+    best_duration: Option<Duration>,
+    histories: Vec<SegmentHistory>,
+}
+
+#[derive(Serialize)]
+struct SegmentHistory {
+    #[serde(rename = "attemptNumber")]
+    attempt_number: i32,
+    #[serde(rename = "endedAt", skip_serializing_if = "Option::is_none")]
+    ended_at: Option<Duration>,
+    #[serde(rename = "isSkipped", skip_serializing_if = "std::ops::Not::not")]
+    is_skipped: bool,
+}
+
+/// Real and game time in milliseconds. Used for both durations and run times,
+/// which share their shape in the format.
+#[derive(Serialize)]
+struct Duration {
+    #[serde(rename = "realtimeMS", skip_serializing_if = "Option::is_none")]
+    realtime_ms: Option<f64>,
+    #[serde(rename = "gametimeMS", skip_serializing_if = "Option::is_none")]
+    gametime_ms: Option<f64>,
+}
+
+impl Duration {
+    /// `None` when neither timing method has a value.
+    fn from_time(time: Time) -> Option<Self> {
+        if time.real_time.is_none() && time.game_time.is_none() {
+            return None;
+        }
+        Some(Self {
+            realtime_ms: time.real_time.map(|t| t.total_milliseconds()),
+            gametime_ms: time.game_time.map(|t| t.total_milliseconds()),
+        })
+    }
+}
+
+fn non_empty(value: &str) -> Option<&str> {
+    (!value.trim().is_empty()).then_some(value)
+}
+
+/// Serializes `run` as a Splits I/O JSON document.
+///
+/// Segment histories hold segment times rather than run times, matching how
+/// livesplit-core reads them back.
+pub fn export_run(run: &Run) -> serde_json::Result<String> {
+    let metadata = run.metadata();
+
+    let splits = Splits {
+        schema_version: SCHEMA_VERSION,
+        timer: Timer {
+            longname: "TuxSplit",
+            shortname: "tuxsplit",
+            version: env!("CARGO_PKG_VERSION"),
+        },
+        game: non_empty(run.game_name()).map(|longname| Name { longname }),
+        category: non_empty(run.category_name()).map(|longname| Name { longname }),
+        runners: metadata
+            .custom_variable_value(RUNNER_VARIABLE)
+            .and_then(non_empty)
+            .map(|name| {
+                vec![Runner {
+                    shortname: name,
+                    longname: name,
+                }]
+            }),
+        links: non_empty(metadata.run_id()).map(|speedruncom_id| Links { speedruncom_id }),
+        attempts: Attempts {
+            total: run.attempt_count(),
+            histories: run
+                .attempt_history()
+                .iter()
+                .map(|attempt| Attempt {
+                    attempt_number: attempt.index(),
+                    duration: Duration::from_time(attempt.time()),
+                })
+                .collect(),
+        },
+        segments: run
+            .segments()
+            .iter()
+            .map(|segment| Segment {
+                name: segment.name(),
+                ended_at: Duration::from_time(segment.personal_best_split_time()),
+                best_duration: Duration::from_time(segment.best_segment_time()),
+                histories: segment
+                    .segment_history()
+                    .iter()
+                    .map(|&(attempt_number, time)| {
+                        let ended_at = Duration::from_time(time);
+                        SegmentHistory {
+                            attempt_number,
+                            is_skipped: ended_at.is_none(),
+                            ended_at,
+                        }
+                    })
+                    .collect(),
+            })
+            .collect(),
+    };
+
+    serde_json::to_string_pretty(&splits)
+}
+
+#[cfg(test)]
+mod splits_io_tests {
+    use super::*;
+    use livesplit_core::run::parser::composite;
+    use livesplit_core::{Segment, TimeSpan, TimingMethod};
+
+    fn time(real: f64, game: Option<f64>) -> Time {
+        Time::new()
+            .with_real_time(Some(TimeSpan::from_seconds(real)))
+            .with_game_time(game.map(TimeSpan::from_seconds))
+    }
+
+    fn run() -> Run {
+        let mut run = Run::new();
+        run.set_game_name("Celeste");
+        run.set_category_name("Any%");
+        run.metadata_mut()
+            .custom_variable_mut(RUNNER_VARIABLE)
+            .permanent()
+            .set_value("madeline");
+        run.set_attempt_count(7);
+        run.add_attempt_with_index(time(95.5, Some(90.0)), 6, None, None, None);
+        run.add_attempt_with_index(Time::new(), 7, None, None, None);
+
+        for (name, pb, gold, history) in [
+            ("Forsaken City", 40.25, 38.0, Some(40.25)),
+            ("Old Site", 95.5, 52.5, None),
+        ] {
+            let mut segment = Segment::new(name);
+            segment.set_personal_best_split_time(time(pb, Some(pb - 5.0)));
+            segment.set_best_segment_time(time(gold, None));
+            segment
+                .segment_history_mut()
+                .insert(6, history.map_or(Time::new(), |h| time(h, None)));
+            run.push_segment(segment);
+        }
+        run
+    }
+
+    #[test]
+    fn export_round_trips_through_the_composite_parser() {
+        let original = run();
+        let json = export_run(&original).unwrap();
+        let parsed = composite::parse(json.as_bytes(), None).unwrap().run;
+
+        assert_eq!(parsed.game_name(), "Celeste");
+        assert_eq!(parsed.category_name(), "Any%");
+        assert_eq!(parsed.attempt_count(), 7);
+        assert_eq!(parsed.attempt_history(), original.attempt_history());
+        assert_eq!(
+            parsed.metadata().custom_variable_value(RUNNER_VARIABLE),
+            Some("madeline")
+        );
+
+        assert_eq!(parsed.len(), original.len());
+        for (parsed, original) in parsed.segments().iter().zip(original.segments()) {
+            assert_eq!(parsed.name(), original.name());
+            for method in [TimingMethod::RealTime, TimingMethod::GameTime] {
+                assert_eq!(
+                    parsed.personal_best_split_time()[method],
+                    original.personal_best_split_time()[method]
+                );
+                assert_eq!(
+                    parsed.best_segment_time()[method],
+                    original.best_segment_time()[method]
+                );
+            }
+            assert_eq!(
+                parsed.segment_history().get(6),
+                original.segment_history().get(6)
+            );
+        }
+    }
+
+    #[test]
+    fn empty_metadata_and_skipped_splits_are_left_out() {
+        let mut run = Run::new();
+        let mut segment = Segment::new("Only");
+        segment.segment_history_mut().insert(1, Time::new());
+        run.push_segment(segment);
+
+        let json = export_run(&run).unwrap();
+        assert!(!json.contains("\"game\""));
+        assert!(!json.contains("\"runners\""));
+        assert!(!json.contains("\"endedAt\""));
+        assert!(json.contains("\"isSkipped\": true"));
+    }
+}
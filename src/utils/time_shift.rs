@@ -0,0 +1,412 @@
+//! Shifting every time of a run by a constant ("Shift Times..." in the editor),
+//! to correct a capture that started late or early.
+//!
+//! Split times move by the full offset. Segment times are differences of split
+//! times, so the only segment whose time changes is the first: the first timed
+//! one in runs or attempts that skipped the splits before it. That is the only
+//! best segment shifted and, in the attempt history (which keeps segment times),
+//! the only segment time of each attempt besides its final time. Times never go
+//! below zero, so a clamped split shortens the segment after it.
+
+use livesplit_core::{Run, RunEditor, TimeSpan, TimingMethod};
+use time::Duration;
+
+use crate::utils::attempt_history::rewrite_history;
+
+/// Which times of the run a shift applies to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShiftScope {
+    /// Comparisons whose split times move, "Personal Best" included.
+    pub comparisons: Vec<String>,
+    pub best_segments: bool,
+    pub history: bool,
+}
+
+/// A time the shift changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShiftTarget {
+    /// Split time of the segment at `index` in a comparison.
+    Split {
+        comparison: String,
+        index: usize,
+    },
+    BestSegment {
+        index: usize,
+    },
+    /// Time of the segment at `index` in attempt `attempt` of the history.
+    AttemptSegment {
+        attempt: i32,
+        index: usize,
+    },
+    /// Final time of attempt `attempt` of the history.
+    AttemptTime {
+        attempt: i32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeEdit {
+    pub target: ShiftTarget,
+    pub before: Duration,
+    pub after: Duration,
+}
+
+fn shifted(time: TimeSpan, offset: Duration) -> (Duration, Duration) {
+    let before = time.to_duration();
+    (before, (before + offset).max(Duration::ZERO))
+}
+
+/// The times of `run` in `method` that shifting `scope` by `offset` changes,
+/// in the order `apply_time_shift` can set them in. Split times of a comparison
+/// come last to first when moving later, so that no split passes the one after
+/// it halfway through (the editor would clamp it).
+pub fn plan_time_shift(
+    run: &Run,
+    method: TimingMethod,
+    offset: Duration,
+    scope: &ShiftScope,
+) -> Vec<TimeEdit> {
+    let mut edits = Vec::new();
+    let mut push = |target: ShiftTarget, time: TimeSpan| {
+        let (before, after) = shifted(time, offset);
+        if before != after {
+            edits.push(TimeEdit {
+                target,
+                before,
+                after,
+            });
+        }
+    };
+    let segments = run.segments();
+
+    for comparison in &scope.comparisons {
+        let mut splits: Vec<(usize, TimeSpan)> = segments
+            .iter()
+            .enumerate()
+            .filter_map(|(index, segment)| Some((index, segment.comparison(comparison)[method]?)))
+            .collect();
+        if offset.is_positive() {
+            splits.reverse();
+        }
+        for (index, time) in splits {
+            let comparison = comparison.clone();
+            push(ShiftTarget::Split { comparison, index }, time);
+        }
+    }
+
+    if scope.best_segments
+        && let Some(time) = segments
+            .first()
+            .and_then(|segment| segment.best_segment_time()[method])
+    {
+        push(ShiftTarget::BestSegment { index: 0 }, time);
+    }
+
+    if scope.history {
+        for attempt in run.attempt_history() {
+            let index = attempt.index();
+            let first = segments.iter().enumerate().find_map(|(position, segment)| {
+                Some((position, segment.segment_history().get(index)?[method]?))
+            });
+            if let Some((position, time)) = first {
+                push(
+                    ShiftTarget::AttemptSegment {
+                        attempt: index,
+                        index: position,
+                    },
+                    time,
+                );
+            }
+            if let Some(time) = attempt.time()[method] {
+                push(ShiftTarget::AttemptTime { attempt: index }, time);
+            }
+        }
+    }
+
+    edits
+}
+
+/// `run` with `edits` of `plan_time_shift` set in `method`. Comparisons and
+/// best segments go through `RunEditor`, the history is rewritten after.
+pub fn apply_time_shift(run: Run, method: TimingMethod, edits: &[TimeEdit]) -> Run {
+    let Ok(mut run_editor) = RunEditor::new(run.clone()) else {
+        return run;
+    };
+    run_editor.select_timing_method(method);
+    for edit in edits {
+        let after = Some(TimeSpan::from(edit.after));
+        match &edit.target {
+            ShiftTarget::Split { comparison, index } => {
+                run_editor.select_only(*index);
+                run_editor
+                    .active_segment()
+                    .set_comparison_time(comparison, after);
+            }
+            ShiftTarget::BestSegment { index } => {
+                run_editor.select_only(*index);
+                run_editor.active_segment().set_best_segment_time(after);
+            }
+            ShiftTarget::AttemptSegment { .. } | ShiftTarget::AttemptTime { .. } => {}
+        }
+    }
+    let mut run = run_editor.close();
+
+    let new_time = |target: ShiftTarget| {
+        edits
+            .iter()
+            .find(|edit| edit.target == target)
+            .map(|edit| TimeSpan::from(edit.after))
+    };
+    let touches_history = edits.iter().any(|edit| {
+        matches!(
+            edit.target,
+            ShiftTarget::AttemptSegment { .. } | ShiftTarget::AttemptTime { .. }
+        )
+    });
+    if touches_history {
+        rewrite_history(
+            &mut run,
+            |attempt| {
+                let mut time = attempt.time();
+                if let Some(after) = new_time(ShiftTarget::AttemptTime {
+                    attempt: attempt.index(),
+                }) {
+                    time[method] = Some(after);
+                }
+                Some((attempt.index(), time))
+            },
+            |index, attempt, mut time| {
+                if let Some(after) = new_time(ShiftTarget::AttemptSegment { attempt, index }) {
+                    time[method] = Some(after);
+                }
+                Some((attempt, time))
+            },
+        );
+        run.mark_as_modified();
+    }
+    run
+}
+
+#[cfg(test)]
+mod time_shift_tests {
+    use super::*;
+    use crate::test_support::real;
+    use livesplit_core::{Segment, Time};
+
+    const PB: &str = "Personal Best";
+    const METHOD: TimingMethod = TimingMethod::RealTime;
+
+    fn ms(millis: i64) -> Duration {
+        Duration::milliseconds(millis)
+    }
+
+    /// PB splits at 10s, 25s, 45s with golds 9s, 14s, 19s, a "Goal" comparison
+    /// and two attempts: the first finished in 50s, the second skipped the
+    /// first split and was reset in the third segment.
+    fn run() -> Run {
+        let mut run = Run::new();
+        let pb = [10.0, 25.0, 45.0];
+        let golds = [9.0, 14.0, 19.0];
+        for (index, name) in ["One", "Two", "Three"].into_iter().enumerate() {
+            let mut segment = Segment::new(name);
+            *segment.personal_best_split_time_mut() = real(pb[index]);
+            segment.set_best_segment_time(real(golds[index]));
+            run.push_segment(segment);
+        }
+        run.add_custom_comparison("Goal").unwrap();
+        for (segment, goal) in run.segments_mut().iter_mut().zip([9.5, 24.0, 44.0]) {
+            *segment.comparison_mut("Goal") = real(goal);
+        }
+        let histories: [(i32, [Option<f64>; 3]); 2] = [
+            (1, [Some(11.0), Some(16.0), Some(23.0)]),
+            (2, [None, Some(26.0), None]),
+        ];
+        for (attempt, times) in histories {
+            for (segment, time) in run.segments_mut().iter_mut().zip(times) {
+                if let Some(time) = time {
+                    segment.segment_history_mut().insert(attempt, real(time));
+                } else if attempt == 2 && segment.name() == "One" {
+                    segment.segment_history_mut().insert(attempt, Time::new());
+                }
+            }
+        }
+        run.add_attempt_with_index(real(50.0), 1, None, None, None);
+        run.add_attempt_with_index(Time::new(), 2, None, None, None);
+        run
+    }
+
+    fn scope(comparisons: &[&str], best_segments: bool, history: bool) -> ShiftScope {
+        ShiftScope {
+            comparisons: comparisons.iter().map(|&c| c.to_owned()).collect(),
+            best_segments,
+            history,
+        }
+    }
+
+    fn split(index: usize) -> ShiftTarget {
+        ShiftTarget::Split {
+            comparison: PB.to_owned(),
+            index,
+        }
+    }
+
+    fn splits(run: &Run, comparison: &str) -> Vec<Option<Duration>> {
+        run.segments()
+            .iter()
+            .map(|segment| segment.comparison(comparison)[METHOD].map(|t| t.to_duration()))
+            .collect()
+    }
+
+    fn segment_times(splits: &[Option<Duration>]) -> Vec<Duration> {
+        let mut previous = Duration::ZERO;
+        splits
+            .iter()
+            .flatten()
+            .map(|&split| {
+                let segment = split - previous;
+                previous = split;
+                segment
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_times_move_in_an_order_that_never_crosses() {
+        let later = plan_time_shift(&run(), METHOD, ms(350), &scope(&[PB], false, false));
+        let targets: Vec<_> = later.iter().map(|edit| edit.target.clone()).collect();
+        assert_eq!(targets, [split(2), split(1), split(0)]);
+        assert_eq!((later[0].before, later[0].after), (ms(45_000), ms(45_350)));
+
+        let earlier = plan_time_shift(&run(), METHOD, ms(-350), &scope(&[PB], false, false));
+        let targets: Vec<_> = earlier.iter().map(|edit| edit.target.clone()).collect();
+        assert_eq!(targets, [split(0), split(1), split(2)]);
+        assert_eq!(earlier[0].after, ms(9_650));
+    }
+
+    #[test]
+    fn only_the_first_segment_time_changes() {
+        let before = run();
+        for offset in [ms(350), ms(-350)] {
+            let edits = plan_time_shift(&before, METHOD, offset, &scope(&[PB], false, false));
+            let after = apply_time_shift(before.clone(), METHOD, &edits);
+            let old = segment_times(&splits(&before, PB));
+            let new = segment_times(&splits(&after, PB));
+            assert_eq!(new[0], old[0] + offset);
+            assert_eq!(new[1..], old[1..]);
+            assert_eq!(
+                splits(&after, PB).last().copied().flatten(),
+                Some(ms(45_000) + offset)
+            );
+        }
+    }
+
+    #[test]
+    fn times_are_clamped_at_zero() {
+        let edits = plan_time_shift(&run(), METHOD, ms(-12_000), &scope(&[PB], true, false));
+        assert_eq!(
+            edits[0],
+            TimeEdit {
+                target: split(0),
+                before: ms(10_000),
+                after: Duration::ZERO,
+            }
+        );
+        let gold = edits.last().unwrap();
+        assert_eq!(gold.target, ShiftTarget::BestSegment { index: 0 });
+        assert_eq!(gold.after, Duration::ZERO);
+
+        let after = apply_time_shift(run(), METHOD, &edits);
+        assert_eq!(
+            splits(&after, PB),
+            [Some(Duration::ZERO), Some(ms(13_000)), Some(ms(33_000))]
+        );
+    }
+
+    #[test]
+    fn skipped_splits_stay_skipped() {
+        let mut run = run();
+        *run.segments_mut()[0].personal_best_split_time_mut() = Time::new();
+        let edits = plan_time_shift(&run, METHOD, ms(500), &scope(&[PB], false, false));
+        assert_eq!(edits.len(), 2);
+        assert!(!edits.iter().any(|edit| edit.target == split(0)));
+
+        let after = apply_time_shift(run, METHOD, &edits);
+        assert_eq!(
+            splits(&after, PB),
+            [None, Some(ms(25_500)), Some(ms(45_500))]
+        );
+    }
+
+    #[test]
+    fn scope_picks_comparisons_golds_and_history() {
+        let run = run();
+        // Nothing to do without an offset or a scope
+        assert!(
+            plan_time_shift(&run, METHOD, Duration::ZERO, &scope(&[PB], true, true)).is_empty()
+        );
+        assert!(plan_time_shift(&run, METHOD, ms(350), &ShiftScope::default()).is_empty());
+        // Times of the other timing method are left alone
+        assert!(
+            plan_time_shift(
+                &run,
+                TimingMethod::GameTime,
+                ms(350),
+                &scope(&[PB], true, true)
+            )
+            .is_empty()
+        );
+
+        let edits = plan_time_shift(&run, METHOD, ms(350), &scope(&["Goal"], true, true));
+        let after = apply_time_shift(run.clone(), METHOD, &edits);
+        assert_eq!(splits(&after, PB), splits(&run, PB));
+        assert_eq!(
+            splits(&after, "Goal"),
+            [Some(ms(9_850)), Some(ms(24_350)), Some(ms(44_350))]
+        );
+        let golds: Vec<_> = after
+            .segments()
+            .iter()
+            .map(|segment| segment.best_segment_time()[METHOD].unwrap().to_duration())
+            .collect();
+        assert_eq!(golds, [ms(9_350), ms(14_000), ms(19_000)]);
+    }
+
+    #[test]
+    fn history_shifts_the_first_timed_segment_and_final_time() {
+        let run = run();
+        let edits = plan_time_shift(&run, METHOD, ms(350), &scope(&[], false, true));
+        let targets: Vec<_> = edits.iter().map(|edit| edit.target.clone()).collect();
+        assert_eq!(
+            targets,
+            [
+                ShiftTarget::AttemptSegment {
+                    attempt: 1,
+                    index: 0
+                },
+                ShiftTarget::AttemptTime { attempt: 1 },
+                // Attempt 2 skipped the first split and has no final time
+                ShiftTarget::AttemptSegment {
+                    attempt: 2,
+                    index: 1
+                },
+            ]
+        );
+
+        let after = apply_time_shift(run, METHOD, &edits);
+        let history = |index: usize, attempt: i32| {
+            after.segments()[index]
+                .segment_history()
+                .get(attempt)
+                .and_then(|t| t[METHOD])
+        };
+        assert_eq!(history(0, 1), Some(TimeSpan::from_seconds(11.35)));
+        assert_eq!(history(1, 1), Some(TimeSpan::from_seconds(16.0)));
+        assert_eq!(history(0, 2), None);
+        assert_eq!(history(1, 2), Some(TimeSpan::from_seconds(26.35)));
+        assert_eq!(
+            after.attempt_history()[0].time().real_time,
+            Some(TimeSpan::from_seconds(50.35))
+        );
+        assert_eq!(after.attempt_history()[1].time().real_time, None);
+    }
+}
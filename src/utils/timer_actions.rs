@@ -0,0 +1,723 @@
+//! Serialized timer mutations.
+//!
+//! Hotkeys fire on their own thread while the auto start and the UI run on the
+//! main loop, so their changes to the timer could interleave. Every one of them is
+//! queued here instead as a `TimerAction`, and a single dispatcher thread applies
+//! them in arrival order, taking the write lock once per action. Whether an action
+//! makes sense in the current phase is decided in one place, `permitted`.
+//!
+//! With the pre-flight checklist on, actions that would start an attempt are
+//! held back by the dispatcher instead, and counted in an `ActionGate` for the
+//! main loop to show the checklist. The start it sends once the checklist is
+//! ticked off is queued with `send_checked`, which goes through. The gate is
+//! also armed while edits to the run are regenerated, which would reset an
+//! attempt started in the meantime.
+//!
+//! Resets of the auto splitter are queued with `send_scripted` and held in
+//! another gate while resets are guarded, for the main loop to decide on them
+//! (see `utils::reset_guard`) and queue the ones it allows.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::thread;
+
+use livesplit_core::{SharedTimer, Timer, TimerPhase};
+use tracing::debug;
+
+use crate::utils::run_snapshots::SharedRunSnapshots;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimerAction {
+    Start,
+    Split,
+    /// Starts an attempt, or splits the one in progress.
+    SplitOrStart,
+    Skip,
+    Undo,
+    Reset {
+        update_splits: bool,
+    },
+    /// Pauses or resumes the attempt, or starts one.
+    TogglePauseOrStart,
+    Pause,
+    Resume,
+    UndoAllPauses,
+    PreviousComparison,
+    NextComparison,
+    /// Switches to the comparison of that name, if the run has one.
+    SetComparison(String),
+    ToggleTimingMethod,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionOutcome {
+    Applied,
+    /// Not valid in the phase the timer was in, so left out.
+    Ignored,
+    /// Would have started an attempt before the pre-flight checklist was ticked
+    /// off, or is a guarded reset of the auto splitter, so left to the main loop
+    /// (see `ActionGate`).
+    Held,
+}
+
+/// What the dispatcher did with an action. `seq` is its position among every
+/// action applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionResult {
+    pub seq: u64,
+    pub action: TimerAction,
+    pub outcome: ActionOutcome,
+}
+
+/// Whether `action` applies to a timer in `phase`.
+pub const fn permitted(action: &TimerAction, phase: TimerPhase) -> bool {
+    use TimerPhase::{Ended, NotRunning, Paused, Running};
+
+    match action {
+        TimerAction::Start => matches!(phase, NotRunning),
+        TimerAction::Split => matches!(phase, Running),
+        TimerAction::SplitOrStart => matches!(phase, NotRunning | Running),
+        TimerAction::Skip => matches!(phase, Running | Paused),
+        TimerAction::Undo | TimerAction::Reset { .. } => !matches!(phase, NotRunning),
+        TimerAction::TogglePauseOrStart => !matches!(phase, Ended),
+        TimerAction::Pause => matches!(phase, Running),
+        TimerAction::Resume => matches!(phase, Paused),
+        TimerAction::UndoAllPauses => !matches!(phase, NotRunning),
+        TimerAction::PreviousComparison
+        | TimerAction::NextComparison
+        | TimerAction::SetComparison(_)
+        | TimerAction::ToggleTimingMethod => true,
+    }
+}
+
+/// Whether `action` would start an attempt on a timer in `phase`.
+pub const fn starts_attempt(action: &TimerAction, phase: TimerPhase) -> bool {
+    matches!(phase, TimerPhase::NotRunning)
+        && matches!(
+            action,
+            TimerAction::Start | TimerAction::SplitOrStart | TimerAction::TogglePauseOrStart
+        )
+}
+
+/// Holds back actions for the main loop: the starts of attempts while the
+/// pre-flight checklist is on or edits to the run are still on their way to
+/// the timer, and the resets of the auto splitter while they are guarded. The
+/// main loop arms it and takes the actions held since the last tick; clones
+/// share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct ActionGate {
+    armed: Arc<AtomicBool>,
+    requests: Arc<AtomicU32>,
+}
+
+impl ActionGate {
+    pub fn set_armed(&self, armed: bool) {
+        self.armed.store(armed, Ordering::Relaxed);
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.armed.load(Ordering::Relaxed)
+    }
+
+    fn request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Takes the actions held back so far. Returns whether there were any.
+    pub fn take_requests(&self) -> bool {
+        self.requests.swap(0, Ordering::Relaxed) > 0
+    }
+}
+
+/// Whether the attempt has pause time that "Undo All Pauses" would add back,
+/// including that of a pause still going on.
+pub fn can_undo_all_pauses(timer: &Timer) -> bool {
+    permitted(&TimerAction::UndoAllPauses, timer.current_phase())
+        && timer.get_pause_time().is_some()
+}
+
+/// Applies `action` to `timer` if its phase permits it.
+pub fn apply(timer: &mut Timer, action: TimerAction) -> ActionOutcome {
+    if !permitted(&action, timer.current_phase()) {
+        return ActionOutcome::Ignored;
+    }
+    match action {
+        TimerAction::Start => timer.start(),
+        TimerAction::Split => timer.split(),
+        TimerAction::SplitOrStart => timer.split_or_start(),
+        TimerAction::Skip => timer.skip_split(),
+        TimerAction::Undo => timer.undo_split(),
+        TimerAction::Reset { update_splits } => {
+            timer.reset(update_splits);
+            // Back to the game time of a new timer, so the auto start sees the
+            // auto-splitter initializing it for the next attempt
+            timer.deinitialize_game_time();
+        }
+        TimerAction::TogglePauseOrStart => timer.toggle_pause_or_start(),
+        TimerAction::Pause => timer.pause(),
+        TimerAction::Resume => timer.resume(),
+        TimerAction::UndoAllPauses => timer.undo_all_pauses(),
+        TimerAction::PreviousComparison => timer.switch_to_previous_comparison(),
+        TimerAction::NextComparison => timer.switch_to_next_comparison(),
+        TimerAction::SetComparison(name) => {
+            if timer.set_current_comparison(name).is_err() {
+                return ActionOutcome::Ignored;
+            }
+        }
+        TimerAction::ToggleTimingMethod => timer.toggle_timing_method(),
+    }
+    ActionOutcome::Applied
+}
+
+struct Request {
+    action: TimerAction,
+    /// Whether the action got past the pre-flight checklist already.
+    checked: bool,
+    /// Whether the auto splitter asked for it.
+    scripted: bool,
+    reply: Option<Sender<ActionResult>>,
+}
+
+/// Sending end of the queue. Clones feed the same dispatcher, which stops once
+/// they are all dropped.
+#[derive(Clone)]
+pub struct ActionQueue {
+    sender: Sender<Request>,
+}
+
+impl ActionQueue {
+    /// Starts the dispatcher applying the queued actions to `timer`.
+    pub fn spawn(timer: SharedTimer) -> Self {
+        Self::spawn_recording(timer, SharedRunSnapshots::default())
+    }
+
+    /// Like `spawn`, also taking a snapshot of the run into `snapshots` before
+    /// every reset that updates the splits.
+    pub fn spawn_recording(timer: SharedTimer, snapshots: SharedRunSnapshots) -> Self {
+        Self::spawn_gated(
+            timer,
+            snapshots,
+            ActionGate::default(),
+            ActionGate::default(),
+        )
+    }
+
+    /// Like `spawn_recording`, also holding back the starts of attempts while
+    /// `preflight` is armed, and the resets of the auto splitter while `resets`
+    /// is.
+    pub fn spawn_gated(
+        timer: SharedTimer,
+        snapshots: SharedRunSnapshots,
+        preflight: ActionGate,
+        resets: ActionGate,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("timer-actions".to_owned())
+            .spawn(move || dispatch(&timer, &receiver, &snapshots, &preflight, &resets))
+            .expect("Failed to start the timer action dispatcher");
+        Self { sender }
+    }
+
+    /// Queues `action`.
+    pub fn send(&self, action: TimerAction) {
+        let _ = self.sender.send(Request {
+            action,
+            checked: false,
+            scripted: false,
+            reply: None,
+        });
+    }
+
+    /// Queues `action` past the pre-flight checklist, for the start confirmed by
+    /// it and the starts that are not a press of the user.
+    pub fn send_checked(&self, action: TimerAction) {
+        let _ = self.sender.send(Request {
+            action,
+            checked: true,
+            scripted: false,
+            reply: None,
+        });
+    }
+
+    /// Queues `action` for the auto splitter, whose resets are held while they
+    /// are guarded.
+    pub fn send_scripted(&self, action: TimerAction) {
+        let _ = self.sender.send(Request {
+            action,
+            checked: false,
+            scripted: true,
+            reply: None,
+        });
+    }
+
+    /// Queues `action`, with what came of it sent back once it is applied.
+    pub fn enqueue(&self, action: TimerAction) -> Receiver<ActionResult> {
+        let (reply, result) = mpsc::channel();
+        let _ = self.sender.send(Request {
+            action,
+            checked: false,
+            scripted: false,
+            reply: Some(reply),
+        });
+        result
+    }
+}
+
+fn dispatch(
+    timer: &RwLock<Timer>,
+    requests: &Receiver<Request>,
+    snapshots: &SharedRunSnapshots,
+    preflight: &ActionGate,
+    resets: &ActionGate,
+) {
+    for (seq, request) in (0..).zip(requests) {
+        let (outcome, phase) = {
+            let mut timer = timer.write().unwrap_or_else(PoisonError::into_inner);
+            let phase = timer.current_phase();
+            if !request.checked && preflight.is_armed() && starts_attempt(&request.action, phase) {
+                preflight.request();
+                (ActionOutcome::Held, phase)
+            } else if request.scripted
+                && resets.is_armed()
+                && matches!(request.action, TimerAction::Reset { .. })
+                && permitted(&request.action, phase)
+            {
+                resets.request();
+                (ActionOutcome::Held, phase)
+            } else {
+                if request.action
+                    == (TimerAction::Reset {
+                        update_splits: true,
+                    })
+                    && permitted(&request.action, phase)
+                {
+                    snapshots
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .take(timer.run(), "Reset with splits updated");
+                }
+                (apply(&mut timer, request.action.clone()), phase)
+            }
+        };
+        match outcome {
+            ActionOutcome::Ignored => debug!("Ignored {:?} while {phase:?}", request.action),
+            ActionOutcome::Held => debug!("Held {:?} for the main loop", request.action),
+            ActionOutcome::Applied => {}
+        }
+        if let Some(reply) = request.reply {
+            let _ = reply.send(ActionResult {
+                seq,
+                action: request.action,
+                outcome,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod timer_actions_tests {
+    use super::*;
+    use livesplit_core::{Run, Segment};
+
+    const ACTIONS: [TimerAction; 13] = [
+        TimerAction::Start,
+        TimerAction::Split,
+        TimerAction::SplitOrStart,
+        TimerAction::Skip,
+        TimerAction::Undo,
+        TimerAction::Reset {
+            update_splits: true,
+        },
+        TimerAction::TogglePauseOrStart,
+        TimerAction::Pause,
+        TimerAction::Resume,
+        TimerAction::UndoAllPauses,
+        TimerAction::PreviousComparison,
+        TimerAction::NextComparison,
+        TimerAction::ToggleTimingMethod,
+    ];
+
+    fn timer() -> Timer {
+        let mut run = Run::new();
+        for name in ["S1", "S2", "S3", "S4"] {
+            run.push_segment(Segment::new(name));
+        }
+        Timer::new(run).unwrap()
+    }
+
+    /// The parts of the timer that do not depend on when the actions ran.
+    fn state(timer: &Timer) -> (TimerPhase, Option<usize>, usize, Vec<bool>, String) {
+        let method = timer.current_timing_method();
+        (
+            timer.current_phase(),
+            timer.current_split_index(),
+            timer.run().attempt_history().len(),
+            timer
+                .run()
+                .segments()
+                .iter()
+                .map(|segment| segment.split_time()[method].is_some())
+                .collect(),
+            timer.current_comparison().to_owned(),
+        )
+    }
+
+    /// xorshift, to pick actions without a rand dependency.
+    fn actions(mut seed: u64, count: usize) -> Vec<TimerAction> {
+        (0..count)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                ACTIONS[(seed % ACTIONS.len() as u64) as usize].clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn actions_are_checked_against_the_phase() {
+        let mut timer = timer();
+        assert_eq!(
+            apply(&mut timer, TimerAction::Split),
+            ActionOutcome::Ignored
+        );
+        assert_eq!(apply(&mut timer, TimerAction::Undo), ActionOutcome::Ignored);
+        assert_eq!(
+            apply(&mut timer, TimerAction::SplitOrStart),
+            ActionOutcome::Applied
+        );
+        assert_eq!(
+            apply(&mut timer, TimerAction::Start),
+            ActionOutcome::Ignored
+        );
+        assert_eq!(
+            apply(&mut timer, TimerAction::Split),
+            ActionOutcome::Applied
+        );
+        assert_eq!(timer.current_split_index(), Some(1));
+
+        assert_eq!(
+            apply(&mut timer, TimerAction::TogglePauseOrStart),
+            ActionOutcome::Applied
+        );
+        assert_eq!(timer.current_phase(), TimerPhase::Paused);
+        // No splitting a paused attempt, skipping is fine
+        assert_eq!(
+            apply(&mut timer, TimerAction::Split),
+            ActionOutcome::Ignored
+        );
+        assert_eq!(apply(&mut timer, TimerAction::Skip), ActionOutcome::Applied);
+        assert_eq!(
+            apply(
+                &mut timer,
+                TimerAction::Reset {
+                    update_splits: false
+                }
+            ),
+            ActionOutcome::Applied
+        );
+        assert_eq!(timer.current_phase(), TimerPhase::NotRunning);
+    }
+
+    #[test]
+    fn undoing_all_pauses_adds_every_pause_back() {
+        let pause = |timer: &mut Timer| {
+            assert_eq!(apply(timer, TimerAction::Pause), ActionOutcome::Applied);
+            thread::sleep(std::time::Duration::from_millis(20));
+            assert_eq!(apply(timer, TimerAction::Resume), ActionOutcome::Applied);
+        };
+
+        let mut timer = timer();
+        assert!(!can_undo_all_pauses(&timer));
+        apply(&mut timer, TimerAction::Start);
+        assert!(!can_undo_all_pauses(&timer));
+        pause(&mut timer);
+        pause(&mut timer);
+        let paused = timer.get_pause_time().unwrap();
+        assert!(paused.total_milliseconds() >= 40.0, "{paused:?}");
+        assert!(can_undo_all_pauses(&timer));
+
+        let real = |timer: &Timer| timer.snapshot().current_time().real_time.unwrap();
+        let before = real(&timer);
+        assert_eq!(
+            apply(&mut timer, TimerAction::UndoAllPauses),
+            ActionOutcome::Applied
+        );
+        assert_eq!(timer.current_phase(), TimerPhase::Running);
+        assert_eq!(timer.get_pause_time(), None);
+        assert!(!can_undo_all_pauses(&timer));
+        // Both pauses count towards the attempt again
+        assert!((real(&timer) - before).total_milliseconds() >= 40.0);
+
+        // Undone while paused, the attempt carries on
+        pause(&mut timer);
+        apply(&mut timer, TimerAction::Pause);
+        assert_eq!(
+            apply(&mut timer, TimerAction::UndoAllPauses),
+            ActionOutcome::Applied
+        );
+        assert_eq!(timer.current_phase(), TimerPhase::Running);
+        assert_eq!(timer.get_pause_time(), None);
+    }
+
+    #[test]
+    fn explicit_pause_and_comparison_actions() {
+        let mut timer = timer();
+        assert_eq!(
+            apply(&mut timer, TimerAction::Pause),
+            ActionOutcome::Ignored
+        );
+        apply(&mut timer, TimerAction::Start);
+        assert_eq!(
+            apply(&mut timer, TimerAction::Resume),
+            ActionOutcome::Ignored
+        );
+        assert_eq!(
+            apply(&mut timer, TimerAction::Pause),
+            ActionOutcome::Applied
+        );
+        assert_eq!(
+            apply(&mut timer, TimerAction::Pause),
+            ActionOutcome::Ignored
+        );
+        assert_eq!(
+            apply(&mut timer, TimerAction::Resume),
+            ActionOutcome::Applied
+        );
+        assert_eq!(timer.current_phase(), TimerPhase::Running);
+
+        assert_eq!(
+            apply(&mut timer, TimerAction::SetComparison("Nope".to_owned())),
+            ActionOutcome::Ignored
+        );
+        assert_eq!(
+            apply(
+                &mut timer,
+                TimerAction::SetComparison("Best Segments".to_owned())
+            ),
+            ActionOutcome::Applied
+        );
+        assert_eq!(timer.current_comparison(), "Best Segments");
+    }
+
+    #[test]
+    fn queued_actions_come_back_in_order() {
+        let shared = timer().into_shared();
+        let queue = ActionQueue::spawn(shared.clone());
+        let results: Vec<ActionResult> = [
+            TimerAction::Split,
+            TimerAction::SplitOrStart,
+            TimerAction::Split,
+        ]
+        .into_iter()
+        .map(|action| queue.enqueue(action))
+        .map(|result| result.recv().unwrap())
+        .collect();
+
+        let outcomes: Vec<(u64, ActionOutcome)> = results
+            .iter()
+            .map(|result| (result.seq, result.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                (0, ActionOutcome::Ignored),
+                (1, ActionOutcome::Applied),
+                (2, ActionOutcome::Applied)
+            ]
+        );
+        assert_eq!(shared.read().unwrap().current_split_index(), Some(1));
+    }
+
+    #[test]
+    fn resets_updating_the_splits_are_snapshotted_first() {
+        let shared = timer().into_shared();
+        let snapshots = SharedRunSnapshots::default();
+        let queue = ActionQueue::spawn_recording(shared.clone(), snapshots.clone());
+        let reset = |update_splits| queue.enqueue(TimerAction::Reset { update_splits });
+
+        // Nothing to reset yet, then a reset that keeps the splits as they are
+        reset(true).recv().unwrap();
+        queue.enqueue(TimerAction::Start).recv().unwrap();
+        reset(false).recv().unwrap();
+        assert!(snapshots.lock().unwrap().is_empty());
+
+        queue.enqueue(TimerAction::Start).recv().unwrap();
+        let attempts = shared.read().unwrap().run().attempt_history().len();
+        reset(true).recv().unwrap();
+
+        let snapshots = snapshots.lock().unwrap();
+        assert_eq!(snapshots.len(), 1);
+        let latest = snapshots.latest().unwrap();
+        assert_eq!(latest.description, "Reset with splits updated");
+        assert_eq!(latest.run.attempt_history().len(), attempts);
+        assert_eq!(
+            shared.read().unwrap().run().attempt_history().len(),
+            attempts + 1
+        );
+    }
+
+    #[test]
+    fn guarded_resets_of_the_auto_splitter_wait_for_the_main_loop() {
+        let shared = timer().into_shared();
+        let resets = ActionGate::default();
+        let queue = ActionQueue::spawn_gated(
+            shared.clone(),
+            SharedRunSnapshots::default(),
+            ActionGate::default(),
+            resets.clone(),
+        );
+        let phase = || shared.read().unwrap().current_phase();
+        let reset = TimerAction::Reset {
+            update_splits: true,
+        };
+
+        resets.set_armed(true);
+        queue.enqueue(TimerAction::Start).recv().unwrap();
+        queue.send_scripted(reset.clone());
+        queue.enqueue(TimerAction::Split).recv().unwrap();
+        assert_eq!(phase(), TimerPhase::Running);
+        assert!(resets.take_requests());
+        assert!(!resets.take_requests());
+
+        // Resets of the user go through
+        queue.enqueue(reset.clone()).recv().unwrap();
+        assert_eq!(phase(), TimerPhase::NotRunning);
+        // Nothing to hold without an attempt to reset
+        queue.send_scripted(reset.clone());
+        queue.enqueue(TimerAction::NextComparison).recv().unwrap();
+        assert!(!resets.take_requests());
+
+        resets.set_armed(false);
+        queue.enqueue(TimerAction::Start).recv().unwrap();
+        queue.send_scripted(reset);
+        queue.enqueue(TimerAction::NextComparison).recv().unwrap();
+        assert_eq!(phase(), TimerPhase::NotRunning);
+        assert!(!resets.take_requests());
+    }
+
+    #[test]
+    fn starts_wait_for_the_preflight_checklist_while_the_gate_is_armed() {
+        let shared = timer().into_shared();
+        let gate = ActionGate::default();
+        let queue = ActionQueue::spawn_gated(
+            shared.clone(),
+            SharedRunSnapshots::default(),
+            gate.clone(),
+            ActionGate::default(),
+        );
+        let outcome = |action| queue.enqueue(action).recv().unwrap().outcome;
+
+        gate.set_armed(true);
+        assert!(!gate.take_requests());
+        // Every action that would start an attempt, as hotkeys send them
+        for action in [
+            TimerAction::Start,
+            TimerAction::SplitOrStart,
+            TimerAction::TogglePauseOrStart,
+        ] {
+            assert_eq!(outcome(action), ActionOutcome::Held);
+        }
+        assert_eq!(
+            shared.read().unwrap().current_phase(),
+            TimerPhase::NotRunning
+        );
+        assert!(gate.take_requests());
+        assert!(!gate.take_requests());
+        // Other actions go through as before
+        assert_eq!(outcome(TimerAction::NextComparison), ActionOutcome::Applied);
+
+        // The start confirmed by the checklist
+        queue.send_checked(TimerAction::Start);
+        assert_eq!(outcome(TimerAction::SplitOrStart), ActionOutcome::Applied);
+        assert_eq!(shared.read().unwrap().current_split_index(), Some(1));
+        // Pausing a running attempt is not a start
+        assert_eq!(
+            outcome(TimerAction::TogglePauseOrStart),
+            ActionOutcome::Applied
+        );
+        assert!(!gate.take_requests());
+
+        outcome(TimerAction::Reset {
+            update_splits: false,
+        });
+        gate.set_armed(false);
+        assert_eq!(outcome(TimerAction::Start), ActionOutcome::Applied);
+        assert!(!gate.take_requests());
+    }
+
+    #[test]
+    fn only_starts_from_a_stopped_timer_start_attempts() {
+        use TimerPhase::{Ended, NotRunning, Paused, Running};
+        let cases = [
+            (TimerAction::Start, NotRunning, true),
+            (TimerAction::SplitOrStart, NotRunning, true),
+            (TimerAction::TogglePauseOrStart, NotRunning, true),
+            (TimerAction::Start, Running, false),
+            (TimerAction::SplitOrStart, Running, false),
+            (TimerAction::TogglePauseOrStart, Running, false),
+            (TimerAction::TogglePauseOrStart, Paused, false),
+            (TimerAction::Start, Ended, false),
+            (TimerAction::SplitOrStart, Ended, false),
+            (TimerAction::Split, NotRunning, false),
+            (TimerAction::Resume, NotRunning, false),
+            (TimerAction::Undo, NotRunning, false),
+            (
+                TimerAction::Reset {
+                    update_splits: true,
+                },
+                NotRunning,
+                false,
+            ),
+            (TimerAction::NextComparison, NotRunning, false),
+        ];
+        for (action, phase, starts) in cases {
+            assert_eq!(
+                starts_attempt(&action, phase),
+                starts,
+                "{action:?} {phase:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn concurrent_actions_match_a_replay_in_dispatch_order() {
+        for round in 0..20 {
+            let shared = timer().into_shared();
+            let queue = ActionQueue::spawn(shared.clone());
+
+            let senders: Vec<_> = (0..4u64)
+                .map(|thread| {
+                    let queue = queue.clone();
+                    thread::spawn(move || {
+                        actions(round * 31 + thread + 1, 200)
+                            .into_iter()
+                            .map(|action| queue.enqueue(action))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            let mut results: Vec<ActionResult> = senders
+                .into_iter()
+                .flat_map(|sender| sender.join().unwrap())
+                .map(|result| result.recv().unwrap())
+                .collect();
+            results.sort_by_key(|result| result.seq);
+            assert_eq!(results.len(), 800);
+
+            let mut replay = timer();
+            for result in &results {
+                assert_eq!(
+                    apply(&mut replay, result.action.clone()),
+                    result.outcome,
+                    "action {} ({:?}) of round {round}",
+                    result.seq,
+                    result.action
+                );
+            }
+            assert_eq!(state(&shared.read().unwrap()), state(&replay));
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Where the main window opens: the geometry stored with the run (in its
+//! sidecar) wins over the one of the config, field by field, and anything
+//! neither has is left to GTK.
+//!
+//! GTK 4 cannot move windows, so positions are read from the sidecar and kept
+//! there, but only used where the backend is able to place windows.
+
+use serde::{Deserialize, Serialize};
+
+/// Smallest size a stored geometry is shrunk to, so the window stays usable.
+pub const MIN_SIZE: i32 = 120;
+
+#[derive(Default, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(default)]
+pub struct WindowGeometry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximized: Option<bool>,
+    /// Position on the screen, only applied where windows can be placed (X11).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<i32>,
+}
+
+/// The geometry to give the window. `None` fields keep GTK's defaults.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub maximized: bool,
+    pub position: Option<(i32, i32)>,
+}
+
+/// Combines the geometry of the run with the one of the config. The size is
+/// clamped to `monitor` (width, height) when it is known. A position is only
+/// given when `can_position`, with both coordinates from the same geometry.
+pub fn resolve_placement(
+    run: Option<&WindowGeometry>,
+    global: &WindowGeometry,
+    monitor: Option<(i32, i32)>,
+    can_position: bool,
+) -> Placement {
+    let run = run.copied().unwrap_or_default();
+    let clamp = |size: i32, max: Option<i32>| {
+        let size = max.map_or(size, |max| size.min(max));
+        size.max(MIN_SIZE)
+    };
+    let width = run
+        .width
+        .or(global.width)
+        .map(|width| clamp(width, monitor.map(|(width, _)| width)));
+    let height = run
+        .height
+        .or(global.height)
+        .map(|height| clamp(height, monitor.map(|(_, height)| height)));
+    let maximized = run.maximized.or(global.maximized).unwrap_or(false);
+
+    let position = || match (run.x, run.y) {
+        (Some(x), Some(y)) => Some((x, y)),
+        (None, None) => Some((global.x?, global.y?)),
+        // Half a position from each side could put the window anywhere
+        _ => None,
+    };
+    Placement {
+        width,
+        height,
+        maximized,
+        position: if can_position { position() } else { None },
+    }
+}
+
+/// The geometry to store for a window of `width` by `height`, keeping the
+/// position `previous` had since it cannot be read back.
+pub fn captured_geometry(
+    previous: Option<&WindowGeometry>,
+    width: i32,
+    height: i32,
+    maximized: bool,
+) -> WindowGeometry {
+    let previous = previous.copied().unwrap_or_default();
+    WindowGeometry {
+        width: Some(width),
+        height: Some(height),
+        maximized: Some(maximized),
+        x: previous.x,
+        y: previous.y,
+    }
+}
+
+#[cfg(test)]
+mod window_placement_tests {
+    use super::*;
+
+    fn geometry(
+        width: Option<i32>,
+        height: Option<i32>,
+        maximized: Option<bool>,
+    ) -> WindowGeometry {
+        WindowGeometry {
+            width,
+            height,
+            maximized,
+            ..WindowGeometry::default()
+        }
+    }
+
+    #[test]
+    fn the_run_wins_over_the_config_field_by_field() {
+        let run = geometry(Some(400), None, Some(false));
+        let global = geometry(Some(800), Some(600), Some(true));
+        let placement = resolve_placement(Some(&run), &global, None, false);
+        assert_eq!(placement.width, Some(400));
+        assert_eq!(placement.height, Some(600));
+        assert!(!placement.maximized);
+
+        // Without a run sidecar, the config applies
+        let placement = resolve_placement(None, &global, None, false);
+        assert_eq!((placement.width, placement.height), (Some(800), Some(600)));
+        assert!(placement.maximized);
+
+        // And without either, GTK decides
+        let placement = resolve_placement(None, &WindowGeometry::default(), None, false);
+        assert_eq!(placement, Placement::default());
+    }
+
+    #[test]
+    fn sizes_fit_the_monitor() {
+        let run = geometry(Some(3840), Some(40), None);
+        let placement = resolve_placement(
+            Some(&run),
+            &WindowGeometry::default(),
+            Some((1920, 1080)),
+            false,
+        );
+        assert_eq!(placement.width, Some(1920));
+        assert_eq!(placement.height, Some(MIN_SIZE));
+
+        // Unknown monitor, only the minimum applies
+        let placement = resolve_placement(Some(&run), &WindowGeometry::default(), None, false);
+        assert_eq!(placement.width, Some(3840));
+    }
+
+    #[test]
+    fn positions_need_a_backend_able_to_place_windows() {
+        let run = WindowGeometry {
+            x: Some(-1920),
+            y: Some(0),
+            ..geometry(Some(400), Some(700), None)
+        };
+        let global = WindowGeometry::default();
+        // Wayland: the size still applies, the position is ignored
+        let placement = resolve_placement(Some(&run), &global, None, false);
+        assert_eq!(placement.position, None);
+        assert_eq!(placement.width, Some(400));
+
+        let placement = resolve_placement(Some(&run), &global, None, true);
+        assert_eq!(placement.position, Some((-1920, 0)));
+
+        // Coordinates are not mixed between the run and the config
+        let half = WindowGeometry {
+            x: Some(10),
+            ..WindowGeometry::default()
+        };
+        let global = WindowGeometry {
+            x: Some(100),
+            y: Some(200),
+            ..WindowGeometry::default()
+        };
+        assert_eq!(
+            resolve_placement(Some(&half), &global, None, true).position,
+            None
+        );
+        assert_eq!(
+            resolve_placement(None, &global, None, true).position,
+            Some((100, 200))
+        );
+    }
+
+    #[test]
+    fn capturing_keeps_the_stored_position() {
+        let previous = WindowGeometry {
+            x: Some(1920),
+            y: Some(40),
+            ..geometry(Some(300), Some(300), None)
+        };
+        let captured = captured_geometry(Some(&previous), 420, 760, true);
+        assert_eq!(captured.width, Some(420));
+        assert_eq!(captured.height, Some(760));
+        assert_eq!(captured.maximized, Some(true));
+        assert_eq!((captured.x, captured.y), (Some(1920), Some(40)));
+
+        let captured = captured_geometry(None, 420, 760, false);
+        assert_eq!((captured.x, captured.y), (None, None));
+    }
+}
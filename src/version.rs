@@ -0,0 +1,37 @@
+//! What this build of TuxSplit is: the crate version and the git commit the
+//! build script found, if any.
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Shown for builds made without git.
+const UNKNOWN_COMMIT: &str = "unknown";
+
+/// `commit` as recorded by the build script, or "unknown" when it is missing
+/// or empty.
+pub fn commit_or_unknown(commit: Option<&str>) -> &str {
+    commit
+        .map(str::trim)
+        .filter(|commit| !commit.is_empty())
+        .unwrap_or(UNKNOWN_COMMIT)
+}
+
+pub fn commit() -> &'static str {
+    commit_or_unknown(option_env!("TUXSPLIT_GIT_COMMIT"))
+}
+
+/// Version and commit, e.g. "0.1.0 (1a2b3c4d5e6f)".
+pub fn describe() -> String {
+    format!("{VERSION} ({})", commit())
+}
+
+#[cfg(test)]
+mod version_tests {
+    use super::*;
+
+    #[test]
+    fn builds_without_git_show_an_unknown_commit() {
+        assert_eq!(commit_or_unknown(None), "unknown");
+        assert_eq!(commit_or_unknown(Some("")), "unknown");
+        assert_eq!(commit_or_unknown(Some(" \n")), "unknown");
+        assert_eq!(commit_or_unknown(Some("1a2b3c4d5e6f\n")), "1a2b3c4d5e6f");
+    }
+}